@@ -1,9 +1,6 @@
 use std::str::FromStr;
 
-use web3::{
-    ethabi::ethereum_types::U256,
-    types::{Address, TransactionRequest},
-};
+use web3::types::{units::parse_units, Address, TransactionRequest};
 
 /// Below sends a transaction to a local node that stores private keys (eg Ganache)
 /// For generating and signing a transaction offline, before transmitting it to a public node (eg Infura) see transaction_public
@@ -22,7 +19,7 @@ async fn main() -> web3::Result {
     let tx_object = TransactionRequest {
         from,
         to: Some(to),
-        value: Some(U256::exp10(17)), //0.1 eth
+        value: Some(parse_units("0.1", "ether").unwrap()),
         ..Default::default()
     };
 
@@ -1,9 +1,8 @@
 use std::str::FromStr;
 
 use web3::{
-    ethabi::ethereum_types::U256,
     signing::SecretKey,
-    types::{Address, TransactionParameters},
+    types::{units::parse_units, Address, TransactionParameters},
 };
 
 /// Below generates and signs a transaction offline, before transmitting it to a public node (eg Infura)
@@ -24,7 +23,7 @@ async fn main() -> web3::Result {
     // Build the tx object
     let tx_object = TransactionParameters {
         to: Some(to),
-        value: U256::exp10(17), //0.1 eth
+        value: parse_units("0.1", "ether").unwrap(),
         ..Default::default()
     };
 
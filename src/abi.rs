@@ -0,0 +1,42 @@
+//! Function selectors and event topics computed from signatures, without an ABI.
+
+use crate::{signing::keccak256, types::H256};
+
+/// Computes the 4-byte selector of a function, e.g. `selector("transfer(address,uint256)")`.
+///
+/// Whitespace in the signature is ignored.
+pub fn selector(signature: &str) -> [u8; 4] {
+    let hash = keccak256(normalize(signature).as_bytes());
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// Computes the topic of an event, e.g. `event_topic("Transfer(address,address,uint256)")`.
+///
+/// Whitespace in the signature is ignored.
+pub fn event_topic(signature: &str) -> H256 {
+    H256(keccak256(normalize(signature).as_bytes()))
+}
+
+fn normalize(signature: &str) -> String {
+    signature.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    #[test]
+    fn should_compute_selector() {
+        assert_eq!(selector("transfer(address,uint256)"), hex!("a9059cbb"));
+        assert_eq!(selector("transfer(address, uint256)"), hex!("a9059cbb"));
+    }
+
+    #[test]
+    fn should_compute_event_topic() {
+        assert_eq!(
+            event_topic("Transfer(address,address,uint256)"),
+            H256(hex!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"))
+        );
+    }
+}
@@ -43,8 +43,8 @@ mod accounts_signing {
         error,
         signing::Signature,
         types::{
-            AccessList, Address, Bytes, Recovery, RecoveryMessage, SignedData, SignedTransaction,
-            TransactionParameters, U256, U64,
+            AccessList, Address, BlockNumber, Bytes, ExtraField, ExtraFields, Recovery, RecoveryMessage, SignedData,
+            SignedTransaction, TransactionParameters, U256, U64,
         },
     };
     use rlp::RlpStream;
@@ -63,9 +63,17 @@ mod accounts_signing {
         /// Signs an Ethereum transaction with a given private key.
         ///
         /// Transaction signing can perform RPC requests in order to fill missing
-        /// parameters required for signing `nonce`, `gas_price` and `chain_id`. Note
-        /// that if all transaction parameters were provided, this future will resolve
+        /// parameters required for signing `nonce`, `gas_price` and `chain_id`, as well as
+        /// `max_priority_fee_per_gas` of EIP-1559 transactions (with `eth_maxPriorityFeePerGas`, or
+        /// the median tip of the latest block from `eth_feeHistory` on nodes without that method).
+        /// Note that if all transaction parameters were provided, this future will resolve
         /// immediately.
+        ///
+        /// The transaction is signed as `TransactionParameters::effective_transaction_type`, so
+        /// transactions with an access list are never signed as legacy transactions unless
+        /// requested explicitly. Fails with `Error::InvalidTransaction` if
+        /// `TransactionParameters::extra_fields` are set for a typed transaction, as only legacy
+        /// transactions can carry them.
         pub async fn sign_transaction<K: signing::Key>(
             &self,
             tx: TransactionParameters,
@@ -83,25 +91,38 @@ mod accounts_signing {
             }
             let from = key.address();
 
-            let gas_price = match tx.transaction_type {
-                Some(tx_type) if tx_type == U64::from(EIP1559_TX_ID) && tx.max_fee_per_gas.is_some() => {
-                    tx.max_fee_per_gas
-                }
+            let transaction_type = tx.effective_transaction_type();
+            let is_legacy = matches!(transaction_type.map(|t| t.as_u64()), Some(LEGACY_TX_ID) | None);
+            if !is_legacy && !tx.extra_fields.is_empty() {
+                return Err(error::Error::InvalidTransaction(format!(
+                    "extra fields can't be encoded in transactions of type {}",
+                    transaction_type.unwrap_or_default()
+                )));
+            }
+            let is_eip1559 = transaction_type == Some(U64::from(EIP1559_TX_ID));
+            let gas_price = match tx.max_fee_per_gas {
+                Some(max_fee_per_gas) if is_eip1559 => Some(max_fee_per_gas),
                 _ => tx.gas_price,
             };
 
-            let (nonce, gas_price, chain_id) = futures::future::try_join3(
+            let (nonce, gas_price, chain_id, suggested_priority_fee) = futures::future::try_join4(
                 maybe!(tx.nonce, self.web3().eth().transaction_count(from, None)),
                 maybe!(gas_price, self.web3().eth().gas_price()),
                 maybe!(tx.chain_id.map(U256::from), self.web3().eth().chain_id()),
+                async {
+                    match tx.max_priority_fee_per_gas {
+                        None if is_eip1559 => self.suggested_priority_fee().await,
+                        _ => Ok(None),
+                    }
+                },
             )
             .await?;
             let chain_id = chain_id.as_u64();
 
-            let max_priority_fee_per_gas = match tx.transaction_type {
-                Some(tx_type) if tx_type == U64::from(EIP1559_TX_ID) => {
-                    tx.max_priority_fee_per_gas.unwrap_or(gas_price)
-                }
+            let max_priority_fee_per_gas = match (tx.max_priority_fee_per_gas, suggested_priority_fee) {
+                (Some(fee), _) if is_eip1559 => fee,
+                // the suggested tip must not exceed the max fee
+                (_, Some(fee)) => fee.min(gas_price),
                 _ => gas_price,
             };
 
@@ -112,15 +133,31 @@ mod accounts_signing {
                 gas_price,
                 value: tx.value,
                 data: tx.data.0,
-                transaction_type: tx.transaction_type,
+                transaction_type,
                 access_list: tx.access_list.unwrap_or_default(),
                 max_priority_fee_per_gas,
+                extra_fields: tx.extra_fields,
             };
 
             let signed = tx.sign(key, chain_id);
             Ok(signed)
         }
 
+        /// Suggests the priority fee of EIP-1559 transactions, `None` if the node can't suggest one.
+        async fn suggested_priority_fee(&self) -> error::Result<Option<U256>> {
+            let eth = self.web3().eth();
+            match eth.max_priority_fee_per_gas().await {
+                Ok(fee) => return Ok(Some(fee)),
+                Err(error::Error::Rpc(_)) => {}
+                Err(err) => return Err(err),
+            }
+            match eth.fee_history(1.into(), BlockNumber::Latest, Some(vec![50.0])).await {
+                Ok(history) => Ok(history.reward.and_then(|reward| reward.first()?.first().copied())),
+                Err(error::Error::Rpc(_)) => Ok(None),
+                Err(err) => Err(err),
+            }
+        }
+
         /// Sign arbitrary string data.
         ///
         /// The data is UTF-8 encoded and enveloped the same way as with
@@ -196,13 +233,22 @@ mod accounts_signing {
         pub transaction_type: Option<U64>,
         pub access_list: AccessList,
         pub max_priority_fee_per_gas: U256,
+        pub extra_fields: ExtraFields,
     }
 
     impl Transaction {
-        fn rlp_append_legacy(&self, stream: &mut RlpStream) {
+        fn rlp_append_legacy(&self, stream: &mut RlpStream, extra_fields: &[ExtraField]) {
             stream.append(&self.nonce);
             stream.append(&self.gas_price);
             stream.append(&self.gas);
+            for field in extra_fields {
+                match field {
+                    ExtraField::Address(Some(address)) => stream.append(address),
+                    ExtraField::Address(None) => stream.append(&""),
+                    ExtraField::Uint(value) => stream.append(value),
+                    ExtraField::Bytes(bytes) => stream.append(&bytes.0),
+                };
+            }
             if let Some(to) = self.to {
                 stream.append(&to);
             } else {
@@ -214,9 +260,9 @@ mod accounts_signing {
 
         fn encode_legacy(&self, chain_id: u64, signature: Option<&Signature>) -> RlpStream {
             let mut stream = RlpStream::new();
-            stream.begin_list(9);
+            stream.begin_list(9 + self.extra_fields.len());
 
-            self.rlp_append_legacy(&mut stream);
+            self.rlp_append_legacy(&mut stream, &self.extra_fields.0);
 
             if let Some(signature) = signature {
                 self.rlp_append_signature(&mut stream, signature);
@@ -238,7 +284,7 @@ mod accounts_signing {
             // append chain_id. from EIP-2930: chainId is defined to be an integer of arbitrary size.
             stream.append(&chain_id);
 
-            self.rlp_append_legacy(&mut stream);
+            self.rlp_append_legacy(&mut stream, &[]);
             self.rlp_append_access_list(&mut stream);
 
             if let Some(signature) = signature {
@@ -296,7 +342,7 @@ mod accounts_signing {
             }
         }
 
-        fn encode(&self, chain_id: u64, signature: Option<&Signature>) -> Vec<u8> {
+        pub(crate) fn encode(&self, chain_id: u64, signature: Option<&Signature>) -> Vec<u8> {
             match self.transaction_type.map(|t| t.as_u64()) {
                 Some(LEGACY_TX_ID) | None => {
                     let stream = self.encode_legacy(chain_id, signature);
@@ -355,9 +401,9 @@ mod accounts_signing {
 mod tests {
     use super::*;
     use crate::{
-        signing::{SecretKey, SecretKeyRef},
+        signing::{SecretKey, SecretKeyRef, Signature},
         transports::test::TestTransport,
-        types::{Address, Recovery, SignedTransaction, TransactionParameters, U256},
+        types::{Address, ExtraFields, Recovery, SignedTransaction, TransactionParameters, U256},
     };
     use accounts_signing::*;
     use hex_literal::hex;
@@ -436,6 +482,166 @@ mod tests {
         accounts.transport().assert_no_more_requests();
     }
 
+    #[test]
+    fn accounts_reject_extra_fields_of_typed_transactions() {
+        let key = SecretKey::from_slice(&hex!(
+            "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f"
+        ))
+        .unwrap();
+
+        let accounts = Accounts::new(TestTransport::default());
+        let result = futures::executor::block_on(accounts.sign_transaction(
+            TransactionParameters {
+                nonce: Some(0.into()),
+                chain_id: Some(42220),
+                transaction_type: Some(2.into()),
+                max_fee_per_gas: Some(1.into()),
+                max_priority_fee_per_gas: Some(1.into()),
+                extra_fields: ExtraFields::celo(None, None, 0.into()),
+                ..Default::default()
+            },
+            &key,
+        ));
+
+        assert_eq!(
+            result,
+            Err(crate::Error::InvalidTransaction(
+                "extra fields can't be encoded in transactions of type 2".into()
+            ))
+        );
+        accounts.transport().assert_no_more_requests();
+    }
+
+    #[test]
+    fn accounts_sign_access_list_transaction() {
+        // test vector taken from go-ethereum (core/types/transaction_test.go, `emptyEip2718Tx` and
+        // `signedEip2718Tx` of `TestEIP2718TransactionSigHash` and `TestEIP2718TransactionEncode`)
+        let sighash = hex!("49b486f0ec0a60dfbbca2d30cb07c9e8ffb2a2ff41f29a1ab6737475f6ff69f3");
+        let key = SecretKey::from_slice(&hex!(
+            "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+        ))
+        .unwrap();
+        let tx = TransactionParameters {
+            nonce: Some(3.into()),
+            to: Some(hex!("b94f5374fce5edbc8e2a8697c15331677e6ebf0b").into()),
+            gas: 25_000.into(),
+            gas_price: Some(1.into()),
+            value: 10.into(),
+            data: hex!("5544").to_vec().into(),
+            chain_id: Some(1),
+            transaction_type: Some(1.into()),
+            access_list: Some(vec![]),
+            ..Default::default()
+        };
+
+        let accounts = Accounts::new(TestTransport::default());
+        let signed = futures::executor::block_on(accounts.sign_transaction(tx, &key)).unwrap();
+
+        assert_eq!(signed.message_hash, sighash.into());
+        assert_eq!(signed.raw_transaction.0[0], 0x01);
+        assert_eq!(
+            signed.recover_from(),
+            Ok(hex!("2c7536e3605d9c16a7a3d7b1898e529396a65c23").into())
+        );
+        accounts.transport().assert_no_more_requests();
+
+        // the signature of the vector, which key is not known
+        let tx = Transaction {
+            nonce: 3.into(),
+            gas: 25_000.into(),
+            gas_price: 1.into(),
+            to: Some(hex!("b94f5374fce5edbc8e2a8697c15331677e6ebf0b").into()),
+            value: 10.into(),
+            data: hex!("5544").to_vec(),
+            transaction_type: Some(1.into()),
+            access_list: vec![],
+            max_priority_fee_per_gas: 0.into(),
+            extra_fields: Default::default(),
+        };
+        let signature = Signature {
+            v: 1,
+            r: hex!("c9519f4f2b30335884581971573fadf60c6204f59a911df35ee8a540456b2660").into(),
+            s: hex!("32f1e8e2c5dd761f9e4f88f41c8310aeaba26a8bfcdacfedfa12ec3862d37521").into(),
+        };
+        assert_eq!(signing::keccak256(&tx.encode(1, None)), sighash);
+        assert_eq!(
+            tx.encode(1, Some(&signature)),
+            hex!("01f8630103018261a894b94f5374fce5edbc8e2a8697c15331677e6ebf0b0a825544c001a0c9519f4f2b30335884581971573fadf60c6204f59a911df35ee8a540456b2660a032f1e8e2c5dd761f9e4f88f41c8310aeaba26a8bfcdacfedfa12ec3862d37521")
+        );
+    }
+
+    #[test]
+    fn accounts_sign_eip1559_transaction_with_suggested_priority_fee() {
+        let key = SecretKey::from_slice(&hex!(
+            "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f"
+        ))
+        .unwrap();
+
+        let mut transport = TestTransport::default();
+        transport.add_response(json!("0x3b9aca00"));
+        let signed = {
+            let accounts = Accounts::new(&transport);
+            futures::executor::block_on(accounts.sign_transaction(
+                TransactionParameters {
+                    nonce: Some(0.into()),
+                    chain_id: Some(1),
+                    transaction_type: Some(2.into()),
+                    max_fee_per_gas: Some(30_000_000_000u64.into()),
+                    ..Default::default()
+                },
+                &key,
+            ))
+            .unwrap()
+        };
+
+        transport.assert_request("eth_maxPriorityFeePerGas", &[]);
+        transport.assert_no_more_requests();
+        let rlp = rlp::Rlp::new(&signed.raw_transaction.0[1..]);
+        assert_eq!(rlp.val_at::<U256>(2).unwrap(), 1_000_000_000u64.into());
+        assert_eq!(rlp.val_at::<U256>(3).unwrap(), 30_000_000_000u64.into());
+    }
+
+    #[test]
+    fn accounts_sign_eip1559_transaction_with_fee_history_priority_fee() {
+        let key = SecretKey::from_slice(&hex!(
+            "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f"
+        ))
+        .unwrap();
+
+        let mut transport = TestTransport::default();
+        transport.add_error(crate::Error::Rpc(crate::rpc::Error::method_not_found()));
+        transport.add_response(json!({
+            "oldestBlock": "0x10",
+            "baseFeePerGas": ["0x6fc23ac00", "0x6fc23ac00"],
+            "gasUsedRatio": [0.5],
+            "reward": [["0x77359400"]]
+        }));
+        let signed = {
+            let accounts = Accounts::new(&transport);
+            futures::executor::block_on(accounts.sign_transaction(
+                TransactionParameters {
+                    nonce: Some(0.into()),
+                    chain_id: Some(1),
+                    transaction_type: Some(2.into()),
+                    max_fee_per_gas: Some(30_000_000_000u64.into()),
+                    ..Default::default()
+                },
+                &key,
+            ))
+            .unwrap()
+        };
+
+        transport.assert_request("eth_maxPriorityFeePerGas", &[]);
+        transport.assert_request(
+            "eth_feeHistory",
+            &["\"0x1\"".into(), "\"latest\"".into(), "[50.0]".into()],
+        );
+        transport.assert_no_more_requests();
+        let rlp = rlp::Rlp::new(&signed.raw_transaction.0[1..]);
+        assert_eq!(rlp.val_at::<U256>(2).unwrap(), 2_000_000_000u64.into());
+        assert_eq!(rlp.val_at::<U256>(3).unwrap(), 30_000_000_000u64.into());
+    }
+
     #[test]
     fn accounts_hash_message() {
         // test vector taken from:
@@ -546,6 +752,7 @@ mod tests {
             transaction_type: None,
             access_list: vec![],
             max_priority_fee_per_gas: 0.into(),
+            extra_fields: Default::default(),
         };
         let skey = SecretKey::from_slice(&hex!(
             "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
@@ -566,4 +773,110 @@ mod tests {
 
         assert_eq!(signed, expected);
     }
+
+    #[test]
+    fn sign_transaction_data_with_extra_fields() {
+        let fee_currency: Address = hex!("765DE816845861e75A25fCA122bb6898B8B1282a").into();
+        let tx = Transaction {
+            nonce: 0.into(),
+            gas: 2_000_000.into(),
+            gas_price: 234_567_897_654_321u64.into(),
+            to: Some(hex!("F0109fC8DF283027b6285cc889F5aA624EaC1F55").into()),
+            value: 1_000_000_000.into(),
+            data: Vec::new(),
+            transaction_type: None,
+            access_list: vec![],
+            max_priority_fee_per_gas: 0.into(),
+            extra_fields: ExtraFields::celo(Some(fee_currency), None, 0.into()),
+        };
+        let skey = SecretKey::from_slice(&hex!(
+            "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+        ))
+        .unwrap();
+        let key = SecretKeyRef::new(&skey);
+
+        let signed = tx.sign(key, 42220);
+
+        let rlp = rlp::Rlp::new(&signed.raw_transaction.0);
+        assert_eq!(rlp.item_count().unwrap(), 12);
+        assert_eq!(rlp.val_at::<Address>(3).unwrap(), fee_currency);
+        assert_eq!(rlp.at(4).unwrap().data().unwrap(), b"");
+        assert_eq!(rlp.val_at::<U256>(5).unwrap(), U256::zero());
+        assert_eq!(
+            rlp.val_at::<Address>(6).unwrap(),
+            hex!("F0109fC8DF283027b6285cc889F5aA624EaC1F55").into()
+        );
+        assert!(signed.v == 42220 * 2 + 35 || signed.v == 42220 * 2 + 36);
+
+        // the extra fields are covered by the signature
+        let recovery = Recovery::from(&signed);
+        let accounts = Accounts::new(TestTransport::default());
+        assert_eq!(accounts.recover(recovery).unwrap(), signing::secret_key_address(&skey));
+    }
+
+    #[test]
+    fn recover_sender_of_raw_transactions() {
+        let skey = SecretKey::from_slice(&hex!(
+            "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+        ))
+        .unwrap();
+        let sender = signing::secret_key_address(&skey);
+        let accounts = Accounts::new(TestTransport::default());
+        // exceeds u32, `v` doesn't fit 32 bits either
+        let chain_id = 0x1_0000_0001u64;
+
+        for transaction_type in [None, Some(1u64), Some(2)] {
+            let tx = Transaction {
+                nonce: 1.into(),
+                gas: 21_000.into(),
+                gas_price: 1_000_000_000.into(),
+                to: Some(Address::repeat_byte(1)),
+                value: 1.into(),
+                data: vec![1, 2, 3],
+                transaction_type: transaction_type.map(Into::into),
+                access_list: vec![crate::types::AccessListItem::new(Address::repeat_byte(2), vec![])],
+                max_priority_fee_per_gas: 1.into(),
+                extra_fields: Default::default(),
+            };
+
+            let signed = tx.sign(SecretKeyRef::new(&skey), chain_id);
+
+            assert_eq!(signed.recover_from(), Ok(sender));
+            assert_eq!(accounts.recover(Recovery::from(&signed)).unwrap(), sender);
+        }
+        assert_eq!(
+            signing::recover_transaction(&[0x02, 0xc0]),
+            Err(signing::RecoveryError::InvalidTransaction)
+        );
+    }
+
+    #[test]
+    fn recover_sender_of_unprotected_transaction() {
+        let skey = SecretKey::from_slice(&hex!(
+            "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+        ))
+        .unwrap();
+        let mut stream = rlp::RlpStream::new_list(6);
+        stream.append(&0u8);
+        stream.append(&U256::from(1_000_000_000));
+        stream.append(&U256::from(21_000));
+        stream.append(&Address::repeat_byte(1));
+        stream.append(&U256::from(1));
+        stream.append(&"");
+        let signature =
+            signing::Key::sign(&SecretKeyRef::new(&skey), &signing::keccak256(stream.as_raw()), None).unwrap();
+
+        let mut raw = rlp::RlpStream::new_list(9);
+        for item in rlp::Rlp::new(stream.as_raw()).iter() {
+            raw.append_raw(item.as_raw(), 1);
+        }
+        raw.append(&signature.v);
+        raw.append(&U256::from_big_endian(signature.r.as_bytes()));
+        raw.append(&U256::from_big_endian(signature.s.as_bytes()));
+
+        assert_eq!(
+            signing::recover_transaction(raw.as_raw()),
+            Ok(signing::secret_key_address(&skey))
+        );
+    }
 }
@@ -0,0 +1,84 @@
+//! `Bor` namespace
+
+use crate::{
+    api::Namespace,
+    helpers::{self, CallFuture},
+    types::{Address, BlockId, BorValidator},
+    Transport,
+};
+
+/// `Bor` namespace (Polygon PoS)
+#[derive(Debug, Clone)]
+pub struct Bor<T> {
+    transport: T,
+}
+
+impl<T: Transport> Namespace<T> for Bor<T> {
+    fn new(transport: T) -> Self
+    where
+        Self: Sized,
+    {
+        Bor { transport }
+    }
+
+    fn transport(&self) -> &T {
+        &self.transport
+    }
+}
+
+impl<T: Transport> Bor<T> {
+    /// Returns the validator set of the current span.
+    pub fn current_validators(&self) -> CallFuture<Vec<BorValidator>, T::Out> {
+        helpers::call(&self.transport, "bor_getCurrentValidators", vec![])
+    }
+
+    /// Returns the root hash of the given block range, as used by checkpoints submitted to Ethereum.
+    ///
+    /// Note the hash is returned by Bor as a hex string without the `0x` prefix.
+    pub fn root_hash(&self, from: u64, to: u64) -> CallFuture<String, T::Out> {
+        let from = helpers::serialize(&from);
+        let to = helpers::serialize(&to);
+        helpers::call(&self.transport, "bor_getRootHash", vec![from, to])
+    }
+
+    /// Returns the producer of the given block.
+    pub fn author(&self, block: BlockId) -> CallFuture<Address, T::Out> {
+        let block = helpers::serialize(&block);
+        helpers::call(&self.transport, "bor_getAuthor", vec![block])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Bor;
+    use crate::{
+        api::Namespace,
+        rpc::Value,
+        types::{Address, BlockNumber, BorValidator},
+    };
+
+    rpc_test! (
+      Bor:current_validators => "bor_getCurrentValidators";
+      serde_json::json!([{
+        "ID": 1,
+        "signer": "0x0000000000000000000000000000000000000123",
+        "power": 10,
+        "accum": 0
+      }]) => vec![BorValidator {
+        id: 1,
+        signer: Address::from_low_u64_be(0x123),
+        voting_power: 10,
+        proposer_priority: 0,
+      }]
+    );
+
+    rpc_test! (
+      Bor:root_hash, 1u64, 32u64 => "bor_getRootHash", vec!["1", "32"];
+      Value::String("d1eb".into()) => "d1eb"
+    );
+
+    rpc_test! (
+      Bor:author, BlockNumber::Latest => "bor_getAuthor", vec![r#""latest""#];
+      Value::String("0x0000000000000000000000000000000000000123".into()) => Address::from_low_u64_be(0x123)
+    );
+}
@@ -0,0 +1,104 @@
+//! `Clique` namespace
+
+use crate::{
+    api::Namespace,
+    helpers::{self, CallFuture},
+    types::{Address, BlockNumber, CliqueSnapshot, H256},
+    Transport,
+};
+
+/// `Clique` namespace (Geth proof-of-authority)
+#[derive(Debug, Clone)]
+pub struct Clique<T> {
+    transport: T,
+}
+
+impl<T: Transport> Namespace<T> for Clique<T> {
+    fn new(transport: T) -> Self
+    where
+        Self: Sized,
+    {
+        Clique { transport }
+    }
+
+    fn transport(&self) -> &T {
+        &self.transport
+    }
+}
+
+impl<T: Transport> Clique<T> {
+    /// Returns the voting state at given block (the latest one by default).
+    pub fn snapshot(&self, block: Option<BlockNumber>) -> CallFuture<CliqueSnapshot, T::Out> {
+        let block = helpers::serialize(&block.unwrap_or(BlockNumber::Latest));
+        helpers::call(&self.transport, "clique_getSnapshot", vec![block])
+    }
+
+    /// Returns the voting state at the block with given hash.
+    pub fn snapshot_at_hash(&self, hash: H256) -> CallFuture<CliqueSnapshot, T::Out> {
+        let hash = helpers::serialize(&hash);
+        helpers::call(&self.transport, "clique_getSnapshotAtHash", vec![hash])
+    }
+
+    /// Returns the authorized signers at given block (the latest one by default).
+    pub fn signers(&self, block: Option<BlockNumber>) -> CallFuture<Vec<Address>, T::Out> {
+        let block = helpers::serialize(&block.unwrap_or(BlockNumber::Latest));
+        helpers::call(&self.transport, "clique_getSigners", vec![block])
+    }
+
+    /// Makes the node vote to authorize (or deauthorize) the address in the blocks it seals.
+    pub fn propose(&self, address: Address, authorize: bool) -> CallFuture<(), T::Out> {
+        let address = helpers::serialize(&address);
+        let authorize = helpers::serialize(&authorize);
+        helpers::call(&self.transport, "clique_propose", vec![address, authorize])
+    }
+
+    /// Drops a pending proposal of the node.
+    pub fn discard(&self, address: Address) -> CallFuture<(), T::Out> {
+        let address = helpers::serialize(&address);
+        helpers::call(&self.transport, "clique_discard", vec![address])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Clique;
+    use crate::{
+        api::Namespace,
+        rpc::Value,
+        types::{Address, BlockNumber, CliqueSnapshot, H256},
+    };
+
+    rpc_test! (
+      Clique:snapshot, Some(BlockNumber::Number(0x10.into())) => "clique_getSnapshot", vec![r#""0x10""#];
+      serde_json::json!({
+        "number": 16,
+        "hash": "0x0000000000000000000000000000000000000000000000000000000000000123",
+        "signers": {"0x0000000000000000000000000000000000000001": {}},
+        "recents": {},
+        "votes": [],
+        "tally": {}
+      }) => CliqueSnapshot {
+        number: 16,
+        hash: H256::from_low_u64_be(0x123),
+        signers: vec![Address::from_low_u64_be(1)].into_iter().collect(),
+        ..Default::default()
+      }
+    );
+
+    rpc_test! (
+      Clique:signers, None => "clique_getSigners", vec![r#""latest""#];
+      serde_json::json!(["0x0000000000000000000000000000000000000001"]) => vec![Address::from_low_u64_be(1)]
+    );
+
+    rpc_test! (
+      Clique:propose, Address::from_low_u64_be(0x123), true
+      => "clique_propose", vec![r#""0x0000000000000000000000000000000000000123""#, "true"];
+      Value::Null => ()
+    );
+
+    rpc_test! (
+      Clique:discard, Address::from_low_u64_be(0x123)
+      => "clique_discard", vec![r#""0x0000000000000000000000000000000000000123""#];
+      Value::Null => ()
+    );
+}
@@ -0,0 +1,95 @@
+//! `Debug` namespace (Geth tracing API)
+
+use crate::{
+    api::Namespace,
+    helpers::{self, CallFuture},
+    types::{BlockNumber, CallRequest, GethTrace, TracingOptions, H256},
+    Transport,
+};
+
+/// `Debug` namespace
+#[derive(Debug, Clone)]
+pub struct Debug<T> {
+    transport: T,
+}
+
+impl<T: Transport> Namespace<T> for Debug<T> {
+    fn new(transport: T) -> Self
+    where
+        Self: Sized,
+    {
+        Debug { transport }
+    }
+
+    fn transport(&self) -> &T {
+        &self.transport
+    }
+}
+
+impl<T: Transport> Debug<T> {
+    /// Replays the transaction and returns its trace.
+    ///
+    /// Use `GasProfile` and `CallFrame::tree` to inspect the gas usage of the result.
+    pub fn trace_transaction(&self, hash: H256, options: TracingOptions) -> CallFuture<GethTrace, T::Out> {
+        let hash = helpers::serialize(&hash);
+        let options = helpers::serialize(&options);
+        helpers::call(&self.transport, "debug_traceTransaction", vec![hash, options])
+    }
+
+    /// Executes the given call on top of the block and returns its trace.
+    pub fn trace_call(
+        &self,
+        req: CallRequest,
+        block: Option<BlockNumber>,
+        options: TracingOptions,
+    ) -> CallFuture<GethTrace, T::Out> {
+        let req = helpers::serialize(&req);
+        let block = helpers::serialize(&block.unwrap_or(BlockNumber::Latest));
+        let options = helpers::serialize(&options);
+        helpers::call(&self.transport, "debug_traceCall", vec![req, block, options])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Debug;
+    use crate::{
+        api::Namespace,
+        rpc::Value,
+        types::{Address, CallFrame, CallRequest, GethTrace, TracingOptions, H256},
+    };
+    use serde_json::json;
+
+    rpc_test! (
+      Debug:trace_transaction, H256::from_low_u64_be(0x123), TracingOptions::call_tracer()
+      =>
+      "debug_traceTransaction", vec![r#""0x0000000000000000000000000000000000000000000000000000000000000123""#, r#"{"tracer":"callTracer"}"#];
+      json!({
+        "type": "CALL",
+        "from": "0x0000000000000000000000000000000000000123",
+        "to": "0x0000000000000000000000000000000000000456",
+        "gas": "0x5208",
+        "gasUsed": "0x5208",
+        "input": "0x"
+      }) => GethTrace::CallTracer(Box::new(CallFrame {
+        call_type: "CALL".into(),
+        from: Address::from_low_u64_be(0x123),
+        to: Some(Address::from_low_u64_be(0x456)),
+        value: None,
+        gas: 21_000.into(),
+        gas_used: 21_000.into(),
+        input: Default::default(),
+        output: None,
+        error: None,
+        revert_reason: None,
+        calls: vec![],
+      }))
+    );
+
+    rpc_test! (
+      Debug:trace_call, CallRequest::default(), None, TracingOptions::default()
+      =>
+      "debug_traceCall", vec![r#"{}"#, r#""latest""#, r#"{}"#];
+      Value::Null => GethTrace::Unknown(Value::Null)
+    );
+}
@@ -0,0 +1,198 @@
+//! `Engine` namespace
+
+use crate::{
+    api::Namespace,
+    helpers::{self, CallFuture},
+    types::{
+        ExecutionPayloadV3, ForkchoiceState, ForkchoiceUpdated, PayloadAttributesV3, PayloadEnvelopeV3, PayloadId,
+        PayloadStatus, H256,
+    },
+    Transport,
+};
+
+/// `Engine` namespace (consensus layer API of execution clients)
+///
+/// Execution clients serve the engine API on a separate, authenticated port. Use an `Http`
+/// transport with `JwtSigner`:
+///
+/// ```no_run
+/// # fn example() -> web3::Result<()> {
+/// use web3::transports::http::{Http, JwtSigner};
+///
+/// let signer = JwtSigner::from_secret_file("/var/lib/geth/jwt.hex")?;
+/// let http = Http::new("http://localhost:8551")?.with_request_signer(signer);
+/// let engine = web3::Web3::new(http).engine();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Engine<T> {
+    transport: T,
+}
+
+impl<T: Transport> Namespace<T> for Engine<T> {
+    fn new(transport: T) -> Self
+    where
+        Self: Sized,
+    {
+        Engine { transport }
+    }
+
+    fn transport(&self) -> &T {
+        &self.transport
+    }
+}
+
+impl<T: Transport> Engine<T> {
+    /// Validates the payload and imports it as a (non-canonical) block.
+    ///
+    /// `versioned_hashes` are the expected hashes of the blobs of the payload transactions.
+    pub fn new_payload_v3(
+        &self,
+        payload: ExecutionPayloadV3,
+        versioned_hashes: Vec<H256>,
+        parent_beacon_block_root: H256,
+    ) -> CallFuture<PayloadStatus, T::Out> {
+        let payload = helpers::serialize(&payload);
+        let versioned_hashes = helpers::serialize(&versioned_hashes);
+        let root = helpers::serialize(&parent_beacon_block_root);
+        helpers::call(
+            &self.transport,
+            "engine_newPayloadV3",
+            vec![payload, versioned_hashes, root],
+        )
+    }
+
+    /// Updates the canonical chain, starting to build a payload on top of the head if attributes are given.
+    pub fn forkchoice_updated_v3(
+        &self,
+        state: ForkchoiceState,
+        attributes: Option<PayloadAttributesV3>,
+    ) -> CallFuture<ForkchoiceUpdated, T::Out> {
+        let state = helpers::serialize(&state);
+        let attributes = helpers::serialize(&attributes);
+        helpers::call(&self.transport, "engine_forkchoiceUpdatedV3", vec![state, attributes])
+    }
+
+    /// Returns the payload built so far by the build process with given id.
+    pub fn get_payload_v3(&self, payload_id: PayloadId) -> CallFuture<PayloadEnvelopeV3, T::Out> {
+        let payload_id = helpers::serialize(&payload_id);
+        helpers::call(&self.transport, "engine_getPayloadV3", vec![payload_id])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Engine;
+    use crate::{
+        api::Namespace,
+        rpc::Value,
+        transports::test::TestTransport,
+        types::{
+            ExecutionPayloadV3, ForkchoiceState, ForkchoiceUpdated, PayloadEnvelopeV3, PayloadStatus, PayloadValidity,
+            H256, H64,
+        },
+    };
+
+    const EXAMPLE_PAYLOAD: &str = r#"{
+      "parentHash": "0x0000000000000000000000000000000000000000000000000000000000000001",
+      "feeRecipient": "0x0000000000000000000000000000000000000002",
+      "stateRoot": "0x0000000000000000000000000000000000000000000000000000000000000003",
+      "receiptsRoot": "0x0000000000000000000000000000000000000000000000000000000000000004",
+      "logsBloom": "0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+      "prevRandao": "0x0000000000000000000000000000000000000000000000000000000000000005",
+      "blockNumber": "0x10",
+      "gasLimit": "0x1c9c380",
+      "gasUsed": "0x0",
+      "timestamp": "0x65f0a0b0",
+      "extraData": "0x",
+      "baseFeePerGas": "0x7",
+      "blockHash": "0x0000000000000000000000000000000000000000000000000000000000000006",
+      "transactions": ["0x02f8"],
+      "withdrawals": [],
+      "blobGasUsed": "0x0",
+      "excessBlobGas": "0x0"
+    }"#;
+
+    fn payload() -> ExecutionPayloadV3 {
+        serde_json::from_str(EXAMPLE_PAYLOAD).unwrap()
+    }
+
+    #[test]
+    fn new_payload_v3() {
+        let mut transport = TestTransport::default();
+        transport.set_response(serde_json::json!({
+            "status": "INVALID_BLOCK_HASH",
+            "latestValidHash": null,
+            "validationError": "bad hash"
+        }));
+
+        let engine = Engine::new(transport.clone());
+        let status = futures::executor::block_on(engine.new_payload_v3(
+            payload(),
+            vec![H256::from_low_u64_be(7)],
+            H256::from_low_u64_be(8),
+        ));
+
+        transport.assert_request_json(
+            "engine_newPayloadV3",
+            &[
+                serde_json::from_str(EXAMPLE_PAYLOAD).unwrap(),
+                serde_json::json!(["0x0000000000000000000000000000000000000000000000000000000000000007"]),
+                serde_json::json!("0x0000000000000000000000000000000000000000000000000000000000000008"),
+            ],
+        );
+        transport.assert_no_more_requests();
+        assert_eq!(
+            status,
+            Ok(PayloadStatus {
+                status: PayloadValidity::InvalidBlockHash,
+                latest_valid_hash: None,
+                validation_error: Some("bad hash".into()),
+            })
+        );
+    }
+
+    rpc_test! (
+      Engine:forkchoice_updated_v3, ForkchoiceState {
+        head_block_hash: H256::from_low_u64_be(1),
+        safe_block_hash: H256::from_low_u64_be(2),
+        finalized_block_hash: H256::from_low_u64_be(3),
+      }, None
+      =>
+      "engine_forkchoiceUpdatedV3", vec![
+        r#"{"finalizedBlockHash":"0x0000000000000000000000000000000000000000000000000000000000000003","headBlockHash":"0x0000000000000000000000000000000000000000000000000000000000000001","safeBlockHash":"0x0000000000000000000000000000000000000000000000000000000000000002"}"#,
+        "null"
+      ];
+      serde_json::json!({
+        "payloadStatus": {"status": "VALID", "latestValidHash": "0x0000000000000000000000000000000000000000000000000000000000000001", "validationError": null},
+        "payloadId": "0x0000000000000009"
+      })
+      => ForkchoiceUpdated {
+        payload_status: PayloadStatus {
+          status: PayloadValidity::Valid,
+          latest_valid_hash: Some(H256::from_low_u64_be(1)),
+          validation_error: None,
+        },
+        payload_id: Some(H64::from_low_u64_be(9)),
+      }
+    );
+
+    rpc_test! (
+      Engine:get_payload_v3, H64::from_low_u64_be(9)
+      =>
+      "engine_getPayloadV3", vec![r#""0x0000000000000009""#];
+      serde_json::json!({
+        "executionPayload": serde_json::from_str::<Value>(EXAMPLE_PAYLOAD).unwrap(),
+        "blockValue": "0x100",
+        "blobsBundle": {"commitments": [], "proofs": [], "blobs": []},
+        "shouldOverrideBuilder": false
+      })
+      => PayloadEnvelopeV3 {
+        execution_payload: payload(),
+        block_value: 0x100.into(),
+        blobs_bundle: Default::default(),
+        should_override_builder: false,
+      }
+    );
+}
@@ -1,20 +1,25 @@
 //! `Eth` namespace
 
 use crate::{
-    api::Namespace,
+    api::{Namespace, ProviderProfile, QuantityFormat},
+    error,
     helpers::{self, CallFuture},
+    rpc,
     types::{
-        Address, Block, BlockHeader, BlockId, BlockNumber, Bytes, CallRequest, FeeHistory, Filter, Index, Log, Proof,
-        SyncState, Transaction, TransactionId, TransactionReceipt, TransactionRequest, Work, H256, H520, H64, U256,
-        U64,
+        AccessListWithGasUsed, Address, Block, BlockHeader, BlockId, BlockNumber, BlockNumberAndHash, Bytes,
+        CallRequest, FeeHistory, Filter, Index, Log, Proof, RawTransaction, SyncState, Transaction, TransactionId,
+        TransactionReceipt, TransactionRequest, Work, H256, H520, H64, U256, U64,
     },
     Transport,
 };
+use futures::{stream::BoxStream, StreamExt, TryStreamExt};
 
 /// `Eth` namespace
 #[derive(Debug, Clone)]
 pub struct Eth<T> {
     transport: T,
+    profile: ProviderProfile,
+    default_block: Option<BlockNumber>,
 }
 
 impl<T: Transport> Namespace<T> for Eth<T> {
@@ -22,7 +27,11 @@ impl<T: Transport> Namespace<T> for Eth<T> {
     where
         Self: Sized,
     {
-        Eth { transport }
+        Eth {
+            transport,
+            profile: Default::default(),
+            default_block: None,
+        }
     }
 
     fn transport(&self) -> &T {
@@ -31,62 +40,131 @@ impl<T: Transport> Namespace<T> for Eth<T> {
 }
 
 impl<T: Transport> Eth<T> {
+    /// Use given provider profile for calls made through this namespace.
+    pub fn with_profile(mut self, profile: ProviderProfile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Borrows the provider profile.
+    pub fn profile(&self) -> &ProviderProfile {
+        &self.profile
+    }
+
+    /// Query the state at given block (instead of `latest`) unless a block is passed explicitly.
+    ///
+    /// Applies to state queries (`call`, `estimate_gas`, `create_access_list`, `balance`, `code`,
+    /// `storage`, `transaction_count` and `proof`) made through this namespace.
+    pub fn with_default_block(mut self, block: BlockNumber) -> Self {
+        self.default_block = Some(block);
+        self
+    }
+
+    /// Block used by state queries if none is given.
+    pub fn default_block(&self) -> BlockNumber {
+        self.default_block.unwrap_or(BlockNumber::Latest)
+    }
+
     /// Get list of available accounts.
     pub fn accounts(&self) -> CallFuture<Vec<Address>, T::Out> {
-        CallFuture::new(self.transport.execute("eth_accounts", vec![]))
+        helpers::call(&self.transport, "eth_accounts", vec![])
     }
 
     /// Get current block number
     pub fn block_number(&self) -> CallFuture<U64, T::Out> {
-        CallFuture::new(self.transport.execute("eth_blockNumber", vec![]))
+        helpers::call(&self.transport, "eth_blockNumber", vec![])
+    }
+
+    /// Get number and hash of the latest finalized block.
+    ///
+    /// Nodes which don't know about finalized blocks (e.g. pre-merge chains) fail the request
+    /// (Geth: "finalized block not found") or return `None`.
+    pub fn finalized_block_number(&self) -> CallFuture<Option<BlockNumberAndHash>, T::Out> {
+        self.block_number_and_hash(BlockNumber::Finalized)
+    }
+
+    /// Get number and hash of the latest safe block.
+    ///
+    /// Nodes which don't know about safe blocks (e.g. pre-merge chains) fail the request
+    /// (Geth: "safe block not found") or return `None`.
+    pub fn safe_block_number(&self) -> CallFuture<Option<BlockNumberAndHash>, T::Out> {
+        self.block_number_and_hash(BlockNumber::Safe)
+    }
+
+    fn block_number_and_hash(&self, block: BlockNumber) -> CallFuture<Option<BlockNumberAndHash>, T::Out> {
+        let block = helpers::serialize(&block);
+        let include_txs = helpers::serialize(&false);
+        helpers::call(&self.transport, "eth_getBlockByNumber", vec![block, include_txs])
     }
 
     /// Call a constant method of contract without changing the state of the blockchain.
-    pub fn call(&self, req: CallRequest, block: Option<BlockId>) -> CallFuture<Bytes, T::Out> {
+    pub fn call(&self, mut req: CallRequest, block: Option<BlockId>) -> CallFuture<Bytes, T::Out> {
+        self.profile.shape_call(&mut req);
         let req = helpers::serialize(&req);
-        let block = helpers::serialize(&block.unwrap_or_else(|| BlockNumber::Latest.into()));
+        let block = helpers::serialize(&block.unwrap_or_else(|| self.default_block().into()));
 
-        CallFuture::new(self.transport.execute("eth_call", vec![req, block]))
+        helpers::call(&self.transport, "eth_call", vec![req, block])
     }
 
     /// Get coinbase address
     pub fn coinbase(&self) -> CallFuture<Address, T::Out> {
-        CallFuture::new(self.transport.execute("eth_coinbase", vec![]))
+        helpers::call(&self.transport, "eth_coinbase", vec![])
     }
 
     /// Compile LLL
     pub fn compile_lll(&self, code: String) -> CallFuture<Bytes, T::Out> {
         let code = helpers::serialize(&code);
-        CallFuture::new(self.transport.execute("eth_compileLLL", vec![code]))
+        helpers::call(&self.transport, "eth_compileLLL", vec![code])
     }
 
     /// Compile Solidity
     pub fn compile_solidity(&self, code: String) -> CallFuture<Bytes, T::Out> {
         let code = helpers::serialize(&code);
-        CallFuture::new(self.transport.execute("eth_compileSolidity", vec![code]))
+        helpers::call(&self.transport, "eth_compileSolidity", vec![code])
     }
 
     /// Compile Serpent
     pub fn compile_serpent(&self, code: String) -> CallFuture<Bytes, T::Out> {
         let code = helpers::serialize(&code);
-        CallFuture::new(self.transport.execute("eth_compileSerpent", vec![code]))
+        helpers::call(&self.transport, "eth_compileSerpent", vec![code])
     }
 
     /// Call a contract without changing the state of the blockchain to estimate gas usage.
-    pub fn estimate_gas(&self, req: CallRequest, block: Option<BlockNumber>) -> CallFuture<U256, T::Out> {
+    pub fn estimate_gas(&self, mut req: CallRequest, block: Option<BlockNumber>) -> CallFuture<U256, T::Out> {
+        self.profile.shape_call(&mut req);
         let req = helpers::serialize(&req);
 
-        let args = match block {
+        let args = match block.or(self.default_block) {
             Some(block) => vec![req, helpers::serialize(&block)],
             None => vec![req],
         };
 
-        CallFuture::new(self.transport.execute("eth_estimateGas", args))
+        helpers::call(&self.transport, "eth_estimateGas", args)
+    }
+
+    /// Generate an access list for a transaction, along with the gas it uses with the list applied.
+    ///
+    /// Attach the result with `TransactionParameters::with_access_list`.
+    pub fn create_access_list(
+        &self,
+        mut req: CallRequest,
+        block: Option<BlockId>,
+    ) -> CallFuture<AccessListWithGasUsed, T::Out> {
+        self.profile.shape_call(&mut req);
+        let req = helpers::serialize(&req);
+        let block = helpers::serialize(&block.unwrap_or_else(|| self.default_block().into()));
+
+        helpers::call(&self.transport, "eth_createAccessList", vec![req, block])
     }
 
     /// Get current recommended gas price
     pub fn gas_price(&self) -> CallFuture<U256, T::Out> {
-        CallFuture::new(self.transport.execute("eth_gasPrice", vec![]))
+        helpers::call(&self.transport, "eth_gasPrice", vec![])
+    }
+
+    /// Get current recommended priority fee (tip) of EIP-1559 transactions
+    pub fn max_priority_fee_per_gas(&self) -> CallFuture<U256, T::Out> {
+        helpers::call(&self.transport, "eth_maxPriorityFeePerGas", vec![])
     }
 
     /// Returns a collection of historical gas information. This can be used for evaluating the max_fee_per_gas
@@ -97,154 +175,206 @@ impl<T: Transport> Eth<T> {
         newest_block: BlockNumber,
         reward_percentiles: Option<Vec<f64>>,
     ) -> CallFuture<FeeHistory, T::Out> {
-        let block_count = helpers::serialize(&block_count);
+        let block_count = match self.profile.fee_history_block_count {
+            QuantityFormat::Decimal if block_count <= U256::from(u64::MAX) => {
+                helpers::serialize(&block_count.low_u64())
+            }
+            _ => helpers::serialize(&block_count),
+        };
         let newest_block = helpers::serialize(&newest_block);
         let reward_percentiles = helpers::serialize(&reward_percentiles);
 
-        CallFuture::new(
-            self.transport
-                .execute("eth_feeHistory", vec![block_count, newest_block, reward_percentiles]),
+        helpers::call(
+            &self.transport,
+            "eth_feeHistory",
+            vec![block_count, newest_block, reward_percentiles],
         )
     }
 
     /// Get balance of given address
     pub fn balance(&self, address: Address, block: Option<BlockNumber>) -> CallFuture<U256, T::Out> {
         let address = helpers::serialize(&address);
-        let block = helpers::serialize(&block.unwrap_or(BlockNumber::Latest));
+        let block = helpers::serialize(&block.unwrap_or_else(|| self.default_block()));
 
-        CallFuture::new(self.transport.execute("eth_getBalance", vec![address, block]))
+        helpers::call(&self.transport, "eth_getBalance", vec![address, block])
     }
 
     /// Get all logs matching a given filter object
+    ///
+    /// Fails with `Error::ResultSetTooLarge` if the provider refuses the query because it
+    /// matches too many logs, see `helpers::LogPager` for automatic pagination.
     pub fn logs(&self, filter: Filter) -> CallFuture<Vec<Log>, T::Out> {
         let filter = helpers::serialize(&filter);
-        CallFuture::new(self.transport.execute("eth_getLogs", vec![filter]))
+        helpers::call(&self.transport, "eth_getLogs", vec![filter]).classify_errors(helpers::logs_error)
     }
 
     /// Get block details with transaction hashes.
     pub fn block(&self, block: BlockId) -> CallFuture<Option<Block<H256>>, T::Out> {
         let include_txs = helpers::serialize(&false);
 
-        let result = match block {
+        match block {
             BlockId::Hash(hash) => {
                 let hash = helpers::serialize(&hash);
-                self.transport.execute("eth_getBlockByHash", vec![hash, include_txs])
+                helpers::call(&self.transport, "eth_getBlockByHash", vec![hash, include_txs])
             }
             BlockId::Number(num) => {
                 let num = helpers::serialize(&num);
-                self.transport.execute("eth_getBlockByNumber", vec![num, include_txs])
+                helpers::call(&self.transport, "eth_getBlockByNumber", vec![num, include_txs])
             }
-        };
-
-        CallFuture::new(result)
+        }
     }
 
     /// Get block details with full transaction objects.
     pub fn block_with_txs(&self, block: BlockId) -> CallFuture<Option<Block<Transaction>>, T::Out> {
         let include_txs = helpers::serialize(&true);
 
-        let result = match block {
+        match block {
             BlockId::Hash(hash) => {
                 let hash = helpers::serialize(&hash);
-                self.transport.execute("eth_getBlockByHash", vec![hash, include_txs])
+                helpers::call(&self.transport, "eth_getBlockByHash", vec![hash, include_txs])
             }
             BlockId::Number(num) => {
                 let num = helpers::serialize(&num);
-                self.transport.execute("eth_getBlockByNumber", vec![num, include_txs])
+                helpers::call(&self.transport, "eth_getBlockByNumber", vec![num, include_txs])
             }
-        };
-
-        CallFuture::new(result)
+        }
     }
 
     /// Get number of transactions in block
     pub fn block_transaction_count(&self, block: BlockId) -> CallFuture<Option<U256>, T::Out> {
-        let result = match block {
+        match block {
             BlockId::Hash(hash) => {
                 let hash = helpers::serialize(&hash);
-                self.transport.execute("eth_getBlockTransactionCountByHash", vec![hash])
+                helpers::call(&self.transport, "eth_getBlockTransactionCountByHash", vec![hash])
             }
             BlockId::Number(num) => {
                 let num = helpers::serialize(&num);
-                self.transport
-                    .execute("eth_getBlockTransactionCountByNumber", vec![num])
+                helpers::call(&self.transport, "eth_getBlockTransactionCountByNumber", vec![num])
             }
-        };
-
-        CallFuture::new(result)
+        }
     }
 
     /// Get code under given address
     pub fn code(&self, address: Address, block: Option<BlockNumber>) -> CallFuture<Bytes, T::Out> {
         let address = helpers::serialize(&address);
-        let block = helpers::serialize(&block.unwrap_or(BlockNumber::Latest));
+        let block = helpers::serialize(&block.unwrap_or_else(|| self.default_block()));
 
-        CallFuture::new(self.transport.execute("eth_getCode", vec![address, block]))
+        helpers::call(&self.transport, "eth_getCode", vec![address, block])
     }
 
     /// Get supported compilers
     pub fn compilers(&self) -> CallFuture<Vec<String>, T::Out> {
-        CallFuture::new(self.transport.execute("eth_getCompilers", vec![]))
+        helpers::call(&self.transport, "eth_getCompilers", vec![])
     }
 
     /// Get chain id
     pub fn chain_id(&self) -> CallFuture<U256, T::Out> {
-        CallFuture::new(self.transport.execute("eth_chainId", vec![]))
+        helpers::call(&self.transport, "eth_chainId", vec![])
     }
 
     /// Get available user accounts. This method is only available in the browser. With MetaMask,
     /// this will cause the popup that prompts the user to allow or deny access to their accounts
     /// to your app.
     pub fn request_accounts(&self) -> CallFuture<Vec<Address>, T::Out> {
-        CallFuture::new(self.transport.execute("eth_requestAccounts", vec![]))
+        helpers::call(&self.transport, "eth_requestAccounts", vec![])
     }
 
     /// Get storage entry
-    pub fn storage(&self, address: Address, idx: U256, block: Option<BlockNumber>) -> CallFuture<H256, T::Out> {
+    ///
+    /// A block hash is sent as an EIP-1898 block parameter (`{"blockHash": ...}`).
+    pub fn storage(&self, address: Address, idx: U256, block: Option<BlockId>) -> CallFuture<H256, T::Out> {
         let address = helpers::serialize(&address);
         let idx = helpers::serialize(&idx);
-        let block = helpers::serialize(&block.unwrap_or(BlockNumber::Latest));
+        let block = helpers::serialize(&block.unwrap_or_else(|| self.default_block().into()));
 
-        CallFuture::new(self.transport.execute("eth_getStorageAt", vec![address, idx, block]))
+        helpers::call(&self.transport, "eth_getStorageAt", vec![address, idx, block])
     }
 
     /// Get nonce
     pub fn transaction_count(&self, address: Address, block: Option<BlockNumber>) -> CallFuture<U256, T::Out> {
         let address = helpers::serialize(&address);
-        let block = helpers::serialize(&block.unwrap_or(BlockNumber::Latest));
+        let block = helpers::serialize(&block.unwrap_or_else(|| self.default_block()));
 
-        CallFuture::new(self.transport.execute("eth_getTransactionCount", vec![address, block]))
+        helpers::call(&self.transport, "eth_getTransactionCount", vec![address, block])
     }
 
     /// Get transaction
     pub fn transaction(&self, id: TransactionId) -> CallFuture<Option<Transaction>, T::Out> {
-        let result = match id {
+        match id {
             TransactionId::Hash(hash) => {
                 let hash = helpers::serialize(&hash);
-                self.transport.execute("eth_getTransactionByHash", vec![hash])
+                helpers::call(&self.transport, "eth_getTransactionByHash", vec![hash])
             }
             TransactionId::Block(BlockId::Hash(hash), index) => {
                 let hash = helpers::serialize(&hash);
                 let idx = helpers::serialize(&index);
-                self.transport
-                    .execute("eth_getTransactionByBlockHashAndIndex", vec![hash, idx])
+                helpers::call(
+                    &self.transport,
+                    "eth_getTransactionByBlockHashAndIndex",
+                    vec![hash, idx],
+                )
             }
             TransactionId::Block(BlockId::Number(number), index) => {
                 let number = helpers::serialize(&number);
                 let idx = helpers::serialize(&index);
-                self.transport
-                    .execute("eth_getTransactionByBlockNumberAndIndex", vec![number, idx])
+                helpers::call(
+                    &self.transport,
+                    "eth_getTransactionByBlockNumberAndIndex",
+                    vec![number, idx],
+                )
+            }
+        }
+    }
+
+    /// Get RLP encoded (signed) transaction, `None` if the transaction is unknown.
+    ///
+    /// Geth returns empty bytes (`0x`) for unknown transactions, which are mapped to `None` too.
+    pub fn raw_transaction(&self, id: TransactionId) -> CallFuture<Option<Bytes>, T::Out> {
+        let call = match id {
+            TransactionId::Hash(hash) => {
+                let hash = helpers::serialize(&hash);
+                helpers::call(&self.transport, "eth_getRawTransactionByHash", vec![hash])
+            }
+            TransactionId::Block(BlockId::Hash(hash), index) => {
+                let hash = helpers::serialize(&hash);
+                let idx = helpers::serialize(&index);
+                helpers::call(
+                    &self.transport,
+                    "eth_getRawTransactionByBlockHashAndIndex",
+                    vec![hash, idx],
+                )
+            }
+            TransactionId::Block(BlockId::Number(number), index) => {
+                let number = helpers::serialize(&number);
+                let idx = helpers::serialize(&index);
+                helpers::call(
+                    &self.transport,
+                    "eth_getRawTransactionByBlockNumberAndIndex",
+                    vec![number, idx],
+                )
             }
         };
+        call.normalize(|value| match value {
+            rpc::Value::String(ref bytes) if bytes == "0x" => rpc::Value::Null,
+            value => value,
+        })
+    }
 
-        CallFuture::new(result)
+    /// Get RLP encoded block (`debug_getRawBlock`, Geth only).
+    pub fn raw_block(&self, block: BlockId) -> CallFuture<Bytes, T::Out> {
+        let block = match block {
+            BlockId::Hash(hash) => helpers::serialize(&hash),
+            BlockId::Number(num) => helpers::serialize(&num),
+        };
+
+        helpers::call(&self.transport, "debug_getRawBlock", vec![block])
     }
 
     /// Get transaction receipt
     pub fn transaction_receipt(&self, hash: H256) -> CallFuture<Option<TransactionReceipt>, T::Out> {
         let hash = helpers::serialize(&hash);
 
-        CallFuture::new(self.transport.execute("eth_getTransactionReceipt", vec![hash]))
+        helpers::call(&self.transport, "eth_getTransactionReceipt", vec![hash])
     }
 
     /// Get uncle header by block ID and uncle index.
@@ -263,92 +393,115 @@ impl<T: Transport> Eth<T> {
     fn fetch_uncle<X>(&self, block: BlockId, index: Index) -> CallFuture<Option<X>, T::Out> {
         let index = helpers::serialize(&index);
 
-        let result = match block {
+        match block {
             BlockId::Hash(hash) => {
                 let hash = helpers::serialize(&hash);
-                self.transport
-                    .execute("eth_getUncleByBlockHashAndIndex", vec![hash, index])
+                helpers::call(&self.transport, "eth_getUncleByBlockHashAndIndex", vec![hash, index])
             }
             BlockId::Number(num) => {
                 let num = helpers::serialize(&num);
-                self.transport
-                    .execute("eth_getUncleByBlockNumberAndIndex", vec![num, index])
+                helpers::call(&self.transport, "eth_getUncleByBlockNumberAndIndex", vec![num, index])
             }
-        };
-
-        CallFuture::new(result)
+        }
     }
 
     /// Get uncle count in block
     pub fn uncle_count(&self, block: BlockId) -> CallFuture<Option<U256>, T::Out> {
-        let result = match block {
+        match block {
             BlockId::Hash(hash) => {
                 let hash = helpers::serialize(&hash);
-                self.transport.execute("eth_getUncleCountByBlockHash", vec![hash])
+                helpers::call(&self.transport, "eth_getUncleCountByBlockHash", vec![hash])
             }
             BlockId::Number(num) => {
                 let num = helpers::serialize(&num);
-                self.transport.execute("eth_getUncleCountByBlockNumber", vec![num])
+                helpers::call(&self.transport, "eth_getUncleCountByBlockNumber", vec![num])
             }
-        };
-
-        CallFuture::new(result)
+        }
     }
 
     /// Get work package
     pub fn work(&self) -> CallFuture<Work, T::Out> {
-        CallFuture::new(self.transport.execute("eth_getWork", vec![]))
+        helpers::call(&self.transport, "eth_getWork", vec![])
     }
 
     /// Get hash rate
     pub fn hashrate(&self) -> CallFuture<U256, T::Out> {
-        CallFuture::new(self.transport.execute("eth_hashrate", vec![]))
+        helpers::call(&self.transport, "eth_hashrate", vec![])
     }
 
     /// Get mining status
     pub fn mining(&self) -> CallFuture<bool, T::Out> {
-        CallFuture::new(self.transport.execute("eth_mining", vec![]))
+        helpers::call(&self.transport, "eth_mining", vec![])
     }
 
     /// Start new block filter
     pub fn new_block_filter(&self) -> CallFuture<U256, T::Out> {
-        CallFuture::new(self.transport.execute("eth_newBlockFilter", vec![]))
+        helpers::call(&self.transport, "eth_newBlockFilter", vec![])
     }
 
     /// Start new pending transaction filter
     pub fn new_pending_transaction_filter(&self) -> CallFuture<U256, T::Out> {
-        CallFuture::new(self.transport.execute("eth_newPendingTransactionFilter", vec![]))
+        helpers::call(&self.transport, "eth_newPendingTransactionFilter", vec![])
     }
 
     /// Start new pending transaction filter
     pub fn protocol_version(&self) -> CallFuture<String, T::Out> {
-        CallFuture::new(self.transport.execute("eth_protocolVersion", vec![]))
+        helpers::call(&self.transport, "eth_protocolVersion", vec![])
+    }
+
+    /// Get pending transactions sent from the accounts managed by the node (`eth_pendingTransactions`).
+    ///
+    /// This is not the whole pending pool, Geth only returns transactions of its own accounts (see
+    /// `Eth::accounts`). Use `Txpool::content` to inspect the whole pool, or
+    /// `Parity::pending_transactions` on OpenEthereum.
+    pub fn pending_transactions(&self) -> CallFuture<Vec<Transaction>, T::Out> {
+        helpers::call(&self.transport, "eth_pendingTransactions", vec![])
     }
 
     /// Sends a rlp-encoded signed transaction
     pub fn send_raw_transaction(&self, rlp: Bytes) -> CallFuture<H256, T::Out> {
         let rlp = helpers::serialize(&rlp);
-        CallFuture::new(self.transport.execute("eth_sendRawTransaction", vec![rlp]))
+        helpers::call(&self.transport, "eth_sendRawTransaction", vec![rlp])
     }
 
     /// Sends a transaction transaction
-    pub fn send_transaction(&self, tx: TransactionRequest) -> CallFuture<H256, T::Out> {
+    pub fn send_transaction(&self, mut tx: TransactionRequest) -> CallFuture<H256, T::Out> {
+        self.profile.shape_transaction(&mut tx);
         let tx = helpers::serialize(&tx);
-        CallFuture::new(self.transport.execute("eth_sendTransaction", vec![tx]))
+        helpers::call(&self.transport, "eth_sendTransaction", vec![tx])
     }
 
     /// Signs a hash of given data
     pub fn sign(&self, address: Address, data: Bytes) -> CallFuture<H520, T::Out> {
         let address = helpers::serialize(&address);
         let data = helpers::serialize(&data);
-        CallFuture::new(self.transport.execute("eth_sign", vec![address, data]))
+        helpers::call(&self.transport, "eth_sign", vec![address, data])
+    }
+
+    /// Signs a transaction with an account managed by the node without dispatching it to the network.
+    ///
+    /// The account needs to be unlocked. Returns the signed transaction in raw bytes
+    /// (ready for `send_raw_transaction`) along with its details, so a dedicated signer
+    /// node can sign transactions which are then broadcast through another node:
+    ///
+    /// ```no_run
+    /// # async fn example<T: web3::Transport>(signer: web3::api::Eth<T>, node: web3::api::Eth<T>, tx: web3::types::TransactionRequest) -> web3::Result<()> {
+    /// let raw = signer.sign_transaction(tx).await?;
+    /// let hash = node.send_raw_transaction(raw.into()).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn sign_transaction(&self, mut tx: TransactionRequest) -> CallFuture<RawTransaction, T::Out> {
+        self.profile.shape_transaction(&mut tx);
+        let tx = helpers::serialize(&tx);
+        helpers::call(&self.transport, "eth_signTransaction", vec![tx])
     }
 
     /// Submit hashrate of external miner
     pub fn submit_hashrate(&self, rate: U256, id: H256) -> CallFuture<bool, T::Out> {
         let rate = helpers::serialize(&rate);
         let id = helpers::serialize(&id);
-        CallFuture::new(self.transport.execute("eth_submitHashrate", vec![rate, id]))
+        helpers::call(&self.transport, "eth_submitHashrate", vec![rate, id])
     }
 
     /// Submit work of external miner
@@ -356,18 +509,17 @@ impl<T: Transport> Eth<T> {
         let nonce = helpers::serialize(&nonce);
         let pow_hash = helpers::serialize(&pow_hash);
         let mix_hash = helpers::serialize(&mix_hash);
-        CallFuture::new(
-            self.transport
-                .execute("eth_submitWork", vec![nonce, pow_hash, mix_hash]),
-        )
+        helpers::call(&self.transport, "eth_submitWork", vec![nonce, pow_hash, mix_hash])
     }
 
     /// Get syncing status
     pub fn syncing(&self) -> CallFuture<SyncState, T::Out> {
-        CallFuture::new(self.transport.execute("eth_syncing", vec![]))
+        helpers::call(&self.transport, "eth_syncing", vec![])
     }
 
     /// Returns the account- and storage-values of the specified account including the Merkle-proof.
+    ///
+    /// The proofs can be verified locally with `proof::verify_proof`.
     pub fn proof(
         &self,
         address: Address,
@@ -376,8 +528,22 @@ impl<T: Transport> Eth<T> {
     ) -> CallFuture<Option<Proof>, T::Out> {
         let add = helpers::serialize(&address);
         let ks = helpers::serialize(&keys);
-        let blk = helpers::serialize(&block.unwrap_or(BlockNumber::Latest));
-        CallFuture::new(self.transport.execute("eth_getProof", vec![add, ks, blk]))
+        let blk = helpers::serialize(&block.unwrap_or_else(|| self.default_block()));
+        helpers::call(&self.transport, "eth_getProof", vec![add, ks, blk])
+    }
+
+    /// Get all logs matching a given filter object, decoding them one by one.
+    ///
+    /// With `Http::with_streamed_arrays` logs are yielded while the response is being received,
+    /// without buffering the whole (possibly huge) response first.
+    pub fn logs_paged(&self, filter: Filter) -> BoxStream<'static, error::Result<Log>>
+    where
+        T::Out: Send + 'static,
+    {
+        let filter = helpers::serialize(&filter);
+        helpers::execute_stream(&self.transport, "eth_getLogs", vec![filter])
+            .map_err(helpers::logs_error)
+            .boxed()
     }
 }
 
@@ -385,12 +551,13 @@ impl<T: Transport> Eth<T> {
 mod tests {
     use super::Eth;
     use crate::{
-        api::Namespace,
+        api::{Namespace, ProviderProfile, QuantityFormat},
         rpc::Value,
+        transports::test::TestTransport,
         types::{
-            Address, Block, BlockHeader, BlockId, BlockNumber, CallRequest, FeeHistory, FilterBuilder, Log, Proof,
-            SyncInfo, SyncState, Transaction, TransactionId, TransactionReceipt, TransactionRequest, Work, H256, H520,
-            H64, U256,
+            AccessListItem, AccessListWithGasUsed, Address, Block, BlockHeader, BlockId, BlockNumber,
+            BlockNumberAndHash, Bytes, CallRequest, FeeHistory, FilterBuilder, Log, Proof, RawTransaction, SyncInfo,
+            SyncState, Transaction, TransactionId, TransactionReceipt, TransactionRequest, Work, H256, H520, H64, U256,
         },
     };
     use hex_literal::hex;
@@ -506,6 +673,27 @@ mod tests {
     "effectiveGasPrice": "0x100"
   }"#;
 
+    // taken from geth
+    const EXAMPLE_SIGNED_TX: &str = r#"{
+    "raw": "0xf86680843b9aca0082520894853f43d8a49eeb85d32cf465507dd71d507100c18307f1108025a0c69f4f710f5d53aeb621e6ce3cd6eeb367c7f5da0e966fa13acd7ef081c36ef9a0020744073b5bc56896f6ea7b85321c2c4bd3234458664f58d6f1b6d72d8e2224",
+    "tx": {
+      "type": "0x0",
+      "chainId": "0x1",
+      "nonce": "0x0",
+      "to": "0x853f43d8a49eeb85d32cf465507dd71d507100c1",
+      "gas": "0x5208",
+      "gasPrice": "0x3b9aca00",
+      "maxPriorityFeePerGas": null,
+      "maxFeePerGas": null,
+      "value": "0x7f110",
+      "input": "0x",
+      "v": "0x25",
+      "r": "0xc69f4f710f5d53aeb621e6ce3cd6eeb367c7f5da0e966fa13acd7ef081c36ef9",
+      "s": "0x20744073b5bc56896f6ea7b85321c2c4bd3234458664f58d6f1b6d72d8e2224",
+      "hash": "0x031fe8eece02e06f28e46988e69daaa52b95b7d2067c257aefe87eb4817e2760"
+    }
+  }"#;
+
     const EXAMPLE_FEE_HISTORY: &str = r#"{
       "baseFeePerGas": [
           "0x15f794d04b",
@@ -634,17 +822,113 @@ mod tests {
       Value::String("0x123".into()) => 0x123
     );
 
+    rpc_test! (
+      Eth:create_access_list, CallRequest {
+        from: None, to: Some(Address::from_low_u64_be(0x123)),
+        gas: None, gas_price: None,
+        value: None, data: None,
+        transaction_type: None, access_list: None,
+        max_fee_per_gas: None, max_priority_fee_per_gas: None,
+      }, None
+      =>
+      "eth_createAccessList", vec![r#"{"to":"0x0000000000000000000000000000000000000123"}"#, r#""latest""#];
+      json!({
+        "accessList": [{"address": "0x0000000000000000000000000000000000000123", "storageKeys": [H256::zero()]}],
+        "gasUsed": "0x5208"
+      }) => AccessListWithGasUsed {
+        access_list: vec![AccessListItem::new(Address::from_low_u64_be(0x123), vec![H256::zero()])],
+        gas_used: 0x5208.into(),
+        error: None,
+      }
+    );
+
     rpc_test! (
       Eth:gas_price => "eth_gasPrice";
       Value::String("0x123".into()) => 0x123
     );
 
+    rpc_test! (
+      Eth:max_priority_fee_per_gas => "eth_maxPriorityFeePerGas";
+      Value::String("0x3b9aca00".into()) => 0x3b9aca00
+    );
+
     rpc_test! (
       Eth:fee_history, 0x3, BlockNumber::Latest, None => "eth_feeHistory", vec![r#""0x3""#, r#""latest""#, r#"null"#];
       ::serde_json::from_str(EXAMPLE_FEE_HISTORY).unwrap()
       => ::serde_json::from_str::<FeeHistory>(EXAMPLE_FEE_HISTORY).unwrap()
     );
 
+    #[test]
+    fn logs_paged() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.set_response(Value::Array(vec![
+            ::serde_json::from_str(EXAMPLE_LOG).unwrap(),
+            ::serde_json::from_str(EXAMPLE_LOG).unwrap(),
+        ]));
+        let eth = Eth::new(&transport);
+
+        // when
+        let logs = futures::executor::block_on(futures::StreamExt::collect::<Vec<_>>(
+            eth.logs_paged(FilterBuilder::default().build()),
+        ));
+
+        // then
+        transport.assert_request("eth_getLogs", &["{}".into()]);
+        transport.assert_no_more_requests();
+        let log = ::serde_json::from_str::<Log>(EXAMPLE_LOG).unwrap();
+        assert_eq!(logs, vec![Ok(log.clone()), Ok(log)]);
+    }
+
+    #[test]
+    fn default_block() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.add_response(Value::String("0x123".into()));
+        transport.add_response(Value::String("0x123".into()));
+        transport.add_response(Value::String("0x123".into()));
+        let eth = Eth::new(&transport).with_default_block(BlockNumber::Finalized);
+
+        // when
+        let address = Address::from_low_u64_be(0x123);
+        let results = futures::executor::block_on(futures::future::join3(
+            eth.balance(address, None),
+            eth.balance(address, Some(BlockNumber::Latest)),
+            eth.estimate_gas(CallRequest::default(), None),
+        ));
+
+        // then
+        let address = r#""0x0000000000000000000000000000000000000123""#;
+        transport.assert_request("eth_getBalance", &[address.into(), r#""finalized""#.into()]);
+        transport.assert_request("eth_getBalance", &[address.into(), r#""latest""#.into()]);
+        transport.assert_request("eth_estimateGas", &["{}".into(), r#""finalized""#.into()]);
+        transport.assert_no_more_requests();
+        assert_eq!(results, (Ok(0x123.into()), Ok(0x123.into()), Ok(0x123.into())));
+    }
+
+    #[test]
+    fn fee_history_decimal_block_count() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.set_response(::serde_json::from_str(EXAMPLE_FEE_HISTORY).unwrap());
+        let result = {
+            let profile = ProviderProfile::default().fee_history_block_count(QuantityFormat::Decimal);
+            let eth = Eth::new(&transport).with_profile(profile);
+
+            // when
+            eth.fee_history(0x3.into(), BlockNumber::Latest, None)
+        };
+
+        // then
+        transport.assert_request("eth_feeHistory", &["3".into(), r#""latest""#.into(), "null".into()]);
+        transport.assert_no_more_requests();
+        let result = futures::executor::block_on(result);
+        assert_eq!(
+            result,
+            Ok(::serde_json::from_str::<FeeHistory>(EXAMPLE_FEE_HISTORY).unwrap())
+        );
+    }
+
     rpc_test! (
       Eth:balance, Address::from_low_u64_be(0x123), None
       =>
@@ -658,6 +942,92 @@ mod tests {
       => vec![::serde_json::from_str::<Log>(EXAMPLE_LOG).unwrap()]
     );
 
+    rpc_test! (
+      Eth:logs:logs_finalized_range, FilterBuilder::default().from_block(BlockNumber::Safe).to_block(BlockNumber::Finalized).build()
+      =>
+      "eth_getLogs", vec![r#"{"fromBlock":"safe","toBlock":"finalized"}"#];
+      Value::Array(vec![]) => vec![]
+    );
+
+    rpc_test! (
+      Eth:balance:balance_finalized, Address::from_low_u64_be(0x123), Some(BlockNumber::Finalized)
+      =>
+      "eth_getBalance", vec![r#""0x0000000000000000000000000000000000000123""#, r#""finalized""#];
+      Value::String("0x123".into()) => 0x123
+    );
+
+    #[test]
+    fn send_transaction_signed_by_node() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.add_response(::serde_json::from_str(EXAMPLE_SIGNED_TX).unwrap());
+        transport.add_response(Value::String(
+            "0x0000000000000000000000000000000000000000000000000000000000000123".into(),
+        ));
+        let eth = Eth::new(&transport);
+        let tx = TransactionRequest {
+            from: hex!("2c7536e3605d9c16a7a3d7b1898e529396a65c23").into(),
+            ..Default::default()
+        };
+
+        // when
+        let result = futures::executor::block_on(async {
+            let raw = eth.sign_transaction(tx).await?;
+            eth.send_raw_transaction(raw.into()).await
+        });
+
+        // then
+        let raw: RawTransaction = ::serde_json::from_str(EXAMPLE_SIGNED_TX).unwrap();
+        transport.assert_request(
+            "eth_signTransaction",
+            &[r#"{"from":"0x2c7536e3605d9c16a7a3d7b1898e529396a65c23"}"#.into()],
+        );
+        transport.assert_request("eth_sendRawTransaction", &[::serde_json::to_string(&raw.raw).unwrap()]);
+        transport.assert_no_more_requests();
+        assert_eq!(result, Ok(H256::from_low_u64_be(0x123)));
+    }
+
+    #[test]
+    fn finalized_block_number() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.set_response(::serde_json::from_str(EXAMPLE_BLOCK).unwrap());
+        let eth = Eth::new(&transport);
+
+        // when
+        let result = futures::executor::block_on(eth.finalized_block_number());
+
+        // then
+        transport.assert_request("eth_getBlockByNumber", &[r#""finalized""#.into(), "false".into()]);
+        transport.assert_no_more_requests();
+        let block = result.unwrap().unwrap();
+        assert_eq!(block.number, Some(0x1b4.into()));
+        assert_eq!(
+            block.hash,
+            Some(
+                "0x0e670ec64341771606e55d6b4ca35a1a6b75ee3d5145a99d05921026d1527331"
+                    .parse()
+                    .unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn safe_block_number_unsupported() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.set_response(Value::Null);
+        let eth = Eth::new(&transport);
+
+        // when
+        let result = futures::executor::block_on(eth.safe_block_number());
+
+        // then
+        transport.assert_request("eth_getBlockByNumber", &[r#""safe""#.into(), "false".into()]);
+        transport.assert_no_more_requests();
+        assert_eq!(result, Ok(None));
+    }
+
     rpc_test! (
       Eth:block:block_by_hash, BlockId::Hash(H256::from_low_u64_be(0x123))
       =>
@@ -760,6 +1130,37 @@ mod tests {
       => Some(::serde_json::from_str::<Transaction>(EXAMPLE_TX).unwrap())
     );
 
+    rpc_test! (
+      Eth:raw_transaction:raw_tx_by_hash, TransactionId::Hash(H256::from_low_u64_be(0x123))
+      =>
+      "eth_getRawTransactionByHash", vec![r#""0x0000000000000000000000000000000000000000000000000000000000000123""#];
+      Value::String("0x02f0".into()) => Some(Bytes(vec![0x02, 0xf0]))
+    );
+
+    rpc_test! (
+      Eth:raw_transaction:raw_tx_unknown, TransactionId::Hash(H256::from_low_u64_be(0x123))
+      =>
+      "eth_getRawTransactionByHash", vec![r#""0x0000000000000000000000000000000000000000000000000000000000000123""#];
+      Value::Null => None
+    );
+
+    rpc_test! (
+      Eth:raw_transaction:raw_tx_by_block_no_and_index, TransactionId::Block(
+        BlockNumber::Latest.into(),
+        5.into()
+      )
+      =>
+      "eth_getRawTransactionByBlockNumberAndIndex", vec![r#""latest""#, r#""0x5""#];
+      Value::String("0x".into()) => None
+    );
+
+    rpc_test! (
+      Eth:raw_block, BlockId::Hash(H256::from_low_u64_be(0x123))
+      =>
+      "debug_getRawBlock", vec![r#""0x0000000000000000000000000000000000000000000000000000000000000123""#];
+      Value::String("0xf9".into()) => Bytes(vec![0xf9])
+    );
+
     rpc_test! (
       Eth:transaction_receipt, H256::from_low_u64_be(0x123)
       =>
@@ -853,6 +1254,12 @@ mod tests {
       Value::String("0x123".into()) => 0x123
     );
 
+    rpc_test! (
+      Eth:pending_transactions => "eth_pendingTransactions";
+      Value::Array(vec![::serde_json::from_str(EXAMPLE_TX).unwrap()])
+      => vec![::serde_json::from_str::<Transaction>(EXAMPLE_TX).unwrap()]
+    );
+
     rpc_test! (
       Eth:protocol_version => "eth_protocolVersion";
       Value::String("0x123".into()) => "0x123"
@@ -886,6 +1293,22 @@ mod tests {
       Value::String("0x0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000123".into()) => H520::from_low_u64_be(0x123)
     );
 
+    rpc_test! (
+      Eth:sign_transaction, TransactionRequest {
+        from: hex!("2c7536e3605d9c16a7a3d7b1898e529396a65c23").into(),
+        to: Some(hex!("853f43d8a49eeb85d32cf465507dd71d507100c1").into()),
+        gas: Some(21_000.into()), gas_price: Some(1_000_000_000.into()),
+        value: Some(0x7f110.into()), data: None,
+        nonce: Some(0x0.into()), condition: None,
+        transaction_type: None, access_list: None,
+        max_fee_per_gas: None, max_priority_fee_per_gas: None,
+      }
+      =>
+      "eth_signTransaction", vec![r#"{"from":"0x2c7536e3605d9c16a7a3d7b1898e529396a65c23","gas":"0x5208","gasPrice":"0x3b9aca00","nonce":"0x0","to":"0x853f43d8a49eeb85d32cf465507dd71d507100c1","value":"0x7f110"}"#];
+      ::serde_json::from_str(EXAMPLE_SIGNED_TX).unwrap()
+      => ::serde_json::from_str::<RawTransaction>(EXAMPLE_SIGNED_TX).unwrap()
+    );
+
     rpc_test! (
       Eth:submit_hashrate, 0x123, H256::from_low_u64_be(0x456)
       =>
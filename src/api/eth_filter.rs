@@ -2,32 +2,133 @@
 
 use crate::{
     api::Namespace,
-    error, helpers, rpc,
-    types::{Filter, Log, H256},
+    error,
+    helpers::{self, CancellationToken, PollPolicy},
+    rpc,
+    types::{Filter, Log, Transaction, H256},
     Transport,
 };
-use futures::{stream, Stream, TryStreamExt};
+use futures::{
+    future::{self, Either},
+    stream, Stream, StreamExt, TryStreamExt,
+};
 use futures_timer::Delay;
 use serde::de::DeserializeOwned;
 use std::{fmt, marker::PhantomData, time::Duration, vec};
 
+/// Options of `BaseFilter::pending_transactions`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PendingTxOptions {
+    /// Polling policy of the filter.
+    pub policy: PollPolicy,
+    /// Maximal number of `eth_getTransactionByHash` requests in flight.
+    pub max_concurrent: usize,
+    /// Skip transactions which are already mined when looked up.
+    pub skip_mined: bool,
+}
+
+impl Default for PendingTxOptions {
+    fn default() -> Self {
+        PendingTxOptions {
+            policy: PollPolicy::default(),
+            max_concurrent: 16,
+            skip_mined: true,
+        }
+    }
+}
+
 fn filter_stream<T: Transport, I: DeserializeOwned>(
     base: BaseFilter<T, I>,
-    poll_interval: Duration,
+    policy: PollPolicy,
+    cancel: Option<CancellationToken>,
 ) -> impl Stream<Item = error::Result<I>> {
+    changes_stream(base, policy, cancel)
+        // map I to Result<I> even though it is always Ok so that try_flatten works
+        .map_ok(|items| stream::iter(items.into_iter().map(Ok)))
+        .try_flatten()
+        .into_stream()
+}
+
+/// Returns the stream of changes returned by each poll.
+///
+/// Up to `policy.max_errors` consecutive failed polls are skipped, further errors are yielded.
+/// The stream ends (uninstalling the filter) once the delays exceed `policy.backoff.max_elapsed`.
+fn changes_stream<T: Transport, I: DeserializeOwned>(
+    base: BaseFilter<T, I>,
+    policy: PollPolicy,
+    cancel: Option<CancellationToken>,
+) -> impl Stream<Item = error::Result<Vec<I>>> {
     let id = helpers::serialize(&base.id);
-    stream::unfold((base, id), move |state| async move {
-        let (base, id) = state;
-        Delay::new(poll_interval).await;
-        let response = base.transport.execute("eth_getFilterChanges", vec![id.clone()]).await;
-        let items: error::Result<Option<Vec<I>>> = response.and_then(helpers::decode);
-        let items = items.map(Option::unwrap_or_default);
-        Some((items, (base, id)))
+    let state = (base, id, policy.interval(), Duration::ZERO, 0);
+    stream::unfold(state, move |state| {
+        let cancel = cancel.clone();
+        async move {
+            let (base, id, mut interval, mut elapsed, mut errors) = state;
+            loop {
+                let mut delay = policy.delay(interval);
+                if let Some(max_elapsed) = policy.backoff.max_elapsed {
+                    if elapsed >= max_elapsed {
+                        // best effort, the stream ends regardless of the outcome
+                        let _ = base.uninstall().await;
+                        return None;
+                    }
+                    delay = delay.min(max_elapsed - elapsed);
+                }
+                elapsed += delay;
+                let delay = Delay::new(delay);
+                match cancel {
+                    Some(ref cancel) => {
+                        if let Either::Left(_) = future::select(cancel.cancelled(), delay).await {
+                            let _ = base.uninstall().await;
+                            return None;
+                        }
+                    }
+                    None => delay.await,
+                }
+                let response = base.transport.execute("eth_getFilterChanges", vec![id.clone()]).await;
+                let items: error::Result<Option<Vec<I>>> = response.and_then(helpers::decode);
+                let items = match items {
+                    Ok(items) => {
+                        errors = 0;
+                        items.unwrap_or_default()
+                    }
+                    Err(err) if errors < policy.max_errors => {
+                        event!(debug, "Polling filter changes failed: {}", err);
+                        errors += 1;
+                        interval = policy.next_interval(interval, false);
+                        continue;
+                    }
+                    Err(err) => {
+                        interval = policy.next_interval(interval, false);
+                        return Some((Err(err), (base, id, interval, elapsed, errors)));
+                    }
+                };
+                interval = policy.next_interval(interval, !items.is_empty());
+                return Some((Ok(items), (base, id, interval, elapsed, errors)));
+            }
+        }
     })
-    // map I to Result<I> even though it is always Ok so that try_flatten works
-    .map_ok(|items| stream::iter(items.into_iter().map(Ok)))
-    .try_flatten()
-    .into_stream()
+}
+
+/// Looks up transactions by hash, skipping the ones the node doesn't know (anymore).
+async fn hydrate<T: Transport>(
+    transport: T,
+    hashes: Vec<H256>,
+    options: PendingTxOptions,
+) -> error::Result<Vec<Transaction>> {
+    let lookups = hashes.into_iter().map(|hash| {
+        let request = transport.execute("eth_getTransactionByHash", vec![helpers::serialize(&hash)]);
+        async move { helpers::decode::<Option<Transaction>>(request.await?) }
+    });
+    let transactions: Vec<_> = stream::iter(lookups)
+        .buffered(options.max_concurrent.max(1))
+        .try_collect()
+        .await?;
+    Ok(transactions
+        .into_iter()
+        .flatten()
+        .filter(|tx| !(options.skip_mined && tx.block_number.is_some()))
+        .collect())
 }
 
 /// Specifies filter items and constructor method.
@@ -135,7 +236,22 @@ impl<T: Transport, I: DeserializeOwned> BaseFilter<T, I> {
 
     /// Returns the stream of items which automatically polls the server
     pub fn stream(self, poll_interval: Duration) -> impl Stream<Item = error::Result<I>> {
-        filter_stream(self, poll_interval)
+        filter_stream(self, PollPolicy::fixed(poll_interval), None)
+    }
+
+    /// Returns the stream of items which polls the server according to given policy.
+    ///
+    /// Use [PollPolicy::adaptive] to back off while the filter is idle.
+    pub fn stream_with_policy(self, policy: PollPolicy) -> impl Stream<Item = error::Result<I>> {
+        filter_stream(self, policy, None)
+    }
+
+    /// Returns the stream of items which polls the server according to given policy until
+    /// `cancel` is triggered.
+    ///
+    /// Once cancelled the filter is uninstalled from the node and the stream ends.
+    pub fn stream_until(self, policy: PollPolicy, cancel: CancellationToken) -> impl Stream<Item = error::Result<I>> {
+        filter_stream(self, policy, Some(cancel))
     }
 }
 
@@ -148,6 +264,21 @@ impl<T: Transport> BaseFilter<T, Log> {
     }
 }
 
+impl<T: Transport> BaseFilter<T, H256> {
+    /// Returns the stream of full transactions of a pending transactions filter.
+    ///
+    /// Transactions returned by each poll are looked up with up to `options.max_concurrent`
+    /// requests in flight. Transactions the node no longer knows (e.g. dropped ones) are skipped.
+    pub fn pending_transactions(self, options: PendingTxOptions) -> impl Stream<Item = error::Result<Transaction>> {
+        let transport = self.transport.clone();
+        changes_stream(self, options.policy, None)
+            .and_then(move |hashes| hydrate(transport.clone(), hashes, options))
+            .map_ok(|transactions| stream::iter(transactions.into_iter().map(Ok)))
+            .try_flatten()
+            .into_stream()
+    }
+}
+
 /// Should be used to create new filter future
 async fn create_filter<T: Transport, F: FilterInterface>(
     transport: T,
@@ -201,12 +332,13 @@ impl<T: Transport> EthFilter<T> {
 
 #[cfg(test)]
 mod tests {
-    use super::EthFilter;
+    use super::{EthFilter, PendingTxOptions, PollPolicy};
     use crate::{
         api::Namespace,
+        helpers::CancellationToken,
         rpc::Value,
         transports::test::TestTransport,
-        types::{Address, FilterBuilder, Log, H256},
+        types::{Address, FilterBuilder, Log, Transaction, H256},
     };
     use futures::stream::StreamExt;
     use hex_literal::hex;
@@ -439,4 +571,175 @@ mod tests {
         transport.assert_request("eth_getFilterChanges", &[r#""0x123""#.into()]);
         transport.assert_no_more_requests();
     }
+
+    #[test]
+    fn pending_transactions_filter_hydrates_transactions() {
+        // given
+        let pending = Transaction {
+            hash: H256::from_low_u64_be(0x456),
+            ..Default::default()
+        };
+        let mined = Transaction {
+            hash: H256::from_low_u64_be(0x457),
+            block_number: Some(1.into()),
+            ..Default::default()
+        };
+        let mut transport = TestTransport::default();
+        transport.set_response(Value::String("0x123".into()));
+        transport.add_response(serde_json::json!([
+            H256::from_low_u64_be(0x456),
+            H256::from_low_u64_be(0x457),
+            H256::from_low_u64_be(0x458),
+        ]));
+        transport.add_response(serde_json::to_value(&pending).unwrap());
+        transport.add_response(serde_json::to_value(&mined).unwrap());
+        transport.add_response(Value::Null);
+        let result: Vec<_> = {
+            let eth = EthFilter::new(&transport);
+
+            // when
+            let filter = futures::executor::block_on(eth.create_pending_transactions_filter()).unwrap();
+            let options = PendingTxOptions {
+                policy: PollPolicy::fixed(Duration::from_secs(0)),
+                max_concurrent: 2,
+                skip_mined: true,
+            };
+            futures::executor::block_on_stream(filter.pending_transactions(options).boxed_local())
+                .take(1)
+                .collect()
+        };
+
+        // then
+        assert_eq!(result, vec![Ok(pending)]);
+        transport.assert_request("eth_newPendingTransactionFilter", &[]);
+        transport.assert_request("eth_getFilterChanges", &[r#""0x123""#.into()]);
+        for hash in &[0x456, 0x457, 0x458] {
+            transport.assert_request(
+                "eth_getTransactionByHash",
+                &[serde_json::to_string(&H256::from_low_u64_be(*hash)).unwrap()],
+            );
+        }
+        transport.assert_no_more_requests();
+    }
+
+    #[test]
+    fn adaptive_poll_policy_backs_off_and_snaps_back() {
+        let policy = PollPolicy::adaptive(Duration::from_secs(1), Duration::from_secs(5));
+
+        let interval = policy.next_interval(policy.interval(), false);
+        assert_eq!(interval, Duration::from_secs(2));
+        let interval = policy.next_interval(interval, false);
+        assert_eq!(interval, Duration::from_secs(4));
+        let interval = policy.next_interval(interval, false);
+        assert_eq!(interval, Duration::from_secs(5));
+        let interval = policy.next_interval(interval, false);
+        assert_eq!(interval, Duration::from_secs(5));
+        let interval = policy.next_interval(interval, true);
+        assert_eq!(interval, Duration::from_secs(1));
+    }
+
+    #[test]
+    fn poll_policy_jitter_stays_within_bounds() {
+        let policy = PollPolicy::fixed(Duration::from_secs(10)).jitter(0.2);
+
+        for _ in 0..100 {
+            let delay = policy.delay(Duration::from_secs(10));
+            assert!(delay >= Duration::from_secs(8) && delay <= Duration::from_secs(12));
+        }
+        assert_eq!(
+            PollPolicy::fixed(Duration::from_secs(10)).delay(Duration::from_secs(10)),
+            Duration::from_secs(10)
+        );
+    }
+
+    #[test]
+    fn blocks_filter_stream_with_policy() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.set_response(Value::String("0x123".into()));
+        transport.add_response(Value::Array(vec![]));
+        transport.add_response(Value::Array(vec![Value::String(
+            r#"0x0000000000000000000000000000000000000000000000000000000000000456"#.into(),
+        )]));
+        let result: Vec<_> = {
+            let eth = EthFilter::new(&transport);
+
+            // when
+            let filter = futures::executor::block_on(eth.create_blocks_filter()).unwrap();
+            let policy = PollPolicy::adaptive(Duration::from_secs(0), Duration::from_millis(1));
+            futures::executor::block_on_stream(filter.stream_with_policy(policy).boxed_local())
+                .take(1)
+                .collect()
+        };
+
+        // then
+        assert_eq!(result, vec![Ok(H256::from_low_u64_be(0x456))]);
+        transport.assert_request("eth_newBlockFilter", &[]);
+        transport.assert_request("eth_getFilterChanges", &[r#""0x123""#.into()]);
+        transport.assert_request("eth_getFilterChanges", &[r#""0x123""#.into()]);
+        transport.assert_no_more_requests();
+    }
+
+    #[test]
+    fn blocks_filter_stream_skips_errors_and_times_out() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.set_response(Value::String("0x123".into()));
+        transport.add_error(crate::Error::Unreachable);
+        transport.add_response(Value::Array(vec![Value::String(
+            r#"0x0000000000000000000000000000000000000000000000000000000000000456"#.into(),
+        )]));
+        transport.add_error(crate::Error::Unreachable);
+        transport.add_response(Value::Bool(true));
+        transport.add_error(crate::Error::Unreachable);
+        transport.add_response(Value::Bool(true));
+        let (skipped, yielded): (Vec<_>, Vec<_>) = {
+            let eth = EthFilter::new(&transport);
+            let policy = PollPolicy::fixed(Duration::from_millis(1)).timeout(Duration::from_millis(3));
+
+            // when
+            let filter = futures::executor::block_on(eth.create_blocks_filter()).unwrap();
+            let stream = filter.clone().stream_with_policy(policy.max_errors(1));
+            let skipped = futures::executor::block_on_stream(stream.boxed_local()).collect();
+            let policy = PollPolicy::fixed(Duration::from_millis(1)).timeout(Duration::from_millis(1));
+            let yielded = futures::executor::block_on_stream(filter.stream_with_policy(policy).boxed_local());
+            (skipped, yielded.collect())
+        };
+
+        // then
+        assert_eq!(skipped, vec![Ok(H256::from_low_u64_be(0x456))]);
+        assert_eq!(yielded, vec![Err(crate::Error::Unreachable)]);
+        transport.assert_request("eth_newBlockFilter", &[]);
+        for _ in 0..3 {
+            transport.assert_request("eth_getFilterChanges", &[r#""0x123""#.into()]);
+        }
+        transport.assert_request("eth_uninstallFilter", &[r#""0x123""#.into()]);
+        transport.assert_request("eth_getFilterChanges", &[r#""0x123""#.into()]);
+        transport.assert_request("eth_uninstallFilter", &[r#""0x123""#.into()]);
+        transport.assert_no_more_requests();
+    }
+
+    #[test]
+    fn blocks_filter_stream_until_cancelled() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.set_response(Value::String("0x123".into()));
+        transport.add_response(Value::Bool(true));
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let result: Vec<_> = {
+            let eth = EthFilter::new(&transport);
+
+            // when
+            let filter = futures::executor::block_on(eth.create_blocks_filter()).unwrap();
+            let stream = filter.stream_until(PollPolicy::fixed(Duration::from_secs(10)), cancel);
+            futures::executor::block_on_stream(stream.boxed_local()).collect()
+        };
+
+        // then
+        assert_eq!(result, vec![]);
+        transport.assert_request("eth_newBlockFilter", &[]);
+        transport.assert_request("eth_uninstallFilter", &[r#""0x123""#.into()]);
+        transport.assert_no_more_requests();
+    }
 }
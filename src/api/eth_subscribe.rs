@@ -1,16 +1,20 @@
 //! `Eth` namespace, subscriptions
 
+#[cfg(feature = "alchemy")]
+use crate::types::{Address, AlchemyAddressFilter, AlchemyMinedTransaction};
 use crate::{
     api::Namespace,
-    error, helpers,
-    types::{BlockHeader, Filter, Log, SyncState, H256},
+    error, helpers, rpc,
+    rpc::Value,
+    types::{BlockHeader, Filter, Log, SyncState, Transaction, H256},
     DuplexTransport,
 };
 use futures::{
     task::{Context, Poll},
-    Stream,
+    Future, Sink, Stream,
 };
 use pin_project::{pin_project, pinned_drop};
+use serde::de::DeserializeOwned;
 use std::{marker::PhantomData, pin::Pin};
 
 /// `Eth` namespace, subscriptions
@@ -36,6 +40,13 @@ impl<T: DuplexTransport> Namespace<T> for EthSubscribe<T> {
 #[derive(Debug, Clone, Eq, Ord, PartialEq, PartialOrd)]
 pub struct SubscriptionId(String);
 
+impl SubscriptionId {
+    /// The id as a parameter of `eth_unsubscribe`.
+    pub(crate) fn param(&self) -> Value {
+        helpers::serialize(&self.0)
+    }
+}
+
 impl From<String> for SubscriptionId {
     fn from(s: String) -> Self {
         SubscriptionId(s)
@@ -45,6 +56,9 @@ impl From<String> for SubscriptionId {
 /// Stream of notifications from a subscription
 /// Given a type deserializable from rpc::Value and a subscription id, yields items of that type as
 /// notifications are delivered.
+///
+/// Dropping the stream cancels the subscription on the node (`eth_unsubscribe` is sent without
+/// waiting for the response), unless [`keep_alive`](SubscriptionStream::keep_alive) was called.
 #[pin_project(PinnedDrop)]
 #[derive(Debug)]
 pub struct SubscriptionStream<T: DuplexTransport, I> {
@@ -52,6 +66,7 @@ pub struct SubscriptionStream<T: DuplexTransport, I> {
     id: SubscriptionId,
     #[pin]
     rx: T::NotificationStream,
+    unsubscribe_on_drop: bool,
     _marker: PhantomData<I>,
 }
 
@@ -62,6 +77,7 @@ impl<T: DuplexTransport, I> SubscriptionStream<T, I> {
             transport,
             id,
             rx,
+            unsubscribe_on_drop: true,
             _marker: PhantomData,
         })
     }
@@ -71,11 +87,35 @@ impl<T: DuplexTransport, I> SubscriptionStream<T, I> {
         &self.id
     }
 
+    /// Keeps the subscription active on the node when the stream is dropped.
+    ///
+    /// Useful if the subscription id is handed over to a different stream or process.
+    pub fn keep_alive(mut self) -> Self {
+        self.unsubscribe_on_drop = false;
+        self
+    }
+
+    /// Forwards the notifications into a (bounded) `sink`, e.g. a processing queue.
+    ///
+    /// `overflow` decides what happens while the sink is not ready. The future resolves
+    /// once the subscription ends and the sink is closed.
+    pub fn feed<S>(self, sink: S, overflow: Overflow) -> Feed<T, I, S>
+    where
+        S: Sink<error::Result<I>>,
+    {
+        Feed {
+            stream: self,
+            sink,
+            overflow,
+            lagged: 0,
+            done: false,
+        }
+    }
+
     /// Unsubscribe from the event represented by this stream
-    pub async fn unsubscribe(self) -> error::Result<bool> {
-        let &SubscriptionId(ref id) = &self.id;
-        let id = helpers::serialize(&id);
-        let response = self.transport.execute("eth_unsubscribe", vec![id]).await?;
+    pub async fn unsubscribe(mut self) -> error::Result<bool> {
+        self.unsubscribe_on_drop = false;
+        let response = self.transport.execute("eth_unsubscribe", vec![self.id.param()]).await?;
         helpers::decode(response)
     }
 }
@@ -90,54 +130,480 @@ where
     fn poll_next(self: Pin<&mut Self>, ctx: &mut Context) -> Poll<Option<Self::Item>> {
         let this = self.project();
         let x = ready!(this.rx.poll_next(ctx));
-        Poll::Ready(x.map(|result| serde_json::from_value(result).map_err(Into::into)))
+        Poll::Ready(x.map(|notification| match notification_error(&notification) {
+            Some(err) => Err(err),
+            None => serde_json::from_value(notification).map_err(Into::into),
+        }))
     }
 }
 
+/// Key of a notification carrying the error which ended the subscription.
+const ERROR_NOTIFICATION: &str = "__subscriptionError";
+
+/// Wraps an error ending a subscription into a notification, yielded as that error by `SubscriptionStream`.
+///
+/// Lets transports emulating subscriptions (e.g. `Polling`) report why the stream ends.
+pub(crate) fn error_notification(err: error::Error) -> Value {
+    let err = match err {
+        error::Error::Rpc(err) => serde_json::json!({ "rpc": err }),
+        err => serde_json::json!({ "transport": err.to_string() }),
+    };
+    serde_json::json!({ ERROR_NOTIFICATION: err })
+}
+
+fn notification_error(notification: &Value) -> Option<error::Error> {
+    let err = notification.as_object()?.get(ERROR_NOTIFICATION)?;
+    if let Some(err) = err.get("rpc") {
+        return serde_json::from_value::<rpc::Error>(err.clone())
+            .ok()
+            .map(error::Error::Rpc);
+    }
+    let message = err.get("transport")?.as_str()?;
+    Some(error::Error::Transport(error::TransportError::Message(message.into())))
+}
+
 #[pinned_drop]
 impl<T, I> PinnedDrop for SubscriptionStream<T, I>
 where
     T: DuplexTransport,
 {
     fn drop(self: Pin<&mut Self>) {
-        let _ = self.transport.unsubscribe(self.id().clone());
+        let _ = if self.unsubscribe_on_drop {
+            self.transport.cancel_subscription(self.id().clone())
+        } else {
+            self.transport.unsubscribe(self.id().clone())
+        };
+    }
+}
+
+/// What [`SubscriptionStream::feed`] does with notifications while the sink is not ready.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    /// Stop reading notifications until the sink is ready.
+    ///
+    /// Combined with a bounded notification buffer of the transport (e.g.
+    /// `WebSocket::with_notification_buffer`) this pauses reading from the connection.
+    Wait,
+    /// Keep reading notifications and drop them while the sink is not ready.
+    ///
+    /// The number of dropped notifications is sent as `Err(Error::Lagged(n))` once the
+    /// sink is ready again.
+    Lag,
+}
+
+/// Future forwarding notifications into a sink, see [`SubscriptionStream::feed`].
+#[pin_project]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Feed<T: DuplexTransport, I, S> {
+    #[pin]
+    stream: SubscriptionStream<T, I>,
+    #[pin]
+    sink: S,
+    overflow: Overflow,
+    lagged: u64,
+    done: bool,
+}
+
+impl<T, I, S> Future for Feed<T, I, S>
+where
+    T: DuplexTransport,
+    I: DeserializeOwned,
+    S: Sink<error::Result<I>>,
+{
+    type Output = Result<(), S::Error>;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context) -> Poll<Self::Output> {
+        let mut this = self.project();
+        loop {
+            let ready = match this.sink.as_mut().poll_ready(ctx) {
+                Poll::Ready(result) => {
+                    result?;
+                    true
+                }
+                Poll::Pending => false,
+            };
+            if ready && *this.lagged > 0 {
+                let lagged = std::mem::take(this.lagged);
+                this.sink.as_mut().start_send(Err(error::Error::Lagged(lagged)))?;
+                continue;
+            }
+            if *this.done {
+                if !ready {
+                    return Poll::Pending;
+                }
+                return this.sink.as_mut().poll_close(ctx);
+            }
+            if !ready && *this.overflow == Overflow::Wait {
+                return Poll::Pending;
+            }
+
+            match this.stream.as_mut().poll_next(ctx) {
+                Poll::Ready(Some(item)) if ready => this.sink.as_mut().start_send(item)?,
+                Poll::Ready(Some(_)) => *this.lagged += 1,
+                Poll::Ready(None) => *this.done = true,
+                Poll::Pending => {
+                    ready!(this.sink.as_mut().poll_flush(ctx))?;
+                    return Poll::Pending;
+                }
+            }
+        }
     }
 }
 
 impl<T: DuplexTransport> EthSubscribe<T> {
-    /// Create a new heads subscription
-    pub async fn subscribe_new_heads(&self) -> error::Result<SubscriptionStream<T, BlockHeader>> {
-        let subscription = helpers::serialize(&&"newHeads");
-        let response = self.transport.execute("eth_subscribe", vec![subscription]).await?;
+    /// Create a subscription of given kind, e.g. a provider specific one.
+    ///
+    /// `params` are passed to `eth_subscribe` after the kind, notifications are deserialized to `I`.
+    pub async fn subscribe<I: DeserializeOwned>(
+        &self,
+        kind: &str,
+        params: Vec<Value>,
+    ) -> error::Result<SubscriptionStream<T, I>> {
+        let mut params = params;
+        params.insert(0, helpers::serialize(&kind));
+        let response = self.transport.execute("eth_subscribe", params).await?;
         let id: String = helpers::decode(response)?;
         SubscriptionStream::new(self.transport.clone(), SubscriptionId(id))
     }
 
+    /// Create a new heads subscription
+    pub async fn subscribe_new_heads(&self) -> error::Result<SubscriptionStream<T, BlockHeader>> {
+        self.subscribe("newHeads", vec![]).await
+    }
+
     /// Create a logs subscription
     pub async fn subscribe_logs(&self, filter: Filter) -> error::Result<SubscriptionStream<T, Log>> {
-        let subscription = helpers::serialize(&&"logs");
-        let filter = helpers::serialize(&filter);
-        let response = self
-            .transport
-            .execute("eth_subscribe", vec![subscription, filter])
-            .await?;
-        let id: String = helpers::decode(response)?;
-        SubscriptionStream::new(self.transport.clone(), SubscriptionId(id))
+        self.subscribe("logs", vec![helpers::serialize(&filter)]).await
     }
 
     /// Create a pending transactions subscription
     pub async fn subscribe_new_pending_transactions(&self) -> error::Result<SubscriptionStream<T, H256>> {
-        let subscription = helpers::serialize(&&"newPendingTransactions");
-        let response = self.transport.execute("eth_subscribe", vec![subscription]).await?;
-        let id: String = helpers::decode(response)?;
-        SubscriptionStream::new(self.transport.clone(), SubscriptionId(id))
+        self.subscribe("newPendingTransactions", vec![]).await
+    }
+
+    /// Create a pending transactions subscription yielding full transactions instead of hashes.
+    ///
+    /// Supported by Geth 1.11 and newer, fails with `Error::IncompatibleEndpoint` if the node
+    /// reports the subscription as unsupported (method not found, `-32004`, or the
+    /// "too many arguments" invalid params error of older Geth versions).
+    pub async fn subscribe_new_pending_transactions_full(&self) -> error::Result<SubscriptionStream<T, Transaction>> {
+        self.subscribe("newPendingTransactions", vec![helpers::serialize(&true)])
+            .await
+            .map_err(|err| match err {
+                error::Error::Rpc(err)
+                    if matches!(err.code.code(), -32601 | -32004)
+                        || (err.code.code() == -32602 && err.message.contains("too many arguments")) =>
+                {
+                    error::Error::IncompatibleEndpoint(format!(
+                        "full pending transactions subscription is not supported: {}",
+                        err.message
+                    ))
+                }
+                err => err,
+            })
     }
 
     /// Create a sync status subscription
     pub async fn subscribe_syncing(&self) -> error::Result<SubscriptionStream<T, SyncState>> {
-        let subscription = helpers::serialize(&&"syncing");
-        let response = self.transport.execute("eth_subscribe", vec![subscription]).await?;
-        let id: String = helpers::decode(response)?;
-        SubscriptionStream::new(self.transport.clone(), SubscriptionId(id))
+        self.subscribe("syncing", vec![]).await
+    }
+
+    /// Create an Alchemy mined transactions subscription, filtered by the node.
+    ///
+    /// A transaction is delivered if it matches any of given filters. With `include_removed`
+    /// transactions dropped from the canonical chain are delivered again with `removed` set.
+    #[cfg(feature = "alchemy")]
+    pub async fn subscribe_alchemy_mined_transactions(
+        &self,
+        addresses: Vec<AlchemyAddressFilter>,
+        include_removed: bool,
+    ) -> error::Result<SubscriptionStream<T, AlchemyMinedTransaction>> {
+        let params = serde_json::json!({
+            "addresses": addresses,
+            "includeRemoved": include_removed,
+            "hashesOnly": false,
+        });
+        self.subscribe("alchemy_minedTransactions", vec![params]).await
+    }
+
+    /// Create an Alchemy pending transactions subscription, filtered by the node.
+    ///
+    /// A transaction is delivered if it's sent from any of `from` or to any of `to` addresses.
+    #[cfg(feature = "alchemy")]
+    pub async fn subscribe_alchemy_pending_transactions(
+        &self,
+        from: Vec<Address>,
+        to: Vec<Address>,
+    ) -> error::Result<SubscriptionStream<T, Transaction>> {
+        let params = serde_json::json!({
+            "fromAddress": from,
+            "toAddress": to,
+            "hashesOnly": false,
+        });
+        self.subscribe("alchemy_pendingTransactions", vec![params]).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{rpc, transports::test::DuplexTestTransport};
+
+    fn subscribe(transport: &DuplexTestTransport) -> SubscriptionStream<DuplexTestTransport, H256> {
+        let mut transport = transport.clone();
+        transport.inner.add_response(rpc::Value::String("0x1".into()));
+        futures::executor::block_on(EthSubscribe::new(transport).subscribe_new_pending_transactions()).unwrap()
+    }
+
+    #[test]
+    fn should_unsubscribe_on_drop() {
+        let mut transport = DuplexTestTransport::default();
+
+        drop(subscribe(&transport));
+
+        transport
+            .inner
+            .assert_request("eth_subscribe", &[r#""newPendingTransactions""#.into()]);
+        transport.inner.assert_request("eth_unsubscribe", &[r#""0x1""#.into()]);
+        transport.inner.assert_no_more_requests();
+    }
+
+    #[test]
+    fn should_not_unsubscribe_twice_or_when_kept_alive() {
+        let mut transport = DuplexTestTransport::default();
+
+        drop(subscribe(&transport).keep_alive());
+        let stream = subscribe(&transport);
+        transport.inner.add_response(rpc::Value::Bool(true));
+        assert_eq!(futures::executor::block_on(stream.unsubscribe()), Ok(true));
+
+        transport
+            .inner
+            .assert_request("eth_subscribe", &[r#""newPendingTransactions""#.into()]);
+        transport
+            .inner
+            .assert_request("eth_subscribe", &[r#""newPendingTransactions""#.into()]);
+        transport.inner.assert_request("eth_unsubscribe", &[r#""0x1""#.into()]);
+        transport.inner.assert_no_more_requests();
+    }
+
+    /// Sends requests only once the response is polled, like `Eip1193`.
+    #[derive(Debug, Default, Clone)]
+    struct LazyTransport {
+        inner: DuplexTestTransport,
+        sent: std::sync::Arc<parking_lot::Mutex<Vec<String>>>,
+    }
+
+    impl crate::Transport for LazyTransport {
+        type Out = futures::future::BoxFuture<'static, error::Result<Value>>;
+
+        fn prepare(&self, method: &str, params: Vec<Value>) -> (crate::RequestId, rpc::Call) {
+            self.inner.prepare(method, params)
+        }
+
+        fn send(&self, id: crate::RequestId, request: rpc::Call) -> Self::Out {
+            let (inner, sent) = (self.inner.clone(), self.sent.clone());
+            Box::pin(async move {
+                if let rpc::Call::MethodCall(ref call) = request {
+                    sent.lock().push(call.method.clone());
+                }
+                inner.send(id, request).await
+            })
+        }
+    }
+
+    impl DuplexTransport for LazyTransport {
+        type NotificationStream = <DuplexTestTransport as DuplexTransport>::NotificationStream;
+
+        fn subscribe(&self, id: SubscriptionId) -> error::Result<Self::NotificationStream> {
+            self.inner.subscribe(id)
+        }
+
+        fn unsubscribe(&self, id: SubscriptionId) -> error::Result<()> {
+            self.inner.unsubscribe(id)
+        }
+    }
+
+    #[test]
+    fn should_unsubscribe_on_drop_with_lazy_transport() {
+        let transport = LazyTransport::default();
+        transport
+            .inner
+            .inner
+            .clone()
+            .add_response(rpc::Value::String("0x1".into()));
+        let stream = futures::executor::block_on(EthSubscribe::new(transport.clone()).subscribe_new_heads()).unwrap();
+        drop(stream);
+        assert_eq!(*transport.sent.lock(), vec!["eth_subscribe", "eth_unsubscribe"]);
+
+        // not delayed by a wrapping transport which is out of requests
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let transport = LazyTransport::default();
+            transport
+                .inner
+                .inner
+                .clone()
+                .add_response(rpc::Value::String("0x1".into()));
+            let throttled = crate::transports::Throttled::new(transport.clone(), 0.001, 1);
+            let stream = futures::executor::block_on(EthSubscribe::new(throttled).subscribe_new_heads()).unwrap();
+            drop(stream);
+            assert_eq!(*transport.sent.lock(), vec!["eth_subscribe", "eth_unsubscribe"]);
+        }
+    }
+
+    #[test]
+    fn should_feed_bounded_sink() {
+        use futures::{
+            channel::mpsc::{self, TryRecvError},
+            task::noop_waker_ref,
+        };
+
+        let notifications = (1..=5).map(|i| rpc::Value::String(format!("{:?}", H256::from_low_u64_be(i))));
+        let mut transport = DuplexTestTransport::default();
+        transport.set_notifications(notifications.collect());
+        let mut ctx = Context::from_waker(noop_waker_ref());
+        let hash = |i| Ok(H256::from_low_u64_be(i));
+
+        // waits for the sink to be ready
+        let (tx, mut rx) = mpsc::channel(0);
+        let mut feed = Box::pin(subscribe(&transport).feed(tx, Overflow::Wait));
+        for i in 1..=5 {
+            assert!(feed.as_mut().poll(&mut ctx).is_pending());
+            assert_eq!(rx.try_recv().unwrap(), hash(i));
+        }
+        assert!(feed.as_mut().poll(&mut ctx).is_ready());
+        assert!(matches!(rx.try_recv(), Err(TryRecvError::Closed)));
+
+        // drops notifications while the sink is full
+        let (tx, mut rx) = mpsc::channel(0);
+        let mut feed = Box::pin(subscribe(&transport).feed(tx, Overflow::Lag));
+        assert!(feed.as_mut().poll(&mut ctx).is_pending());
+        assert_eq!(rx.try_recv().unwrap(), hash(1));
+        assert!(feed.as_mut().poll(&mut ctx).is_pending());
+        assert_eq!(rx.try_recv().unwrap(), Err(error::Error::Lagged(4)));
+        assert!(feed.as_mut().poll(&mut ctx).is_ready());
+        assert!(matches!(rx.try_recv(), Err(TryRecvError::Closed)));
+    }
+
+    #[test]
+    fn should_subscribe_to_full_pending_transactions() {
+        let mut transport = DuplexTestTransport::default();
+        transport.inner.add_response(rpc::Value::String("0x2".into()));
+        let stream =
+            futures::executor::block_on(EthSubscribe::new(transport.clone()).subscribe_new_pending_transactions_full())
+                .unwrap();
+        assert_eq!(stream.keep_alive().id(), &SubscriptionId("0x2".into()));
+
+        transport.inner.add_error(error::Error::Rpc(rpc::Error {
+            code: rpc::ErrorCode::ServerError(-32004),
+            message: "notifications not supported".into(),
+            data: None,
+        }));
+        let result =
+            futures::executor::block_on(EthSubscribe::new(transport.clone()).subscribe_new_pending_transactions_full());
+        assert!(matches!(result, Err(error::Error::IncompatibleEndpoint(_))));
+
+        // Geth before 1.11 rejects the extra param
+        transport.inner.add_error(error::Error::Rpc(rpc::Error::invalid_params(
+            "too many arguments, want at most 1",
+        )));
+        let result =
+            futures::executor::block_on(EthSubscribe::new(transport.clone()).subscribe_new_pending_transactions_full());
+        assert!(matches!(result, Err(error::Error::IncompatibleEndpoint(_))));
+
+        // other errors are passed through
+        let error = rpc::Error::invalid_params("invalid argument 0");
+        transport.inner.add_error(error::Error::Rpc(error.clone()));
+        let result =
+            futures::executor::block_on(EthSubscribe::new(transport.clone()).subscribe_new_pending_transactions_full());
+        assert_eq!(result.err(), Some(error::Error::Rpc(error)));
+
+        for _ in 0..3 {
+            transport
+                .inner
+                .assert_request("eth_subscribe", &[r#""newPendingTransactions""#.into(), "true".into()]);
+        }
+        transport
+            .inner
+            .assert_request("eth_subscribe", &[r#""newPendingTransactions""#.into(), "true".into()]);
+        transport.inner.assert_no_more_requests();
+    }
+
+    #[test]
+    fn should_decode_new_heads() {
+        // newHeads notification with the header fields added up to Cancun (synthetic values)
+        let head = serde_json::json!({
+            "parentHash": "0x9ba2b3a6a9b3c0c9ad1a1c2ee2b6a1e5ad5e7ab4c5da4f0d9c3a1e7c1a0e7d11",
+            "sha3Uncles": "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347",
+            "miner": "0x95222290dd7278aa3ddd389cc1e1d165cc4bafe5",
+            "stateRoot": "0x3b5b1d3d1c1a5a2e7e2c5c2f4a4b0b2c6e7d8f9a0b1c2d3e4f5a6b7c8d9e0f1a",
+            "transactionsRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+            "receiptsRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+            "logsBloom": format!("0x{}", "0".repeat(512)),
+            "difficulty": "0x0",
+            "number": "0x13a7c2b",
+            "gasLimit": "0x1c9c380",
+            "gasUsed": "0xe4e1c0",
+            "timestamp": "0x66a0f3f7",
+            "extraData": "0x6265617665726275696c642e6f7267",
+            "mixHash": "0x2b3e8b7a8c3b6f0f0d2a6c5e4b3a291817161514131211100908070605040300",
+            "nonce": "0x0000000000000000",
+            "baseFeePerGas": "0x9502f900",
+            "withdrawalsRoot": "0x7a4ecf19774d15cf9c15adf0dd8e8a250c128b26c9e2ab2a08d6c9c8ffbd104f",
+            "blobGasUsed": "0x60000",
+            "excessBlobGas": "0x4b00000",
+            "parentBeaconBlockRoot": "0xc8d9e0f1a2b3c4d5e6f708192a3b4c5d6e7f8091a2b3c4d5e6f708192a3b4c5d",
+            "hash": "0x5c1e8d1f0a3b2c4d5e6f708192a3b4c5d6e7f8091a2b3c4d5e6f708192a3b4c5"
+        });
+        let mut transport = DuplexTestTransport::default();
+        transport.set_notifications(vec![head.clone()]);
+        transport.inner.add_response(rpc::Value::String("0x4".into()));
+
+        let heads = futures::executor::block_on(EthSubscribe::new(transport.clone()).subscribe_new_heads()).unwrap();
+        let header = futures::executor::block_on(futures::StreamExt::next(&mut heads.keep_alive()))
+            .unwrap()
+            .unwrap();
+
+        transport
+            .inner
+            .assert_request("eth_subscribe", &[r#""newHeads""#.into()]);
+        transport.inner.assert_no_more_requests();
+        assert_eq!(header.number, Some(0x13a7c2b.into()));
+        assert_eq!(header.base_fee_per_gas, Some(0x9502f900u64.into()));
+        assert_eq!(
+            header.withdrawals_root,
+            Some(
+                "0x7a4ecf19774d15cf9c15adf0dd8e8a250c128b26c9e2ab2a08d6c9c8ffbd104f"
+                    .parse()
+                    .unwrap()
+            )
+        );
+        assert_eq!(header.blob_gas_used, Some(0x60000.into()));
+        assert_eq!(header.excess_blob_gas, Some(0x4b00000.into()));
+        assert!(header.parent_beacon_block_root.is_some());
+        assert_eq!(serde_json::to_value(&header).unwrap(), head);
+    }
+
+    #[cfg(feature = "alchemy")]
+    #[test]
+    fn should_subscribe_to_alchemy_mined_transactions() {
+        let mut transport = DuplexTestTransport::default();
+        transport.inner.add_response(rpc::Value::String("0x3".into()));
+        let filters = vec![AlchemyAddressFilter::to(Address::from_low_u64_be(1))];
+        let stream = futures::executor::block_on(
+            EthSubscribe::new(transport.clone()).subscribe_alchemy_mined_transactions(filters, true),
+        )
+        .unwrap();
+        drop(stream.keep_alive());
+
+        transport.inner.assert_request(
+            "eth_subscribe",
+            &[
+                r#""alchemy_minedTransactions""#.into(),
+                r#"{"addresses":[{"to":"0x0000000000000000000000000000000000000001"}],"hashesOnly":false,"includeRemoved":true}"#.into(),
+            ],
+        );
+        transport.inner.assert_no_more_requests();
     }
 }
@@ -0,0 +1,98 @@
+//! Custom namespaces
+
+/// Declares a namespace with custom RPC methods, e.g. for chain specific APIs.
+///
+/// Every method serializes its parameters in order and decodes the result into the return type.
+/// The namespace implements `Namespace`, so it can be created with `Web3::api`.
+///
+/// ```no_run
+/// use web3::types::{BlockNumber, H256};
+///
+/// web3::define_namespace! {
+///     /// Arbitrum tracing
+///     pub struct ArbTrace;
+///
+///     /// Returns traces of the block
+///     fn block(block: BlockNumber) -> Vec<serde_json::Value> = "arbtrace_block";
+///     /// Returns traces of the transaction
+///     fn transaction(hash: H256) -> Vec<serde_json::Value> = "arbtrace_transaction";
+/// }
+///
+/// # async fn example() -> web3::Result<()> {
+/// let web3 = web3::Web3::new(web3::transports::Http::new("http://localhost:8547")?);
+/// let traces = web3.api::<ArbTrace<_>>().block(BlockNumber::Latest).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[macro_export]
+macro_rules! define_namespace {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident;
+
+        $(
+            $(#[$method_attr:meta])*
+            fn $method:ident ( $( $param:ident : $param_ty:ty ),* $(,)? ) -> $ret:ty = $rpc:literal;
+        )*
+    ) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone)]
+        $vis struct $name<T> {
+            transport: T,
+        }
+
+        impl<T: $crate::Transport> $crate::api::Namespace<T> for $name<T> {
+            fn new(transport: T) -> Self
+            where
+                Self: Sized,
+            {
+                $name { transport }
+            }
+
+            fn transport(&self) -> &T {
+                &self.transport
+            }
+        }
+
+        impl<T: $crate::Transport> $name<T> {
+            $(
+                $(#[$method_attr])*
+                pub fn $method(&self, $( $param: $param_ty ),*) -> $crate::helpers::CallFuture<$ret, T::Out> {
+                    let params = vec![$( $crate::helpers::serialize(&$param) ),*];
+                    $crate::helpers::call(&self.transport, $rpc, params)
+                }
+            )*
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        api::Namespace,
+        rpc::Value,
+        types::{BlockNumber, H256, U64},
+    };
+
+    define_namespace! {
+        /// Test namespace
+        pub struct Custom;
+
+        /// No params
+        fn version() -> String = "custom_version";
+        /// Multiple params
+        fn lookup(hash: H256, block: BlockNumber, full: bool) -> Option<U64> = "custom_lookup";
+    }
+
+    rpc_test! (
+      Custom:version => "custom_version";
+      Value::String("1.0".into()) => "1.0"
+    );
+
+    rpc_test! (
+      Custom:lookup, H256::from_low_u64_be(1), BlockNumber::Latest, true
+      =>
+      "custom_lookup", vec![r#""0x0000000000000000000000000000000000000000000000000000000000000001""#, r#""latest""#, r#"true"#];
+      Value::String("0x10".into()) => Some(U64::from(16))
+    );
+}
@@ -1,40 +1,70 @@
 //! `Web3` implementation
 
 mod accounts;
+mod bor;
+mod clique;
+mod debug;
+mod engine;
 mod eth;
 mod eth_filter;
 mod eth_subscribe;
+mod macros;
 mod net;
 mod parity;
 mod parity_accounts;
 mod parity_set;
 mod personal;
+mod profile;
+#[cfg(feature = "simulation")]
+mod simulation;
+mod test_node;
 mod traces;
 mod txpool;
 mod web3;
+#[cfg(feature = "zksync")]
+mod zksync;
 
 pub use self::{
     accounts::Accounts,
+    bor::Bor,
+    clique::Clique,
+    debug::Debug,
+    engine::Engine,
     eth::Eth,
-    eth_filter::{BaseFilter, EthFilter},
-    eth_subscribe::{EthSubscribe, SubscriptionId, SubscriptionStream},
+    eth_filter::{BaseFilter, EthFilter, PendingTxOptions},
+    eth_subscribe::{EthSubscribe, Feed, Overflow, SubscriptionId, SubscriptionStream},
     net::Net,
     parity::Parity,
     parity_accounts::ParityAccounts,
     parity_set::ParitySet,
     personal::Personal,
+    profile::{ProviderProfile, QuantityFormat},
+    test_node::TestNode,
     traces::Traces,
     txpool::Txpool,
     web3::Web3 as Web3Api,
 };
 
+pub use crate::helpers::PollPolicy;
+
+#[cfg(feature = "simulation")]
+pub use self::simulation::{Simulation, SimulationBackend};
+#[cfg(feature = "zksync")]
+pub use self::zksync::ZkSync;
+
+pub(crate) use self::eth_subscribe::error_notification;
+
 use crate::{
-    confirm, error,
-    types::{Bytes, TransactionReceipt, TransactionRequest, U64},
+    confirm,
+    error::{self, Error},
+    helpers::{self, CallFuture},
+    rpc,
+    types::{BlockId, BlockNumber, Bytes, NodeHealth, SyncState, TransactionReceipt, TransactionRequest, U64},
     DuplexTransport, Transport,
 };
 use futures::Future;
-use std::time::Duration;
+use serde::{de::DeserializeOwned, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 /// Common API for all namespaces
 pub trait Namespace<T: Transport>: Clone {
@@ -49,12 +79,55 @@ pub trait Namespace<T: Transport>: Clone {
 #[derive(Debug, Clone)]
 pub struct Web3<T: Transport> {
     transport: T,
+    profile: ProviderProfile,
+}
+
+#[cfg(any(
+    feature = "http",
+    feature = "ws-tokio",
+    feature = "ws-async-std",
+    feature = "ipc-tokio"
+))]
+impl Web3<crate::transports::EnvTransport> {
+    /// Create new `Web3` with the transport configured by environment variables.
+    ///
+    /// Reads `WEB3_PROVIDER_URL` (or `WEB3_WS_URL`) and picks HTTP, WebSocket or IPC transport
+    /// by the URL scheme (among the enabled ones). See [`crate::transports::env`] for details.
+    ///
+    /// ```no_run
+    /// # async fn example() -> web3::Result<()> {
+    /// let web3 = web3::Web3::from_env().await?;
+    /// let block_number = web3.eth().block_number().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn from_env() -> error::Result<Self> {
+        Ok(Web3::new(crate::transports::env::from_env().await?))
+    }
 }
 
 impl<T: Transport> Web3<T> {
     /// Create new `Web3` with given transport
     pub fn new(transport: T) -> Self {
-        Web3 { transport }
+        Web3 {
+            transport,
+            profile: Default::default(),
+        }
+    }
+
+    /// Create new `Web3` with given transport and provider profile
+    pub fn with_profile(transport: T, profile: ProviderProfile) -> Self {
+        Web3 { transport, profile }
+    }
+
+    /// Create a builder for `Web3` with given transport.
+    pub fn builder(transport: T) -> Web3Builder<T> {
+        Web3Builder {
+            transport,
+            profile: Default::default(),
+            check_endpoint: true,
+            shape_requests: false,
+        }
     }
 
     /// Borrows a transport.
@@ -62,6 +135,92 @@ impl<T: Transport> Web3<T> {
         &self.transport
     }
 
+    /// Borrows the provider profile.
+    pub fn profile(&self) -> &ProviderProfile {
+        &self.profile
+    }
+
+    /// Call an arbitrary JSON-RPC method and decode the result.
+    ///
+    /// This is an escape hatch for provider-specific methods (e.g. `erigon_getHeaderByNumber`)
+    /// which are not covered by any namespace. `params` should serialize to a JSON array
+    /// (e.g. a tuple or a `Vec`); `()` means no parameters and any other value is sent as
+    /// a single parameter.
+    ///
+    /// ```no_run
+    /// # async fn example<T: web3::Transport>(web3: web3::Web3<T>) -> web3::Result<()> {
+    /// use web3::types::U64;
+    ///
+    /// let header: serde_json::Value = web3.request("erigon_getHeaderByNumber", (U64::from(1),)).await?;
+    /// let number: U64 = web3.request("eth_blockNumber", ()).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn request<R, P>(&self, method: &str, params: P) -> CallFuture<R, T::Out>
+    where
+        R: DeserializeOwned,
+        P: Serialize,
+    {
+        let params = match helpers::serialize(&params) {
+            rpc::Value::Array(params) => params,
+            rpc::Value::Null => vec![],
+            param => vec![param],
+        };
+        helpers::call(&self.transport, method, params)
+    }
+
+    /// Checks that the endpoint speaks Ethereum JSON-RPC.
+    ///
+    /// Probes `web3_clientVersion` (must return a string if supported) and `eth_chainId`
+    /// (must be supported and return a hex quantity). Non-EVM endpoints are reported as
+    /// `Error::IncompatibleEndpoint` instead of failing later with decoding errors.
+    pub async fn check_endpoint(&self) -> error::Result<()> {
+        match self.transport.execute("web3_clientVersion", vec![]).await {
+            Ok(rpc::Value::String(_)) | Err(Error::Rpc(_)) => {}
+            Ok(other) => {
+                return Err(Error::IncompatibleEndpoint(format!(
+                    "web3_clientVersion returned {}, expected a string",
+                    other
+                )))
+            }
+            Err(err) => return Err(err),
+        }
+
+        match self.transport.execute("eth_chainId", vec![]).await {
+            Ok(rpc::Value::String(ref id)) if is_quantity(id) => Ok(()),
+            Ok(other) => Err(Error::IncompatibleEndpoint(format!(
+                "eth_chainId returned {}, expected a hex quantity",
+                other
+            ))),
+            Err(Error::Rpc(err)) => Err(Error::IncompatibleEndpoint(format!(
+                "eth_chainId is not supported: {}",
+                err.message
+            ))),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Probes the node for readiness checks.
+    ///
+    /// Fetches `eth_chainId`, `eth_syncing`, `net_peerCount` and the latest block concurrently.
+    pub async fn health(&self) -> error::Result<NodeHealth> {
+        let (chain_id, syncing, peers, block) = futures::future::try_join4(
+            self.eth().chain_id(),
+            self.eth().syncing(),
+            self.net().peer_count(),
+            self.eth().block(BlockId::Number(BlockNumber::Latest)),
+        )
+        .await?;
+        let block = block.ok_or_else(|| Error::InvalidResponse("latest block not found".into()))?;
+        let produced = UNIX_EPOCH + Duration::from_secs(block.timestamp.low_u64());
+        Ok(NodeHealth {
+            synced: syncing == SyncState::NotSyncing,
+            peers,
+            head_age: SystemTime::now().duration_since(produced).unwrap_or_default(),
+            chain_id,
+        })
+    }
+
     /// Access methods from custom namespace
     pub fn api<A: Namespace<T>>(&self) -> A {
         A::new(self.transport.clone())
@@ -72,9 +231,29 @@ impl<T: Transport> Web3<T> {
         self.api()
     }
 
+    /// Access methods from `bor` namespace (Polygon PoS)
+    pub fn bor(&self) -> bor::Bor<T> {
+        self.api()
+    }
+
+    /// Access methods from `clique` namespace (Geth proof-of-authority)
+    pub fn clique(&self) -> clique::Clique<T> {
+        self.api()
+    }
+
+    /// Access methods from `debug` namespace (Geth tracing)
+    pub fn debug(&self) -> debug::Debug<T> {
+        self.api()
+    }
+
+    /// Access methods from `engine` namespace (consensus layer API, requires JWT authentication)
+    pub fn engine(&self) -> engine::Engine<T> {
+        self.api()
+    }
+
     /// Access methods from `eth` namespace
     pub fn eth(&self) -> eth::Eth<T> {
-        self.api()
+        self.api::<eth::Eth<T>>().with_profile(self.profile.clone())
     }
 
     /// Access methods from `net` namespace
@@ -112,6 +291,11 @@ impl<T: Transport> Web3<T> {
         self.api()
     }
 
+    /// Access methods of development nodes (Anvil, Hardhat)
+    pub fn test_node(&self) -> test_node::TestNode<T> {
+        self.api()
+    }
+
     /// Access methods from `trace` namespace
     pub fn trace(&self) -> traces::Traces<T> {
         self.api()
@@ -122,38 +306,114 @@ impl<T: Transport> Web3<T> {
         self.api()
     }
 
+    /// Access bundle simulation (`eth_callMany` / `debug_traceCallMany`)
+    #[cfg(feature = "simulation")]
+    pub fn simulation(&self) -> simulation::Simulation<T> {
+        self.api()
+    }
+
+    /// Access methods from `zks` namespace (zkSync Era)
+    #[cfg(feature = "zksync")]
+    pub fn zksync(&self) -> zksync::ZkSync<T> {
+        self.api()
+    }
+
     /// Should be used to wait for confirmations
-    pub async fn wait_for_confirmations<F, V>(
-        &self,
-        poll_interval: Duration,
-        confirmations: usize,
-        check: V,
-    ) -> error::Result<()>
+    pub async fn wait_for_confirmations<F, V, P>(&self, policy: P, confirmations: usize, check: V) -> error::Result<()>
     where
         F: Future<Output = error::Result<Option<U64>>>,
         V: confirm::ConfirmationCheck<Check = F>,
+        P: Into<PollPolicy>,
     {
-        confirm::wait_for_confirmations(self.eth(), self.eth_filter(), poll_interval, confirmations, check).await
+        confirm::wait_for_confirmations(self.eth(), self.eth_filter(), policy, confirmations, check).await
     }
 
     /// Sends transaction and returns future resolved after transaction is confirmed
-    pub async fn send_transaction_with_confirmation(
+    pub async fn send_transaction_with_confirmation<P: Into<PollPolicy>>(
         &self,
         tx: TransactionRequest,
-        poll_interval: Duration,
+        policy: P,
         confirmations: usize,
     ) -> error::Result<TransactionReceipt> {
-        confirm::send_transaction_with_confirmation(self.transport.clone(), tx, poll_interval, confirmations).await
+        confirm::send_transaction_with_confirmation(self.transport.clone(), tx, policy, confirmations).await
     }
 
     /// Sends raw transaction and returns future resolved after transaction is confirmed
-    pub async fn send_raw_transaction_with_confirmation(
+    pub async fn send_raw_transaction_with_confirmation<P: Into<PollPolicy>>(
         &self,
         tx: Bytes,
-        poll_interval: Duration,
+        policy: P,
         confirmations: usize,
     ) -> error::Result<TransactionReceipt> {
-        confirm::send_raw_transaction_with_confirmation(self.transport.clone(), tx, poll_interval, confirmations).await
+        confirm::send_raw_transaction_with_confirmation(self.transport.clone(), tx, policy, confirmations).await
+    }
+}
+
+fn is_quantity(value: &str) -> bool {
+    match value.strip_prefix("0x") {
+        Some(digits) => !digits.is_empty() && digits.chars().all(|c| c.is_ascii_hexdigit()),
+        None => false,
+    }
+}
+
+/// Builder for `Web3`
+#[derive(Debug, Clone)]
+pub struct Web3Builder<T: Transport> {
+    transport: T,
+    profile: ProviderProfile,
+    check_endpoint: bool,
+    shape_requests: bool,
+}
+
+impl<T: Transport> Web3Builder<T> {
+    /// Sets the provider profile.
+    pub fn profile(mut self, profile: ProviderProfile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Whether to check the endpoint with `Web3::check_endpoint` when building (enabled by default).
+    pub fn check_endpoint(mut self, check_endpoint: bool) -> Self {
+        self.check_endpoint = check_endpoint;
+        self
+    }
+
+    /// Whether to detect the client with `web3_clientVersion` when building and adapt requests to
+    /// it with `ProviderProfile::for_client` (disabled by default).
+    pub fn shape_requests(mut self, shape_requests: bool) -> Self {
+        self.shape_requests = shape_requests;
+        self
+    }
+
+    /// Builds `Web3`, failing with `Error::IncompatibleEndpoint` if the endpoint is not an Ethereum one.
+    pub async fn build(self) -> error::Result<Web3<T>> {
+        let mut web3 = Web3::with_profile(self.transport, self.profile);
+        if self.check_endpoint {
+            web3.check_endpoint().await?;
+        }
+        if self.shape_requests {
+            let client_version = web3.web3().client_version().await?;
+            web3.profile = web3.profile.for_client(&client_version);
+        }
+        Ok(web3)
+    }
+}
+
+impl<T> Web3<T>
+where
+    T: Transport + Send + Sync + 'static,
+    T::Out: Send + 'static,
+{
+    /// Fails requests with `Error::WrongChain` if the endpoint doesn't serve chain `chain_id`.
+    ///
+    /// See [`ExpectChain`](crate::transports::ExpectChain) for details, duplex transports should
+    /// be wrapped with [`ExpectChain::watch_connection`](crate::transports::ExpectChain::watch_connection)
+    /// to check the chain again after reconnecting.
+    pub fn expect_chain(self, chain_id: u64) -> Web3<crate::transports::ExpectChain<T>> {
+        Web3::with_profile(
+            crate::transports::ExpectChain::new(self.transport, chain_id),
+            self.profile,
+        )
     }
 }
 
@@ -162,4 +422,174 @@ impl<T: DuplexTransport> Web3<T> {
     pub fn eth_subscribe(&self) -> eth_subscribe::EthSubscribe<T> {
         self.api()
     }
+
+    /// Should be used to wait for confirmations, reacting to new blocks as they are pushed by the node.
+    pub async fn wait_for_confirmations_subscribed<F, V, P>(
+        &self,
+        policy: P,
+        confirmations: usize,
+        check: V,
+    ) -> error::Result<()>
+    where
+        F: Future<Output = error::Result<Option<U64>>>,
+        V: confirm::ConfirmationCheck<Check = F>,
+        P: Into<PollPolicy>,
+    {
+        confirm::wait_for_confirmations_subscribed(self.transport.clone(), policy, confirmations, check).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Web3;
+    use crate::{
+        error::Error,
+        rpc::Value,
+        transports::test::{self, TestTransport},
+        types::{Address, BlockNumber, NodeHealth, H256, U64},
+    };
+    use std::time::Duration;
+
+    #[test]
+    fn request_with_tuple_params() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.set_response(Value::String("0x123".into()));
+        let result = {
+            let web3 = Web3::new(&transport);
+
+            // when
+            web3.request::<U64, _>("custom_balance", (Address::from_low_u64_be(0x123), BlockNumber::Latest))
+        };
+
+        // then
+        transport.assert_request(
+            "custom_balance",
+            &[
+                r#""0x0000000000000000000000000000000000000123""#.into(),
+                r#""latest""#.into(),
+            ],
+        );
+        transport.assert_no_more_requests();
+        assert_eq!(futures::executor::block_on(result), Ok(0x123.into()));
+    }
+
+    #[test]
+    fn request_without_params() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.set_response(Value::Bool(true));
+        let result = {
+            let web3 = Web3::new(&transport);
+
+            // when
+            web3.request::<bool, _>("custom_ready", ())
+        };
+
+        // then
+        transport.assert_request("custom_ready", &[]);
+        transport.assert_no_more_requests();
+        assert_eq!(futures::executor::block_on(result), Ok(true));
+    }
+
+    #[test]
+    fn builder_checks_endpoint() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.add_response(Value::String("Geth/v1.13.0".into()));
+        transport.add_response(Value::String("0x1".into()));
+
+        // when
+        let result = futures::executor::block_on(Web3::builder(transport.clone()).build());
+
+        // then
+        transport.assert_request("web3_clientVersion", &[]);
+        transport.assert_request("eth_chainId", &[]);
+        transport.assert_no_more_requests();
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn builder_rejects_incompatible_endpoint() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.add_response(Value::String("pathfinder".into()));
+        transport.add_response(Value::String("SN_MAIN".into()));
+
+        // when
+        let result = futures::executor::block_on(Web3::builder(transport.clone()).build());
+
+        // then
+        transport.assert_request("web3_clientVersion", &[]);
+        transport.assert_request("eth_chainId", &[]);
+        transport.assert_no_more_requests();
+        assert_eq!(
+            result.map(|_| ()),
+            Err(Error::IncompatibleEndpoint(
+                "eth_chainId returned \"SN_MAIN\", expected a hex quantity".into()
+            ))
+        );
+    }
+
+    #[test]
+    fn builder_skips_check() {
+        let transport = TestTransport::default();
+        let result =
+            futures::executor::block_on(Web3::builder(transport.clone()).check_endpoint(false).build()).map(|_| ());
+
+        transport.assert_no_more_requests();
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn health_probes_node() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.add_response(Value::String("0x1".into()));
+        transport.add_response(Value::Bool(false));
+        transport.add_response(Value::String("0x5".into()));
+        transport.add_response(test::block(1, H256::zero(), H256::zero(), 0xffff_ffff));
+
+        // when
+        let result = futures::executor::block_on(Web3::new(transport.clone()).health());
+
+        // then
+        transport.assert_request("eth_chainId", &[]);
+        transport.assert_request("eth_syncing", &[]);
+        transport.assert_request("net_peerCount", &[]);
+        transport.assert_request("eth_getBlockByNumber", &[r#""latest""#.into(), "false".into()]);
+        transport.assert_no_more_requests();
+        let health = result.unwrap();
+        assert_eq!(
+            health,
+            NodeHealth {
+                synced: true,
+                peers: 5.into(),
+                head_age: Duration::from_secs(0),
+                chain_id: 1.into(),
+            }
+        );
+        assert!(health.is_ready(1, Duration::from_secs(60)));
+        assert!(!health.is_ready(6, Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn builder_shapes_requests_for_client() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.add_response(Value::String("Geth/v1.13.0".into()));
+
+        // when
+        let result = futures::executor::block_on(
+            Web3::builder(transport.clone())
+                .check_endpoint(false)
+                .shape_requests(true)
+                .build(),
+        );
+
+        // then
+        transport.assert_request("web3_clientVersion", &[]);
+        transport.assert_no_more_requests();
+        assert!(result.unwrap().profile().strip_condition);
+    }
 }
@@ -1,6 +1,11 @@
 //! `Net` namespace
 
-use crate::{api::Namespace, helpers::CallFuture, types::U256, Transport};
+use crate::{
+    api::Namespace,
+    helpers::{self, CallFuture},
+    types::U256,
+    Transport,
+};
 
 /// `Net` namespace
 #[derive(Debug, Clone)]
@@ -24,17 +29,17 @@ impl<T: Transport> Namespace<T> for Net<T> {
 impl<T: Transport> Net<T> {
     /// Returns the network id.
     pub fn version(&self) -> CallFuture<String, T::Out> {
-        CallFuture::new(self.transport.execute("net_version", vec![]))
+        helpers::call(&self.transport, "net_version", vec![])
     }
 
     /// Returns number of peers connected to node.
     pub fn peer_count(&self) -> CallFuture<U256, T::Out> {
-        CallFuture::new(self.transport.execute("net_peerCount", vec![]))
+        helpers::call(&self.transport, "net_peerCount", vec![])
     }
 
     /// Whether the node is listening for network connections
     pub fn is_listening(&self) -> CallFuture<bool, T::Out> {
-        CallFuture::new(self.transport.execute("net_listening", vec![]))
+        helpers::call(&self.transport, "net_listening", vec![])
     }
 }
 
@@ -2,9 +2,10 @@ use crate::{
     api::Namespace,
     helpers::{self, CallFuture},
     rpc::Value,
-    types::{Bytes, CallRequest, ParityPendingTransactionFilter, Transaction},
+    types::{Bytes, CallRequest, LocalTransactionStatus, ParityPendingTransactionFilter, Transaction, H256},
     Transport,
 };
+use std::collections::BTreeMap;
 
 /// `Parity` namespace
 #[derive(Debug, Clone)]
@@ -30,7 +31,7 @@ impl<T: Transport> Parity<T> {
     pub fn call(&self, reqs: Vec<CallRequest>) -> CallFuture<Vec<Bytes>, T::Out> {
         let reqs = helpers::serialize(&reqs);
 
-        CallFuture::new(self.transport.execute("parity_call", vec![reqs]))
+        helpers::call(&self.transport, "parity_call", vec![reqs])
     }
 
     /// Get pending transactions
@@ -48,7 +49,17 @@ impl<T: Transport> Parity<T> {
             _ => vec![],
         };
 
-        CallFuture::new(self.transport.execute("parity_pendingTransactions", params))
+        helpers::call(&self.transport, "parity_pendingTransactions", params)
+    }
+
+    /// Get all transactions in the queue, including the ones not ready to be included in a block
+    pub fn all_transactions(&self) -> CallFuture<Vec<Transaction>, T::Out> {
+        helpers::call(&self.transport, "parity_allTransactions", vec![])
+    }
+
+    /// Get the status of transactions sent through the node, by hash
+    pub fn local_transactions(&self) -> CallFuture<BTreeMap<H256, LocalTransactionStatus>, T::Out> {
+        helpers::call(&self.transport, "parity_localTransactions", vec![])
     }
 }
 
@@ -58,9 +69,13 @@ mod tests {
     use crate::{
         api::Namespace,
         rpc::Value,
-        types::{Address, CallRequest, FilterCondition, ParityPendingTransactionFilter, Transaction, U64},
+        types::{
+            Address, CallRequest, FilterCondition, LocalTransactionStatus, ParityPendingTransactionFilter, Transaction,
+            H256, U64,
+        },
     };
     use hex_literal::hex;
+    use std::collections::BTreeMap;
 
     const EXAMPLE_PENDING_TX: &str = r#"{
     "blockHash": null,
@@ -146,4 +161,51 @@ mod tests {
         Value::Array(vec![::serde_json::from_str(EXAMPLE_PENDING_TX).unwrap()])
       => vec![::serde_json::from_str::<Transaction>(EXAMPLE_PENDING_TX).unwrap()]
     );
+
+    rpc_test!(
+        Parity:all_transactions => "parity_allTransactions";
+        Value::Array(vec![::serde_json::from_str(EXAMPLE_PENDING_TX).unwrap()])
+      => vec![::serde_json::from_str::<Transaction>(EXAMPLE_PENDING_TX).unwrap()]
+    );
+
+    rpc_test!(
+        Parity:local_transactions => "parity_localTransactions";
+        ::serde_json::json!({
+            "0x0000000000000000000000000000000000000000000000000000000000000001": {"status": "pending"},
+            "0x0000000000000000000000000000000000000000000000000000000000000002": {
+                "status": "mined",
+                "transaction": ::serde_json::from_str::<Value>(EXAMPLE_PENDING_TX).unwrap()
+            },
+            "0x0000000000000000000000000000000000000000000000000000000000000003": {
+                "status": "replaced",
+                "transaction": ::serde_json::from_str::<Value>(EXAMPLE_PENDING_TX).unwrap(),
+                "hash": "0x0000000000000000000000000000000000000000000000000000000000000004",
+                "gasPrice": "0xba43b7401"
+            },
+            "0x0000000000000000000000000000000000000000000000000000000000000005": {
+                "status": "rejected",
+                "transaction": ::serde_json::from_str::<Value>(EXAMPLE_PENDING_TX).unwrap(),
+                "error": "Transaction nonce is too low."
+            }
+        })
+      => {
+        let transaction = ::serde_json::from_str::<Transaction>(EXAMPLE_PENDING_TX).unwrap();
+        let mut statuses = BTreeMap::new();
+        statuses.insert(H256::from_low_u64_be(1), LocalTransactionStatus::Pending);
+        statuses.insert(H256::from_low_u64_be(2), LocalTransactionStatus::Mined { transaction: transaction.clone() });
+        statuses.insert(
+            H256::from_low_u64_be(3),
+            LocalTransactionStatus::Replaced {
+                transaction: transaction.clone(),
+                hash: H256::from_low_u64_be(4),
+                gas_price: 0xba43b7401u64.into(),
+            },
+        );
+        statuses.insert(
+            H256::from_low_u64_be(5),
+            LocalTransactionStatus::Rejected { transaction, error: "Transaction nonce is too low.".into() },
+        );
+        statuses
+      }
+    );
 }
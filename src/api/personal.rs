@@ -29,14 +29,14 @@ impl<T: Transport> Namespace<T> for Personal<T> {
 impl<T: Transport> Personal<T> {
     /// Returns a list of available accounts.
     pub fn list_accounts(&self) -> CallFuture<Vec<Address>, T::Out> {
-        CallFuture::new(self.transport.execute("personal_listAccounts", vec![]))
+        helpers::call(&self.transport, "personal_listAccounts", vec![])
     }
 
     /// Creates a new account and protects it with given password.
     /// Returns the address of created account.
     pub fn new_account(&self, password: &str) -> CallFuture<Address, T::Out> {
         let password = helpers::serialize(&password);
-        CallFuture::new(self.transport.execute("personal_newAccount", vec![password]))
+        helpers::call(&self.transport, "personal_newAccount", vec![password])
     }
 
     /// Unlocks the account with given password for some period of time (or single transaction).
@@ -45,9 +45,10 @@ impl<T: Transport> Personal<T> {
         let address = helpers::serialize(&address);
         let password = helpers::serialize(&password);
         let duration = helpers::serialize(&duration);
-        CallFuture::new(
-            self.transport
-                .execute("personal_unlockAccount", vec![address, password, duration]),
+        helpers::call(
+            &self.transport,
+            "personal_unlockAccount",
+            vec![address, password, duration],
         )
     }
 
@@ -56,10 +57,7 @@ impl<T: Transport> Personal<T> {
     pub fn send_transaction(&self, transaction: TransactionRequest, password: &str) -> CallFuture<H256, T::Out> {
         let transaction = helpers::serialize(&transaction);
         let password = helpers::serialize(&password);
-        CallFuture::new(
-            self.transport
-                .execute("personal_sendTransaction", vec![transaction, password]),
-        )
+        helpers::call(&self.transport, "personal_sendTransaction", vec![transaction, password])
     }
 
     /// Signs an Ethereum specific message with `sign(keccak256("\x19Ethereum Signed Message: " + len(data) + data)))`
@@ -70,7 +68,7 @@ impl<T: Transport> Personal<T> {
         let data = helpers::serialize(&data);
         let address = helpers::serialize(&account);
         let password = helpers::serialize(&password);
-        CallFuture::new(self.transport.execute("personal_sign", vec![data, address, password]))
+        helpers::call(&self.transport, "personal_sign", vec![data, address, password])
     }
 
     /// Signs a transaction without dispatching it to the network.
@@ -83,10 +81,7 @@ impl<T: Transport> Personal<T> {
     ) -> CallFuture<RawTransaction, T::Out> {
         let transaction = helpers::serialize(&transaction);
         let password = helpers::serialize(&password);
-        CallFuture::new(
-            self.transport
-                .execute("personal_signTransaction", vec![transaction, password]),
-        )
+        helpers::call(&self.transport, "personal_signTransaction", vec![transaction, password])
     }
 
     /// Imports a raw key and protects it with the given password.
@@ -96,10 +91,7 @@ impl<T: Transport> Personal<T> {
         let private_key = helpers::serialize(&private_key);
         let password = helpers::serialize(&password);
 
-        CallFuture::new(
-            self.transport
-                .execute("personal_importRawKey", vec![private_key, password]),
-        )
+        helpers::call(&self.transport, "personal_importRawKey", vec![private_key, password])
     }
 }
 
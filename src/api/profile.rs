@@ -0,0 +1,129 @@
+//! Provider and chain specific quirks.
+
+use crate::types::{CallRequest, TransactionRequest, U64};
+
+/// Format used to serialize a quantity parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QuantityFormat {
+    /// `0x`-prefixed hex string, as required by the JSON-RPC spec.
+    #[default]
+    Hex,
+    /// Plain JSON number, accepted by some gateways which reject hex strings.
+    Decimal,
+}
+
+/// Describes quirks of the provider (or chain) the client is talking to.
+///
+/// The default profile follows the JSON-RPC spec, deviations have to be opted into.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProviderProfile {
+    /// Format of the `block_count` parameter of `eth_feeHistory`.
+    pub fee_history_block_count: QuantityFormat,
+    /// Remove the (Parity specific) `condition` from transaction requests.
+    pub strip_condition: bool,
+    /// Remove `accessList` from transaction and call requests (and the type of access list
+    /// transactions), for chains before the Berlin hard fork.
+    pub strip_access_list: bool,
+}
+
+impl ProviderProfile {
+    /// Sets the format of the `block_count` parameter of `eth_feeHistory`.
+    pub fn fee_history_block_count(mut self, format: QuantityFormat) -> Self {
+        self.fee_history_block_count = format;
+        self
+    }
+
+    /// Sets whether to remove `condition` from transaction requests.
+    pub fn strip_condition(mut self, strip: bool) -> Self {
+        self.strip_condition = strip;
+        self
+    }
+
+    /// Sets whether to remove `accessList` from transaction and call requests.
+    pub fn strip_access_list(mut self, strip: bool) -> Self {
+        self.strip_access_list = strip;
+        self
+    }
+
+    /// Adapts requests to the client reporting given `web3_clientVersion`.
+    ///
+    /// `condition` is only kept for Parity and OpenEthereum, the only clients supporting it.
+    pub fn for_client(self, client_version: &str) -> Self {
+        let client = client_version
+            .split('/')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let supports_condition = client == "parity" || client == "parity-ethereum" || client == "openethereum";
+        self.strip_condition(!supports_condition)
+    }
+
+    /// Removes fields of a transaction request unsupported by the provider.
+    pub fn shape_transaction(&self, tx: &mut TransactionRequest) {
+        if self.strip_condition {
+            tx.condition = None;
+        }
+        if self.strip_access_list {
+            tx.access_list = None;
+            strip_access_list_type(&mut tx.transaction_type);
+        }
+    }
+
+    /// Removes fields of a call request unsupported by the provider.
+    pub fn shape_call(&self, req: &mut CallRequest) {
+        if self.strip_access_list {
+            req.access_list = None;
+            strip_access_list_type(&mut req.transaction_type);
+        }
+    }
+}
+
+fn strip_access_list_type(transaction_type: &mut Option<U64>) {
+    if *transaction_type == Some(U64::one()) {
+        *transaction_type = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{AccessListItem, Address, TransactionCondition};
+
+    #[test]
+    fn should_strip_condition_for_clients_not_supporting_it() {
+        let tx = TransactionRequest::builder()
+            .from(Address::zero())
+            .condition(TransactionCondition::Block(5))
+            .build();
+
+        let mut geth = tx.clone();
+        ProviderProfile::default()
+            .for_client("Geth/v1.13.0-stable/linux-amd64/go1.21.1")
+            .shape_transaction(&mut geth);
+        let mut parity = tx.clone();
+        ProviderProfile::default()
+            .for_client("OpenEthereum//v3.3.5-stable/x86_64-linux-musl/rustc1.59.0")
+            .shape_transaction(&mut parity);
+        let mut default = tx.clone();
+        ProviderProfile::default().shape_transaction(&mut default);
+
+        assert_eq!(geth.condition, None);
+        assert_eq!(parity, tx);
+        assert_eq!(default, tx);
+    }
+
+    #[test]
+    fn should_strip_access_list() {
+        let mut req = CallRequest::builder()
+            .transaction_type(U64::one())
+            .access_list(vec![AccessListItem {
+                address: Address::zero(),
+                storage_keys: vec![],
+            }])
+            .build();
+
+        ProviderProfile::default().strip_access_list(true).shape_call(&mut req);
+
+        assert_eq!(req, CallRequest::default());
+    }
+}
@@ -0,0 +1,277 @@
+//! Bundle simulation API (`eth_callMany` / `debug_traceCallMany`)
+
+use crate::{
+    api::Namespace,
+    error::{self, Error},
+    helpers, rpc,
+    types::{Bundle, Bytes, CallFrame, SimulatedCall, StateContext, TracingOptions},
+    Transport,
+};
+use parking_lot::Mutex;
+use serde::Deserialize;
+use std::sync::Arc;
+
+/// RPC method used to simulate bundles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimulationBackend {
+    /// `eth_callMany` (Erigon), returns the output or error of each call.
+    CallMany,
+    /// `debug_traceCallMany` with the call tracer, also reports the gas used by each call.
+    TraceCallMany,
+}
+
+/// Simulation of transaction bundles on top of a block.
+///
+/// Unless a backend is set with `with_backend`, `eth_callMany` is tried first, falling back to
+/// `debug_traceCallMany` if the node doesn't support it. The detected backend is shared by
+/// clones of the namespace.
+#[derive(Debug, Clone)]
+pub struct Simulation<T> {
+    transport: T,
+    backend: Arc<Mutex<Option<SimulationBackend>>>,
+}
+
+impl<T: Transport> Namespace<T> for Simulation<T> {
+    fn new(transport: T) -> Self
+    where
+        Self: Sized,
+    {
+        Simulation {
+            transport,
+            backend: Default::default(),
+        }
+    }
+
+    fn transport(&self) -> &T {
+        &self.transport
+    }
+}
+
+impl<T: Transport> Simulation<T> {
+    /// Use given backend instead of detecting it.
+    pub fn with_backend(self, backend: SimulationBackend) -> Self {
+        Simulation {
+            transport: self.transport,
+            backend: Arc::new(Mutex::new(Some(backend))),
+        }
+    }
+
+    /// Backend in use, `None` until detected.
+    pub fn backend(&self) -> Option<SimulationBackend> {
+        *self.backend.lock()
+    }
+
+    /// Simulates the bundles one after another on top of the given state.
+    ///
+    /// Returns the outcome of every call of every bundle; failed calls don't fail the simulation.
+    pub async fn call_many(
+        &self,
+        bundles: Vec<Bundle>,
+        context: StateContext,
+    ) -> error::Result<Vec<Vec<SimulatedCall>>> {
+        match self.backend() {
+            Some(backend) => self.call_many_with(backend, &bundles, &context).await,
+            None => {
+                let result = self
+                    .call_many_with(SimulationBackend::CallMany, &bundles, &context)
+                    .await;
+                let (backend, result) = match result {
+                    Err(Error::Rpc(ref err)) if is_method_not_found(err) => {
+                        let backend = SimulationBackend::TraceCallMany;
+                        (backend, self.call_many_with(backend, &bundles, &context).await)
+                    }
+                    result => (SimulationBackend::CallMany, result),
+                };
+                if result.is_ok() {
+                    *self.backend.lock() = Some(backend);
+                }
+                result
+            }
+        }
+    }
+
+    async fn call_many_with(
+        &self,
+        backend: SimulationBackend,
+        bundles: &[Bundle],
+        context: &StateContext,
+    ) -> error::Result<Vec<Vec<SimulatedCall>>> {
+        let bundles = helpers::serialize(&bundles);
+        let context = helpers::serialize(&context);
+        match backend {
+            SimulationBackend::CallMany => {
+                let result = self.transport.execute("eth_callMany", vec![bundles, context]).await?;
+                let result: Vec<Vec<CallManyResult>> = helpers::decode(result)?;
+                Ok(map_results(result, SimulatedCall::from))
+            }
+            SimulationBackend::TraceCallMany => {
+                let options = helpers::serialize(&TracingOptions::call_tracer());
+                let result = self
+                    .transport
+                    .execute("debug_traceCallMany", vec![bundles, context, options])
+                    .await?;
+                let result: Vec<Vec<CallFrame>> = helpers::decode(result)?;
+                Ok(map_results(result, SimulatedCall::from))
+            }
+        }
+    }
+}
+
+fn map_results<R>(results: Vec<Vec<R>>, f: impl Fn(R) -> SimulatedCall + Copy) -> Vec<Vec<SimulatedCall>> {
+    results
+        .into_iter()
+        .map(|bundle| bundle.into_iter().map(f).collect())
+        .collect()
+}
+
+fn is_method_not_found(err: &rpc::Error) -> bool {
+    let message = err.message.to_ascii_lowercase();
+    err.code == rpc::ErrorCode::MethodNotFound
+        || message.contains("does not exist")
+        || message.contains("not supported")
+}
+
+/// Result of a single call returned by `eth_callMany`.
+#[derive(Debug, Deserialize)]
+struct CallManyResult {
+    #[serde(default)]
+    value: Option<Bytes>,
+    /// Either the message, or an object with `message` and (revert) `data`.
+    #[serde(default)]
+    error: Option<serde_json::Value>,
+}
+
+impl From<CallManyResult> for SimulatedCall {
+    fn from(result: CallManyResult) -> Self {
+        let mut output = result.value.unwrap_or_default();
+        let error = result.error.map(|error| match error {
+            serde_json::Value::String(message) => message,
+            error => {
+                if let Some(data) = error
+                    .get("data")
+                    .and_then(|data| serde_json::from_value(data.clone()).ok())
+                {
+                    output = data;
+                }
+                match error.get("message").and_then(|message| message.as_str()) {
+                    Some(message) => message.into(),
+                    None => error.to_string(),
+                }
+            }
+        });
+        SimulatedCall {
+            output,
+            error,
+            gas_used: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Simulation, SimulationBackend};
+    use crate::{
+        api::Namespace,
+        error::Error,
+        rpc,
+        transports::test::TestTransport,
+        types::{BlockNumber, Bundle, CallRequest, SimulatedCall},
+    };
+    use serde_json::json;
+
+    fn bundles() -> Vec<Bundle> {
+        vec![Bundle::new(vec![CallRequest::default(), CallRequest::default()])]
+    }
+
+    #[test]
+    fn should_simulate_with_call_many() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.set_response(json!([[{"value": "0x01"}, {"error": "execution reverted"}]]));
+        let simulation = Simulation::new(transport.clone());
+
+        // when
+        let result = futures::executor::block_on(simulation.call_many(bundles(), BlockNumber::Latest.into()));
+
+        // then
+        transport.assert_request(
+            "eth_callMany",
+            &[
+                r#"[{"transactions":[{},{}]}]"#.into(),
+                r#"{"blockNumber":"latest"}"#.into(),
+            ],
+        );
+        transport.assert_no_more_requests();
+        assert_eq!(
+            result,
+            Ok(vec![vec![
+                SimulatedCall {
+                    output: vec![1].into(),
+                    ..Default::default()
+                },
+                SimulatedCall {
+                    error: Some("execution reverted".into()),
+                    ..Default::default()
+                },
+            ]])
+        );
+        assert_eq!(simulation.backend(), Some(SimulationBackend::CallMany));
+    }
+
+    #[test]
+    fn should_fall_back_to_trace_call_many() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.add_error(Error::Rpc(rpc::Error::method_not_found()));
+        transport.add_response(json!([[{
+            "type": "CALL",
+            "from": "0x0000000000000000000000000000000000000123",
+            "gas": "0x5208",
+            "gasUsed": "0x5000",
+            "input": "0x",
+            "output": "0x02"
+        }]]));
+        let simulation = Simulation::new(transport.clone());
+
+        // when
+        let result = futures::executor::block_on(simulation.call_many(bundles(), BlockNumber::Latest.into()));
+
+        // then
+        transport.assert_request(
+            "eth_callMany",
+            &[
+                r#"[{"transactions":[{},{}]}]"#.into(),
+                r#"{"blockNumber":"latest"}"#.into(),
+            ],
+        );
+        transport.assert_request(
+            "debug_traceCallMany",
+            &[
+                r#"[{"transactions":[{},{}]}]"#.into(),
+                r#"{"blockNumber":"latest"}"#.into(),
+                r#"{"tracer":"callTracer"}"#.into(),
+            ],
+        );
+        transport.assert_no_more_requests();
+        assert_eq!(
+            result,
+            Ok(vec![vec![SimulatedCall {
+                output: vec![2].into(),
+                error: None,
+                gas_used: Some(0x5000.into()),
+            }]])
+        );
+        assert_eq!(simulation.backend(), Some(SimulationBackend::TraceCallMany));
+    }
+
+    #[test]
+    fn should_decode_revert_data_of_call_many() {
+        let result: super::CallManyResult = serde_json::from_value(json!({
+            "error": {"message": "execution reverted", "data": "0x4e487b710000000000000000000000000000000000000000000000000000000000000001"}
+        }))
+        .unwrap();
+        let call = SimulatedCall::from(result);
+
+        assert_eq!(call.revert_reason(), Some("panic code 0x1".into()));
+    }
+}
@@ -0,0 +1,86 @@
+//! `TestNode` namespace
+
+use crate::{
+    api::Namespace,
+    helpers::{self, CallFuture},
+    types::{Address, U256},
+    Transport,
+};
+
+/// Methods of development nodes (Anvil, Hardhat) used for fork testing.
+///
+/// Uses the `anvil_` method prefix by default, call `hardhat` to use the `hardhat_` one.
+#[derive(Debug, Clone)]
+pub struct TestNode<T> {
+    transport: T,
+    prefix: &'static str,
+}
+
+impl<T: Transport> Namespace<T> for TestNode<T> {
+    fn new(transport: T) -> Self
+    where
+        Self: Sized,
+    {
+        TestNode {
+            transport,
+            prefix: "anvil",
+        }
+    }
+
+    fn transport(&self) -> &T {
+        &self.transport
+    }
+}
+
+impl<T: Transport> TestNode<T> {
+    /// Switches to `hardhat_` prefixed methods.
+    pub fn hardhat(mut self) -> Self {
+        self.prefix = "hardhat";
+        self
+    }
+
+    /// Allows sending transactions from given address without its private key.
+    pub fn impersonate_account(&self, address: Address) -> CallFuture<(), T::Out> {
+        let address = helpers::serialize(&address);
+        self.call("impersonateAccount", vec![address])
+    }
+
+    /// Stops impersonating given address.
+    pub fn stop_impersonating_account(&self, address: Address) -> CallFuture<(), T::Out> {
+        let address = helpers::serialize(&address);
+        self.call("stopImpersonatingAccount", vec![address])
+    }
+
+    /// Sets the balance of given address.
+    pub fn set_balance(&self, address: Address, balance: U256) -> CallFuture<(), T::Out> {
+        let address = helpers::serialize(&address);
+        let balance = helpers::serialize(&balance);
+        self.call("setBalance", vec![address, balance])
+    }
+
+    fn call<R>(&self, method: &str, params: Vec<crate::rpc::Value>) -> CallFuture<R, T::Out> {
+        helpers::call(&self.transport, &format!("{}_{}", self.prefix, method), params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TestNode;
+    use crate::{
+        api::Namespace,
+        rpc::Value,
+        types::{Address, U256},
+    };
+
+    rpc_test! (
+      TestNode:impersonate_account, Address::from_low_u64_be(0x123)
+      => "anvil_impersonateAccount", vec![r#""0x0000000000000000000000000000000000000123""#];
+      Value::Null => ()
+    );
+
+    rpc_test! (
+      TestNode:set_balance, Address::from_low_u64_be(0x123), U256::from(0x10)
+      => "anvil_setBalance", vec![r#""0x0000000000000000000000000000000000000123""#, r#""0x10""#];
+      Value::Null => ()
+    );
+}
@@ -35,7 +35,7 @@ impl<T: Transport> Traces<T> {
         let req = helpers::serialize(&req);
         let block = helpers::serialize(&block.unwrap_or(BlockNumber::Latest));
         let trace_type = helpers::serialize(&trace_type);
-        CallFuture::new(self.transport.execute("trace_call", vec![req, trace_type, block]))
+        helpers::call(&self.transport, "trace_call", vec![req, trace_type, block])
     }
 
     /// Performs multiple call traces on top of the same block. Allows to trace dependent transactions.
@@ -46,27 +46,21 @@ impl<T: Transport> Traces<T> {
     ) -> CallFuture<Vec<BlockTrace>, T::Out> {
         let reqs_with_trace_types = helpers::serialize(&reqs_with_trace_types);
         let block = helpers::serialize(&block.unwrap_or_else(|| BlockNumber::Latest.into()));
-        CallFuture::new(
-            self.transport
-                .execute("trace_callMany", vec![reqs_with_trace_types, block]),
-        )
+        helpers::call(&self.transport, "trace_callMany", vec![reqs_with_trace_types, block])
     }
 
     /// Traces a call to `eth_sendRawTransaction` without making the call, returning the traces
     pub fn raw_transaction(&self, data: Bytes, trace_type: Vec<TraceType>) -> CallFuture<BlockTrace, T::Out> {
         let data = helpers::serialize(&data);
         let trace_type = helpers::serialize(&trace_type);
-        CallFuture::new(self.transport.execute("trace_rawTransaction", vec![data, trace_type]))
+        helpers::call(&self.transport, "trace_rawTransaction", vec![data, trace_type])
     }
 
     /// Replays a transaction, returning the traces
     pub fn replay_transaction(&self, hash: H256, trace_type: Vec<TraceType>) -> CallFuture<BlockTrace, T::Out> {
         let hash = helpers::serialize(&hash);
         let trace_type = helpers::serialize(&trace_type);
-        CallFuture::new(
-            self.transport
-                .execute("trace_replayTransaction", vec![hash, trace_type]),
-        )
+        helpers::call(&self.transport, "trace_replayTransaction", vec![hash, trace_type])
     }
 
     /// Replays all transactions in a block returning the requested traces for each transaction
@@ -77,16 +71,17 @@ impl<T: Transport> Traces<T> {
     ) -> CallFuture<Vec<BlockTrace>, T::Out> {
         let block = helpers::serialize(&block);
         let trace_type = helpers::serialize(&trace_type);
-        CallFuture::new(
-            self.transport
-                .execute("trace_replayBlockTransactions", vec![block, trace_type]),
+        helpers::call(
+            &self.transport,
+            "trace_replayBlockTransactions",
+            vec![block, trace_type],
         )
     }
 
     /// Returns traces created at given block
     pub fn block(&self, block: BlockNumber) -> CallFuture<Vec<Trace>, T::Out> {
         let block = helpers::serialize(&block);
-        CallFuture::new(self.transport.execute("trace_block", vec![block]))
+        helpers::call(&self.transport, "trace_block", vec![block])
     }
 
     /// Return traces matching the given filter
@@ -94,20 +89,20 @@ impl<T: Transport> Traces<T> {
     /// See [TraceFilterBuilder](../types/struct.TraceFilterBuilder.html)
     pub fn filter(&self, filter: TraceFilter) -> CallFuture<Vec<Trace>, T::Out> {
         let filter = helpers::serialize(&filter);
-        CallFuture::new(self.transport.execute("trace_filter", vec![filter]))
+        helpers::call(&self.transport, "trace_filter", vec![filter])
     }
 
-    /// Returns trace at the given position
-    pub fn get(&self, hash: H256, index: Vec<Index>) -> CallFuture<Trace, T::Out> {
+    /// Returns trace at the given position, `None` if there is no such trace
+    pub fn get(&self, hash: H256, index: Vec<Index>) -> CallFuture<Option<Trace>, T::Out> {
         let hash = helpers::serialize(&hash);
         let index = helpers::serialize(&index);
-        CallFuture::new(self.transport.execute("trace_get", vec![hash, index]))
+        helpers::call(&self.transport, "trace_get", vec![hash, index])
     }
 
     /// Returns all traces of a given transaction
     pub fn transaction(&self, hash: H256) -> CallFuture<Vec<Trace>, T::Out> {
         let hash = helpers::serialize(&hash);
-        CallFuture::new(self.transport.execute("trace_transaction", vec![hash]))
+        helpers::call(&self.transport, "trace_transaction", vec![hash])
     }
 }
 
@@ -282,7 +277,7 @@ mod tests {
     =>
     "trace_get", vec![r#""0x0000000000000000000000000000000000000000000000000000000000000123""#, r#"["0x0"]"#];
     ::serde_json::from_str(EXAMPLE_TRACE).unwrap()
-    => ::serde_json::from_str::<Trace>(EXAMPLE_TRACE).unwrap()
+    => Some(::serde_json::from_str::<Trace>(EXAMPLE_TRACE).unwrap())
     );
 
     rpc_test!(
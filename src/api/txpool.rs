@@ -2,8 +2,8 @@
 
 use crate::{
     api::Namespace,
-    helpers::CallFuture,
-    types::{TxpoolContentInfo, TxpoolInspectInfo, TxpoolStatus},
+    helpers::{self, CallFuture},
+    types::{Address, TxpoolContentFromInfo, TxpoolContentInfo, TxpoolInspectInfo, TxpoolStatus},
     Transport,
 };
 
@@ -29,17 +29,23 @@ impl<T: Transport> Namespace<T> for Txpool<T> {
 impl<T: Transport> Txpool<T> {
     /// returns txpool content info
     pub fn content(&self) -> CallFuture<TxpoolContentInfo, T::Out> {
-        CallFuture::new(self.transport.execute("txpool_content", vec![]))
+        helpers::call(&self.transport, "txpool_content", vec![])
+    }
+
+    /// returns txpool content info of transactions sent by `address` (geth >= 1.10.5)
+    pub fn content_from(&self, address: Address) -> CallFuture<TxpoolContentFromInfo, T::Out> {
+        let address = helpers::serialize(&address);
+        helpers::call(&self.transport, "txpool_contentFrom", vec![address])
     }
 
     /// returns txpool inspect info
     pub fn inspect(&self) -> CallFuture<TxpoolInspectInfo, T::Out> {
-        CallFuture::new(self.transport.execute("txpool_inspect", vec![]))
+        helpers::call(&self.transport, "txpool_inspect", vec![])
     }
 
     /// returns txpool status
     pub fn status(&self) -> CallFuture<TxpoolStatus, T::Out> {
-        CallFuture::new(self.transport.execute("txpool_status", vec![]))
+        helpers::call(&self.transport, "txpool_status", vec![])
     }
 }
 
@@ -48,7 +54,7 @@ mod tests {
     use super::Txpool;
     use crate::{
         api::Namespace,
-        types::{TxpoolContentInfo, TxpoolInspectInfo, TxpoolStatus},
+        types::{Address, TxpoolContentFromInfo, TxpoolContentInfo, TxpoolInspectInfo, TxpoolStatus},
     };
 
     const EXAMPLE_CONTENT_INFO: &str = r#"{
@@ -181,6 +187,25 @@ mod tests {
         }
       }"#;
 
+    const EXAMPLE_CONTENT_FROM_INFO: &str = r#"{
+        "pending": {},
+        "queued": {
+          "2": {
+            "blockHash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+            "blockNumber": null,
+            "from": "0x9b11bf0459b0c4b2f87f8cebca4cfc26f294b63a",
+            "gas": "0x15f90",
+            "gasPrice": "0xba43b7400",
+            "hash": "0x3a3c0698552eec2455ed3190eac3996feccc806970a4a056106deaf6ceb1e5e3",
+            "input": "0x",
+            "nonce": "0x2",
+            "to": "0x24a461f25ee6a318bdef7f33de634a67bb67ac9d",
+            "transactionIndex": null,
+            "value": "0xebec21ee1da40000"
+          }
+        }
+      }"#;
+
     const EXAMPLE_STATUS: &str = r#"{
         "pending": "0xa",
         "queued": "0x7"
@@ -192,6 +217,14 @@ mod tests {
       => ::serde_json::from_str::<TxpoolContentInfo>(EXAMPLE_CONTENT_INFO).unwrap()
     );
 
+    rpc_test! (
+      Txpool:content_from, Address::from_low_u64_be(0x123)
+      =>
+      "txpool_contentFrom", vec![r#""0x0000000000000000000000000000000000000123""#];
+      ::serde_json::from_str(EXAMPLE_CONTENT_FROM_INFO).unwrap()
+      => ::serde_json::from_str::<TxpoolContentFromInfo>(EXAMPLE_CONTENT_FROM_INFO).unwrap()
+    );
+
     rpc_test! (
       Txpool:inspect => "txpool_inspect";
       ::serde_json::from_str(EXAMPLE_INSPECT_INFO).unwrap()
@@ -29,13 +29,13 @@ impl<T: Transport> Namespace<T> for Web3<T> {
 impl<T: Transport> Web3<T> {
     /// Returns client version
     pub fn client_version(&self) -> CallFuture<String, T::Out> {
-        CallFuture::new(self.transport.execute("web3_clientVersion", vec![]))
+        helpers::call(&self.transport, "web3_clientVersion", vec![])
     }
 
     /// Returns sha3 of the given data
     pub fn sha3(&self, bytes: Bytes) -> CallFuture<H256, T::Out> {
         let bytes = helpers::serialize(&bytes);
-        CallFuture::new(self.transport.execute("web3_sha3", vec![bytes]))
+        helpers::call(&self.transport, "web3_sha3", vec![bytes])
     }
 }
 
@@ -0,0 +1,150 @@
+//! `ZkSync` namespace
+
+use crate::{
+    api::Namespace,
+    helpers::{self, CallFuture},
+    types::{Address, BridgeContracts, CallRequest, L2ToL1MessageProof, ZkSyncFee, H256, U256},
+    Transport,
+};
+use std::collections::BTreeMap;
+
+/// `ZkSync` namespace (zkSync Era `zks_*` methods)
+#[derive(Debug, Clone)]
+pub struct ZkSync<T> {
+    transport: T,
+}
+
+impl<T: Transport> Namespace<T> for ZkSync<T> {
+    fn new(transport: T) -> Self
+    where
+        Self: Sized,
+    {
+        ZkSync { transport }
+    }
+
+    fn transport(&self) -> &T {
+        &self.transport
+    }
+}
+
+impl<T: Transport> ZkSync<T> {
+    /// Estimates the fee of a transaction, including the gas per pubdata limit.
+    pub fn estimate_fee(&self, req: CallRequest) -> CallFuture<ZkSyncFee, T::Out> {
+        let req = helpers::serialize(&req);
+        helpers::call(&self.transport, "zks_estimateFee", vec![req])
+    }
+
+    /// Returns all confirmed token balances of an account.
+    pub fn all_account_balances(&self, address: Address) -> CallFuture<BTreeMap<Address, U256>, T::Out> {
+        let address = helpers::serialize(&address);
+        helpers::call(&self.transport, "zks_getAllAccountBalances", vec![address])
+    }
+
+    /// Returns the addresses of the default bridge contracts.
+    pub fn bridge_contracts(&self) -> CallFuture<BridgeContracts, T::Out> {
+        helpers::call(&self.transport, "zks_getBridgeContracts", vec![])
+    }
+
+    /// Returns the proof of a message sent from L2 to L1 in a given block.
+    pub fn l2_to_l1_msg_proof(
+        &self,
+        block: u32,
+        sender: Address,
+        msg: H256,
+        log_position: Option<u64>,
+    ) -> CallFuture<Option<L2ToL1MessageProof>, T::Out> {
+        let mut params = vec![
+            helpers::serialize(&block),
+            helpers::serialize(&sender),
+            helpers::serialize(&msg),
+        ];
+        if let Some(log_position) = log_position {
+            params.push(helpers::serialize(&log_position));
+        }
+        helpers::call(&self.transport, "zks_getL2ToL1MsgProof", params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ZkSync;
+    use crate::{
+        api::Namespace,
+        rpc::Value,
+        types::{Address, BridgeContracts, CallRequest, L2ToL1MessageProof, ZkSyncFee, H256},
+    };
+    use hex_literal::hex;
+    use std::collections::BTreeMap;
+
+    rpc_test! (
+      ZkSync:estimate_fee, CallRequest { to: Some(Address::from_low_u64_be(0x123)), ..Default::default() }
+      =>
+      "zks_estimateFee", vec![r#"{"to":"0x0000000000000000000000000000000000000123"}"#];
+      serde_json::json!({
+        "gas_limit": "0x156c00",
+        "gas_per_pubdata_limit": "0x143b",
+        "max_fee_per_gas": "0xee6b280",
+        "max_priority_fee_per_gas": "0x0"
+      }) => ZkSyncFee {
+        gas_limit: 0x156c00.into(),
+        gas_per_pubdata_limit: 0x143b.into(),
+        max_fee_per_gas: 0xee6b280u64.into(),
+        max_priority_fee_per_gas: 0.into(),
+      }
+    );
+
+    rpc_test! (
+      ZkSync:all_account_balances, Address::from_low_u64_be(0x123)
+      =>
+      "zks_getAllAccountBalances", vec![r#""0x0000000000000000000000000000000000000123""#];
+      serde_json::json!({ "0x0000000000000000000000000000000000000000": "0x10" })
+      => {
+        let mut balances = BTreeMap::new();
+        balances.insert(Address::zero(), 16.into());
+        balances
+      }
+    );
+
+    rpc_test! (
+      ZkSync:bridge_contracts => "zks_getBridgeContracts";
+      serde_json::json!({
+        "l1Erc20DefaultBridge": "0x0000000000000000000000000000000000000001",
+        "l2Erc20DefaultBridge": "0x0000000000000000000000000000000000000002"
+      }) => BridgeContracts {
+        l1_erc20_default_bridge: Some(Address::from_low_u64_be(1)),
+        l2_erc20_default_bridge: Some(Address::from_low_u64_be(2)),
+        ..Default::default()
+      }
+    );
+
+    rpc_test! (
+      ZkSync:l2_to_l1_msg_proof, 8u32, Address::from_low_u64_be(0x123), H256::zero(), None::<u64>
+      =>
+      "zks_getL2ToL1MsgProof", vec![
+        "8",
+        r#""0x0000000000000000000000000000000000000123""#,
+        r#""0x0000000000000000000000000000000000000000000000000000000000000000""#
+      ];
+      serde_json::json!({
+        "id": 0,
+        "proof": ["0x66687aadf862bd776c8fc18b8e9f8e20089714856ee233b3902a591d0d5f2925"],
+        "root": "0x0000000000000000000000000000000000000000000000000000000000000001"
+      }) => Some(L2ToL1MessageProof {
+        id: 0,
+        proof: vec![H256(hex!("66687aadf862bd776c8fc18b8e9f8e20089714856ee233b3902a591d0d5f2925"))],
+        root: H256::from_low_u64_be(1),
+      })
+    );
+
+    rpc_test! (
+      ZkSync:l2_to_l1_msg_proof:l2_to_l1_msg_proof_with_position, 8u32, Address::from_low_u64_be(0x123), H256::zero(), Some(2u64)
+      =>
+      "zks_getL2ToL1MsgProof", vec![
+        "8",
+        r#""0x0000000000000000000000000000000000000123""#,
+        r#""0x0000000000000000000000000000000000000000000000000000000000000000""#,
+        "2"
+      ];
+      Value::Null => None
+    );
+}
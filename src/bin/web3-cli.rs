@@ -0,0 +1,439 @@
+//! Command line utility for common operations, built on the crate's APIs.
+//!
+//! Run `cargo run --features cli --bin web3-cli -- help` for usage.
+
+use futures::{Stream, StreamExt};
+use std::{collections::HashMap, fmt::Display, str::FromStr, time::Duration};
+use web3::{
+    api::{EthFilter, Namespace},
+    contract::Contract,
+    ethabi::{
+        self,
+        token::{LenientTokenizer, Tokenizer},
+        Token,
+    },
+    helpers::LogPager,
+    signing::{keyfile::KeyFile, SecretKey},
+    transports::{Either, Http, WebSocket},
+    types::{Address, BlockNumber, Bytes, CallRequest, FilterBuilder, TransactionParameters, TransactionRequest, U256},
+    Transport, Web3,
+};
+
+const USAGE: &str = "\
+Usage: web3-cli [--rpc <url>] <command> [arguments]
+
+The node URL defaults to `WEB3_RPC_URL` or http://localhost:8545, `ws://` and `wss://` URLs
+connect over WebSocket.
+
+Transactions are signed by the node for --from, or locally with the key in the file given by
+--key: either a keystore (decrypted with the password in `WEB3_KEY_PASSWORD`) or a hex encoded
+private key. Without --from and --key the hex encoded key in `WEB3_PRIVATE_KEY` is used.
+
+Commands:
+  block-number                          Print the latest block number
+  balance <address> [--block <n>]       Print the balance of an account
+  send --to <address> --value <wei> (--from <address> | --key <path>) [--confirmations <n>]
+                                        Send ether and wait for the receipt
+  call <address> <abi.json> <function> [args...] [--from <address>]
+                                        Call a constant function and print the outputs
+  deploy <abi.json> <code.hex> [args...] (--from <address> | --key <path>) [--confirmations <n>]
+                                        Deploy a contract and print its address
+  logs [--address <address>] [--from-block <n>] [--follow]
+                                        Print logs, with --follow keep printing new ones
+  subscribe <heads|pending|logs> [--address <address>]
+                                        Print notifications of a subscription (WebSocket only)
+";
+
+/// Hex encoded private key used when neither --from nor --key is given.
+const KEY_ENV: &str = "WEB3_PRIVATE_KEY";
+/// Password of the keystore given by --key.
+const PASSWORD_ENV: &str = "WEB3_KEY_PASSWORD";
+
+/// Options not taking a value.
+const FLAGS: &[&str] = &["follow"];
+
+type Error = Box<dyn std::error::Error>;
+type Result<T = ()> = std::result::Result<T, Error>;
+
+#[derive(Debug, Default, PartialEq)]
+struct Args {
+    positional: Vec<String>,
+    options: HashMap<String, String>,
+}
+
+impl Args {
+    fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self> {
+        let mut parsed = Args::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.strip_prefix("--") {
+                Some(name) if FLAGS.contains(&name) => {
+                    parsed.options.insert(name.into(), String::new());
+                }
+                Some(name) => {
+                    let value = args.next().ok_or_else(|| format!("Missing value of --{}", name))?;
+                    parsed.options.insert(name.into(), value);
+                }
+                None => parsed.positional.push(arg),
+            }
+        }
+        Ok(parsed)
+    }
+
+    fn positional(&self, index: usize, name: &str) -> Result<&str> {
+        self.positional
+            .get(index)
+            .map(String::as_str)
+            .ok_or_else(|| format!("Missing <{}>", name).into())
+    }
+
+    fn option<T>(&self, name: &str) -> Result<Option<T>>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.options.get(name).map(|value| parse(value, name)).transpose()
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.options.contains_key(name)
+    }
+}
+
+fn parse<T>(value: &str, name: &str) -> Result<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    value
+        .parse()
+        .map_err(|err| format!("Invalid {} {:?}: {}", name, value, err).into())
+}
+
+fn parse_block(value: &str) -> Result<BlockNumber> {
+    Ok(match value {
+        "latest" => BlockNumber::Latest,
+        "earliest" => BlockNumber::Earliest,
+        "pending" => BlockNumber::Pending,
+        "safe" => BlockNumber::Safe,
+        "finalized" => BlockNumber::Finalized,
+        number => BlockNumber::Number(parse::<u64>(number, "block")?.into()),
+    })
+}
+
+/// Parses function arguments according to the ABI.
+fn tokenize(inputs: &[ethabi::Param], args: &[String]) -> Result<Vec<Token>> {
+    if inputs.len() != args.len() {
+        return Err(format!("Expected {} arguments, got {}", inputs.len(), args.len()).into());
+    }
+    inputs
+        .iter()
+        .zip(args)
+        .map(|(param, arg)| {
+            LenientTokenizer::tokenize(&param.kind, arg)
+                .map_err(|err| format!("Invalid {} {:?}: {}", param.kind, arg, err).into())
+        })
+        .collect()
+}
+
+fn parse_key(value: &str, name: &str) -> Result<SecretKey> {
+    value
+        .trim()
+        .trim_start_matches("0x")
+        .parse()
+        .map_err(|err| format!("Invalid {}: {}", name, err).into())
+}
+
+/// Reads a private key from a keystore or a file with the hex encoded key.
+fn load_key(path: &str, password: Option<&str>) -> Result<SecretKey> {
+    let contents = std::fs::read_to_string(path).map_err(|err| format!("Cannot read {}: {}", path, err))?;
+    if !contents.trim_start().starts_with('{') {
+        return parse_key(&contents, "key file");
+    }
+    let password = password.ok_or_else(|| format!("{} is required to decrypt {}", PASSWORD_ENV, path))?;
+    let key = KeyFile::load(contents.as_bytes())?.decrypt(password.as_bytes())?;
+    Ok(*key)
+}
+
+/// Source of signatures: an account of the node or a local key.
+enum Signer {
+    Node(Address),
+    Local(SecretKey),
+}
+
+impl Signer {
+    fn from_args(args: &Args) -> Result<Self> {
+        if let Some(path) = args.options.get("key") {
+            let password = std::env::var(PASSWORD_ENV).ok();
+            return Ok(Signer::Local(load_key(path, password.as_deref())?));
+        }
+        if let Some(from) = args.option("from")? {
+            return Ok(Signer::Node(from));
+        }
+        match std::env::var(KEY_ENV) {
+            Ok(key) => Ok(Signer::Local(parse_key(&key, KEY_ENV)?)),
+            Err(_) => Err(format!("Either --from, --key or {} is required", KEY_ENV).into()),
+        }
+    }
+}
+
+fn print_json<S: serde::Serialize>(value: &S) -> Result {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+async fn print_stream<S, I>(stream: S) -> Result
+where
+    S: Stream<Item = web3::Result<I>>,
+    I: serde::Serialize,
+{
+    futures::pin_mut!(stream);
+    while let Some(item) = stream.next().await {
+        print_json(&item?)?;
+    }
+    Ok(())
+}
+
+async fn block_number<T: Transport>(web3: Web3<T>) -> Result {
+    println!("{}", web3.eth().block_number().await?);
+    Ok(())
+}
+
+async fn balance<T: Transport>(web3: Web3<T>, args: &Args) -> Result {
+    let address = parse(args.positional(1, "address")?, "address")?;
+    let block = args.options.get("block").map(|block| parse_block(block)).transpose()?;
+    println!("{}", web3.eth().balance(address, block).await?);
+    Ok(())
+}
+
+async fn send<T: Transport>(web3: Web3<T>, args: &Args) -> Result {
+    let to = args.option("to")?.ok_or("Missing --to")?;
+    let value: U256 = args.option("value")?.ok_or("Missing --value")?;
+    let confirmations = args.option("confirmations")?.unwrap_or(1);
+    let poll_interval = Duration::from_secs(1);
+    let receipt = match Signer::from_args(args)? {
+        Signer::Node(from) => {
+            let tx = TransactionRequest {
+                from,
+                to: Some(to),
+                value: Some(value),
+                ..Default::default()
+            };
+            web3.send_transaction_with_confirmation(tx, poll_interval, confirmations)
+                .await?
+        }
+        Signer::Local(key) => {
+            let tx = TransactionParameters {
+                to: Some(to),
+                value,
+                ..Default::default()
+            };
+            let signed = web3.accounts().sign_transaction(tx, &key).await?;
+            web3.send_raw_transaction_with_confirmation(signed.raw_transaction, poll_interval, confirmations)
+                .await?
+        }
+    };
+    print_json(&receipt)
+}
+
+async fn call<T: Transport>(web3: Web3<T>, args: &Args) -> Result {
+    let address = parse(args.positional(1, "address")?, "address")?;
+    let abi = ethabi::Contract::load(std::fs::read(args.positional(2, "abi.json")?)?.as_slice())?;
+    let function = abi.function(args.positional(3, "function")?)?;
+    let params = tokenize(&function.inputs, &args.positional[4..])?;
+    let request = CallRequest {
+        from: args.option("from")?,
+        to: Some(address),
+        data: Some(Bytes(function.encode_input(&params)?)),
+        ..Default::default()
+    };
+    let output = web3.eth().call(request, None).await?;
+    for token in function.decode_output(&output.0)? {
+        println!("{}", token);
+    }
+    Ok(())
+}
+
+async fn deploy<T: Transport>(web3: Web3<T>, args: &Args) -> Result {
+    let abi = std::fs::read(args.positional(1, "abi.json")?)?;
+    let code = std::fs::read_to_string(args.positional(2, "code.hex")?)?;
+    let inputs = ethabi::Contract::load(abi.as_slice())?
+        .constructor()
+        .map(|constructor| constructor.inputs.clone())
+        .unwrap_or_default();
+    let params = tokenize(&inputs, &args.positional[3..])?;
+    let builder = Contract::deploy(web3.eth(), &abi)?.confirmations(args.option("confirmations")?.unwrap_or(1));
+    let contract = match Signer::from_args(args)? {
+        Signer::Node(from) => builder.execute(code.trim(), &params[..], from).await?,
+        Signer::Local(key) => {
+            let chain_id = web3.eth().chain_id().await?.as_u64();
+            builder
+                .sign_with_key_and_execute(code.trim(), &params[..], &key, Some(chain_id))
+                .await?
+        }
+    };
+    println!("{:?}", contract.address());
+    Ok(())
+}
+
+async fn logs<T: Transport>(web3: Web3<T>, args: &Args) -> Result {
+    let mut filter = FilterBuilder::default();
+    if let Some(address) = args.option("address")? {
+        filter = filter.address(vec![address]);
+    }
+    let latest = web3.eth().block_number().await?;
+    let from_block = match args.options.get("from-block") {
+        Some(block) => parse_block(block)?,
+        None => BlockNumber::Number(latest),
+    };
+    let history = filter
+        .clone()
+        .from_block(from_block)
+        .to_block(BlockNumber::Number(latest))
+        .build();
+    print_stream(LogPager::new(web3.eth(), history).stream()).await?;
+
+    if args.flag("follow") {
+        let filter = filter.from_block(BlockNumber::Number(latest + 1)).build();
+        let filter = EthFilter::new(web3.transport().clone())
+            .create_logs_filter(filter)
+            .await?;
+        print_stream(filter.stream(Duration::from_secs(1))).await?;
+    }
+    Ok(())
+}
+
+async fn subscribe(web3: Web3<WebSocket>, args: &Args) -> Result {
+    let subscribe = web3.eth_subscribe();
+    match args.positional(1, "kind")? {
+        "heads" => print_stream(subscribe.subscribe_new_heads().await?).await,
+        "pending" => print_stream(subscribe.subscribe_new_pending_transactions().await?).await,
+        "logs" => {
+            let mut filter = FilterBuilder::default();
+            if let Some(address) = args.option("address")? {
+                filter = filter.address(vec![address]);
+            }
+            print_stream(subscribe.subscribe_logs(filter.build()).await?).await
+        }
+        kind => Err(format!("Unknown subscription {:?}, expected heads, pending or logs", kind).into()),
+    }
+}
+
+async fn run<T: Transport>(web3: Web3<T>, args: &Args) -> Result {
+    match args.positional(0, "command")? {
+        "block-number" => block_number(web3).await,
+        "balance" => balance(web3, args).await,
+        "send" => send(web3, args).await,
+        "call" => call(web3, args).await,
+        "deploy" => deploy(web3, args).await,
+        "logs" => logs(web3, args).await,
+        command => Err(format!("Unknown command {:?}\n\n{}", command, USAGE).into()),
+    }
+}
+
+async fn main_inner() -> Result {
+    let args = Args::parse(std::env::args().skip(1))?;
+    if args.positional.is_empty() || args.positional[0] == "help" {
+        print!("{}", USAGE);
+        return Ok(());
+    }
+    let url = match args.options.get("rpc") {
+        Some(url) => url.clone(),
+        None => std::env::var("WEB3_RPC_URL").unwrap_or_else(|_| "http://localhost:8545".into()),
+    };
+    let websocket = url.starts_with("ws://") || url.starts_with("wss://");
+
+    if args.positional[0] == "subscribe" {
+        if !websocket {
+            return Err("subscribe requires a ws:// or wss:// URL".into());
+        }
+        return subscribe(Web3::new(WebSocket::new(&url).await?), &args).await;
+    }
+    let transport = if websocket {
+        Either::Right(WebSocket::new(&url).await?)
+    } else {
+        Either::Left(Http::new(&url)?)
+    };
+    run(Web3::new(transport), &args).await
+}
+
+#[tokio::main]
+async fn main() {
+    if let Err(err) = main_inner().await {
+        eprintln!("Error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Result<Args> {
+        Args::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn should_parse_arguments() {
+        let parsed = args(&["--rpc", "ws://node", "logs", "--follow", "--from-block", "10"]).unwrap();
+
+        assert_eq!(parsed.positional, vec!["logs"]);
+        assert_eq!(parsed.options["rpc"], "ws://node");
+        assert!(parsed.flag("follow"));
+        assert_eq!(parsed.option::<u64>("from-block").unwrap(), Some(10));
+        assert!(parsed.option::<u64>("rpc").is_err());
+        assert!(args(&["balance", "--block"]).is_err());
+    }
+
+    #[test]
+    fn should_tokenize_function_arguments() {
+        let abi = ethabi::Contract::load(
+            br#"[{"type":"function","name":"f","inputs":[{"name":"a","type":"address"},{"name":"b","type":"uint256"}],"outputs":[]}]"#
+                .as_ref(),
+        )
+        .unwrap();
+        let inputs = &abi.function("f").unwrap().inputs;
+
+        let tokens = tokenize(
+            inputs,
+            &["0x0000000000000000000000000000000000000001".into(), "1000".into()],
+        )
+        .unwrap();
+
+        assert_eq!(
+            tokens,
+            vec![Token::Address(Address::from_low_u64_be(1)), Token::Uint(1000.into())]
+        );
+        assert!(tokenize(inputs, &["1".into()]).is_err());
+    }
+
+    #[test]
+    fn should_load_key_from_file() {
+        let key: SecretKey = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+            .parse()
+            .unwrap();
+        let dir = std::env::temp_dir();
+        let hex_path = dir.join(format!("web3-cli-key-{}.hex", std::process::id()));
+        let keystore_path = dir.join(format!("web3-cli-key-{}.json", std::process::id()));
+        std::fs::write(
+            &hex_path,
+            "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318\n",
+        )
+        .unwrap();
+        let keyfile = KeyFile::encrypt(&key, b"secret", web3::signing::keyfile::Kdf::pbkdf2(2).unwrap()).unwrap();
+        std::fs::write(&keystore_path, serde_json::to_vec(&keyfile).unwrap()).unwrap();
+
+        let from_hex = load_key(hex_path.to_str().unwrap(), None);
+        let from_keystore = load_key(keystore_path.to_str().unwrap(), Some("secret"));
+        let without_password = load_key(keystore_path.to_str().unwrap(), None);
+        let wrong_password = load_key(keystore_path.to_str().unwrap(), Some("wrong"));
+        std::fs::remove_file(&hex_path).unwrap();
+        std::fs::remove_file(&keystore_path).unwrap();
+
+        assert_eq!(from_hex.unwrap(), key);
+        assert_eq!(from_keystore.unwrap(), key);
+        assert!(without_password.is_err());
+        assert!(wrong_password.is_err());
+        assert!(load_key("/nonexistent/web3-cli-key", None).is_err());
+    }
+}
@@ -0,0 +1,251 @@
+//! `Web3` connected to an endpoint given as a string, e.g. from configuration.
+
+use crate::{
+    api::{Web3, Web3Builder},
+    error::Result,
+    helpers::backoff::Backoff,
+    transports::{
+        env::{Endpoint, EnvTransport},
+        AuthProvider, Either, Http, IdStrategy, Ipc, NotificationBuffer, Retry, WebSocket,
+    },
+};
+use std::{sync::Arc, time::Duration};
+
+/// Transport of `Web3` built by [`Builder`].
+pub type BuilderTransport = Retry<EnvTransport>;
+
+/// Builder of `Web3` picking the transport by the scheme of the endpoint URL.
+///
+/// `http(s)://` URLs use [`Http`], `ws(s)://` URLs [`WebSocket`], and `ipc://` URLs or plain paths
+/// [`Ipc`]. Options not applicable to the picked transport are ignored. Options of `Web3` itself
+/// are set on the [`Web3Builder`] returned by `connect`.
+///
+/// ```no_run
+/// # async fn example(endpoint: &str) -> web3::Result<()> {
+/// use std::time::Duration;
+///
+/// let web3 = web3::Builder::new(endpoint)
+///     .timeout(Duration::from_secs(10))
+///     .retry(3, Duration::from_millis(500))
+///     .connect()
+///     .await?
+///     .shape_requests(true)
+///     .build()
+///     .await?;
+/// let block_number = web3.eth().block_number().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Builder {
+    url: String,
+    auth: Option<Arc<dyn AuthProvider>>,
+    id_strategy: IdStrategy,
+    notification_buffer: NotificationBuffer,
+    reconnect: Option<Backoff>,
+    timeout: Option<Duration>,
+    max_retries: usize,
+    retry_backoff: Backoff,
+}
+
+impl std::fmt::Debug for Builder {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Builder")
+            .field("url", &self.url)
+            .field("auth", &self.auth.is_some())
+            .field("id_strategy", &self.id_strategy)
+            .field("notification_buffer", &self.notification_buffer)
+            .field("reconnect", &self.reconnect)
+            .field("timeout", &self.timeout)
+            .field("max_retries", &self.max_retries)
+            .field("retry_backoff", &self.retry_backoff)
+            .finish()
+    }
+}
+
+impl Builder {
+    /// Creates a builder connecting to given endpoint.
+    pub fn new(url: &str) -> Self {
+        Builder {
+            url: url.into(),
+            auth: None,
+            id_strategy: Default::default(),
+            notification_buffer: Default::default(),
+            reconnect: None,
+            timeout: None,
+            max_retries: 0,
+            retry_backoff: Default::default(),
+        }
+    }
+
+    /// Sends a bearer token of the provider with HTTP requests and the WebSocket handshake.
+    ///
+    /// See `Http::with_auth_provider` and `WebSocket::with_auth_provider`.
+    pub fn auth<A: AuthProvider + 'static>(mut self, provider: A) -> Self {
+        self.auth = Some(Arc::new(provider));
+        self
+    }
+
+    /// Sets how JSON-RPC ids of HTTP and WebSocket requests are generated.
+    pub fn id_strategy(mut self, strategy: IdStrategy) -> Self {
+        self.id_strategy = strategy;
+        self
+    }
+
+    /// Sets how notifications of WebSocket and IPC subscriptions are buffered.
+    pub fn notification_buffer(mut self, buffer: NotificationBuffer) -> Self {
+        self.notification_buffer = buffer;
+        self
+    }
+
+    /// Reconnects the WebSocket and IPC transports with given backoff when the connection is lost.
+    ///
+    /// See `WebSocket::with_reconnect` and `Ipc::with_reconnect`.
+    pub fn reconnect<B: Into<Backoff>>(mut self, backoff: B) -> Self {
+        self.reconnect = Some(backoff.into());
+        self
+    }
+
+    /// Fails requests not completed within `timeout` with `TransportError::Timeout`.
+    ///
+    /// See `Http::with_timeout`, `WebSocket::with_timeout` and `Ipc::with_timeout`.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Retries requests failed by the endpoint (including timeouts) up to `max_retries` times,
+    /// waiting according to `backoff` in between (no retries by default).
+    ///
+    /// See [`Retry`].
+    pub fn retry<B: Into<Backoff>>(mut self, max_retries: usize, backoff: B) -> Self {
+        self.max_retries = max_retries;
+        self.retry_backoff = backoff.into();
+        self
+    }
+
+    /// Connects to the endpoint, returns a builder of `Web3` using the connection.
+    ///
+    /// Fails if the URL has an unsupported scheme or the connection can't be established.
+    pub async fn connect(self) -> Result<Web3Builder<BuilderTransport>> {
+        let transport = match Endpoint::parse(&self.url)? {
+            Endpoint::Http(url) => {
+                let mut http = Http::new(&url)?.with_id_strategy(self.id_strategy);
+                if let Some(auth) = self.auth {
+                    http = http.with_auth_provider(move || auth.token());
+                }
+                if let Some(timeout) = self.timeout {
+                    http = http.with_timeout(timeout);
+                }
+                Either::Left(http)
+            }
+            Endpoint::Ws(url) => {
+                let mut ws = WebSocket::connect(&url, self.auth, self.reconnect).await?;
+                if let Some(timeout) = self.timeout {
+                    ws = ws.with_timeout(timeout);
+                }
+                Either::Right(Either::Left(
+                    ws.with_id_strategy(self.id_strategy)
+                        .with_notification_buffer(self.notification_buffer),
+                ))
+            }
+            Endpoint::Ipc(path) => {
+                let mut ipc = match self.reconnect {
+                    Some(backoff) => Ipc::with_reconnect(path, backoff).await?,
+                    None => Ipc::new(path).await?,
+                };
+                if let Some(timeout) = self.timeout {
+                    ipc = ipc.with_timeout(timeout);
+                }
+                Either::Right(Either::Right(ipc.with_notification_buffer(self.notification_buffer)))
+            }
+        };
+        Ok(Web3::builder(Retry::new(
+            transport,
+            self.max_retries,
+            self.retry_backoff,
+        )))
+    }
+
+    /// Connects to the endpoint and builds `Web3` with the default options of [`Web3Builder`].
+    ///
+    /// Fails if the URL has an unsupported scheme, the connection can't be established, or the
+    /// endpoint is not an Ethereum one.
+    pub async fn build(self) -> Result<Web3<BuilderTransport>> {
+        self.connect().await?.build().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+
+    fn build(builder: Builder) -> Result<Web3<BuilderTransport>> {
+        futures::executor::block_on(async { builder.connect().await?.check_endpoint(false).build().await })
+    }
+
+    #[test]
+    fn should_pick_transport_by_scheme() {
+        let web3 = build(Builder::new("https://localhost:8545").auth(|| Ok("token".to_owned()))).unwrap();
+        assert!(matches!(web3.transport().inner(), Either::Left(_)));
+
+        let result = build(Builder::new("ftp://localhost"));
+        assert!(matches!(result, Err(Error::Transport(_))));
+    }
+
+    #[tokio::test]
+    async fn should_connect_to_websocket_urls() {
+        use crate::transports::ws::compat;
+        use futures::io::{BufReader, BufWriter};
+        use soketto::handshake;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut server = handshake::Server::new(BufReader::new(BufWriter::new(compat::compat(socket))));
+            let key = server.receive_request().await.unwrap().key();
+            let accept = handshake::server::Response::Accept { key, protocol: None };
+            server.send_response(&accept).await.unwrap();
+            let (_sender, mut receiver) = server.into_builder().finish();
+            let mut data = Vec::new();
+            while receiver.receive_data(&mut data).await.is_ok() {}
+        });
+
+        let web3 = Builder::new(&url)
+            .timeout(Duration::from_secs(1))
+            .connect()
+            .await
+            .unwrap()
+            .check_endpoint(false)
+            .build()
+            .await
+            .unwrap();
+
+        assert!(matches!(web3.transport().inner(), Either::Right(Either::Left(_))));
+    }
+
+    #[tokio::test]
+    async fn should_connect_to_ipc_urls_and_paths() {
+        let path = std::env::temp_dir().join(format!("web3-builder-{}.ipc", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let _listener = tokio::net::UnixListener::bind(&path).unwrap();
+
+        for url in [format!("ipc://{}", path.display()), path.display().to_string()].iter() {
+            let web3 = Builder::new(url)
+                .timeout(Duration::from_secs(1))
+                .retry(1, Duration::from_millis(0))
+                .connect()
+                .await
+                .unwrap()
+                .check_endpoint(false)
+                .build()
+                .await
+                .unwrap();
+
+            assert!(matches!(web3.transport().inner(), Either::Right(Either::Right(_))));
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,13 +1,21 @@
 //! Easy to use utilities for confirmations.
 
 use crate::{
-    api::{Eth, EthFilter, Namespace},
+    api::{Eth, EthFilter, EthSubscribe, Namespace},
     error,
-    types::{Bytes, TransactionReceipt, TransactionRequest, H256, U64},
-    Transport,
+    helpers::{backoff::Backoff, poll_stream, poll_until, CancellationToken, PollPolicy},
+    types::{
+        Block, BlockHeader, BlockId, BlockNumber, BlockRef, Bytes, ChainCheckpoint, Transaction, TransactionId,
+        TransactionReceipt, TransactionRequest, H256, U64,
+    },
+    DuplexTransport, Transport,
 };
-use futures::{Future, StreamExt};
-use std::time::Duration;
+use futures::{
+    future::{self, Either},
+    Future, Stream, StreamExt, TryStreamExt,
+};
+use futures_timer::Delay;
+use std::{cell::Cell, collections::VecDeque, time::Duration};
 
 /// Checks whether an event has been confirmed.
 pub trait ConfirmationCheck {
@@ -30,11 +38,21 @@ where
     }
 }
 
+/// Number of consecutive polling errors tolerated at least while waiting for confirmations.
+const CONFIRMATION_MAX_ERRORS: usize = 3;
+
+/// Maximal number of `eth_getTransactionReceipt` requests in flight while waiting for receipts.
+const MAX_CONCURRENT_RECEIPTS: usize = 16;
+
 /// Should be used to wait for confirmations
-pub async fn wait_for_confirmations<T, V, F>(
+///
+/// The block filter is polled according to `policy` (e.g. a `Duration` for a fixed interval),
+/// failing once it times out or the node returns more than `policy.max_errors` errors in a row.
+/// At least 3 consecutive errors are tolerated, so that a transient failure doesn't abort the wait.
+pub async fn wait_for_confirmations<T, V, F, P>(
     eth: Eth<T>,
     eth_filter: EthFilter<T>,
-    poll_interval: Duration,
+    policy: P,
     confirmations: usize,
     check: V,
 ) -> error::Result<()>
@@ -42,25 +60,111 @@ where
     T: Transport,
     V: ConfirmationCheck<Check = F>,
     F: Future<Output = error::Result<Option<U64>>>,
+    P: Into<PollPolicy>,
 {
+    wait_for_confirmations_with_cancel(eth, eth_filter, policy, confirmations, check, CancellationToken::new()).await
+}
+
+/// Should be used to wait for confirmations, giving up with `Error::Cancelled` once `cancel` is triggered.
+///
+/// The block filter used for polling is uninstalled when the wait ends, regardless of the outcome.
+pub async fn wait_for_confirmations_with_cancel<T, V, F, P>(
+    eth: Eth<T>,
+    eth_filter: EthFilter<T>,
+    policy: P,
+    confirmations: usize,
+    check: V,
+    cancel: CancellationToken,
+) -> error::Result<()>
+where
+    T: Transport,
+    V: ConfirmationCheck<Check = F>,
+    F: Future<Output = error::Result<Option<U64>>>,
+    P: Into<PollPolicy>,
+{
+    let mut policy = policy.into();
+    policy.max_errors = policy.max_errors.max(CONFIRMATION_MAX_ERRORS);
     let filter = eth_filter.create_blocks_filter().await?;
-    // TODO #396: The stream should have additional checks.
-    // * We should not continue calling next on a stream that has completed (has returned None). We expect this to never
-    //   happen for the blocks filter but to be safe we should handle this case for example by `fuse`ing the stream or
-    //   erroring when it does complete.
-    // * We do not handle the case where the stream returns an error which means we are wrongly counting it as a
-    //   confirmation.
-    let filter_stream = filter.stream(poll_interval).skip(confirmations);
-    futures::pin_mut!(filter_stream);
-    loop {
-        let _ = filter_stream.next().await;
+    let blocks_seen = Cell::new(0);
+    let result = {
+        let (eth, filter, check, blocks_seen) = (&eth, &filter, &check, &blocks_seen);
+        let wait = poll_until(
+            move || async move {
+                let blocks = filter.poll().await?.unwrap_or_default();
+                blocks_seen.set(blocks_seen.get() + blocks.len());
+                // no point in checking before enough blocks arrived
+                if blocks.is_empty() || blocks_seen.get() <= confirmations {
+                    return Ok(false);
+                }
+                match check.check().await? {
+                    Some(confirmation_block_number) => {
+                        let block_number = eth.block_number().await?;
+                        Ok(confirmation_block_number.low_u64() + confirmations as u64 <= block_number.low_u64())
+                    }
+                    None => Ok(false),
+                }
+            },
+            |confirmed| *confirmed,
+            policy,
+        );
+        futures::pin_mut!(wait);
+        match future::select(cancel.cancelled(), wait).await {
+            Either::Left(_) => Err(error::Error::Cancelled),
+            Either::Right((result, _)) => result.map(|_| ()),
+        }
+    };
+    // best effort, the filter would expire on the node eventually anyway
+    let _ = filter.uninstall().await;
+    result
+}
+
+/// Should be used to wait for confirmations on duplex transports.
+///
+/// Reacts to blocks pushed by a `newHeads` subscription instead of polling a block filter, falls
+/// back to polling according to `policy` if the node rejects the subscription.
+pub async fn wait_for_confirmations_subscribed<T, V, F, P>(
+    transport: T,
+    policy: P,
+    confirmations: usize,
+    check: V,
+) -> error::Result<()>
+where
+    T: DuplexTransport,
+    V: ConfirmationCheck<Check = F>,
+    F: Future<Output = error::Result<Option<U64>>>,
+    P: Into<PollPolicy>,
+{
+    let eth = Eth::new(transport.clone());
+    let heads = match EthSubscribe::new(transport.clone()).subscribe_new_heads().await {
+        Ok(heads) => heads,
+        Err(err) => {
+            event!(
+                debug,
+                "newHeads subscription failed ({}), polling for blocks instead",
+                err
+            );
+            let eth_filter = EthFilter::new(transport);
+            return wait_for_confirmations(eth, eth_filter, policy, confirmations, check).await;
+        }
+    };
+    // same as with polling, no point in checking before enough blocks arrived
+    let heads = heads.skip(confirmations);
+    futures::pin_mut!(heads);
+    while let Some(head) = heads.next().await {
+        let head = head?;
         if let Some(confirmation_block_number) = check.check().await? {
-            let block_number = eth.block_number().await?;
+            let block_number = match head.number {
+                Some(number) => number,
+                None => eth.block_number().await?,
+            };
             if confirmation_block_number.low_u64() + confirmations as u64 <= block_number.low_u64() {
                 return Ok(());
             }
         }
     }
+    Err(error::Error::Transport(error::TransportError::Message(
+        "newHeads subscription ended".into(),
+    )))
 }
 
 async fn transaction_receipt_block_number_check<T: Transport>(eth: &Eth<T>, hash: H256) -> error::Result<Option<U64>> {
@@ -71,16 +175,23 @@ async fn transaction_receipt_block_number_check<T: Transport>(eth: &Eth<T>, hash
 async fn send_transaction_with_confirmation_<T: Transport>(
     hash: H256,
     transport: T,
-    poll_interval: Duration,
+    policy: PollPolicy,
     confirmations: usize,
 ) -> error::Result<TransactionReceipt> {
     let eth = Eth::new(transport.clone());
-    if confirmations > 0 {
-        let confirmation_check = || transaction_receipt_block_number_check(&eth, hash);
-        let eth_filter = EthFilter::new(transport.clone());
-        let eth = eth.clone();
-        wait_for_confirmations(eth, eth_filter, poll_interval, confirmations, confirmation_check).await?;
+    // fast path for chains mining instantly (e.g. auto-mining dev nodes), skips the first poll interval
+    if let Some(receipt) = eth.transaction_receipt(hash).await? {
+        if let Some(block_number) = receipt.block_number {
+            if confirmations == 0
+                || block_number.low_u64() + confirmations as u64 <= eth.block_number().await?.low_u64()
+            {
+                return Ok(receipt);
+            }
+        }
     }
+    let confirmation_check = || transaction_receipt_block_number_check(&eth, hash);
+    let eth_filter = EthFilter::new(transport);
+    wait_for_confirmations(eth.clone(), eth_filter, policy, confirmations, confirmation_check).await?;
     // TODO #397: We should remove this `expect`. No matter what happens inside the node, this shouldn't be a panic.
     let receipt = eth
         .transaction_receipt(hash)
@@ -90,41 +201,500 @@ async fn send_transaction_with_confirmation_<T: Transport>(
     Ok(receipt)
 }
 
+/// Transaction sent to the node, awaiting confirmations.
+///
+/// The hash is known right away, e.g. to be persisted before waiting with
+/// [`wait`](PendingTransaction::wait).
+#[derive(Debug, Clone)]
+pub struct PendingTransaction<T: Transport> {
+    transport: T,
+    hash: H256,
+    policy: PollPolicy,
+    confirmations: usize,
+}
+
+impl<T: Transport> PendingTransaction<T> {
+    /// Creates a handle of a sent transaction, polling for `confirmations` every second.
+    pub fn new(transport: T, hash: H256, confirmations: usize) -> Self {
+        PendingTransaction {
+            transport,
+            hash,
+            policy: PollPolicy::default(),
+            confirmations,
+        }
+    }
+
+    /// Hash of the transaction.
+    pub fn hash(&self) -> H256 {
+        self.hash
+    }
+
+    /// Sets the policy of polling for new blocks, e.g. a `Duration` for a fixed interval.
+    pub fn policy<P: Into<PollPolicy>>(mut self, policy: P) -> Self {
+        self.policy = policy.into();
+        self
+    }
+
+    /// Sets the number of confirmations to wait for.
+    pub fn confirmations(mut self, confirmations: usize) -> Self {
+        self.confirmations = confirmations;
+        self
+    }
+
+    /// Waits for the confirmations and returns the receipt.
+    pub async fn wait(self) -> error::Result<TransactionReceipt> {
+        send_transaction_with_confirmation_(self.hash, self.transport, self.policy, self.confirmations).await
+    }
+}
+
 /// Sends transaction and returns future resolved after transaction is confirmed
-pub async fn send_transaction_with_confirmation<T>(
+///
+/// With `confirmations = 0` the future resolves as soon as the receipt exists. The receipt is
+/// checked right away, so instantly mined transactions don't wait for the first poll.
+pub async fn send_transaction_with_confirmation<T, P>(
     transport: T,
     tx: TransactionRequest,
-    poll_interval: Duration,
+    policy: P,
     confirmations: usize,
 ) -> error::Result<TransactionReceipt>
 where
     T: Transport,
+    P: Into<PollPolicy>,
 {
     let hash = Eth::new(&transport).send_transaction(tx).await?;
-    send_transaction_with_confirmation_(hash, transport, poll_interval, confirmations).await
+    send_transaction_with_confirmation_(hash, transport, policy.into(), confirmations).await
 }
 
 /// Sends raw transaction and returns future resolved after transaction is confirmed
-pub async fn send_raw_transaction_with_confirmation<T>(
+///
+/// See `send_transaction_with_confirmation` for the handling of `confirmations`.
+pub async fn send_raw_transaction_with_confirmation<T, P>(
     transport: T,
     tx: Bytes,
-    poll_interval: Duration,
+    policy: P,
     confirmations: usize,
 ) -> error::Result<TransactionReceipt>
 where
     T: Transport,
+    P: Into<PollPolicy>,
 {
     let hash = Eth::new(&transport).send_raw_transaction(tx).await?;
-    send_transaction_with_confirmation_(hash, transport, poll_interval, confirmations).await
+    send_transaction_with_confirmation_(hash, transport, policy.into(), confirmations).await
+}
+
+/// Sends raw transaction and returns future resolved once the node returns it by hash.
+///
+/// Load balanced providers may route the follow-up request to a node the transaction hasn't
+/// propagated to yet. The transaction is polled for up to `hash_visibility_timeout`, after which
+/// the future fails with a `Transport` error mentioning the hash (the transaction is already sent).
+pub async fn submit_and_get<T>(transport: T, tx: Bytes, hash_visibility_timeout: Duration) -> error::Result<Transaction>
+where
+    T: Transport,
+{
+    let eth = Eth::new(transport);
+    let hash = eth.send_raw_transaction(tx).await?;
+    let backoff =
+        Backoff::exponential(Duration::from_millis(50), Duration::from_secs(1)).max_elapsed(hash_visibility_timeout);
+    let attempts = poll_stream(|| eth.transaction(TransactionId::Hash(hash)), backoff);
+    futures::pin_mut!(attempts);
+    while let Some(transaction) = attempts.next().await {
+        if let Some(transaction) = transaction? {
+            return Ok(transaction);
+        }
+    }
+    Err(error::Error::Transport(error::TransportError::Message(format!(
+        "transaction {:?} not visible after {:?}",
+        hash, hash_visibility_timeout
+    ))))
+}
+
+/// Progress reported by `wait_for_receipts`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReceiptEvent {
+    /// Transaction is mined with enough confirmations.
+    Confirmed(Box<TransactionReceipt>),
+    /// All transactions are confirmed, receipts are in the order of the hashes. Last event of the stream.
+    Done(Vec<TransactionReceipt>),
+}
+
+/// Waits for receipts of many transactions at once.
+///
+/// A single block filter is shared by all transactions, receipts of the pending ones are fetched
+/// when new blocks arrive (with up to 16 requests in flight). Each transaction is confirmed once `confirmations` blocks are mined on
+/// top of its block. The filter is polled according to `policy`.
+///
+/// The stream ends after `ReceiptEvent::Done` or the first error.
+/// The filter is uninstalled when the stream is polled past `Done`.
+pub async fn wait_for_receipts<T, P>(
+    transport: T,
+    hashes: Vec<H256>,
+    confirmations: usize,
+    policy: P,
+) -> error::Result<impl Stream<Item = error::Result<ReceiptEvent>>>
+where
+    T: Transport,
+    P: Into<PollPolicy>,
+{
+    let filter = EthFilter::new(transport.clone()).create_blocks_filter().await?;
+    let poller = filter.clone();
+    let ticks = poll_stream(
+        move || {
+            let filter = poller.clone();
+            async move { filter.poll().await }
+        },
+        policy,
+    )
+    .filter_map(|blocks| {
+        future::ready(match blocks {
+            Ok(Some(blocks)) if !blocks.is_empty() => Some(Ok(None)),
+            Ok(_) => None,
+            Err(err) => Some(Err(err)),
+        })
+    });
+    let events = track_receipts(Eth::new(transport), hashes, confirmations, ticks);
+    let uninstall = futures::stream::once(async move {
+        // best effort, the filter would expire on the node eventually anyway
+        let _ = filter.uninstall().await;
+    })
+    .filter_map(|_| future::ready(None));
+    Ok(events.chain(uninstall))
+}
+
+/// Waits for receipts of many transactions at once on duplex transports.
+///
+/// Same as `wait_for_receipts`, but reacts to blocks pushed by a single `newHeads` subscription.
+/// Falls back to polling if the node rejects the subscription.
+pub async fn wait_for_receipts_subscribed<T, P>(
+    transport: T,
+    hashes: Vec<H256>,
+    confirmations: usize,
+    policy: P,
+) -> error::Result<impl Stream<Item = error::Result<ReceiptEvent>>>
+where
+    T: DuplexTransport,
+    P: Into<PollPolicy>,
+{
+    match EthSubscribe::new(transport.clone()).subscribe_new_heads().await {
+        Ok(heads) => {
+            let ticks = heads.map(|head| head.map(|head| head.number));
+            let events = track_receipts(Eth::new(transport), hashes, confirmations, ticks);
+            Ok(Either::Left(events))
+        }
+        Err(err) => {
+            event!(
+                debug,
+                "newHeads subscription failed ({}), polling for blocks instead",
+                err
+            );
+            Ok(Either::Right(
+                wait_for_receipts(transport, hashes, confirmations, policy).await?,
+            ))
+        }
+    }
+}
+
+struct ReceiptTracker<T: Transport> {
+    eth: Eth<T>,
+    confirmations: usize,
+    pending: Vec<(usize, H256)>,
+    receipts: Vec<Option<TransactionReceipt>>,
+    events: VecDeque<ReceiptEvent>,
+    finished: bool,
+}
+
+impl<T: Transport> ReceiptTracker<T> {
+    /// Fetches receipts of pending transactions given the number of the latest block, if known.
+    async fn check(&mut self, head: Option<U64>) -> error::Result<()> {
+        if !self.pending.is_empty() {
+            let head = match head {
+                Some(head) => head,
+                None if self.confirmations > 0 => self.eth.block_number().await?,
+                None => U64::max_value(),
+            };
+            let eth = &self.eth;
+            let receipts: Vec<_> =
+                futures::stream::iter(self.pending.iter().map(|&(_, hash)| eth.transaction_receipt(hash)))
+                    .buffered(MAX_CONCURRENT_RECEIPTS)
+                    .try_collect()
+                    .await?;
+            let confirmations = self.confirmations as u64;
+            let mut pending = Vec::with_capacity(self.pending.len());
+            for (&(index, hash), receipt) in self.pending.iter().zip(receipts) {
+                match receipt {
+                    Some(receipt) if matches!(receipt.block_number, Some(number) if number.low_u64().saturating_add(confirmations) <= head.low_u64()) =>
+                    {
+                        self.events
+                            .push_back(ReceiptEvent::Confirmed(Box::new(receipt.clone())));
+                        self.receipts[index] = Some(receipt);
+                    }
+                    _ => pending.push((index, hash)),
+                }
+            }
+            self.pending = pending;
+        }
+        if self.pending.is_empty() {
+            let receipts = self.receipts.drain(..).flatten().collect();
+            self.events.push_back(ReceiptEvent::Done(receipts));
+            self.finished = true;
+        }
+        Ok(())
+    }
+}
+
+fn track_receipts<T, S>(
+    eth: Eth<T>,
+    hashes: Vec<H256>,
+    confirmations: usize,
+    ticks: S,
+) -> impl Stream<Item = error::Result<ReceiptEvent>>
+where
+    T: Transport,
+    S: Stream<Item = error::Result<Option<U64>>>,
+{
+    let tracker = ReceiptTracker {
+        eth,
+        confirmations,
+        receipts: vec![None; hashes.len()],
+        pending: hashes.into_iter().enumerate().collect(),
+        events: VecDeque::new(),
+        finished: false,
+    };
+    // transactions might be mined already
+    let ticks = futures::stream::once(future::ready(Ok(None))).chain(ticks);
+    futures::stream::unfold((tracker, Box::pin(ticks)), |(mut tracker, mut ticks)| async move {
+        loop {
+            if let Some(event) = tracker.events.pop_front() {
+                return Some((Ok(event), (tracker, ticks)));
+            }
+            if tracker.finished {
+                return None;
+            }
+            let result = match ticks.next().await {
+                Some(Ok(head)) => tracker.check(head).await,
+                Some(Err(err)) => Err(err),
+                None => Err(error::Error::Transport(error::TransportError::Message(
+                    "stream of new blocks ended".into(),
+                ))),
+            };
+            if let Err(err) = result {
+                tracker.finished = true;
+                return Some((Err(err), (tracker, ticks)));
+            }
+        }
+    })
+}
+
+/// Reorganization of the canonical chain detected by `ReorgWatcher`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reorg {
+    /// Hashes of blocks removed from the canonical chain, oldest first.
+    pub old_branch: Vec<H256>,
+    /// Hashes of blocks which replaced them, oldest first.
+    pub new_branch: Vec<H256>,
+    /// Number of removed blocks.
+    pub depth: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Head {
+    number: u64,
+    hash: H256,
+    parent_hash: H256,
+}
+
+impl Head {
+    fn new(number: Option<U64>, hash: Option<H256>, parent_hash: H256) -> Option<Self> {
+        Some(Head {
+            number: number?.as_u64(),
+            hash: hash?,
+            parent_hash,
+        })
+    }
+
+    fn from_block<TX>(block: Block<TX>) -> Option<Self> {
+        Head::new(block.number, block.hash, block.parent_hash)
+    }
+}
+
+/// Tracks the canonical chain and detects reorganizations.
+///
+/// The watcher remembers the last `max_depth` canonical blocks. When a new head doesn't
+/// extend the tracked chain, its ancestors are fetched with `eth_getBlockByHash` until
+/// a tracked block is found and the replaced blocks are reported as a `Reorg`.
+/// For reorganizations deeper than `max_depth` all tracked blocks are reported as removed.
+#[derive(Debug, Clone)]
+pub struct ReorgWatcher<T: Transport> {
+    eth: Eth<T>,
+    max_depth: usize,
+    chain: VecDeque<(u64, H256)>,
+}
+
+impl<T: Transport> ReorgWatcher<T> {
+    /// Creates a new watcher tracking up to 64 blocks.
+    pub fn new(eth: Eth<T>) -> Self {
+        ReorgWatcher {
+            eth,
+            max_depth: 64,
+            chain: VecDeque::new(),
+        }
+    }
+
+    /// Creates a watcher tracking the blocks of a checkpoint, e.g. after a restart.
+    pub fn resume_from(eth: Eth<T>, checkpoint: ChainCheckpoint) -> Self {
+        let mut watcher = Self::new(eth);
+        watcher.max_depth = watcher.max_depth.max(checkpoint.blocks.len());
+        watcher.chain = checkpoint
+            .blocks
+            .into_iter()
+            .map(|block| (block.number, block.hash))
+            .collect();
+        watcher
+    }
+
+    /// Returns a checkpoint of the tracked blocks, to be persisted for `resume_from`.
+    pub fn checkpoint(&self) -> ChainCheckpoint {
+        ChainCheckpoint::new(
+            self.chain
+                .iter()
+                .map(|&(number, hash)| BlockRef { number, hash })
+                .collect(),
+        )
+    }
+
+    /// Sets the number of tracked blocks, which is the deepest reorganization reported precisely.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth.max(1);
+        self
+    }
+
+    /// Returns the hash of the latest tracked canonical block.
+    pub fn head(&self) -> Option<H256> {
+        self.chain.back().map(|(_, hash)| *hash)
+    }
+
+    /// Processes a new chain head, returning the reorganization it caused if any.
+    ///
+    /// Pending headers (without a hash or number) are ignored.
+    pub async fn push(&mut self, header: BlockHeader) -> error::Result<Option<Reorg>> {
+        match Head::new(header.number, header.hash, header.parent_hash) {
+            Some(head) => self.process(head).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Turns a stream of new heads (e.g. `EthSubscribe::subscribe_new_heads`) into a stream of reorganizations.
+    pub fn watch<S>(self, heads: S) -> impl Stream<Item = error::Result<Reorg>>
+    where
+        S: Stream<Item = error::Result<BlockHeader>>,
+    {
+        futures::stream::unfold((self, Box::pin(heads)), |(mut watcher, mut heads)| async move {
+            loop {
+                let result = match heads.next().await? {
+                    Ok(header) => watcher.push(header).await,
+                    Err(err) => Err(err),
+                };
+                match result {
+                    Ok(None) => continue,
+                    Ok(Some(reorg)) => return Some((Ok(reorg), (watcher, heads))),
+                    Err(err) => return Some((Err(err), (watcher, heads))),
+                }
+            }
+        })
+    }
+
+    /// Polls the latest block every `poll_interval` and returns a stream of reorganizations.
+    ///
+    /// For transports without subscription support. After an error the next poll is delayed too.
+    pub fn poll(self, poll_interval: Duration) -> impl Stream<Item = error::Result<Reorg>> {
+        futures::stream::unfold((self, false), move |(mut watcher, mut wait)| async move {
+            loop {
+                if wait {
+                    Delay::new(poll_interval).await;
+                }
+                wait = true;
+                let result = match watcher.eth.block(BlockId::Number(BlockNumber::Latest)).await {
+                    Ok(block) => match block.and_then(Head::from_block) {
+                        Some(head) => watcher.process(head).await,
+                        None => Ok(None),
+                    },
+                    Err(err) => Err(err),
+                };
+                match result {
+                    Ok(None) => continue,
+                    Ok(Some(reorg)) => return Some((Ok(reorg), (watcher, false))),
+                    Err(err) => return Some((Err(err), (watcher, true))),
+                }
+            }
+        })
+    }
+
+    async fn process(&mut self, head: Head) -> error::Result<Option<Reorg>> {
+        if self.chain.iter().any(|(_, hash)| *hash == head.hash) {
+            return Ok(None);
+        }
+
+        // walk back the new branch until it connects to the tracked chain
+        let mut new_branch = vec![head];
+        let mut ancestor = None;
+        while let (Some(&(lowest, _)), Some(oldest)) = (self.chain.front(), new_branch.last()) {
+            if let Some(pos) = self.chain.iter().position(|(_, hash)| *hash == oldest.parent_hash) {
+                ancestor = Some(pos);
+                break;
+            }
+            if oldest.number <= lowest || new_branch.len() >= self.max_depth {
+                break;
+            }
+            let parent_hash = oldest.parent_hash;
+            let parent = self
+                .eth
+                .block(BlockId::Hash(parent_hash))
+                .await?
+                .and_then(Head::from_block)
+                .ok_or_else(|| error::Error::InvalidResponse(format!("Block {:?} not found", parent_hash)))?;
+            new_branch.push(parent);
+        }
+        new_branch.reverse();
+
+        let old_branch: Vec<_> = match ancestor {
+            Some(pos) => self.chain.drain(pos + 1..).map(|(_, hash)| hash).collect(),
+            // the new branch is above the tracked chain, so blocks were only missed
+            None if !matches!(self.chain.back(), Some(&(tip, _)) if new_branch[0].number <= tip) => {
+                self.chain.clear();
+                vec![]
+            }
+            None => self.chain.drain(..).map(|(_, hash)| hash).collect(),
+        };
+
+        self.chain
+            .extend(new_branch.iter().map(|head| (head.number, head.hash)));
+        while self.chain.len() > self.max_depth {
+            self.chain.pop_front();
+        }
+
+        if old_branch.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Reorg {
+            depth: old_branch.len(),
+            old_branch,
+            new_branch: new_branch.into_iter().map(|head| head.hash).collect(),
+        }))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::send_transaction_with_confirmation;
+    use super::{
+        send_transaction_with_confirmation, submit_and_get, wait_for_confirmations, wait_for_confirmations_subscribed,
+        wait_for_confirmations_with_cancel, wait_for_receipts, Head, ReceiptEvent, Reorg, ReorgWatcher,
+    };
     use crate::{
-        rpc::Value,
-        transports::test::TestTransport,
-        types::{Address, TransactionReceipt, TransactionRequest, H256, U64},
+        api::{Eth, EthFilter, Namespace},
+        error,
+        helpers::CancellationToken,
+        rpc::{self, Value},
+        transports::test::{self, DuplexTestTransport, TestTransport},
+        types::{Address, Transaction, TransactionReceipt, TransactionRequest, H256, U64},
     };
     use serde_json::json;
     use std::time::Duration;
@@ -165,12 +735,14 @@ mod tests {
             transaction_type: None,
             effective_gas_price: Default::default(),
             revert_reason: None,
+            other: Default::default(),
         };
 
         let poll_interval = Duration::from_secs(0);
         transport.add_response(Value::String(
             r#"0x0000000000000000000000000000000000000000000000000000000000000111"#.into(),
         ));
+        transport.add_response(Value::Null);
         transport.add_response(Value::String("0x123".into()));
         transport.add_response(Value::Array(vec![
             Value::String(r#"0x0000000000000000000000000000000000000000000000000000000000000456"#.into()),
@@ -187,11 +759,10 @@ mod tests {
             Value::String(r#"0x0000000000000000000000000000000000000000000000000000000000000460"#.into()),
             Value::String(r#"0x0000000000000000000000000000000000000000000000000000000000000461"#.into()),
         ]));
-        transport.add_response(Value::Null);
         transport.add_response(json!(transaction_receipt));
         transport.add_response(Value::String("0x6".into()));
-        transport.add_response(json!(transaction_receipt));
         transport.add_response(Value::Bool(true));
+        transport.add_response(json!(transaction_receipt));
 
         let confirmation = {
             let future =
@@ -200,6 +771,10 @@ mod tests {
         };
 
         transport.assert_request("eth_sendTransaction", &[r#"{"from":"0x0000000000000000000000000000000000000123","gasPrice":"0x1","to":"0x0000000000000000000000000000000000000123","value":"0x1"}"#.into()]);
+        transport.assert_request(
+            "eth_getTransactionReceipt",
+            &[r#""0x0000000000000000000000000000000000000000000000000000000000000111""#.into()],
+        );
         transport.assert_request("eth_newBlockFilter", &[]);
         transport.assert_request("eth_getFilterChanges", &[r#""0x123""#.into()]);
         transport.assert_request("eth_getFilterChanges", &[r#""0x123""#.into()]);
@@ -213,11 +788,40 @@ mod tests {
             "eth_getTransactionReceipt",
             &[r#""0x0000000000000000000000000000000000000000000000000000000000000111""#.into()],
         );
+        transport.assert_request("eth_blockNumber", &[]);
+        transport.assert_request("eth_uninstallFilter", &[r#""0x123""#.into()]);
         transport.assert_request(
             "eth_getTransactionReceipt",
             &[r#""0x0000000000000000000000000000000000000000000000000000000000000111""#.into()],
         );
-        transport.assert_request("eth_blockNumber", &[]);
+        transport.assert_no_more_requests();
+        assert_eq!(confirmation, Ok(transaction_receipt));
+    }
+
+    #[test]
+    fn test_send_transaction_with_confirmation_mined_instantly() {
+        let mut transport = TestTransport::default();
+        let transaction_receipt = TransactionReceipt {
+            transaction_hash: H256::from_low_u64_be(0x111),
+            block_number: Some(2.into()),
+            ..Default::default()
+        };
+        transport.add_response(Value::String(
+            r#"0x0000000000000000000000000000000000000000000000000000000000000111"#.into(),
+        ));
+        transport.add_response(json!(transaction_receipt));
+
+        let confirmation = futures::executor::block_on(send_transaction_with_confirmation(
+            &transport,
+            TransactionRequest::default(),
+            Duration::from_secs(10),
+            0,
+        ));
+
+        transport.assert_request(
+            "eth_sendTransaction",
+            &[r#"{"from":"0x0000000000000000000000000000000000000000"}"#.into()],
+        );
         transport.assert_request(
             "eth_getTransactionReceipt",
             &[r#""0x0000000000000000000000000000000000000000000000000000000000000111""#.into()],
@@ -225,4 +829,349 @@ mod tests {
         transport.assert_no_more_requests();
         assert_eq!(confirmation, Ok(transaction_receipt));
     }
+
+    #[test]
+    fn test_wait_for_receipts() {
+        let mut transport = TestTransport::default();
+        let receipt = |hash: u64| TransactionReceipt {
+            transaction_hash: H256::from_low_u64_be(hash),
+            block_number: Some(2.into()),
+            ..Default::default()
+        };
+        transport.add_response(json!("0x123"));
+        transport.add_response(json!(receipt(1)));
+        transport.add_response(Value::Null);
+        transport.add_response(json!([H256::from_low_u64_be(2)]));
+        transport.add_response(json!(receipt(2)));
+        transport.add_response(Value::Bool(true));
+        let events = futures::executor::block_on(async {
+            let hashes = vec![H256::from_low_u64_be(1), H256::from_low_u64_be(2)];
+            let events = wait_for_receipts(&transport, hashes, 0, Duration::from_millis(0))
+                .await
+                .unwrap();
+            futures::StreamExt::collect::<Vec<_>>(events).await
+        });
+
+        assert_eq!(
+            events,
+            vec![
+                Ok(ReceiptEvent::Confirmed(Box::new(receipt(1)))),
+                Ok(ReceiptEvent::Confirmed(Box::new(receipt(2)))),
+                Ok(ReceiptEvent::Done(vec![receipt(1), receipt(2)])),
+            ]
+        );
+        transport.assert_request("eth_newBlockFilter", &[]);
+        transport.assert_request(
+            "eth_getTransactionReceipt",
+            &[r#""0x0000000000000000000000000000000000000000000000000000000000000001""#.into()],
+        );
+        transport.assert_request(
+            "eth_getTransactionReceipt",
+            &[r#""0x0000000000000000000000000000000000000000000000000000000000000002""#.into()],
+        );
+        transport.assert_request("eth_getFilterChanges", &[r#""0x123""#.into()]);
+        transport.assert_request(
+            "eth_getTransactionReceipt",
+            &[r#""0x0000000000000000000000000000000000000000000000000000000000000002""#.into()],
+        );
+        transport.assert_request("eth_uninstallFilter", &[r#""0x123""#.into()]);
+        transport.assert_no_more_requests();
+    }
+
+    #[test]
+    fn test_submit_and_get() {
+        let mut transport = TestTransport::default();
+        let transaction = Transaction {
+            hash: H256::from_low_u64_be(0x111),
+            ..Default::default()
+        };
+        transport.add_response(Value::String(
+            r#"0x0000000000000000000000000000000000000000000000000000000000000111"#.into(),
+        ));
+        transport.add_response(Value::Null);
+        transport.add_response(json!(transaction));
+
+        let result = futures::executor::block_on(submit_and_get(
+            &transport,
+            vec![1, 2, 3].into(),
+            Duration::from_millis(1),
+        ));
+
+        transport.assert_request("eth_sendRawTransaction", &[r#""0x010203""#.into()]);
+        transport.assert_request(
+            "eth_getTransactionByHash",
+            &[r#""0x0000000000000000000000000000000000000000000000000000000000000111""#.into()],
+        );
+        transport.assert_request(
+            "eth_getTransactionByHash",
+            &[r#""0x0000000000000000000000000000000000000000000000000000000000000111""#.into()],
+        );
+        transport.assert_no_more_requests();
+        assert_eq!(result, Ok(transaction));
+    }
+
+    #[test]
+    fn test_submit_and_get_times_out() {
+        let mut transport = TestTransport::default();
+        transport.add_response(Value::String(
+            r#"0x0000000000000000000000000000000000000000000000000000000000000111"#.into(),
+        ));
+        transport.add_response(Value::Null);
+        transport.add_response(Value::Null);
+
+        let result = futures::executor::block_on(submit_and_get(&transport, vec![1].into(), Duration::from_millis(1)));
+
+        assert!(matches!(result, Err(crate::Error::Transport(_))));
+        transport.assert_request("eth_sendRawTransaction", &[r#""0x01""#.into()]);
+        transport.assert_request(
+            "eth_getTransactionByHash",
+            &[r#""0x0000000000000000000000000000000000000000000000000000000000000111""#.into()],
+        );
+        transport.assert_request(
+            "eth_getTransactionByHash",
+            &[r#""0x0000000000000000000000000000000000000000000000000000000000000111""#.into()],
+        );
+        transport.assert_no_more_requests();
+    }
+
+    #[test]
+    fn test_wait_for_confirmations_tolerates_transient_errors() {
+        let mut transport = TestTransport::default();
+        transport.add_response(Value::String("0x123".into()));
+        transport.add_error(error::Error::Unreachable);
+        transport.add_response(json!([H256::from_low_u64_be(1), H256::from_low_u64_be(2)]));
+        transport.add_response(Value::String("0x2".into()));
+        transport.add_response(Value::Bool(true));
+
+        let result = {
+            let check = || async { Ok(Some(1.into())) };
+            let future = wait_for_confirmations(
+                Eth::new(&transport),
+                EthFilter::new(&transport),
+                Duration::from_secs(0),
+                1,
+                check,
+            );
+            futures::executor::block_on(future)
+        };
+
+        assert_eq!(result, Ok(()));
+        transport.assert_request("eth_newBlockFilter", &[]);
+        transport.assert_request("eth_getFilterChanges", &[r#""0x123""#.into()]);
+        transport.assert_request("eth_getFilterChanges", &[r#""0x123""#.into()]);
+        transport.assert_request("eth_blockNumber", &[]);
+        transport.assert_request("eth_uninstallFilter", &[r#""0x123""#.into()]);
+        transport.assert_no_more_requests();
+    }
+
+    #[test]
+    fn test_wait_for_confirmations_cancelled() {
+        let mut transport = TestTransport::default();
+        transport.add_response(Value::String("0x123".into()));
+        transport.add_response(Value::Bool(true));
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+
+        let result = {
+            let check = || async { Ok(None) };
+            let future = wait_for_confirmations_with_cancel(
+                Eth::new(&transport),
+                EthFilter::new(&transport),
+                Duration::from_secs(0),
+                1,
+                check,
+                cancel,
+            );
+            futures::executor::block_on(future)
+        };
+
+        transport.assert_request("eth_newBlockFilter", &[]);
+        transport.assert_request("eth_uninstallFilter", &[r#""0x123""#.into()]);
+        transport.assert_no_more_requests();
+        assert_eq!(result, Err(crate::Error::Cancelled));
+    }
+
+    #[test]
+    fn test_wait_for_confirmations_subscribed() {
+        let mut transport = DuplexTestTransport::default();
+        transport.set_notifications((1..5).map(|number| block(head(number, 0, 0))).collect());
+        transport.inner.add_response(Value::String("0x1".into()));
+        transport.inner.add_response(Value::Bool(true));
+
+        let result = {
+            let check = || async { Ok(Some(U64::from(2))) };
+            futures::executor::block_on(wait_for_confirmations_subscribed(
+                transport.clone(),
+                Duration::from_secs(0),
+                1,
+                check,
+            ))
+        };
+
+        assert_eq!(result, Ok(()));
+        transport
+            .inner
+            .assert_request("eth_subscribe", &[r#""newHeads""#.into()]);
+        transport.inner.assert_request("eth_unsubscribe", &[r#""0x1""#.into()]);
+        transport.inner.assert_no_more_requests();
+    }
+
+    #[test]
+    fn test_wait_for_confirmations_subscribed_falls_back_to_polling() {
+        let mut transport = DuplexTestTransport::default();
+        transport
+            .inner
+            .add_error(crate::Error::Rpc(rpc::Error::method_not_found()));
+        transport.inner.add_response(Value::String("0x123".into()));
+        transport.inner.add_response(json!([H256::from_low_u64_be(1)]));
+        transport.inner.add_response(Value::String("0x1".into()));
+        transport.inner.add_response(Value::Bool(true));
+
+        let result = {
+            let check = || async { Ok(Some(U64::from(1))) };
+            futures::executor::block_on(wait_for_confirmations_subscribed(
+                transport.clone(),
+                Duration::from_secs(0),
+                0,
+                check,
+            ))
+        };
+
+        assert_eq!(result, Ok(()));
+        transport
+            .inner
+            .assert_request("eth_subscribe", &[r#""newHeads""#.into()]);
+        transport.inner.assert_request("eth_newBlockFilter", &[]);
+        transport
+            .inner
+            .assert_request("eth_getFilterChanges", &[r#""0x123""#.into()]);
+        transport.inner.assert_request("eth_blockNumber", &[]);
+        transport
+            .inner
+            .assert_request("eth_uninstallFilter", &[r#""0x123""#.into()]);
+        transport.inner.assert_no_more_requests();
+    }
+
+    fn hash(number: u64, fork: u8) -> H256 {
+        let mut hash = H256::from_low_u64_be(number);
+        hash.0[0] = fork;
+        hash
+    }
+
+    fn head(number: u64, fork: u8, parent_fork: u8) -> Head {
+        Head {
+            number,
+            hash: hash(number, fork),
+            parent_hash: hash(number - 1, parent_fork),
+        }
+    }
+
+    fn block(head: Head) -> Value {
+        test::block(head.number, head.hash, head.parent_hash, 0)
+    }
+
+    #[test]
+    fn reorg_watcher_follows_chain() {
+        let transport = TestTransport::default();
+        let mut watcher = ReorgWatcher::new(Eth::new(transport.clone()));
+
+        for number in 1..4 {
+            let result = futures::executor::block_on(watcher.process(head(number, 0, 0)));
+            assert_eq!(result, Ok(None));
+        }
+
+        transport.assert_no_more_requests();
+        assert_eq!(watcher.head(), Some(hash(3, 0)));
+    }
+
+    #[test]
+    fn reorg_watcher_detects_reorg() {
+        let mut transport = TestTransport::default();
+        transport.add_response(block(head(3, 1, 0)));
+        let mut watcher = ReorgWatcher::new(Eth::new(transport.clone()));
+
+        for number in 1..4 {
+            futures::executor::block_on(watcher.process(head(number, 0, 0))).unwrap();
+        }
+        // the new branch forks off after block 2
+        let result = futures::executor::block_on(watcher.process(head(4, 1, 1)));
+
+        transport.assert_request(
+            "eth_getBlockByHash",
+            &[serde_json::to_string(&hash(3, 1)).unwrap(), "false".into()],
+        );
+        transport.assert_no_more_requests();
+        assert_eq!(
+            result,
+            Ok(Some(Reorg {
+                old_branch: vec![hash(3, 0)],
+                new_branch: vec![hash(3, 1), hash(4, 1)],
+                depth: 1,
+            }))
+        );
+        assert_eq!(watcher.head(), Some(hash(4, 1)));
+    }
+
+    #[test]
+    fn reorg_watcher_ignores_missed_blocks() {
+        let mut transport = TestTransport::default();
+        transport.add_response(block(head(3, 0, 0)));
+        let mut watcher = ReorgWatcher::new(Eth::new(transport.clone()));
+
+        futures::executor::block_on(watcher.process(head(2, 0, 0))).unwrap();
+        let result = futures::executor::block_on(watcher.process(head(4, 0, 0)));
+
+        transport.assert_request(
+            "eth_getBlockByHash",
+            &[serde_json::to_string(&hash(3, 0)).unwrap(), "false".into()],
+        );
+        transport.assert_no_more_requests();
+        assert_eq!(result, Ok(None));
+        assert_eq!(watcher.head(), Some(hash(4, 0)));
+    }
+
+    #[test]
+    fn reorg_watcher_resumes_from_checkpoint() {
+        let transport = TestTransport::default();
+        let mut watcher = ReorgWatcher::new(Eth::new(transport.clone()));
+        for number in 1..4 {
+            futures::executor::block_on(watcher.process(head(number, 0, 0))).unwrap();
+        }
+
+        let checkpoint = watcher.checkpoint();
+        let mut resumed = ReorgWatcher::resume_from(Eth::new(transport.clone()), checkpoint.clone());
+        assert_eq!(resumed.checkpoint(), checkpoint);
+
+        // a block replacing the tracked head is detected without extra requests
+        let result = futures::executor::block_on(resumed.process(head(3, 1, 0)));
+        transport.assert_no_more_requests();
+        assert_eq!(
+            result,
+            Ok(Some(Reorg {
+                old_branch: vec![hash(3, 0)],
+                new_branch: vec![hash(3, 1)],
+                depth: 1,
+            }))
+        );
+    }
+
+    #[test]
+    fn reorg_watcher_delays_polls_after_errors() {
+        let mut transport = TestTransport::default();
+        transport.add_error(error::Error::Unreachable);
+        transport.add_response(block(head(3, 1, 0)));
+        let mut watcher = ReorgWatcher::new(Eth::new(transport.clone()));
+        for number in 1..4 {
+            futures::executor::block_on(watcher.process(head(number, 0, 0))).unwrap();
+        }
+        let mut reorgs = Box::pin(watcher.poll(Duration::from_millis(50)));
+
+        let failed = futures::executor::block_on(futures::StreamExt::next(&mut reorgs));
+        let started = std::time::Instant::now();
+        let reorg = futures::executor::block_on(futures::StreamExt::next(&mut reorgs));
+
+        assert_eq!(failed, Some(Err(error::Error::Unreachable)));
+        assert!(matches!(reorg, Some(Ok(Reorg { depth: 1, .. }))));
+        assert!(started.elapsed() >= Duration::from_millis(50));
+    }
 }
@@ -0,0 +1,220 @@
+//! Solidity compilation artifacts (Hardhat, Foundry).
+
+use crate::types::Address;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// Library placeholders by source file and library name.
+pub type LinkReferences = BTreeMap<String, BTreeMap<String, Vec<LinkReference>>>;
+
+/// Position of a library address placeholder in the bytecode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct LinkReference {
+    /// Offset in bytes
+    pub start: usize,
+    /// Length in bytes
+    pub length: usize,
+}
+
+/// Contract bytecode with unresolved library references.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bytecode {
+    /// Hex encoded bytecode (without `0x`), containing placeholders for libraries
+    pub object: String,
+    /// Placeholders to be replaced with library addresses
+    pub link_references: LinkReferences,
+}
+
+impl Bytecode {
+    /// Replaces library placeholders with their addresses and returns the hex encoded bytecode.
+    ///
+    /// Libraries are looked up by the fully qualified name (`contracts/Lib.sol:Lib`) first
+    /// and by the name alone otherwise. Placeholders are replaced by their offsets, so the
+    /// placeholder format (which differs between compiler versions) doesn't matter.
+    pub fn link<S>(&self, libraries: &HashMap<S, Address>) -> ethabi::Result<String>
+    where
+        S: AsRef<str> + Eq + std::hash::Hash,
+    {
+        let libraries: HashMap<&str, Address> = libraries
+            .iter()
+            .map(|(name, address)| (name.as_ref(), *address))
+            .collect();
+        let mut code = self.object.clone();
+
+        for (file, references) in &self.link_references {
+            for (name, positions) in references {
+                let address = libraries
+                    .get(format!("{}:{}", file, name).as_str())
+                    .or_else(|| libraries.get(name.as_str()))
+                    .ok_or_else(|| ethabi::Error::InvalidName(format!("Library {}:{} is not linked", file, name)))?;
+                let address = hex::encode(address);
+
+                for position in positions {
+                    let (start, end) = (position.start * 2, (position.start + position.length) * 2);
+                    if position.length != 20 || end > code.len() {
+                        return Err(ethabi::Error::InvalidName(format!(
+                            "Invalid link reference for {}:{} at {}",
+                            file, name, position.start
+                        )));
+                    }
+                    code.replace_range(start..end, &address);
+                }
+            }
+        }
+
+        hex::decode(&code).map_err(|e| ethabi::Error::InvalidName(format!("hex decode error: {}", e)))?;
+        Ok(code)
+    }
+}
+
+/// Contract compilation artifact.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Artifact {
+    /// Contract name (Hardhat artifacts only)
+    pub contract_name: Option<String>,
+    /// Contract ABI
+    pub abi: ethabi::Contract,
+    /// Creation bytecode
+    pub bytecode: Bytecode,
+    /// Runtime bytecode
+    pub deployed_bytecode: Option<Bytecode>,
+}
+
+impl Artifact {
+    /// Loads an artifact emitted by Hardhat (`artifacts/**/*.json`) or Foundry (`out/**/*.json`).
+    pub fn load(json: &[u8]) -> ethabi::Result<Self> {
+        let RawArtifact {
+            contract_name,
+            abi,
+            bytecode,
+            deployed_bytecode,
+            link_references,
+            deployed_link_references,
+        } = serde_json::from_slice(json)?;
+        Ok(Artifact {
+            contract_name,
+            abi,
+            bytecode: bytecode.into_bytecode(link_references),
+            deployed_bytecode: deployed_bytecode.map(|bytecode| bytecode.into_bytecode(deployed_link_references)),
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct RawArtifact {
+    #[serde(default, rename = "contractName")]
+    contract_name: Option<String>,
+    abi: ethabi::Contract,
+    bytecode: RawBytecode,
+    #[serde(default, rename = "deployedBytecode")]
+    deployed_bytecode: Option<RawBytecode>,
+    // Hardhat keeps link references next to the bytecode
+    #[serde(default, rename = "linkReferences")]
+    link_references: LinkReferences,
+    #[serde(default, rename = "deployedLinkReferences")]
+    deployed_link_references: LinkReferences,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawBytecode {
+    /// Hardhat
+    Hex(String),
+    /// Foundry
+    Object {
+        object: String,
+        #[serde(default, rename = "linkReferences")]
+        link_references: LinkReferences,
+    },
+}
+
+impl RawBytecode {
+    fn into_bytecode(self, link_references: LinkReferences) -> Bytecode {
+        let (object, link_references) = match self {
+            RawBytecode::Hex(object) => (object, link_references),
+            RawBytecode::Object {
+                object,
+                link_references,
+            } => (object, link_references),
+        };
+        Bytecode {
+            object: object.trim_start_matches("0x").to_owned(),
+            link_references,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ABI: &str = r#"[{"inputs":[],"name":"test","outputs":[{"type":"uint256","name":""}],"stateMutability":"view","type":"function"}]"#;
+    // PUSH20 <Lib> followed by STOP, with a solc >= 0.5 style placeholder
+    const BYTECODE: &str = "0x73__$0d5ef2a9ba0e5e1fd7a88fdb0e6f35e4e0$__00";
+
+    fn libraries() -> HashMap<&'static str, Address> {
+        let mut libraries = HashMap::new();
+        libraries.insert("Lib", Address::from_low_u64_be(0x123));
+        libraries
+    }
+
+    #[test]
+    fn should_load_and_link_hardhat_artifact() {
+        let json = format!(
+            r#"{{
+              "_format": "hh-sol-artifact-1",
+              "contractName": "Main",
+              "sourceName": "contracts/Main.sol",
+              "abi": {},
+              "bytecode": "{}",
+              "deployedBytecode": "0x00",
+              "linkReferences": {{"contracts/Lib.sol": {{"Lib": [{{"length": 20, "start": 1}}]}}}},
+              "deployedLinkReferences": {{}}
+            }}"#,
+            ABI, BYTECODE
+        );
+
+        let artifact = Artifact::load(json.as_bytes()).unwrap();
+        assert_eq!(artifact.contract_name, Some("Main".into()));
+        assert!(artifact.abi.function("test").is_ok());
+        assert_eq!(
+            artifact.deployed_bytecode.map(|bytecode| bytecode.object),
+            Some("00".into())
+        );
+        assert_eq!(
+            artifact.bytecode.link(&libraries()).unwrap(),
+            "73000000000000000000000000000000000000012300"
+        );
+    }
+
+    #[test]
+    fn should_load_and_link_foundry_artifact() {
+        let json = format!(
+            r#"{{
+              "abi": {},
+              "bytecode": {{
+                "object": "{}",
+                "sourceMap": "",
+                "linkReferences": {{"src/Lib.sol": {{"Lib": [{{"start": 1, "length": 20}}]}}}}
+              }},
+              "deployedBytecode": {{"object": "0x00", "sourceMap": "", "linkReferences": {{}}}},
+              "methodIdentifiers": {{"test()": "f8a8fd6d"}}
+            }}"#,
+            ABI, BYTECODE
+        );
+
+        let artifact = Artifact::load(json.as_bytes()).unwrap();
+        assert_eq!(artifact.contract_name, None);
+
+        let mut qualified = HashMap::new();
+        qualified.insert("src/Lib.sol:Lib".to_owned(), Address::from_low_u64_be(0x456));
+        assert_eq!(
+            artifact.bytecode.link(&qualified).unwrap(),
+            "73000000000000000000000000000000000000045600"
+        );
+        assert!(matches!(
+            artifact.bytecode.link(&HashMap::<String, Address>::new()),
+            Err(ethabi::Error::InvalidName(ref msg)) if msg == "Library src/Lib.sol:Lib is not linked"
+        ));
+    }
+}
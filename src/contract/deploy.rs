@@ -28,6 +28,8 @@ pub struct Builder<T: Transport> {
 
 impl<T: Transport> Builder<T> {
     /// Number of confirmations required after code deployment.
+    ///
+    /// `0` resolves as soon as the deployment is mined.
     pub fn confirmations(mut self, confirmations: usize) -> Self {
         self.confirmations = confirmations;
         self
@@ -45,6 +47,14 @@ impl<T: Transport> Builder<T> {
         self
     }
 
+    /// ABI-encoded constructor arguments, as appended to the code on deployment.
+    ///
+    /// These are the bytes block explorers (e.g. Etherscan or Sourcify) ask for when verifying
+    /// the deployed contract.
+    pub fn encoded_constructor_args<P: Tokenize>(&self, params: P) -> Result<Bytes, Error> {
+        encode_constructor_args(&self.abi, params)
+    }
+
     /// Execute deployment passing code and constructor parameters.
     pub async fn execute<P, V>(self, code: V, params: P, from: Address) -> Result<Contract<T>, Error>
     where
@@ -141,6 +151,7 @@ impl<T: Transport> Builder<T> {
                 access_list: tx.access_list,
                 max_fee_per_gas: tx.max_fee_per_gas,
                 max_priority_fee_per_gas: tx.max_priority_fee_per_gas,
+                extra_fields: Default::default(),
             };
             let signed_tx = crate::api::Accounts::new(transport.clone())
                 .sign_transaction(tx, from)
@@ -188,17 +199,10 @@ impl<T: Transport> Builder<T> {
         let code =
             hex::decode(&code_hex).map_err(|e| ethabi::Error::InvalidName(format!("hex decode error: {}", e)))?;
 
-        let params = params.into_tokens();
-        let data = match (abi.constructor(), params.is_empty()) {
-            (None, false) => {
-                return Err(Error::Abi(ethabi::Error::InvalidName(
-                    "Constructor is not defined in the ABI.".into(),
-                )));
-            }
-            (None, true) => code,
-            (Some(constructor), _) => constructor.encode_input(code, &params)?,
-        };
+        let mut data = code;
+        data.extend(encode_constructor_args(&abi, params)?.0);
 
+        let options = options.resolve().await?;
         let tx = TransactionRequest {
             from,
             to: None,
@@ -226,6 +230,21 @@ impl<T: Transport> Builder<T> {
     }
 }
 
+/// ABI-encodes constructor arguments of the contract, without the code.
+///
+/// Fails if the parameters don't match the constructor, or if they are given and the ABI
+/// has no constructor.
+pub fn encode_constructor_args<P: Tokenize>(abi: &ethabi::Contract, params: P) -> Result<Bytes, Error> {
+    let params = params.into_tokens();
+    match (abi.constructor(), params.is_empty()) {
+        (None, false) => Err(Error::Abi(ethabi::Error::InvalidName(
+            "Constructor is not defined in the ABI.".into(),
+        ))),
+        (None, true) => Ok(Bytes::default()),
+        (Some(constructor), _) => Ok(constructor.encode_input(Vec::new(), &params)?.into()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
@@ -246,6 +265,8 @@ mod tests {
         transport.add_response(rpc::Value::String(
             "0x70ae45a5067fdf3356aa615ca08d925a38c7ff21b486a61e79d5af3969ebc1a1".into(),
         ));
+        // receipt, not mined yet
+        transport.add_response(rpc::Value::Null);
         // BlockFilter
         transport.add_response(rpc::Value::String("0x0".into()));
         // getFilterChanges
@@ -262,6 +283,8 @@ mod tests {
         transport.add_response(receipt.clone());
         // block number
         transport.add_response(rpc::Value::String("0x25a".into()));
+        // uninstall filter
+        transport.add_response(rpc::Value::Bool(true));
         // receipt again
         transport.add_response(receipt);
 
@@ -286,6 +309,10 @@ mod tests {
         transport.assert_request("eth_sendTransaction", &[
       "{\"data\":\"0x0102030400000000000000000000000000000000000000000000000000000000000f42400000000000000000000000000000000000000000000000000000000000000080000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000000000000000000000000000000000000c000000000000000000000000000000000000000000000000000000000000000084d7920546f6b656e00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000024d54000000000000000000000000000000000000000000000000000000000000\",\"from\":\"0x0000000000000000000000000000000000000005\",\"value\":\"0x5\"}".into(),
     ]);
+        transport.assert_request(
+            "eth_getTransactionReceipt",
+            &["\"0x70ae45a5067fdf3356aa615ca08d925a38c7ff21b486a61e79d5af3969ebc1a1\"".into()],
+        );
         transport.assert_request("eth_newBlockFilter", &[]);
         transport.assert_request("eth_getFilterChanges", &["\"0x0\"".into()]);
         transport.assert_request("eth_getFilterChanges", &["\"0x0\"".into()]);
@@ -294,6 +321,7 @@ mod tests {
             &["\"0x70ae45a5067fdf3356aa615ca08d925a38c7ff21b486a61e79d5af3969ebc1a1\"".into()],
         );
         transport.assert_request("eth_blockNumber", &[]);
+        transport.assert_request("eth_uninstallFilter", &["\"0x0\"".into()]);
         transport.assert_request(
             "eth_getTransactionReceipt",
             &["\"0x70ae45a5067fdf3356aa615ca08d925a38c7ff21b486a61e79d5af3969ebc1a1\"".into()],
@@ -313,6 +341,7 @@ mod tests {
             transport.add_response(rpc::Value::String(
                 "0x70ae45a5067fdf3356aa615ca08d925a38c7ff21b486a61e79d5af3969ebc1a1".into(),
             ));
+            transport.add_response(rpc::Value::Null);
             transport.add_response(rpc::Value::String("0x0".into()));
             transport.add_response(rpc::Value::Array(vec![rpc::Value::String(
                 "0xd5311584a9867d8e129113e1ec9db342771b94bd4533aeab820a5bcc2c54878f".into(),
@@ -322,6 +351,7 @@ mod tests {
             )]));
             transport.add_response(receipt.clone());
             transport.add_response(rpc::Value::String("0x25a".into()));
+            transport.add_response(rpc::Value::Bool(true));
             transport.add_response(receipt.clone());
         }
 
@@ -344,6 +374,10 @@ mod tests {
         transport.assert_request("eth_sendTransaction", &[
             "{\"data\":\"0x60ad61002f600b82828239805160001a6073146000811461001f57610021565bfe5b5030600052607381538281f3fe73000000000000000000000000000000000000000030146080604052600436106056576000357c0100000000000000000000000000000000000000000000000000000000900463ffffffff168063f8a8fd6d14605b575b600080fd5b60616077565b6040518082815260200191505060405180910390f35b600061010090509056fea165627a7a72305820b50091adcb7ef9987dd8daa665cec572801bf8243530d70d52631f9d5ddb943e0029\",\"from\":\"0x0000000000000000000000000000000000000000\"}"
             .into()]);
+        transport.assert_request(
+            "eth_getTransactionReceipt",
+            &["\"0x70ae45a5067fdf3356aa615ca08d925a38c7ff21b486a61e79d5af3969ebc1a1\"".into()],
+        );
         transport.assert_request("eth_newBlockFilter", &[]);
         transport.assert_request("eth_getFilterChanges", &["\"0x0\"".into()]);
         transport.assert_request("eth_getFilterChanges", &["\"0x0\"".into()]);
@@ -352,6 +386,7 @@ mod tests {
             &["\"0x70ae45a5067fdf3356aa615ca08d925a38c7ff21b486a61e79d5af3969ebc1a1\"".into()],
         );
         transport.assert_request("eth_blockNumber", &[]);
+        transport.assert_request("eth_uninstallFilter", &["\"0x0\"".into()]);
         transport.assert_request(
             "eth_getTransactionReceipt",
             &["\"0x70ae45a5067fdf3356aa615ca08d925a38c7ff21b486a61e79d5af3969ebc1a1\"".into()],
@@ -371,6 +406,10 @@ mod tests {
             "{\"data\":\"0x608060405234801561001057600080fd5b5061013f806100206000396000f3fe608060405260043610610041576000357c0100000000000000000000000000000000000000000000000000000000900463ffffffff168063f8a8fd6d14610046575b600080fd5b34801561005257600080fd5b5061005b610071565b6040518082815260200191505060405180910390f35b600073600515dfe465f600f0c9793fa27cd2794f3ec0e163f8a8fd6d6040518163ffffffff167c010000000000000000000000000000000000000000000000000000000002815260040160206040518083038186803b1580156100d357600080fd5b505af41580156100e7573d6000803e3d6000fd5b505050506040513d60208110156100fd57600080fd5b810190808051906020019092919050505090509056fea165627a7a72305820580d3776b3d132142f431e141a2e20bd4dd4907fa304feea7b604e8f39ed59520029\",\"from\":\"0x0000000000000000000000000000000000000000\"}"
             .into()]);
 
+        transport.assert_request(
+            "eth_getTransactionReceipt",
+            &["\"0x70ae45a5067fdf3356aa615ca08d925a38c7ff21b486a61e79d5af3969ebc1a1\"".into()],
+        );
         transport.assert_request("eth_newBlockFilter", &[]);
         transport.assert_request("eth_getFilterChanges", &["\"0x0\"".into()]);
         transport.assert_request("eth_getFilterChanges", &["\"0x0\"".into()]);
@@ -379,10 +418,33 @@ mod tests {
             &["\"0x70ae45a5067fdf3356aa615ca08d925a38c7ff21b486a61e79d5af3969ebc1a1\"".into()],
         );
         transport.assert_request("eth_blockNumber", &[]);
+        transport.assert_request("eth_uninstallFilter", &["\"0x0\"".into()]);
         transport.assert_request(
             "eth_getTransactionReceipt",
             &["\"0x70ae45a5067fdf3356aa615ca08d925a38c7ff21b486a61e79d5af3969ebc1a1\"".into()],
         );
         transport.assert_no_more_requests();
     }
+
+    #[test]
+    fn should_encode_constructor_args() {
+        let transport = TestTransport::default();
+        let builder = Contract::deploy(api::Eth::new(&transport), include_bytes!("./res/token.json")).unwrap();
+
+        let args = builder
+            .encoded_constructor_args((U256::from(1_000_000), "My Token".to_owned(), 3u64, "MT".to_owned()))
+            .unwrap();
+
+        assert_eq!(hex::encode(&args.0), "00000000000000000000000000000000000000000000000000000000000f42400000000000000000000000000000000000000000000000000000000000000080000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000000000000000000000000000000000000c000000000000000000000000000000000000000000000000000000000000000084d7920546f6b656e00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000024d54000000000000000000000000000000000000000000000000000000000000");
+        assert!(builder.encoded_constructor_args((U256::from(1),)).is_err());
+        transport.assert_no_more_requests();
+    }
+
+    #[test]
+    fn should_require_constructor_for_args() {
+        let lib: Value = serde_json::from_slice(include_bytes!("./res/MyLibrary.json")).unwrap();
+        let abi: ethabi::Contract = serde_json::from_value(lib["abi"].clone()).unwrap();
+        assert_eq!(super::encode_constructor_args(&abi, ()).unwrap().0, Vec::<u8>::new());
+        assert!(super::encode_constructor_args(&abi, (U256::from(1),)).is_err());
+    }
 }
@@ -0,0 +1,173 @@
+//! ABI values of types known only at runtime.
+
+use crate::{
+    contract::{error::Error, tokens::Tokenizable},
+    types::{Address, Bytes, U256},
+};
+pub use ethabi::{ParamType, Token};
+use serde::{Deserialize, Serialize};
+
+/// A serde-friendly ABI value, convertible to and from [`Token`].
+///
+/// Serialized as `{"type": "<kind>", "value": <value>}`, with `uint` values as hex quantities and
+/// `int` values as (signed) decimal strings, e.g. `{"type": "int", "value": "-1"}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "camelCase")]
+pub enum DynamicValue {
+    /// `address`
+    Address(Address),
+    /// `bytesN`
+    FixedBytes(Bytes),
+    /// `bytes`
+    Bytes(Bytes),
+    /// `intN`, stored in two's complement
+    Int(#[serde(with = "signed")] U256),
+    /// `uintN`
+    Uint(U256),
+    /// `bool`
+    Bool(bool),
+    /// `string`
+    String(String),
+    /// `T[N]`
+    FixedArray(Vec<DynamicValue>),
+    /// `T[]`
+    Array(Vec<DynamicValue>),
+    /// Tuple (struct)
+    Tuple(Vec<DynamicValue>),
+}
+
+impl From<Token> for DynamicValue {
+    fn from(token: Token) -> Self {
+        let values = |tokens: Vec<Token>| tokens.into_iter().map(Into::into).collect();
+        match token {
+            Token::Address(address) => DynamicValue::Address(address),
+            Token::FixedBytes(bytes) => DynamicValue::FixedBytes(bytes.into()),
+            Token::Bytes(bytes) => DynamicValue::Bytes(bytes.into()),
+            Token::Int(value) => DynamicValue::Int(value),
+            Token::Uint(value) => DynamicValue::Uint(value),
+            Token::Bool(value) => DynamicValue::Bool(value),
+            Token::String(value) => DynamicValue::String(value),
+            Token::FixedArray(tokens) => DynamicValue::FixedArray(values(tokens)),
+            Token::Array(tokens) => DynamicValue::Array(values(tokens)),
+            Token::Tuple(tokens) => DynamicValue::Tuple(values(tokens)),
+        }
+    }
+}
+
+impl From<DynamicValue> for Token {
+    fn from(value: DynamicValue) -> Self {
+        let tokens = |values: Vec<DynamicValue>| values.into_iter().map(Into::into).collect();
+        match value {
+            DynamicValue::Address(address) => Token::Address(address),
+            DynamicValue::FixedBytes(bytes) => Token::FixedBytes(bytes.0),
+            DynamicValue::Bytes(bytes) => Token::Bytes(bytes.0),
+            DynamicValue::Int(value) => Token::Int(value),
+            DynamicValue::Uint(value) => Token::Uint(value),
+            DynamicValue::Bool(value) => Token::Bool(value),
+            DynamicValue::String(value) => Token::String(value),
+            DynamicValue::FixedArray(values) => Token::FixedArray(tokens(values)),
+            DynamicValue::Array(values) => Token::Array(tokens(values)),
+            DynamicValue::Tuple(values) => Token::Tuple(tokens(values)),
+        }
+    }
+}
+
+impl Tokenizable for DynamicValue {
+    fn from_token(token: Token) -> Result<Self, Error> {
+        Ok(token.into())
+    }
+
+    fn into_token(self) -> Token {
+        self.into()
+    }
+}
+
+/// (De)serializes two's complement 256-bit integers as signed decimal strings.
+mod signed {
+    use crate::types::U256;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    fn negate(value: U256) -> U256 {
+        (!value).overflowing_add(U256::one()).0
+    }
+
+    pub fn serialize<S: Serializer>(value: &U256, serializer: S) -> Result<S::Ok, S::Error> {
+        if value.bit(255) {
+            serializer.serialize_str(&format!("-{}", negate(*value)))
+        } else {
+            serializer.serialize_str(&value.to_string())
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<U256, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        let (negative, digits) = match value.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, value.as_str()),
+        };
+        let abs = U256::from_dec_str(digits).map_err(|err| D::Error::custom(format!("{:?}", err)))?;
+        let min = U256::one() << 255;
+        match negative {
+            true if abs <= min => Ok(negate(abs)),
+            false if abs < min => Ok(abs),
+            _ => Err(D::Error::custom(format!("{} is out of range of int256", value))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn should_convert_tokens() {
+        let token = Token::Tuple(vec![
+            Token::Address(Address::from_low_u64_be(1)),
+            Token::Array(vec![Token::Int(U256::MAX), Token::Uint(5.into())]),
+            Token::FixedBytes(vec![1, 2]),
+        ]);
+
+        let value = DynamicValue::from(token.clone());
+
+        assert_eq!(
+            value,
+            DynamicValue::Tuple(vec![
+                DynamicValue::Address(Address::from_low_u64_be(1)),
+                DynamicValue::Array(vec![DynamicValue::Int(U256::MAX), DynamicValue::Uint(5.into())]),
+                DynamicValue::FixedBytes(vec![1, 2].into()),
+            ])
+        );
+        assert_eq!(Token::from(value), token);
+    }
+
+    #[test]
+    fn should_serialize_dynamic_values() {
+        let value = DynamicValue::Array(vec![
+            DynamicValue::Int(U256::MAX),
+            DynamicValue::Int(7.into()),
+            DynamicValue::Uint(16.into()),
+            DynamicValue::Bool(true),
+        ]);
+        let json = json!({"type": "array", "value": [
+            {"type": "int", "value": "-1"},
+            {"type": "int", "value": "7"},
+            {"type": "uint", "value": "0x10"},
+            {"type": "bool", "value": true},
+        ]});
+
+        assert_eq!(serde_json::to_value(&value).unwrap(), json);
+        assert_eq!(serde_json::from_value::<DynamicValue>(json).unwrap(), value);
+    }
+
+    #[test]
+    fn should_check_int_range() {
+        let min = "-57896044618658097711785492504343953926634992332820282019728792003956564819968";
+        let value: DynamicValue = serde_json::from_value(json!({"type": "int", "value": min})).unwrap();
+        assert_eq!(value, DynamicValue::Int(U256::one() << 255));
+        assert_eq!(serde_json::to_value(&value).unwrap()["value"], min);
+
+        let max_plus_one = &min[1..];
+        assert!(serde_json::from_value::<DynamicValue>(json!({"type": "int", "value": max_plus_one})).is_err());
+    }
+}
@@ -0,0 +1,320 @@
+//! Avatars of ENS names.
+//!
+//! The `avatar` text record holds either a URI of the image or a reference to an NFT, as specified
+//! in [ENSIP 12](https://docs.ens.domains/ensip/12).
+
+use crate::{
+    api::Eth,
+    contract::{Contract, Options},
+    rpc,
+    types::{Address, U256},
+    Error, Transport,
+};
+use futures::Future;
+
+type ContractError = crate::contract::Error;
+
+/// Token standard of an NFT avatar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NftStandard {
+    /// ERC 721, metadata URI returned by `tokenURI`
+    Erc721,
+    /// ERC 1155, metadata URI returned by `uri`
+    Erc1155,
+}
+
+/// Location of an avatar.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AvatarSource {
+    /// An `http://` or `https://` URL
+    Http(String),
+    /// An `ipfs://` URL
+    Ipfs(String),
+    /// A `data:` URI with the image itself
+    Data(String),
+    /// An NFT referenced by the name
+    Nft {
+        /// Standard of the token
+        standard: NftStandard,
+        /// Id of the chain the token is on
+        chain_id: u64,
+        /// Address of the token contract
+        contract: Address,
+        /// Id of the token
+        token_id: U256,
+        /// URI of the token metadata (its `image` field is the avatar), `None` if the token is on
+        /// another chain than the one queried
+        metadata: Option<Box<AvatarSource>>,
+        /// Whether the token is owned by the Ethereum address of the name, always `false` if the
+        /// token is on another chain than the one queried
+        verified: bool,
+    },
+    /// A URI of an unsupported scheme
+    Other(String),
+}
+
+impl AvatarSource {
+    /// Interprets a URI, normalizing IPFS paths to `ipfs://` URLs.
+    pub fn from_uri(uri: &str) -> Self {
+        let uri = uri.trim();
+        let lowercase = uri.to_ascii_lowercase();
+        if lowercase.starts_with("http://") || lowercase.starts_with("https://") {
+            AvatarSource::Http(uri.into())
+        } else if lowercase.starts_with("data:") {
+            AvatarSource::Data(uri.into())
+        } else if lowercase.starts_with("ipfs://") {
+            let path = &uri["ipfs://".len()..];
+            let path = path.strip_prefix("ipfs/").unwrap_or(path);
+            AvatarSource::Ipfs(format!("ipfs://{}", path))
+        } else if let Some(path) = uri.strip_prefix("/ipfs/") {
+            AvatarSource::Ipfs(format!("ipfs://{}", path))
+        } else {
+            AvatarSource::Other(uri.into())
+        }
+    }
+
+    /// Returns the URI, of the metadata for NFTs.
+    pub fn uri(&self) -> Option<&str> {
+        match self {
+            AvatarSource::Http(uri) | AvatarSource::Ipfs(uri) | AvatarSource::Data(uri) | AvatarSource::Other(uri) => {
+                Some(uri)
+            }
+            AvatarSource::Nft { metadata, .. } => metadata.as_ref().and_then(|metadata| metadata.uri()),
+        }
+    }
+
+    /// Returns an HTTP URL of the avatar (or NFT metadata), using given gateway for IPFS.
+    pub fn http_url(&self, ipfs_gateway: &str) -> Option<String> {
+        match self {
+            AvatarSource::Http(uri) => Some(uri.clone()),
+            AvatarSource::Ipfs(uri) => Some(format!(
+                "{}/ipfs/{}",
+                ipfs_gateway.trim_end_matches('/'),
+                &uri["ipfs://".len()..]
+            )),
+            AvatarSource::Nft { metadata, .. } => {
+                metadata.as_ref().and_then(|metadata| metadata.http_url(ipfs_gateway))
+            }
+            AvatarSource::Data(_) | AvatarSource::Other(_) => None,
+        }
+    }
+}
+
+/// Parses an NFT reference of the form `eip155:<chain id>/<erc721|erc1155>:<contract>/<token id>`.
+fn parse_nft(record: &str) -> Option<(NftStandard, u64, Address, U256)> {
+    let record = record.trim().to_ascii_lowercase();
+    let (chain, asset) = record.split_once('/')?;
+    let chain_id = chain.strip_prefix("eip155:")?.parse().ok()?;
+    let (standard, asset) = asset.split_once(':')?;
+    let standard = match standard {
+        "erc721" => NftStandard::Erc721,
+        "erc1155" => NftStandard::Erc1155,
+        _ => return None,
+    };
+    let (contract, token_id) = asset.split_once('/')?;
+    let contract = contract.trim_start_matches("0x").parse().ok()?;
+    let token_id = U256::from_dec_str(token_id).ok()?;
+
+    Some((standard, chain_id, contract, token_id))
+}
+
+/// Interprets an `avatar` text record, querying the metadata URI of NFTs on the connected chain.
+///
+/// The `owner` future resolving the address of the name is only awaited for NFTs, to verify that
+/// the address owns the token.
+pub(crate) async fn resolve<T, F>(eth: Eth<T>, record: &str, owner: F) -> Result<AvatarSource, ContractError>
+where
+    T: Transport,
+    F: Future<Output = Result<Address, ContractError>>,
+{
+    let (standard, chain_id, contract, token_id) = match parse_nft(record) {
+        Some(nft) => nft,
+        None => return Ok(AvatarSource::from_uri(record)),
+    };
+
+    if eth.chain_id().await? != chain_id.into() {
+        return Ok(AvatarSource::Nft {
+            standard,
+            chain_id,
+            contract,
+            token_id,
+            metadata: None,
+            verified: false,
+        });
+    }
+
+    // See https://eips.ethereum.org/EIPS/eip-721 and https://eips.ethereum.org/EIPS/eip-1155.
+    let json = include_bytes!("Nft.json");
+    let token = Contract::from_json(eth, contract, json).expect("Contract Creation");
+    let owner = owner.await?;
+
+    let (verified, uri) = match standard {
+        NftStandard::Erc721 => {
+            let token_owner: Option<Address> =
+                reverted_as_none(token.query("ownerOf", token_id, None, Options::default(), None).await)?;
+            let uri: String = token
+                .query("tokenURI", token_id, None, Options::default(), None)
+                .await?;
+            (token_owner == Some(owner), uri)
+        }
+        NftStandard::Erc1155 => {
+            let balance: Option<U256> = reverted_as_none(
+                token
+                    .query("balanceOf", (owner, token_id), None, Options::default(), None)
+                    .await,
+            )?;
+            let uri: String = token.query("uri", token_id, None, Options::default(), None).await?;
+            let mut id = [0u8; 32];
+            token_id.to_big_endian(&mut id);
+            (
+                balance.is_some_and(|balance| !balance.is_zero()),
+                uri.replace("{id}", &hex::encode(id)),
+            )
+        }
+    };
+
+    Ok(AvatarSource::Nft {
+        standard,
+        chain_id,
+        contract,
+        token_id,
+        metadata: Some(Box::new(AvatarSource::from_uri(&uri))),
+        verified,
+    })
+}
+
+/// Turns a reverted ownership check (e.g. `ownerOf` of a burned token) into `None`, so that the
+/// avatar is returned unverified instead of failing.
+fn reverted_as_none<R>(result: Result<R, ContractError>) -> Result<Option<R>, ContractError> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(ContractError::Api(Error::Revert(_))) => Ok(None),
+        // Geth and most nodes report reverts of `eth_call` with code 3 or a message starting
+        // with "execution reverted"
+        Err(ContractError::Api(Error::Rpc(err)))
+            if err.code == rpc::ErrorCode::ServerError(3) || err.message.starts_with("execution reverted") =>
+        {
+            Ok(None)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{api::Namespace, ethabi::Token, rpc, transports::test::TestTransport};
+
+    #[test]
+    fn should_interpret_uris() {
+        assert_eq!(
+            AvatarSource::from_uri("https://example.com/a.png"),
+            AvatarSource::Http("https://example.com/a.png".into())
+        );
+        assert_eq!(
+            AvatarSource::from_uri("ipfs://ipfs/QmHash/a.png"),
+            AvatarSource::Ipfs("ipfs://QmHash/a.png".into())
+        );
+        assert_eq!(
+            AvatarSource::from_uri("/ipfs/QmHash").http_url("https://ipfs.io/"),
+            Some("https://ipfs.io/ipfs/QmHash".into())
+        );
+        assert_eq!(
+            AvatarSource::from_uri("data:image/svg+xml;base64,PHN2Zz4="),
+            AvatarSource::Data("data:image/svg+xml;base64,PHN2Zz4=".into())
+        );
+        assert_eq!(AvatarSource::from_uri("ar://tx"), AvatarSource::Other("ar://tx".into()));
+        assert_eq!(
+            parse_nft("eip155:1/erc1155:0xb32979486938aa9694bfc898f35dbed459f44424/10063"),
+            Some((
+                NftStandard::Erc1155,
+                1,
+                "b32979486938aa9694bfc898f35dbed459f44424".parse().unwrap(),
+                10063.into()
+            ))
+        );
+        assert_eq!(
+            parse_nft("eip155:1/erc20:0xb32979486938aa9694bfc898f35dbed459f44424/1"),
+            None
+        );
+    }
+
+    fn encode(token: Token) -> rpc::Value {
+        rpc::Value::String(format!("0x{}", hex::encode(crate::ethabi::encode(&[token]))))
+    }
+
+    #[test]
+    fn should_resolve_nft_metadata() {
+        let owner = Address::from_low_u64_be(1);
+        let mut transport = TestTransport::default();
+        transport.add_response(rpc::Value::String("0x1".into()));
+        transport.add_response(encode(Token::Uint(1.into())));
+        transport.add_response(encode(Token::String("https://api.example.com/{id}.json".into())));
+        transport.add_response(rpc::Value::String("0x5".into()));
+        let eth = Eth::new(transport.clone());
+        let record = "eip155:1/erc1155:0xb32979486938aa9694bfc898f35dbed459f44424/10063";
+
+        let avatar = futures::executor::block_on(resolve(eth.clone(), record, async { Ok(owner) })).unwrap();
+        assert_eq!(
+            avatar.uri(),
+            Some("https://api.example.com/000000000000000000000000000000000000000000000000000000000000274f.json")
+        );
+        assert!(matches!(avatar, AvatarSource::Nft { verified: true, .. }));
+
+        let avatar = futures::executor::block_on(resolve(eth, record, async { Ok(owner) })).unwrap();
+        assert!(matches!(
+            avatar,
+            AvatarSource::Nft {
+                metadata: None,
+                verified: false,
+                ..
+            }
+        ));
+        assert_eq!(transport.requests_of("eth_call").len(), 2);
+    }
+
+    #[test]
+    fn should_not_verify_nft_if_ownership_check_reverts() {
+        let mut transport = TestTransport::default();
+        transport.add_response(rpc::Value::String("0x1".into()));
+        transport.add_error(Error::Rpc(rpc::Error {
+            code: rpc::ErrorCode::ServerError(3),
+            message: "execution reverted: ERC721: invalid token ID".into(),
+            data: None,
+        }));
+        transport.add_response(encode(Token::String("ipfs://ipfs/QmHash".into())));
+        let eth = Eth::new(transport.clone());
+        let record = "eip155:1/erc721:0xb47e3cd837ddf8e4c57f05d70ab865de6e193bbb/2430";
+        let owner = async { Ok(Address::from_low_u64_be(1)) };
+
+        let avatar = futures::executor::block_on(resolve(eth, record, owner)).unwrap();
+
+        assert_eq!(avatar.uri(), Some("ipfs://QmHash"));
+        assert!(matches!(avatar, AvatarSource::Nft { verified: false, .. }));
+        assert_eq!(transport.requests_of("eth_call").len(), 2);
+    }
+
+    #[test]
+    fn should_not_verify_nft_owned_by_other_address() {
+        let mut transport = TestTransport::default();
+        transport.add_response(rpc::Value::String("0x1".into()));
+        transport.add_response(encode(Token::Address(Address::from_low_u64_be(2))));
+        transport.add_response(encode(Token::String("ipfs://ipfs/QmHash".into())));
+        let eth = Eth::new(transport.clone());
+        let record = "eip155:1/erc721:0xb47e3cd837ddf8e4c57f05d70ab865de6e193bbb/2430";
+        let owner = async { Ok(Address::from_low_u64_be(1)) };
+
+        let avatar = futures::executor::block_on(resolve(eth, record, owner)).unwrap();
+
+        assert_eq!(avatar.uri(), Some("ipfs://QmHash"));
+        assert!(matches!(avatar, AvatarSource::Nft { verified: false, .. }));
+        transport.assert_request("eth_chainId", &[]);
+        transport.assert_request(
+            "eth_call",
+            &[
+                r#"{"data":"0x6352211e000000000000000000000000000000000000000000000000000000000000097e","to":"0xb47e3cd837ddf8e4c57f05d70ab865de6e193bbb"}"#.into(),
+                r#""latest""#.into(),
+            ],
+        );
+    }
+}
@@ -0,0 +1,490 @@
+//! Content hash records ([EIP 1577](https://eips.ethereum.org/EIPS/eip-1577)).
+
+use crate::{ethabi::Error, types::H256};
+use std::{fmt, str::FromStr};
+
+const IPFS: u64 = 0xe3;
+const SWARM: u64 = 0xe4;
+const IPNS: u64 = 0xe5;
+const ONION: u64 = 0x01bc;
+const ONION3: u64 = 0x01bd;
+const ARWEAVE: u64 = 0xb29910;
+
+const DAG_PB: u64 = 0x70;
+const SWARM_MANIFEST: u64 = 0xfa;
+const SHA2_256: u64 = 0x12;
+const KECCAK_256: u64 = 0x1b;
+
+const BASE58: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const BASE32: &[u8] = b"abcdefghijklmnopqrstuvwxyz234567";
+const BASE64_URL: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Decoded content hash of an ENS name.
+///
+/// `Display` formats the content as a URL (`ipfs://...`), see [`gateway_url`](ContentHash::gateway_url)
+/// for an URL which can be opened in a regular browser. Content hashes with unknown codecs are
+/// kept as raw bytes and displayed as hex.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContentHash {
+    /// IPFS content
+    Ipfs(Cid),
+    /// IPNS name
+    Ipns(Cid),
+    /// Swarm manifest hash
+    Swarm(H256),
+    /// Arweave transaction id
+    Arweave([u8; 32]),
+    /// Tor onion service address (without the `.onion` suffix)
+    Onion(String),
+    /// Content hash with a codec not known to this crate, as stored by the resolver
+    Unknown(Vec<u8>),
+}
+
+/// Content identifier as used by IPFS and IPNS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cid {
+    /// CID version, `0` or `1`
+    pub version: u64,
+    /// Multicodec of the content, e.g. `0x70` (dag-pb)
+    pub codec: u64,
+    /// Multihash of the content (hash function code, length and digest)
+    pub hash: Vec<u8>,
+}
+
+impl Cid {
+    /// Binary representation of the CID.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        if self.version == 0 {
+            return self.hash.clone();
+        }
+        let mut bytes = Vec::new();
+        write_varint(&mut bytes, self.version);
+        write_varint(&mut bytes, self.codec);
+        bytes.extend_from_slice(&self.hash);
+        bytes
+    }
+
+    /// Parses the binary representation of the CID.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() == 34 && bytes[..2] == [SHA2_256 as u8, 32] {
+            return Ok(Cid {
+                version: 0,
+                codec: DAG_PB,
+                hash: bytes.to_vec(),
+            });
+        }
+        let mut reader = bytes;
+        let version = read_varint(&mut reader)?;
+        let codec = read_varint(&mut reader)?;
+        if version != 1 || !is_multihash(reader) {
+            return Err(Error::InvalidData);
+        }
+        Ok(Cid {
+            version,
+            codec,
+            hash: reader.to_vec(),
+        })
+    }
+
+    /// Converts the CID to version 1.
+    pub fn into_v1(self) -> Self {
+        Cid { version: 1, ..self }
+    }
+}
+
+impl fmt::Display for Cid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // CIDv0 is a base58 encoded sha2-256 multihash, CIDv1 uses multibase (`b` for base32).
+        if self.version == 0 {
+            write!(f, "{}", base58_encode(&self.hash))
+        } else {
+            write!(f, "b{}", base_encode(&self.to_bytes(), BASE32, 5))
+        }
+    }
+}
+
+impl FromStr for Cid {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() == 46 && s.starts_with("Qm") {
+            let hash = base58_decode(s)?;
+            return match Cid::from_bytes(&hash)? {
+                cid if cid.version == 0 => Ok(cid),
+                _ => Err(Error::InvalidData),
+            };
+        }
+        match s.strip_prefix('b') {
+            Some(encoded) => Cid::from_bytes(&base_decode(encoded, BASE32, 5)?),
+            None => Err(Error::InvalidData),
+        }
+    }
+}
+
+impl ContentHash {
+    /// Decodes the raw content hash, as returned by the resolver.
+    ///
+    /// Unknown codecs are returned as `ContentHash::Unknown`, malformed content of a known codec
+    /// is an error.
+    pub fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        let mut reader = bytes;
+        let content = match read_varint(&mut reader)? {
+            IPFS => {
+                let cid = Cid::from_bytes(reader)?;
+                // displayed as `Qm...` whenever possible
+                match Cid::from_bytes(&cid.hash) {
+                    Ok(v0) if v0.version == 0 && cid.codec == DAG_PB => ContentHash::Ipfs(v0),
+                    _ => ContentHash::Ipfs(cid),
+                }
+            }
+            IPNS => ContentHash::Ipns(Cid::from_bytes(reader)?),
+            SWARM => {
+                let cid = Cid::from_bytes(reader)?;
+                match cid.hash.as_slice() {
+                    [code, 32, digest @ ..] if cid.codec == SWARM_MANIFEST && *code as u64 == KECCAK_256 => {
+                        ContentHash::Swarm(H256::from_slice(digest))
+                    }
+                    _ => return Err(Error::InvalidData),
+                }
+            }
+            ARWEAVE if reader.len() == 32 => {
+                let mut id = [0u8; 32];
+                id.copy_from_slice(reader);
+                ContentHash::Arweave(id)
+            }
+            ONION | ONION3 => {
+                let address = std::str::from_utf8(reader).map_err(|_| Error::InvalidData)?;
+                ContentHash::Onion(address.to_owned())
+            }
+            ARWEAVE => return Err(Error::InvalidData),
+            _ => ContentHash::Unknown(bytes.to_vec()),
+        };
+        Ok(content)
+    }
+
+    /// Encodes the content hash, to be stored by the resolver.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        match self {
+            ContentHash::Ipfs(cid) => {
+                write_varint(&mut bytes, IPFS);
+                bytes.extend(cid.clone().into_v1().to_bytes());
+            }
+            ContentHash::Ipns(cid) => {
+                write_varint(&mut bytes, IPNS);
+                bytes.extend(cid.clone().into_v1().to_bytes());
+            }
+            ContentHash::Swarm(hash) => {
+                write_varint(&mut bytes, SWARM);
+                let mut multihash = vec![KECCAK_256 as u8, 32];
+                multihash.extend_from_slice(hash.as_bytes());
+                bytes.extend(
+                    Cid {
+                        version: 1,
+                        codec: SWARM_MANIFEST,
+                        hash: multihash,
+                    }
+                    .to_bytes(),
+                );
+            }
+            ContentHash::Arweave(id) => {
+                write_varint(&mut bytes, ARWEAVE);
+                bytes.extend_from_slice(id);
+            }
+            ContentHash::Onion(address) => {
+                write_varint(&mut bytes, if address.len() == 16 { ONION } else { ONION3 });
+                bytes.extend_from_slice(address.as_bytes());
+            }
+            ContentHash::Unknown(raw) => bytes.extend_from_slice(raw),
+        }
+        bytes
+    }
+
+    /// URL of the content at a public HTTP gateway, `None` for unknown codecs.
+    pub fn gateway_url(&self) -> Option<String> {
+        let url = match self {
+            ContentHash::Ipfs(cid) => format!("https://ipfs.io/ipfs/{}", cid),
+            ContentHash::Ipns(cid) => format!("https://ipfs.io/ipns/{}", cid),
+            ContentHash::Swarm(hash) => format!("https://gateway.ethswarm.org/bzz/{:x}", hash),
+            ContentHash::Arweave(_) => format!("https://arweave.net/{}", self.path()),
+            ContentHash::Onion(address) => format!("http://{}.onion", address),
+            ContentHash::Unknown(_) => return None,
+        };
+        Some(url)
+    }
+
+    fn path(&self) -> String {
+        match self {
+            ContentHash::Ipfs(cid) | ContentHash::Ipns(cid) => cid.to_string(),
+            ContentHash::Swarm(hash) => format!("{:x}", hash),
+            ContentHash::Arweave(id) => base_encode(id, BASE64_URL, 6),
+            ContentHash::Onion(address) => format!("{}.onion", address),
+            ContentHash::Unknown(raw) => format!("0x{}", hex::encode(raw)),
+        }
+    }
+}
+
+impl fmt::Display for ContentHash {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let scheme = match self {
+            ContentHash::Ipfs(_) => "ipfs",
+            ContentHash::Ipns(_) => "ipns",
+            ContentHash::Swarm(_) => "bzz",
+            ContentHash::Arweave(_) => "ar",
+            ContentHash::Onion(_) => "http",
+            ContentHash::Unknown(_) => return write!(f, "{}", self.path()),
+        };
+        write!(f, "{}://{}", scheme, self.path())
+    }
+}
+
+impl FromStr for ContentHash {
+    type Err = Error;
+
+    /// Parses the URL format produced by `Display`, e.g. `ipfs://Qm...`, or a hex encoded raw
+    /// content hash.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(raw) = s.strip_prefix("0x") {
+            return ContentHash::decode(&hex::decode(raw).map_err(|_| Error::InvalidData)?);
+        }
+        let (scheme, path) = s.split_at(s.find("://").ok_or(Error::InvalidData)?);
+        let path = &path[3..];
+        match scheme {
+            "ipfs" => Ok(ContentHash::Ipfs(path.parse()?)),
+            "ipns" => Ok(ContentHash::Ipns(path.parse()?)),
+            "bzz" => Ok(ContentHash::Swarm(path.parse().map_err(|_| Error::InvalidData)?)),
+            "ar" => {
+                let decoded = base_decode(path, BASE64_URL, 6)?;
+                let mut id = [0u8; 32];
+                if decoded.len() != id.len() {
+                    return Err(Error::InvalidData);
+                }
+                id.copy_from_slice(&decoded);
+                Ok(ContentHash::Arweave(id))
+            }
+            "http" | "https" => match path.strip_suffix(".onion") {
+                Some(address) if address.len() == 16 || address.len() == 56 => Ok(ContentHash::Onion(address.into())),
+                _ => Err(Error::InvalidData),
+            },
+            _ => Err(Error::InvalidData),
+        }
+    }
+}
+
+fn is_multihash(bytes: &[u8]) -> bool {
+    let mut reader = bytes;
+    matches!(
+        (read_varint(&mut reader), read_varint(&mut reader)),
+        (Ok(_), Ok(len)) if len == reader.len() as u64
+    )
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn read_varint(bytes: &mut &[u8]) -> Result<u64, Error> {
+    let mut value = 0u64;
+    for (index, byte) in bytes.iter().enumerate().take(9) {
+        value |= u64::from(byte & 0x7f) << (7 * index);
+        if byte & 0x80 == 0 {
+            *bytes = &bytes[index + 1..];
+            return Ok(value);
+        }
+    }
+    Err(Error::InvalidData)
+}
+
+/// Encodes with a power of two alphabet (base32, base64), without padding.
+fn base_encode(bytes: &[u8], alphabet: &[u8], bits: u32) -> String {
+    let mut encoded = String::new();
+    let (mut buffer, mut buffered) = (0u32, 0u32);
+    for byte in bytes {
+        buffer = (buffer << 8) | u32::from(*byte);
+        buffered += 8;
+        while buffered >= bits {
+            buffered -= bits;
+            encoded.push(alphabet[((buffer >> buffered) & ((1 << bits) - 1)) as usize] as char);
+        }
+    }
+    if buffered > 0 {
+        encoded.push(alphabet[((buffer << (bits - buffered)) & ((1 << bits) - 1)) as usize] as char);
+    }
+    encoded
+}
+
+fn base_decode(encoded: &str, alphabet: &[u8], bits: u32) -> Result<Vec<u8>, Error> {
+    let mut bytes = Vec::new();
+    let (mut buffer, mut buffered) = (0u32, 0u32);
+    for c in encoded.bytes() {
+        let value = alphabet.iter().position(|a| *a == c).ok_or(Error::InvalidData)?;
+        buffer = (buffer << bits) | value as u32;
+        buffered += bits;
+        if buffered >= 8 {
+            buffered -= 8;
+            bytes.push((buffer >> buffered) as u8);
+        }
+    }
+    Ok(bytes)
+}
+
+fn base58_encode(bytes: &[u8]) -> String {
+    let zeros = bytes.iter().take_while(|b| **b == 0).count();
+    // little endian base58 digits
+    let mut digits: Vec<u8> = Vec::new();
+    for byte in bytes {
+        let mut carry = u32::from(*byte);
+        for digit in digits.iter_mut() {
+            carry += u32::from(*digit) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    let mut encoded = "1".repeat(zeros);
+    encoded.extend(digits.iter().rev().map(|digit| BASE58[*digit as usize] as char));
+    encoded
+}
+
+fn base58_decode(encoded: &str) -> Result<Vec<u8>, Error> {
+    let zeros = encoded.bytes().take_while(|c| *c == b'1').count();
+    // little endian bytes
+    let mut bytes: Vec<u8> = Vec::new();
+    for c in encoded.bytes() {
+        let mut carry = BASE58.iter().position(|a| *a == c).ok_or(Error::InvalidData)? as u32;
+        for byte in bytes.iter_mut() {
+            carry += u32::from(*byte) * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+    let mut decoded = vec![0; zeros];
+    decoded.extend(bytes.into_iter().rev());
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    // https://eips.ethereum.org/EIPS/eip-1577#example
+    const IPFS_HASH: [u8; 38] = hex!("e3010170122029f2d17be6139079dc48696d1f582a8530eb9805b561eda517e22a892c7e3f1f");
+    const SWARM_HASH: [u8; 39] = hex!("e40101fa011b20d1de9994b4d039f6548d191eb26786769f580809256b4685ef316805265ea162");
+
+    #[test]
+    fn should_decode_ipfs() {
+        let content = ContentHash::decode(&IPFS_HASH).unwrap();
+        assert_eq!(
+            content,
+            ContentHash::Ipfs("QmRAQB6YaCyidP37UdDnjFY5vQuiBrcqdyoW1CuDgwxkD4".parse().unwrap())
+        );
+        assert_eq!(
+            content.to_string(),
+            "ipfs://QmRAQB6YaCyidP37UdDnjFY5vQuiBrcqdyoW1CuDgwxkD4"
+        );
+        assert_eq!(
+            content.gateway_url().as_deref(),
+            Some("https://ipfs.io/ipfs/QmRAQB6YaCyidP37UdDnjFY5vQuiBrcqdyoW1CuDgwxkD4")
+        );
+        assert_eq!(content.encode(), IPFS_HASH.to_vec());
+
+        let v1: Cid = "bafybeibj6lixxzqtsb45ysdjnupvqkufgdvzqbnvmhw2kf7cfkesy7r7d4"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            v1.to_string(),
+            "bafybeibj6lixxzqtsb45ysdjnupvqkufgdvzqbnvmhw2kf7cfkesy7r7d4"
+        );
+        assert_eq!(ContentHash::Ipfs(v1).encode(), IPFS_HASH.to_vec());
+    }
+
+    #[test]
+    fn should_decode_swarm() {
+        let content = ContentHash::decode(&SWARM_HASH).unwrap();
+        assert_eq!(
+            content,
+            ContentHash::Swarm(H256(hex!(
+                "d1de9994b4d039f6548d191eb26786769f580809256b4685ef316805265ea162"
+            )))
+        );
+        assert_eq!(
+            content.to_string(),
+            "bzz://d1de9994b4d039f6548d191eb26786769f580809256b4685ef316805265ea162"
+        );
+        assert_eq!(content.encode(), SWARM_HASH.to_vec());
+    }
+
+    #[test]
+    fn should_roundtrip_other_contents() {
+        let mut arweave = [0u8; 32];
+        arweave
+            .iter_mut()
+            .enumerate()
+            .for_each(|(index, byte)| *byte = index as u8);
+        let contents = vec![
+            (
+                ContentHash::Arweave(arweave),
+                "ar://AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8",
+            ),
+            (
+                ContentHash::Onion("zqktlwi4fecvo6ri".into()),
+                "http://zqktlwi4fecvo6ri.onion",
+            ),
+            (
+                ContentHash::Ipns(Cid {
+                    version: 1,
+                    codec: 0x72,
+                    hash: hex!("00050102030405").to_vec(),
+                }),
+                "ipns://bafzaabibaibqibi",
+            ),
+        ];
+
+        for (content, url) in contents {
+            assert_eq!(content.to_string(), url);
+            assert_eq!(url.parse::<ContentHash>().unwrap(), content);
+            assert_eq!(ContentHash::decode(&content.encode()).unwrap(), content);
+        }
+        assert_eq!(
+            &ContentHash::Onion("zqktlwi4fecvo6ri".into()).encode()[..2],
+            &[0xbc, 0x03]
+        );
+    }
+
+    #[test]
+    fn should_keep_unknown_codecs() {
+        // skynet (0x90b2c6)
+        let raw = hex!("90b2c60508001e49c1cd1ae7d5c9a4b6d1d03a2bcb6bdd93f14b0e3a3f3a1e8b0e1a3a5b").to_vec();
+        let content = ContentHash::decode(&raw).unwrap();
+        assert_eq!(content, ContentHash::Unknown(raw.clone()));
+        assert_eq!(content.encode(), raw);
+        assert_eq!(content.gateway_url(), None);
+        assert_eq!(content.to_string(), format!("0x{}", hex::encode(&raw)));
+        assert_eq!(content.to_string().parse::<ContentHash>().unwrap(), content);
+        assert_eq!(
+            format!("0x{}", hex::encode(IPFS_HASH)).parse::<ContentHash>().unwrap(),
+            ContentHash::decode(&IPFS_HASH).unwrap()
+        );
+    }
+
+    #[test]
+    fn should_reject_invalid_content() {
+        assert!(ContentHash::decode(&[]).is_err());
+        assert!(ContentHash::decode(&IPFS_HASH[..20]).is_err());
+        assert!(ContentHash::decode(&ContentHash::Arweave([1; 32]).encode()[..16]).is_err());
+        assert!("ftp://example.com".parse::<ContentHash>().is_err());
+        assert!("ipfs://Qm000".parse::<ContentHash>().is_err());
+    }
+}
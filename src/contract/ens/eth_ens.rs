@@ -1,6 +1,9 @@
 use crate::{
     api::Namespace,
-    contract::ens::{public_resolver::PublicResolver, registry::Registry, reverse_resolver::ReverseResolver},
+    contract::ens::{
+        avatar::AvatarSource, content_hash::ContentHash, public_resolver::PublicResolver,
+        registrar::RegistrarController, registry::Registry, reverse_resolver::ReverseResolver,
+    },
     signing::namehash,
     types::{Address, TransactionId, U256},
     Transport, Web3,
@@ -398,14 +401,14 @@ impl<T: Transport> Ens<T> {
 
     /// Returns the content hash for ```node```, if one exists.
     ///
-    /// Values are formatted as machine-readable [multicodecs](https://github.com/multiformats/multicodec), as specified in [EIP 1577](https://eips.ethereum.org/EIPS/eip-1577).
+    /// Values are decoded from machine-readable [multicodecs](https://github.com/multiformats/multicodec), as specified in [EIP 1577](https://eips.ethereum.org/EIPS/eip-1577).
     ///
-    /// ```content_hash``` is used to store IPFS and Swarm content hashes, which permit resolving ENS addresses to distributed content (eg, websites) hosted on these distributed networks.
+    /// ```content_hash``` is used to store IPFS and Swarm content hashes, which permit resolving ENS addresses to distributed content (eg, websites) hosted on these distributed networks. Records with codecs not known to this crate are returned as raw bytes in `ContentHash::Unknown`.
     ///
     /// This function has interface ID *0xbc1c58d1*.
     ///
     /// This function is specified in [EIP 1577](https://eips.ethereum.org/EIPS/eip-1157).
-    pub async fn content_hash(&self, node: &str) -> Result<Vec<u8>, ContractError> {
+    pub async fn content_hash(&self, node: &str) -> Result<Option<ContentHash>, ContractError> {
         let node = self.normalize_name(node)?;
         let node = namehash(&node);
 
@@ -416,14 +419,18 @@ impl<T: Transport> Ens<T> {
             return Err(ContractError::InterfaceUnsupported);
         }
 
-        resolver.content_hash(node).await
+        let hash = resolver.content_hash(node).await?;
+        if hash.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(ContentHash::decode(&hash)?))
     }
 
     /// Sets the content hash for the provided ```node``` to ```hash```.
     ///
     /// Only callable by the owner of ```node```.
     ///
-    /// Values are formatted as machine-readable [multicodecs](https://github.com/multiformats/multicodec), as specified in [EIP 1577](https://eips.ethereum.org/EIPS/eip-1577).
+    /// Values are encoded as machine-readable [multicodecs](https://github.com/multiformats/multicodec), as specified in [EIP 1577](https://eips.ethereum.org/EIPS/eip-1577).
     ///
     /// Emits the following event:
     /// ```solidity
@@ -433,7 +440,7 @@ impl<T: Transport> Ens<T> {
         &self,
         from: Address,
         node: &str,
-        hash: Vec<u8>,
+        hash: &ContentHash,
     ) -> Result<TransactionId, ContractError> {
         let node = self.normalize_name(node)?;
         let node = namehash(&node);
@@ -445,12 +452,7 @@ impl<T: Transport> Ens<T> {
             return Err(ContractError::InterfaceUnsupported);
         }
 
-        // https://eips.ethereum.org/EIPS/eip-1577
-        if !(hash[0] == 0xe3 || hash[0] == 0xe4) {
-            return Err(ContractError::Abi(EthError::InvalidData));
-        }
-
-        resolver.set_content_hash(from, node, hash).await
+        resolver.set_content_hash(from, node, hash.encode()).await
     }
 
     /// Retrieves text metadata for ```node```.
@@ -521,6 +523,22 @@ impl<T: Transport> Ens<T> {
         resolver.set_text_data(from, node, key, value).await
     }
 
+    /// Returns the avatar of ```node```, if one is set.
+    ///
+    /// Interprets the ```avatar``` text record as specified in [ENSIP 12](https://docs.ens.domains/ensip/12).
+    /// For ERC 721 and ERC 1155 NFTs on the connected chain the URI of the token metadata is queried with ```tokenURI``` or ```uri```, the avatar being the ```image``` of the metadata.
+    /// The NFT is verified to be owned by the Ethereum address of ```node```, unverified avatars should not be displayed.
+    pub async fn avatar(&self, node: &str) -> Result<Option<AvatarSource>, ContractError> {
+        let record = self.text(node, "avatar".into()).await?;
+        if record.is_empty() {
+            return Ok(None);
+        }
+
+        crate::contract::ens::avatar::resolve(self.web3.eth(), &record, self.eth_address(node))
+            .await
+            .map(Some)
+    }
+
     /*** Reverse Resolver Functions Below ***/
 
     /// Returns the canonical ENS name associated with the provided ```addr```.
@@ -573,4 +591,13 @@ impl<T: Transport> Ens<T> {
 
         resolver.set_canonical_name(from, node, name).await
     }
+
+    /*** Registrar Controller Functions Below ***/
+
+    /// Returns the controller of the `.eth` registrar deployed on the mainnet.
+    ///
+    /// Second-level `.eth` names are registered with a commitment followed by the registration, see [`RegistrarController`].
+    pub fn registrar(&self) -> RegistrarController<T> {
+        RegistrarController::new(self.web3.eth())
+    }
 }
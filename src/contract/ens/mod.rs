@@ -21,9 +21,15 @@
 //! }
 //! ```
 
+pub mod avatar;
+pub mod content_hash;
 mod eth_ens;
 pub mod public_resolver;
+pub mod registrar;
 pub mod registry;
 pub mod reverse_resolver;
 
+pub use avatar::{AvatarSource, NftStandard};
+pub use content_hash::{Cid, ContentHash};
 pub use eth_ens::Ens;
+pub use registrar::{RegistrarController, Registration, RentPrice};
@@ -0,0 +1,450 @@
+//! .eth Registrar Controller contract interface.
+
+use crate::{
+    api::Eth,
+    contract::{
+        tokens::{Tokenizable, Tokenize},
+        Contract, Options,
+    },
+    ethabi::Token,
+    helpers::poll::{poll_until, PollPolicy},
+    signing::keccak256,
+    types::{Address, BlockId, BlockNumber, TransactionId, H256, U256},
+    Transport,
+};
+
+type ContractError = crate::contract::Error;
+
+const ETH_REGISTRAR_CONTROLLER_ADDRESS: &str = "253553366Da8546fC250F225fe3d25d0C782303b";
+
+/// Price of registering or renewing a name, in wei.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RentPrice {
+    /// Price for the duration
+    pub base: U256,
+    /// Temporary premium of recently expired names
+    pub premium: U256,
+}
+
+impl RentPrice {
+    /// Returns the amount to pay.
+    pub fn total(&self) -> U256 {
+        self.base + self.premium
+    }
+}
+
+impl Tokenizable for RentPrice {
+    fn from_token(token: Token) -> Result<Self, ContractError> {
+        match token {
+            Token::Tuple(tokens) if tokens.len() == 2 => {
+                let mut tokens = tokens.into_iter();
+                Ok(RentPrice {
+                    base: U256::from_token(tokens.next().expect("Two tokens; qed"))?,
+                    premium: U256::from_token(tokens.next().expect("Two tokens; qed"))?,
+                })
+            }
+            other => Err(ContractError::InvalidOutputType(format!(
+                "Expected `(uint256,uint256)`, got {:?}",
+                other
+            ))),
+        }
+    }
+
+    fn into_token(self) -> Token {
+        Token::Tuple(vec![self.base.into_token(), self.premium.into_token()])
+    }
+}
+
+/// Parameters of a name registration, committed to before registering.
+///
+/// The same parameters (including the secret) must be used for [`RegistrarController::commit`]
+/// and [`RegistrarController::register`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Registration {
+    /// Label to register, e.g. `alice` for `alice.eth`
+    pub name: String,
+    /// Owner of the registered name
+    pub owner: Address,
+    /// Duration of the registration in seconds
+    pub duration: u64,
+    /// Secret hiding the name in the commitment
+    pub secret: H256,
+    /// Resolver to set, required if `data` is not empty
+    pub resolver: Address,
+    /// Calls to the resolver made after the registration, e.g. `setAddr`
+    pub data: Vec<Vec<u8>>,
+    /// Whether to set the reverse record of the sender to the name
+    pub reverse_record: bool,
+    /// Fuses of the name wrapper burnt by the owner
+    pub owner_controlled_fuses: u16,
+}
+
+impl Registration {
+    /// Creates a registration of `name` for `duration` seconds.
+    ///
+    /// The `secret` hides the name until it's registered, it should be generated with a
+    /// cryptographically secure random number generator (e.g. `random_secret`, with the `getrandom` feature)
+    /// and kept until the registration.
+    pub fn new(name: &str, owner: Address, duration: u64, secret: H256) -> Self {
+        Registration {
+            name: name.into(),
+            owner,
+            duration,
+            secret,
+            resolver: Address::zero(),
+            data: vec![],
+            reverse_record: false,
+            owner_controlled_fuses: 0,
+        }
+    }
+
+    /// Generates a random secret for a registration.
+    #[cfg(feature = "getrandom")]
+    pub fn random_secret() -> Result<H256, getrandom::Error> {
+        let mut secret = H256::zero();
+        getrandom::getrandom(secret.as_bytes_mut())?;
+        Ok(secret)
+    }
+
+    /// Returns the commitment to the registration, as computed by `makeCommitment`.
+    pub fn commitment(&self) -> H256 {
+        let mut tokens = self.clone().into_tokens();
+        tokens[0] = Token::FixedBytes(keccak256(self.name.as_bytes()).to_vec());
+
+        keccak256(&crate::ethabi::encode(&tokens)).into()
+    }
+}
+
+impl Tokenize for Registration {
+    fn into_tokens(self) -> Vec<Token> {
+        (
+            self.name,
+            self.owner,
+            U256::from(self.duration),
+            self.secret,
+            self.resolver,
+            self.data,
+            self.reverse_record,
+            self.owner_controlled_fuses,
+        )
+            .into_tokens()
+    }
+}
+
+/// The controller of the `.eth` registrar, registering and renewing second-level `.eth` names.
+///
+/// Names are registered in two steps to prevent front-running: a commitment to the registration is
+/// sent first, and the name is registered once the commitment is at least `minCommitmentAge` old
+/// (but not older than `maxCommitmentAge`).
+///
+/// [Specification](https://docs.ens.domains/registry/eth)
+///
+/// [Source](https://github.com/ensdomains/ens-contracts/blob/staging/contracts/ethregistrar/ETHRegistrarController.sol)
+#[derive(Debug, Clone)]
+pub struct RegistrarController<T: Transport> {
+    eth: Eth<T>,
+    contract: Contract<T>,
+}
+
+impl<T: Transport> RegistrarController<T> {
+    /// Creates new instance of [`RegistrarController`] deployed on the mainnet.
+    pub fn new(eth: Eth<T>) -> Self {
+        let address = ETH_REGISTRAR_CONTROLLER_ADDRESS.parse().expect("Parsing Address");
+
+        Self::at(eth, address)
+    }
+
+    /// Creates new instance of [`RegistrarController`] given contract address.
+    pub fn at(eth: Eth<T>, address: Address) -> Self {
+        // See https://github.com/ensdomains/ens-contracts for up to date contracts.
+        let json = include_bytes!("ETHRegistrarController.json");
+
+        let contract = Contract::from_json(eth.clone(), address, json).expect("Contract Creation");
+
+        Self { eth, contract }
+    }
+
+    /// Returns the address of the controller.
+    pub fn address(&self) -> Address {
+        self.contract.address()
+    }
+}
+
+impl<T: Transport> RegistrarController<T> {
+    /// Returns true if the label is valid and not registered.
+    pub async fn available(&self, name: &str) -> Result<bool, ContractError> {
+        let options = Options::default();
+
+        self.contract
+            .query("available", name.to_owned(), None, options, None)
+            .await
+    }
+
+    /// Returns the price of registering or renewing the label for `duration` seconds.
+    pub async fn rent_price(&self, name: &str, duration: u64) -> Result<RentPrice, ContractError> {
+        let options = Options::default();
+
+        self.contract
+            .query(
+                "rentPrice",
+                (name.to_owned(), U256::from(duration)),
+                None,
+                options,
+                None,
+            )
+            .await
+    }
+
+    /// Returns the minimal age of a commitment in seconds before the name can be registered.
+    pub async fn min_commitment_age(&self) -> Result<U256, ContractError> {
+        let options = Options::default();
+
+        self.contract.query("minCommitmentAge", (), None, options, None).await
+    }
+
+    /// Returns the age of a commitment in seconds after which it expires.
+    pub async fn max_commitment_age(&self) -> Result<U256, ContractError> {
+        let options = Options::default();
+
+        self.contract.query("maxCommitmentAge", (), None, options, None).await
+    }
+
+    /// Returns the timestamp of the block including the commitment, or 0 if it was not committed.
+    pub async fn commitment_timestamp(&self, commitment: H256) -> Result<U256, ContractError> {
+        let options = Options::default();
+
+        self.contract
+            .query("commitments", commitment, None, options, None)
+            .await
+    }
+
+    /// Commits to a registration, see [`Registration::commitment`].
+    pub async fn commit(&self, from: Address, commitment: H256) -> Result<TransactionId, ContractError> {
+        let options = Options::default();
+
+        let id = self.contract.call("commit", commitment, from, options).await?;
+
+        Ok(TransactionId::Hash(id))
+    }
+
+    /// Waits until the commitment is included in a block and old enough to register the name.
+    ///
+    /// Polls the commitment until it's mined and then the latest block until its timestamp is at
+    /// least `minCommitmentAge` later.
+    pub async fn wait_for_commitment(&self, commitment: H256, policy: PollPolicy) -> Result<(), ContractError> {
+        self.wait_for_commitment_age(commitment, policy).await.map(|_| ())
+    }
+
+    /// Waits for the commitment, returning its timestamp and the timestamp of the latest block.
+    async fn wait_for_commitment_age(
+        &self,
+        commitment: H256,
+        policy: PollPolicy,
+    ) -> Result<(U256, U256), ContractError> {
+        let min_age = self.min_commitment_age().await?;
+
+        let committed_at = poll_until(
+            || async move {
+                self.commitment_timestamp(commitment).await.map_err(|err| match err {
+                    ContractError::Api(err) => err,
+                    err => crate::error::Error::Decoder(err.to_string()),
+                })
+            },
+            |timestamp| !timestamp.is_zero(),
+            policy,
+        )
+        .await?;
+
+        let ready_at = committed_at + min_age;
+        let latest = poll_until(
+            || self.eth.block(BlockId::Number(BlockNumber::Latest)),
+            |block| block.as_ref().is_some_and(|block| block.timestamp >= ready_at),
+            policy,
+        )
+        .await?
+        .expect("the block is checked by the condition; qed");
+
+        Ok((committed_at, latest.timestamp))
+    }
+
+    /// Registers the committed name, paying `value` wei.
+    ///
+    /// `value` should be at least the [`rent_price`](Self::rent_price) of the name, the excess is
+    /// refunded.
+    pub async fn register(
+        &self,
+        from: Address,
+        registration: &Registration,
+        value: U256,
+    ) -> Result<TransactionId, ContractError> {
+        let options = Options::with(|options| options.value = Some(value));
+
+        let id = self
+            .contract
+            .call("register", registration.clone(), from, options)
+            .await?;
+
+        Ok(TransactionId::Hash(id))
+    }
+
+    /// Commits to the registration, waits for the commitment and registers the name.
+    ///
+    /// Pays the rent price with a 5% margin for price changes in between, the excess is refunded.
+    /// Fails with `Error::CommitmentExpired` instead of registering if the commitment got older than
+    /// `maxCommitmentAge` while waiting (e.g. with a slow poll policy).
+    pub async fn commit_and_register(
+        &self,
+        from: Address,
+        registration: &Registration,
+        policy: PollPolicy,
+    ) -> Result<TransactionId, ContractError> {
+        let commitment = registration.commitment();
+        let max_age = self.max_commitment_age().await?;
+
+        self.commit(from, commitment).await?;
+        let (committed_at, now) = self.wait_for_commitment_age(commitment, policy).await?;
+        if committed_at + max_age <= now {
+            return Err(ContractError::CommitmentExpired);
+        }
+
+        let price = self.rent_price(&registration.name, registration.duration).await?;
+        let value = price.total() * 105 / 100;
+
+        self.register(from, registration, value).await
+    }
+
+    /// Extends the registration of the label by `duration` seconds, paying `value` wei.
+    ///
+    /// `value` should be at least the [`rent_price`](Self::rent_price) of the renewal, the excess
+    /// is refunded. Anyone can renew any name.
+    pub async fn renew(
+        &self,
+        from: Address,
+        name: &str,
+        duration: u64,
+        value: U256,
+    ) -> Result<TransactionId, ContractError> {
+        let options = Options::with(|options| options.value = Some(value));
+
+        let id = self
+            .contract
+            .call("renew", (name.to_owned(), U256::from(duration)), from, options)
+            .await?;
+
+        Ok(TransactionId::Hash(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        api::Namespace,
+        rpc,
+        transports::test::{self, TestTransport},
+    };
+    use hex_literal::hex;
+
+    fn uint(value: u64) -> rpc::Value {
+        rpc::Value::String(format!(
+            "0x{}",
+            hex::encode(crate::ethabi::encode(&[Token::Uint(value.into())]))
+        ))
+    }
+
+    #[test]
+    fn should_compute_commitment() {
+        let mut registration = Registration::new(
+            "alice",
+            Address::from_low_u64_be(1),
+            31_536_000,
+            H256::repeat_byte(0x11),
+        );
+        registration.resolver = Address::from_low_u64_be(2);
+        registration.data = vec![vec![0xde, 0xad, 0xbe, 0xef]];
+        registration.reverse_record = true;
+
+        // abi.encode(label, owner, duration, secret, resolver, data, reverseRecord, ownerControlledFuses)
+        let mut preimage = keccak256(b"alice").to_vec();
+        preimage.extend(hex!(
+            "
+            0000000000000000000000000000000000000000000000000000000000000001
+            0000000000000000000000000000000000000000000000000000000001e13380
+            1111111111111111111111111111111111111111111111111111111111111111
+            0000000000000000000000000000000000000000000000000000000000000002
+            0000000000000000000000000000000000000000000000000000000000000100
+            0000000000000000000000000000000000000000000000000000000000000001
+            0000000000000000000000000000000000000000000000000000000000000000
+            0000000000000000000000000000000000000000000000000000000000000001
+            0000000000000000000000000000000000000000000000000000000000000020
+            0000000000000000000000000000000000000000000000000000000000000004
+            deadbeef00000000000000000000000000000000000000000000000000000000
+            "
+        ));
+        assert_eq!(registration.commitment(), H256::from(keccak256(&preimage)));
+    }
+
+    #[test]
+    fn should_register_after_min_commitment_age() {
+        let hash = rpc::Value::String(format!("{:?}", H256::repeat_byte(1)));
+        let mut transport = TestTransport::default();
+        transport.add_response(uint(86_400));
+        transport.add_response(hash.clone());
+        transport.add_response(uint(60));
+        transport.add_response(uint(0));
+        transport.add_response(uint(1_000));
+        transport.add_response(test::block(1, H256::zero(), H256::zero(), 1_030));
+        transport.add_response(test::block(1, H256::zero(), H256::zero(), 1_060));
+        transport.add_response(rpc::Value::String(format!(
+            "0x{}",
+            hex::encode(crate::ethabi::encode(&[
+                Token::Uint(1_000.into()),
+                Token::Uint(0.into())
+            ]))
+        )));
+        transport.add_response(hash);
+        let controller = RegistrarController::new(Eth::new(transport.clone()));
+        let from = Address::from_low_u64_be(1);
+        let registration = Registration::new("alice", from, 31_536_000, H256::repeat_byte(0x11));
+        let policy = PollPolicy::new(std::time::Duration::from_millis(1));
+
+        let id = futures::executor::block_on(controller.commit_and_register(from, &registration, policy)).unwrap();
+
+        assert_eq!(id, TransactionId::Hash(H256::repeat_byte(1)));
+        assert_eq!(transport.requests_of("eth_call").len(), 5);
+        assert_eq!(transport.requests_of("eth_getBlockByNumber").len(), 2);
+        let sent = transport.requests_of("eth_sendTransaction");
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[1][0]["value"], "0x41a");
+    }
+
+    #[test]
+    fn should_not_register_after_max_commitment_age() {
+        let hash = rpc::Value::String(format!("{:?}", H256::repeat_byte(1)));
+        let mut transport = TestTransport::default();
+        transport.add_response(uint(120));
+        transport.add_response(hash);
+        transport.add_response(uint(60));
+        transport.add_response(uint(1_000));
+        transport.add_response(test::block(1, H256::zero(), H256::zero(), 1_030));
+        transport.add_response(test::block(1, H256::zero(), H256::zero(), 1_200));
+        let controller = RegistrarController::new(Eth::new(transport.clone()));
+        let from = Address::from_low_u64_be(1);
+        let registration = Registration::new("alice", from, 31_536_000, H256::repeat_byte(0x11));
+        let policy = PollPolicy::new(std::time::Duration::from_millis(1));
+
+        let result = futures::executor::block_on(controller.commit_and_register(from, &registration, policy));
+
+        assert!(matches!(result, Err(ContractError::CommitmentExpired)));
+        assert_eq!(transport.requests_of("eth_sendTransaction").len(), 1);
+    }
+
+    #[cfg(feature = "getrandom")]
+    #[test]
+    fn should_generate_random_secrets() {
+        let secret = Registration::random_secret().unwrap();
+
+        assert_ne!(secret, H256::zero());
+        assert_ne!(secret, Registration::random_secret().unwrap());
+    }
+}
@@ -21,6 +21,20 @@ pub enum Error {
     Deployment(crate::contract::deploy::Error),
     /// Contract does not support this interface.
     InterfaceUnsupported,
+    /// Storage layout is missing or doesn't match the requested variable.
+    #[display(fmt = "Storage layout error: {}", _0)]
+    #[from(ignore)]
+    StorageLayout(String),
+    /// Function parameters don't match the ABI.
+    #[display(fmt = "Invalid parameters: {}", _0)]
+    #[from(ignore)]
+    InvalidParams(String),
+    /// Event sink failed to store a batch.
+    #[display(fmt = "Sink error: {}", _0)]
+    #[from(ignore)]
+    Sink(crate::contract::sink::SinkError),
+    /// ENS registration commitment is older than `maxCommitmentAge`.
+    CommitmentExpired,
 }
 
 impl std::error::Error for Error {
@@ -31,6 +45,10 @@ impl std::error::Error for Error {
             Error::Api(ref e) => Some(e),
             Error::Deployment(ref e) => Some(e),
             Error::InterfaceUnsupported => None,
+            Error::StorageLayout(_) => None,
+            Error::InvalidParams(_) => None,
+            Error::Sink(ref e) => Some(&**e),
+            Error::CommitmentExpired => None,
         }
     }
 }
@@ -0,0 +1,336 @@
+//! ABI-aware log filters.
+
+use crate::{
+    contract::{tokens::Detokenize, Error, Result},
+    types::{Address, BlockNumber, Filter, FilterBuilder, Log},
+};
+use ethabi::{Event, EventParam, RawTopicFilter, Token, Topic};
+
+/// Matcher of an indexed event parameter.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Match {
+    /// Any value.
+    Any,
+    /// Exactly this value.
+    Exact(Token),
+    /// Any of these values.
+    OneOf(Vec<Token>),
+}
+
+impl From<Match> for Topic<Token> {
+    fn from(matcher: Match) -> Self {
+        match matcher {
+            Match::Any => Topic::Any,
+            Match::Exact(token) => Topic::This(token),
+            Match::OneOf(tokens) => Topic::OneOf(tokens),
+        }
+    }
+}
+
+/// Builds a log filter for an event, matching indexed parameters by value.
+///
+/// ```no_run
+/// # use web3::contract::events::{EventFilterBuilder, Match};
+/// # use web3::{ethabi::Token, types::Address};
+/// # fn main() -> web3::contract::Result<()> {
+/// let (filter, decoder) = EventFilterBuilder::from_signature(
+///     "Transfer(address indexed from, address indexed to, uint256 value)",
+/// )?
+/// .address(vec![Address::repeat_byte(1), Address::repeat_byte(2)])
+/// .param("to", Match::OneOf(vec![Token::Address(Address::repeat_byte(3)), Token::Address(Address::repeat_byte(4))]))
+/// .build()?;
+/// // pass `filter` to `eth_getLogs`, `eth_newFilter` or `eth_subscribe("logs")`,
+/// // then `decoder.decode::<(Address, Address, web3::types::U256)>(&log)`
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct EventFilterBuilder {
+    event: Event,
+    matchers: Vec<(String, Match)>,
+    addresses: Vec<Address>,
+    from_block: Option<BlockNumber>,
+    to_block: Option<BlockNumber>,
+}
+
+impl EventFilterBuilder {
+    /// Creates a builder matching any occurrence of given event.
+    pub fn new(event: Event) -> Self {
+        EventFilterBuilder {
+            event,
+            matchers: vec![],
+            addresses: vec![],
+            from_block: None,
+            to_block: None,
+        }
+    }
+
+    /// Creates a builder from a human readable signature, e.g.
+    /// `Transfer(address indexed from, address indexed to, uint256 value)`.
+    ///
+    /// Parameter names are optional, unnamed parameters are named after their position (`_0`,
+    /// `_1`, ...). Indexed parameters can also be matched with [`topic`](EventFilterBuilder::topic).
+    pub fn from_signature(signature: &str) -> Result<Self> {
+        Ok(EventFilterBuilder::new(parse_signature(signature)?))
+    }
+
+    /// Matches logs emitted by any of given addresses.
+    pub fn address(mut self, addresses: Vec<Address>) -> Self {
+        self.addresses = addresses;
+        self
+    }
+
+    /// Matches an indexed parameter by name.
+    pub fn param(mut self, name: &str, matcher: Match) -> Self {
+        self.matchers.push((name.into(), matcher));
+        self
+    }
+
+    /// Matches the indexed parameter at `position` (counting indexed parameters only).
+    pub fn topic(self, position: usize, matcher: Match) -> Self {
+        self.param(&format!("#{}", position), matcher)
+    }
+
+    /// Sets the first block of the range.
+    pub fn from_block(mut self, block: BlockNumber) -> Self {
+        self.from_block = Some(block);
+        self
+    }
+
+    /// Sets the last block of the range.
+    pub fn to_block(mut self, block: BlockNumber) -> Self {
+        self.to_block = Some(block);
+        self
+    }
+
+    /// Returns the RPC filter and a decoder of the matched logs.
+    pub fn build(&self) -> Result<(Filter, EventDecoder)> {
+        let indexed = self
+            .event
+            .inputs
+            .iter()
+            .filter(|param| param.indexed)
+            .collect::<Vec<_>>();
+        let mut topics = vec![Topic::Any, Topic::Any, Topic::Any];
+        for (name, matcher) in &self.matchers {
+            let position = match name.strip_prefix('#') {
+                Some(position) => position.parse::<usize>().ok().filter(|p| *p < indexed.len()),
+                None => indexed.iter().position(|param| &param.name == name),
+            };
+            let position = position.ok_or_else(|| {
+                ethabi::Error::InvalidName(format!("{} has no indexed parameter {}", self.event.name, name))
+            })?;
+            *topics.get_mut(position).ok_or_else(|| {
+                ethabi::Error::InvalidName(format!("{}: only 3 indexed parameters can be matched", name))
+            })? = matcher.clone().into();
+        }
+        let mut topics = topics.into_iter();
+        let filter = self.event.filter(RawTopicFilter {
+            topic0: topics.next().unwrap_or_default(),
+            topic1: topics.next().unwrap_or_default(),
+            topic2: topics.next().unwrap_or_default(),
+        })?;
+
+        let mut builder = FilterBuilder::default().topic_filter(filter);
+        if !self.addresses.is_empty() {
+            builder = builder.address(self.addresses.clone());
+        }
+        if let Some(block) = self.from_block {
+            builder = builder.from_block(block);
+        }
+        if let Some(block) = self.to_block {
+            builder = builder.to_block(block);
+        }
+        let decoder = EventDecoder {
+            event: self.event.clone(),
+            addresses: self.addresses.clone(),
+        };
+        Ok((builder.build(), decoder))
+    }
+}
+
+/// Decodes logs matched by a filter built with `EventFilterBuilder`.
+#[derive(Debug, Clone)]
+pub struct EventDecoder {
+    event: Event,
+    addresses: Vec<Address>,
+}
+
+impl EventDecoder {
+    /// Returns the decoded event.
+    pub fn event(&self) -> &Event {
+        &self.event
+    }
+
+    /// Returns true if the log was emitted by a filtered address and carries the event signature.
+    pub fn matches(&self, log: &Log) -> bool {
+        (self.addresses.is_empty() || self.addresses.contains(&log.address))
+            && (self.event.anonymous || log.topics.first() == Some(&self.event.signature()))
+    }
+
+    /// Decodes the parameters of a log, in the order of the event definition.
+    pub fn decode_log(&self, log: &Log) -> Result<ethabi::Log> {
+        Ok(self.event.parse_log(ethabi::RawLog {
+            topics: log.topics.clone(),
+            data: log.data.0.clone(),
+        })?)
+    }
+
+    /// Decodes the parameters of a log into given type.
+    pub fn decode<R: Detokenize>(&self, log: &Log) -> Result<R> {
+        let log = self.decode_log(log)?;
+        R::from_tokens(log.params.into_iter().map(|param| param.value).collect())
+    }
+}
+
+fn parse_signature(signature: &str) -> Result<Event> {
+    let invalid = || {
+        Error::Abi(ethabi::Error::InvalidName(format!(
+            "Invalid event signature: {}",
+            signature
+        )))
+    };
+    let signature = signature.trim();
+    let signature = signature.strip_prefix("event ").unwrap_or(signature).trim();
+    let (name, params) = signature
+        .strip_suffix(')')
+        .and_then(|signature| signature.split_once('('))
+        .ok_or_else(invalid)?;
+    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return Err(invalid());
+    }
+
+    let mut inputs = vec![];
+    for param in split_params(params) {
+        let mut parts = param.split_whitespace();
+        let kind = match parts.next() {
+            Some(kind) => ethabi::param_type::Reader::read(kind).map_err(|_| invalid())?,
+            None if inputs.is_empty() && params.trim().is_empty() => break,
+            None => return Err(invalid()),
+        };
+        let (indexed, name) = match (parts.next(), parts.next(), parts.next()) {
+            (Some("indexed"), name, None) => (true, name),
+            (name, None, None) => (false, name),
+            _ => return Err(invalid()),
+        };
+        inputs.push(EventParam {
+            // `ethabi` decodes logs by parameter name
+            name: name.map_or_else(|| format!("_{}", inputs.len()), Into::into),
+            kind,
+            indexed,
+        });
+    }
+    Ok(Event {
+        name: name.into(),
+        inputs,
+        anonymous: false,
+    })
+}
+
+/// Splits parameters at top level commas (tuple types contain commas).
+fn split_params(params: &str) -> Vec<&str> {
+    let mut depth = 0;
+    let mut start = 0;
+    let mut result = vec![];
+    for (index, c) in params.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                result.push(&params[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    result.push(&params[start..]);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{H256, U256};
+
+    const TRANSFER: &str = "Transfer(address indexed from, address indexed to, uint256 value)";
+
+    fn topic(address: Address) -> H256 {
+        H256::from(address)
+    }
+
+    #[test]
+    fn should_build_filter_with_addresses_and_alternatives() {
+        let (filter, _) = EventFilterBuilder::from_signature(TRANSFER)
+            .unwrap()
+            .address(vec![Address::repeat_byte(1), Address::repeat_byte(2)])
+            .param(
+                "to",
+                Match::OneOf(vec![
+                    Token::Address(Address::repeat_byte(3)),
+                    Token::Address(Address::repeat_byte(4)),
+                ]),
+            )
+            .from_block(BlockNumber::Number(5.into()))
+            .build()
+            .unwrap();
+
+        let signature: H256 = "ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+            .parse()
+            .unwrap();
+        let expected = FilterBuilder::default()
+            .address(vec![Address::repeat_byte(1), Address::repeat_byte(2)])
+            .topics(
+                Some(vec![signature]),
+                None,
+                Some(vec![topic(Address::repeat_byte(3)), topic(Address::repeat_byte(4))]),
+                None,
+            )
+            .from_block(BlockNumber::Number(5.into()))
+            .build();
+        assert_eq!(filter, expected);
+    }
+
+    #[test]
+    fn should_decode_matching_logs() {
+        let (_, decoder) =
+            EventFilterBuilder::from_signature("event Transfer(address indexed, address indexed, uint256)")
+                .unwrap()
+                .topic(0, Match::Exact(Token::Address(Address::repeat_byte(1))))
+                .build()
+                .unwrap();
+        let log: Log = serde_json::from_value(serde_json::json!({
+            "address": Address::repeat_byte(9),
+            "topics": [
+                decoder.event().signature(),
+                topic(Address::repeat_byte(1)),
+                topic(Address::repeat_byte(2)),
+            ],
+            "data": format!("0x{:064x}", 7),
+            "blockHash": null,
+            "blockNumber": null,
+            "transactionHash": null,
+            "transactionIndex": null,
+            "logIndex": null,
+            "transactionLogIndex": null,
+            "logType": null,
+            "removed": null,
+        }))
+        .unwrap();
+
+        assert!(decoder.matches(&log));
+        let (from, to, value): (Address, Address, U256) = decoder.decode(&log).unwrap();
+        assert_eq!(from, Address::repeat_byte(1));
+        assert_eq!(to, Address::repeat_byte(2));
+        assert_eq!(value, 7.into());
+    }
+
+    #[test]
+    fn should_reject_unknown_params() {
+        let builder = EventFilterBuilder::from_signature(TRANSFER).unwrap();
+
+        assert!(builder.clone().param("value", Match::Any).build().is_err());
+        assert!(builder.topic(2, Match::Any).build().is_err());
+        assert!(EventFilterBuilder::from_signature("Transfer(address indexed from").is_err());
+        assert!(EventFilterBuilder::from_signature("Ping()").is_ok());
+    }
+}
@@ -1,24 +1,36 @@
 //! Ethereum Contract Interface
 
 use crate::{
-    api::{Eth, Namespace},
+    api::{Eth, Namespace, TestNode},
     confirm,
-    contract::tokens::{Detokenize, Tokenize},
-    futures::Future,
+    contract::{
+        dynamic::DynamicValue,
+        tokens::{Detokenize, Tokenizable, Tokenize},
+    },
+    futures::{future::BoxFuture, Future, FutureExt},
     types::{
-        AccessList, Address, BlockId, Bytes, CallRequest, FilterBuilder, TransactionCondition, TransactionReceipt,
-        TransactionRequest, H256, U256, U64,
+        AccessList, Address, BlockId, BlockNumber, Bytes, CallRequest, FilterBuilder, TransactionCondition,
+        TransactionReceipt, TransactionRequest, H256, U256, U64,
     },
     Transport,
 };
-use std::{collections::HashMap, hash::Hash, time};
+use std::{collections::HashMap, hash::Hash, sync::Arc, time};
 
+pub mod artifact;
 pub mod deploy;
+pub mod dynamic;
 pub mod ens;
 mod error;
+pub mod events;
+mod params;
+pub mod sink;
+pub mod state_cache;
+pub mod storage;
 pub mod tokens;
 
-pub use crate::contract::error::Error;
+pub use crate::contract::{
+    deploy::encode_constructor_args, error::Error, params::decode_input, storage::StorageLayout,
+};
 
 /// Contract `Result` type.
 pub type Result<T> = std::result::Result<T, Error>;
@@ -44,6 +56,16 @@ pub struct Options {
     pub max_fee_per_gas: Option<U256>,
     /// miner bribe
     pub max_priority_fee_per_gas: Option<U256>,
+    /// Impersonate `from` in `Contract::call` if the node doesn't control it (Anvil/Hardhat only)
+    pub impersonate: bool,
+    /// Balance set for the impersonated sender
+    pub impersonate_balance: Option<U256>,
+    /// Gas limit resolved at send time, unless `gas` is set
+    pub gas_source: Option<Lazy>,
+    /// Gas price resolved at send time (e.g. by a gas price oracle), unless `gas_price` is set
+    pub gas_price_source: Option<Lazy>,
+    /// Nonce resolved at send time (e.g. by a nonce manager), unless `nonce` is set
+    pub nonce_source: Option<Lazy>,
 }
 
 impl Options {
@@ -56,6 +78,62 @@ impl Options {
         func(&mut options);
         options
     }
+
+    /// Resolves lazy values of fields which aren't set explicitly.
+    ///
+    /// Called by the contract when sending or signing a transaction.
+    pub async fn resolve(mut self) -> crate::error::Result<Options> {
+        async fn resolve(value: &mut Option<U256>, source: &Option<Lazy>) -> crate::error::Result<()> {
+            if let (None, Some(source)) = (&value, source) {
+                *value = Some(source.resolve().await?);
+            }
+            Ok(())
+        }
+
+        resolve(&mut self.gas, &self.gas_source).await?;
+        resolve(&mut self.gas_price, &self.gas_price_source).await?;
+        resolve(&mut self.nonce, &self.nonce_source).await?;
+        Ok(self)
+    }
+}
+
+/// Value of a transaction field resolved at send time.
+///
+/// ```
+/// # use web3::contract::{Lazy, Options};
+/// let options = Options::with(|options| {
+///     options.gas_price_source = Some(Lazy::new(|| async { Ok(10_000_000_000u64.into()) }));
+/// });
+/// ```
+#[derive(Clone)]
+pub struct Lazy(Arc<dyn Fn() -> BoxFuture<'static, crate::error::Result<U256>> + Send + Sync>);
+
+impl Lazy {
+    /// Creates a source calling given function for each transaction.
+    pub fn new<F, R>(source: F) -> Self
+    where
+        F: Fn() -> R + Send + Sync + 'static,
+        R: Future<Output = crate::error::Result<U256>> + Send + 'static,
+    {
+        Lazy(Arc::new(move || source().boxed()))
+    }
+
+    /// Resolves the value.
+    pub async fn resolve(&self) -> crate::error::Result<U256> {
+        (self.0)().await
+    }
+}
+
+impl std::fmt::Debug for Lazy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Lazy")
+    }
+}
+
+impl PartialEq for Lazy {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
 }
 
 /// Ethereum Contract Interface
@@ -64,6 +142,11 @@ pub struct Contract<T: Transport> {
     address: Address,
     eth: Eth<T>,
     abi: ethabi::Contract,
+    storage_layout: Option<StorageLayout>,
+    strict_params: bool,
+    block: Option<BlockId>,
+    #[cfg(feature = "decode-offload")]
+    decode_offload: Option<usize>,
 }
 
 impl<T: Transport> Contract<T> {
@@ -80,6 +163,36 @@ impl<T: Transport> Contract<T> {
         })
     }
 
+    /// Creates deployment builder for a Hardhat or Foundry artifact.
+    ///
+    /// Pass the linked bytecode to `execute`:
+    ///
+    /// ```no_run
+    /// # async fn example<T: web3::Transport>(eth: web3::api::Eth<T>, json: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+    /// use std::collections::HashMap;
+    /// use web3::{contract::{artifact::Artifact, Contract}, types::Address};
+    ///
+    /// let artifact = Artifact::load(json)?;
+    /// let mut libraries = HashMap::new();
+    /// libraries.insert("MyLibrary", Address::from_low_u64_be(0x123));
+    /// let code = artifact.bytecode.link(&libraries)?;
+    /// let contract = Contract::deploy_from_artifact(eth, &artifact)
+    ///     .execute(code, (), Address::zero())
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn deploy_from_artifact(eth: Eth<T>, artifact: &artifact::Artifact) -> deploy::Builder<T> {
+        deploy::Builder {
+            eth,
+            abi: artifact.abi.clone(),
+            options: Options::default(),
+            confirmations: 1,
+            poll_interval: time::Duration::from_secs(7),
+            linker: HashMap::default(),
+        }
+    }
+
     /// test
     pub fn deploy_from_truffle<S>(
         eth: Eth<T>,
@@ -105,7 +218,57 @@ impl<T: Transport> Contract<T> {
 impl<T: Transport> Contract<T> {
     /// Creates new Contract Interface given blockchain address and ABI
     pub fn new(eth: Eth<T>, address: Address, abi: ethabi::Contract) -> Self {
-        Contract { address, eth, abi }
+        Contract {
+            address,
+            eth,
+            abi,
+            storage_layout: None,
+            strict_params: false,
+            block: None,
+            #[cfg(feature = "decode-offload")]
+            decode_offload: None,
+        }
+    }
+
+    /// Returns a view of the contract reading its state at given block.
+    ///
+    /// Queries and storage reads without an explicit block target this block instead of the
+    /// latest one. Storage reads at a block hash look up the number of the block first.
+    pub fn at_block<B: Into<BlockId>>(&self, block: B) -> Self {
+        Contract {
+            block: Some(block.into()),
+            ..self.clone()
+        }
+    }
+
+    /// Returns the default block of queries set with `at_block`.
+    pub fn block(&self) -> Option<BlockId> {
+        self.block
+    }
+
+    /// Attaches the storage layout emitted by solc, enabling `read_storage`.
+    pub fn with_storage_layout(mut self, layout: StorageLayout) -> Self {
+        self.storage_layout = Some(layout);
+        self
+    }
+
+    /// Validates function parameters strictly before encoding.
+    ///
+    /// `bytesN` values have to be exactly `N` bytes long (instead of at most `N`) and unsigned
+    /// integers have to fit their type. Mismatches are reported as `Error::InvalidParams`.
+    pub fn with_strict_params(mut self) -> Self {
+        self.strict_params = true;
+        self
+    }
+
+    /// Decodes `query` results of at least `threshold` bytes on a blocking thread
+    /// (`tokio::task::spawn_blocking`), so that huge outputs don't stall the executor.
+    ///
+    /// Requires a Tokio runtime.
+    #[cfg(feature = "decode-offload")]
+    pub fn with_decode_offload(mut self, threshold: usize) -> Self {
+        self.decode_offload = Some(threshold);
+        self
     }
 
     /// Creates new Contract Interface given blockchain address and JSON containing ABI
@@ -129,7 +292,7 @@ impl<T: Transport> Contract<T> {
     where
         P: Tokenize,
     {
-        let data = self.abi.function(func)?.encode_input(&params.into_tokens())?;
+        let data = self.encode_input(self.abi.function(func)?, params)?;
         let Options {
             gas,
             gas_price,
@@ -140,8 +303,17 @@ impl<T: Transport> Contract<T> {
             access_list,
             max_fee_per_gas,
             max_priority_fee_per_gas,
-        } = options;
-        self.eth
+            impersonate,
+            impersonate_balance,
+            ..
+        } = options.resolve().await?;
+        let test_node = if impersonate {
+            self.impersonate(from, impersonate_balance).await?
+        } else {
+            None
+        };
+        let result = self
+            .eth
             .send_transaction(TransactionRequest {
                 from,
                 to: Some(self.address),
@@ -157,10 +329,40 @@ impl<T: Transport> Contract<T> {
                 max_priority_fee_per_gas,
             })
             .await
-            .map_err(Error::from)
+            .map_err(Error::from);
+        if let Some(test_node) = test_node {
+            // best effort, the node is only used for testing
+            let _ = test_node.stop_impersonating_account(from).await;
+        }
+        result
     }
 
-    /// Execute a contract function and wait for confirmations
+    /// Impersonates `from` unless the node controls it already.
+    ///
+    /// Returns the namespace used for impersonation, `anvil_` methods are tried before `hardhat_` ones.
+    async fn impersonate(&self, from: Address, balance: Option<U256>) -> Result<Option<TestNode<T>>> {
+        if self.eth.accounts().await?.contains(&from) {
+            return Ok(None);
+        }
+        let mut test_node = TestNode::new(self.eth.transport().clone());
+        match test_node.impersonate_account(from).await {
+            Err(crate::Error::Rpc(_)) => {
+                test_node = test_node.hardhat();
+                test_node.impersonate_account(from).await?;
+            }
+            result => result?,
+        }
+        if let Some(balance) = balance {
+            test_node.set_balance(from, balance).await?;
+        }
+        Ok(Some(test_node))
+    }
+
+    /// Execute a contract function, returning a handle to wait for confirmations.
+    ///
+    /// Resolves once the transaction is sent, the receipt is returned by
+    /// [`PendingTransaction::wait`](confirm::PendingTransaction::wait) (polling every second
+    /// unless configured otherwise).
     pub async fn call_with_confirmations(
         &self,
         func: &str,
@@ -168,16 +370,16 @@ impl<T: Transport> Contract<T> {
         from: Address,
         options: Options,
         confirmations: usize,
-    ) -> crate::error::Result<TransactionReceipt> {
-        let poll_interval = time::Duration::from_secs(1);
-
+    ) -> crate::error::Result<confirm::PendingTransaction<T>> {
         let fn_data = self
             .abi
             .function(func)
-            .and_then(|function| function.encode_input(&params.into_tokens()))
+            .map_err(Error::from)
+            .and_then(|function| self.encode_input(function, params))
             // TODO [ToDr] SendTransactionWithConfirmation should support custom error type (so that we can return
             // `contract::Error` instead of more generic `Error`.
-            .map_err(|err| crate::error::Error::Decoder(format!("{:?}", err)))?;
+            .map_err(|err| crate::error::Error::Decoder(format!("{}", err)))?;
+        let options = options.resolve().await?;
         let transaction_request = TransactionRequest {
             from,
             to: Some(self.address),
@@ -192,13 +394,12 @@ impl<T: Transport> Contract<T> {
             max_fee_per_gas: options.max_fee_per_gas,
             max_priority_fee_per_gas: options.max_priority_fee_per_gas,
         };
-        confirm::send_transaction_with_confirmation(
+        let hash = self.eth.send_transaction(transaction_request).await?;
+        Ok(confirm::PendingTransaction::new(
             self.eth.transport().clone(),
-            transaction_request,
-            poll_interval,
+            hash,
             confirmations,
-        )
-        .await
+        ))
     }
 
     /// Estimate gas required for this function call.
@@ -206,7 +407,7 @@ impl<T: Transport> Contract<T> {
     where
         P: Tokenize,
     {
-        let data = self.abi.function(func)?.encode_input(&params.into_tokens())?;
+        let data = self.encode_input(self.abi.function(func)?, params)?;
         self.eth
             .estimate_gas(
                 CallRequest {
@@ -242,18 +443,49 @@ impl<T: Transport> Contract<T> {
         B: Into<Option<BlockId>>,
         P: Tokenize,
     {
+        let block = block.into().or_else(|| self.block());
+        let output = self.query_tokens(func, params.into_tokens(), from.into(), options, block);
+        async move { R::from_tokens(output.await?) }
+    }
+
+    /// Call constant function with parameters and outputs of types known only at runtime.
+    ///
+    /// Parameters are checked against the ABI of the function, like the ones of `query`.
+    pub fn query_dynamic<A, B>(
+        &self,
+        func: &str,
+        params: Vec<DynamicValue>,
+        from: A,
+        options: Options,
+        block: B,
+    ) -> impl Future<Output = Result<Vec<DynamicValue>>> + '_
+    where
+        A: Into<Option<Address>>,
+        B: Into<Option<BlockId>>,
+    {
+        let params: Vec<ethabi::Token> = params.into_iter().map(Into::into).collect();
+        let block = block.into().or_else(|| self.block());
+        let output = self.query_tokens(func, params, from.into(), options, block);
+        async move { Ok(output.await?.into_iter().map(Into::into).collect()) }
+    }
+
+    fn query_tokens(
+        &self,
+        func: &str,
+        params: Vec<ethabi::Token>,
+        from: Option<Address>,
+        options: Options,
+        block: Option<BlockId>,
+    ) -> impl Future<Output = Result<Vec<ethabi::Token>>> + '_ {
         let result = self
             .abi
             .function(func)
-            .and_then(|function| {
-                function
-                    .encode_input(&params.into_tokens())
-                    .map(|call| (call, function))
-            })
+            .map_err(Error::from)
+            .and_then(|function| self.encode_input(function, &params[..]).map(|call| (call, function)))
             .map(|(call, function)| {
                 let call_future = self.eth.call(
                     CallRequest {
-                        from: from.into(),
+                        from,
                         to: Some(self.address),
                         gas: options.gas,
                         gas_price: options.gas_price,
@@ -264,20 +496,75 @@ impl<T: Transport> Contract<T> {
                         max_fee_per_gas: options.max_fee_per_gas,
                         max_priority_fee_per_gas: options.max_priority_fee_per_gas,
                     },
-                    block.into(),
+                    block,
                 );
                 (call_future, function)
             });
         // NOTE for the batch transport to work correctly, we must call `transport.execute` without ever polling the future,
         // hence it cannot be a fully `async` function.
-        async {
+        async move {
             let (call_future, function) = result?;
             let bytes = call_future.await?;
-            let output = function.decode_output(&bytes.0)?;
-            R::from_tokens(output)
+            self.decode_output(function, bytes.0).await
+        }
+    }
+
+    fn encode_input(&self, function: &ethabi::Function, params: impl Tokenize) -> Result<Vec<u8>> {
+        params::encode_input(function, &params.into_tokens(), self.strict_params)
+    }
+
+    #[cfg(not(feature = "decode-offload"))]
+    async fn decode_output(&self, function: &ethabi::Function, bytes: Vec<u8>) -> Result<Vec<ethabi::Token>> {
+        Ok(function.decode_output(&bytes)?)
+    }
+
+    #[cfg(feature = "decode-offload")]
+    async fn decode_output(&self, function: &ethabi::Function, bytes: Vec<u8>) -> Result<Vec<ethabi::Token>> {
+        match self.decode_offload {
+            Some(threshold) if bytes.len() >= threshold => {
+                let function = function.clone();
+                match tokio::task::spawn_blocking(move || function.decode_output(&bytes)).await {
+                    Ok(output) => Ok(output?),
+                    Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+                    Err(_) => Err(crate::Error::Cancelled.into()),
+                }
+            }
+            _ => Ok(function.decode_output(&bytes)?),
         }
     }
 
+    /// Reads a state variable directly from storage with `eth_getStorageAt`.
+    ///
+    /// Requires a storage layout (see `with_storage_layout`) and works with private variables too.
+    /// Only value types are supported; struct members are addressed with dots, e.g. `config.owner`.
+    /// For mappings use `read_storage_with_keys`.
+    pub async fn read_storage<R, B>(&self, path: &str, block: B) -> Result<R>
+    where
+        R: Tokenizable,
+        B: Into<Option<BlockNumber>>,
+    {
+        self.read_storage_with_keys(path, (), block).await
+    }
+
+    /// Reads a state variable nested in mappings, using `keys` for consecutive mapping lookups.
+    ///
+    /// E.g. `read_storage_with_keys::<U256, _, _>("allowances", (owner, spender), None)`.
+    pub async fn read_storage_with_keys<R, K, B>(&self, path: &str, keys: K, block: B) -> Result<R>
+    where
+        R: Tokenizable,
+        K: Tokenize,
+        B: Into<Option<BlockNumber>>,
+    {
+        let layout = self
+            .storage_layout
+            .as_ref()
+            .ok_or_else(|| Error::StorageLayout("Storage layout is not set".into()))?;
+        let location = layout.locate(path, keys.into_tokens())?;
+        let block = block.into().map(BlockId::from).or(self.block);
+        let word = self.eth.storage(self.address, location.slot, block).await?;
+        R::from_token(location.decode(word)?)
+    }
+
     /// Find events matching the topics.
     pub async fn events<A, B, C, R>(&self, event: &str, topic0: A, topic1: B, topic2: C) -> Result<Vec<R>>
     where
@@ -323,6 +610,68 @@ impl<T: Transport> Contract<T> {
             })
             .collect::<Result<Vec<R>>>()
     }
+
+    /// Returns a builder of filters for given event, restricted to logs of this contract.
+    pub fn event_filter(&self, event: &str) -> Result<events::EventFilterBuilder> {
+        let event = self.abi.event(event)?.clone();
+        Ok(events::EventFilterBuilder::new(event).address(vec![self.address]))
+    }
+
+    /// Identifies the function of this contract called with given calldata (e.g. the input of a
+    /// pending transaction) and decodes the parameters.
+    ///
+    /// Returns the name of the function and the decoded parameters, see `contract::decode_input`.
+    pub fn decode_input(&self, data: &[u8]) -> Result<(String, Vec<ethabi::Token>)> {
+        params::decode_input(&self.abi, data)
+    }
+
+    /// Decodes the parameters of a call of given function from calldata.
+    ///
+    /// Fails if the calldata calls a different function.
+    pub fn decode_call<R>(&self, function: &str, data: &[u8]) -> Result<R>
+    where
+        R: Detokenize,
+    {
+        let called = params::find_function(&self.abi, data)?;
+        if called.name != function {
+            return Err(Error::InvalidParams(format!(
+                "calldata calls {}, not {}",
+                called.name, function
+            )));
+        }
+        R::from_tokens(called.decode_input(&data[4..])?)
+    }
+
+    /// Decodes the given event from logs of a transaction receipt.
+    ///
+    /// Logs emitted by other contracts or for other events are skipped. Anonymous events have no
+    /// signature topic, so logs of the contract which can't be decoded as `event` are skipped too.
+    pub fn decode_logs<R>(&self, receipt: &TransactionReceipt, event: &str) -> Result<Vec<R>>
+    where
+        R: Detokenize,
+    {
+        let ev = self.abi.event(event)?;
+        let signature = ev.signature();
+        receipt
+            .logs
+            .iter()
+            .filter(|log| log.address == self.address)
+            .filter(|log| ev.anonymous || log.topics.first() == Some(&signature))
+            .filter_map(|log| {
+                let parsed = ev.parse_log(ethabi::RawLog {
+                    topics: log.topics.clone(),
+                    data: log.data.0.clone(),
+                });
+                match parsed {
+                    Err(_) if ev.anonymous => None,
+                    Err(err) => Some(Err(err.into())),
+                    Ok(log) => Some(R::from_tokens(
+                        log.params.into_iter().map(|x| x.value).collect::<Vec<_>>(),
+                    )),
+                }
+            })
+            .collect()
+    }
 }
 
 #[cfg(feature = "signing")]
@@ -345,10 +694,12 @@ mod contract_signing {
             let fn_data = self
                 .abi
                 .function(func)
-                .and_then(|function| function.encode_input(&params.into_tokens()))
+                .map_err(Error::from)
+                .and_then(|function| self.encode_input(function, params))
                 // TODO [ToDr] SendTransactionWithConfirmation should support custom error type (so that we can return
                 // `contract::Error` instead of more generic `Error`.
-                .map_err(|err| crate::error::Error::Decoder(format!("{:?}", err)))?;
+                .map_err(|err| crate::error::Error::Decoder(format!("{}", err)))?;
+            let options = options.resolve().await?;
             let accounts = Accounts::new(self.eth.transport().clone());
             let mut tx = TransactionParameters {
                 nonce: options.nonce,
@@ -385,9 +736,11 @@ mod contract_signing {
             self.eth.send_raw_transaction(signed.raw_transaction).await
         }
 
-        /// Submit contract call transaction to the transaction pool and wait for the transaction to be included in a block.
+        /// Submit contract call transaction to the transaction pool, returning a handle to wait for confirmations.
         ///
-        /// This function will wait for block inclusion of the transaction before returning.
+        /// Resolves once the transaction is sent, the receipt is returned by
+        /// [`PendingTransaction::wait`](confirm::PendingTransaction::wait) (polling every second
+        /// unless configured otherwise).
         // If you'd rather just submit transaction and receive it's hash, please use [`signed_call`] instead.
         pub async fn signed_call_with_confirmations(
             &self,
@@ -396,29 +749,26 @@ mod contract_signing {
             options: Options,
             confirmations: usize,
             key: impl signing::Key,
-        ) -> crate::Result<TransactionReceipt> {
-            let poll_interval = time::Duration::from_secs(1);
-            let signed = self.sign(func, params, options, key).await?;
-
-            confirm::send_raw_transaction_with_confirmation(
+        ) -> crate::Result<confirm::PendingTransaction<T>> {
+            let hash = self.signed_call(func, params, options, key).await?;
+            Ok(confirm::PendingTransaction::new(
                 self.eth.transport().clone(),
-                signed.raw_transaction,
-                poll_interval,
+                hash,
                 confirmations,
-            )
-            .await
+            ))
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Contract, Options};
+    use super::{Contract, Lazy, Options};
     use crate::{
         api::{self, Namespace},
+        contract::dynamic::DynamicValue,
         rpc,
-        transports::test::TestTransport,
-        types::{Address, BlockId, BlockNumber, H256, U256},
+        transports::test::{self, TestTransport},
+        types::{Address, BlockId, BlockNumber, TransactionReceipt, H256, U256},
         Transport,
     };
 
@@ -519,6 +869,35 @@ mod tests {
         assert_eq!(result, "Hello World!".to_owned());
     }
 
+    #[cfg(feature = "decode-offload")]
+    #[test]
+    fn should_decode_large_output_on_blocking_thread() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.set_response(rpc::Value::String("0x0000000000000000000000000000000000000000000000000000000000000020000000000000000000000000000000000000000000000000000000000000000c48656c6c6f20576f726c64210000000000000000000000000000000000000000".into()));
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+
+        let result: String = {
+            let token = contract(&transport).with_decode_offload(32);
+
+            // when
+            runtime
+                .block_on(token.query("name", (), None, Options::default(), None))
+                .unwrap()
+        };
+
+        // then
+        transport.assert_request(
+            "eth_call",
+            &[
+                "{\"data\":\"0x06fdde03\",\"to\":\"0x0000000000000000000000000000000000000001\"}".into(),
+                "\"latest\"".into(),
+            ],
+        );
+        transport.assert_no_more_requests();
+        assert_eq!(result, "Hello World!".to_owned());
+    }
+
     #[test]
     fn should_call_a_contract_function() {
         // given
@@ -539,6 +918,65 @@ mod tests {
         assert_eq!(result, H256::from_low_u64_be(5));
     }
 
+    #[test]
+    fn should_return_pending_transaction_before_confirmation() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.add_response(rpc::Value::String(format!("{:?}", H256::from_low_u64_be(5))));
+        transport.add_response(serde_json::json!(crate::types::TransactionReceipt {
+            transaction_hash: H256::from_low_u64_be(5),
+            block_number: Some(1.into()),
+            ..Default::default()
+        }));
+
+        let shared = transport.clone();
+        let token = contract(&shared);
+
+        // when
+        let pending = futures::executor::block_on(token.call_with_confirmations(
+            "name",
+            (),
+            Address::from_low_u64_be(5),
+            Options::default(),
+            0,
+        ))
+        .unwrap();
+
+        // then
+        assert_eq!(pending.hash(), H256::from_low_u64_be(5));
+        transport.assert_request("eth_sendTransaction", &["{\"data\":\"0x06fdde03\",\"from\":\"0x0000000000000000000000000000000000000005\",\"to\":\"0x0000000000000000000000000000000000000001\"}".into()]);
+        transport.assert_no_more_requests();
+        let receipt = futures::executor::block_on(pending.wait()).unwrap();
+        assert_eq!(receipt.transaction_hash, H256::from_low_u64_be(5));
+        transport.assert_request(
+            "eth_getTransactionReceipt",
+            &["\"0x0000000000000000000000000000000000000000000000000000000000000005\"".into()],
+        );
+    }
+
+    #[test]
+    fn should_resolve_lazy_options_at_send_time() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.set_response(rpc::Value::String(format!("{:?}", H256::from_low_u64_be(5))));
+        let options = Options::with(|options| {
+            options.gas_price = Some(3.into());
+            options.gas_price_source = Some(Lazy::new(|| async { unreachable!("gas price is set") }));
+            options.nonce_source = Some(Lazy::new(|| async { Ok(7.into()) }));
+        });
+
+        {
+            let token = contract(&transport);
+
+            // when
+            futures::executor::block_on(token.call("name", (), Address::from_low_u64_be(5), options)).unwrap();
+        }
+
+        // then
+        transport.assert_request("eth_sendTransaction", &["{\"data\":\"0x06fdde03\",\"from\":\"0x0000000000000000000000000000000000000005\",\"gasPrice\":\"0x3\",\"nonce\":\"0x7\",\"to\":\"0x0000000000000000000000000000000000000001\"}".into()]);
+        transport.assert_no_more_requests();
+    }
+
     #[test]
     fn should_estimate_gas_usage() {
         // given
@@ -586,4 +1024,304 @@ mod tests {
         transport.assert_no_more_requests();
         assert_eq!(result, 0x20.into());
     }
+
+    #[test]
+    fn should_query_at_default_block() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.add_response(rpc::Value::String(
+            "0x0000000000000000000000000000000000000000000000000000000000000020".into(),
+        ));
+        transport.add_response(rpc::Value::String(
+            "0x0000000000000000000000000000000000000000000000000000000000000021".into(),
+        ));
+
+        let (historical, latest): (U256, U256) = {
+            let token = contract(&transport).at_block(BlockNumber::Number(0x10.into()));
+
+            // when
+            let historical =
+                futures::executor::block_on(token.query("balanceOf", Address::zero(), None, Options::default(), None));
+            let latest = futures::executor::block_on(token.query(
+                "balanceOf",
+                Address::zero(),
+                None,
+                Options::default(),
+                BlockId::Number(BlockNumber::Latest),
+            ));
+            (historical.unwrap(), latest.unwrap())
+        };
+
+        // then
+        let call = "{\"data\":\"0x70a082310000000000000000000000000000000000000000000000000000000000000000\",\"to\":\"0x0000000000000000000000000000000000000001\"}";
+        transport.assert_request("eth_call", &[call.into(), "\"0x10\"".into()]);
+        transport.assert_request("eth_call", &[call.into(), "\"latest\"".into()]);
+        transport.assert_no_more_requests();
+        assert_eq!((historical, latest), (0x20.into(), 0x21.into()));
+    }
+
+    #[cfg(feature = "signing")]
+    #[test]
+    fn should_return_pending_signed_call() {
+        use crate::signing::{SecretKey, SecretKeyRef};
+
+        // given
+        let mut transport = TestTransport::default();
+        transport.add_method_response("eth_chainId", rpc::Value::String("0x1".into()));
+        transport.add_method_response(
+            "eth_sendRawTransaction",
+            rpc::Value::String(format!("{:?}", H256::from_low_u64_be(5))),
+        );
+        let key = SecretKey::from_slice(&[1; 32]).unwrap();
+        let options = Options::with(|options| {
+            options.gas = Some(100_000.into());
+            options.gas_price = Some(1.into());
+            options.nonce = Some(0.into());
+        });
+
+        // when
+        let pending = futures::executor::block_on(contract(&transport).signed_call_with_confirmations(
+            "transfer",
+            (Address::from_low_u64_be(2), U256::from(1)),
+            options,
+            1,
+            SecretKeyRef::new(&key),
+        ))
+        .unwrap();
+
+        // then
+        assert_eq!(pending.hash(), H256::from_low_u64_be(5));
+        assert_eq!(transport.requests_of("eth_sendRawTransaction").len(), 1);
+        assert!(transport.requests_of("eth_getTransactionReceipt").is_empty());
+    }
+
+    #[test]
+    fn should_query_dynamic() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.set_response(rpc::Value::String(
+            "0x0000000000000000000000000000000000000000000000000000000000000020".into(),
+        ));
+
+        let result = {
+            let token = contract(&transport);
+
+            // when
+            futures::executor::block_on(token.query_dynamic(
+                "balanceOf",
+                vec![DynamicValue::Address(Address::from_low_u64_be(5))],
+                None,
+                Options::default(),
+                None,
+            ))
+            .unwrap()
+        };
+
+        // then
+        transport.assert_request("eth_call", &["{\"data\":\"0x70a082310000000000000000000000000000000000000000000000000000000000000005\",\"to\":\"0x0000000000000000000000000000000000000001\"}".into(), "\"latest\"".into()]);
+        transport.assert_no_more_requests();
+        assert_eq!(result, vec![DynamicValue::Uint(0x20.into())]);
+    }
+
+    #[test]
+    fn should_read_storage() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.set_response(rpc::Value::String(
+            "0x0000000000000000000000000000000000000000000000000000000000000020".into(),
+        ));
+        let layout = super::StorageLayout::load(
+            br#"{
+              "storage": [{"label": "totalSupply", "offset": 0, "slot": "3", "type": "t_uint256"}],
+              "types": {"t_uint256": {"encoding": "inplace", "label": "uint256", "numberOfBytes": "32"}}
+            }"#,
+        )
+        .unwrap();
+
+        let result: U256 = {
+            let token = contract(&transport).with_storage_layout(layout);
+
+            // when
+            futures::executor::block_on(token.read_storage("totalSupply", BlockNumber::Number(1.into()))).unwrap()
+        };
+
+        // then
+        transport.assert_request(
+            "eth_getStorageAt",
+            &[
+                "\"0x0000000000000000000000000000000000000001\"".into(),
+                "\"0x3\"".into(),
+                "\"0x1\"".into(),
+            ],
+        );
+        transport.assert_no_more_requests();
+        assert_eq!(result, 0x20.into());
+    }
+
+    #[test]
+    fn should_read_storage_at_block_hash() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.set_response(rpc::Value::String(
+            "0x0000000000000000000000000000000000000000000000000000000000000020".into(),
+        ));
+        let layout = super::StorageLayout::load(
+            br#"{
+              "storage": [{"label": "totalSupply", "offset": 0, "slot": "3", "type": "t_uint256"}],
+              "types": {"t_uint256": {"encoding": "inplace", "label": "uint256", "numberOfBytes": "32"}}
+            }"#,
+        )
+        .unwrap();
+        let token = contract(&transport).with_storage_layout(layout);
+
+        // when
+        let result: U256 = futures::executor::block_on(
+            token
+                .at_block(H256::from_low_u64_be(2))
+                .read_storage("totalSupply", None),
+        )
+        .unwrap();
+
+        // then
+        assert_eq!(result, 0x20.into());
+        transport.assert_request(
+            "eth_getStorageAt",
+            &[
+                "\"0x0000000000000000000000000000000000000001\"".into(),
+                "\"0x3\"".into(),
+                r#"{"blockHash":"0x0000000000000000000000000000000000000000000000000000000000000002"}"#.into(),
+            ],
+        );
+        transport.assert_no_more_requests();
+    }
+
+    #[test]
+    fn should_impersonate_sender() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.add_response(rpc::Value::Array(vec![]));
+        transport.add_error(crate::Error::Rpc(rpc::Error::method_not_found()));
+        transport.add_response(rpc::Value::Null);
+        transport.add_response(rpc::Value::Null);
+        transport.add_response(rpc::Value::String(format!("{:?}", H256::from_low_u64_be(5))));
+        transport.add_response(rpc::Value::Null);
+
+        let result = {
+            let token = contract(&transport);
+            let options = Options::with(|options| {
+                options.impersonate = true;
+                options.impersonate_balance = Some(0x10.into());
+            });
+
+            // when
+            futures::executor::block_on(token.call("name", (), Address::from_low_u64_be(5), options)).unwrap()
+        };
+
+        // then
+        let sender = r#""0x0000000000000000000000000000000000000005""#;
+        transport.assert_request("eth_accounts", &[]);
+        transport.assert_request("anvil_impersonateAccount", &[sender.into()]);
+        transport.assert_request("hardhat_impersonateAccount", &[sender.into()]);
+        transport.assert_request("hardhat_setBalance", &[sender.into(), r#""0x10""#.into()]);
+        transport.assert_request("eth_sendTransaction", &["{\"data\":\"0x06fdde03\",\"from\":\"0x0000000000000000000000000000000000000005\",\"to\":\"0x0000000000000000000000000000000000000001\"}".into()]);
+        transport.assert_request("hardhat_stopImpersonatingAccount", &[sender.into()]);
+        transport.assert_no_more_requests();
+        assert_eq!(result, H256::from_low_u64_be(5));
+    }
+
+    #[test]
+    fn should_decode_receipt_logs() {
+        // given
+        let transport = TestTransport::default();
+        let token = contract(&transport);
+        let transfer = token.abi().event("Transfer").unwrap().signature();
+        let log = |address: u64, topic: H256| {
+            serde_json::from_value(serde_json::json!({
+                "address": Address::from_low_u64_be(address),
+                "topics": [topic, H256::from_low_u64_be(2), H256::from_low_u64_be(3)],
+                "data": "0x0000000000000000000000000000000000000000000000000000000000000010",
+            }))
+            .unwrap()
+        };
+        let receipt = TransactionReceipt {
+            logs: vec![log(1, transfer), log(2, transfer), log(1, H256::from_low_u64_be(4))],
+            ..Default::default()
+        };
+
+        // when
+        let result: Vec<(Address, Address, U256)> = token.decode_logs(&receipt, "Transfer").unwrap();
+
+        // then
+        transport.assert_no_more_requests();
+        assert_eq!(
+            result,
+            vec![(Address::from_low_u64_be(2), Address::from_low_u64_be(3), 0x10.into())]
+        );
+    }
+
+    #[test]
+    fn should_skip_undecodable_logs_of_anonymous_events() {
+        // given
+        let transport = TestTransport::default();
+        let abi = br#"[{
+            "type": "event",
+            "name": "Deposit",
+            "anonymous": true,
+            "inputs": [
+                {"name": "owner", "type": "address", "indexed": true},
+                {"name": "amount", "type": "uint256", "indexed": false}
+            ]
+        }]"#;
+        let contract = Contract::from_json(api::Eth::new(&transport), Address::from_low_u64_be(1), abi).unwrap();
+        let log = |topics: Vec<H256>, data: &str| {
+            serde_json::from_value(serde_json::json!({
+                "address": Address::from_low_u64_be(1),
+                "topics": topics,
+                "data": data,
+            }))
+            .unwrap()
+        };
+        let amount = "0x0000000000000000000000000000000000000000000000000000000000000010";
+        let receipt = TransactionReceipt {
+            logs: vec![
+                log(vec![H256::from_low_u64_be(4), H256::from_low_u64_be(2)], amount),
+                log(vec![H256::from_low_u64_be(2)], amount),
+                log(vec![H256::from_low_u64_be(3)], "0x"),
+            ],
+            ..Default::default()
+        };
+
+        // when
+        let result: Vec<(Address, U256)> = contract.decode_logs(&receipt, "Deposit").unwrap();
+
+        // then
+        transport.assert_no_more_requests();
+        assert_eq!(result, vec![(Address::from_low_u64_be(2), 0x10.into())]);
+    }
+
+    #[test]
+    fn should_decode_input() {
+        // given
+        let transport = TestTransport::default();
+        let token = contract(&transport);
+        let to = Address::from_low_u64_be(2);
+        let data = token
+            .abi()
+            .function("transfer")
+            .unwrap()
+            .encode_input(&[ethabi::Token::Address(to), ethabi::Token::Uint(5.into())])
+            .unwrap();
+
+        // when
+        let (function, params) = token.decode_input(&data).unwrap();
+        let call: (Address, U256) = token.decode_call("transfer", &data).unwrap();
+
+        // then
+        assert_eq!(function, "transfer");
+        assert_eq!(params, vec![ethabi::Token::Address(to), ethabi::Token::Uint(5.into())]);
+        assert_eq!(call, (to, 5.into()));
+        assert!(token.decode_call::<(Address, U256)>("approve", &data).is_err());
+        assert!(token.decode_input(&[0xde, 0xad, 0xbe, 0xef]).is_err());
+        assert!(super::decode_input(token.abi(), &data[..3]).is_err());
+    }
 }
@@ -0,0 +1,193 @@
+//! Validation of function parameters before encoding, and decoding of calldata.
+
+use crate::contract::{Error, Result};
+use ethabi::{Contract, Function, Param, ParamType, Token};
+
+/// Encodes a function call, reporting mismatching parameters positionally.
+///
+/// Besides the checks done by `ethabi`, strict mode requires `bytesN` values to have exactly `N`
+/// bytes (instead of at most `N`) and unsigned integers to fit their type.
+pub(crate) fn encode_input(function: &Function, tokens: &[Token], strict: bool) -> Result<Vec<u8>> {
+    check(&function.inputs, tokens, strict)
+        .map_err(|err| Error::InvalidParams(format!("{}: {}", function.name, err)))?;
+    Ok(function.encode_input(tokens)?)
+}
+
+/// Identifies the function called with given calldata by its selector and decodes the parameters.
+///
+/// Returns the name of the function and the decoded parameters. Fails if the ABI has no function
+/// with the selector or the parameters don't match it.
+pub fn decode_input(abi: &Contract, data: &[u8]) -> Result<(String, Vec<Token>)> {
+    let function = find_function(abi, data)?;
+    Ok((function.name.clone(), function.decode_input(&data[4..])?))
+}
+
+/// Returns the function of the ABI with the selector of given calldata.
+pub(crate) fn find_function<'a>(abi: &'a Contract, data: &[u8]) -> Result<&'a Function> {
+    if data.len() < 4 {
+        return Err(Error::InvalidParams(format!(
+            "calldata of {} bytes has no function selector",
+            data.len()
+        )));
+    }
+    abi.functions()
+        .find(|function| function.short_signature()[..] == data[..4])
+        .ok_or_else(|| {
+            Error::Abi(ethabi::Error::InvalidName(format!(
+                "No function with selector 0x{}",
+                hex::encode(&data[..4])
+            )))
+        })
+}
+
+fn check(params: &[Param], tokens: &[Token], strict: bool) -> std::result::Result<(), String> {
+    if params.len() != tokens.len() {
+        let types = |kinds: Vec<String>| kinds.join(", ");
+        return Err(format!(
+            "expected {} params ({}), got {} ({})",
+            params.len(),
+            types(params.iter().map(|param| param.kind.to_string()).collect()),
+            tokens.len(),
+            types(tokens.iter().map(describe).collect()),
+        ));
+    }
+    for (index, (param, token)) in params.iter().zip(tokens).enumerate() {
+        let path = if param.name.is_empty() {
+            format!("param {}", index + 1)
+        } else {
+            format!("param {} ({})", index + 1, param.name)
+        };
+        check_token(&path, &param.kind, token, strict)?;
+    }
+    Ok(())
+}
+
+fn check_token(path: &str, kind: &ParamType, token: &Token, strict: bool) -> std::result::Result<(), String> {
+    let mismatch = || Err(format!("{}: expected {}, got {}", path, kind, describe(token)));
+    match (kind, token) {
+        (ParamType::Address, Token::Address(_))
+        | (ParamType::Bool, Token::Bool(_))
+        | (ParamType::String, Token::String(_))
+        | (ParamType::Bytes, Token::Bytes(_))
+        | (ParamType::Int(_), Token::Int(_)) => Ok(()),
+        (ParamType::Uint(size), Token::Uint(value)) if strict && value.bits() > *size => {
+            Err(format!("{}: expected {}, got {} which doesn't fit", path, kind, value))
+        }
+        (ParamType::Uint(_), Token::Uint(_)) => Ok(()),
+        (ParamType::FixedBytes(size), Token::FixedBytes(bytes))
+            if bytes.len() > *size || strict && bytes.len() != *size =>
+        {
+            mismatch()
+        }
+        (ParamType::FixedBytes(_), Token::FixedBytes(_)) => Ok(()),
+        (ParamType::Array(inner), Token::Array(tokens)) => tokens
+            .iter()
+            .enumerate()
+            .try_for_each(|(index, token)| check_token(&format!("{}[{}]", path, index), inner, token, strict)),
+        (ParamType::FixedArray(inner, size), Token::FixedArray(tokens)) if tokens.len() == *size => tokens
+            .iter()
+            .enumerate()
+            .try_for_each(|(index, token)| check_token(&format!("{}[{}]", path, index), inner, token, strict)),
+        // `ethabi` doesn't check the length of tuples.
+        (ParamType::Tuple(kinds), Token::Tuple(tokens)) if tokens.len() == kinds.len() => kinds
+            .iter()
+            .zip(tokens)
+            .enumerate()
+            .try_for_each(|(index, (kind, token))| check_token(&format!("{}.{}", path, index), kind, token, strict)),
+        _ => mismatch(),
+    }
+}
+
+/// Describes the type of a token, as far as it can be told from the value.
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Address(_) => "address".into(),
+        Token::Bool(_) => "bool".into(),
+        Token::String(_) => "string".into(),
+        Token::Int(_) => "int".into(),
+        Token::Uint(_) => "uint".into(),
+        Token::Bytes(bytes) => format!("bytes of length {}", bytes.len()),
+        Token::FixedBytes(bytes) => format!("bytes{}", bytes.len()),
+        Token::Array(tokens) => format!("array of {} elements", tokens.len()),
+        Token::FixedArray(tokens) => format!("fixed array of {} elements", tokens.len()),
+        Token::Tuple(tokens) => format!("tuple of {} elements", tokens.len()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Address, U256};
+
+    #[allow(deprecated)]
+    fn function(inputs: Vec<(&str, ParamType)>) -> Function {
+        Function {
+            name: "test".into(),
+            inputs: inputs
+                .into_iter()
+                .map(|(name, kind)| Param {
+                    name: name.into(),
+                    kind,
+                    internal_type: None,
+                })
+                .collect(),
+            outputs: vec![],
+            constant: None,
+            state_mutability: ethabi::StateMutability::View,
+        }
+    }
+
+    fn error(result: Result<Vec<u8>>) -> String {
+        match result {
+            Err(Error::InvalidParams(message)) => message,
+            other => panic!("Expected invalid params, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn should_report_mismatching_params_positionally() {
+        let function = function(vec![
+            ("", ParamType::Address),
+            ("interfaceId", ParamType::FixedBytes(4)),
+        ]);
+
+        let count = encode_input(&function, &[Token::Address(Address::zero())], false);
+        let kind = encode_input(
+            &function,
+            &[Token::Address(Address::zero()), Token::Bytes(vec![0; 10])],
+            false,
+        );
+
+        assert_eq!(
+            error(count),
+            "test: expected 2 params (address, bytes4), got 1 (address)"
+        );
+        assert_eq!(
+            error(kind),
+            "test: param 2 (interfaceId): expected bytes4, got bytes of length 10"
+        );
+    }
+
+    #[test]
+    fn should_apply_extra_checks_in_strict_mode() {
+        let function = function(vec![
+            ("", ParamType::FixedBytes(4)),
+            ("", ParamType::Array(Box::new(ParamType::Uint(8)))),
+        ]);
+        let tokens = [
+            Token::FixedBytes(vec![1, 2]),
+            Token::Array(vec![Token::Uint(1.into()), Token::Uint(U256::from(256))]),
+        ];
+
+        assert!(encode_input(&function, &tokens, false).is_ok());
+        assert_eq!(
+            error(encode_input(&function, &tokens, true)),
+            "test: param 1: expected bytes4, got bytes2"
+        );
+        let tokens = [Token::FixedBytes(vec![1, 2, 3, 4]), tokens[1].clone()];
+        assert_eq!(
+            error(encode_input(&function, &tokens, true)),
+            "test: param 2[1]: expected uint8, got 256 which doesn't fit"
+        );
+    }
+}
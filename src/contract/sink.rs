@@ -0,0 +1,390 @@
+//! Indexing contract events into a storage backend.
+//!
+//! Implement `EventSink` for the storage and let `EventIndexer` take care of fetching, decoding,
+//! batching, retries and resuming.
+
+use crate::{
+    contract::{Contract, Error, Result},
+    helpers::{backoff::Backoff, resolve_block, LogPager},
+    types::{BlockNumber, Filter, FilterBuilder, Log, LogCheckpoint, H256},
+    Transport,
+};
+use futures::{future::BoxFuture, StreamExt};
+use futures_timer::Delay;
+use std::collections::HashMap;
+
+/// Error returned by an `EventSink`.
+pub type SinkError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Event decoded from a log, together with the log it was decoded from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedEvent {
+    /// Event name
+    pub name: String,
+    /// Decoded parameters
+    pub params: Vec<ethabi::LogParam>,
+    /// Original log (block, transaction and log index)
+    pub log: Log,
+}
+
+/// Batch of events handed to an `EventSink`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventBatch {
+    /// Events in chain order
+    pub events: Vec<DecodedEvent>,
+    /// Checkpoint after the last event of the batch
+    pub checkpoint: LogCheckpoint,
+}
+
+/// Storage backend of an `EventIndexer`.
+///
+/// For exactly-once delivery `handle` has to store the events and the checkpoint atomically (e.g.
+/// in a single database transaction) and `checkpoint` has to return the last stored checkpoint.
+/// A batch which failed to be stored is handed over again on retry. Once the whole block range
+/// was fetched, the last batch's checkpoint is moved to the end of the range, so this batch may
+/// have no events if there were none since the previous one.
+pub trait EventSink {
+    /// Returns the checkpoint stored with the last batch, `None` if nothing was stored yet.
+    fn checkpoint(&mut self) -> BoxFuture<'_, std::result::Result<Option<LogCheckpoint>, SinkError>>;
+
+    /// Stores a batch of events together with its checkpoint.
+    fn handle<'a>(&'a mut self, batch: &'a EventBatch) -> BoxFuture<'a, std::result::Result<(), SinkError>>;
+}
+
+/// Feeds events of a contract into an `EventSink`.
+///
+/// Logs are fetched with `helpers::LogPager` starting after the sink's checkpoint, so `run` can be
+/// called repeatedly (e.g. on every new block) to keep following the chain.
+#[derive(Debug, Clone)]
+pub struct EventIndexer<T: Transport> {
+    contract: Contract<T>,
+    from_block: BlockNumber,
+    to_block: BlockNumber,
+    batch_size: usize,
+    chunk_size: u64,
+    max_retries: usize,
+    backoff: Backoff,
+}
+
+impl<T: Transport> EventIndexer<T> {
+    /// Creates an indexer of all (non-anonymous) events of given contract, from the earliest to
+    /// the latest block.
+    pub fn new(contract: Contract<T>) -> Self {
+        EventIndexer {
+            contract,
+            from_block: BlockNumber::Earliest,
+            to_block: BlockNumber::Latest,
+            batch_size: 1_000,
+            chunk_size: 10_000,
+            max_retries: 3,
+            backoff: Backoff::default(),
+        }
+    }
+
+    /// Sets the block range to index, `from` is ignored if the sink's checkpoint is further.
+    pub fn block_range(mut self, from: BlockNumber, to: BlockNumber) -> Self {
+        self.from_block = from;
+        self.to_block = to;
+        self
+    }
+
+    /// Sets the maximal number of events handed to the sink at once.
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Sets the initial number of blocks queried at once.
+    pub fn chunk_size(mut self, chunk_size: u64) -> Self {
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    /// Sets how many times a failure (of the node or the sink) is retried in a row, and the delays
+    /// between attempts.
+    pub fn retry<B: Into<Backoff>>(mut self, max_retries: usize, backoff: B) -> Self {
+        self.max_retries = max_retries;
+        self.backoff = backoff.into();
+        self
+    }
+
+    /// Indexes the block range into the sink, returning the last checkpoint.
+    pub async fn run<S: EventSink>(&self, sink: &mut S) -> Result<Option<LogCheckpoint>> {
+        let mut checkpoint = sink.checkpoint().await.map_err(Error::Sink)?;
+        let mut attempts = 0;
+        let mut delays = self.backoff.delays();
+        loop {
+            let last = checkpoint.clone();
+            match self.run_once(sink, &mut checkpoint).await {
+                Ok(()) => return Ok(checkpoint),
+                Err(err) => {
+                    // only consecutive failures count
+                    if checkpoint != last {
+                        attempts = 0;
+                        delays = self.backoff.delays();
+                    }
+                    let delay = match delays.next() {
+                        Some(delay) if attempts < self.max_retries => delay,
+                        _ => return Err(err),
+                    };
+                    attempts += 1;
+                    event!(warn, "Indexing failed ({}), retrying from {:?}", err, checkpoint);
+                    Delay::new(delay).await;
+                }
+            }
+        }
+    }
+
+    async fn run_once<S: EventSink>(&self, sink: &mut S, checkpoint: &mut Option<LogCheckpoint>) -> Result<()> {
+        let events: HashMap<H256, &ethabi::Event> = self
+            .contract
+            .abi
+            .events()
+            .filter(|event| !event.anonymous)
+            .map(|event| (event.signature(), event))
+            .collect();
+        // resolve the end of the range once, so that the checkpoint can be moved past it
+        let to_block = resolve_block(&self.contract.eth, Some(self.to_block)).await?;
+        let end = LogCheckpoint::after_block(to_block);
+        if checkpoint
+            .as_ref()
+            .is_some_and(|checkpoint| checkpoint.last >= end.last)
+        {
+            return Ok(());
+        }
+        let filter = self.filter(events.keys().copied().collect(), to_block);
+        let mut pager = LogPager::new(self.contract.eth.clone(), filter).chunk_size(self.chunk_size);
+        if let Some(checkpoint) = checkpoint.clone() {
+            pager = pager.resume_from(checkpoint);
+        }
+
+        let logs = pager.stream();
+        futures::pin_mut!(logs);
+        let mut batch = Vec::new();
+        while let Some(log) = logs.next().await {
+            let log = log?;
+            let event = match log.topics.first().and_then(|topic| events.get(topic)) {
+                Some(event) => event,
+                None => continue,
+            };
+            let params = event
+                .parse_log(ethabi::RawLog {
+                    topics: log.topics.clone(),
+                    data: log.data.0.clone(),
+                })?
+                .params;
+            batch.push(DecodedEvent {
+                name: event.name.clone(),
+                params,
+                log,
+            });
+            if batch.len() >= self.batch_size {
+                let events = std::mem::take(&mut batch);
+                let last = events.last().and_then(|event| LogCheckpoint::after_log(&event.log));
+                let last =
+                    last.ok_or_else(|| crate::Error::InvalidResponse("log without block number or index".into()))?;
+                flush(sink, events, last, checkpoint).await?;
+            }
+        }
+        // blocks after the last log had no events either
+        flush(sink, batch, end, checkpoint).await
+    }
+
+    fn filter(&self, signatures: Vec<H256>, to_block: u64) -> Filter {
+        FilterBuilder::default()
+            .address(vec![self.contract.address])
+            .topics(Some(signatures), None, None, None)
+            .from_block(self.from_block)
+            .to_block(BlockNumber::Number(to_block.into()))
+            .build()
+    }
+}
+
+async fn flush<S: EventSink>(
+    sink: &mut S,
+    events: Vec<DecodedEvent>,
+    last: LogCheckpoint,
+    checkpoint: &mut Option<LogCheckpoint>,
+) -> Result<()> {
+    let batch = EventBatch {
+        events,
+        checkpoint: last,
+    };
+    sink.handle(&batch).await.map_err(Error::Sink)?;
+    *checkpoint = Some(batch.checkpoint);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{api::Namespace, rpc, transports::test::TestTransport, types::LogKey};
+
+    const ABI: &str = r#"[{"anonymous":false,"inputs":[{"indexed":false,"name":"value","type":"uint256"}],"name":"Stored","type":"event"}]"#;
+
+    #[derive(Default)]
+    struct MemorySink {
+        batches: Vec<EventBatch>,
+        failures: usize,
+    }
+
+    impl EventSink for MemorySink {
+        fn checkpoint(&mut self) -> BoxFuture<'_, std::result::Result<Option<LogCheckpoint>, SinkError>> {
+            Box::pin(async move { Ok(self.batches.last().map(|batch| batch.checkpoint.clone())) })
+        }
+
+        fn handle<'a>(&'a mut self, batch: &'a EventBatch) -> BoxFuture<'a, std::result::Result<(), SinkError>> {
+            Box::pin(async move {
+                if self.failures > 0 {
+                    self.failures -= 1;
+                    return Err("database is locked".into());
+                }
+                self.batches.push(batch.clone());
+                Ok(())
+            })
+        }
+    }
+
+    fn indexer(transport: &TestTransport) -> EventIndexer<TestTransport> {
+        let abi = ethabi::Contract::load(ABI.as_bytes()).unwrap();
+        let contract = Contract::new(crate::api::Eth::new(transport.clone()), Default::default(), abi);
+        EventIndexer::new(contract)
+            .block_range(BlockNumber::Number(0.into()), BlockNumber::Number(9.into()))
+            .batch_size(2)
+            .retry(1, std::time::Duration::from_secs(0))
+    }
+
+    fn stored(value: u64, block: u64, index: u64) -> rpc::Value {
+        let signature = ethabi::Contract::load(ABI.as_bytes())
+            .unwrap()
+            .event("Stored")
+            .unwrap()
+            .signature();
+        serde_json::json!({
+            "address": "0x0000000000000000000000000000000000000000",
+            "topics": [signature],
+            "data": format!("0x{:064x}", value),
+            "blockNumber": format!("{:#x}", block),
+            "logIndex": format!("{:#x}", index),
+        })
+    }
+
+    fn values(batch: &EventBatch) -> Vec<ethabi::Token> {
+        batch.events.iter().map(|event| event.params[0].value.clone()).collect()
+    }
+
+    fn uints(values: &[u64]) -> Vec<ethabi::Token> {
+        values.iter().map(|&value| ethabi::Token::Uint(value.into())).collect()
+    }
+
+    #[test]
+    fn should_index_events_in_batches() {
+        let mut transport = TestTransport::default();
+        transport.add_response(rpc::Value::Array(vec![
+            stored(1, 1, 0),
+            stored(2, 1, 1),
+            stored(3, 4, 0),
+        ]));
+        let mut sink = MemorySink::default();
+
+        let checkpoint = futures::executor::block_on(indexer(&transport).run(&mut sink)).unwrap();
+
+        assert_eq!(sink.batches.len(), 2);
+        assert_eq!(sink.batches[0].events[0].name, "Stored");
+        assert_eq!(values(&sink.batches[0]), uints(&[1, 2]));
+        assert_eq!(values(&sink.batches[1]), uints(&[3]));
+        assert_eq!(
+            checkpoint.map(|checkpoint| checkpoint.last),
+            Some(LogKey {
+                block_number: 9,
+                log_index: u64::MAX
+            })
+        );
+        assert_eq!(transport.requests().len(), 1);
+    }
+
+    #[test]
+    fn should_advance_checkpoint_past_empty_ranges() {
+        let mut transport = TestTransport::default();
+        transport.add_response(rpc::Value::Array(vec![]));
+        let mut sink = MemorySink::default();
+        let indexer = indexer(&transport);
+
+        let checkpoint = futures::executor::block_on(indexer.run(&mut sink)).unwrap();
+        // the range was indexed already
+        let again = futures::executor::block_on(indexer.run(&mut sink)).unwrap();
+
+        assert_eq!(checkpoint, Some(LogCheckpoint::after_block(9)));
+        assert_eq!(again, checkpoint);
+        assert_eq!(sink.batches.len(), 1);
+        assert!(sink.batches[0].events.is_empty());
+        assert_eq!(transport.requests().len(), 1);
+    }
+
+    #[test]
+    fn should_be_send() {
+        fn assert_send<T: Send>(_: &T) {}
+
+        let transport = TestTransport::default();
+        let mut sink = MemorySink::default();
+        let indexer = indexer(&transport);
+
+        assert_send(&indexer.run(&mut sink));
+    }
+
+    #[test]
+    fn should_retry_from_last_checkpoint() {
+        let mut transport = TestTransport::default();
+        let logs = rpc::Value::Array(vec![stored(1, 1, 0), stored(2, 1, 1), stored(3, 4, 0)]);
+        transport.add_response(logs.clone());
+        transport.add_response(logs);
+        let mut sink = FailSecond {
+            inner: MemorySink::default(),
+            handled: 0,
+        };
+
+        let checkpoint = futures::executor::block_on(indexer(&transport).run(&mut sink));
+
+        // the first batch is not handed over again
+        assert!(checkpoint.is_ok());
+        let values = sink.inner.batches.iter().map(values).collect::<Vec<_>>();
+        assert_eq!(values, vec![uints(&[1, 2]), uints(&[3])]);
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].1[0]["fromBlock"], "0x0");
+        assert_eq!(requests[1].1[0]["fromBlock"], "0x1");
+    }
+
+    /// Fails the second batch once.
+    struct FailSecond {
+        inner: MemorySink,
+        handled: usize,
+    }
+
+    impl EventSink for FailSecond {
+        fn checkpoint(&mut self) -> BoxFuture<'_, std::result::Result<Option<LogCheckpoint>, SinkError>> {
+            self.inner.checkpoint()
+        }
+
+        fn handle<'a>(&'a mut self, batch: &'a EventBatch) -> BoxFuture<'a, std::result::Result<(), SinkError>> {
+            self.handled += 1;
+            if self.handled == 2 {
+                self.inner.failures = 1;
+            }
+            self.inner.handle(batch)
+        }
+    }
+
+    #[test]
+    fn should_give_up_after_retries() {
+        let mut transport = TestTransport::default();
+        transport.add_error(crate::Error::Unreachable);
+        transport.add_error(crate::Error::Unreachable);
+        let mut sink = MemorySink::default();
+
+        let result = futures::executor::block_on(indexer(&transport).run(&mut sink));
+
+        assert!(matches!(result, Err(Error::Api(crate::Error::Unreachable))));
+        assert!(sink.batches.is_empty());
+    }
+}
@@ -0,0 +1,459 @@
+//! Results of view functions kept up to date by contract events.
+//!
+//! `StateCache` doesn't fetch logs itself: subscribe to (or poll) logs matching
+//! [`StateCache::filter`] and hand them over to [`StateCache::follow`], or to
+//! [`StateCache::apply_log`] and call [`StateCache::refresh`] to re-query the invalidated results.
+//!
+//! ```no_run
+//! # use web3::contract::{state_cache::{StateCache, Trigger}, Contract};
+//! # use web3::types::{Address, U256};
+//! # async fn example(
+//! #     web3: web3::Web3<web3::transports::WebSocket>,
+//! #     token: Contract<web3::transports::WebSocket>,
+//! # ) -> web3::contract::Result<()> {
+//! let holder = Address::repeat_byte(1);
+//! let mut cache = StateCache::new(token);
+//! cache.track(
+//!     "balanceOf",
+//!     holder,
+//!     vec![
+//!         Trigger::event("Transfer").param("from", holder),
+//!         Trigger::event("Transfer").param("to", holder),
+//!     ],
+//! )?;
+//!
+//! // or `web3.eth_filter().create_logs_filter(cache.filter()).await?.stream(interval)`
+//! let logs = web3.eth_subscribe().subscribe_logs(cache.filter()).await?;
+//! cache
+//!     .follow(logs, |cache| {
+//!         let balance: web3::contract::Result<U256> = cache.get("balanceOf", holder).unwrap();
+//!         println!("Balance: {:?}", balance);
+//!     })
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    contract::{
+        tokens::{Detokenize, Tokenizable, Tokenize},
+        Contract, Error, Options, Result,
+    },
+    types::{Filter, FilterBuilder, Log},
+    Transport,
+};
+use ethabi::Token;
+use futures::{FutureExt, Stream, StreamExt};
+
+/// Logs of the contract which invalidate a cached result.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trigger {
+    event: String,
+    params: Vec<(String, Token)>,
+}
+
+impl Trigger {
+    /// Matches all logs of given event.
+    pub fn event(name: &str) -> Self {
+        Trigger {
+            event: name.into(),
+            params: vec![],
+        }
+    }
+
+    /// Matches only logs with given value of the (decoded) parameter.
+    pub fn param<V: Tokenizable>(mut self, name: &str, value: V) -> Self {
+        self.params.push((name.into(), value.into_token()));
+        self
+    }
+
+    fn matches(&self, log: &ethabi::Log) -> bool {
+        self.params.iter().all(|(name, value)| {
+            log.params
+                .iter()
+                .any(|param| &param.name == name && &param.value == value)
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    function: String,
+    params: Vec<Token>,
+    triggers: Vec<Trigger>,
+    value: Option<Vec<Token>>,
+    stale: bool,
+}
+
+/// Cache of view function results, invalidated by logs of the contract.
+///
+/// Logs removed by a reorg invalidate the results the same way new logs do. When logs might have
+/// been missed (e.g. after reconnecting a subscription), call [`invalidate_all`](Self::invalidate_all).
+#[derive(Debug, Clone)]
+pub struct StateCache<T: Transport> {
+    contract: Contract<T>,
+    entries: Vec<Entry>,
+}
+
+impl<T: Transport> StateCache<T> {
+    /// Creates an empty cache of given contract.
+    ///
+    /// Results are queried at the default block of the contract (see `Contract::at_block`).
+    pub fn new(contract: Contract<T>) -> Self {
+        StateCache {
+            contract,
+            entries: vec![],
+        }
+    }
+
+    /// Returns the contract.
+    pub fn contract(&self) -> &Contract<T> {
+        &self.contract
+    }
+
+    /// Starts tracking the result of a view function, invalidated by logs matching any of the triggers.
+    ///
+    /// Triggers are added to the ones of a call tracked before. The result is queried on the
+    /// next `refresh`.
+    pub fn track<P: Tokenize>(&mut self, function: &str, params: P, triggers: Vec<Trigger>) -> Result<()> {
+        self.contract.abi().function(function)?;
+        for trigger in &triggers {
+            let event = self.contract.abi().event(&trigger.event)?;
+            if let Some((name, _)) = trigger
+                .params
+                .iter()
+                .find(|(name, _)| !event.inputs.iter().any(|input| &input.name == name))
+            {
+                return Err(Error::Abi(ethabi::Error::InvalidName(format!(
+                    "Event {} has no parameter {}",
+                    event.name, name
+                ))));
+            }
+        }
+
+        let params = params.into_tokens();
+        match self.entry_mut(function, &params) {
+            Some(entry) => entry.triggers.extend(triggers),
+            None => self.entries.push(Entry {
+                function: function.into(),
+                params,
+                triggers,
+                value: None,
+                stale: true,
+            }),
+        }
+        Ok(())
+    }
+
+    /// Stops tracking the result of a view function.
+    pub fn untrack<P: Tokenize>(&mut self, function: &str, params: P) {
+        let params = params.into_tokens();
+        self.entries
+            .retain(|entry| entry.function != function || entry.params != params);
+    }
+
+    /// Returns the last result of a tracked call, `None` if it wasn't queried yet.
+    ///
+    /// The result may be stale, see `is_stale`.
+    pub fn get<R: Detokenize, P: Tokenize>(&self, function: &str, params: P) -> Option<Result<R>> {
+        let params = params.into_tokens();
+        let value = self.entry(function, &params)?.value.clone()?;
+        Some(R::from_tokens(value))
+    }
+
+    /// Returns true if the call is tracked and its result has to be refreshed.
+    pub fn is_stale<P: Tokenize>(&self, function: &str, params: P) -> bool {
+        let params = params.into_tokens();
+        self.entry(function, &params).is_some_and(|entry| entry.stale)
+    }
+
+    /// Returns a filter of the logs of all trigger events, to subscribe to or poll.
+    ///
+    /// Matches all logs of the contract if nothing is tracked.
+    pub fn filter(&self) -> Filter {
+        let mut signatures = vec![];
+        for trigger in self.entries.iter().flat_map(|entry| &entry.triggers) {
+            if let Ok(event) = self.contract.abi().event(&trigger.event) {
+                let signature = event.signature();
+                if !signatures.contains(&signature) {
+                    signatures.push(signature);
+                }
+            }
+        }
+        let filter = FilterBuilder::default().address(vec![self.contract.address()]);
+        if signatures.is_empty() {
+            return filter.build();
+        }
+        filter.topics(Some(signatures), None, None, None).build()
+    }
+
+    /// Invalidates the results of calls with a trigger matching the log, returns their number.
+    ///
+    /// Logs of other contracts and events, as well as logs which can't be decoded, are ignored.
+    pub fn apply_log(&mut self, log: &Log) -> usize {
+        if log.address != self.contract.address() {
+            return 0;
+        }
+        let abi = self.contract.abi();
+        let decode = |name: &str| {
+            let event = abi.event(name).ok()?;
+            if !event.anonymous && log.topics.first() != Some(&event.signature()) {
+                return None;
+            }
+            event
+                .parse_log(ethabi::RawLog {
+                    topics: log.topics.clone(),
+                    data: log.data.0.clone(),
+                })
+                .ok()
+        };
+
+        let mut invalidated = 0;
+        for entry in &mut self.entries {
+            let matches = entry
+                .triggers
+                .iter()
+                .any(|trigger| decode(&trigger.event).is_some_and(|decoded| trigger.matches(&decoded)));
+            if matches {
+                if !entry.stale {
+                    invalidated += 1;
+                }
+                entry.stale = true;
+            }
+        }
+        invalidated
+    }
+
+    /// Invalidates all results.
+    pub fn invalidate_all(&mut self) {
+        for entry in &mut self.entries {
+            entry.stale = true;
+        }
+    }
+
+    /// Queries the invalidated results, returns their number.
+    ///
+    /// The calls are made concurrently (so they can be batched with `Batch` transport), results of
+    /// failed calls stay invalidated.
+    pub async fn refresh(&mut self) -> Result<usize> {
+        let contract = &self.contract;
+        let stale: Vec<_> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.stale)
+            .map(|(index, entry)| {
+                let block = contract.block();
+                let query =
+                    contract.query_tokens(&entry.function, entry.params.clone(), None, Options::default(), block);
+                async move { (index, query.await) }
+            })
+            .collect();
+        let results = futures::future::join_all(stale).await;
+
+        let refreshed = results.len();
+        let mut error = None;
+        for (index, result) in results {
+            match result {
+                Ok(value) => {
+                    let entry = &mut self.entries[index];
+                    entry.value = Some(value);
+                    entry.stale = false;
+                }
+                Err(err) => error = error.or(Some(err)),
+            }
+        }
+        match error {
+            Some(err) => Err(err),
+            None => Ok(refreshed),
+        }
+    }
+
+    /// Keeps the results up to date with a stream of logs matching [`filter`](Self::filter), e.g. a
+    /// `subscribe_logs` subscription or a polled logs filter.
+    ///
+    /// Queries the invalidated results first, then refreshes them after the logs received at once
+    /// invalidate any, calling `on_update` after every refresh. Returns when the stream ends or on
+    /// the first error of the stream or of a refresh.
+    pub async fn follow<S, F>(&mut self, logs: S, mut on_update: F) -> Result<()>
+    where
+        S: Stream<Item = crate::error::Result<Log>>,
+        F: FnMut(&Self),
+    {
+        self.refresh().await?;
+        on_update(self);
+        futures::pin_mut!(logs);
+        while let Some(log) = logs.next().await {
+            let mut invalidated = self.apply_log(&log?);
+            while let Some(Some(log)) = logs.next().now_or_never() {
+                invalidated += self.apply_log(&log?);
+            }
+            if invalidated > 0 {
+                self.refresh().await?;
+                on_update(self);
+            }
+        }
+        Ok(())
+    }
+
+    fn entry(&self, function: &str, params: &[Token]) -> Option<&Entry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.function == function && entry.params == params)
+    }
+
+    fn entry_mut(&mut self, function: &str, params: &[Token]) -> Option<&mut Entry> {
+        self.entries
+            .iter_mut()
+            .find(|entry| entry.function == function && entry.params == params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        api::{self, Namespace},
+        rpc,
+        transports::test::TestTransport,
+        types::{Address, Bytes, H256, U256},
+    };
+
+    fn contract(transport: &TestTransport) -> Contract<&TestTransport> {
+        let eth = api::Eth::new(transport);
+        Contract::from_json(eth, Address::from_low_u64_be(1), include_bytes!("./res/token.json")).unwrap()
+    }
+
+    fn transfer(from: Address, to: Address, removed: bool) -> Log {
+        Log {
+            address: Address::from_low_u64_be(1),
+            topics: vec![
+                ethabi::long_signature(
+                    "Transfer",
+                    &[
+                        ethabi::ParamType::Address,
+                        ethabi::ParamType::Address,
+                        ethabi::ParamType::Uint(256),
+                    ],
+                ),
+                H256::from(from),
+                H256::from(to),
+            ],
+            data: Bytes(H256::from_low_u64_be(5).as_bytes().to_vec()),
+            block_hash: None,
+            block_number: None,
+            transaction_hash: None,
+            transaction_index: None,
+            log_index: None,
+            transaction_log_index: None,
+            log_type: None,
+            removed: Some(removed),
+        }
+    }
+
+    fn balance(value: u64) -> rpc::Value {
+        rpc::Value::String(format!("{:?}", H256::from_low_u64_be(value)))
+    }
+
+    #[test]
+    fn should_refresh_invalidated_results() {
+        let (holder, other) = (Address::repeat_byte(0xaa), Address::repeat_byte(0xbb));
+        let mut transport = TestTransport::default();
+        transport.add_response(balance(10));
+        transport.add_response(balance(15));
+        transport.add_response(balance(10));
+        let mut cache = StateCache::new(contract(&transport));
+        cache
+            .track(
+                "balanceOf",
+                holder,
+                vec![
+                    Trigger::event("Transfer").param("from", holder),
+                    Trigger::event("Transfer").param("to", holder),
+                ],
+            )
+            .unwrap();
+        assert!(cache.get::<U256, _>("balanceOf", holder).is_none());
+
+        assert_eq!(futures::executor::block_on(cache.refresh()).unwrap(), 1);
+        assert_eq!(cache.get::<U256, _>("balanceOf", holder).unwrap().unwrap(), 10.into());
+
+        // unrelated transfer
+        assert_eq!(cache.apply_log(&transfer(other, other, false)), 0);
+        assert_eq!(futures::executor::block_on(cache.refresh()).unwrap(), 0);
+
+        assert_eq!(cache.apply_log(&transfer(other, holder, false)), 1);
+        assert!(cache.is_stale("balanceOf", holder));
+        assert_eq!(futures::executor::block_on(cache.refresh()).unwrap(), 1);
+        assert_eq!(cache.get::<U256, _>("balanceOf", holder).unwrap().unwrap(), 15.into());
+
+        // the transfer is reorged out
+        assert_eq!(cache.apply_log(&transfer(other, holder, true)), 1);
+        assert_eq!(futures::executor::block_on(cache.refresh()).unwrap(), 1);
+        assert_eq!(cache.get::<U256, _>("balanceOf", holder).unwrap().unwrap(), 10.into());
+
+        assert_eq!(transport.requests_of("eth_call").len(), 3);
+    }
+
+    #[test]
+    fn should_follow_logs() {
+        let holder = Address::repeat_byte(0xaa);
+        let mut transport = TestTransport::default();
+        transport.add_response(balance(10));
+        transport.add_response(balance(20));
+        let mut cache = StateCache::new(contract(&transport));
+        cache
+            .track(
+                "balanceOf",
+                holder,
+                vec![Trigger::event("Transfer").param("to", holder)],
+            )
+            .unwrap();
+        let logs = futures::stream::iter(vec![
+            Ok(transfer(holder, holder, false)),
+            Ok(transfer(holder, holder, false)),
+        ]);
+
+        let mut balances = vec![];
+        futures::executor::block_on(cache.follow(logs, |cache| {
+            balances.push(cache.get::<U256, _>("balanceOf", holder).unwrap().unwrap())
+        }))
+        .unwrap();
+
+        // logs received at once are refreshed together
+        assert_eq!(balances, vec![U256::from(10), U256::from(20)]);
+        assert_eq!(transport.requests_of("eth_call").len(), 2);
+    }
+
+    #[test]
+    fn should_build_filter_of_trigger_events() {
+        let transport = TestTransport::default();
+        let mut cache = StateCache::new(contract(&transport));
+        assert_eq!(
+            serde_json::to_value(cache.filter()).unwrap(),
+            serde_json::json!({"address": "0x0000000000000000000000000000000000000001"})
+        );
+        cache
+            .track("totalSupply", (), vec![Trigger::event("Transfer")])
+            .unwrap_err();
+        cache
+            .track(
+                "name",
+                (),
+                vec![Trigger::event("Transfer").param("amount", U256::one())],
+            )
+            .unwrap_err();
+        cache.track("name", (), vec![Trigger::event("Transfer")]).unwrap();
+        cache.track("symbol", (), vec![Trigger::event("Transfer")]).unwrap();
+
+        let filter = serde_json::to_value(cache.filter()).unwrap();
+
+        assert_eq!(
+            filter,
+            serde_json::json!({
+                "address": "0x0000000000000000000000000000000000000001",
+                "topics": ["0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"],
+            })
+        );
+    }
+}
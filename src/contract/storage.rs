@@ -0,0 +1,280 @@
+//! Solidity storage layout support.
+
+use crate::{
+    contract::{Error, Result},
+    signing::keccak256,
+    types::{Address, H256, U256},
+};
+use ethabi::Token;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Storage layout of a contract as emitted by solc (`storageLayout` output selection).
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct StorageLayout {
+    /// State variables
+    pub storage: Vec<StorageEntry>,
+    /// Types of the state variables, by type id
+    #[serde(default)]
+    pub types: BTreeMap<String, StorageType>,
+}
+
+/// A state variable or a struct member.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct StorageEntry {
+    /// Name of the variable
+    pub label: String,
+    /// Offset in bytes within the slot
+    pub offset: usize,
+    /// Slot number (decimal)
+    pub slot: String,
+    /// Type id, key of `StorageLayout::types`
+    #[serde(rename = "type")]
+    pub type_id: String,
+}
+
+/// Type of a state variable.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct StorageType {
+    /// Encoding: `inplace`, `mapping`, `dynamic_array` or `bytes`
+    pub encoding: String,
+    /// Canonical type name, e.g. `uint256`
+    pub label: String,
+    /// Number of used bytes (decimal)
+    #[serde(rename = "numberOfBytes")]
+    pub number_of_bytes: String,
+    /// Key type id of a mapping
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// Value type id of a mapping
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    /// Members of a struct
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub members: Option<Vec<StorageEntry>>,
+}
+
+/// Location of a value in storage.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct StorageLocation<'a> {
+    pub slot: U256,
+    pub offset: usize,
+    pub ty: &'a StorageType,
+}
+
+impl StorageLayout {
+    /// Loads the layout from the JSON emitted by solc.
+    pub fn load(json: &[u8]) -> serde_json::Result<Self> {
+        serde_json::from_slice(json)
+    }
+
+    /// Computes the storage slot of a variable.
+    ///
+    /// Struct members are addressed with dots (e.g. `config.owner`) and mapping keys are taken
+    /// from `keys` in order, whenever a mapping is encountered on the path.
+    pub fn slot(&self, path: &str, keys: Vec<Token>) -> Result<U256> {
+        self.locate(path, keys).map(|location| location.slot)
+    }
+
+    pub(crate) fn locate(&self, path: &str, keys: Vec<Token>) -> Result<StorageLocation<'_>> {
+        let mut keys = keys.into_iter();
+        let mut entries = &self.storage;
+        let mut location: Option<StorageLocation> = None;
+
+        for label in path.split('.') {
+            if let Some(ref location) = location {
+                entries = location
+                    .ty
+                    .members
+                    .as_ref()
+                    .ok_or_else(|| storage_error(format!("`{}` is not a struct", location.ty.label)))?;
+            }
+            let entry = entries
+                .iter()
+                .find(|entry| entry.label == label)
+                .ok_or_else(|| storage_error(format!("Unknown variable `{}`", label)))?;
+            let base = location.as_ref().map(|location| location.slot).unwrap_or_default();
+            let mut current = StorageLocation {
+                slot: base + parse_decimal(&entry.slot)?,
+                offset: entry.offset,
+                ty: self.storage_type(&entry.type_id)?,
+            };
+
+            while current.ty.encoding == "mapping" {
+                let key = match keys.next() {
+                    Some(key) => key,
+                    None => return Err(storage_error(format!("Missing key for `{}`", current.ty.label))),
+                };
+                let key_type = self.storage_type(current.ty.key.as_deref().unwrap_or_default())?;
+                let mut data = encode_key(key, key_type)?;
+                let mut slot = [0u8; 32];
+                current.slot.to_big_endian(&mut slot);
+                data.extend_from_slice(&slot);
+                current = StorageLocation {
+                    slot: U256::from_big_endian(&keccak256(&data)),
+                    offset: 0,
+                    ty: self.storage_type(current.ty.value.as_deref().unwrap_or_default())?,
+                };
+            }
+            location = Some(current);
+        }
+
+        if keys.next().is_some() {
+            return Err(storage_error(format!("Too many keys for `{}`", path)));
+        }
+        location.ok_or_else(|| storage_error(format!("Unknown variable `{}`", path)))
+    }
+
+    fn storage_type(&self, id: &str) -> Result<&StorageType> {
+        self.types
+            .get(id)
+            .ok_or_else(|| storage_error(format!("Unknown type `{}`", id)))
+    }
+}
+
+impl StorageLocation<'_> {
+    /// Extracts the value from the slot contents.
+    pub(crate) fn decode(&self, word: H256) -> Result<Token> {
+        let size = parse_decimal(&self.ty.number_of_bytes)?.low_u64() as usize;
+        if self.ty.encoding != "inplace" || size == 0 || size + self.offset > 32 {
+            return Err(storage_error(format!("`{}` is not a value type", self.ty.label)));
+        }
+        let bytes = &word.0[32 - self.offset - size..32 - self.offset];
+        let label = self.ty.label.as_str();
+
+        let token = if label == "bool" {
+            Token::Bool(bytes.iter().any(|b| *b != 0))
+        } else if label.starts_with("address") || label.starts_with("contract ") {
+            Token::Address(Address::from_slice(&bytes[bytes.len() - 20..]))
+        } else if label.starts_with("uint") || label.starts_with("enum ") {
+            Token::Uint(U256::from_big_endian(bytes))
+        } else if label.starts_with("int") {
+            // sign extend
+            let fill = if bytes[0] & 0x80 == 0 { 0 } else { 0xff };
+            let mut value = [fill; 32];
+            value[32 - size..].copy_from_slice(bytes);
+            Token::Int(U256::from_big_endian(&value))
+        } else if label.starts_with("bytes") {
+            Token::FixedBytes(bytes.to_vec())
+        } else {
+            return Err(storage_error(format!("Unsupported type `{}`", label)));
+        };
+        Ok(token)
+    }
+}
+
+/// Encodes a mapping key for slot computation.
+fn encode_key(key: Token, ty: &StorageType) -> Result<Vec<u8>> {
+    match (ty.encoding.as_str(), key) {
+        ("bytes", Token::String(s)) => Ok(s.into_bytes()),
+        ("bytes", Token::Bytes(b)) => Ok(b),
+        ("inplace", key) if !key.is_dynamic() => Ok(ethabi::encode(&[key])),
+        (_, key) => Err(storage_error(format!("Invalid key {:?} for `{}`", key, ty.label))),
+    }
+}
+
+fn parse_decimal(value: &str) -> Result<U256> {
+    U256::from_dec_str(value).map_err(|_| storage_error(format!("Invalid number `{}`", value)))
+}
+
+fn storage_error(msg: String) -> Error {
+    Error::StorageLayout(msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // storage layout of:
+    // contract Token {
+    //     struct Config { address owner; bool paused; int64 fee; }
+    //     uint256 totalSupply;
+    //     Config config;
+    //     mapping(address => uint256) balances;
+    //     mapping(address => mapping(address => uint256)) allowances;
+    // }
+    const LAYOUT: &str = r#"{
+      "storage": [
+        {"astId": 1, "contract": "Token.sol:Token", "label": "totalSupply", "offset": 0, "slot": "0", "type": "t_uint256"},
+        {"astId": 2, "contract": "Token.sol:Token", "label": "config", "offset": 0, "slot": "1", "type": "t_struct(Config)1_storage"},
+        {"astId": 3, "contract": "Token.sol:Token", "label": "balances", "offset": 0, "slot": "2", "type": "t_mapping(t_address,t_uint256)"},
+        {"astId": 4, "contract": "Token.sol:Token", "label": "allowances", "offset": 0, "slot": "3", "type": "t_mapping(t_address,t_mapping(t_address,t_uint256))"}
+      ],
+      "types": {
+        "t_address": {"encoding": "inplace", "label": "address", "numberOfBytes": "20"},
+        "t_bool": {"encoding": "inplace", "label": "bool", "numberOfBytes": "1"},
+        "t_int64": {"encoding": "inplace", "label": "int64", "numberOfBytes": "8"},
+        "t_uint256": {"encoding": "inplace", "label": "uint256", "numberOfBytes": "32"},
+        "t_mapping(t_address,t_uint256)": {"encoding": "mapping", "key": "t_address", "label": "mapping(address => uint256)", "numberOfBytes": "32", "value": "t_uint256"},
+        "t_mapping(t_address,t_mapping(t_address,t_uint256))": {"encoding": "mapping", "key": "t_address", "label": "mapping(address => mapping(address => uint256))", "numberOfBytes": "32", "value": "t_mapping(t_address,t_uint256)"},
+        "t_struct(Config)1_storage": {"encoding": "inplace", "label": "struct Token.Config", "numberOfBytes": "64", "members": [
+          {"astId": 5, "contract": "Token.sol:Token", "label": "owner", "offset": 0, "slot": "0", "type": "t_address"},
+          {"astId": 6, "contract": "Token.sol:Token", "label": "paused", "offset": 20, "slot": "0", "type": "t_bool"},
+          {"astId": 7, "contract": "Token.sol:Token", "label": "fee", "offset": 0, "slot": "1", "type": "t_int64"}
+        ]}
+      }
+    }"#;
+
+    fn layout() -> StorageLayout {
+        StorageLayout::load(LAYOUT.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn should_compute_slots() {
+        let layout = layout();
+        let holder = Address::from_low_u64_be(0x123);
+        let spender = Address::from_low_u64_be(0x456);
+
+        assert_eq!(layout.slot("totalSupply", vec![]).unwrap(), 0.into());
+        assert_eq!(layout.slot("config.fee", vec![]).unwrap(), 2.into());
+
+        let mut data = H256::from(holder).0.to_vec();
+        data.extend_from_slice(&H256::from_low_u64_be(2).0);
+        let balance_slot = U256::from_big_endian(&keccak256(&data));
+        assert_eq!(
+            layout.slot("balances", vec![Token::Address(holder)]).unwrap(),
+            balance_slot
+        );
+
+        let mut data = H256::from(holder).0.to_vec();
+        data.extend_from_slice(&H256::from_low_u64_be(3).0);
+        let data = [H256::from(spender).0, keccak256(&data)].concat();
+        let allowance_slot = U256::from_big_endian(&keccak256(&data));
+        assert_eq!(
+            layout
+                .slot("allowances", vec![Token::Address(holder), Token::Address(spender)])
+                .unwrap(),
+            allowance_slot
+        );
+    }
+
+    #[test]
+    fn should_reject_invalid_paths() {
+        let layout = layout();
+        assert!(layout.slot("unknown", vec![]).is_err());
+        assert!(layout.slot("balances", vec![]).is_err());
+        assert!(layout.slot("totalSupply", vec![Token::Uint(1.into())]).is_err());
+        assert!(layout.slot("totalSupply.owner", vec![]).is_err());
+    }
+
+    #[test]
+    fn should_decode_packed_values() {
+        let layout = layout();
+        let mut word = [0u8; 32];
+        word[11] = 1;
+        word[12..].copy_from_slice(Address::from_low_u64_be(0x123).as_bytes());
+
+        let owner = layout.locate("config.owner", vec![]).unwrap();
+        assert_eq!(
+            owner.decode(word.into()).unwrap(),
+            Token::Address(Address::from_low_u64_be(0x123))
+        );
+        let paused = layout.locate("config.paused", vec![]).unwrap();
+        assert_eq!(paused.decode(word.into()).unwrap(), Token::Bool(true));
+
+        let fee = layout.locate("config.fee", vec![]).unwrap();
+        let mut word = [0u8; 32];
+        word[24..].copy_from_slice(&(-5i64).to_be_bytes());
+        assert_eq!(fee.decode(word.into()).unwrap(), Token::Int(U256::MAX - 4));
+    }
+}
@@ -2,11 +2,16 @@
 
 use crate::{
     contract::error::Error,
-    types::{Address, Bytes, BytesArray, H256, U128, U256},
+    types::{Address, Bytes, BytesArray, H128, H256, H512, H64, U128, U256},
 };
 use arrayvec::ArrayVec;
 use ethabi::Token;
 
+/// Derives `Tokenizable` for structs matching Solidity structs, and `Detokenize` for structs
+/// holding all outputs of a function.
+#[cfg(feature = "derive")]
+pub use web3_derive::{Detokenize, Tokenizable};
+
 /// Output type possible to deserialize from Contract ABI
 pub trait Detokenize {
     /// Creates a new instance from parsed ABI tokens.
@@ -171,26 +176,39 @@ impl Tokenizable for Bytes {
     }
 }
 
-impl Tokenizable for H256 {
-    fn from_token(token: Token) -> Result<Self, Error> {
-        match token {
-            Token::FixedBytes(mut s) => {
-                if s.len() != 32 {
-                    return Err(Error::InvalidOutputType(format!("Expected `H256`, got {:?}", s)));
+macro_rules! impl_fixed_hash {
+    ($($type: ident,)*) => {
+        $(
+            impl Tokenizable for $type {
+                fn from_token(token: Token) -> Result<Self, Error> {
+                    match token {
+                        Token::FixedBytes(s) => {
+                            if s.len() != $type::len_bytes() {
+                                return Err(Error::InvalidOutputType(format!(
+                                    concat!("Expected `", stringify!($type), "`, got {:?}"),
+                                    s
+                                )));
+                            }
+                            Ok($type::from_slice(&s))
+                        }
+                        other => Err(Error::InvalidOutputType(format!(
+                            concat!("Expected `", stringify!($type), "`, got {:?}"),
+                            other
+                        ))),
+                    }
                 }
-                let mut data = [0; 32];
-                for (idx, val) in s.drain(..).enumerate() {
-                    data[idx] = val;
+
+                fn into_token(self) -> Token {
+                    Token::FixedBytes(self.as_ref().to_vec())
                 }
-                Ok(data.into())
             }
-            other => Err(Error::InvalidOutputType(format!("Expected `H256`, got {:?}", other))),
-        }
-    }
+        )*
+    };
+}
 
-    fn into_token(self) -> Token {
-        Token::FixedBytes(self.as_ref().to_vec())
-    }
+// `H160` is `Address`, tokenized as `Token::Address`.
+impl_fixed_hash! {
+    H64, H128, H256, H512,
 }
 
 impl Tokenizable for Address {
@@ -293,7 +311,7 @@ macro_rules! tokenizable_item {
 }
 
 tokenizable_item! {
-    Token, String, Address, H256, U256, U128, bool, BytesArray, Vec<u8>,
+    Token, String, Address, H64, H128, H256, H512, U256, U128, bool, BytesArray, Vec<u8>,
     i8, i16, i32, i64, i128, u16, u32, u64, u128,
 }
 
@@ -431,6 +449,21 @@ impl_fixed_types!(13);
 impl_fixed_types!(14);
 impl_fixed_types!(15);
 impl_fixed_types!(16);
+impl_fixed_types!(17);
+impl_fixed_types!(18);
+impl_fixed_types!(19);
+impl_fixed_types!(20);
+impl_fixed_types!(21);
+impl_fixed_types!(22);
+impl_fixed_types!(23);
+impl_fixed_types!(24);
+impl_fixed_types!(25);
+impl_fixed_types!(26);
+impl_fixed_types!(27);
+impl_fixed_types!(28);
+impl_fixed_types!(29);
+impl_fixed_types!(30);
+impl_fixed_types!(31);
 impl_fixed_types!(32);
 impl_fixed_types!(64);
 impl_fixed_types!(80);
@@ -442,7 +475,7 @@ impl_fixed_types!(1024);
 #[cfg(test)]
 mod tests {
     use super::{Detokenize, Tokenizable};
-    use crate::types::{Address, BytesArray, U256};
+    use crate::types::{Address, BytesArray, H128, H256, H512, H64, U256};
     use ethabi::{Token, Uint};
     use hex_literal::hex;
 
@@ -492,6 +525,91 @@ mod tests {
         assert_eq!(data[7][0], 8);
     }
 
+    #[test]
+    fn should_tokenize_fixed_bytes_and_hashes() {
+        assert_eq!(
+            hex!("01ffc9a7").into_token(),
+            Token::FixedBytes(hex!("01ffc9a7").into())
+        );
+        assert_eq!(<[u8; 20]>::from_token(Token::FixedBytes(vec![1; 20])).unwrap(), [1; 20]);
+        assert!(<[u8; 20]>::from_token(Token::FixedBytes(vec![1; 21])).is_err());
+
+        let hash = H128::from_low_u64_be(5);
+        assert_eq!(H128::from_token(hash.into_token()).unwrap(), hash);
+        assert_eq!(H64::repeat_byte(1).into_token(), Token::FixedBytes(vec![1; 8]));
+        assert_eq!(
+            H512::from_token(Token::FixedBytes(vec![2; 64])).unwrap(),
+            H512::repeat_byte(2)
+        );
+        assert!(H256::from_token(Token::FixedBytes(vec![0; 31])).is_err());
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn should_derive_tokenizable() {
+        #[derive(Debug, Clone, PartialEq, super::Tokenizable)]
+        struct Point(U256, U256);
+
+        #[derive(Debug, Clone, PartialEq, super::Tokenizable)]
+        struct Shape {
+            owner: Address,
+            points: Vec<Point>,
+            origin: Point,
+        }
+
+        let shape = Shape {
+            owner: Address::from_low_u64_be(1),
+            points: vec![Point(1.into(), 2.into())],
+            origin: Point(0.into(), 0.into()),
+        };
+        let token = shape.clone().into_token();
+        assert_eq!(
+            token,
+            Token::Tuple(vec![
+                Token::Address(Address::from_low_u64_be(1)),
+                Token::Array(vec![Token::Tuple(vec![Token::Uint(1.into()), Token::Uint(2.into())])]),
+                Token::Tuple(vec![Token::Uint(0.into()), Token::Uint(0.into())]),
+            ])
+        );
+        let decoded: Shape = Detokenize::from_tokens(vec![token]).unwrap();
+        assert_eq!(decoded, shape);
+        assert!(Point::from_token(Token::Tuple(vec![Token::Uint(1.into())])).is_err());
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn should_derive_detokenize() {
+        // the generated code doesn't pick up local items
+        #[allow(dead_code)]
+        type Result<T> = std::result::Result<T, ()>;
+
+        #[derive(Debug, PartialEq, super::Tokenizable)]
+        struct Point(U256, U256);
+
+        #[derive(Debug, PartialEq, super::Detokenize)]
+        struct Reserves {
+            reserves: Vec<U256>,
+            origin: Point,
+            timestamp: u32,
+        }
+
+        let reserves: Reserves = Detokenize::from_tokens(vec![
+            Token::Array(vec![Token::Uint(1.into()), Token::Uint(2.into())]),
+            Token::Tuple(vec![Token::Uint(0.into()), Token::Uint(0.into())]),
+            Token::Uint(3.into()),
+        ])
+        .unwrap();
+        assert_eq!(
+            reserves,
+            Reserves {
+                reserves: vec![1.into(), 2.into()],
+                origin: Point(0.into(), 0.into()),
+                timestamp: 3,
+            }
+        );
+        assert!(Reserves::from_tokens(vec![Token::Uint(3.into())]).is_err());
+    }
+
     #[test]
     fn should_decode_array_of_bytes() {
         let token = Token::Array(vec![Token::Uint(Uint::from(0)), Token::Uint(Uint::from(1))]);
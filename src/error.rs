@@ -16,6 +16,9 @@ pub enum TransportError {
     /// Arbitrary, developer-readable description of the occurred error.
     #[display(fmt = "{}", _0)]
     Message(String),
+    /// The request didn't complete in time.
+    #[display(fmt = "request timed out")]
+    Timeout,
 }
 
 /// Errors which can occur when attempting to generate resource uri.
@@ -51,14 +54,63 @@ pub enum Error {
     #[display(fmt = "Transaction reverted: {}", _0)]
     #[from(ignore)]
     Revert(String),
+    /// operation cancelled
+    #[display(fmt = "Operation cancelled")]
+    Cancelled,
+    /// endpoint doesn't look like an Ethereum JSON-RPC endpoint
+    #[display(fmt = "Incompatible endpoint: {}", _0)]
+    #[from(ignore)]
+    IncompatibleEndpoint(String),
+    /// node returned `null` where a value is required, holds the RPC method (or the expected type
+    /// if the value wasn't decoded by a `CallFuture` of the method)
+    #[display(fmt = "Unexpected null response from {}", _0)]
+    #[from(ignore)]
+    NullResponse(String),
+    /// `eth_getLogs` query matched too many logs or spanned too many blocks
+    #[display(fmt = "Result set too large: {}", "error.message")]
+    #[from(ignore)]
+    ResultSetTooLarge {
+        /// Block range (inclusive) suggested by the provider
+        suggested_range: Option<(u64, u64)>,
+        /// Original error returned by the provider
+        error: RPCError,
+    },
+    /// subscription consumer fell behind and notifications were dropped
+    #[display(fmt = "Subscription lagged behind, {} notifications dropped", _0)]
+    #[from(ignore)]
+    Lagged(u64),
+    /// endpoint serves a different chain than expected
+    #[display(fmt = "Wrong chain: expected chain id {}, endpoint serves {}", expected, actual)]
+    #[from(ignore)]
+    WrongChain {
+        /// Expected chain id
+        expected: u64,
+        /// Chain id reported by the endpoint
+        actual: u64,
+    },
+    /// transaction parameters can't be signed
+    #[display(fmt = "Invalid transaction: {}", _0)]
+    #[from(ignore)]
+    InvalidTransaction(String),
 }
 
 impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         use self::Error::*;
         match *self {
-            Unreachable | Decoder(_) | InvalidResponse(_) | Transport { .. } | Internal | Revert(_) => None,
-            Rpc(ref e) => Some(e),
+            Unreachable
+            | Decoder(_)
+            | InvalidResponse(_)
+            | Transport { .. }
+            | Internal
+            | Revert(_)
+            | Cancelled
+            | IncompatibleEndpoint(_)
+            | NullResponse(_)
+            | InvalidTransaction(_)
+            | Lagged(_)
+            | WrongChain { .. } => None,
+            Rpc(ref e) | ResultSetTooLarge { error: ref e, .. } => Some(e),
             Io(ref e) => Some(e),
             Recovery(ref e) => Some(e),
         }
@@ -84,6 +136,19 @@ impl Clone for Error {
             Recovery(e) => Recovery(e.clone()),
             Internal => Internal,
             Revert(s) => Revert(s.clone()),
+            Cancelled => Cancelled,
+            IncompatibleEndpoint(s) => IncompatibleEndpoint(s.clone()),
+            NullResponse(s) => NullResponse(s.clone()),
+            InvalidTransaction(s) => InvalidTransaction(s.clone()),
+            ResultSetTooLarge { suggested_range, error } => ResultSetTooLarge {
+                suggested_range: *suggested_range,
+                error: error.clone(),
+            },
+            Lagged(n) => Lagged(*n),
+            WrongChain { expected, actual } => WrongChain {
+                expected: *expected,
+                actual: *actual,
+            },
         }
     }
 }
@@ -93,13 +158,28 @@ impl PartialEq for Error {
     fn eq(&self, other: &Self) -> bool {
         use self::Error::*;
         match (self, other) {
-            (Unreachable, Unreachable) | (Internal, Internal) => true,
+            (Unreachable, Unreachable) | (Internal, Internal) | (Cancelled, Cancelled) => true,
             (Decoder(a), Decoder(b)) | (InvalidResponse(a), InvalidResponse(b)) => a == b,
             (Transport(a), Transport(b)) => a == b,
             (Rpc(a), Rpc(b)) => a == b,
             (Io(a), Io(b)) => a.kind() == b.kind(),
             (Recovery(a), Recovery(b)) => a == b,
-            (Revert(a), Revert(b)) => a == b,
+            (Revert(a), Revert(b))
+            | (IncompatibleEndpoint(a), IncompatibleEndpoint(b))
+            | (NullResponse(a), NullResponse(b))
+            | (InvalidTransaction(a), InvalidTransaction(b)) => a == b,
+            (
+                ResultSetTooLarge {
+                    suggested_range: a,
+                    error: e,
+                },
+                ResultSetTooLarge {
+                    suggested_range: b,
+                    error: f,
+                },
+            ) => a == b && e == f,
+            (Lagged(a), Lagged(b)) => a == b,
+            (WrongChain { expected, actual }, WrongChain { expected: e, actual: a }) => expected == e && actual == a,
             _ => false,
         }
     }
@@ -1,18 +1,53 @@
 //! Web3 helpers.
 
-use crate::{error, rpc, Error};
+use crate::{error, rpc, Error, RequestId, Transport};
 use futures::{
-    task::{Context, Poll},
+    task::{Context, Poll, Waker},
     Future,
 };
+use parking_lot::Mutex;
 use pin_project::pin_project;
 use serde::de::DeserializeOwned;
-use std::{marker::PhantomData, pin::Pin};
+use std::{
+    marker::PhantomData,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// Emits an event of given level (`trace`, `debug`, `info`, `warn` or `error`) through `tracing`
+/// if the feature is enabled or through `log` otherwise.
+macro_rules! event {
+    ($level:ident, $($arg:tt)*) => {{
+        #[cfg(feature = "tracing")]
+        tracing::$level!($($arg)*);
+        #[cfg(not(feature = "tracing"))]
+        log::$level!($($arg)*);
+    }};
+}
+
+pub mod backoff;
+pub mod poll;
+
+pub use self::poll::{poll_stream, poll_until, PollPolicy};
 
 /// Takes any type which is deserializable from rpc::Value and such a value and
 /// yields the deserialized value
+///
+/// `null` is only accepted by types which allow it (e.g. `Option`), otherwise
+/// `Error::NullResponse` is returned.
 pub fn decode<T: serde::de::DeserializeOwned>(value: rpc::Value) -> error::Result<T> {
-    serde_json::from_value(value).map_err(Into::into)
+    let is_null = value.is_null();
+    serde_json::from_value(value).map_err(|err| {
+        if is_null {
+            Error::NullResponse(std::any::type_name::<T>().into())
+        } else {
+            err.into()
+        }
+    })
 }
 
 /// Calls decode on the result of the wrapped future.
@@ -21,6 +56,9 @@ pub fn decode<T: serde::de::DeserializeOwned>(value: rpc::Value) -> error::Resul
 pub struct CallFuture<T, F> {
     #[pin]
     inner: F,
+    method: Option<String>,
+    map_err: fn(Error) -> Error,
+    map_value: fn(rpc::Value) -> rpc::Value,
     _marker: PhantomData<T>,
 }
 
@@ -29,11 +67,50 @@ impl<T, F> CallFuture<T, F> {
     pub fn new(inner: F) -> Self {
         CallFuture {
             inner,
+            method: None,
+            map_err: |err| err,
+            map_value: |value| value,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Classifies errors of the inner future with `map_err`.
+    pub(crate) fn classify_errors(mut self, map_err: fn(Error) -> Error) -> Self {
+        self.map_err = map_err;
+        self
+    }
+
+    /// Normalizes the result of the inner future with `map_value` before it's decoded.
+    pub(crate) fn normalize(mut self, map_value: fn(rpc::Value) -> rpc::Value) -> Self {
+        self.map_value = map_value;
+        self
+    }
+
+    /// Fails the call with `TransportError::Timeout` unless it completes within `timeout`.
+    ///
+    /// Shortens the default timeout of the transport (see `transports::Timeout`) for this call. A
+    /// longer timeout has no effect, as the transport still fails the request after its own timeout.
+    pub fn timeout(self, timeout: Duration) -> CallFuture<T, Deadline<F>> {
+        CallFuture {
+            inner: Deadline::new(self.inner, timeout),
+            method: self.method,
+            map_err: self.map_err,
+            map_value: self.map_value,
             _marker: PhantomData,
         }
     }
 }
 
+/// Calls the RPC method, the returned future decodes the result.
+///
+/// A `null` result of a type which doesn't allow it fails with `Error::NullResponse` naming the method.
+pub fn call<T, X: Transport>(transport: &X, method: &str, params: Vec<rpc::Value>) -> CallFuture<T, X::Out> {
+    CallFuture {
+        method: Some(method.into()),
+        ..CallFuture::new(transport.execute(method, params))
+    }
+}
+
 impl<T, F> Future for CallFuture<T, F>
 where
     T: serde::de::DeserializeOwned,
@@ -44,10 +121,339 @@ where
     fn poll(self: Pin<&mut Self>, ctx: &mut Context) -> Poll<Self::Output> {
         let this = self.project();
         let x = ready!(this.inner.poll(ctx));
-        Poll::Ready(x.and_then(decode))
+        let method = this.method;
+        Poll::Ready(
+            x.map_err(*this.map_err)
+                .map(*this.map_value)
+                .and_then(decode)
+                .map_err(|err| match (err, method) {
+                    (Error::NullResponse(_), Some(method)) => Error::NullResponse(method.clone()),
+                    (err, _) => err,
+                }),
+        )
+    }
+}
+
+/// Fails the wrapped future with `TransportError::Timeout` unless it completes in time.
+///
+/// The timeout starts when the future is created. Dropping it doesn't cancel a request the
+/// transport already sent, its response is just ignored.
+#[pin_project]
+#[derive(Debug)]
+pub struct Deadline<F> {
+    #[pin]
+    inner: F,
+    delay: futures_timer::Delay,
+}
+
+impl<F> Deadline<F> {
+    /// Wraps the future, failing it after `timeout`.
+    pub fn new(inner: F, timeout: Duration) -> Self {
+        Deadline {
+            inner,
+            delay: futures_timer::Delay::new(timeout),
+        }
+    }
+}
+
+impl<F, T> Future for Deadline<F>
+where
+    F: Future<Output = error::Result<T>>,
+{
+    type Output = error::Result<T>;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context) -> Poll<Self::Output> {
+        let this = self.project();
+        if let Poll::Ready(result) = this.inner.poll(ctx) {
+            return Poll::Ready(result);
+        }
+        ready!(Pin::new(this.delay).poll(ctx));
+        Poll::Ready(Err(Error::Transport(error::TransportError::Timeout)))
+    }
+}
+
+/// A token used to cancel long-running operations, like confirmation waits or polling streams.
+///
+/// Clones share the cancellation state, so the token can be handed to an operation
+/// while another clone is kept to trigger the cancellation.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    inner: Arc<CancellationState>,
+}
+
+#[derive(Debug, Default)]
+struct CancellationState {
+    cancelled: AtomicBool,
+    wakers: Mutex<Vec<Waker>>,
+}
+
+impl CancellationToken {
+    /// Create a new, not yet cancelled token.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Cancel all operations observing this token.
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        for waker in self.inner.wakers.lock().drain(..) {
+            waker.wake();
+        }
+    }
+
+    /// Returns true if the token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Returns a future resolved once the token is cancelled.
+    pub fn cancelled(&self) -> Cancelled {
+        Cancelled { token: self.clone() }
+    }
+}
+
+/// Future resolved when a [CancellationToken] gets cancelled.
+#[derive(Debug)]
+pub struct Cancelled {
+    token: CancellationToken,
+}
+
+impl Future for Cancelled {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context) -> Poll<Self::Output> {
+        if self.token.is_cancelled() {
+            return Poll::Ready(());
+        }
+        let mut wakers = self.token.inner.wakers.lock();
+        // check again in case `cancel` was called before we acquired the lock.
+        if self.token.is_cancelled() {
+            return Poll::Ready(());
+        }
+        if !wakers.iter().any(|waker| waker.will_wake(ctx.waker())) {
+            wakers.push(ctx.waker().clone());
+        }
+        Poll::Pending
     }
 }
 
+/// Fetches logs matching a filter over a large block range in chunks.
+///
+/// The range is split into chunks of `chunk_size` blocks (10,000 by default). When the provider
+/// rejects a chunk for returning too many results (or spanning too many blocks), i.e. `Eth::logs`
+/// fails with `Error::ResultSetTooLarge`, the chunk is bisected and retried, and subsequent chunks
+/// use the reduced size. Any other error ends the stream. Logs are yielded in order.
+#[derive(Debug, Clone)]
+pub struct LogPager<T: crate::Transport> {
+    eth: crate::api::Eth<T>,
+    filter: crate::types::Filter,
+    chunk_size: u64,
+    checkpoint: Option<crate::types::LogCheckpoint>,
+}
+
+impl<T: crate::Transport> LogPager<T> {
+    /// Creates a new pager for given filter.
+    pub fn new(eth: crate::api::Eth<T>, filter: crate::types::Filter) -> Self {
+        LogPager {
+            eth,
+            filter,
+            chunk_size: 10_000,
+            checkpoint: None,
+        }
+    }
+
+    /// Resumes from a checkpoint, skipping logs which were already processed.
+    ///
+    /// The filter's `from_block` is ignored if the checkpoint is further in the chain.
+    pub fn resume_from(mut self, checkpoint: crate::types::LogCheckpoint) -> Self {
+        self.checkpoint = Some(checkpoint);
+        self
+    }
+
+    /// Sets the initial number of blocks queried at once.
+    pub fn chunk_size(mut self, chunk_size: u64) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    /// Returns a stream of all logs matching the filter.
+    pub fn stream(self) -> impl futures::Stream<Item = error::Result<crate::types::Log>> {
+        let state = LogPagerState {
+            pager: self,
+            range: None,
+            pending: Vec::new(),
+            logs: Default::default(),
+            done: false,
+        };
+        futures::stream::unfold(state, |mut state| async move {
+            let item = state.next().await?;
+            Some((item, state))
+        })
+    }
+
+    async fn resolve(&self, block: Option<crate::types::BlockNumber>) -> error::Result<u64> {
+        resolve_block(&self.eth, block).await
+    }
+}
+
+/// Resolves a block tag to the number of the block, `None` being the latest block.
+pub(crate) async fn resolve_block<T: crate::Transport>(
+    eth: &crate::api::Eth<T>,
+    block: Option<crate::types::BlockNumber>,
+) -> error::Result<u64> {
+    use crate::types::BlockNumber;
+
+    let number = match block {
+        Some(BlockNumber::Number(number)) => Some(number),
+        Some(BlockNumber::Earliest) => Some(0.into()),
+        Some(BlockNumber::Finalized) => eth.finalized_block_number().await?.and_then(|block| block.number),
+        Some(BlockNumber::Safe) => eth.safe_block_number().await?.and_then(|block| block.number),
+        Some(BlockNumber::Latest) | Some(BlockNumber::Pending) | None => Some(eth.block_number().await?),
+    };
+    number
+        .map(|number| number.as_u64())
+        .ok_or_else(|| Error::InvalidResponse(format!("Cannot resolve block {:?}", block)))
+}
+
+struct LogPagerState<T: crate::Transport> {
+    pager: LogPager<T>,
+    /// Next block to fetch and the last block of the range.
+    range: Option<(u64, u64)>,
+    /// Bisected ranges left to fetch (in reverse order).
+    pending: Vec<(u64, u64)>,
+    logs: std::collections::VecDeque<crate::types::Log>,
+    done: bool,
+}
+
+impl<T: crate::Transport> LogPagerState<T> {
+    async fn next(&mut self) -> Option<error::Result<crate::types::Log>> {
+        loop {
+            if let Some(log) = self.logs.pop_front() {
+                match self.pager.checkpoint {
+                    Some(ref checkpoint) if checkpoint.is_processed(&log) => continue,
+                    _ => return Some(Ok(log)),
+                }
+            }
+            if self.done {
+                return None;
+            }
+            match self.fetch_next().await {
+                Ok(true) => {}
+                Ok(false) => self.done = true,
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+
+    /// Fetches the next chunk, returns `false` if the range is exhausted.
+    async fn fetch_next(&mut self) -> error::Result<bool> {
+        let (next, end) = match self.range {
+            Some(range) => range,
+            None => {
+                let mut from = self.pager.resolve(self.pager.filter.from_block()).await?;
+                if let Some(ref checkpoint) = self.pager.checkpoint {
+                    from = from.max(checkpoint.next_block());
+                }
+                let to = self.pager.resolve(self.pager.filter.to_block()).await?;
+                self.range = Some((from, to));
+                (from, to)
+            }
+        };
+
+        let (from, to) = match self.pending.pop() {
+            Some(range) => range,
+            None if next > end => return Ok(false),
+            None => {
+                let to = next.saturating_add(self.pager.chunk_size - 1).min(end);
+                self.range = Some((to.saturating_add(1), end));
+                (next, to)
+            }
+        };
+
+        let filter = self.pager.filter.with_block_range(from.into(), to.into());
+        match self.pager.eth.logs(filter).await {
+            Ok(logs) => self.logs.extend(logs),
+            Err(Error::ResultSetTooLarge { suggested_range, .. }) if from < to => {
+                // prefer the range suggested by the provider, bisect otherwise
+                let mid = match suggested_range {
+                    Some((_, end)) if end >= from && end < to => end,
+                    _ => from + (to - from) / 2,
+                };
+                self.pager.chunk_size = self.pager.chunk_size.min(mid - from + 1);
+                self.pending.push((mid + 1, to));
+                self.pending.push((from, mid));
+            }
+            Err(err) => return Err(err),
+        }
+        Ok(true)
+    }
+}
+
+/// Turns an `eth_getLogs` error indicating that the query should be split into
+/// `Error::ResultSetTooLarge`, extracting the block range suggested by the provider (if any).
+///
+/// Recognizes the messages of the providers, or the `-32005` (limit exceeded) code with a suggested
+/// range. Other errors (e.g. rate limits, which Infura reports with `-32005` as well, or invalid
+/// filters) are returned unchanged.
+pub(crate) fn logs_error(err: Error) -> Error {
+    /// Lowercase prefixes of the messages.
+    const MESSAGES: &[&str] = &[
+        // geth, nethermind, infura
+        "query returned more than",
+        // alchemy
+        "log response size exceeded",
+        // ankr
+        "block range is too wide",
+        // erigon, bor
+        "query exceeds max block range",
+        // quicknode
+        "eth_getlogs is limited to",
+        "eth_getlogs and eth_newfilter are limited to",
+    ];
+    match err {
+        Error::Rpc(err) => {
+            let message = err.message.trim().to_lowercase();
+            let suggested_range = suggested_range(&err);
+            if MESSAGES.iter().any(|prefix| message.starts_with(prefix))
+                || (err.code == rpc::ErrorCode::ServerError(-32005) && suggested_range.is_some())
+            {
+                Error::ResultSetTooLarge {
+                    suggested_range,
+                    error: err,
+                }
+            } else {
+                Error::Rpc(err)
+            }
+        }
+        err => err,
+    }
+}
+
+/// Parses the range from `data` (`{"from": "0x1", "to": "0x2"}`) or the message (`[0x1, 0x2]`).
+fn suggested_range(err: &rpc::Error) -> Option<(u64, u64)> {
+    fn parse(number: &str) -> Option<u64> {
+        u64::from_str_radix(number.trim().strip_prefix("0x")?, 16).ok()
+    }
+
+    let from_data = err.data.as_ref().and_then(|data| {
+        let from = parse(data.get("from")?.as_str()?)?;
+        let to = parse(data.get("to")?.as_str()?)?;
+        Some((from, to))
+    });
+    let from_message = || {
+        let start = err.message.find('[')?;
+        let end = start + err.message[start..].find(']')?;
+        let mut numbers = err.message[start + 1..end].split(',');
+        let from = parse(numbers.next()?)?;
+        let to = parse(numbers.next()?)?;
+        Some((from, to))
+    };
+    from_data.or_else(from_message).filter(|(from, to)| from <= to)
+}
+
 /// Serialize a type. Panics if the type is returns error during serialization.
 pub fn serialize<T: serde::Serialize>(t: &T) -> rpc::Value {
     serde_json::to_value(t).expect("Types never fail to serialize.")
@@ -87,6 +493,33 @@ where
     }
 }
 
+/// Executes a call returning an array and yields its items, while they are received if the
+/// transport supports it (see `Transport::execute_stream`) or once the whole result is received.
+pub(crate) fn execute_stream<X, T>(
+    transport: &X,
+    method: &str,
+    params: Vec<rpc::Value>,
+) -> futures::stream::BoxStream<'static, error::Result<T>>
+where
+    X: Transport,
+    X::Out: Send + 'static,
+    T: DeserializeOwned + Send + 'static,
+{
+    use futures::{stream, StreamExt, TryFutureExt, TryStreamExt};
+
+    if let Some(stream) = transport.execute_stream(method, params.clone()) {
+        return stream;
+    }
+    stream::once(
+        transport
+            .execute(method, params)
+            .and_then(|value| async move { decode::<Vec<T>>(value) }),
+    )
+    .map_ok(|items| stream::iter(items.into_iter().map(Ok)))
+    .try_flatten()
+    .boxed()
+}
+
 /// Parse bytes slice into JSON-RPC notification.
 pub fn to_notification_from_slice(notification: &[u8]) -> error::Result<rpc::Notification> {
     serde_json::from_slice(notification).map_err(|e| error::Error::InvalidResponse(format!("{:?}", e)))
@@ -105,9 +538,510 @@ pub fn to_result_from_output(output: rpc::Output) -> error::Result<rpc::Value> {
     }
 }
 
+/// Parse the JSON value returned for a batch request into `rpc::Output`s.
+///
+/// Some nodes respond to a batch with a single error object, which is turned into an error.
+#[cfg(any(feature = "http", feature = "wasm-fetch"))]
+pub(crate) fn handle_possible_error_object_for_batched_request(value: rpc::Value) -> error::Result<Vec<rpc::Output>> {
+    if value.is_object() {
+        let output: rpc::Output = serde_json::from_value(value)?;
+        return Err(match output {
+            rpc::Output::Failure(failure) => Error::Rpc(failure.error),
+            rpc::Output::Success(success) => {
+                // totally unlikely - we got json success object for batched request
+                Error::InvalidResponse(format!("Invalid response for batched request: {:?}", success))
+            }
+        });
+    }
+    let outputs = serde_json::from_value(value)?;
+    Ok(outputs)
+}
+
+// According to the jsonrpc specification batch responses can be returned in any order so we need to
+// restore the intended order.
+#[cfg(any(feature = "http", feature = "wasm-fetch"))]
+pub(crate) fn handle_batch_response(
+    ids: &[rpc::Id],
+    outputs: Vec<rpc::Output>,
+) -> error::Result<Vec<error::Result<rpc::Value>>> {
+    if ids.len() != outputs.len() {
+        return Err(Error::InvalidResponse("unexpected number of responses".to_string()));
+    }
+    let mut outputs = outputs
+        .into_iter()
+        .map(|output| Ok((id_of_output(&output)?, to_result_from_output(output))))
+        .collect::<error::Result<std::collections::HashMap<_, _>>>()?;
+    ids.iter()
+        .map(|id| {
+            outputs
+                .remove(id)
+                .ok_or_else(|| Error::InvalidResponse(format!("batch response is missing id {:?}", id)))
+        })
+        .collect()
+}
+
+#[cfg(any(feature = "http", feature = "wasm-fetch"))]
+fn id_of_output(output: &rpc::Output) -> error::Result<rpc::Id> {
+    let id = match output {
+        rpc::Output::Success(success) => &success.id,
+        rpc::Output::Failure(failure) => &failure.id,
+    };
+    match id {
+        rpc::Id::Null => Err(Error::InvalidResponse("response id is null".to_string())),
+        id => Ok(id.clone()),
+    }
+}
+
+/// JSON-RPC id of the call, `Null` for notifications.
+#[cfg(any(
+    feature = "http",
+    feature = "wasm-fetch",
+    feature = "ws-tokio",
+    feature = "ws-async-std"
+))]
+pub(crate) fn id_of_call(call: &rpc::Call) -> rpc::Id {
+    match call {
+        rpc::Call::MethodCall(call) => call.id.clone(),
+        _ => rpc::Id::Null,
+    }
+}
+
+/// Span of a single RPC request (or batch) sent by a transport.
+///
+/// With the `tracing` feature enabled this is a `tracing` span carrying the request id,
+/// method and transport kind, which records the request duration once finished.
+/// Without the feature it does nothing.
+#[derive(Debug)]
+pub struct RequestSpan {
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+    #[cfg(feature = "tracing")]
+    started: std::time::Instant,
+}
+
+impl RequestSpan {
+    /// Opens a span for a single call.
+    pub fn new(transport: &'static str, id: RequestId, call: &rpc::Call) -> Self {
+        let method = match call {
+            rpc::Call::MethodCall(call) => call.method.as_str(),
+            rpc::Call::Notification(notification) => notification.method.as_str(),
+            rpc::Call::Invalid { .. } => "invalid",
+        };
+        Self::open(transport, id, method)
+    }
+
+    /// Opens a span for a batch of calls.
+    pub fn batch(transport: &'static str, id: RequestId) -> Self {
+        Self::open(transport, id, "batch")
+    }
+
+    #[cfg(feature = "tracing")]
+    fn open(transport: &'static str, id: RequestId, method: &str) -> Self {
+        let span = tracing::debug_span!(
+            "rpc",
+            transport,
+            id,
+            method,
+            duration_ms = tracing::field::Empty,
+            error = tracing::field::Empty,
+        );
+        RequestSpan {
+            span,
+            started: std::time::Instant::now(),
+        }
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    fn open(_transport: &'static str, _id: RequestId, _method: &str) -> Self {
+        RequestSpan {}
+    }
+
+    /// Runs given closure within the span.
+    pub fn in_scope<R>(&self, f: impl FnOnce() -> R) -> R {
+        #[cfg(feature = "tracing")]
+        return self.span.in_scope(f);
+        #[cfg(not(feature = "tracing"))]
+        f()
+    }
+
+    /// Records the outcome and duration of the request.
+    pub fn finish<T>(&self, result: &error::Result<T>) {
+        #[cfg(feature = "tracing")]
+        {
+            self.span
+                .record("duration_ms", self.started.elapsed().as_millis() as u64);
+            match result {
+                Ok(_) => tracing::debug!(parent: &self.span, "request finished"),
+                Err(err) => {
+                    self.span.record("error", tracing::field::display(err));
+                    tracing::debug!(parent: &self.span, "request failed");
+                }
+            }
+        }
+        #[cfg(not(feature = "tracing"))]
+        let _ = result;
+    }
+
+    /// Records the outcome and duration of a request whose response is streamed, once the
+    /// stream ends or fails.
+    pub fn finish_stream<S, T>(self, stream: S) -> impl futures::Stream<Item = error::Result<T>>
+    where
+        S: futures::Stream<Item = error::Result<T>> + Unpin,
+    {
+        use futures::StreamExt;
+
+        futures::stream::unfold((stream, Some(self)), |(mut stream, span)| async move {
+            let span = span?;
+            match stream.next().await {
+                Some(Ok(item)) => Some((Ok(item), (stream, Some(span)))),
+                Some(Err(err)) => {
+                    let err = Err(err);
+                    span.finish(&err);
+                    Some((err, (stream, None)))
+                }
+                None => {
+                    span.finish(&Ok(()));
+                    None
+                }
+            }
+        })
+    }
+}
+
 #[macro_use]
 #[cfg(test)]
 pub mod tests {
+    use super::{build_request, decode, logs_error, CancellationToken, LogPager, RequestSpan};
+    use crate::{
+        api::{Eth, Namespace},
+        rpc,
+        transports::test::TestTransport,
+        types::{BlockNumber, FilterBuilder},
+    };
+    use futures::{FutureExt, StreamExt};
+
+    #[test]
+    fn decode_distinguishes_null_responses() {
+        use crate::types::U64;
+
+        assert_eq!(decode::<Option<U64>>(rpc::Value::Null), Ok(None));
+        assert_eq!(
+            decode::<U64>(rpc::Value::Null),
+            Err(crate::Error::NullResponse("ethereum_types::uint::U64".into()))
+        );
+        assert!(matches!(
+            decode::<U64>(rpc::Value::Bool(true)),
+            Err(crate::Error::Decoder(_))
+        ));
+    }
+
+    #[test]
+    fn call_future_reports_method_of_null_response() {
+        let mut transport = TestTransport::default();
+        transport.add_response(rpc::Value::Null);
+        transport.add_response(rpc::Value::Null);
+        let eth = Eth::new(transport);
+
+        assert_eq!(
+            futures::executor::block_on(eth.block_number()),
+            Err(crate::Error::NullResponse("eth_blockNumber".into()))
+        );
+        assert_eq!(
+            futures::executor::block_on(eth.transaction_receipt(Default::default())),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn cancellation_token_resolves_all_clones() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        let mut cancelled = clone.cancelled();
+
+        assert!(!clone.is_cancelled());
+        assert_eq!((&mut cancelled).now_or_never(), None);
+
+        token.cancel();
+
+        assert!(clone.is_cancelled());
+        assert_eq!(futures::executor::block_on(cancelled), ());
+    }
+
+    #[test]
+    fn request_span_runs_in_scope() {
+        let span = RequestSpan::new("test", 1, &build_request(1, "eth_blockNumber", vec![]));
+
+        assert_eq!(span.in_scope(|| 5), 5);
+        span.finish(&Err::<(), _>(crate::Error::Unreachable));
+    }
+
+    fn log_with_index(index: u64) -> rpc::Value {
+        serde_json::json!({
+            "address": "0x16c5785ac562ff41e2dcfdf829c5a142f1fccd7d",
+            "topics": [],
+            "data": "0x",
+            "logIndex": format!("{:#x}", index),
+        })
+    }
+
+    fn logs_range(params: &[rpc::Value]) -> (String, String) {
+        (
+            params[0]["fromBlock"].as_str().unwrap().to_owned(),
+            params[0]["toBlock"].as_str().unwrap().to_owned(),
+        )
+    }
+
+    #[test]
+    fn log_pager_splits_range_into_chunks() {
+        let mut transport = TestTransport::default();
+        transport.add_response(rpc::Value::Array(vec![log_with_index(1)]));
+        transport.add_response(rpc::Value::Array(vec![]));
+        transport.add_response(rpc::Value::Array(vec![log_with_index(2)]));
+        let filter = FilterBuilder::default()
+            .from_block(BlockNumber::Number(0.into()))
+            .to_block(BlockNumber::Number(25.into()))
+            .build();
+
+        let logs = futures::executor::block_on(
+            LogPager::new(Eth::new(transport.clone()), filter)
+                .chunk_size(10)
+                .stream()
+                .collect::<Vec<_>>(),
+        );
+
+        let indices: Vec<_> = logs.into_iter().map(|log| log.unwrap().log_index).collect();
+        assert_eq!(indices, vec![Some(1.into()), Some(2.into())]);
+        let ranges: Vec<_> = transport
+            .requests()
+            .iter()
+            .map(|(_, params)| logs_range(params))
+            .collect();
+        assert_eq!(
+            ranges,
+            vec![
+                ("0x0".to_owned(), "0x9".to_owned()),
+                ("0xa".to_owned(), "0x13".to_owned()),
+                ("0x14".to_owned(), "0x19".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn log_pager_bisects_on_too_many_results() {
+        let mut transport = TestTransport::default();
+        transport.add_response(rpc::Value::String("0x7".into()));
+        transport.add_error(crate::Error::Rpc(rpc::Error {
+            code: rpc::ErrorCode::ServerError(-32005),
+            message: "query returned more than 10000 results".into(),
+            data: None,
+        }));
+        transport.add_response(rpc::Value::Array(vec![log_with_index(1)]));
+        transport.add_response(rpc::Value::Array(vec![log_with_index(2)]));
+        let filter = FilterBuilder::default()
+            .from_block(BlockNumber::Number(0.into()))
+            .build();
+
+        let logs = futures::executor::block_on(
+            LogPager::new(Eth::new(transport.clone()), filter)
+                .stream()
+                .collect::<Vec<_>>(),
+        );
+
+        let indices: Vec<_> = logs.into_iter().map(|log| log.unwrap().log_index).collect();
+        assert_eq!(indices, vec![Some(1.into()), Some(2.into())]);
+        let requests = transport.requests();
+        assert_eq!(requests[0].0, "eth_blockNumber");
+        let ranges: Vec<_> = requests[1..].iter().map(|(_, params)| logs_range(params)).collect();
+        assert_eq!(
+            ranges,
+            vec![
+                ("0x0".to_owned(), "0x7".to_owned()),
+                ("0x0".to_owned(), "0x3".to_owned()),
+                ("0x4".to_owned(), "0x7".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn log_pager_uses_suggested_range() {
+        let mut transport = TestTransport::default();
+        transport.add_error(crate::Error::Rpc(rpc::Error {
+            code: rpc::ErrorCode::ServerError(-32602),
+            message: "Log response size exceeded. Based on your parameters, this block range should work: [0x0, 0x2]"
+                .into(),
+            data: None,
+        }));
+        transport.add_response(rpc::Value::Array(vec![log_with_index(1)]));
+        transport.add_response(rpc::Value::Array(vec![log_with_index(2)]));
+        let filter = FilterBuilder::default()
+            .from_block(BlockNumber::Number(0.into()))
+            .to_block(BlockNumber::Number(7.into()))
+            .build();
+
+        let logs = futures::executor::block_on(
+            LogPager::new(Eth::new(transport.clone()), filter)
+                .stream()
+                .collect::<Vec<_>>(),
+        );
+
+        let indices: Vec<_> = logs.into_iter().map(|log| log.unwrap().log_index).collect();
+        assert_eq!(indices, vec![Some(1.into()), Some(2.into())]);
+        let ranges: Vec<_> = transport
+            .requests()
+            .iter()
+            .map(|(_, params)| logs_range(params))
+            .collect();
+        assert_eq!(
+            ranges,
+            vec![
+                ("0x0".to_owned(), "0x7".to_owned()),
+                ("0x0".to_owned(), "0x2".to_owned()),
+                ("0x3".to_owned(), "0x7".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn logs_error_extracts_suggested_range() {
+        let infura = rpc::Error {
+            code: rpc::ErrorCode::ServerError(-32005),
+            message: "query returned more than 10000 results".into(),
+            data: Some(serde_json::json!({"from": "0x10", "limit": 10000, "to": "0x1f"})),
+        };
+        assert_eq!(
+            logs_error(crate::Error::Rpc(infura.clone())),
+            crate::Error::ResultSetTooLarge {
+                suggested_range: Some((0x10, 0x1f)),
+                error: infura,
+            }
+        );
+
+        let message = rpc::Error {
+            code: rpc::ErrorCode::ServerError(-32000),
+            message: "query returned more than 10000 results. Try with this block range [0x4F35B3, 0x4F3B8C].".into(),
+            data: None,
+        };
+        assert!(matches!(
+            logs_error(crate::Error::Rpc(message)),
+            crate::Error::ResultSetTooLarge {
+                suggested_range: Some((0x4f35b3, 0x4f3b8c)),
+                ..
+            }
+        ));
+
+        let ankr = rpc::Error {
+            code: rpc::ErrorCode::ServerError(-32600),
+            message: "block range is too wide".into(),
+            data: None,
+        };
+        assert!(matches!(
+            logs_error(crate::Error::Rpc(ankr)),
+            crate::Error::ResultSetTooLarge {
+                suggested_range: None,
+                ..
+            }
+        ));
+
+        let other = rpc::Error::invalid_params("invalid address");
+        assert_eq!(logs_error(crate::Error::Rpc(other.clone())), crate::Error::Rpc(other));
+        for message in ["too many requests", "exceed max topics", "invalid block range params"] {
+            let other = rpc::Error {
+                code: rpc::ErrorCode::ServerError(-32000),
+                message: message.into(),
+                data: None,
+            };
+            assert_eq!(logs_error(crate::Error::Rpc(other.clone())), crate::Error::Rpc(other));
+        }
+        let rate_limited = rpc::Error {
+            code: rpc::ErrorCode::ServerError(-32005),
+            message: "daily request count exceeded, request rate limited".into(),
+            data: Some(serde_json::json!({"rate": {"allowed_rps": 1, "backoff_seconds": 30}})),
+        };
+        assert_eq!(
+            logs_error(crate::Error::Rpc(rate_limited.clone())),
+            crate::Error::Rpc(rate_limited)
+        );
+    }
+
+    #[test]
+    fn log_pager_stops_on_other_errors() {
+        let mut transport = TestTransport::default();
+        transport.add_error(crate::Error::Unreachable);
+        let filter = FilterBuilder::default()
+            .from_block(BlockNumber::Number(0.into()))
+            .to_block(BlockNumber::Number(5.into()))
+            .build();
+
+        let logs = futures::executor::block_on(
+            LogPager::new(Eth::new(transport.clone()), filter)
+                .stream()
+                .collect::<Vec<_>>(),
+        );
+
+        assert_eq!(logs, vec![Err(crate::Error::Unreachable)]);
+    }
+
+    #[test]
+    fn log_pager_does_not_bisect_on_rate_limits() {
+        let rate_limited = rpc::Error {
+            code: rpc::ErrorCode::ServerError(-32000),
+            message: "too many requests".into(),
+            data: None,
+        };
+        let mut transport = TestTransport::default();
+        transport.add_error(crate::Error::Rpc(rate_limited.clone()));
+        let filter = FilterBuilder::default()
+            .from_block(BlockNumber::Number(0.into()))
+            .to_block(BlockNumber::Number(5.into()))
+            .build();
+
+        let logs = futures::executor::block_on(
+            LogPager::new(Eth::new(transport.clone()), filter)
+                .stream()
+                .collect::<Vec<_>>(),
+        );
+
+        assert_eq!(logs, vec![Err(crate::Error::Rpc(rate_limited))]);
+        assert_eq!(transport.requests_of("eth_getLogs").len(), 1);
+    }
+
+    #[test]
+    fn log_pager_resumes_from_checkpoint() {
+        let mut transport = TestTransport::default();
+        let mut processed = log_with_index(1);
+        processed["blockNumber"] = "0x5".into();
+        let mut next = log_with_index(2);
+        next["blockNumber"] = "0x5".into();
+        transport.add_response(rpc::Value::Array(vec![processed, next]));
+        let filter = FilterBuilder::default()
+            .from_block(BlockNumber::Number(0.into()))
+            .to_block(BlockNumber::Number(9.into()))
+            .build();
+        let checkpoint = crate::types::LogCheckpoint {
+            version: 1,
+            last: crate::types::LogKey {
+                block_number: 5,
+                log_index: 1,
+            },
+        };
+
+        let logs = futures::executor::block_on(
+            LogPager::new(Eth::new(transport.clone()), filter)
+                .resume_from(checkpoint)
+                .stream()
+                .collect::<Vec<_>>(),
+        );
+
+        let indices: Vec<_> = logs.into_iter().map(|log| log.unwrap().log_index).collect();
+        assert_eq!(indices, vec![Some(2.into())]);
+        let ranges: Vec<_> = transport
+            .requests()
+            .iter()
+            .map(|(_, params)| logs_range(params))
+            .collect();
+        assert_eq!(ranges, vec![("0x5".to_owned(), "0x9".to_owned())]);
+    }
+
     macro_rules! rpc_test {
     // With parameters
     (
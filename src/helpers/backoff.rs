@@ -0,0 +1,196 @@
+//! Exponential backoff with jitter, shared by everything that retries or polls.
+
+use std::time::Duration;
+
+/// How a delay is randomized.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Jitter {
+    /// Use the delay as is.
+    None,
+    /// Pick uniformly from `[0, delay]`.
+    Full,
+    /// Pick uniformly from `[delay / 2, delay]`.
+    Equal,
+    /// Add or subtract up to given fraction (`0.0..=1.0`) of the delay.
+    Proportional(f64),
+}
+
+impl Jitter {
+    /// Randomizes given delay.
+    pub fn apply(&self, delay: Duration) -> Duration {
+        let secs = delay.as_secs_f64();
+        let secs = match *self {
+            Jitter::None => return delay,
+            Jitter::Full => secs * random_unit(),
+            Jitter::Equal => secs / 2.0 * (1.0 + random_unit()),
+            Jitter::Proportional(fraction) if fraction <= 0.0 => return delay,
+            Jitter::Proportional(fraction) => secs * (1.0 + (random_unit() * 2.0 - 1.0) * fraction.min(1.0)),
+        };
+        Duration::from_secs_f64(secs.max(0.0))
+    }
+}
+
+/// Exponential backoff: delays start at `base` and grow by `factor` after every attempt, up to `cap`.
+///
+/// With `max_elapsed` set the delays end once their sum would exceed it. Time spent outside of the
+/// delays (e.g. in the retried requests) isn't counted.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Backoff {
+    /// Delay before the first retry
+    pub base: Duration,
+    /// Upper bound of the delay (before jitter)
+    pub cap: Duration,
+    /// Factor the delay is multiplied by after each attempt
+    pub factor: f64,
+    /// Randomization applied to each delay
+    pub jitter: Jitter,
+    /// Total time to wait before giving up
+    pub max_elapsed: Option<Duration>,
+}
+
+impl Backoff {
+    /// Delays doubling from `base` up to `cap`.
+    pub fn exponential(base: Duration, cap: Duration) -> Self {
+        Backoff {
+            base,
+            cap: cap.max(base),
+            factor: 2.0,
+            jitter: Jitter::None,
+            max_elapsed: None,
+        }
+    }
+
+    /// Constant delay.
+    pub fn fixed(interval: Duration) -> Self {
+        Backoff {
+            factor: 1.0,
+            ..Backoff::exponential(interval, interval)
+        }
+    }
+
+    /// Sets the factor the delay grows by after each attempt.
+    pub fn factor(mut self, factor: f64) -> Self {
+        self.factor = factor.max(1.0);
+        self
+    }
+
+    /// Sets the randomization of delays.
+    pub fn jitter(mut self, jitter: Jitter) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Sets the total time to wait before giving up.
+    pub fn max_elapsed(mut self, max_elapsed: Duration) -> Self {
+        self.max_elapsed = Some(max_elapsed);
+        self
+    }
+
+    /// Returns the interval following given one (before jitter).
+    pub fn next_interval(&self, current: Duration) -> Duration {
+        let secs = (current.as_secs_f64() * self.factor).min(self.cap.as_secs_f64());
+        Duration::from_secs_f64(secs).max(self.base)
+    }
+
+    /// Returns the delays to wait between consecutive attempts.
+    pub fn delays(&self) -> Delays {
+        Delays {
+            backoff: *self,
+            interval: self.base,
+            elapsed: Duration::from_secs(0),
+        }
+    }
+}
+
+impl Default for Backoff {
+    /// Delays doubling from 100ms up to 10s, with equal jitter.
+    fn default() -> Self {
+        Backoff::exponential(Duration::from_millis(100), Duration::from_secs(10)).jitter(Jitter::Equal)
+    }
+}
+
+impl From<Duration> for Backoff {
+    fn from(interval: Duration) -> Self {
+        Backoff::fixed(interval)
+    }
+}
+
+/// Iterator of delays of a `Backoff`, see `Backoff::delays`.
+#[derive(Debug, Clone)]
+pub struct Delays {
+    backoff: Backoff,
+    interval: Duration,
+    elapsed: Duration,
+}
+
+impl Iterator for Delays {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Duration> {
+        let mut delay = self.backoff.jitter.apply(self.interval);
+        if let Some(max_elapsed) = self.backoff.max_elapsed {
+            if self.elapsed >= max_elapsed {
+                return None;
+            }
+            delay = delay.min(max_elapsed - self.elapsed);
+        }
+        self.elapsed += delay;
+        self.interval = self.backoff.next_interval(self.interval);
+        Some(delay)
+    }
+}
+
+/// Returns a pseudo-random number in `[0, 1)`.
+///
+/// Good enough for jitter, avoids pulling in a RNG dependency.
+fn random_unit() -> f64 {
+    use std::{
+        collections::hash_map::RandomState,
+        hash::{BuildHasher, Hasher},
+    };
+    let bits = RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn should_grow_delays_up_to_cap() {
+        let delays = Backoff::exponential(ms(100), ms(500))
+            .delays()
+            .take(5)
+            .collect::<Vec<_>>();
+        assert_eq!(delays, vec![ms(100), ms(200), ms(400), ms(500), ms(500)]);
+
+        let delays = Backoff::fixed(ms(100)).delays().take(3).collect::<Vec<_>>();
+        assert_eq!(delays, vec![ms(100); 3]);
+    }
+
+    #[test]
+    fn should_stop_after_max_elapsed() {
+        let delays = Backoff::exponential(ms(100), ms(500))
+            .max_elapsed(ms(1000))
+            .delays()
+            .collect::<Vec<_>>();
+        assert_eq!(delays, vec![ms(100), ms(200), ms(400), ms(300)]);
+    }
+
+    #[test]
+    fn should_apply_jitter_within_bounds() {
+        for _ in 0..100 {
+            assert!(Jitter::Full.apply(ms(100)) <= ms(100));
+            let equal = Jitter::Equal.apply(ms(100));
+            assert!(equal >= ms(50) && equal <= ms(100));
+            let proportional = Jitter::Proportional(0.1).apply(ms(100));
+            assert!(proportional >= ms(90) && proportional <= ms(110));
+        }
+        assert_eq!(Jitter::None.apply(ms(100)), ms(100));
+        assert_eq!(Jitter::Proportional(0.0).apply(ms(100)), ms(100));
+    }
+}
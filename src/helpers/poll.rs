@@ -0,0 +1,245 @@
+//! Polling a request until its result satisfies a condition.
+//!
+//! ```no_run
+//! # async fn example(web3: web3::Web3<web3::transports::Http>) -> web3::Result<()> {
+//! use std::time::Duration;
+//! use web3::{helpers::poll::{poll_until, PollPolicy}, types::Address};
+//!
+//! let account = Address::repeat_byte(1);
+//! let policy = PollPolicy::fixed(Duration::from_secs(1)).timeout(Duration::from_secs(60));
+//! let balance = poll_until(|| web3.eth().balance(account, None), |balance| !balance.is_zero(), policy).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    error,
+    helpers::backoff::{Backoff, Jitter},
+};
+use futures::{Future, Stream, StreamExt};
+use futures_timer::Delay;
+use std::time::Duration;
+
+/// How often and for how long to poll, used by filter streams, confirmations and `poll_until`.
+///
+/// A fixed policy polls at a constant interval. An adaptive policy starts at `backoff.base` and
+/// multiplies the interval by `backoff.factor` after every attempt (up to `backoff.cap`).
+///
+/// Filter streams snap back to `backoff.base` as soon as entries show up again, skip up to
+/// `max_errors` consecutive failed polls (yielding further errors without ending) and end once the
+/// delays exceed `backoff.max_elapsed`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PollPolicy {
+    /// Delays between attempts, polling ends once they end (see `Backoff::max_elapsed`)
+    pub backoff: Backoff,
+    /// Number of consecutive failed attempts tolerated before giving up
+    pub max_errors: usize,
+}
+
+impl PollPolicy {
+    /// Polls with given delays, giving up on the first error.
+    pub fn new<B: Into<Backoff>>(backoff: B) -> Self {
+        PollPolicy {
+            backoff: backoff.into(),
+            max_errors: 0,
+        }
+    }
+
+    /// Polls at a constant interval.
+    pub fn fixed(interval: Duration) -> Self {
+        PollPolicy::new(Backoff::fixed(interval))
+    }
+
+    /// Polls every `min_interval` at first, doubling the interval up to `max_interval`.
+    pub fn adaptive(min_interval: Duration, max_interval: Duration) -> Self {
+        PollPolicy::new(Backoff::exponential(min_interval, max_interval))
+    }
+
+    /// Sets the factor the interval grows by after each attempt.
+    pub fn backoff_factor(mut self, factor: f64) -> Self {
+        self.backoff = self.backoff.factor(factor);
+        self
+    }
+
+    /// Sets the jitter applied to every delay, as a fraction (`0.0..=1.0`) of the interval.
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        self.backoff = self.backoff.jitter(Jitter::Proportional(jitter.clamp(0.0, 1.0)));
+        self
+    }
+
+    /// Sets the total time to wait before giving up.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.backoff = self.backoff.max_elapsed(timeout);
+        self
+    }
+
+    /// Sets the number of consecutive failed attempts tolerated before giving up.
+    pub fn max_errors(mut self, max_errors: usize) -> Self {
+        self.max_errors = max_errors;
+        self
+    }
+
+    /// Returns the first interval.
+    pub fn interval(&self) -> Duration {
+        self.backoff.base
+    }
+
+    /// Returns the interval to use after a poll, given the current one.
+    pub(crate) fn next_interval(&self, current: Duration, had_entries: bool) -> Duration {
+        if had_entries {
+            return self.backoff.base;
+        }
+        self.backoff.next_interval(current)
+    }
+
+    /// Applies jitter to given interval.
+    pub(crate) fn delay(&self, interval: Duration) -> Duration {
+        self.backoff.jitter.apply(interval)
+    }
+}
+
+impl Default for PollPolicy {
+    /// Polls every second, without a timeout.
+    fn default() -> Self {
+        PollPolicy::fixed(Duration::from_secs(1))
+    }
+}
+
+impl From<Duration> for PollPolicy {
+    fn from(interval: Duration) -> Self {
+        PollPolicy::fixed(interval)
+    }
+}
+
+impl From<Backoff> for PollPolicy {
+    fn from(backoff: Backoff) -> Self {
+        PollPolicy::new(backoff)
+    }
+}
+
+/// Calls `f` right away and then after every delay of the policy, yielding the results.
+///
+/// Up to `policy.max_errors` consecutive errors are skipped, the next one is yielded and ends the
+/// stream. The stream also ends once the delays end.
+pub fn poll_stream<F, R, T, P>(f: F, policy: P) -> impl Stream<Item = error::Result<T>>
+where
+    F: FnMut() -> R,
+    R: Future<Output = error::Result<T>>,
+    P: Into<PollPolicy>,
+{
+    let policy = policy.into();
+    futures::stream::unfold(
+        (f, policy.backoff.delays(), true, 0),
+        move |(mut f, mut delays, first, mut errors)| async move {
+            if errors > policy.max_errors {
+                return None;
+            }
+            if !first {
+                Delay::new(delays.next()?).await;
+            }
+            loop {
+                match f().await {
+                    Ok(value) => return Some((Ok(value), (f, delays, false, 0))),
+                    Err(err) if errors >= policy.max_errors => return Some((Err(err), (f, delays, false, errors + 1))),
+                    Err(err) => {
+                        event!(debug, "Polling attempt failed: {}", err);
+                        errors += 1;
+                        Delay::new(delays.next()?).await;
+                    }
+                }
+            }
+        },
+    )
+}
+
+/// Calls `f` until its result satisfies the predicate, returns the result.
+///
+/// Fails with the error of the last attempt once more than `policy.max_errors` consecutive attempts
+/// fail, or with a `Transport` error once the delays of the policy end.
+pub async fn poll_until<F, R, T, P, Q>(mut f: F, mut predicate: P, policy: Q) -> error::Result<T>
+where
+    F: FnMut() -> R,
+    R: Future<Output = error::Result<T>>,
+    P: FnMut(&T) -> bool,
+    Q: Into<PollPolicy>,
+{
+    let count = std::cell::Cell::new(0);
+    let attempts = poll_stream(
+        || {
+            count.set(count.get() + 1);
+            f()
+        },
+        policy,
+    );
+    futures::pin_mut!(attempts);
+    while let Some(result) = attempts.next().await {
+        let value = result?;
+        if predicate(&value) {
+            return Ok(value);
+        }
+    }
+    Err(error::Error::Transport(error::TransportError::Message(format!(
+        "condition not met after {} attempts",
+        count.get()
+    ))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{api::Namespace, rpc::Value, transports::test::TestTransport, Web3};
+
+    fn policy() -> PollPolicy {
+        PollPolicy::fixed(Duration::from_millis(1)).timeout(Duration::from_millis(50))
+    }
+
+    #[test]
+    fn should_poll_until_predicate_is_met() {
+        let mut transport = TestTransport::default();
+        transport.add_response(Value::String("0x1".into()));
+        transport.add_response(Value::String("0x2".into()));
+        transport.add_response(Value::String("0x3".into()));
+        let web3 = Web3::new(transport.clone());
+
+        let result = futures::executor::block_on(poll_until(
+            || web3.eth().block_number(),
+            |number| number.as_u64() >= 3,
+            policy(),
+        ));
+
+        assert_eq!(result, Ok(3.into()));
+        assert_eq!(transport.requests_of("eth_blockNumber").len(), 3);
+    }
+
+    #[test]
+    fn should_tolerate_errors_up_to_max() {
+        let mut transport = TestTransport::default();
+        transport.add_error(error::Error::Unreachable);
+        transport.add_response(Value::String("0x3".into()));
+        let eth = crate::api::Eth::new(transport.clone());
+
+        let result = futures::executor::block_on(poll_until(|| eth.block_number(), |_| true, policy()));
+        assert_eq!(result, Err(error::Error::Unreachable));
+
+        let result = futures::executor::block_on(poll_until(|| eth.block_number(), |_| true, policy().max_errors(1)));
+        assert_eq!(result, Ok(3.into()));
+    }
+
+    #[test]
+    fn should_give_up_after_timeout() {
+        let mut transport = TestTransport::default();
+        for _ in 0..100 {
+            transport.add_response(Value::String("0x1".into()));
+        }
+        let eth = crate::api::Eth::new(transport);
+
+        let policy = PollPolicy::fixed(Duration::from_millis(1)).timeout(Duration::from_millis(3));
+        let result = futures::executor::block_on(poll_until(|| eth.block_number(), |_| false, policy));
+        assert_eq!(
+            result,
+            Err(error::Error::Transport(error::TransportError::Message(
+                "condition not met after 4 attempts".into()
+            )))
+        );
+    }
+}
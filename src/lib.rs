@@ -20,16 +20,29 @@ pub use futures::executor::{block_on, block_on_stream};
 
 pub use ethabi;
 
+// Lets derived code refer to `web3::` in the crate's own tests.
+#[cfg(test)]
+extern crate self as web3;
+
 // it needs to be before other modules
 // otherwise the macro for tests is not available.
 #[macro_use]
 pub mod helpers;
 
+pub mod abi;
 pub mod api;
+#[cfg(all(
+    feature = "http",
+    any(feature = "ws-tokio", feature = "ws-async-std"),
+    feature = "ipc-tokio"
+))]
+pub mod builder;
 pub mod confirm;
 pub mod contract;
 pub mod error;
+pub mod proof;
 pub mod signing;
+pub mod siwe;
 pub mod transports;
 pub mod types;
 
@@ -38,6 +51,13 @@ pub use crate::{
     error::{Error, Result},
 };
 
+#[cfg(all(
+    feature = "http",
+    any(feature = "ws-tokio", feature = "ws-async-std"),
+    feature = "ipc-tokio"
+))]
+pub use crate::builder::Builder;
+
 /// Assigned RequestId
 pub type RequestId = usize;
 
@@ -59,6 +79,23 @@ pub trait Transport: std::fmt::Debug + Clone {
         let (id, request) = self.prepare(method, params);
         self.send(id, request)
     }
+
+    /// Execute remote method returning an array, yielding its decoded items while the response is received.
+    ///
+    /// Returns `None` if the transport can't decode responses incrementally (the default), the
+    /// result has to be received with `execute` then. `Http::with_streamed_arrays` enables it,
+    /// transport wrappers forward it to the transport they wrap.
+    fn execute_stream<T>(
+        &self,
+        method: &str,
+        params: Vec<rpc::Value>,
+    ) -> Option<futures::stream::BoxStream<'static, error::Result<T>>>
+    where
+        T: serde::de::DeserializeOwned + Send + 'static,
+    {
+        let _ = (method, params);
+        None
+    }
 }
 
 /// A transport implementation supporting batch requests.
@@ -82,6 +119,26 @@ pub trait DuplexTransport: Transport {
 
     /// Remove a subscription from this transport
     fn unsubscribe(&self, id: api::SubscriptionId) -> error::Result<()>;
+
+    /// Cancels a subscription whose stream was dropped, on the node and in this transport.
+    ///
+    /// `eth_unsubscribe` is sent without waiting for the response: the request future is polled
+    /// once before it's dropped, so transports sending requests lazily dispatch it as well.
+    /// Transports emulating subscriptions or delaying requests override it.
+    fn cancel_subscription(&self, id: api::SubscriptionId) -> error::Result<()> {
+        let mut request = Box::pin(self.execute("eth_unsubscribe", vec![id.param()]));
+        let mut ctx = futures::task::Context::from_waker(futures::task::noop_waker_ref());
+        let _ = futures::Future::poll(request.as_mut(), &mut ctx);
+        self.unsubscribe(id)
+    }
+
+    /// Stream of connection state changes, e.g. to pause processing while the connection is down.
+    ///
+    /// The current state is yielded first. Transports without a persistent connection return
+    /// an empty stream.
+    fn connection_events(&self) -> futures::stream::BoxStream<'static, transports::ConnectionEvent> {
+        Box::pin(futures::stream::empty())
+    }
 }
 
 impl<X, T> Transport for X
@@ -100,6 +157,17 @@ where
     fn send(&self, id: RequestId, request: rpc::Call) -> Self::Out {
         (**self).send(id, request)
     }
+
+    fn execute_stream<I>(
+        &self,
+        method: &str,
+        params: Vec<rpc::Value>,
+    ) -> Option<futures::stream::BoxStream<'static, error::Result<I>>>
+    where
+        I: serde::de::DeserializeOwned + Send + 'static,
+    {
+        (**self).execute_stream(method, params)
+    }
 }
 
 impl<X, T> BatchTransport for X
@@ -135,6 +203,14 @@ where
     fn unsubscribe(&self, id: api::SubscriptionId) -> error::Result<()> {
         (**self).unsubscribe(id)
     }
+
+    fn cancel_subscription(&self, id: api::SubscriptionId) -> error::Result<()> {
+        (**self).cancel_subscription(id)
+    }
+
+    fn connection_events(&self) -> futures::stream::BoxStream<'static, transports::ConnectionEvent> {
+        (**self).connection_events()
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,488 @@
+//! Local verification of Merkle-Patricia trie proofs.
+//!
+//! Account and storage proofs returned by `eth_getProof` (EIP-1186) are checked against a state
+//! root, receipt and log proofs against a receipts root, both taken from a block header obtained
+//! from a trusted source, so the values they prove don't have to be trusted.
+//!
+//! Nodes don't serve receipt proofs, they have to be built from all receipts of the block (e.g.
+//! returned by `eth_getBlockReceipts`) or obtained from elsewhere.
+
+use crate::{
+    signing::keccak256,
+    types::{Bytes, Log, Proof, StorageProof, TransactionReceipt, H256, U256},
+};
+use rlp::{Rlp, RlpStream};
+
+/// An error verifying a Merkle-Patricia trie proof.
+#[derive(Debug, derive_more::Display, Clone, PartialEq, Eq)]
+pub enum ProofError {
+    /// A node of the proof is not a valid trie node.
+    #[display(fmt = "Invalid trie node: {}", _0)]
+    InvalidNode(String),
+    /// A node of the proof doesn't match the hash its parent (or the root) refers to.
+    #[display(fmt = "Proof node doesn't match its hash")]
+    HashMismatch,
+    /// The proof ends before reaching the key, or contains nodes past it.
+    #[display(fmt = "Proof doesn't match the path of the key")]
+    InvalidLength,
+    /// The proof is valid, but proves a different value.
+    #[display(fmt = "Proven value doesn't match the claimed one")]
+    ValueMismatch,
+}
+
+impl std::error::Error for ProofError {}
+
+impl From<rlp::DecoderError> for ProofError {
+    fn from(err: rlp::DecoderError) -> Self {
+        ProofError::InvalidNode(err.to_string())
+    }
+}
+
+/// Verifies the account proof and all storage proofs against given state root.
+pub fn verify_proof(proof: &Proof, state_root: H256) -> Result<(), ProofError> {
+    verify_account_proof(proof, state_root)?;
+    for storage_proof in &proof.storage_proof {
+        verify_storage_proof(storage_proof, proof.storage_hash)?;
+    }
+    Ok(())
+}
+
+/// Verifies that the account fields of the proof are stored in the state with given root.
+///
+/// Empty accounts may be proven absent from the state.
+pub fn verify_account_proof(proof: &Proof, state_root: H256) -> Result<(), ProofError> {
+    let key = keccak256(proof.address.as_bytes());
+    let value = verify_trie_proof(state_root, &key, &proof.account_proof)?;
+
+    let mut account = RlpStream::new_list(4);
+    account.append(&proof.nonce);
+    account.append(&proof.balance);
+    account.append(&proof.storage_hash);
+    account.append(&proof.code_hash);
+    let is_empty = proof.nonce.is_zero()
+        && proof.balance.is_zero()
+        && proof.storage_hash.0 == empty_trie_root()
+        && proof.code_hash.0 == keccak256(&[]);
+
+    match value {
+        Some(value) if value == account.as_raw() => Ok(()),
+        None if is_empty => Ok(()),
+        _ => Err(ProofError::ValueMismatch),
+    }
+}
+
+/// Verifies that the slot holds the value of the proof in the storage with given root.
+///
+/// Zero values are proven by absence of the slot.
+pub fn verify_storage_proof(proof: &StorageProof, storage_root: H256) -> Result<(), ProofError> {
+    let mut slot = [0u8; 32];
+    proof.key.to_big_endian(&mut slot);
+    let value = verify_trie_proof(storage_root, &keccak256(&slot), &proof.proof)?;
+
+    match value {
+        Some(value) if !proof.value.is_zero() && rlp::decode::<U256>(&value)? == proof.value => Ok(()),
+        None if proof.value.is_zero() => Ok(()),
+        _ => Err(ProofError::ValueMismatch),
+    }
+}
+
+/// Verifies that the receipt is stored at its transaction index in the receipts trie with given root.
+pub fn verify_receipt_proof(
+    receipt: &TransactionReceipt,
+    receipts_root: H256,
+    proof: &[Bytes],
+) -> Result<(), ProofError> {
+    let key = rlp::encode(&receipt.transaction_index.as_u64());
+    match verify_trie_proof(receipts_root, &key, proof)? {
+        Some(value) if value == encode_receipt(receipt) => Ok(()),
+        _ => Err(ProofError::ValueMismatch),
+    }
+}
+
+/// Verifies that the log was emitted by the receipt, and the receipt is stored in the receipts
+/// trie with given root.
+///
+/// Only the consensus fields of the log (`address`, `topics` and `data`) are proven.
+pub fn verify_log_proof(
+    log: &Log,
+    receipt: &TransactionReceipt,
+    receipts_root: H256,
+    proof: &[Bytes],
+) -> Result<(), ProofError> {
+    let emitted = receipt
+        .logs
+        .iter()
+        .any(|emitted| emitted.address == log.address && emitted.topics == log.topics && emitted.data == log.data);
+    if !emitted {
+        return Err(ProofError::ValueMismatch);
+    }
+    verify_receipt_proof(receipt, receipts_root, proof)
+}
+
+/// Consensus encoding of the receipt, as stored in the receipts trie (EIP-2718).
+pub fn encode_receipt(receipt: &TransactionReceipt) -> Vec<u8> {
+    let mut stream = RlpStream::new_list(4);
+    match (receipt.status, receipt.root) {
+        (Some(status), _) => stream.append(&status.as_u64()),
+        // receipts before Byzantium hold the intermediate state root instead of the status
+        (None, Some(root)) => stream.append(&root),
+        (None, None) => stream.append_empty_data(),
+    };
+    stream.append(&receipt.cumulative_gas_used);
+    stream.append(&receipt.logs_bloom.as_bytes());
+    stream.begin_list(receipt.logs.len());
+    for log in &receipt.logs {
+        stream.begin_list(3);
+        stream.append(&log.address);
+        stream.append_list(&log.topics);
+        stream.append(&log.data.0);
+    }
+
+    let mut encoded = vec![];
+    match receipt.transaction_type {
+        Some(transaction_type) if !transaction_type.is_zero() => encoded.push(transaction_type.as_u64() as u8),
+        _ => {}
+    }
+    encoded.extend_from_slice(&stream.out());
+    encoded
+}
+
+/// Returns the value stored under `key` in the trie with given root, or `None` if the proof
+/// shows the key is absent.
+///
+/// The proof consists of the RLP-encoded nodes on the path to the key, starting with the root.
+pub fn verify_trie_proof(root: H256, key: &[u8], proof: &[Bytes]) -> Result<Option<Vec<u8>>, ProofError> {
+    if proof.is_empty() && root.0 == empty_trie_root() {
+        return Ok(None);
+    }
+
+    let path: Vec<u8> = key.iter().flat_map(|byte| vec![byte >> 4, byte & 0xf]).collect();
+    let mut offset = 0;
+    let mut nodes = proof.iter();
+    let mut next = NodeRef::Hash(root);
+    let value = loop {
+        let node = match next {
+            NodeRef::Hash(hash) => {
+                let node = nodes.next().ok_or(ProofError::InvalidLength)?;
+                if keccak256(&node.0) != hash.0 {
+                    return Err(ProofError::HashMismatch);
+                }
+                node.0.clone()
+            }
+            NodeRef::Inline(node) => node,
+        };
+
+        let node = Rlp::new(&node);
+        match node.item_count()? {
+            17 => {
+                if offset == path.len() {
+                    let value = node.at(16)?.data()?;
+                    break if value.is_empty() { None } else { Some(value.to_vec()) };
+                }
+                let child = node.at(path[offset] as usize)?;
+                offset += 1;
+                match NodeRef::from_rlp(&child)? {
+                    Some(child) => next = child,
+                    None => break None,
+                }
+            }
+            2 => {
+                let (partial, is_leaf) = decode_path(node.at(0)?.data()?)?;
+                let rest = &path[offset..];
+                if is_leaf {
+                    break if rest == &partial[..] {
+                        Some(node.at(1)?.data()?.to_vec())
+                    } else {
+                        None
+                    };
+                }
+                if !rest.starts_with(&partial) {
+                    break None;
+                }
+                offset += partial.len();
+                next = NodeRef::from_rlp(&node.at(1)?)?
+                    .ok_or_else(|| ProofError::InvalidNode("Extension without child".into()))?;
+            }
+            count => return Err(ProofError::InvalidNode(format!("List of {} items", count))),
+        }
+    };
+
+    if nodes.next().is_some() {
+        return Err(ProofError::InvalidLength);
+    }
+    Ok(value)
+}
+
+/// Reference of a child node, nodes shorter than 32 bytes are embedded in their parent.
+enum NodeRef {
+    Hash(H256),
+    Inline(Vec<u8>),
+}
+
+impl NodeRef {
+    fn from_rlp(rlp: &Rlp) -> Result<Option<Self>, ProofError> {
+        if rlp.is_list() {
+            return Ok(Some(NodeRef::Inline(rlp.as_raw().to_vec())));
+        }
+        match rlp.data()? {
+            [] => Ok(None),
+            hash if hash.len() == 32 => Ok(Some(NodeRef::Hash(H256::from_slice(hash)))),
+            _ => Err(ProofError::InvalidNode("Invalid child reference".into())),
+        }
+    }
+}
+
+/// Decodes the hex-prefix encoded path of a leaf or extension node.
+fn decode_path(encoded: &[u8]) -> Result<(Vec<u8>, bool), ProofError> {
+    let (first, rest) = encoded
+        .split_first()
+        .ok_or_else(|| ProofError::InvalidNode("Empty path".into()))?;
+    let flag = first >> 4;
+    if flag > 3 {
+        return Err(ProofError::InvalidNode(format!("Invalid path flag {}", flag)));
+    }
+    let mut path = Vec::with_capacity(rest.len() * 2 + 1);
+    if flag & 1 == 1 {
+        path.push(first & 0xf);
+    }
+    path.extend(rest.iter().flat_map(|byte| vec![byte >> 4, byte & 0xf]));
+    Ok((path, flag & 2 == 2))
+}
+
+/// Root of an empty trie, `keccak256(rlp(""))`.
+fn empty_trie_root() -> [u8; 32] {
+    keccak256(&rlp::NULL_RLP)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Address, H2048};
+
+    /// Hex-prefix encodes the path of a leaf.
+    fn leaf(path: &[u8], value: &[u8]) -> Vec<u8> {
+        let mut encoded = vec![];
+        if path.len() % 2 == 1 {
+            encoded.push(0x30 | path[0]);
+        } else {
+            encoded.push(0x20);
+        }
+        let start = path.len() % 2;
+        encoded.extend(path[start..].chunks(2).map(|pair| pair[0] << 4 | pair[1]));
+        let mut stream = RlpStream::new_list(2);
+        stream.append(&encoded);
+        stream.append(&value);
+        stream.out().to_vec()
+    }
+
+    fn branch(children: &[(u8, &[u8])]) -> Vec<u8> {
+        let mut stream = RlpStream::new_list(17);
+        for nibble in 0..17u8 {
+            match children.iter().find(|(n, _)| *n == nibble) {
+                Some((_, node)) => stream.append(&keccak256(node).to_vec()),
+                None => stream.append_empty_data(),
+            };
+        }
+        stream.out().to_vec()
+    }
+
+    fn path(key: &[u8]) -> Vec<u8> {
+        keccak256(key)
+            .iter()
+            .flat_map(|byte| vec![byte >> 4, byte & 0xf])
+            .collect()
+    }
+
+    fn slot(key: u64) -> [u8; 32] {
+        let mut slot = [0u8; 32];
+        U256::from(key).to_big_endian(&mut slot);
+        slot
+    }
+
+    /// Storage with slots 0 and 1, which hashes differ in the first nibble.
+    fn storage() -> (H256, Vec<u8>, Vec<u8>, Vec<u8>) {
+        let (path0, path1) = (path(&slot(0)), path(&slot(1)));
+        let leaf0 = leaf(&path0[1..], &rlp::encode(&U256::from(0x1234)));
+        let leaf1 = leaf(&path1[1..], &rlp::encode(&U256::from(7)));
+        let root = branch(&[(path0[0], &leaf0), (path1[0], &leaf1)]);
+        (keccak256(&root).into(), root, leaf0, leaf1)
+    }
+
+    fn account() -> (H256, Proof) {
+        let (storage_hash, root, leaf0, _) = storage();
+        let address = Address::from_low_u64_be(0x123);
+        let mut proof = Proof {
+            address,
+            balance: 1_000.into(),
+            code_hash: keccak256(b"code").into(),
+            nonce: 2.into(),
+            storage_hash,
+            account_proof: vec![],
+            storage_proof: vec![
+                StorageProof {
+                    key: 0.into(),
+                    value: 0x1234.into(),
+                    proof: vec![root.clone().into(), leaf0.into()],
+                },
+                StorageProof {
+                    key: 2.into(),
+                    value: 0.into(),
+                    proof: vec![root.into()],
+                },
+            ],
+        };
+        let mut account = RlpStream::new_list(4);
+        account.append(&proof.nonce);
+        account.append(&proof.balance);
+        account.append(&proof.storage_hash);
+        account.append(&proof.code_hash);
+        let state = leaf(&path(address.as_bytes()), account.as_raw());
+        proof.account_proof = vec![state.clone().into()];
+        (keccak256(&state).into(), proof)
+    }
+
+    #[test]
+    fn should_verify_proof() {
+        let (state_root, proof) = account();
+        assert_eq!(verify_proof(&proof, state_root), Ok(()));
+    }
+
+    #[test]
+    fn should_reject_wrong_values() {
+        let (state_root, proof) = account();
+
+        let mut wrong_balance = proof.clone();
+        wrong_balance.balance = 1_001.into();
+        assert_eq!(verify_proof(&wrong_balance, state_root), Err(ProofError::ValueMismatch));
+
+        let mut wrong_slot = proof.clone();
+        wrong_slot.storage_proof[0].value = 0x1235.into();
+        assert_eq!(verify_proof(&wrong_slot, state_root), Err(ProofError::ValueMismatch));
+
+        let mut missing_slot = proof.clone();
+        missing_slot.storage_proof[1].value = 1.into();
+        assert_eq!(verify_proof(&missing_slot, state_root), Err(ProofError::ValueMismatch));
+
+        assert_eq!(
+            verify_proof(&proof, H256::repeat_byte(1)),
+            Err(ProofError::HashMismatch)
+        );
+    }
+
+    #[test]
+    fn should_reject_incomplete_proofs() {
+        let (storage_root, root, leaf0, leaf1) = storage();
+
+        assert_eq!(
+            verify_trie_proof(storage_root, &keccak256(&slot(0)), &[root.clone().into()]),
+            Err(ProofError::InvalidLength)
+        );
+        assert_eq!(
+            verify_trie_proof(storage_root, &keccak256(&slot(0)), &[root.clone().into(), leaf1.into()]),
+            Err(ProofError::HashMismatch)
+        );
+        assert_eq!(
+            verify_trie_proof(
+                storage_root,
+                &keccak256(&slot(0)),
+                &[root.into(), leaf0.clone().into(), leaf0.into()]
+            ),
+            Err(ProofError::InvalidLength)
+        );
+    }
+
+    #[test]
+    fn should_verify_empty_storage() {
+        let proof = StorageProof {
+            key: 1.into(),
+            value: 0.into(),
+            proof: vec![],
+        };
+
+        assert_eq!(verify_storage_proof(&proof, empty_trie_root().into()), Ok(()));
+    }
+
+    /// Receipts trie with the receipts of transactions 0 and 1, which keys `0x80` and `0x01`
+    /// differ in the first nibble.
+    fn receipts() -> (H256, Vec<TransactionReceipt>, Vec<Bytes>) {
+        let log = Log {
+            address: Address::from_low_u64_be(0x123),
+            topics: vec![H256::from_low_u64_be(1), H256::from_low_u64_be(2)],
+            data: vec![1, 2, 3].into(),
+            block_hash: None,
+            block_number: None,
+            transaction_hash: None,
+            transaction_index: None,
+            log_index: None,
+            transaction_log_index: None,
+            log_type: None,
+            removed: None,
+        };
+        let legacy = TransactionReceipt {
+            transaction_index: 0.into(),
+            status: Some(1.into()),
+            cumulative_gas_used: 21_000.into(),
+            logs_bloom: H2048::repeat_byte(1),
+            ..Default::default()
+        };
+        let eip1559 = TransactionReceipt {
+            transaction_index: 1.into(),
+            status: Some(0.into()),
+            cumulative_gas_used: 80_000.into(),
+            logs: vec![log],
+            logs_bloom: H2048::repeat_byte(2),
+            transaction_type: Some(2.into()),
+            ..Default::default()
+        };
+
+        let leaf0 = leaf(&[0], &encode_receipt(&legacy));
+        let leaf1 = leaf(&[1], &encode_receipt(&eip1559));
+        let root = branch(&[(8, &leaf0), (0, &leaf1)]);
+        (
+            keccak256(&root).into(),
+            vec![legacy, eip1559],
+            vec![root.clone().into(), leaf0.into(), root.into(), leaf1.into()],
+        )
+    }
+
+    #[test]
+    fn should_encode_receipts() {
+        let (_, receipts, _) = receipts();
+
+        let legacy = encode_receipt(&receipts[0]);
+        assert_eq!(&legacy[..6], &[0xf9, 0x01, 0x08, 0x01, 0x82, 0x52]);
+        let eip1559 = encode_receipt(&receipts[1]);
+        assert_eq!(eip1559[0], 0x02);
+        assert_eq!(rlp::Rlp::new(&eip1559[1..]).at(0).unwrap().as_raw(), &[0x80]);
+    }
+
+    #[test]
+    fn should_verify_receipt_and_log_proofs() {
+        let (receipts_root, receipts, nodes) = receipts();
+        let (proof0, proof1) = (&nodes[..2], &nodes[2..]);
+        let log = receipts[1].logs[0].clone();
+
+        assert_eq!(verify_receipt_proof(&receipts[0], receipts_root, proof0), Ok(()));
+        assert_eq!(verify_receipt_proof(&receipts[1], receipts_root, proof1), Ok(()));
+        assert_eq!(verify_log_proof(&log, &receipts[1], receipts_root, proof1), Ok(()));
+
+        let mut wrong_status = receipts[1].clone();
+        wrong_status.status = Some(1.into());
+        assert_eq!(
+            verify_receipt_proof(&wrong_status, receipts_root, proof1),
+            Err(ProofError::ValueMismatch)
+        );
+        let mut wrong_index = receipts[0].clone();
+        wrong_index.transaction_index = 1.into();
+        assert_eq!(
+            verify_receipt_proof(&wrong_index, receipts_root, proof1),
+            Err(ProofError::ValueMismatch)
+        );
+        let mut other_log = log;
+        other_log.data = vec![4].into();
+        assert_eq!(
+            verify_log_proof(&other_log, &receipts[1], receipts_root, proof1),
+            Err(ProofError::ValueMismatch)
+        );
+    }
+}
@@ -20,12 +20,22 @@ pub enum RecoveryError {
     /// A signature is invalid and the sender could not be recovered.
     #[display(fmt = "Signature is invalid (check recovery id).")]
     InvalidSignature,
+    /// A raw transaction is not a valid RLP encoded (typed or legacy) transaction.
+    #[display(fmt = "Raw transaction is not a valid signed transaction.")]
+    InvalidTransaction,
 }
 impl std::error::Error for RecoveryError {}
 
 #[cfg(feature = "signing")]
 pub use feature_gated::*;
 
+#[cfg(feature = "keystore")]
+pub mod keyfile;
+#[cfg(feature = "mnemonic")]
+mod wallet;
+#[cfg(feature = "mnemonic")]
+pub use wallet::{Wallet, WalletError};
+
 #[cfg(feature = "signing")]
 mod feature_gated {
     use super::*;
@@ -38,7 +48,7 @@ mod feature_gated {
     };
     use std::ops::Deref;
 
-    static CONTEXT: Lazy<Secp256k1<All>> = Lazy::new(Secp256k1::new);
+    pub(crate) static CONTEXT: Lazy<Secp256k1<All>> = Lazy::new(Secp256k1::new);
 
     /// A trait representing ethereum-compatible key with signing capabilities.
     ///
@@ -148,6 +158,60 @@ mod feature_gated {
         Ok(public_key_address(&public_key))
     }
 
+    /// Recover the sender of a raw signed transaction.
+    ///
+    /// Supports legacy transactions (with or without EIP-155 replay protection, any chain id),
+    /// chain specific fields of legacy transactions, and typed (EIP-2718) transactions.
+    pub fn recover_transaction(raw: &[u8]) -> Result<Address, RecoveryError> {
+        use crate::types::U256;
+        use rlp::{Rlp, RlpStream};
+
+        let invalid = |_| RecoveryError::InvalidTransaction;
+        let (tx_type, payload) = match raw.first() {
+            Some(&tx_type) if tx_type < 0x7f => (Some(tx_type), &raw[1..]),
+            Some(_) => (None, raw),
+            None => return Err(RecoveryError::InvalidTransaction),
+        };
+        let rlp = Rlp::new(payload);
+        let count = rlp.item_count().map_err(invalid)?;
+        if !rlp.is_list() || count < 4 {
+            return Err(RecoveryError::InvalidTransaction);
+        }
+        let v: u64 = rlp.val_at(count - 3).map_err(invalid)?;
+        let r: U256 = rlp.val_at(count - 2).map_err(invalid)?;
+        let s: U256 = rlp.val_at(count - 1).map_err(invalid)?;
+
+        // the signed payload is the transaction without signature, EIP-155 adds `chain_id, 0, 0`
+        let chain_id = match tx_type {
+            None if v >= 35 => Some((v - 35) / 2),
+            _ => None,
+        };
+        let mut stream = RlpStream::new_list(count - 3 + if chain_id.is_some() { 3 } else { 0 });
+        for item in rlp.iter().take(count - 3) {
+            stream.append_raw(item.as_raw(), 1);
+        }
+        if let Some(chain_id) = chain_id {
+            stream.append(&chain_id);
+            stream.append(&0u8);
+            stream.append(&0u8);
+        }
+        let message = match tx_type {
+            Some(tx_type) => keccak256(&[&[tx_type], stream.as_raw()].concat()),
+            None => keccak256(stream.as_raw()),
+        };
+
+        let recovery_id = match (tx_type, v) {
+            (Some(_), 0 | 1) => v as i32,
+            (None, 27 | 28) => v as i32 - 27,
+            (None, v) if v >= 35 => ((v - 35) % 2) as i32,
+            _ => return Err(RecoveryError::InvalidSignature),
+        };
+        let mut signature = [0u8; 64];
+        r.to_big_endian(&mut signature[..32]);
+        s.to_big_endian(&mut signature[32..]);
+        recover(&message, &signature, recovery_id)
+    }
+
     /// Gets the address of a public key.
     ///
     /// The public address is defined as the low 20 bytes of the keccak hash of
@@ -170,6 +234,121 @@ mod feature_gated {
         let public_key = PublicKey::from_secret_key(secp, key);
         public_key_address(&public_key)
     }
+
+    /// Return value of EIP-1271 `isValidSignature(bytes32,bytes)` for valid signatures.
+    pub(crate) const EIP1271_MAGIC_VALUE: [u8; 4] = [0x16, 0x26, 0xba, 0x7e];
+
+    /// Suffix of ERC-6492 wrapped signatures.
+    pub(crate) const ERC6492_SUFFIX: [u8; 32] = [
+        0x64, 0x92, 0x64, 0x92, 0x64, 0x92, 0x64, 0x92, 0x64, 0x92, 0x64, 0x92, 0x64, 0x92, 0x64, 0x92, 0x64, 0x92,
+        0x64, 0x92, 0x64, 0x92, 0x64, 0x92, 0x64, 0x92, 0x64, 0x92, 0x64, 0x92, 0x64, 0x92,
+    ];
+
+    /// Init code validating an ERC-6492 signature of a not yet deployed contract wallet
+    /// in a single `eth_call`.
+    ///
+    /// It expects `abi.encode(factory, len(factoryCalldata), signer, len(isValidSignatureCalldata))`
+    /// followed by both calldata appended to the code. It calls the factory (ignoring the result),
+    /// then `isValidSignature` on the signer, and returns its 32 bytes result (or zero on failure).
+    pub(crate) const ERC6492_VALIDATOR: [u8; 58] = [
+        0x61, 0x00, 0x3a, 0x38, 0x03, 0x61, 0x00, 0x3a, 0x60, 0x00, 0x39, // codecopy(0, 58, codesize - 58)
+        0x60, 0x00, 0x60, 0x00, 0x60, 0x20, 0x51, 0x60, 0x80, 0x60, 0x00, 0x60, 0x00, 0x51, 0x5a, 0xf1,
+        0x50, // pop(call(gas, factory, 0, 0x80, factoryCalldataLen, 0, 0))
+        0x60, 0x20, 0x60, 0x00, 0x60, 0x60, 0x51, 0x60, 0x20, 0x51, 0x60, 0x80, 0x01, 0x60, 0x40, 0x51, 0x5a,
+        0xfa, // staticcall(gas, signer, 0x80 + factoryCalldataLen, calldataLen, 0, 0x20)
+        0x60, 0x00, 0x51, 0x02, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3, // return(success * mload(0))
+    ];
+
+    /// Verifies a message signed with `personal_sign` (EIP-191) by given address.
+    ///
+    /// Supports signatures of externally owned accounts, of deployed contract wallets
+    /// (EIP-1271 `isValidSignature`) and of contract wallets not deployed yet (ERC-6492).
+    /// Returns `Ok(false)` for invalid signatures and errors only if the node can't be queried.
+    pub async fn verify_message<T, M, S>(
+        eth: &crate::api::Eth<T>,
+        address: Address,
+        message: M,
+        signature: S,
+    ) -> crate::error::Result<bool>
+    where
+        T: crate::Transport,
+        M: AsRef<[u8]>,
+        S: AsRef<[u8]>,
+    {
+        use crate::{
+            error::Error,
+            types::{Bytes, CallRequest},
+        };
+        use ethabi::{ParamType, Token};
+
+        let hash = hash_message(message);
+        let signature = signature.as_ref();
+
+        let is_valid_signature_call = |signature: Vec<u8>| {
+            let mut data = EIP1271_MAGIC_VALUE.to_vec();
+            data.extend(ethabi::encode(&[
+                Token::FixedBytes(hash.as_bytes().to_vec()),
+                Token::Bytes(signature),
+            ]));
+            data
+        };
+        let is_magic_value = |result: crate::error::Result<Bytes>| match result {
+            Ok(output) => Ok(output.0.starts_with(&EIP1271_MAGIC_VALUE)),
+            Err(Error::Rpc(_)) => Ok(false),
+            Err(err) => Err(err),
+        };
+
+        let code = eth.code(address, None).await?;
+
+        if signature.len() >= 32 && signature.ends_with(&ERC6492_SUFFIX) {
+            let wrapped = &signature[..signature.len() - 32];
+            let tokens = match ethabi::decode(&[ParamType::Address, ParamType::Bytes, ParamType::Bytes], wrapped) {
+                Ok(tokens) => tokens,
+                Err(_) => return Ok(false),
+            };
+            let (factory, factory_calldata, signature) = match &tokens[..] {
+                [Token::Address(factory), Token::Bytes(calldata), Token::Bytes(signature)] => {
+                    (*factory, calldata.clone(), signature.clone())
+                }
+                _ => return Ok(false),
+            };
+
+            let calldata = is_valid_signature_call(signature);
+            if !code.0.is_empty() {
+                let request = CallRequest::builder().to(address).data(calldata.into()).build();
+                return is_magic_value(eth.call(request, None).await);
+            }
+
+            let mut data = ERC6492_VALIDATOR.to_vec();
+            data.extend(ethabi::encode(&[
+                Token::Address(factory),
+                Token::Uint(factory_calldata.len().into()),
+                Token::Address(address),
+                Token::Uint(calldata.len().into()),
+            ]));
+            data.extend(factory_calldata);
+            data.extend(calldata);
+            let request = CallRequest::builder().data(data.into()).build();
+            return is_magic_value(eth.call(request, None).await);
+        }
+
+        if !code.0.is_empty() {
+            let calldata = is_valid_signature_call(signature.to_vec());
+            let request = CallRequest::builder().to(address).data(calldata.into()).build();
+            return is_magic_value(eth.call(request, None).await);
+        }
+
+        if signature.len() != 65 {
+            return Ok(false);
+        }
+        // Accept both electrum (27/28) and standard (0/1) notation.
+        let recovery_id = match signature[64] {
+            v @ (0 | 1) => v as i32,
+            v @ (27 | 28) => v as i32 - 27,
+            _ => return Ok(false),
+        };
+        Ok(recover(hash.as_bytes(), &signature[..64], recovery_id) == Ok(address))
+    }
 }
 
 /// A struct that represents the components of a secp256k1 signature.
@@ -279,4 +458,238 @@ mod tests {
 
         assert_eq!(expected, result);
     }
+
+    #[cfg(feature = "signing")]
+    mod verify_message {
+        use super::*;
+        use crate::{
+            api::{Eth, Namespace},
+            rpc::Value,
+            transports::test::TestTransport,
+            types::Address,
+        };
+        use std::str::FromStr;
+
+        const MESSAGE: &str = "Hello, world!";
+
+        fn signature(key: &SecretKey) -> Vec<u8> {
+            let signature = SecretKeyRef::new(key)
+                .sign(hash_message(MESSAGE).as_bytes(), None)
+                .unwrap();
+            let mut bytes = signature.r.as_bytes().to_vec();
+            bytes.extend_from_slice(signature.s.as_bytes());
+            bytes.push(signature.v as u8);
+            bytes
+        }
+
+        fn magic_value() -> Value {
+            Value::String(format!("0x1626ba7e{}", "0".repeat(56)))
+        }
+
+        #[test]
+        fn should_verify_eoa_signature() {
+            let key = SecretKey::from_str("4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318").unwrap();
+            let address = SecretKeyRef::new(&key).address();
+            let signature = signature(&key);
+
+            let mut transport = TestTransport::default();
+            transport.add_response(Value::String("0x".into()));
+            transport.add_response(Value::String("0x".into()));
+            let eth = Eth::new(&transport);
+
+            let valid = futures::executor::block_on(verify_message(&eth, address, MESSAGE, &signature));
+            let invalid = futures::executor::block_on(verify_message(&eth, Address::zero(), MESSAGE, &signature));
+
+            assert_eq!(valid, Ok(true));
+            assert_eq!(invalid, Ok(false));
+        }
+
+        #[test]
+        fn should_verify_contract_signature() {
+            let wallet = Address::from_low_u64_be(0x123);
+
+            let mut transport = TestTransport::default();
+            transport.add_response(Value::String("0x6080".into()));
+            transport.add_response(magic_value());
+            let result = {
+                let eth = Eth::new(&transport);
+                futures::executor::block_on(verify_message(&eth, wallet, MESSAGE, [1u8; 65]))
+            };
+
+            assert_eq!(result, Ok(true));
+            transport.assert_request(
+                "eth_getCode",
+                &[
+                    r#""0x0000000000000000000000000000000000000123""#.into(),
+                    r#""latest""#.into(),
+                ],
+            );
+            let (_, params) = transport.requests().remove(1);
+            assert_eq!(params[0]["to"], "0x0000000000000000000000000000000000000123");
+            assert!(params[0]["data"].as_str().unwrap().starts_with("0x1626ba7e"));
+        }
+
+        #[test]
+        fn should_verify_undeployed_contract_signature() {
+            let wallet = Address::from_low_u64_be(0x123);
+            let mut signature = ethabi::encode(&[
+                ethabi::Token::Address(Address::from_low_u64_be(0x456)),
+                ethabi::Token::Bytes(vec![1, 2, 3]),
+                ethabi::Token::Bytes(vec![4; 65]),
+            ]);
+            signature.extend_from_slice(&ERC6492_SUFFIX);
+
+            let mut transport = TestTransport::default();
+            transport.add_response(Value::String("0x".into()));
+            transport.add_response(magic_value());
+            let result = {
+                let eth = Eth::new(&transport);
+                futures::executor::block_on(verify_message(&eth, wallet, MESSAGE, &signature))
+            };
+
+            assert_eq!(result, Ok(true));
+            let (method, params) = transport.requests().remove(1);
+            assert_eq!(method, "eth_call");
+            assert!(params[0].get("to").is_none());
+            let data = params[0]["data"].as_str().unwrap();
+            assert!(data.starts_with(&format!("0x{}", hex::encode(ERC6492_VALIDATOR))));
+        }
+
+        /// Executes init code using only the opcodes of `ERC6492_VALIDATOR`, `call` handles calls
+        /// to other contracts: `(is_static, address, calldata) -> Option<output>`.
+        fn execute(code: &[u8], mut call: impl FnMut(bool, Address, &[u8]) -> Option<Vec<u8>>) -> Vec<u8> {
+            use crate::types::U256;
+
+            fn word(value: U256) -> [u8; 32] {
+                let mut word = [0u8; 32];
+                value.to_big_endian(&mut word);
+                word
+            }
+            fn range(memory: &mut Vec<u8>, offset: U256, size: U256) -> std::ops::Range<usize> {
+                let (offset, size) = (offset.as_usize(), size.as_usize());
+                if memory.len() < offset + size {
+                    memory.resize(offset + size, 0);
+                }
+                offset..offset + size
+            }
+
+            let (mut pc, mut stack, mut memory) = (0, Vec::<U256>::new(), Vec::new());
+            loop {
+                let opcode = code[pc];
+                pc += 1;
+                match opcode {
+                    0x60 | 0x61 => {
+                        let size = (opcode - 0x5f) as usize;
+                        stack.push(U256::from_big_endian(&code[pc..pc + size]));
+                        pc += size;
+                    }
+                    0x01 | 0x02 | 0x03 => {
+                        let (a, b) = (stack.pop().unwrap(), stack.pop().unwrap());
+                        stack.push(match opcode {
+                            0x01 => a + b,
+                            0x02 => a * b,
+                            _ => a - b,
+                        });
+                    }
+                    0x38 => stack.push(code.len().into()),
+                    0x39 => {
+                        let (dest, offset, size) = (stack.pop().unwrap(), stack.pop().unwrap(), stack.pop().unwrap());
+                        let dest = range(&mut memory, dest, size);
+                        memory[dest].copy_from_slice(&code[offset.as_usize()..offset.as_usize() + size.as_usize()]);
+                    }
+                    0x50 => {
+                        stack.pop();
+                    }
+                    0x51 => {
+                        let offset = stack.pop().unwrap();
+                        let word = range(&mut memory, offset, 32.into());
+                        stack.push(U256::from_big_endian(&memory[word]));
+                    }
+                    0x52 => {
+                        let (offset, value) = (stack.pop().unwrap(), stack.pop().unwrap());
+                        let dest = range(&mut memory, offset, 32.into());
+                        memory[dest].copy_from_slice(&word(value));
+                    }
+                    0x5a => stack.push(1_000_000.into()),
+                    0xf1 | 0xfa => {
+                        let _gas = stack.pop().unwrap();
+                        let address = Address::from_slice(&word(stack.pop().unwrap())[12..]);
+                        if opcode == 0xf1 {
+                            assert_eq!(stack.pop(), Some(U256::zero()), "no value is transferred");
+                        }
+                        let (args, args_size) = (stack.pop().unwrap(), stack.pop().unwrap());
+                        let (ret, ret_size) = (stack.pop().unwrap(), stack.pop().unwrap());
+                        let args = range(&mut memory, args, args_size);
+                        let output = call(opcode == 0xfa, address, &memory[args]);
+                        stack.push((output.is_some() as u8).into());
+                        let ret = range(&mut memory, ret, ret_size);
+                        let output = output.unwrap_or_default();
+                        let len = output.len().min(ret.len());
+                        memory[ret.start..ret.start + len].copy_from_slice(&output[..len]);
+                    }
+                    0xf3 => {
+                        let (offset, size) = (stack.pop().unwrap(), stack.pop().unwrap());
+                        let output = range(&mut memory, offset, size);
+                        return memory[output].to_vec();
+                    }
+                    opcode => panic!("unexpected opcode {:#x}", opcode),
+                }
+            }
+        }
+
+        #[test]
+        fn should_deploy_and_validate_with_erc6492_validator() {
+            let wallet = Address::from_low_u64_be(0x123);
+            let factory = Address::from_low_u64_be(0x456);
+            let factory_calldata = vec![0xab; 68];
+            let inner_signature = vec![4; 65];
+            let mut signature = ethabi::encode(&[
+                ethabi::Token::Address(factory),
+                ethabi::Token::Bytes(factory_calldata.clone()),
+                ethabi::Token::Bytes(inner_signature.clone()),
+            ]);
+            signature.extend_from_slice(&ERC6492_SUFFIX);
+            let mut transport = TestTransport::default();
+            transport.add_response(Value::String("0x".into()));
+            transport.add_response(magic_value());
+            let eth = Eth::new(&transport);
+            futures::executor::block_on(verify_message(&eth, wallet, MESSAGE, &signature)).unwrap();
+            let (_, params) = transport.requests().remove(1);
+            let code = hex::decode(&params[0]["data"].as_str().unwrap()[2..]).unwrap();
+            let mut is_valid_signature = EIP1271_MAGIC_VALUE.to_vec();
+            is_valid_signature.extend(ethabi::encode(&[
+                ethabi::Token::FixedBytes(hash_message(MESSAGE).as_bytes().to_vec()),
+                ethabi::Token::Bytes(inner_signature),
+            ]));
+            let mut magic = EIP1271_MAGIC_VALUE.to_vec();
+            magic.resize(32, 0);
+
+            // the factory deploys the wallet, which accepts the signature
+            let mut deployed = false;
+            let output = execute(&code, |is_static, address, calldata| {
+                if address == factory && !is_static {
+                    assert_eq!(calldata, &factory_calldata[..]);
+                    deployed = true;
+                    Some(vec![])
+                } else if address == wallet && is_static && deployed {
+                    assert_eq!(calldata, &is_valid_signature[..]);
+                    Some(magic.clone())
+                } else {
+                    None
+                }
+            });
+            assert_eq!(output, magic);
+
+            // the factory reverts as the wallet is deployed already, which still counts
+            let output = execute(&code, |is_static, address, _| match address == wallet && is_static {
+                true => Some(magic.clone()),
+                false => None,
+            });
+            assert_eq!(output, magic);
+
+            // neither the factory nor the wallet succeed
+            let output = execute(&code, |_, _, _| None);
+            assert_eq!(output, vec![0; 32]);
+        }
+    }
 }
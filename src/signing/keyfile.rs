@@ -0,0 +1,394 @@
+//! Web3 Secret Storage (V3 JSON keystore) support.
+//!
+//! Keystores encrypted with `scrypt` or `pbkdf2` (`hmac-sha256`) and `aes-128-ctr` can be
+//! decrypted into a `DecryptedKey` usable for signing, and new keystores can be created.
+//!
+//! ```no_run
+//! # fn example(json: &[u8]) -> Result<(), web3::signing::keyfile::KeyFileError> {
+//! use web3::signing::{keyfile::KeyFile, Key};
+//!
+//! let key = KeyFile::load(json)?.decrypt(b"password")?;
+//! println!("Loaded {:?}", key.address());
+//! # Ok(())
+//! # }
+//! ```
+
+use super::{keccak256, secret_key_address, SecretKey};
+use crate::types::Address;
+use aes::cipher::{KeyIvInit, StreamCipher};
+use serde::{Deserialize, Serialize};
+use std::ops::Deref;
+
+type Aes128Ctr = ctr::Ctr128BE<aes::Aes128>;
+
+const CIPHER: &str = "aes-128-ctr";
+const PRF: &str = "hmac-sha256";
+const DKLEN: usize = 32;
+
+/// Error while loading, decrypting or creating a keystore.
+#[derive(Debug, derive_more::Display)]
+pub enum KeyFileError {
+    /// Keystore is not valid JSON or has missing fields.
+    #[display(fmt = "Invalid keystore: {}", _0)]
+    Json(serde_json::Error),
+    /// Only version 3 keystores are supported.
+    #[display(fmt = "Unsupported keystore version: {}", _0)]
+    UnsupportedVersion(u32),
+    /// Only `aes-128-ctr` is supported.
+    #[display(fmt = "Unsupported cipher: {}", _0)]
+    UnsupportedCipher(String),
+    /// Key derivation parameters are invalid or unsupported.
+    #[display(fmt = "Invalid key derivation parameters: {}", _0)]
+    InvalidKdfParams(String),
+    /// The MAC doesn't match, most likely the password is wrong.
+    #[display(fmt = "Invalid password")]
+    InvalidPassword,
+    /// Decrypted data is not a valid secret key.
+    #[display(fmt = "Invalid secret key")]
+    InvalidKey,
+    /// Random number generator failed.
+    #[display(fmt = "Random number generator failed: {}", _0)]
+    Random(getrandom::Error),
+}
+
+impl std::error::Error for KeyFileError {}
+
+impl From<serde_json::Error> for KeyFileError {
+    fn from(err: serde_json::Error) -> Self {
+        KeyFileError::Json(err)
+    }
+}
+
+/// V3 JSON keystore.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct KeyFile {
+    /// Keystore version, always 3
+    pub version: u32,
+    /// UUID of the keystore
+    pub id: String,
+    /// Address of the key (hex without `0x` prefix), not authenticated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    /// Encrypted key
+    #[serde(alias = "Crypto")]
+    pub crypto: Crypto,
+}
+
+/// Encrypted key of a `KeyFile`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Crypto {
+    /// Cipher, `aes-128-ctr`
+    pub cipher: String,
+    /// Cipher parameters
+    pub cipherparams: CipherParams,
+    /// Encrypted secret key
+    #[serde(with = "hex_bytes")]
+    pub ciphertext: Vec<u8>,
+    /// Key derivation function and its parameters
+    #[serde(flatten)]
+    pub kdf: Kdf,
+    /// MAC of the ciphertext
+    #[serde(with = "hex_bytes")]
+    pub mac: Vec<u8>,
+}
+
+/// Parameters of the `aes-128-ctr` cipher.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct CipherParams {
+    /// Initialization vector
+    #[serde(with = "hex_bytes")]
+    pub iv: Vec<u8>,
+}
+
+/// Key derivation function deriving the encryption key from the password.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "kdf", content = "kdfparams", rename_all = "lowercase")]
+pub enum Kdf {
+    /// `scrypt`
+    Scrypt(ScryptParams),
+    /// `pbkdf2` with `hmac-sha256`
+    Pbkdf2(Pbkdf2Params),
+}
+
+/// Parameters of `scrypt`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ScryptParams {
+    /// Derived key length
+    pub dklen: usize,
+    /// CPU/memory cost, a power of 2
+    pub n: u64,
+    /// Block size
+    pub r: u32,
+    /// Parallelization
+    pub p: u32,
+    /// Salt
+    #[serde(with = "hex_bytes")]
+    pub salt: Vec<u8>,
+}
+
+/// Parameters of `pbkdf2`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Pbkdf2Params {
+    /// Derived key length
+    pub dklen: usize,
+    /// Number of iterations
+    pub c: u32,
+    /// Pseudo-random function, `hmac-sha256`
+    pub prf: String,
+    /// Salt
+    #[serde(with = "hex_bytes")]
+    pub salt: Vec<u8>,
+}
+
+impl Kdf {
+    /// `scrypt` with the standard parameters (`n = 2^18`, `r = 8`, `p = 1`) and a random salt.
+    pub fn scrypt() -> Result<Self, KeyFileError> {
+        Kdf::scrypt_with(18, 8, 1)
+    }
+
+    /// `scrypt` with the light parameters used by Geth (`n = 2^12`, `r = 8`, `p = 6`) and a random salt.
+    pub fn scrypt_light() -> Result<Self, KeyFileError> {
+        Kdf::scrypt_with(12, 8, 6)
+    }
+
+    /// `scrypt` with given parameters and a random salt.
+    pub fn scrypt_with(log_n: u8, r: u32, p: u32) -> Result<Self, KeyFileError> {
+        Ok(Kdf::Scrypt(ScryptParams {
+            dklen: DKLEN,
+            n: 1 << log_n,
+            r,
+            p,
+            salt: random(32)?,
+        }))
+    }
+
+    /// `pbkdf2` with given number of iterations and a random salt.
+    pub fn pbkdf2(iterations: u32) -> Result<Self, KeyFileError> {
+        Ok(Kdf::Pbkdf2(Pbkdf2Params {
+            dklen: DKLEN,
+            c: iterations,
+            prf: PRF.into(),
+            salt: random(32)?,
+        }))
+    }
+
+    fn derive(&self, password: &[u8]) -> Result<Vec<u8>, KeyFileError> {
+        let invalid = |err: &dyn std::fmt::Display| KeyFileError::InvalidKdfParams(err.to_string());
+        let dklen = match *self {
+            Kdf::Scrypt(ScryptParams { dklen, .. }) | Kdf::Pbkdf2(Pbkdf2Params { dklen, .. }) => dklen,
+        };
+        if dklen < DKLEN {
+            return Err(invalid(&format!("dklen {} is shorter than {}", dklen, DKLEN)));
+        }
+        let mut key = vec![0; dklen];
+        match self {
+            Kdf::Scrypt(params) => {
+                if !params.n.is_power_of_two() {
+                    return Err(invalid(&format!("n {} is not a power of 2", params.n)));
+                }
+                let log_n = params.n.trailing_zeros() as u8;
+                let scrypt_params =
+                    scrypt::Params::new(log_n, params.r, params.p, dklen).map_err(|err| invalid(&err))?;
+                scrypt::scrypt(password, &params.salt, &scrypt_params, &mut key).map_err(|err| invalid(&err))?;
+            }
+            Kdf::Pbkdf2(params) => {
+                if params.prf != PRF {
+                    return Err(invalid(&format!("unsupported prf {}", params.prf)));
+                }
+                pbkdf2::pbkdf2_hmac::<sha2::Sha256>(password, &params.salt, params.c, &mut key);
+            }
+        }
+        Ok(key)
+    }
+}
+
+impl KeyFile {
+    /// Parses a keystore from JSON.
+    pub fn load(json: &[u8]) -> Result<Self, KeyFileError> {
+        let keyfile: KeyFile = serde_json::from_slice(json)?;
+        if keyfile.version != 3 {
+            return Err(KeyFileError::UnsupportedVersion(keyfile.version));
+        }
+        Ok(keyfile)
+    }
+
+    /// Encrypts a secret key with given password, using given key derivation function.
+    pub fn encrypt(key: &SecretKey, password: &[u8], kdf: Kdf) -> Result<Self, KeyFileError> {
+        let derived = kdf.derive(password)?;
+        let iv = random(16)?;
+        let mut ciphertext = key.secret_bytes().to_vec();
+        Aes128Ctr::new(derived[..16].into(), iv[..].into()).apply_keystream(&mut ciphertext);
+        let mac = mac(&derived, &ciphertext);
+        Ok(KeyFile {
+            version: 3,
+            id: uuid_v4()?,
+            address: Some(hex::encode(secret_key_address(key))),
+            crypto: Crypto {
+                cipher: CIPHER.into(),
+                cipherparams: CipherParams { iv },
+                ciphertext,
+                kdf,
+                mac: mac.to_vec(),
+            },
+        })
+    }
+
+    /// Decrypts the secret key with given password.
+    pub fn decrypt(&self, password: &[u8]) -> Result<DecryptedKey, KeyFileError> {
+        let crypto = &self.crypto;
+        if crypto.cipher != CIPHER {
+            return Err(KeyFileError::UnsupportedCipher(crypto.cipher.clone()));
+        }
+        if crypto.cipherparams.iv.len() != 16 {
+            return Err(KeyFileError::UnsupportedCipher(format!(
+                "{} with {} bytes iv",
+                crypto.cipher,
+                crypto.cipherparams.iv.len()
+            )));
+        }
+        let derived = crypto.kdf.derive(password)?;
+        if mac(&derived, &crypto.ciphertext)[..] != crypto.mac[..] {
+            return Err(KeyFileError::InvalidPassword);
+        }
+        let mut plaintext = crypto.ciphertext.clone();
+        Aes128Ctr::new(derived[..16].into(), crypto.cipherparams.iv[..].into()).apply_keystream(&mut plaintext);
+        let key = SecretKey::from_slice(&plaintext).map_err(|_| KeyFileError::InvalidKey);
+        plaintext.iter_mut().for_each(|byte| *byte = 0);
+        Ok(DecryptedKey { key: key? })
+    }
+}
+
+/// Secret key decrypted from a `KeyFile`.
+///
+/// Implements `Key` and erases the key from memory (on a best effort basis) when dropped.
+pub struct DecryptedKey {
+    key: SecretKey,
+}
+
+impl DecryptedKey {
+    /// Address of the key.
+    pub fn address(&self) -> Address {
+        secret_key_address(&self.key)
+    }
+}
+
+impl Deref for DecryptedKey {
+    type Target = SecretKey;
+
+    fn deref(&self) -> &Self::Target {
+        &self.key
+    }
+}
+
+impl Drop for DecryptedKey {
+    fn drop(&mut self) {
+        self.key.non_secure_erase();
+    }
+}
+
+impl std::fmt::Debug for DecryptedKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DecryptedKey")
+            .field("address", &self.address())
+            .finish()
+    }
+}
+
+fn mac(derived: &[u8], ciphertext: &[u8]) -> [u8; 32] {
+    let mut data = derived[16..32].to_vec();
+    data.extend_from_slice(ciphertext);
+    keccak256(&data)
+}
+
+fn random(len: usize) -> Result<Vec<u8>, KeyFileError> {
+    let mut bytes = vec![0; len];
+    getrandom::getrandom(&mut bytes).map_err(KeyFileError::Random)?;
+    Ok(bytes)
+}
+
+fn uuid_v4() -> Result<String, KeyFileError> {
+    let mut bytes = random(16)?;
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex = hex::encode(bytes);
+    Ok(format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    ))
+}
+
+mod hex_bytes {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        let value = value.strip_prefix("0x").unwrap_or(&value);
+        hex::decode(value).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signing::Key;
+
+    // Test vector from the Web3 Secret Storage Definition.
+    const PBKDF2_KEYFILE: &str = r#"{
+        "crypto" : {
+            "cipher" : "aes-128-ctr",
+            "cipherparams" : {
+                "iv" : "6087dab2f9fdbbfaddc31a909735c1e6"
+            },
+            "ciphertext" : "5318b4d5bcd28de64ee5559e671353e16f075ecae9f99c7a79a38af5f869aa46",
+            "kdf" : "pbkdf2",
+            "kdfparams" : {
+                "c" : 262144,
+                "dklen" : 32,
+                "prf" : "hmac-sha256",
+                "salt" : "ae3cd4e7013836a3df6bd7241b12db061dbe2c6785853cce422d148a624ce0bd"
+            },
+            "mac" : "517ead924a9d0dc3124507e3393d175ce3ff7c1e96529c6c555ce9e51205e9b2"
+        },
+        "id" : "3198bc9c-6672-5ab3-d995-4942343ae5b6",
+        "version" : 3
+    }"#;
+
+    #[test]
+    fn should_decrypt_pbkdf2_keyfile() {
+        let keyfile = KeyFile::load(PBKDF2_KEYFILE.as_bytes()).unwrap();
+
+        let key = keyfile.decrypt(b"testpassword").unwrap();
+
+        assert_eq!(
+            hex::encode(key.secret_bytes()),
+            "7a28b5ba57c53603b0b07b56bba752f7784bf506fa95edc395f5cf6c7514fe9d"
+        );
+        assert!(matches!(
+            keyfile.decrypt(b"wrongpassword"),
+            Err(KeyFileError::InvalidPassword)
+        ));
+    }
+
+    #[test]
+    fn should_encrypt_and_decrypt_scrypt_keyfile() {
+        let key = SecretKey::from_slice(&[0x11; 32]).unwrap();
+
+        let keyfile = KeyFile::encrypt(&key, b"password", Kdf::scrypt_with(10, 8, 1).unwrap()).unwrap();
+        let json = serde_json::to_string(&keyfile).unwrap();
+        let decrypted = KeyFile::load(json.as_bytes()).unwrap().decrypt(b"password").unwrap();
+
+        assert_eq!(*decrypted, key);
+        assert_eq!(decrypted.address(), Key::address(&&key));
+        assert_eq!(keyfile.address, Some(hex::encode(decrypted.address())));
+        assert!(json.contains(r#""kdf":"scrypt""#));
+        assert_eq!(keyfile.id.len(), 36);
+    }
+}
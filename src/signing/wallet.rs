@@ -0,0 +1,253 @@
+//! HD wallet derivation from BIP-39 mnemonics.
+
+use super::{secret_key_address, SecretKey, CONTEXT};
+use crate::types::Address;
+use hmac::{Hmac, Mac};
+use secp256k1::{PublicKey, Scalar};
+use sha2::{Digest, Sha256, Sha512};
+use std::ops::Deref;
+
+/// Offset of hardened child indexes.
+const HARDENED: u32 = 0x8000_0000;
+/// BIP-39 English word list, sorted.
+const WORDS: &str = include_str!("bip39-english.txt");
+
+/// Error while deriving a `Wallet`.
+#[derive(Debug, Clone, PartialEq, derive_more::Display)]
+pub enum WalletError {
+    /// Mnemonic has an invalid number of words.
+    #[display(fmt = "Mnemonic has {} words, expected 12, 15, 18, 21 or 24", _0)]
+    InvalidMnemonic(usize),
+    /// Mnemonic contains a word missing from the BIP-39 English word list.
+    #[display(fmt = "Mnemonic word {:?} is not in the BIP-39 English word list", _0)]
+    UnknownWord(String),
+    /// Checksum of the mnemonic doesn't match, e.g. a word was mistyped or swapped.
+    #[display(fmt = "Mnemonic checksum is invalid")]
+    InvalidChecksum,
+    /// Derivation path is malformed.
+    #[display(fmt = "Invalid derivation path: {}", _0)]
+    InvalidPath(String),
+    /// Derived key is invalid (probability lower than 1 in 2^127).
+    #[display(fmt = "Derived key is invalid")]
+    InvalidKey,
+}
+
+impl std::error::Error for WalletError {}
+
+/// Key derived from a BIP-39 mnemonic along a BIP-32 path.
+///
+/// Implements `Key` and erases the key from memory (on a best effort basis) when dropped.
+pub struct Wallet {
+    key: SecretKey,
+}
+
+impl Wallet {
+    /// Derives the key of account `index` along the default Ethereum path (`m/44'/60'/0'/0/index`),
+    /// with an empty passphrase. Matches the accounts of Ganache, Hardhat, Anvil and MetaMask.
+    pub fn from_mnemonic(phrase: &str, index: u32) -> Result<Self, WalletError> {
+        Wallet::from_mnemonic_with_path(phrase, "", &format!("m/44'/60'/0'/0/{}", index))
+    }
+
+    /// Derives the key along given path (e.g. `m/44'/60'/0'/0/0`, hardened indexes marked with `'`
+    /// or `h`) from a mnemonic protected with given passphrase.
+    ///
+    /// The words and the checksum are checked against the BIP-39 English word list, derive the seed
+    /// of mnemonics in other languages yourself and use `from_seed`. Non-ASCII passphrases have to be
+    /// NFKD normalized.
+    pub fn from_mnemonic_with_path(phrase: &str, passphrase: &str, path: &str) -> Result<Self, WalletError> {
+        let words = phrase.split_whitespace().collect::<Vec<_>>();
+        check_mnemonic(&words)?;
+        let mut seed = [0u8; 64];
+        let salt = format!("mnemonic{}", passphrase);
+        pbkdf2::pbkdf2_hmac::<Sha512>(words.join(" ").as_bytes(), salt.as_bytes(), 2048, &mut seed);
+        let wallet = Wallet::from_seed(&seed, path);
+        seed.iter_mut().for_each(|byte| *byte = 0);
+        wallet
+    }
+
+    /// Derives the key along given path from a BIP-32 seed.
+    pub fn from_seed(seed: &[u8], path: &str) -> Result<Self, WalletError> {
+        let path = parse_path(path)?;
+        let (mut key, mut chain_code) = split(hmac_sha512(b"Bitcoin seed", &[seed]))?;
+        for index in path {
+            let mut child = derive_child(&key, &chain_code, index)?;
+            std::mem::swap(&mut key, &mut child.0);
+            chain_code = child.1;
+            child.0.non_secure_erase();
+        }
+        Ok(Wallet { key })
+    }
+
+    /// Address of the key.
+    pub fn address(&self) -> Address {
+        secret_key_address(&self.key)
+    }
+}
+
+impl Deref for Wallet {
+    type Target = SecretKey;
+
+    fn deref(&self) -> &Self::Target {
+        &self.key
+    }
+}
+
+impl Drop for Wallet {
+    fn drop(&mut self) {
+        self.key.non_secure_erase();
+    }
+}
+
+impl std::fmt::Debug for Wallet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Wallet").field("address", &self.address()).finish()
+    }
+}
+
+/// Checks the number of words, that every word is in the word list and the checksum.
+fn check_mnemonic(words: &[&str]) -> Result<(), WalletError> {
+    if words.len() < 12 || words.len() > 24 || !words.len().is_multiple_of(3) {
+        return Err(WalletError::InvalidMnemonic(words.len()));
+    }
+    // every word encodes 11 bits of the entropy followed by the checksum, at most 33 bytes
+    let list = WORDS.lines().collect::<Vec<_>>();
+    let mut bits = [0u8; 33];
+    for (position, word) in words.iter().enumerate() {
+        let index = list
+            .binary_search(word)
+            .map_err(|_| WalletError::UnknownWord((*word).into()))?;
+        for bit in 0..11 {
+            if index & (1 << (10 - bit)) != 0 {
+                let offset = position * 11 + bit;
+                bits[offset / 8] |= 0x80 >> (offset % 8);
+            }
+        }
+    }
+    // the checksum has one bit per 3 words, the entropy 32 bits per checksum bit
+    let checksum_bits = words.len() / 3;
+    let entropy_len = checksum_bits * 4;
+    let expected = Sha256::digest(&bits[..entropy_len])[0] >> (8 - checksum_bits);
+    if bits[entropy_len] >> (8 - checksum_bits) != expected {
+        return Err(WalletError::InvalidChecksum);
+    }
+    Ok(())
+}
+
+fn parse_path(path: &str) -> Result<Vec<u32>, WalletError> {
+    let invalid = || WalletError::InvalidPath(path.into());
+    let mut segments = path.trim().split('/');
+    if segments.next() != Some("m") {
+        return Err(invalid());
+    }
+    segments
+        .map(|segment| {
+            let (number, offset) = match segment.strip_suffix('\'').or_else(|| segment.strip_suffix('h')) {
+                Some(number) => (number, HARDENED),
+                None => (segment, 0),
+            };
+            match number.parse::<u32>() {
+                Ok(index) if index < HARDENED => Ok(index + offset),
+                _ => Err(invalid()),
+            }
+        })
+        .collect()
+}
+
+fn derive_child(key: &SecretKey, chain_code: &[u8; 32], index: u32) -> Result<(SecretKey, [u8; 32]), WalletError> {
+    let index_bytes = index.to_be_bytes();
+    let output = if index >= HARDENED {
+        hmac_sha512(chain_code, &[&[0], &key.secret_bytes(), &index_bytes])
+    } else {
+        let public = PublicKey::from_secret_key(&CONTEXT, key).serialize();
+        hmac_sha512(chain_code, &[&public, &index_bytes])
+    };
+    let mut tweak = [0u8; 32];
+    tweak.copy_from_slice(&output[..32]);
+    let tweak = Scalar::from_be_bytes(tweak).map_err(|_| WalletError::InvalidKey)?;
+    let child = key.add_tweak(&tweak).map_err(|_| WalletError::InvalidKey)?;
+    let mut chain_code = [0u8; 32];
+    chain_code.copy_from_slice(&output[32..]);
+    Ok((child, chain_code))
+}
+
+fn split(output: [u8; 64]) -> Result<(SecretKey, [u8; 32]), WalletError> {
+    let key = SecretKey::from_slice(&output[..32]).map_err(|_| WalletError::InvalidKey)?;
+    let mut chain_code = [0u8; 32];
+    chain_code.copy_from_slice(&output[32..]);
+    Ok((key, chain_code))
+}
+
+fn hmac_sha512(key: &[u8], data: &[&[u8]]) -> [u8; 64] {
+    let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("HMAC accepts keys of any length; qed");
+    for data in data {
+        mac.update(data);
+    }
+    mac.finalize().into_bytes().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MNEMONIC: &str = "test test test test test test test test test test test junk";
+
+    #[test]
+    fn should_derive_default_accounts() {
+        let first = Wallet::from_mnemonic(MNEMONIC, 0).unwrap();
+        let second = Wallet::from_mnemonic(MNEMONIC, 1).unwrap();
+
+        assert_eq!(
+            hex::encode(first.secret_bytes()),
+            "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80"
+        );
+        assert_eq!(
+            first.address(),
+            "f39fd6e51aad88f6f4ce6ab8827279cfffb92266".parse().unwrap()
+        );
+        assert_eq!(
+            second.address(),
+            "70997970c51812dc3a010c7d01b50e0d17dc79c8".parse().unwrap()
+        );
+    }
+
+    #[test]
+    fn should_derive_bip32_test_vector() {
+        // Test vector 1 of BIP-32, chain m/0'/1
+        let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+
+        let wallet = Wallet::from_seed(&seed, "m/0h/1").unwrap();
+
+        assert_eq!(
+            hex::encode(wallet.secret_bytes()),
+            "3c6cb8d0f6a264c91ea8b5030fadaa8e538b020f0a387421a12de9319dc93368"
+        );
+    }
+
+    #[test]
+    fn should_reject_invalid_input() {
+        assert_eq!(
+            Wallet::from_mnemonic("test junk", 0).map(|_| ()),
+            Err(WalletError::InvalidMnemonic(2))
+        );
+        assert_eq!(
+            Wallet::from_mnemonic("test test test test test test test test test test test tesst", 0).map(|_| ()),
+            Err(WalletError::UnknownWord("tesst".into()))
+        );
+        assert_eq!(
+            Wallet::from_mnemonic("test test test test test test test test test test test test", 0).map(|_| ()),
+            Err(WalletError::InvalidChecksum)
+        );
+        assert!(check_mnemonic(&["zoo"; 24]).is_err());
+        assert!(check_mnemonic(&[&["zoo"; 23][..], &["vote"]].concat()).is_ok());
+        assert!(check_mnemonic(&[&["abandon"; 11][..], &["about"]].concat()).is_ok());
+        assert_eq!(
+            Wallet::from_mnemonic_with_path(MNEMONIC, "", "m/44'/x").map(|_| ()),
+            Err(WalletError::InvalidPath("m/44'/x".into()))
+        );
+        assert!(parse_path("44'/60'").is_err());
+        assert_eq!(
+            parse_path("m/44'/60h/0").unwrap(),
+            vec![HARDENED + 44, HARDENED + 60, 0]
+        );
+    }
+}
@@ -0,0 +1,555 @@
+//! Sign-In with Ethereum (EIP-4361) messages.
+
+use crate::types::{Address, AddressChecksum};
+use std::{
+    fmt,
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+const PREAMBLE: &str = " wants you to sign in with your Ethereum account:";
+
+/// Error while parsing or verifying a SIWE message.
+#[derive(Debug, derive_more::Display, Clone)]
+pub enum SiweError {
+    /// The message is malformed.
+    #[display(fmt = "Invalid SIWE message: {}", _0)]
+    Parse(String),
+    /// The message has expired.
+    #[display(fmt = "SIWE message has expired")]
+    Expired,
+    /// The message is not valid yet.
+    #[display(fmt = "SIWE message is not valid yet")]
+    NotYetValid,
+    /// The signature doesn't match the address of the message.
+    #[display(fmt = "Invalid SIWE signature")]
+    InvalidSignature,
+    /// Signature verification failed.
+    #[display(fmt = "{}", _0)]
+    Web3(crate::Error),
+}
+
+impl std::error::Error for SiweError {}
+
+/// RFC 3339 timestamp, keeping its original textual representation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Timestamp {
+    text: String,
+    time: SystemTime,
+}
+
+impl Timestamp {
+    /// Returns the time.
+    pub fn time(&self) -> SystemTime {
+        self.time
+    }
+}
+
+impl From<SystemTime> for Timestamp {
+    fn from(time: SystemTime) -> Self {
+        let secs = time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+        let secs_of_day = secs % 86_400;
+        let text = format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+            year,
+            month,
+            day,
+            secs_of_day / 3600,
+            secs_of_day / 60 % 60,
+            secs_of_day % 60
+        );
+        Timestamp {
+            text,
+            time: UNIX_EPOCH + Duration::from_secs(secs),
+        }
+    }
+}
+
+impl FromStr for Timestamp {
+    type Err = SiweError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || SiweError::Parse(format!("invalid timestamp: {}", text));
+        let bytes = text.as_bytes();
+        if bytes.len() < 20 || bytes[4] != b'-' || bytes[7] != b'-' || !matches!(bytes[10], b'T' | b't') {
+            return Err(invalid());
+        }
+        if bytes[13] != b':' || bytes[16] != b':' {
+            return Err(invalid());
+        }
+        let number = |range: std::ops::Range<usize>| -> Result<i64, SiweError> {
+            let digits = text.get(range).ok_or_else(invalid)?;
+            if !digits.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(invalid());
+            }
+            digits.parse().map_err(|_| invalid())
+        };
+        let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+        let (hour, minute, second) = (number(11..13)?, number(14..16)?, number(17..19)?);
+        if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 || second > 60 {
+            return Err(invalid());
+        }
+
+        let mut rest = &text[19..];
+        let mut nanos = 0u32;
+        if let Some(fraction) = rest.strip_prefix('.') {
+            let len = fraction.bytes().take_while(u8::is_ascii_digit).count();
+            if len == 0 {
+                return Err(invalid());
+            }
+            let digits = &fraction[..len.min(9)];
+            nanos = digits.parse::<u32>().map_err(|_| invalid())? * 10u32.pow(9 - digits.len() as u32);
+            rest = &fraction[len..];
+        }
+        let offset = match rest {
+            "Z" | "z" => 0,
+            _ if rest.len() == 6 && rest.as_bytes()[3] == b':' => {
+                let sign = match rest.as_bytes()[0] {
+                    b'+' => 1,
+                    b'-' => -1,
+                    _ => return Err(invalid()),
+                };
+                let offset = number(text.len() - 5..text.len() - 3)? * 3600 + number(text.len() - 2..text.len())? * 60;
+                sign * offset
+            }
+            _ => return Err(invalid()),
+        };
+
+        let secs = days_from_civil(year, month, day) * 86_400 + hour * 3600 + minute * 60 + second - offset;
+        if secs < 0 {
+            return Err(invalid());
+        }
+        Ok(Timestamp {
+            text: text.to_owned(),
+            time: UNIX_EPOCH + Duration::new(secs as u64, nanos),
+        })
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+/// Days since the unix epoch of a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Proleptic Gregorian date of given number of days since the unix epoch.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = if days >= 0 { days } else { days - 146_096 } / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Sign-In with Ethereum message.
+///
+/// Use `to_string()` to get the text to be signed with `personal_sign` and `FromStr` to parse it back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    /// Domain requesting the signing
+    pub domain: String,
+    /// Address performing the signing
+    pub address: Address,
+    /// Human-readable assertion the user signs
+    pub statement: Option<String>,
+    /// Subject of the signing
+    pub uri: String,
+    /// Message version, currently `1`
+    pub version: String,
+    /// EIP-155 chain id
+    pub chain_id: u64,
+    /// Randomized token to prevent replay attacks
+    pub nonce: String,
+    /// Time the message was generated
+    pub issued_at: Timestamp,
+    /// Time after which the message is no longer valid
+    pub expiration_time: Option<Timestamp>,
+    /// Time before which the message is not valid yet
+    pub not_before: Option<Timestamp>,
+    /// System-specific request identifier
+    pub request_id: Option<String>,
+    /// Resources the user wishes to have resolved
+    pub resources: Vec<String>,
+}
+
+impl Message {
+    /// Creates a new message issued now.
+    pub fn new(domain: &str, address: Address, uri: &str, chain_id: u64, nonce: &str) -> Self {
+        Message {
+            domain: domain.into(),
+            address,
+            statement: None,
+            uri: uri.into(),
+            version: "1".into(),
+            chain_id,
+            nonce: nonce.into(),
+            issued_at: SystemTime::now().into(),
+            expiration_time: None,
+            not_before: None,
+            request_id: None,
+            resources: vec![],
+        }
+    }
+
+    /// Sets the statement.
+    pub fn statement(mut self, statement: &str) -> Self {
+        self.statement = Some(statement.into());
+        self
+    }
+
+    /// Sets the issuing time.
+    pub fn issued_at(mut self, time: SystemTime) -> Self {
+        self.issued_at = time.into();
+        self
+    }
+
+    /// Sets the expiration time.
+    pub fn expiration_time(mut self, time: SystemTime) -> Self {
+        self.expiration_time = Some(time.into());
+        self
+    }
+
+    /// Sets the time before which the message is not valid.
+    pub fn not_before(mut self, time: SystemTime) -> Self {
+        self.not_before = Some(time.into());
+        self
+    }
+
+    /// Sets the request id.
+    pub fn request_id(mut self, request_id: &str) -> Self {
+        self.request_id = Some(request_id.into());
+        self
+    }
+
+    /// Adds a resource.
+    pub fn resource(mut self, resource: &str) -> Self {
+        self.resources.push(resource.into());
+        self
+    }
+
+    /// Checks that the message is valid at given time.
+    pub fn validate_time(&self, now: SystemTime) -> Result<(), SiweError> {
+        if let Some(ref expiration_time) = self.expiration_time {
+            if now >= expiration_time.time() {
+                return Err(SiweError::Expired);
+            }
+        }
+        if let Some(ref not_before) = self.not_before {
+            if now < not_before.time() {
+                return Err(SiweError::NotYetValid);
+            }
+        }
+        Ok(())
+    }
+
+    /// Verifies the time window of the message and its signature by `address`.
+    ///
+    /// Signatures of contract wallets are supported as described in `signing::verify_message`.
+    /// Note that the caller is responsible for checking `domain` and `nonce`.
+    #[cfg(feature = "signing")]
+    pub async fn verify<T: crate::Transport>(
+        &self,
+        eth: &crate::api::Eth<T>,
+        signature: &[u8],
+    ) -> Result<(), SiweError> {
+        self.validate_time(SystemTime::now())?;
+        match crate::signing::verify_message(eth, self.address, self.to_string(), signature).await {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(SiweError::InvalidSignature),
+            Err(err) => Err(SiweError::Web3(err)),
+        }
+    }
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}{}", self.domain, PREAMBLE)?;
+        writeln!(f, "{}", self.address.to_checksum(None))?;
+        writeln!(f)?;
+        if let Some(ref statement) = self.statement {
+            writeln!(f, "{}", statement)?;
+            writeln!(f)?;
+        }
+        writeln!(f, "URI: {}", self.uri)?;
+        writeln!(f, "Version: {}", self.version)?;
+        writeln!(f, "Chain ID: {}", self.chain_id)?;
+        writeln!(f, "Nonce: {}", self.nonce)?;
+        write!(f, "Issued At: {}", self.issued_at)?;
+        if let Some(ref expiration_time) = self.expiration_time {
+            write!(f, "\nExpiration Time: {}", expiration_time)?;
+        }
+        if let Some(ref not_before) = self.not_before {
+            write!(f, "\nNot Before: {}", not_before)?;
+        }
+        if let Some(ref request_id) = self.request_id {
+            write!(f, "\nRequest ID: {}", request_id)?;
+        }
+        if !self.resources.is_empty() {
+            write!(f, "\nResources:")?;
+            for resource in &self.resources {
+                write!(f, "\n- {}", resource)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Message {
+    type Err = SiweError;
+
+    fn from_str(message: &str) -> Result<Self, Self::Err> {
+        let mut lines = message.split('\n').peekable();
+        let missing = |what: &str| SiweError::Parse(format!("missing {}", what));
+
+        let domain = lines
+            .next()
+            .ok_or_else(|| missing("preamble"))?
+            .strip_suffix(PREAMBLE)
+            .ok_or_else(|| SiweError::Parse("invalid preamble".into()))?
+            .to_owned();
+        let text = lines.next().ok_or_else(|| missing("address"))?;
+        // EIP-4361 requires the EIP-55 checksummed form
+        let address = Address::from_checksum(text)
+            .ok()
+            .filter(|address| address.to_checksum(None) == text)
+            .ok_or_else(|| SiweError::Parse(format!("invalid address: {}", text)))?;
+        if lines.next() != Some("") {
+            return Err(SiweError::Parse("expected empty line after address".into()));
+        }
+
+        let statement = match lines.peek() {
+            Some(line) if !line.starts_with("URI: ") => {
+                let statement = lines.next().map(ToOwned::to_owned);
+                if lines.next() != Some("") {
+                    return Err(SiweError::Parse("expected empty line after statement".into()));
+                }
+                statement
+            }
+            _ => None,
+        };
+
+        let mut field = |tag: &str| -> Result<String, SiweError> {
+            lines
+                .next()
+                .and_then(|line| line.strip_prefix(tag))
+                .map(ToOwned::to_owned)
+                .ok_or_else(|| SiweError::Parse(format!("missing {}", tag.trim_end_matches(": "))))
+        };
+        let uri = field("URI: ")?;
+        let version = field("Version: ")?;
+        if version != "1" {
+            return Err(SiweError::Parse(format!("unsupported version: {}", version)));
+        }
+        let chain_id = field("Chain ID: ")?
+            .parse()
+            .map_err(|_| SiweError::Parse("invalid chain id".into()))?;
+        let nonce = field("Nonce: ")?;
+        if nonce.len() < 8 || !nonce.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(SiweError::Parse(format!("invalid nonce: {}", nonce)));
+        }
+        let issued_at = field("Issued At: ")?.parse()?;
+
+        let mut optional = |tag: &str| match lines.peek().and_then(|line| line.strip_prefix(tag)) {
+            Some(value) => {
+                let value = value.to_owned();
+                lines.next();
+                Some(value)
+            }
+            None => None,
+        };
+        let expiration_time = optional("Expiration Time: ").map(|time| time.parse()).transpose()?;
+        let not_before = optional("Not Before: ").map(|time| time.parse()).transpose()?;
+        let request_id = optional("Request ID: ");
+        let mut resources = vec![];
+        if optional("Resources:").is_some() {
+            while let Some(resource) = optional("- ") {
+                resources.push(resource);
+            }
+        }
+        if let Some(line) = lines.next() {
+            return Err(SiweError::Parse(format!("unexpected line: {}", line)));
+        }
+
+        Ok(Message {
+            domain,
+            address,
+            statement,
+            uri,
+            version,
+            chain_id,
+            nonce,
+            issued_at,
+            expiration_time,
+            not_before,
+            request_id,
+            resources,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MESSAGE: &str = "service.org wants you to sign in with your Ethereum account:
+0xe5A12547fe4E872D192E3eCecb76F2Ce1aeA4946
+
+I accept the ServiceOrg Terms of Service: https://service.org/tos
+
+URI: https://service.org/login
+Version: 1
+Chain ID: 1
+Nonce: 32891756
+Issued At: 2021-09-30T16:25:24Z
+Resources:
+- ipfs://bafybeiemxf5abjwjbikoz4mc3a3dla6ual3jsgpdr4cjr3oz3evfyavhwq/
+- https://example.com/my-web2-claim.json";
+
+    #[test]
+    fn should_parse_and_format_message() {
+        let message: Message = MESSAGE.parse().unwrap();
+
+        assert_eq!(message.domain, "service.org");
+        assert_eq!(
+            message.address,
+            "e5a12547fe4e872d192e3ececb76f2ce1aea4946".parse::<Address>().unwrap()
+        );
+        assert_eq!(
+            message.statement.as_deref(),
+            Some("I accept the ServiceOrg Terms of Service: https://service.org/tos")
+        );
+        assert_eq!(message.chain_id, 1);
+        assert_eq!(message.nonce, "32891756");
+        assert_eq!(
+            message.issued_at.time(),
+            UNIX_EPOCH + Duration::from_secs(1_633_019_124)
+        );
+        assert_eq!(message.resources.len(), 2);
+        assert_eq!(message.to_string(), MESSAGE);
+    }
+
+    #[test]
+    fn should_build_message() {
+        let address = "e5a12547fe4e872d192e3ececb76f2ce1aea4946".parse().unwrap();
+        let message = Message::new("service.org", address, "https://service.org/login", 1, "32891756")
+            .statement("I accept the ServiceOrg Terms of Service: https://service.org/tos")
+            .issued_at(UNIX_EPOCH + Duration::from_secs(1_633_019_124))
+            .resource("ipfs://bafybeiemxf5abjwjbikoz4mc3a3dla6ual3jsgpdr4cjr3oz3evfyavhwq/")
+            .resource("https://example.com/my-web2-claim.json");
+
+        assert_eq!(message.to_string(), MESSAGE);
+    }
+
+    #[test]
+    fn should_parse_message_without_statement() {
+        let text = "example.com wants you to sign in with your Ethereum account:
+0xe5A12547fe4E872D192E3eCecb76F2Ce1aeA4946
+
+URI: https://example.com
+Version: 1
+Chain ID: 10
+Nonce: abcdef123
+Issued At: 2021-09-30T16:25:24.000+02:00
+Expiration Time: 2021-10-01T00:00:00Z
+Not Before: 2021-09-30T00:00:00Z
+Request ID: 42";
+        let message: Message = text.parse().unwrap();
+
+        assert_eq!(message.statement, None);
+        assert_eq!(message.request_id.as_deref(), Some("42"));
+        assert_eq!(
+            message.issued_at.time(),
+            UNIX_EPOCH + Duration::from_secs(1_633_019_124 - 7200)
+        );
+        assert_eq!(message.to_string(), text);
+    }
+
+    #[test]
+    fn should_reject_invalid_messages() {
+        assert!(matches!(
+            MESSAGE.replace("Version: 1", "Version: 2").parse::<Message>(),
+            Err(SiweError::Parse(_))
+        ));
+        assert!(matches!(
+            MESSAGE.replace("Nonce: 32891756", "Nonce: 1").parse::<Message>(),
+            Err(SiweError::Parse(_))
+        ));
+        assert!(matches!(
+            MESSAGE.replace("wants you", "asks you").parse::<Message>(),
+            Err(SiweError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn should_reject_unchecksummed_addresses() {
+        let checksummed = "0xe5A12547fe4E872D192E3eCecb76F2Ce1aeA4946";
+        for address in &[
+            checksummed.to_lowercase(),
+            format!("0x{}", checksummed[2..].to_uppercase()),
+            checksummed.replace('A', "a").replacen('e', "E", 1),
+            checksummed[2..].to_owned(),
+        ] {
+            assert!(matches!(
+                MESSAGE.replace(checksummed, address).parse::<Message>(),
+                Err(SiweError::Parse(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn should_reject_invalid_nonces() {
+        for nonce in &["1234567", "3289-1756", "32891756 ", ""] {
+            assert!(matches!(
+                MESSAGE
+                    .replace("Nonce: 32891756", &format!("Nonce: {}", nonce))
+                    .parse::<Message>(),
+                Err(SiweError::Parse(_))
+            ));
+        }
+        assert!(MESSAGE
+            .replace("Nonce: 32891756", "Nonce: abcDEF12")
+            .parse::<Message>()
+            .is_ok());
+    }
+
+    #[test]
+    fn should_validate_time_window() {
+        let start = UNIX_EPOCH + Duration::from_secs(1_000_000);
+        let message = Message::new("example.com", Address::zero(), "https://example.com", 1, "abcdef123")
+            .not_before(start)
+            .expiration_time(start + Duration::from_secs(60));
+
+        assert!(matches!(
+            message.validate_time(start - Duration::from_secs(1)),
+            Err(SiweError::NotYetValid)
+        ));
+        assert!(message.validate_time(start).is_ok());
+        assert!(matches!(
+            message.validate_time(start + Duration::from_secs(60)),
+            Err(SiweError::Expired)
+        ));
+    }
+
+    #[test]
+    fn should_format_timestamps() {
+        let timestamp = Timestamp::from(UNIX_EPOCH + Duration::from_secs(951_782_400));
+        assert_eq!(timestamp.to_string(), "2000-02-29T00:00:00Z");
+        assert_eq!(timestamp, "2000-02-29T00:00:00Z".parse().unwrap());
+    }
+}
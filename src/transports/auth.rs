@@ -0,0 +1,22 @@
+//! Authentication of the transports with (rotating) bearer tokens.
+
+use crate::error;
+
+/// Provides the current token sent in the `Authorization: Bearer` header.
+///
+/// The provider is invoked for every HTTP request and at every WebSocket handshake, so tokens
+/// which expire can be refreshed (or cached) by the implementation. Closures returning
+/// `web3::Result<String>` implement it.
+pub trait AuthProvider: Send + Sync {
+    /// Returns the current token, errors fail the request (or the connection).
+    fn token(&self) -> error::Result<String>;
+}
+
+impl<F> AuthProvider for F
+where
+    F: Fn() -> error::Result<String> + Send + Sync,
+{
+    fn token(&self) -> error::Result<String> {
+        self()
+    }
+}
@@ -6,6 +6,7 @@ use crate::{
 };
 use futures::{
     channel::oneshot,
+    stream::{self, StreamExt},
     task::{Context, Poll},
     Future, FutureExt,
 };
@@ -19,6 +20,9 @@ type PendingRequests = Arc<Mutex<BTreeMap<RequestId, Pending>>>;
 ///
 /// Note: cloned instances of [Batch] share the queues of pending and unsent requests.
 /// If you want to avoid it, use [Batch::new] repeatedly instead.
+///
+/// Calls streamed with `Transport::execute_stream` (e.g. `Eth::logs_paged` over
+/// `Http::with_streamed_arrays`) are sent right away instead of being batched.
 #[derive(Debug, Clone)]
 pub struct Batch<T> {
     transport: T,
@@ -42,6 +46,46 @@ where
     /// Sends all requests as a batch.
     pub fn submit_batch(&self) -> impl Future<Output = error::Result<Vec<error::Result<rpc::Value>>>> {
         let batch = std::mem::take(&mut *self.batch.lock());
+        self.send_batch(batch)
+    }
+
+    /// Sends all requests split into batches of at most `max_batch_size` requests, with up to
+    /// `max_concurrent` batches in flight at a time.
+    ///
+    /// Results are returned in the order of requests. If any batch fails the first error is
+    /// returned, requests of the remaining batches are still resolved.
+    pub fn submit_chunked(
+        &self,
+        max_batch_size: usize,
+        max_concurrent: usize,
+    ) -> impl Future<Output = error::Result<Vec<error::Result<rpc::Value>>>>
+    where
+        T: Clone,
+    {
+        let batch = std::mem::take(&mut *self.batch.lock());
+        let chunks = batch
+            .chunks(max_batch_size.max(1))
+            .map(|chunk| chunk.to_vec())
+            .collect::<Vec<_>>();
+        let this = self.clone();
+        let results = stream::iter(chunks)
+            .map(move |chunk| this.send_batch(chunk))
+            .buffered(max_concurrent.max(1))
+            .collect::<Vec<_>>();
+
+        async move {
+            let mut outputs = Vec::new();
+            for result in results.await {
+                outputs.extend(result?);
+            }
+            Ok(outputs)
+        }
+    }
+
+    fn send_batch(
+        &self,
+        batch: Vec<(RequestId, rpc::Call)>,
+    ) -> impl Future<Output = error::Result<Vec<error::Result<rpc::Value>>>> {
         let ids = batch.iter().map(|&(id, _)| id).collect::<Vec<_>>();
 
         let batch = self.transport.send_batch(batch);
@@ -82,6 +126,18 @@ where
 
         SingleResult(rx)
     }
+
+    fn execute_stream<X>(
+        &self,
+        method: &str,
+        params: Vec<rpc::Value>,
+    ) -> Option<futures::stream::BoxStream<'static, error::Result<X>>>
+    where
+        X: serde::de::DeserializeOwned + Send + 'static,
+    {
+        // streamed calls are sent right away, not as a part of the batch
+        self.transport.execute_stream(method, params)
+    }
 }
 
 /// Result of calling a single method that will be part of the batch.
@@ -95,3 +151,86 @@ impl Future for SingleResult {
         Poll::Ready(ready!(self.0.poll_unpin(ctx)).map_err(|_| Error::Internal)?)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers;
+    use futures::future::{self, BoxFuture};
+
+    /// Answers each call with its method name, failing batches containing `fail`.
+    #[derive(Debug, Clone, Default)]
+    struct EchoTransport {
+        batches: Arc<Mutex<Vec<usize>>>,
+    }
+
+    impl Transport for EchoTransport {
+        type Out = BoxFuture<'static, error::Result<rpc::Value>>;
+
+        fn prepare(&self, method: &str, params: Vec<rpc::Value>) -> (RequestId, rpc::Call) {
+            let id = method.len() + params.len();
+            (id, helpers::build_request(id, method, params))
+        }
+
+        fn send(&self, _id: RequestId, request: rpc::Call) -> Self::Out {
+            let response = match request {
+                rpc::Call::MethodCall(call) => Ok(call.method.into()),
+                _ => Err(Error::Internal),
+            };
+            Box::pin(future::ready(response))
+        }
+    }
+
+    impl BatchTransport for EchoTransport {
+        type Batch = future::Ready<error::Result<Vec<error::Result<rpc::Value>>>>;
+
+        fn send_batch<I>(&self, requests: I) -> Self::Batch
+        where
+            I: IntoIterator<Item = (RequestId, rpc::Call)>,
+        {
+            let methods = requests
+                .into_iter()
+                .map(|(_, call)| match call {
+                    rpc::Call::MethodCall(call) => call.method,
+                    _ => unreachable!(),
+                })
+                .collect::<Vec<_>>();
+            self.batches.lock().push(methods.len());
+            if methods.iter().any(|method| method == "fail") {
+                return future::ready(Err(Error::Unreachable));
+            }
+            future::ready(Ok(methods.into_iter().map(|method| Ok(method.into())).collect()))
+        }
+    }
+
+    #[test]
+    fn should_submit_in_chunks_preserving_order() {
+        let transport = EchoTransport::default();
+        let batch = Batch::new(transport.clone());
+        let methods = ["a", "bb", "ccc", "dddd", "eeeee"];
+        let results = methods
+            .iter()
+            .map(|method| batch.execute(method, vec![]))
+            .collect::<Vec<_>>();
+
+        let submitted = futures::executor::block_on(batch.submit_chunked(2, 2));
+
+        let expected = methods.iter().map(|method| Ok((*method).into())).collect::<Vec<_>>();
+        assert_eq!(submitted, Ok(expected.clone()));
+        assert_eq!(futures::executor::block_on(future::join_all(results)), expected);
+        assert_eq!(*transport.batches.lock(), vec![2, 2, 1]);
+    }
+
+    #[test]
+    fn should_resolve_all_requests_when_chunk_fails() {
+        let batch = Batch::new(EchoTransport::default());
+        let first = batch.execute("fail", vec![]);
+        let second = batch.execute("ok", vec![]);
+
+        let submitted = futures::executor::block_on(batch.submit_chunked(1, 1));
+
+        assert_eq!(submitted, Err(Error::Unreachable));
+        assert_eq!(futures::executor::block_on(first), Err(Error::Unreachable));
+        assert_eq!(futures::executor::block_on(second), Ok("ok".into()));
+    }
+}
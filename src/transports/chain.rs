@@ -0,0 +1,429 @@
+//! Transport wrapper validating the chain of the endpoint.
+
+use crate::{
+    api,
+    error::{self, Error},
+    helpers, rpc,
+    transports::{pool::is_endpoint_failure, ConnectionEvent},
+    types::U64,
+    BatchTransport, DuplexTransport, RequestId, Transport,
+};
+use futures::{
+    future::{self, BoxFuture, FutureExt, Shared},
+    stream::{self, BoxStream, StreamExt},
+    task::{noop_waker_ref, Context, Poll},
+};
+use parking_lot::Mutex;
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+type Validation = Shared<BoxFuture<'static, error::Result<()>>>;
+
+/// A transport wrapper failing requests with `Error::WrongChain` if the endpoint serves a different chain.
+///
+/// The chain id is checked with `eth_chainId` before the first request is sent, and checked again
+/// after the endpoint fails (e.g. a dropped connection, which may be re-established to another
+/// node). Concurrent requests wait for a single check. To validate every endpoint of a
+/// `FallbackTransport`, wrap the endpoints rather than the pool, endpoints on a wrong chain are
+/// then skipped.
+///
+/// Duplex transports may reconnect without any request failing, use
+/// [`watch_connection`](ExpectChain::watch_connection) to check the chain again after every
+/// reconnection.
+///
+/// ```no_run
+/// # fn example() -> web3::Result<()> {
+/// let http = web3::transports::Http::new("http://localhost:8545")?;
+/// // Fail instead of silently talking to a testnet.
+/// let web3 = web3::Web3::new(http).expect_chain(1);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ExpectChain<T> {
+    transport: T,
+    chain_id: u64,
+    validated: Arc<AtomicBool>,
+    validation: Arc<Mutex<Option<Validation>>>,
+    events: Arc<Mutex<Option<BoxStream<'static, ConnectionEvent>>>>,
+}
+
+impl<T: fmt::Debug> fmt::Debug for ExpectChain<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExpectChain")
+            .field("transport", &self.transport)
+            .field("chain_id", &self.chain_id)
+            .field("validated", &self.validated)
+            .finish()
+    }
+}
+
+impl<T> ExpectChain<T> {
+    /// Wraps given transport expecting the endpoint to serve chain `chain_id`.
+    pub fn new(transport: T, chain_id: u64) -> Self {
+        ExpectChain {
+            transport,
+            chain_id,
+            validated: Default::default(),
+            validation: Default::default(),
+            events: Default::default(),
+        }
+    }
+
+    /// Borrows the inner transport.
+    pub fn inner(&self) -> &T {
+        &self.transport
+    }
+
+    /// Returns the expected chain id.
+    pub fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    /// Forces the chain id to be checked again before the next request, e.g. after reconnecting.
+    pub fn invalidate(&self) {
+        self.validated.store(false, Ordering::Release);
+    }
+
+    fn track<R>(&self, result: &error::Result<R>) {
+        if let Err(ref err) = result {
+            if is_endpoint_failure(err) {
+                self.invalidate();
+            }
+        }
+    }
+
+    /// Invalidates the chain id if the connection was (re-)established since the last request.
+    fn check_connection(&self) {
+        let mut events = self.events.lock();
+        let mut cx = Context::from_waker(noop_waker_ref());
+        while let Some(stream) = events.as_mut() {
+            match stream.poll_next_unpin(&mut cx) {
+                Poll::Ready(Some(ConnectionEvent::Connected))
+                | Poll::Ready(Some(ConnectionEvent::Reconnecting { .. })) => self.invalidate(),
+                Poll::Ready(Some(ConnectionEvent::Disconnected { .. })) => {}
+                Poll::Ready(None) => *events = None,
+                Poll::Pending => break,
+            }
+        }
+    }
+}
+
+impl<T: DuplexTransport> ExpectChain<T> {
+    /// Checks the chain id again whenever the transport (re-)connects, see
+    /// [`DuplexTransport::connection_events`].
+    pub fn watch_connection(self) -> Self {
+        *self.events.lock() = Some(self.transport.connection_events());
+        self
+    }
+}
+
+impl<T: Transport> ExpectChain<T> {
+    /// Checks the chain id of the endpoint.
+    pub async fn validate(&self) -> error::Result<()> {
+        check_chain(self.transport.clone(), self.chain_id, self.validated.clone()).await
+    }
+
+    async fn ensure_validated(&self) -> error::Result<()>
+    where
+        T: Send + Sync + 'static,
+        T::Out: Send + 'static,
+    {
+        self.check_connection();
+        if self.validated.load(Ordering::Acquire) {
+            return Ok(());
+        }
+        let validation = self
+            .validation
+            .lock()
+            .get_or_insert_with(|| {
+                check_chain(self.transport.clone(), self.chain_id, self.validated.clone())
+                    .boxed()
+                    .shared()
+            })
+            .clone();
+        let result = validation.clone().await;
+        {
+            let mut current = self.validation.lock();
+            if current.as_ref().is_some_and(|current| current.ptr_eq(&validation)) {
+                *current = None;
+            }
+        }
+        self.track(&result);
+        result
+    }
+}
+
+async fn check_chain<T: Transport>(transport: T, chain_id: u64, validated: Arc<AtomicBool>) -> error::Result<()> {
+    let actual: U64 = helpers::decode(transport.execute("eth_chainId", vec![]).await?)?;
+    let actual = actual.as_u64();
+    if actual != chain_id {
+        validated.store(false, Ordering::Release);
+        return Err(Error::WrongChain {
+            expected: chain_id,
+            actual,
+        });
+    }
+    validated.store(true, Ordering::Release);
+    Ok(())
+}
+
+impl<T> Transport for ExpectChain<T>
+where
+    T: Transport + Send + Sync + 'static,
+    T::Out: Send + 'static,
+{
+    type Out = BoxFuture<'static, error::Result<rpc::Value>>;
+
+    fn prepare(&self, method: &str, params: Vec<rpc::Value>) -> (RequestId, rpc::Call) {
+        self.transport.prepare(method, params)
+    }
+
+    fn send(&self, id: RequestId, request: rpc::Call) -> Self::Out {
+        let this = self.clone();
+        async move {
+            this.ensure_validated().await?;
+            let result = this.transport.send(id, request).await;
+            this.track(&result);
+            result
+        }
+        .boxed()
+    }
+
+    fn execute_stream<X>(&self, method: &str, params: Vec<rpc::Value>) -> Option<BoxStream<'static, error::Result<X>>>
+    where
+        X: serde::de::DeserializeOwned + Send + 'static,
+    {
+        let stream = self.transport.execute_stream(method, params)?;
+        let this = self.clone();
+        let stream = async move {
+            if let Err(err) = this.ensure_validated().await {
+                return stream::once(future::ready(Err(err))).boxed();
+            }
+            stream.inspect(move |item| this.track(item)).boxed()
+        };
+        Some(stream.flatten_stream().boxed())
+    }
+}
+
+impl<T> BatchTransport for ExpectChain<T>
+where
+    T: BatchTransport + Send + Sync + 'static,
+    T::Out: Send + 'static,
+    T::Batch: Send + 'static,
+{
+    type Batch = BoxFuture<'static, error::Result<Vec<error::Result<rpc::Value>>>>;
+
+    fn send_batch<I>(&self, requests: I) -> Self::Batch
+    where
+        I: IntoIterator<Item = (RequestId, rpc::Call)>,
+    {
+        let this = self.clone();
+        let requests: Vec<_> = requests.into_iter().collect();
+        async move {
+            this.ensure_validated().await?;
+            let result = this.transport.send_batch(requests).await;
+            this.track(&result);
+            result
+        }
+        .boxed()
+    }
+}
+
+impl<T> DuplexTransport for ExpectChain<T>
+where
+    T: DuplexTransport + Send + Sync + 'static,
+    T::Out: Send + 'static,
+{
+    type NotificationStream = T::NotificationStream;
+
+    fn subscribe(&self, id: api::SubscriptionId) -> error::Result<Self::NotificationStream> {
+        self.transport.subscribe(id)
+    }
+
+    fn unsubscribe(&self, id: api::SubscriptionId) -> error::Result<()> {
+        self.transport.unsubscribe(id)
+    }
+
+    fn cancel_subscription(&self, id: api::SubscriptionId) -> error::Result<()> {
+        self.transport.cancel_subscription(id)
+    }
+
+    fn connection_events(&self) -> BoxStream<'static, ConnectionEvent> {
+        self.transport.connection_events()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::TransportError;
+    use futures::{channel::mpsc, future};
+    use futures_timer::Delay;
+    use std::{collections::VecDeque, time::Duration};
+
+    #[derive(Debug, Clone, Default)]
+    struct MockTransport {
+        requests: Arc<Mutex<Vec<String>>>,
+        responses: Arc<Mutex<VecDeque<error::Result<rpc::Value>>>>,
+        events: Arc<Mutex<Vec<mpsc::UnboundedSender<ConnectionEvent>>>>,
+    }
+
+    impl MockTransport {
+        fn with_responses(responses: Vec<error::Result<rpc::Value>>) -> Self {
+            MockTransport {
+                responses: Arc::new(Mutex::new(responses.into())),
+                ..Default::default()
+            }
+        }
+
+        fn requests(&self) -> Vec<String> {
+            self.requests.lock().clone()
+        }
+
+        fn emit(&self, event: ConnectionEvent) {
+            for listener in self.events.lock().iter() {
+                let _ = listener.unbounded_send(event.clone());
+            }
+        }
+    }
+
+    impl Transport for MockTransport {
+        type Out = BoxFuture<'static, error::Result<rpc::Value>>;
+
+        fn prepare(&self, method: &str, params: Vec<rpc::Value>) -> (RequestId, rpc::Call) {
+            self.requests.lock().push(method.into());
+            (1, helpers::build_request(1, method, params))
+        }
+
+        fn send(&self, _id: RequestId, _request: rpc::Call) -> Self::Out {
+            let response = self.responses.lock().pop_front().unwrap_or(Err(Error::Unreachable));
+            Delay::new(Duration::from_millis(1)).map(|_| response).boxed()
+        }
+    }
+
+    impl DuplexTransport for MockTransport {
+        type NotificationStream = BoxStream<'static, rpc::Value>;
+
+        fn subscribe(&self, _id: api::SubscriptionId) -> error::Result<Self::NotificationStream> {
+            Ok(stream::empty().boxed())
+        }
+
+        fn unsubscribe(&self, _id: api::SubscriptionId) -> error::Result<()> {
+            Ok(())
+        }
+
+        fn connection_events(&self) -> BoxStream<'static, ConnectionEvent> {
+            let (tx, rx) = mpsc::unbounded();
+            let _ = tx.unbounded_send(ConnectionEvent::Connected);
+            self.events.lock().push(tx);
+            rx.boxed()
+        }
+    }
+
+    fn chain_id(id: &str) -> error::Result<rpc::Value> {
+        Ok(rpc::Value::String(id.into()))
+    }
+
+    #[test]
+    fn should_validate_chain_once() {
+        let inner = MockTransport::with_responses(vec![chain_id("0x1"), chain_id("0x10"), chain_id("0x11")]);
+        let transport = ExpectChain::new(inner.clone(), 1);
+
+        let first = futures::executor::block_on(transport.execute("eth_blockNumber", vec![]));
+        let second = futures::executor::block_on(transport.execute("eth_blockNumber", vec![]));
+
+        assert_eq!(first, chain_id("0x10"));
+        assert_eq!(second, chain_id("0x11"));
+        assert_eq!(
+            inner.requests(),
+            vec!["eth_blockNumber", "eth_chainId", "eth_blockNumber"]
+        );
+    }
+
+    #[test]
+    fn should_share_validation_between_concurrent_requests() {
+        let inner = MockTransport::with_responses(vec![chain_id("0x1"), chain_id("0x10"), chain_id("0x11")]);
+        let transport = ExpectChain::new(inner.clone(), 1);
+
+        let (first, second) = futures::executor::block_on(future::join(
+            transport.execute("eth_blockNumber", vec![]),
+            transport.execute("eth_blockNumber", vec![]),
+        ));
+
+        assert_eq!(first, chain_id("0x10"));
+        assert_eq!(second, chain_id("0x11"));
+        assert_eq!(
+            inner.requests(),
+            vec!["eth_blockNumber", "eth_blockNumber", "eth_chainId"]
+        );
+    }
+
+    #[test]
+    fn should_revalidate_after_reconnecting() {
+        let inner = MockTransport::with_responses(vec![
+            chain_id("0x1"),
+            chain_id("0x10"),
+            chain_id("0x11"),
+            chain_id("0x5"),
+        ]);
+        let transport = ExpectChain::new(inner.clone(), 1).watch_connection();
+
+        let first = futures::executor::block_on(transport.execute("eth_blockNumber", vec![]));
+        let second = futures::executor::block_on(transport.execute("eth_blockNumber", vec![]));
+        inner.emit(ConnectionEvent::Disconnected { cause: "closed".into() });
+        inner.emit(ConnectionEvent::Reconnecting { attempt: 1 });
+        inner.emit(ConnectionEvent::Connected);
+        let third = futures::executor::block_on(transport.execute("eth_blockNumber", vec![]));
+
+        assert_eq!(first, chain_id("0x10"));
+        assert_eq!(second, chain_id("0x11"));
+        assert_eq!(third, Err(Error::WrongChain { expected: 1, actual: 5 }));
+        assert_eq!(
+            inner.requests(),
+            vec![
+                "eth_blockNumber",
+                "eth_chainId",
+                "eth_blockNumber",
+                "eth_blockNumber",
+                "eth_chainId"
+            ]
+        );
+    }
+
+    #[test]
+    fn should_reject_wrong_chain() {
+        let inner = MockTransport::with_responses(vec![chain_id("0x5")]);
+        let transport = ExpectChain::new(inner.clone(), 1);
+
+        let result = futures::executor::block_on(transport.execute("eth_blockNumber", vec![]));
+
+        assert_eq!(result, Err(Error::WrongChain { expected: 1, actual: 5 }));
+        assert_eq!(inner.requests(), vec!["eth_blockNumber", "eth_chainId"]);
+    }
+
+    #[test]
+    fn should_revalidate_after_endpoint_failure() {
+        let inner = MockTransport::with_responses(vec![
+            chain_id("0x1"),
+            Err(Error::Transport(TransportError::Code(502))),
+            chain_id("0x5"),
+        ]);
+        let transport = ExpectChain::new(inner.clone(), 1);
+
+        let first = futures::executor::block_on(transport.execute("eth_blockNumber", vec![]));
+        let second = futures::executor::block_on(transport.execute("eth_blockNumber", vec![]));
+
+        assert_eq!(first, Err(Error::Transport(TransportError::Code(502))));
+        assert_eq!(second, Err(Error::WrongChain { expected: 1, actual: 5 }));
+        assert_eq!(
+            inner.requests(),
+            vec!["eth_blockNumber", "eth_chainId", "eth_blockNumber", "eth_chainId"]
+        );
+    }
+}
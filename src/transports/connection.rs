@@ -0,0 +1,107 @@
+//! Connection state of the duplex transports.
+
+/// A change of the connection state of a transport.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectionEvent {
+    /// The connection has been (re-)established.
+    Connected,
+    /// The connection has been lost.
+    Disconnected {
+        /// Description of the error which terminated the connection
+        cause: String,
+    },
+    /// Reconnection is being attempted.
+    Reconnecting {
+        /// Number of the attempt, starting at 1
+        attempt: u32,
+    },
+}
+
+#[cfg(any(feature = "ws-tokio", feature = "ws-async-std", feature = "ipc-tokio"))]
+pub(crate) use self::state::ConnectionState;
+
+#[cfg(any(feature = "ws-tokio", feature = "ws-async-std", feature = "ipc-tokio"))]
+mod state {
+    use super::ConnectionEvent;
+    use futures::{channel::mpsc, stream::BoxStream, StreamExt};
+    use parking_lot::Mutex;
+
+    /// Connection events broadcast by the transport task to all listeners.
+    #[derive(Debug, Default)]
+    pub(crate) struct ConnectionState {
+        inner: Mutex<Inner>,
+    }
+
+    #[derive(Debug, Default)]
+    struct Inner {
+        last: Option<ConnectionEvent>,
+        listeners: Vec<mpsc::UnboundedSender<ConnectionEvent>>,
+        closed: bool,
+    }
+
+    impl ConnectionState {
+        /// Returns a stream of subsequent events, starting with the last one.
+        ///
+        /// The stream ends once the transport task finishes.
+        pub(crate) fn listen(&self) -> BoxStream<'static, ConnectionEvent> {
+            let mut inner = self.inner.lock();
+            let (tx, rx) = mpsc::unbounded();
+            if let Some(ref event) = inner.last {
+                let _ = tx.unbounded_send(event.clone());
+            }
+            if !inner.closed {
+                inner.listeners.push(tx);
+            }
+            rx.boxed()
+        }
+
+        /// Delivers the event to all listeners.
+        pub(crate) fn emit(&self, event: ConnectionEvent) {
+            let mut inner = self.inner.lock();
+            inner
+                .listeners
+                .retain(|listener| listener.unbounded_send(event.clone()).is_ok());
+            inner.last = Some(event);
+        }
+
+        /// Ends the streams of all listeners.
+        pub(crate) fn close(&self) {
+            let mut inner = self.inner.lock();
+            inner.closed = true;
+            inner.listeners.clear();
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn should_replay_last_event() {
+            let state = ConnectionState::default();
+            let mut early = state.listen();
+            state.emit(ConnectionEvent::Connected);
+            state.emit(ConnectionEvent::Disconnected { cause: "eof".into() });
+            let late = state.listen();
+            state.close();
+
+            assert_eq!(
+                futures::executor::block_on_stream(late).collect::<Vec<_>>(),
+                vec![ConnectionEvent::Disconnected { cause: "eof".into() }]
+            );
+            assert_eq!(
+                futures::executor::block_on(early.next()),
+                Some(ConnectionEvent::Connected)
+            );
+            assert_eq!(
+                futures::executor::block_on(early.next()),
+                Some(ConnectionEvent::Disconnected { cause: "eof".into() })
+            );
+            assert_eq!(futures::executor::block_on(early.next()), None);
+            assert_eq!(
+                futures::executor::block_on_stream(state.listen()).collect::<Vec<_>>(),
+                vec![ConnectionEvent::Disconnected { cause: "eof".into() }]
+            );
+        }
+    }
+}
@@ -43,20 +43,24 @@ impl Eip1193 {
         let subscriptions_for_closure = subscriptions.clone();
         let msg_handler = Closure::wrap(Box::new(move |evt_js: JsValue| {
             let evt = deserialize_from_js::<MessageEvent>(evt_js).expect("Couldn't parse event data");
-            log::trace!("Message from provider: {:?}", evt);
+            event!(trace, "Message from provider: {:?}", evt);
             match evt.event_type.as_str() {
                 "eth_subscription" => {
                     let subscriptions_map = subscriptions_for_closure.borrow();
                     match subscriptions_map.get(&SubscriptionId::from(evt.data.subscription.clone())) {
                         Some(sink) => {
                             if let Err(err) = sink.unbounded_send(evt.data.result) {
-                                log::error!("Error sending notification: {}", err)
+                                event!(error, "Error sending notification: {}", err)
                             }
                         }
-                        None => log::warn!("Got message for non-existent subscription {}", evt.data.subscription),
+                        None => event!(
+                            warn,
+                            "Got message for non-existent subscription {}",
+                            evt.data.subscription
+                        ),
                     }
                 }
-                other => log::warn!("Got unknown notification type: {}", other),
+                other => event!(warn, "Got unknown notification type: {}", other),
             }
         }) as Box<dyn FnMut(JsValue)>);
         provider_and_listeners.on("message", msg_handler);
@@ -116,7 +120,7 @@ impl Eip1193 {
             Closure::wrap(Box::new(move |evt| {
                 let evt_parsed = handler(evt);
                 if let Err(err) = sender.unbounded_send(evt_parsed) {
-                    log::error!("Couldn't send ad hoc event to channel: {}", err)
+                    event!(error, "Couldn't send ad hoc event to channel: {}", err)
                 }
             })),
         );
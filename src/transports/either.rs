@@ -1,6 +1,6 @@
 //! A strongly-typed transport alternative.
 
-use crate::{api, error, rpc, BatchTransport, DuplexTransport, RequestId, Transport};
+use crate::{api, error, rpc, transports::ConnectionEvent, BatchTransport, DuplexTransport, RequestId, Transport};
 use futures::{
     future::{BoxFuture, FutureExt},
     stream::{BoxStream, StreamExt},
@@ -42,6 +42,16 @@ where
             Self::Right(ref b) => b.send(id, request).boxed(),
         }
     }
+
+    fn execute_stream<X>(&self, method: &str, params: Vec<rpc::Value>) -> Option<BoxStream<'static, error::Result<X>>>
+    where
+        X: serde::de::DeserializeOwned + Send + 'static,
+    {
+        match *self {
+            Self::Left(ref a) => a.execute_stream(method, params),
+            Self::Right(ref b) => b.execute_stream(method, params),
+        }
+    }
 }
 
 impl<A, B, ABatch, BBatch> BatchTransport for Either<A, B>
@@ -90,4 +100,18 @@ where
             Self::Right(ref b) => b.unsubscribe(id),
         }
     }
+
+    fn cancel_subscription(&self, id: api::SubscriptionId) -> error::Result {
+        match *self {
+            Self::Left(ref a) => a.cancel_subscription(id),
+            Self::Right(ref b) => b.cancel_subscription(id),
+        }
+    }
+
+    fn connection_events(&self) -> BoxStream<'static, ConnectionEvent> {
+        match *self {
+            Self::Left(ref a) => a.connection_events(),
+            Self::Right(ref b) => b.connection_events(),
+        }
+    }
 }
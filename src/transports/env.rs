@@ -0,0 +1,209 @@
+//! Transport configured from environment variables.
+//!
+//! - `WEB3_PROVIDER_URL` - `http(s)://`, `ws(s)://` or `ipc://` URL, or a path to an IPC socket.
+//! - `WEB3_WS_URL` - `ws(s)://` URL, used if `WEB3_PROVIDER_URL` is not set.
+//! - `WEB3_API_KEY` - substituted for the `{api_key}` placeholder in the URL
+//!   (e.g. `https://mainnet.infura.io/v3/{api_key}`), so the key can be kept separately.
+//!
+//! Each transport is only available with its feature (`http`, `ws-tokio`/`ws-async-std` or
+//! `ipc-tokio`), URLs of disabled transports are rejected.
+
+use crate::{
+    error::{Error, Result, TransportError},
+    rpc,
+    transports::Either,
+    BatchTransport, RequestId, Transport,
+};
+use futures::future;
+use std::path::PathBuf;
+
+#[cfg(feature = "http")]
+type HttpTransport = crate::transports::Http;
+#[cfg(not(feature = "http"))]
+type HttpTransport = Disabled;
+#[cfg(any(feature = "ws-tokio", feature = "ws-async-std"))]
+type WsTransport = crate::transports::WebSocket;
+#[cfg(not(any(feature = "ws-tokio", feature = "ws-async-std")))]
+type WsTransport = Disabled;
+#[cfg(feature = "ipc-tokio")]
+type IpcTransport = crate::transports::Ipc;
+#[cfg(not(feature = "ipc-tokio"))]
+type IpcTransport = Disabled;
+
+/// Transport picked by [`from_env`]: HTTP, WebSocket or IPC.
+///
+/// Transports of disabled features are replaced by [`Disabled`].
+pub type EnvTransport = Either<HttpTransport, Either<WsTransport, IpcTransport>>;
+
+/// Stands in for a transport which feature is disabled, it can't be constructed.
+#[derive(Debug, Clone)]
+pub enum Disabled {}
+
+impl Transport for Disabled {
+    type Out = future::Ready<Result<rpc::Value>>;
+
+    fn prepare(&self, _method: &str, _params: Vec<rpc::Value>) -> (RequestId, rpc::Call) {
+        match *self {}
+    }
+
+    fn send(&self, _id: RequestId, _request: rpc::Call) -> Self::Out {
+        match *self {}
+    }
+}
+
+impl BatchTransport for Disabled {
+    type Batch = future::Ready<Result<Vec<Result<rpc::Value>>>>;
+
+    fn send_batch<T>(&self, _requests: T) -> Self::Batch
+    where
+        T: IntoIterator<Item = (RequestId, rpc::Call)>,
+    {
+        match *self {}
+    }
+}
+
+/// Name of the variable holding the provider URL.
+pub const PROVIDER_URL_VAR: &str = "WEB3_PROVIDER_URL";
+/// Name of the variable holding the WebSocket URL.
+pub const WS_URL_VAR: &str = "WEB3_WS_URL";
+/// Name of the variable substituted for the `{api_key}` placeholder.
+pub const API_KEY_VAR: &str = "WEB3_API_KEY";
+
+const API_KEY_PLACEHOLDER: &str = "{api_key}";
+
+/// Creates a transport from the environment variables, picking the transport by the URL scheme.
+pub async fn from_env() -> Result<EnvTransport> {
+    match Endpoint::from_vars(|name| std::env::var(name).ok())? {
+        #[cfg(feature = "http")]
+        Endpoint::Http(url) => Ok(Either::Left(HttpTransport::new(&url)?)),
+        #[cfg(not(feature = "http"))]
+        Endpoint::Http(_) => Err(disabled("HTTP", "http")),
+        #[cfg(any(feature = "ws-tokio", feature = "ws-async-std"))]
+        Endpoint::Ws(url) => Ok(Either::Right(Either::Left(WsTransport::new(&url).await?))),
+        #[cfg(not(any(feature = "ws-tokio", feature = "ws-async-std")))]
+        Endpoint::Ws(_) => Err(disabled("WebSocket", "ws-tokio")),
+        #[cfg(feature = "ipc-tokio")]
+        Endpoint::Ipc(path) => Ok(Either::Right(Either::Right(IpcTransport::new(path).await?))),
+        #[cfg(not(feature = "ipc-tokio"))]
+        Endpoint::Ipc(_) => Err(disabled("IPC", "ipc-tokio")),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum Endpoint {
+    Http(String),
+    Ws(String),
+    Ipc(PathBuf),
+}
+
+impl Endpoint {
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let set = |name| var(name).filter(|url: &String| !url.trim().is_empty());
+        let url = set(PROVIDER_URL_VAR)
+            .or_else(|| set(WS_URL_VAR))
+            .ok_or_else(|| error(format!("neither {} nor {} is set", PROVIDER_URL_VAR, WS_URL_VAR)))?;
+        let url = match (url.contains(API_KEY_PLACEHOLDER), var(API_KEY_VAR)) {
+            (true, Some(key)) => url.replace(API_KEY_PLACEHOLDER, &key),
+            (true, None) => return Err(error(format!("{} is required by the provider URL", API_KEY_VAR))),
+            (false, _) => url,
+        };
+        Self::parse(&url)
+    }
+
+    /// Picks the transport by the scheme of the URL, a URL without a scheme is a path to an IPC socket.
+    pub(crate) fn parse(url: &str) -> Result<Self> {
+        let url = url.trim();
+        let (scheme, path) = match url.split_once("://") {
+            Some((scheme, path)) => (Some(scheme.to_ascii_lowercase()), path),
+            None => (None, url),
+        };
+        match scheme.as_deref() {
+            Some("http") | Some("https") => Ok(Endpoint::Http(url.into())),
+            Some("ws") | Some("wss") => Ok(Endpoint::Ws(url.into())),
+            Some("ipc") | Some("file") | None => Ok(Endpoint::Ipc(path.into())),
+            Some(scheme) => Err(error(format!("unsupported provider URL scheme {:?}", scheme))),
+        }
+    }
+}
+
+#[cfg(not(all(
+    feature = "http",
+    any(feature = "ws-tokio", feature = "ws-async-std"),
+    feature = "ipc-tokio"
+)))]
+fn disabled(transport: &str, feature: &str) -> Error {
+    error(format!(
+        "{} transport is disabled, enable the `{}` feature",
+        transport, feature
+    ))
+}
+
+fn error(message: String) -> Error {
+    Error::Transport(TransportError::Message(message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn endpoint(vars: &[(&str, &str)]) -> Result<Endpoint> {
+        let vars: HashMap<_, _> = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        Endpoint::from_vars(|name| vars.get(name).cloned())
+    }
+
+    #[test]
+    fn should_pick_transport_by_scheme() {
+        assert_eq!(
+            endpoint(&[(PROVIDER_URL_VAR, "http://localhost:8545")]).unwrap(),
+            Endpoint::Http("http://localhost:8545".into())
+        );
+        assert_eq!(
+            endpoint(&[(PROVIDER_URL_VAR, "WSS://localhost:8546")]).unwrap(),
+            Endpoint::Ws("WSS://localhost:8546".into())
+        );
+        assert_eq!(
+            endpoint(&[(PROVIDER_URL_VAR, "ipc:///tmp/geth.ipc")]).unwrap(),
+            Endpoint::Ipc("/tmp/geth.ipc".into())
+        );
+        assert_eq!(
+            endpoint(&[(PROVIDER_URL_VAR, "/tmp/geth.ipc")]).unwrap(),
+            Endpoint::Ipc("/tmp/geth.ipc".into())
+        );
+        assert!(endpoint(&[(PROVIDER_URL_VAR, "ftp://localhost")]).is_err());
+    }
+
+    #[test]
+    fn should_prefer_provider_url() {
+        assert_eq!(
+            endpoint(&[
+                (PROVIDER_URL_VAR, "http://localhost:8545"),
+                (WS_URL_VAR, "ws://localhost:8546")
+            ])
+            .unwrap(),
+            Endpoint::Http("http://localhost:8545".into())
+        );
+        assert_eq!(
+            endpoint(&[(WS_URL_VAR, "ws://localhost:8546")]).unwrap(),
+            Endpoint::Ws("ws://localhost:8546".into())
+        );
+        assert_eq!(
+            endpoint(&[(PROVIDER_URL_VAR, " "), (WS_URL_VAR, "ws://localhost:8546")]).unwrap(),
+            Endpoint::Ws("ws://localhost:8546".into())
+        );
+        assert!(endpoint(&[]).is_err());
+    }
+
+    #[test]
+    fn should_substitute_api_key() {
+        assert_eq!(
+            endpoint(&[
+                (PROVIDER_URL_VAR, "https://mainnet.infura.io/v3/{api_key}"),
+                (API_KEY_VAR, "secret")
+            ])
+            .unwrap(),
+            Endpoint::Http("https://mainnet.infura.io/v3/secret".into())
+        );
+        assert!(endpoint(&[(PROVIDER_URL_VAR, "https://mainnet.infura.io/v3/{api_key}")]).is_err());
+    }
+}
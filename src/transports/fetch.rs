@@ -0,0 +1,170 @@
+//! Fetch Transport
+//!
+//! Lightweight HTTP transport for the `wasm32-unknown-unknown` target built directly on the
+//! browser's `fetch` API, without pulling in `reqwest`. Works both in window and worker contexts.
+
+use crate::{
+    error::{Error, Result, TransportError},
+    helpers::{self, handle_batch_response, handle_possible_error_object_for_batched_request},
+    BatchTransport, RequestId, Transport,
+};
+use futures::future::LocalBoxFuture;
+use jsonrpc_core::types::{Call, Request, Value};
+use serde::de::DeserializeOwned;
+use std::{cell::Cell, rc::Rc};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Headers, RequestInit, Response, Window, WorkerGlobalScope};
+
+/// Fetch Transport
+#[derive(Clone, Debug)]
+pub struct Fetch {
+    inner: Rc<Inner>,
+}
+
+#[derive(Clone, Debug)]
+struct Inner {
+    url: String,
+    headers: Vec<(String, String)>,
+    id: Cell<RequestId>,
+}
+
+impl Fetch {
+    /// Create new fetch transport posting requests to given URL.
+    pub fn new(url: &str) -> Self {
+        Fetch {
+            inner: Rc::new(Inner {
+                url: url.into(),
+                headers: Vec::new(),
+                id: Cell::new(0),
+            }),
+        }
+    }
+
+    /// Adds a header sent with every request, e.g. `Authorization`.
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        Rc::make_mut(&mut self.inner).headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Sets the counter value of the next request, see [`IdStrategy`](crate::transports::IdStrategy#request-counter).
+    #[cfg(feature = "test-support")]
+    pub fn set_next_request_id(&self, id: RequestId) {
+        self.inner.id.set(id);
+    }
+
+    fn next_id(&self) -> RequestId {
+        let id = self.inner.id.get();
+        self.inner.id.set(id.wrapping_add(1));
+        id
+    }
+}
+
+fn js_error(context: &str, err: JsValue) -> Error {
+    Error::Transport(TransportError::Message(format!("{}: {:?}", context, err)))
+}
+
+// Id is only used for logging.
+async fn execute_rpc<T: DeserializeOwned>(inner: Rc<Inner>, request: &Request, id: RequestId) -> Result<T> {
+    let body = serde_json::to_string(request)?;
+    event!(debug, "[id:{}] sending request: {:?}", id, body);
+
+    let headers = Headers::new().map_err(|err| js_error("failed to create headers", err))?;
+    headers
+        .set("Content-Type", "application/json")
+        .map_err(|err| js_error("failed to set header", err))?;
+    for (name, value) in &inner.headers {
+        headers
+            .set(name, value)
+            .map_err(|err| js_error("failed to set header", err))?;
+    }
+    let init = RequestInit::new();
+    init.set_method("POST");
+    init.set_headers(&headers);
+    init.set_body(&JsValue::from_str(&body));
+    let request = web_sys::Request::new_with_str_and_init(&inner.url, &init)
+        .map_err(|err| js_error("failed to create request", err))?;
+
+    // `fetch` lives on the global scope, which is either a window or a worker.
+    let global = js_sys::global();
+    let promise = if let Some(window) = global.dyn_ref::<Window>() {
+        window.fetch_with_request(&request)
+    } else if let Some(worker) = global.dyn_ref::<WorkerGlobalScope>() {
+        worker.fetch_with_request(&request)
+    } else {
+        return Err(Error::Transport(TransportError::Message(
+            "fetch is not available in this context".into(),
+        )));
+    };
+    let response: Response = JsFuture::from(promise)
+        .await
+        .map_err(|err| js_error("failed to send request", err))?
+        .dyn_into()
+        .map_err(|err| js_error("unexpected fetch result", err))?;
+    let text = response
+        .text()
+        .map_err(|err| js_error("failed to read response", err))?;
+    let text = JsFuture::from(text)
+        .await
+        .map_err(|err| js_error("failed to read response", err))?
+        .as_string()
+        .unwrap_or_default();
+    event!(debug, "[id:{}] received response: {:?}", id, text);
+    if !response.ok() {
+        return Err(Error::Transport(TransportError::Code(response.status())));
+    }
+    helpers::arbitrary_precision_deserialize_workaround(text.as_bytes()).map_err(|err| {
+        Error::Transport(TransportError::Message(format!(
+            "failed to deserialize response: {}: {}",
+            err, text
+        )))
+    })
+}
+
+type RpcResult = Result<Value>;
+
+impl Transport for Fetch {
+    type Out = LocalBoxFuture<'static, Result<Value>>;
+
+    fn prepare(&self, method: &str, params: Vec<Value>) -> (RequestId, Call) {
+        let id = self.next_id();
+        let request = helpers::build_request(id, method, params);
+        (id, request)
+    }
+
+    fn send(&self, id: RequestId, call: Call) -> Self::Out {
+        let inner = self.inner.clone();
+        let span = helpers::RequestSpan::new("fetch", id, &call);
+        Box::pin(async move {
+            let result = execute_rpc(inner, &Request::Single(call), id)
+                .await
+                .and_then(helpers::to_result_from_output);
+            span.finish(&result);
+            result
+        })
+    }
+}
+
+impl BatchTransport for Fetch {
+    type Batch = LocalBoxFuture<'static, Result<Vec<RpcResult>>>;
+
+    fn send_batch<T>(&self, requests: T) -> Self::Batch
+    where
+        T: IntoIterator<Item = (RequestId, Call)>,
+    {
+        // Batch calls don't need an id but it helps associate the response log with the request log.
+        let id = self.next_id();
+        let inner = self.inner.clone();
+        let calls: Vec<_> = requests.into_iter().map(|(_, call)| call).collect();
+        let ids: Vec<_> = calls.iter().map(helpers::id_of_call).collect();
+        let span = helpers::RequestSpan::batch("fetch", id);
+        Box::pin(async move {
+            let result = execute_rpc(inner, &Request::Batch(calls), id)
+                .await
+                .and_then(handle_possible_error_object_for_batched_request)
+                .and_then(|outputs| handle_batch_response(&ids, outputs));
+            span.finish(&result);
+            result
+        })
+    }
+}
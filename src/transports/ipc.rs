@@ -1,27 +1,35 @@
 //! IPC transport
 
 use crate::{
-    api::SubscriptionId, error::TransportError, helpers, BatchTransport, DuplexTransport, Error, RequestId, Result,
-    Transport,
+    api::SubscriptionId,
+    error::TransportError,
+    helpers::{self, backoff::Backoff},
+    transports::{
+        connection::ConnectionState,
+        notifications::{NotificationBuffer, NotificationSender, NotificationStream},
+        ConnectionEvent,
+    },
+    BatchTransport, DuplexTransport, Error, RequestId, Result, Transport,
 };
 use futures::{
     future::{join_all, JoinAll},
-    stream::StreamExt,
+    stream::{BoxStream, StreamExt},
 };
+use futures_timer::Delay;
 use jsonrpc_core as rpc;
 use std::{
     collections::BTreeMap,
-    path::Path,
+    path::{Path, PathBuf},
     pin::Pin,
     sync::{atomic::AtomicUsize, Arc},
     task::{Context, Poll},
+    time::Duration,
 };
 use tokio::{
-    io::AsyncWriteExt,
+    io::{AsyncReadExt, AsyncWriteExt},
     sync::{mpsc, oneshot},
 };
 use tokio_stream::wrappers::UnboundedReceiverStream;
-use tokio_util::io::ReaderStream;
 
 #[cfg(unix)]
 use tokio::net::UnixStream;
@@ -31,6 +39,9 @@ use tokio::net::UnixStream;
 pub struct Ipc {
     id: Arc<AtomicUsize>,
     messages_tx: mpsc::UnboundedSender<TransportMessage>,
+    notification_buffer: NotificationBuffer,
+    events: Arc<ConnectionState>,
+    timeout: Option<Duration>,
 }
 
 #[cfg(unix)]
@@ -41,16 +52,81 @@ impl Ipc {
     pub async fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
         let stream = UnixStream::connect(path).await?;
 
-        Ok(Self::with_stream(stream))
+        Ok(Self::with_stream(stream, None))
     }
 
-    fn with_stream(stream: UnixStream) -> Self {
+    /// Creates a new IPC transport which reconnects when the connection is lost (e.g. the node restarts).
+    ///
+    /// Reconnection is attempted according to `backoff` (a `Duration` retries at a fixed interval), the
+    /// transport is closed once the backoff gives up. Requests in flight when the connection is lost
+    /// fail with a transport error, as do requests sent before the connection is re-established.
+    /// Subscription streams end, since the node doesn't keep subscriptions across connections.
+    pub async fn with_reconnect<P, B>(path: P, backoff: B) -> Result<Self>
+    where
+        P: AsRef<Path>,
+        B: Into<Backoff>,
+    {
+        let path = path.as_ref().to_path_buf();
+        let stream = UnixStream::connect(&path).await?;
+
+        Ok(Self::with_stream(stream, Some((path, backoff.into()))))
+    }
+
+    /// Sets the counter value of the next request, see [`IdStrategy`](crate::transports::IdStrategy#request-counter).
+    #[cfg(feature = "test-support")]
+    pub fn set_next_request_id(&self, id: RequestId) {
+        self.id.store(id, std::sync::atomic::Ordering::Release);
+    }
+
+    fn with_stream(stream: UnixStream, reconnect: Option<(PathBuf, Backoff)>) -> Self {
         let id = Arc::new(AtomicUsize::new(1));
         let (messages_tx, messages_rx) = mpsc::unbounded_channel();
+        let events = Arc::new(ConnectionState::default());
+        events.emit(ConnectionEvent::Connected);
+
+        let server_events = events.clone();
+        tokio::spawn(async move {
+            let result = run_server(
+                stream,
+                UnboundedReceiverStream::new(messages_rx),
+                reconnect,
+                &server_events,
+            )
+            .await;
+            server_events.close();
+            result
+        });
+
+        Ipc {
+            id,
+            messages_tx,
+            notification_buffer: Default::default(),
+            events,
+            timeout: None,
+        }
+    }
+}
+
+impl Ipc {
+    /// Sets how notifications of subsequent subscriptions are buffered.
+    ///
+    /// With [`NotificationBuffer::Bounded`] the socket is not read while a subscription
+    /// stream is full, which propagates the backpressure to the node.
+    pub fn with_notification_buffer(mut self, buffer: NotificationBuffer) -> Self {
+        self.notification_buffer = buffer;
+        self
+    }
 
-        tokio::spawn(run_server(stream, UnboundedReceiverStream::new(messages_rx)));
+    /// Fails requests (and batches) without a response within `timeout` with `TransportError::Timeout`.
+    ///
+    /// Without a timeout requests wait for the response as long as the connection is open.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
 
-        Ipc { id, messages_tx }
+    fn deadline(&self) -> Option<Delay> {
+        self.timeout.map(Delay::new)
     }
 }
 
@@ -64,10 +140,15 @@ impl Transport for Ipc {
     }
 
     fn send(&self, id: RequestId, call: rpc::Call) -> Self::Out {
+        let span = helpers::RequestSpan::new("ipc", id, &call);
         let (response_tx, response_rx) = oneshot::channel();
         let message = TransportMessage::Single((id, call, response_tx));
 
-        SingleResponse(self.messages_tx.send(message).map(|()| response_rx).map_err(Into::into))
+        SingleResponse(
+            self.messages_tx.send(message).map(|()| response_rx).map_err(Into::into),
+            span,
+            self.deadline(),
+        )
     }
 }
 
@@ -77,34 +158,36 @@ impl BatchTransport for Ipc {
     fn send_batch<T: IntoIterator<Item = (RequestId, rpc::Call)>>(&self, requests: T) -> Self::Batch {
         let mut response_rxs = vec![];
 
-        let message = TransportMessage::Batch(
-            requests
-                .into_iter()
-                .map(|(id, call)| {
-                    let (response_tx, response_rx) = oneshot::channel();
-                    response_rxs.push(response_rx);
+        let requests: Vec<_> = requests
+            .into_iter()
+            .map(|(id, call)| {
+                let (response_tx, response_rx) = oneshot::channel();
+                response_rxs.push(response_rx);
 
-                    (id, call, response_tx)
-                })
-                .collect(),
-        );
+                (id, call, response_tx)
+            })
+            .collect();
+        let span = helpers::RequestSpan::batch("ipc", requests.first().map_or(0, |request| request.0));
+        let message = TransportMessage::Batch(requests);
 
         BatchResponse(
             self.messages_tx
                 .send(message)
                 .map(|()| join_all(response_rxs))
                 .map_err(Into::into),
+            span,
+            self.deadline(),
         )
     }
 }
 
 impl DuplexTransport for Ipc {
-    type NotificationStream = UnboundedReceiverStream<rpc::Value>;
+    type NotificationStream = NotificationStream;
 
     fn subscribe(&self, id: SubscriptionId) -> Result<Self::NotificationStream> {
-        let (tx, rx) = mpsc::unbounded_channel();
+        let (tx, rx) = self.notification_buffer.channel();
         self.messages_tx.send(TransportMessage::Subscribe(id, tx))?;
-        Ok(UnboundedReceiverStream::new(rx))
+        Ok(rx)
     }
 
     fn unsubscribe(&self, id: SubscriptionId) -> Result<()> {
@@ -112,43 +195,67 @@ impl DuplexTransport for Ipc {
             .send(TransportMessage::Unsubscribe(id))
             .map_err(Into::into)
     }
+
+    fn connection_events(&self) -> BoxStream<'static, ConnectionEvent> {
+        self.events.listen()
+    }
+}
+
+/// Polls the timeout of a request, if any.
+fn poll_deadline(deadline: &mut Option<Delay>, cx: &mut Context<'_>) -> Poll<Error> {
+    match deadline {
+        Some(delay) => futures::Future::poll(Pin::new(delay), cx).map(|()| Error::Transport(TransportError::Timeout)),
+        None => Poll::Pending,
+    }
 }
 
 /// A future representing a pending RPC request. Resolves to a JSON RPC output.
-pub struct SingleResponse(Result<oneshot::Receiver<rpc::Output>>);
+pub struct SingleResponse(
+    Result<oneshot::Receiver<rpc::Output>>,
+    helpers::RequestSpan,
+    Option<Delay>,
+);
 
 impl futures::Future for SingleResponse {
     type Output = Result<rpc::Value>;
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        match &mut self.0 {
-            Err(err) => Poll::Ready(Err(err.clone())),
-            Ok(ref mut rx) => {
-                let output = ready!(futures::Future::poll(Pin::new(rx), cx))?;
-                Poll::Ready(helpers::to_result_from_output(output))
-            }
-        }
+        let this = &mut *self;
+        let result = match &mut this.0 {
+            Err(err) => Err(err.clone()),
+            Ok(ref mut rx) => match futures::Future::poll(Pin::new(rx), cx) {
+                Poll::Ready(output) => output.map_err(Into::into).and_then(helpers::to_result_from_output),
+                Poll::Pending => Err(ready!(poll_deadline(&mut this.2, cx))),
+            },
+        };
+        this.1.finish(&result);
+        Poll::Ready(result)
     }
 }
 
 /// A future representing a pending batch RPC request. Resolves to a vector of JSON RPC value.
-pub struct BatchResponse(Result<JoinAll<oneshot::Receiver<rpc::Output>>>);
+pub struct BatchResponse(
+    Result<JoinAll<oneshot::Receiver<rpc::Output>>>,
+    helpers::RequestSpan,
+    Option<Delay>,
+);
 
 impl futures::Future for BatchResponse {
     type Output = Result<Vec<Result<rpc::Value>>>;
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        match &mut self.0 {
-            Err(err) => Poll::Ready(Err(err.clone())),
-            Ok(ref mut rxs) => {
-                let poll = futures::Future::poll(Pin::new(rxs), cx);
-                let values = ready!(poll)
+        let this = &mut *self;
+        let result = match &mut this.0 {
+            Err(err) => Err(err.clone()),
+            Ok(ref mut rxs) => match futures::Future::poll(Pin::new(rxs), cx) {
+                Poll::Ready(outputs) => Ok(outputs
                     .into_iter()
                     .map(|r| r.map_err(Into::into))
                     .map(|r| r.and_then(helpers::to_result_from_output))
-                    .collect();
-
-                Poll::Ready(Ok(values))
-            }
-        }
+                    .collect()),
+                Poll::Pending => Err(ready!(poll_deadline(&mut this.2, cx))),
+            },
+        };
+        this.1.finish(&result);
+        Poll::Ready(result)
     }
 }
 
@@ -158,111 +265,222 @@ type TransportRequest = (RequestId, rpc::Call, oneshot::Sender<rpc::Output>);
 enum TransportMessage {
     Single(TransportRequest),
     Batch(Vec<TransportRequest>),
-    Subscribe(SubscriptionId, mpsc::UnboundedSender<rpc::Value>),
+    Subscribe(SubscriptionId, NotificationSender),
     Unsubscribe(SubscriptionId),
 }
 
-#[cfg(unix)]
-async fn run_server(unix_stream: UnixStream, messages_rx: UnboundedReceiverStream<TransportMessage>) -> Result<()> {
-    let (socket_reader, mut socket_writer) = unix_stream.into_split();
-    let mut pending_response_txs = BTreeMap::default();
-    let mut subscription_txs = BTreeMap::default();
+/// State shared by consecutive connections of the server.
+#[derive(Default)]
+struct ServerState {
+    pending_response_txs: BTreeMap<RequestId, oneshot::Sender<rpc::Output>>,
+    subscription_txs: BTreeMap<SubscriptionId, NotificationSender>,
+}
+
+impl ServerState {
+    /// Fails all pending requests and ends all subscriptions.
+    ///
+    /// Dropping the senders makes the pending futures resolve with a transport error
+    /// and the notification streams terminate.
+    fn fail_all(&mut self) {
+        if !self.pending_response_txs.is_empty() || !self.subscription_txs.is_empty() {
+            event!(
+                warn,
+                "IPC connection lost, failing {} pending requests and {} subscriptions",
+                self.pending_response_txs.len(),
+                self.subscription_txs.len()
+            );
+        }
+        self.pending_response_txs.clear();
+        self.subscription_txs.clear();
+    }
+}
+
+/// Why a connection has been terminated.
+enum ConnectionEnd {
+    /// All transport handles were dropped.
+    Closed,
+    /// The socket has been closed or failed.
+    Disconnected(Error),
+}
 
-    let mut socket_reader = ReaderStream::new(socket_reader);
+#[cfg(unix)]
+async fn run_server(
+    mut unix_stream: UnixStream,
+    messages_rx: UnboundedReceiverStream<TransportMessage>,
+    reconnect: Option<(PathBuf, Backoff)>,
+    events: &ConnectionState,
+) -> Result<()> {
     let mut messages_rx = messages_rx.fuse();
-    let mut read_buffer = vec![];
+    let mut state = ServerState::default();
+
+    loop {
+        let err = match run_connection(unix_stream, &mut messages_rx, &mut state).await {
+            ConnectionEnd::Closed => return Ok(()),
+            ConnectionEnd::Disconnected(err) => err,
+        };
+        state.fail_all();
+        events.emit(ConnectionEvent::Disconnected { cause: err.to_string() });
+
+        let (path, backoff) = match reconnect {
+            Some((ref path, backoff)) => (path, backoff),
+            None => return Err(err),
+        };
+        event!(warn, "IPC connection lost ({:?}), reconnecting to {:?}", err, path);
+        let mut delays = backoff.delays();
+        let mut attempt = 0;
+        unix_stream = loop {
+            let delay = match delays.next() {
+                Some(delay) => delay,
+                None => {
+                    event!(warn, "IPC reconnection to {:?} failed, giving up", path);
+                    return Err(err);
+                }
+            };
+            // requests can't be sent until reconnected, so fail them right away
+            let retry = tokio::time::sleep(delay);
+            tokio::pin!(retry);
+            loop {
+                tokio::select! {
+                    message = messages_rx.next() => match message {
+                        None => return Ok(()),
+                        Some(message) => drop(message),
+                    },
+                    _ = &mut retry => break,
+                }
+            }
+            attempt += 1;
+            events.emit(ConnectionEvent::Reconnecting { attempt });
+            match UnixStream::connect(path).await {
+                Ok(stream) => break stream,
+                Err(err) => event!(debug, "IPC reconnection failed: {:?}", err),
+            }
+        };
+        event!(info, "IPC reconnected to {:?}", path);
+        events.emit(ConnectionEvent::Connected);
+    }
+}
+
+#[cfg(unix)]
+async fn run_connection(
+    unix_stream: UnixStream,
+    messages_rx: &mut futures::stream::Fuse<UnboundedReceiverStream<TransportMessage>>,
+    state: &mut ServerState,
+) -> ConnectionEnd {
+    let (mut socket_reader, mut socket_writer) = unix_stream.into_split();
+    let ServerState {
+        pending_response_txs,
+        subscription_txs,
+    } = state;
+
+    let mut read_buffer = Vec::with_capacity(4096);
     let mut closed = false;
 
-    while !closed || !pending_response_txs.is_empty() {
+    while !closed || has_pending(pending_response_txs) {
         tokio::select! {
-            message = messages_rx.next() => match message {
+            message = messages_rx.next(), if !closed => match message {
                 None => closed = true,
                 Some(TransportMessage::Subscribe(id, tx)) => {
                     if subscription_txs.insert(id.clone(), tx).is_some() {
-                        log::warn!("Replacing a subscription with id {:?}", id);
+                        event!(warn, "Replacing a subscription with id {:?}", id);
                     }
                 },
                 Some(TransportMessage::Unsubscribe(id)) => {
                     if subscription_txs.remove(&id).is_none() {
-                        log::warn!("Unsubscribing not subscribed id {:?}", id);
+                        event!(warn, "Unsubscribing not subscribed id {:?}", id);
                     }
                 },
                 Some(TransportMessage::Single((request_id, rpc_call, response_tx))) => {
+                    prune_abandoned(pending_response_txs);
                     if pending_response_txs.insert(request_id, response_tx).is_some() {
-                        log::warn!("Replacing a pending request with id {:?}", request_id);
+                        event!(warn, "Replacing a pending request with id {:?}", request_id);
                     }
 
                     let bytes = helpers::to_string(&rpc::Request::Single(rpc_call)).into_bytes();
                     if let Err(err) = socket_writer.write_all(&bytes).await {
-                        pending_response_txs.remove(&request_id);
-                        log::error!("IPC write error: {:?}", err);
+                        event!(error, "IPC write error: {:?}", err);
+                        return ConnectionEnd::Disconnected(err.into());
                     }
                 }
                 Some(TransportMessage::Batch(requests)) => {
-                    let mut request_ids = vec![];
                     let mut rpc_calls = vec![];
 
+                    prune_abandoned(pending_response_txs);
                     for (request_id, rpc_call, response_tx) in requests {
-                        request_ids.push(request_id);
                         rpc_calls.push(rpc_call);
 
                         if pending_response_txs.insert(request_id, response_tx).is_some() {
-                            log::warn!("Replacing a pending request with id {:?}", request_id);
+                            event!(warn, "Replacing a pending request with id {:?}", request_id);
                         }
                     }
 
                     let bytes = helpers::to_string(&rpc::Request::Batch(rpc_calls)).into_bytes();
-
                     if let Err(err) = socket_writer.write_all(&bytes).await {
-                        log::error!("IPC write error: {:?}", err);
-                        for request_id in request_ids {
-                            pending_response_txs.remove(&request_id);
-                        }
+                        event!(error, "IPC write error: {:?}", err);
+                        return ConnectionEnd::Disconnected(err.into());
                     }
                 }
             },
-            bytes = socket_reader.next() => match bytes {
-                Some(Ok(bytes)) => {
-                    read_buffer.extend_from_slice(&bytes);
-
-                    let read_len = {
-                        let mut de: serde_json::StreamDeserializer<_, serde_json::Value> =
-                            serde_json::Deserializer::from_slice(&read_buffer).into_iter();
-
-                        while let Some(Ok(value)) = de.next() {
-                            if let Ok(notification) = serde_json::from_value::<rpc::Notification>(value.clone()) {
-                                let _ = notify(&mut subscription_txs, notification);
-                                continue;
-                            }
-
-                            if let Ok(response) = serde_json::from_value::<rpc::Response>(value) {
-                                let _ = respond(&mut pending_response_txs, response);
-                                continue;
-                            }
-
-                            log::warn!("JSON is not a response or notification");
-                        }
-
-                        de.byte_offset()
-                    };
-
-                    read_buffer.copy_within(read_len.., 0);
-                    read_buffer.truncate(read_buffer.len() - read_len);
+            read = socket_reader.read_buf(&mut read_buffer) => match read {
+                Ok(0) => return ConnectionEnd::Disconnected(Error::Transport(TransportError::Message(
+                    "IPC connection closed".into(),
+                ))),
+                Ok(_) => {
+                    let read_len = dispatch_messages(&read_buffer, pending_response_txs, subscription_txs).await;
+                    read_buffer.drain(..read_len);
                 },
-                Some(Err(err)) => {
-                    log::error!("IPC read error: {:?}", err);
-                    return Err(err.into());
+                Err(err) => {
+                    event!(error, "IPC read error: {:?}", err);
+                    return ConnectionEnd::Disconnected(err.into());
                 },
-                None => break,
             }
         };
     }
 
-    Ok(())
+    ConnectionEnd::Closed
+}
+
+/// Handles all complete JSON values in the buffer and returns the number of consumed bytes.
+///
+/// Values are not delimited, so the buffer is parsed as a stream of JSON values and the trailing
+/// incomplete one is left for the next read. Waits while a bounded subscription buffer is full.
+/// Drops senders of requests whose futures were dropped (e.g. timed out), the responses would be ignored anyway.
+fn prune_abandoned(pending_response_txs: &mut BTreeMap<RequestId, oneshot::Sender<rpc::Output>>) {
+    pending_response_txs.retain(|_, response_tx| !response_tx.is_closed());
 }
 
-fn notify(
-    subscription_txs: &mut BTreeMap<SubscriptionId, mpsc::UnboundedSender<rpc::Value>>,
+/// Whether any requests still wait for a response.
+fn has_pending(pending_response_txs: &mut BTreeMap<RequestId, oneshot::Sender<rpc::Output>>) -> bool {
+    prune_abandoned(pending_response_txs);
+    !pending_response_txs.is_empty()
+}
+
+async fn dispatch_messages(
+    buffer: &[u8],
+    pending_response_txs: &mut BTreeMap<RequestId, oneshot::Sender<rpc::Output>>,
+    subscription_txs: &mut BTreeMap<SubscriptionId, NotificationSender>,
+) -> usize {
+    let mut de: serde_json::StreamDeserializer<_, serde_json::Value> =
+        serde_json::Deserializer::from_slice(buffer).into_iter();
+
+    while let Some(Ok(value)) = de.next() {
+        if let Ok(notification) = serde_json::from_value::<rpc::Notification>(value.clone()) {
+            let _ = notify(subscription_txs, notification).await;
+            continue;
+        }
+
+        if let Ok(response) = serde_json::from_value::<rpc::Response>(value) {
+            let _ = respond(pending_response_txs, response);
+            continue;
+        }
+
+        event!(warn, "JSON is not a response or notification");
+    }
+
+    de.byte_offset()
+}
+
+async fn notify(
+    subscription_txs: &mut BTreeMap<SubscriptionId, NotificationSender>,
     notification: rpc::Notification,
 ) -> std::result::Result<(), ()> {
     if let rpc::Params::Map(params) = notification.params {
@@ -271,15 +489,15 @@ fn notify(
 
         if let (Some(&rpc::Value::String(ref id)), Some(result)) = (id, result) {
             let id: SubscriptionId = id.clone().into();
-            if let Some(tx) = subscription_txs.get(&id) {
-                if let Err(e) = tx.send(result.clone()) {
-                    log::error!("Error sending notification: {:?} (id: {:?}", e, id);
+            if let Some(tx) = subscription_txs.get_mut(&id) {
+                if let Err(e) = tx.send(result.clone()).await {
+                    event!(error, "Error sending notification: {:?} (id: {:?}", e, id);
                 }
             } else {
-                log::warn!("Got notification for unknown subscription (id: {:?})", id);
+                event!(warn, "Got notification for unknown subscription (id: {:?})", id);
             }
         } else {
-            log::error!("Got unsupported notification (id: {:?})", id);
+            event!(error, "Got unsupported notification (id: {:?})", id);
         }
     }
 
@@ -311,17 +529,17 @@ fn respond_output(
     let id = match id {
         rpc::Id::Num(num) => num as usize,
         _ => {
-            log::warn!("Got unsupported response (id: {:?})", id);
+            event!(warn, "Got unsupported response (id: {:?})", id);
             return Err(());
         }
     };
 
     let response_tx = pending_response_txs.remove(&id).ok_or_else(|| {
-        log::warn!("Got response for unknown request (id: {:?})", id);
+        event!(warn, "Got response for unknown request (id: {:?})", id);
     })?;
 
     response_tx.send(output).map_err(|err| {
-        log::warn!("Sending a response to deallocated channel: {:?}", err);
+        event!(warn, "Sending a response to deallocated channel: {:?}", err);
     })
 }
 
@@ -341,12 +559,14 @@ impl From<oneshot::error::RecvError> for Error {
 mod test {
     use super::*;
     use serde_json::json;
+    use std::time::Duration;
     use tokio::{io::AsyncWriteExt, net::UnixStream};
+    use tokio_util::io::ReaderStream;
 
     #[tokio::test]
     async fn works_for_single_requests() {
         let (stream1, stream2) = UnixStream::pair().unwrap();
-        let ipc = Ipc::with_stream(stream1);
+        let ipc = Ipc::with_stream(stream1, None);
 
         tokio::spawn(eth_node_single(stream2));
 
@@ -426,7 +646,7 @@ mod test {
     #[tokio::test]
     async fn works_for_batch_request() {
         let (stream1, stream2) = UnixStream::pair().unwrap();
-        let ipc = Ipc::with_stream(stream1);
+        let ipc = Ipc::with_stream(stream1, None);
 
         tokio::spawn(eth_node_batch(stream2));
 
@@ -478,10 +698,26 @@ mod test {
         }
     }
 
+    #[tokio::test]
+    async fn fails_requests_without_response_in_time() {
+        let (stream1, _node) = UnixStream::pair().unwrap();
+        let ipc = Ipc::with_stream(stream1, None).with_timeout(Duration::from_millis(10));
+        let mut events = ipc.connection_events();
+
+        let response = ipc.execute("eth_test", vec![]).await;
+        assert_eq!(response, Err(Error::Transport(TransportError::Timeout)));
+
+        // the abandoned request doesn't keep the connection open once the transport is dropped
+        drop(ipc);
+        let closed =
+            tokio::time::timeout(Duration::from_secs(5), async { while events.next().await.is_some() {} }).await;
+        assert!(closed.is_ok());
+    }
+
     #[tokio::test]
     async fn works_for_partial_batches() {
         let (stream1, stream2) = UnixStream::pair().unwrap();
-        let ipc = Ipc::with_stream(stream1);
+        let ipc = Ipc::with_stream(stream1, None);
 
         tokio::spawn(eth_node_partial_batches(stream2));
 
@@ -523,4 +759,98 @@ mod test {
 
         tx.flush().await.unwrap();
     }
+
+    #[tokio::test]
+    async fn reconnects_after_disconnect() {
+        let path = std::env::temp_dir().join(format!("web3-ipc-test-{}.ipc", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = tokio::net::UnixListener::bind(&path).unwrap();
+
+        let server = tokio::spawn(async move {
+            // the first connection is dropped after receiving a request
+            let (stream, _) = listener.accept().await.unwrap();
+            let (rx, tx) = stream.into_split();
+            let _ = ReaderStream::new(rx).next().await;
+            drop(tx);
+
+            let (stream, _) = listener.accept().await.unwrap();
+            let (rx, mut tx) = stream.into_split();
+            let mut rx = ReaderStream::new(rx);
+            if let Some(Ok(bytes)) = rx.next().await {
+                let v: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+                let response = json!({"jsonrpc": "2.0", "id": v["id"], "result": true});
+                tx.write_all(serde_json::to_string(&response).unwrap().as_ref())
+                    .await
+                    .unwrap();
+            }
+        });
+
+        let ipc = Ipc::with_reconnect(&path, Duration::from_millis(10)).await.unwrap();
+        let mut events = ipc.connection_events();
+        let response = ipc.execute("eth_test", vec![]).await;
+        assert!(matches!(response, Err(Error::Transport(_))));
+
+        let mut response = ipc.execute("eth_test", vec![]).await;
+        for _ in 0..100 {
+            if response.is_ok() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            response = ipc.execute("eth_test", vec![]).await;
+        }
+        assert_eq!(response, Ok(json!(true)));
+        assert_eq!(events.next().await, Some(ConnectionEvent::Connected));
+        assert!(matches!(
+            events.next().await,
+            Some(ConnectionEvent::Disconnected { .. })
+        ));
+        assert_eq!(events.next().await, Some(ConnectionEvent::Reconnecting { attempt: 1 }));
+        let mut event = events.next().await;
+        while let Some(ConnectionEvent::Reconnecting { .. }) = event {
+            event = events.next().await;
+        }
+        assert_eq!(event, Some(ConnectionEvent::Connected));
+
+        server.await.unwrap();
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn pauses_reading_while_subscription_is_full() {
+        let (stream1, stream2) = UnixStream::pair().unwrap();
+        let ipc = Ipc::with_stream(stream1, None).with_notification_buffer(NotificationBuffer::Bounded(1));
+        let mut notifications = ipc.subscribe(SubscriptionId::from("0x1".to_owned())).unwrap();
+
+        tokio::spawn(async move {
+            let (rx, mut tx) = stream2.into_split();
+            let _ = ReaderStream::new(rx).next().await;
+            for i in 0..4 {
+                let notification = json!({
+                    "jsonrpc": "2.0",
+                    "method": "eth_subscription",
+                    "params": {"subscription": "0x1", "result": i},
+                });
+                tx.write_all(serde_json::to_string(&notification).unwrap().as_ref())
+                    .await
+                    .unwrap();
+            }
+            tx.write_all(r#"{"jsonrpc": "2.0", "id": 1, "result": true}"#.as_ref())
+                .await
+                .unwrap();
+            // keep the connection open
+            let _ = futures::future::pending::<()>().await;
+        });
+
+        let response = ipc.execute("eth_test", vec![]);
+        tokio::pin!(response);
+        // the response follows notifications which don't fit the buffer
+        assert!(tokio::time::timeout(Duration::from_millis(100), &mut response)
+            .await
+            .is_err());
+
+        for i in 0..4 {
+            assert_eq!(notifications.next().await, Some(json!(i)));
+        }
+        assert_eq!(response.await, Ok(json!(true)));
+    }
 }
@@ -1,26 +1,75 @@
 //! Supported Ethereum JSON-RPC transports.
 
+pub mod auth;
+pub use self::auth::AuthProvider;
 pub mod batch;
 
 pub use self::batch::Batch;
+pub mod chain;
+pub use self::chain::ExpectChain;
+pub mod connection;
+pub use self::connection::ConnectionEvent;
 pub mod either;
 pub use self::either::Either;
+pub mod polling;
+pub use self::polling::Polling;
+pub mod pool;
+pub use self::pool::FallbackTransport;
+pub mod request_id;
+pub use self::request_id::IdStrategy;
+pub mod retry;
+pub use self::retry::Retry;
+
+// `std::time::Instant` is not available on wasm.
+#[cfg(not(target_arch = "wasm32"))]
+pub mod throttle;
+#[cfg(not(target_arch = "wasm32"))]
+pub use self::throttle::Throttled;
+pub mod timeout;
+pub use self::timeout::Timeout;
+pub mod validate;
+pub use self::validate::Validating;
 
 #[cfg(any(feature = "http", feature = "http-rustls"))]
 pub mod http;
 #[cfg(any(feature = "http", feature = "http-rustls"))]
 pub use self::http::Http;
 
+#[cfg(feature = "wasm-fetch")]
+pub mod fetch;
+#[cfg(feature = "wasm-fetch")]
+pub use self::fetch::Fetch;
+
 #[cfg(any(feature = "ws-tokio", feature = "ws-async-std"))]
 pub mod ws;
 #[cfg(any(feature = "ws-tokio", feature = "ws-async-std"))]
 pub use self::ws::WebSocket;
 
+#[cfg(any(feature = "ws-tokio", feature = "ws-async-std", feature = "ipc-tokio"))]
+mod notifications;
+#[cfg(any(feature = "ws-tokio", feature = "ws-async-std", feature = "ipc-tokio"))]
+pub use self::notifications::{NotificationBuffer, NotificationStream};
+
 #[cfg(feature = "ipc-tokio")]
 pub mod ipc;
 #[cfg(feature = "ipc-tokio")]
 pub use self::ipc::Ipc;
 
+#[cfg(any(
+    feature = "http",
+    feature = "ws-tokio",
+    feature = "ws-async-std",
+    feature = "ipc-tokio"
+))]
+pub mod env;
+#[cfg(any(
+    feature = "http",
+    feature = "ws-tokio",
+    feature = "ws-async-std",
+    feature = "ipc-tokio"
+))]
+pub use self::env::EnvTransport;
+
 #[cfg(any(feature = "test", test))]
 pub mod test;
 
@@ -0,0 +1,85 @@
+//! Subscription notification buffers shared by the duplex transports.
+
+use crate::rpc;
+use futures::{
+    channel::mpsc,
+    task::{Context, Poll},
+    SinkExt, Stream,
+};
+use std::pin::Pin;
+
+/// How notifications of a subscription are buffered until the stream is polled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NotificationBuffer {
+    /// Buffer all notifications, however slow the consumer is.
+    #[default]
+    Unbounded,
+    /// Buffer (about) the given number of notifications per subscription.
+    ///
+    /// When the buffer of any subscription is full, the transport stops reading from the
+    /// connection until the consumer catches up, so the node (or the OS socket buffer) holds
+    /// the backlog instead. Note that responses to requests are delayed as well, so the
+    /// subscription must not wait for a request of the same transport while it's not polled.
+    Bounded(usize),
+}
+
+impl NotificationBuffer {
+    pub(crate) fn channel(self) -> (NotificationSender, NotificationStream) {
+        match self {
+            NotificationBuffer::Unbounded => {
+                let (tx, rx) = mpsc::unbounded();
+                (
+                    NotificationSender::Unbounded(tx),
+                    NotificationStream(Receiver::Unbounded(rx)),
+                )
+            }
+            NotificationBuffer::Bounded(capacity) => {
+                let (tx, rx) = mpsc::channel(capacity);
+                (
+                    NotificationSender::Bounded(tx),
+                    NotificationStream(Receiver::Bounded(rx)),
+                )
+            }
+        }
+    }
+}
+
+/// Sending half of a subscription buffer, owned by the transport task.
+#[derive(Debug)]
+pub(crate) enum NotificationSender {
+    Unbounded(mpsc::UnboundedSender<rpc::Value>),
+    Bounded(mpsc::Sender<rpc::Value>),
+}
+
+impl NotificationSender {
+    /// Delivers the notification, waiting for capacity if the buffer is bounded.
+    ///
+    /// Fails if the stream has been dropped.
+    pub(crate) async fn send(&mut self, value: rpc::Value) -> Result<(), mpsc::SendError> {
+        match self {
+            NotificationSender::Unbounded(tx) => tx.unbounded_send(value).map_err(|err| err.into_send_error()),
+            NotificationSender::Bounded(tx) => tx.send(value).await,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Receiver {
+    Unbounded(mpsc::UnboundedReceiver<rpc::Value>),
+    Bounded(mpsc::Receiver<rpc::Value>),
+}
+
+/// Stream of notifications of a single subscription.
+#[derive(Debug)]
+pub struct NotificationStream(Receiver);
+
+impl Stream for NotificationStream {
+    type Item = rpc::Value;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        match self.0 {
+            Receiver::Unbounded(ref mut rx) => Pin::new(rx).poll_next(cx),
+            Receiver::Bounded(ref mut rx) => Pin::new(rx).poll_next(cx),
+        }
+    }
+}
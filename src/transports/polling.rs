@@ -0,0 +1,341 @@
+//! Pseudo-duplex transport emulating subscriptions with filter polling.
+
+use crate::{
+    api::{error_notification, SubscriptionId},
+    error::{self, Error},
+    helpers, rpc, DuplexTransport, RequestId, Transport,
+};
+use futures::{
+    future::{self, BoxFuture, FutureExt},
+    stream::{BoxStream, StreamExt},
+};
+use futures_timer::Delay;
+use parking_lot::Mutex;
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::Arc,
+    time::Duration,
+};
+
+/// A transport serving `eth_subscribe` with filters polled over a request/response transport.
+///
+/// Lets subscription based code (`EthSubscribe`, `wait_for_confirmations_subscribed`) run over
+/// HTTP where WebSocket upgrades are blocked. `newHeads` (`eth_newBlockFilter`, headers fetched
+/// with `eth_getBlockByHash`), `logs` (`eth_newFilter`) and `newPendingTransactions` (hashes only,
+/// `eth_newPendingTransactionFilter`) are supported. Other requests are passed through.
+///
+/// Dropping a `SubscriptionStream` stops polling. Its filter is uninstalled before the next request
+/// sent through the transport, since dropping can't wait for a response; cancel the subscription with
+/// `SubscriptionStream::unsubscribe` to uninstall it right away. When polling fails the stream
+/// yields the error and ends.
+#[derive(Debug, Clone)]
+pub struct Polling<T> {
+    transport: T,
+    poll_interval: Duration,
+    subscriptions: Arc<Mutex<BTreeMap<SubscriptionId, Subscription>>>,
+    /// Filters of dropped subscriptions, waiting to be uninstalled
+    stale: Arc<Mutex<Vec<rpc::Value>>>,
+}
+
+#[derive(Debug, Clone)]
+struct Subscription {
+    filter_id: rpc::Value,
+    kind: Kind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    NewHeads,
+    Logs,
+    PendingTransactions,
+}
+
+impl<T> Polling<T> {
+    /// Wraps given transport, polling filters every `poll_interval`.
+    pub fn new(transport: T, poll_interval: Duration) -> Self {
+        Polling {
+            transport,
+            poll_interval,
+            subscriptions: Default::default(),
+            stale: Default::default(),
+        }
+    }
+
+    /// Returns the underlying transport.
+    pub fn inner(&self) -> &T {
+        &self.transport
+    }
+}
+
+impl<T> Polling<T>
+where
+    T: Transport + Send + Sync + 'static,
+    T::Out: Send + 'static,
+{
+    async fn eth_subscribe(self, params: Vec<rpc::Value>) -> error::Result<rpc::Value> {
+        let unsupported = || {
+            Error::Rpc(rpc::Error::invalid_params(format!(
+                "subscription {:?} is not supported when polling",
+                params
+            )))
+        };
+        let (kind, filter_id) = match (params.first().and_then(rpc::Value::as_str), params.get(1)) {
+            (Some("newHeads"), None) => (
+                Kind::NewHeads,
+                self.transport.execute("eth_newBlockFilter", vec![]).await?,
+            ),
+            (Some("logs"), filter) => {
+                let filter = filter.cloned().unwrap_or_else(|| serde_json::json!({}));
+                (Kind::Logs, self.transport.execute("eth_newFilter", vec![filter]).await?)
+            }
+            (Some("newPendingTransactions"), None) => (
+                Kind::PendingTransactions,
+                self.transport
+                    .execute("eth_newPendingTransactionFilter", vec![])
+                    .await?,
+            ),
+            _ => return Err(unsupported()),
+        };
+        let id: String = helpers::decode(filter_id.clone())?;
+        self.subscriptions
+            .lock()
+            .insert(SubscriptionId::from(id.clone()), Subscription { filter_id, kind });
+        Ok(rpc::Value::String(id))
+    }
+
+    async fn eth_unsubscribe(self, params: Vec<rpc::Value>) -> error::Result<rpc::Value> {
+        let id: String = helpers::decode(params.into_iter().next().unwrap_or_default())?;
+        let subscription = self.subscriptions.lock().remove(&SubscriptionId::from(id));
+        match subscription {
+            Some(subscription) => {
+                self.transport
+                    .execute("eth_uninstallFilter", vec![subscription.filter_id])
+                    .await
+            }
+            None => Ok(rpc::Value::Bool(false)),
+        }
+    }
+
+    /// Uninstalls the filters of dropped subscriptions, the results are ignored.
+    async fn uninstall_stale(self) {
+        let stale = std::mem::take(&mut *self.stale.lock());
+        let uninstalls = stale
+            .into_iter()
+            .map(|filter_id| self.transport.execute("eth_uninstallFilter", vec![filter_id]));
+        future::join_all(uninstalls).await;
+    }
+
+    /// Fetches new notifications of a subscription.
+    async fn changes(&self, subscription: &Subscription) -> error::Result<Vec<rpc::Value>> {
+        let changes = self
+            .transport
+            .execute("eth_getFilterChanges", vec![subscription.filter_id.clone()])
+            .await?;
+        let changes: Vec<rpc::Value> = helpers::decode(changes)?;
+        if subscription.kind != Kind::NewHeads {
+            return Ok(changes);
+        }
+        let mut headers = Vec::with_capacity(changes.len());
+        for hash in changes {
+            let block = self
+                .transport
+                .execute("eth_getBlockByHash", vec![hash, rpc::Value::Bool(false)])
+                .await?;
+            if !block.is_null() {
+                headers.push(block);
+            }
+        }
+        Ok(headers)
+    }
+}
+
+impl<T> Transport for Polling<T>
+where
+    T: Transport + Send + Sync + 'static,
+    T::Out: Send + 'static,
+{
+    type Out = BoxFuture<'static, error::Result<rpc::Value>>;
+
+    fn prepare(&self, method: &str, params: Vec<rpc::Value>) -> (RequestId, rpc::Call) {
+        match method {
+            // handled locally, the id is never sent
+            "eth_subscribe" | "eth_unsubscribe" => (0, helpers::build_request(0, method, params)),
+            _ => self.transport.prepare(method, params),
+        }
+    }
+
+    fn send(&self, id: RequestId, request: rpc::Call) -> Self::Out {
+        let (method, params) = match request {
+            rpc::Call::MethodCall(rpc::MethodCall {
+                ref method,
+                params: rpc::Params::Array(ref params),
+                ..
+            }) => (method.as_str(), params.clone()),
+            _ => ("", vec![]),
+        };
+        let uninstall = self.clone().uninstall_stale();
+        let response = match method {
+            "eth_subscribe" => self.clone().eth_subscribe(params).boxed(),
+            "eth_unsubscribe" => self.clone().eth_unsubscribe(params).boxed(),
+            _ => self.transport.send(id, request).boxed(),
+        };
+        uninstall.then(|_| response).boxed()
+    }
+
+    fn execute_stream<X>(&self, method: &str, params: Vec<rpc::Value>) -> Option<BoxStream<'static, error::Result<X>>>
+    where
+        X: serde::de::DeserializeOwned + Send + 'static,
+    {
+        let stream = self.transport.execute_stream(method, params)?;
+        let uninstall = self.clone().uninstall_stale();
+        Some(uninstall.map(|_| stream).flatten_stream().boxed())
+    }
+}
+
+impl<T> DuplexTransport for Polling<T>
+where
+    T: Transport + Send + Sync + 'static,
+    T::Out: Send + 'static,
+{
+    type NotificationStream = BoxStream<'static, rpc::Value>;
+
+    fn subscribe(&self, id: SubscriptionId) -> error::Result<Self::NotificationStream> {
+        if !self.subscriptions.lock().contains_key(&id) {
+            return Err(Error::Transport(error::TransportError::Message(format!(
+                "unknown subscription {:?}",
+                id
+            ))));
+        }
+        let this = self.clone();
+        let stream = futures::stream::unfold(Some(VecDeque::new()), move |pending| {
+            let this = this.clone();
+            let id = id.clone();
+            async move {
+                // `None` once the error ending the stream has been yielded
+                let mut pending = pending?;
+                loop {
+                    if let Some(notification) = pending.pop_front() {
+                        return Some((notification, Some(pending)));
+                    }
+                    Delay::new(this.poll_interval).await;
+                    // stop once unsubscribed
+                    let subscription = this.subscriptions.lock().get(&id).cloned()?;
+                    match this.changes(&subscription).await {
+                        Ok(changes) => pending.extend(changes),
+                        Err(err) => {
+                            event!(warn, "Polling subscription {:?} failed: {}", id, err);
+                            return Some((error_notification(err), None));
+                        }
+                    }
+                }
+            }
+        });
+        Ok(stream.boxed())
+    }
+
+    fn unsubscribe(&self, id: SubscriptionId) -> error::Result<()> {
+        if let Some(subscription) = self.subscriptions.lock().remove(&id) {
+            self.stale.lock().push(subscription.filter_id);
+        }
+        Ok(())
+    }
+
+    fn cancel_subscription(&self, id: SubscriptionId) -> error::Result<()> {
+        // the filter is uninstalled with the next request
+        self.unsubscribe(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        api::{EthSubscribe, Namespace},
+        transports::test::{self, TestTransport},
+        types::H256,
+    };
+
+    #[test]
+    fn should_poll_new_heads() {
+        let mut transport = TestTransport::default();
+        transport.add_response("0x10".into());
+        transport.add_response(serde_json::json!([H256::from_low_u64_be(2)]));
+        transport.add_response(test::block(2, H256::from_low_u64_be(2), H256::zero(), 0));
+        transport.add_response(rpc::Value::Bool(true));
+        let polling = Polling::new(transport.clone(), Duration::from_millis(0));
+
+        let header = futures::executor::block_on(async {
+            let stream = EthSubscribe::new(polling.clone()).subscribe_new_heads().await.unwrap();
+            let mut stream = stream.take(1);
+            let header = stream.next().await.unwrap().unwrap();
+            assert_eq!(stream.into_inner().unsubscribe().await, Ok(true));
+            header
+        });
+
+        assert_eq!(header.number, Some(2.into()));
+        transport.assert_request("eth_newBlockFilter", &[]);
+        transport.assert_request("eth_getFilterChanges", &[r#""0x10""#.into()]);
+        transport.assert_request(
+            "eth_getBlockByHash",
+            &[format!(r#""{:?}""#, H256::from_low_u64_be(2)), "false".into()],
+        );
+        transport.assert_request("eth_uninstallFilter", &[r#""0x10""#.into()]);
+        transport.assert_no_more_requests();
+        assert!(polling.subscriptions.lock().is_empty());
+    }
+
+    #[test]
+    fn should_uninstall_filter_of_dropped_subscription() {
+        let mut transport = TestTransport::default();
+        transport.add_response("0x10".into());
+        transport.add_response("0x1".into());
+        transport.add_method_response("eth_uninstallFilter", rpc::Value::Bool(true));
+        let polling = Polling::new(transport.clone(), Duration::from_millis(0));
+        let web3 = crate::Web3::new(polling.clone());
+
+        let block_number = futures::executor::block_on(async {
+            let stream = web3.eth_subscribe().subscribe_new_pending_transactions().await.unwrap();
+            drop(stream.keep_alive());
+            web3.eth().block_number().await
+        });
+
+        assert_eq!(block_number, Ok(1.into()));
+        transport.assert_request("eth_newPendingTransactionFilter", &[]);
+        transport.assert_request("eth_blockNumber", &[]);
+        transport.assert_request("eth_uninstallFilter", &[r#""0x10""#.into()]);
+        transport.assert_no_more_requests();
+        assert!(polling.stale.lock().is_empty());
+    }
+
+    #[test]
+    fn should_yield_error_before_ending() {
+        let mut transport = TestTransport::default();
+        transport.add_response("0x10".into());
+        transport.add_error(Error::Rpc(rpc::Error::invalid_params("filter not found")));
+        let polling = Polling::new(transport, Duration::from_millis(0));
+
+        let notifications = futures::executor::block_on(async {
+            let stream = EthSubscribe::new(polling)
+                .subscribe_logs(Default::default())
+                .await
+                .unwrap();
+            stream.collect::<Vec<_>>().await
+        });
+
+        assert_eq!(
+            notifications,
+            vec![Err(Error::Rpc(rpc::Error::invalid_params("filter not found")))]
+        );
+    }
+
+    #[test]
+    fn should_reject_unsupported_subscriptions() {
+        let transport = TestTransport::default();
+        let polling = Polling::new(transport.clone(), Duration::from_millis(0));
+
+        let result = futures::executor::block_on(EthSubscribe::new(polling).subscribe_syncing());
+
+        assert!(matches!(result, Err(Error::Rpc(_))));
+        assert!(transport.requests().is_empty());
+    }
+}
@@ -0,0 +1,394 @@
+//! Multi-endpoint transport with automatic failover.
+
+use crate::{
+    error::{self, Error},
+    helpers::{self, backoff::Backoff},
+    rpc, BatchTransport, RequestId, Transport,
+};
+use futures::{
+    future::{join_all, BoxFuture, FutureExt},
+    stream::{BoxStream, StreamExt},
+};
+use futures_timer::Delay;
+use parking_lot::Mutex;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+type BatchResult = error::Result<Vec<error::Result<rpc::Value>>>;
+
+/// A transport routing requests over an ordered list of endpoints.
+///
+/// Requests go to the first healthy endpoint in the list. When an endpoint fails
+/// (transport or I/O errors, not RPC errors), the request is retried on the next one and the
+/// endpoint is deprioritized for a delay of the `Backoff` (growing with consecutive failures),
+/// after which it gets requests again. Endpoints lagging more than
+/// `max_block_lag` blocks behind the best known head are considered stale; heads are updated
+/// with `eth_blockNumber` probes by `check_health`, which should be run periodically.
+///
+/// Streamed responses (`Transport::execute_stream`) go to the first healthy endpoint without
+/// failover.
+///
+/// To mix different transport types (e.g. WS primary with HTTP backups) wrap them in `Either`.
+#[derive(Debug, Clone)]
+pub struct FallbackTransport<T> {
+    transports: Arc<Vec<T>>,
+    health: Arc<Mutex<Vec<Health>>>,
+    max_block_lag: u64,
+    backoff: Backoff,
+    id: Arc<AtomicUsize>,
+}
+
+#[derive(Debug, Default)]
+struct Health {
+    failures: u32,
+    block_number: Option<u64>,
+    cooldown: Option<Delay>,
+}
+
+impl Health {
+    fn succeeded(&mut self) {
+        self.failures = 0;
+        self.cooldown = None;
+    }
+
+    fn failed(&mut self, backoff: &Backoff) {
+        self.failures = self.failures.saturating_add(1);
+        let delay = backoff.delays().nth(self.failures as usize - 1).unwrap_or(backoff.cap);
+        self.cooldown = Some(Delay::new(delay));
+    }
+
+    /// Whether the endpoint recently failed and its cooldown didn't elapse yet.
+    fn cooling_down(&mut self) -> bool {
+        let elapsed = match self.cooldown.as_mut() {
+            Some(cooldown) => cooldown.now_or_never().is_some(),
+            None => return false,
+        };
+        if elapsed {
+            self.cooldown = None;
+        }
+        !elapsed
+    }
+}
+
+impl<T> FallbackTransport<T> {
+    /// Creates a new transport from endpoints ordered by priority.
+    ///
+    /// Panics if `transports` is empty.
+    pub fn new(transports: Vec<T>) -> Self {
+        assert!(!transports.is_empty(), "at least one transport is required");
+        let health = transports.iter().map(|_| Health::default()).collect();
+        FallbackTransport {
+            transports: Arc::new(transports),
+            health: Arc::new(Mutex::new(health)),
+            max_block_lag: 5,
+            backoff: Backoff::exponential(Duration::from_secs(1), Duration::from_secs(60)),
+            id: Default::default(),
+        }
+    }
+
+    /// Sets the delays a failed endpoint is deprioritized for (default: doubling from 1s up to 60s).
+    pub fn with_backoff<B: Into<Backoff>>(mut self, backoff: B) -> Self {
+        self.backoff = backoff.into();
+        self
+    }
+
+    /// Sets how many blocks an endpoint can lag behind the best head before being considered stale (default: 5).
+    pub fn max_block_lag(mut self, max_block_lag: u64) -> Self {
+        self.max_block_lag = max_block_lag;
+        self
+    }
+
+    /// Returns the underlying transports.
+    pub fn transports(&self) -> &[T] {
+        &self.transports
+    }
+
+    /// Returns indices of the endpoints in the order they should be tried.
+    fn order(&self) -> Vec<usize> {
+        let mut health = self.health.lock();
+        let head = health.iter().filter_map(|h| h.block_number).max();
+        let keys: Vec<_> = health
+            .iter_mut()
+            .map(|h| {
+                let stale = match (head, h.block_number) {
+                    (Some(head), Some(number)) => head.saturating_sub(number) > self.max_block_lag,
+                    (Some(_), None) => true,
+                    _ => false,
+                };
+                if h.cooling_down() || stale {
+                    (true, h.failures)
+                } else {
+                    (false, 0)
+                }
+            })
+            .collect();
+
+        let mut order: Vec<_> = (0..health.len()).collect();
+        order.sort_by_key(|&idx| (keys[idx], idx));
+        order
+    }
+
+    fn report(&self, idx: usize, success: bool) {
+        let mut health = self.health.lock();
+        if success {
+            health[idx].succeeded();
+        } else {
+            health[idx].failed(&self.backoff);
+        }
+    }
+}
+
+impl<T: Transport> FallbackTransport<T> {
+    /// Probes all endpoints with `eth_blockNumber` and updates their health.
+    pub async fn check_health(&self) {
+        let probes = self
+            .transports
+            .iter()
+            .map(|transport| transport.execute("eth_blockNumber", vec![]));
+        let results = join_all(probes).await;
+
+        let mut health = self.health.lock();
+        for (health, result) in health.iter_mut().zip(results) {
+            match result.and_then(helpers::decode::<crate::types::U64>) {
+                Ok(number) => {
+                    health.succeeded();
+                    health.block_number = Some(number.as_u64());
+                }
+                Err(_) => health.failed(&self.backoff),
+            }
+        }
+    }
+}
+
+/// Whether the error indicates a problem with the endpoint rather than with the request.
+pub(crate) fn is_endpoint_failure(err: &Error) -> bool {
+    matches!(
+        err,
+        Error::Unreachable | Error::Transport(_) | Error::Io(_) | Error::InvalidResponse(_) | Error::WrongChain { .. }
+    )
+}
+
+/// Prepares the call again with given transport, so that it gets an id valid for that transport.
+fn reprepare<T: Transport>(transport: &T, id: RequestId, call: &rpc::Call) -> (RequestId, rpc::Call) {
+    match call {
+        rpc::Call::MethodCall(rpc::MethodCall {
+            method,
+            params: rpc::Params::Array(params),
+            ..
+        }) => transport.prepare(method, params.clone()),
+        rpc::Call::MethodCall(rpc::MethodCall {
+            method,
+            params: rpc::Params::None,
+            ..
+        }) => transport.prepare(method, vec![]),
+        _ => (id, call.clone()),
+    }
+}
+
+impl<T> Transport for FallbackTransport<T>
+where
+    T: Transport + Send + Sync + 'static,
+    T::Out: Send + 'static,
+{
+    type Out = BoxFuture<'static, error::Result<rpc::Value>>;
+
+    fn prepare(&self, method: &str, params: Vec<rpc::Value>) -> (RequestId, rpc::Call) {
+        let id = self.id.fetch_add(1, Ordering::AcqRel);
+        (id, helpers::build_request(id, method, params))
+    }
+
+    fn send(&self, id: RequestId, request: rpc::Call) -> Self::Out {
+        let this = self.clone();
+        async move {
+            let mut last_error = Error::Unreachable;
+            for idx in this.order() {
+                let transport = &this.transports[idx];
+                let (id, request) = reprepare(transport, id, &request);
+                match transport.send(id, request).await {
+                    Err(err) if is_endpoint_failure(&err) => {
+                        this.report(idx, false);
+                        last_error = err;
+                    }
+                    result => {
+                        this.report(idx, true);
+                        return result;
+                    }
+                }
+            }
+            Err(last_error)
+        }
+        .boxed()
+    }
+
+    fn execute_stream<X>(&self, method: &str, params: Vec<rpc::Value>) -> Option<BoxStream<'static, error::Result<X>>>
+    where
+        X: serde::de::DeserializeOwned + Send + 'static,
+    {
+        // a partially received response can't be resumed on another endpoint, so the stream
+        // isn't failed over
+        let idx = self.order()[0];
+        let stream = self.transports[idx].execute_stream(method, params)?;
+        let this = self.clone();
+        let mut reported = false;
+        let stream = stream.inspect(move |item| {
+            if !reported {
+                reported = true;
+                this.report(idx, !matches!(item, Err(err) if is_endpoint_failure(err)));
+            }
+        });
+        Some(stream.boxed())
+    }
+}
+
+impl<T> BatchTransport for FallbackTransport<T>
+where
+    T: BatchTransport + Send + Sync + 'static,
+    T::Out: Send + 'static,
+    T::Batch: Send + 'static,
+{
+    type Batch = BoxFuture<'static, BatchResult>;
+
+    fn send_batch<I>(&self, requests: I) -> Self::Batch
+    where
+        I: IntoIterator<Item = (RequestId, rpc::Call)>,
+    {
+        let this = self.clone();
+        let requests: Vec<_> = requests.into_iter().collect();
+        async move {
+            let mut last_error = Error::Unreachable;
+            for idx in this.order() {
+                let transport = &this.transports[idx];
+                let requests: Vec<_> = requests
+                    .iter()
+                    .map(|(id, request)| reprepare(transport, *id, request))
+                    .collect();
+                match transport.send_batch(requests).await {
+                    Err(err) if is_endpoint_failure(&err) => {
+                        this.report(idx, false);
+                        last_error = err;
+                    }
+                    result => {
+                        this.report(idx, true);
+                        return result;
+                    }
+                }
+            }
+            Err(last_error)
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::TransportError;
+    use futures::future;
+    use std::collections::VecDeque;
+
+    #[derive(Debug, Clone, Default)]
+    struct MockTransport {
+        requests: Arc<Mutex<Vec<String>>>,
+        responses: Arc<Mutex<VecDeque<error::Result<rpc::Value>>>>,
+    }
+
+    impl MockTransport {
+        fn with_responses(responses: Vec<error::Result<rpc::Value>>) -> Self {
+            MockTransport {
+                requests: Default::default(),
+                responses: Arc::new(Mutex::new(responses.into())),
+            }
+        }
+
+        fn requests(&self) -> Vec<String> {
+            self.requests.lock().clone()
+        }
+    }
+
+    impl Transport for MockTransport {
+        type Out = BoxFuture<'static, error::Result<rpc::Value>>;
+
+        fn prepare(&self, method: &str, params: Vec<rpc::Value>) -> (RequestId, rpc::Call) {
+            self.requests.lock().push(method.into());
+            (1, helpers::build_request(1, method, params))
+        }
+
+        fn send(&self, _id: RequestId, _request: rpc::Call) -> Self::Out {
+            let response = self.responses.lock().pop_front().unwrap_or(Err(Error::Unreachable));
+            future::ready(response).boxed()
+        }
+    }
+
+    fn transport_error() -> error::Result<rpc::Value> {
+        Err(Error::Transport(TransportError::Code(502)))
+    }
+
+    #[test]
+    fn should_fail_over_to_next_endpoint() {
+        let primary = MockTransport::with_responses(vec![transport_error(), Ok(rpc::Value::Bool(true))]);
+        let backup = MockTransport::with_responses(vec![Ok(rpc::Value::Bool(false))]);
+        let pool = FallbackTransport::new(vec![primary.clone(), backup.clone()]);
+
+        let result = futures::executor::block_on(pool.execute("eth_syncing", vec![]));
+        assert_eq!(result, Ok(rpc::Value::Bool(false)));
+        assert_eq!(primary.requests(), vec!["eth_syncing".to_owned()]);
+        assert_eq!(backup.requests(), vec!["eth_syncing".to_owned()]);
+
+        // primary is deprioritized after the failure
+        assert_eq!(pool.order(), vec![1, 0]);
+    }
+
+    #[test]
+    fn should_retry_failed_endpoint_after_backoff() {
+        let primary = MockTransport::with_responses(vec![transport_error(), Ok(rpc::Value::Bool(true))]);
+        let backup = MockTransport::with_responses(vec![Ok(rpc::Value::Bool(false))]);
+        let pool = FallbackTransport::new(vec![primary.clone(), backup]).with_backoff(Duration::from_millis(20));
+
+        futures::executor::block_on(pool.execute("eth_syncing", vec![])).unwrap();
+        assert_eq!(pool.order(), vec![1, 0]);
+
+        std::thread::sleep(Duration::from_millis(50));
+        assert_eq!(pool.order(), vec![0, 1]);
+        let result = futures::executor::block_on(pool.execute("eth_syncing", vec![]));
+        assert_eq!(result, Ok(rpc::Value::Bool(true)));
+        assert_eq!(primary.requests().len(), 2);
+    }
+
+    #[test]
+    fn should_not_fail_over_on_rpc_error() {
+        let rpc_error = Err(Error::Rpc(rpc::Error::method_not_found()));
+        let primary = MockTransport::with_responses(vec![rpc_error.clone()]);
+        let backup = MockTransport::default();
+        let pool = FallbackTransport::new(vec![primary, backup.clone()]);
+
+        let result = futures::executor::block_on(pool.execute("eth_foo", vec![]));
+        assert_eq!(result, rpc_error);
+        assert!(backup.requests().is_empty());
+    }
+
+    #[test]
+    fn should_return_last_error_if_all_fail() {
+        let pool = FallbackTransport::new(vec![
+            MockTransport::with_responses(vec![transport_error()]),
+            MockTransport::with_responses(vec![Err(Error::Unreachable)]),
+        ]);
+
+        let result = futures::executor::block_on(pool.execute("eth_syncing", vec![]));
+        assert_eq!(result, Err(Error::Unreachable));
+    }
+
+    #[test]
+    fn should_deprioritize_stale_endpoints() {
+        let primary = MockTransport::with_responses(vec![Ok(rpc::Value::String("0x10".into()))]);
+        let backup = MockTransport::with_responses(vec![Ok(rpc::Value::String("0x20".into()))]);
+        let pool = FallbackTransport::new(vec![primary, backup]).max_block_lag(2);
+
+        futures::executor::block_on(pool.check_health());
+        assert_eq!(pool.order(), vec![1, 0]);
+    }
+}
@@ -0,0 +1,78 @@
+//! JSON-RPC ids of requests sent by a transport.
+
+use crate::{helpers, rpc, RequestId};
+
+/// How the JSON-RPC id of a request is derived from the transport's request counter.
+///
+/// Numeric ids of different clients sharing a (multiplexing) proxy can collide, use `Strided`
+/// or `Prefixed` ids to keep them apart.
+///
+/// # Request counter
+///
+/// Every transport counts the requests it prepares. With the `test-support` feature the
+/// counter can be moved with `set_next_request_id`, e.g. to get stable ids in recorded fixtures.
+/// Ids must not be reused while requests with the same id are pending. Wrapping transports
+/// (like `FallbackTransport`) send requests with the ids of the transports they wrap, so it's
+/// those that have to be seeded.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum IdStrategy {
+    /// The counter as a number.
+    #[default]
+    Sequential,
+    /// `offset + counter * stride` as a number, e.g. `offset` set to the index of the client
+    /// and `stride` to the number of clients.
+    Strided {
+        /// Added to every id
+        offset: u64,
+        /// Multiplier of the counter
+        stride: u64,
+    },
+    /// The counter as a string, following the prefix (e.g. a UUID generated per process).
+    Prefixed(String),
+}
+
+impl IdStrategy {
+    /// JSON-RPC id of the request with given counter value.
+    pub fn id(&self, id: RequestId) -> rpc::Id {
+        match self {
+            IdStrategy::Sequential => rpc::Id::Num(id as u64),
+            IdStrategy::Strided { offset, stride } => {
+                rpc::Id::Num(offset.wrapping_add((id as u64).wrapping_mul(*stride)))
+            }
+            IdStrategy::Prefixed(prefix) => rpc::Id::Str(format!("{}{}", prefix, id)),
+        }
+    }
+
+    /// Builds a JSON-RPC request with the id of given counter value.
+    pub fn build_request(&self, id: RequestId, method: &str, params: Vec<rpc::Value>) -> rpc::Call {
+        let mut call = helpers::build_request(id, method, params);
+        if let rpc::Call::MethodCall(ref mut call) = call {
+            call.id = self.id(id);
+        }
+        call
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_derive_ids() {
+        assert_eq!(IdStrategy::Sequential.id(5), rpc::Id::Num(5));
+        assert_eq!(IdStrategy::Strided { offset: 2, stride: 3 }.id(5), rpc::Id::Num(17));
+        assert_eq!(
+            IdStrategy::Prefixed("a2f4-".into()).id(5),
+            rpc::Id::Str("a2f4-5".into())
+        );
+        assert_eq!(
+            IdStrategy::Prefixed("p".into()).build_request(7, "eth_blockNumber", vec![]),
+            rpc::Call::MethodCall(rpc::MethodCall {
+                jsonrpc: Some(rpc::Version::V2),
+                method: "eth_blockNumber".into(),
+                params: rpc::Params::Array(vec![]),
+                id: rpc::Id::Str("p7".into()),
+            })
+        );
+    }
+}
@@ -0,0 +1,225 @@
+//! Transport wrapper retrying requests the endpoint failed.
+
+use crate::{
+    api, error,
+    helpers::backoff::{Backoff, Delays},
+    rpc,
+    transports::{pool::is_endpoint_failure, ConnectionEvent},
+    BatchTransport, DuplexTransport, RequestId, Transport,
+};
+use futures::{
+    task::{Context, Poll},
+    Future,
+};
+use futures_timer::Delay;
+use std::{iter::Take, pin::Pin};
+
+/// A transport wrapper retrying requests which fail because of the endpoint (transport or I/O
+/// errors, including timeouts, not RPC errors), waiting according to a `Backoff` between attempts.
+///
+/// Requests are sent again with the same id, batches are retried as a whole. Subscriptions and
+/// streamed responses (`Transport::execute_stream`) are not retried. Note that requests with side effects (e.g. `eth_sendRawTransaction`) may reach the node
+/// more than once.
+///
+/// ```no_run
+/// # fn example() -> web3::Result<()> {
+/// use std::time::Duration;
+///
+/// let http = web3::transports::Http::new("http://localhost:8545")?.with_timeout(Duration::from_secs(10));
+/// let web3 = web3::Web3::new(web3::transports::Retry::new(http, 3, Duration::from_millis(500)));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Retry<T> {
+    transport: T,
+    max_retries: usize,
+    backoff: Backoff,
+}
+
+impl<T> Retry<T> {
+    /// Wraps given transport, retrying failed requests up to `max_retries` times.
+    pub fn new<B: Into<Backoff>>(transport: T, max_retries: usize, backoff: B) -> Self {
+        Retry {
+            transport,
+            max_retries,
+            backoff: backoff.into(),
+        }
+    }
+
+    /// Borrows the inner transport.
+    pub fn inner(&self) -> &T {
+        &self.transport
+    }
+}
+
+/// Future of a request sent by `Retry`, resolving once an attempt succeeds, fails with an error
+/// not caused by the endpoint, or the retries run out.
+pub struct Retrying<T, R, F> {
+    transport: T,
+    request: R,
+    send: fn(&T, &R) -> F,
+    attempt: Pin<Box<F>>,
+    delay: Option<Delay>,
+    delays: Take<Delays>,
+}
+
+// The transport and the request are never pinned.
+impl<T, R, F> Unpin for Retrying<T, R, F> {}
+
+impl<T, R, F> Retrying<T, R, F> {
+    fn new(retry: &Retry<T>, request: R, send: fn(&T, &R) -> F) -> Self
+    where
+        T: Clone,
+    {
+        let transport = retry.transport.clone();
+        let attempt = Box::pin(send(&transport, &request));
+        Retrying {
+            transport,
+            request,
+            send,
+            attempt,
+            delay: None,
+            delays: retry.backoff.delays().take(retry.max_retries),
+        }
+    }
+}
+
+impl<T, R, F, V> Future for Retrying<T, R, F>
+where
+    F: Future<Output = error::Result<V>>,
+{
+    type Output = error::Result<V>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = &mut *self;
+        loop {
+            if let Some(delay) = this.delay.as_mut() {
+                ready!(Pin::new(delay).poll(cx));
+                this.delay = None;
+                this.attempt = Box::pin((this.send)(&this.transport, &this.request));
+            }
+            match ready!(this.attempt.as_mut().poll(cx)) {
+                Err(err) if is_endpoint_failure(&err) => match this.delays.next() {
+                    Some(delay) => {
+                        event!(debug, "Request failed ({}), retrying in {:?}", err, delay);
+                        this.delay = Some(Delay::new(delay));
+                    }
+                    None => return Poll::Ready(Err(err)),
+                },
+                result => return Poll::Ready(result),
+            }
+        }
+    }
+}
+
+impl<T: Transport> Transport for Retry<T> {
+    type Out = Retrying<T, (RequestId, rpc::Call), T::Out>;
+
+    fn prepare(&self, method: &str, params: Vec<rpc::Value>) -> (RequestId, rpc::Call) {
+        self.transport.prepare(method, params)
+    }
+
+    fn send(&self, id: RequestId, request: rpc::Call) -> Self::Out {
+        Retrying::new(self, (id, request), |transport, (id, request)| {
+            transport.send(*id, request.clone())
+        })
+    }
+
+    fn execute_stream<X>(
+        &self,
+        method: &str,
+        params: Vec<rpc::Value>,
+    ) -> Option<futures::stream::BoxStream<'static, error::Result<X>>>
+    where
+        X: serde::de::DeserializeOwned + Send + 'static,
+    {
+        self.transport.execute_stream(method, params)
+    }
+}
+
+impl<T: BatchTransport> BatchTransport for Retry<T> {
+    type Batch = Retrying<T, Vec<(RequestId, rpc::Call)>, T::Batch>;
+
+    fn send_batch<I>(&self, requests: I) -> Self::Batch
+    where
+        I: IntoIterator<Item = (RequestId, rpc::Call)>,
+    {
+        Retrying::new(self, requests.into_iter().collect(), |transport, requests| {
+            transport.send_batch(requests.clone())
+        })
+    }
+}
+
+impl<T: DuplexTransport> DuplexTransport for Retry<T> {
+    type NotificationStream = T::NotificationStream;
+
+    fn subscribe(&self, id: api::SubscriptionId) -> error::Result<Self::NotificationStream> {
+        self.transport.subscribe(id)
+    }
+
+    fn unsubscribe(&self, id: api::SubscriptionId) -> error::Result {
+        self.transport.unsubscribe(id)
+    }
+
+    fn cancel_subscription(&self, id: api::SubscriptionId) -> error::Result {
+        self.transport.cancel_subscription(id)
+    }
+
+    fn connection_events(&self) -> futures::stream::BoxStream<'static, ConnectionEvent> {
+        self.transport.connection_events()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        error::{Error, TransportError},
+        transports::test::TestTransport,
+    };
+    use std::time::Duration;
+
+    #[test]
+    fn should_retry_endpoint_failures() {
+        let mut transport = TestTransport::default();
+        transport.add_error(Error::Transport(TransportError::Timeout));
+        transport.add_error(Error::Unreachable);
+        transport.add_response(rpc::Value::Bool(true));
+        let retry = Retry::new(transport.clone(), 2, Duration::from_millis(0));
+
+        let result = futures::executor::block_on(retry.execute("eth_syncing", vec![]));
+
+        assert_eq!(result, Ok(rpc::Value::Bool(true)));
+    }
+
+    #[test]
+    fn should_give_up_after_max_retries() {
+        let mut transport = TestTransport::default();
+        transport.add_error(Error::Unreachable);
+        transport.add_error(Error::Unreachable);
+        transport.add_response(rpc::Value::Bool(true));
+        let retry = Retry::new(transport.clone(), 1, Duration::from_millis(0));
+
+        let result = futures::executor::block_on(retry.execute("eth_syncing", vec![]));
+
+        assert_eq!(result, Err(Error::Unreachable));
+        // the response to a third attempt is left
+        let next = futures::executor::block_on(transport.execute("eth_syncing", vec![]));
+        assert_eq!(next, Ok(rpc::Value::Bool(true)));
+    }
+
+    #[test]
+    fn should_not_retry_rpc_errors() {
+        let mut transport = TestTransport::default();
+        transport.add_error(Error::Rpc(rpc::Error::method_not_found()));
+        transport.add_response(rpc::Value::Bool(true));
+        let retry = Retry::new(transport.clone(), 3, Duration::from_millis(0));
+
+        let result = futures::executor::block_on(retry.execute("eth_syncing", vec![]));
+
+        assert_eq!(result, Err(Error::Rpc(rpc::Error::method_not_found())));
+        let next = futures::executor::block_on(transport.execute("eth_syncing", vec![]));
+        assert_eq!(next, Ok(rpc::Value::Bool(true)));
+    }
+}
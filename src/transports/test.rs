@@ -1,20 +1,34 @@
 //! Test Transport
 
 use crate::{
+    api::SubscriptionId,
     error::{self, Error},
-    helpers, rpc, RequestId, Transport,
+    helpers, rpc,
+    types::{Address, H2048, H256, U64},
+    DuplexTransport, RequestId, Transport,
 };
 use futures::future::{self, BoxFuture, FutureExt};
-use std::{cell::RefCell, collections::VecDeque, rc::Rc};
+use futures_timer::Delay;
+use parking_lot::Mutex;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+    time::Duration,
+};
 
 type Result<T> = BoxFuture<'static, error::Result<T>>;
 
 /// Test Transport
+///
+/// Responses scripted for a method (`add_method_response`) are returned to requests of that
+/// method, all other requests get the responses queued with `add_response` in order.
 #[derive(Debug, Default, Clone)]
 pub struct TestTransport {
     asserted: usize,
-    requests: Rc<RefCell<Vec<(String, Vec<rpc::Value>)>>>,
-    responses: Rc<RefCell<VecDeque<rpc::Value>>>,
+    requests: Arc<Mutex<Vec<(String, Vec<rpc::Value>)>>>,
+    responses: Arc<Mutex<VecDeque<error::Result<rpc::Value>>>>,
+    method_responses: Arc<Mutex<HashMap<String, VecDeque<error::Result<rpc::Value>>>>>,
+    latency: Arc<Mutex<Option<Duration>>>,
 }
 
 impl Transport for TestTransport {
@@ -22,31 +36,75 @@ impl Transport for TestTransport {
 
     fn prepare(&self, method: &str, params: Vec<rpc::Value>) -> (RequestId, rpc::Call) {
         let request = helpers::build_request(1, method, params.clone());
-        self.requests.borrow_mut().push((method.into(), params));
-        (self.requests.borrow().len(), request)
+        self.requests.lock().push((method.into(), params));
+        (self.requests.lock().len(), request)
     }
 
     fn send(&self, id: RequestId, request: rpc::Call) -> Result<rpc::Value> {
-        future::ready(match self.responses.borrow_mut().pop_front() {
-            Some(response) => Ok(response),
+        let scripted = match request {
+            rpc::Call::MethodCall(ref call) => self
+                .method_responses
+                .lock()
+                .get_mut(&call.method)
+                .and_then(VecDeque::pop_front),
+            _ => None,
+        };
+        let response = match scripted.or_else(|| self.responses.lock().pop_front()) {
+            Some(response) => response,
             None => {
                 println!("Unexpected request (id: {:?}): {:?}", id, request);
                 Err(Error::Unreachable)
             }
-        })
-        .boxed()
+        };
+        match *self.latency.lock() {
+            Some(latency) => Delay::new(latency).map(move |_| response).boxed(),
+            None => future::ready(response).boxed(),
+        }
     }
 }
 
 impl TestTransport {
     /// Set response
     pub fn set_response(&mut self, value: rpc::Value) {
-        *self.responses.borrow_mut() = vec![value].into();
+        *self.responses.lock() = vec![Ok(value)].into();
     }
 
     /// Add response
     pub fn add_response(&mut self, value: rpc::Value) {
-        self.responses.borrow_mut().push_back(value);
+        self.responses.lock().push_back(Ok(value));
+    }
+
+    /// Add error response
+    pub fn add_error(&mut self, error: Error) {
+        self.responses.lock().push_back(Err(error));
+    }
+
+    /// Add response to the next request of given method
+    pub fn add_method_response(&mut self, method: &str, value: rpc::Value) {
+        self.method_responses
+            .lock()
+            .entry(method.into())
+            .or_default()
+            .push_back(Ok(value));
+    }
+
+    /// Add error response to the next request of given method
+    pub fn add_method_error(&mut self, method: &str, error: Error) {
+        self.method_responses
+            .lock()
+            .entry(method.into())
+            .or_default()
+            .push_back(Err(error));
+    }
+
+    /// Delays all subsequent responses by given duration
+    pub fn set_latency(&mut self, latency: Duration) {
+        *self.latency.lock() = Some(latency);
+    }
+
+    /// Returns all requests made so far (method and params).
+    pub fn requests(&self) -> Vec<(String, Vec<rpc::Value>)> {
+        self.requests.lock().clone()
     }
 
     /// Assert request
@@ -54,15 +112,35 @@ impl TestTransport {
         let idx = self.asserted;
         self.asserted += 1;
 
-        let (m, p) = self.requests.borrow().get(idx).expect("Expected result.").clone();
+        let (m, p) = self.requests.lock().get(idx).expect("Expected result.").clone();
         assert_eq!(&m, method);
         let p: Vec<String> = p.into_iter().map(|p| serde_json::to_string(&p).unwrap()).collect();
         assert_eq!(p, params);
     }
 
+    /// Assert request, comparing params as JSON values (ignoring key order of objects)
+    pub fn assert_request_json(&mut self, method: &str, params: &[rpc::Value]) {
+        let idx = self.asserted;
+        self.asserted += 1;
+
+        let (m, p) = self.requests.lock().get(idx).expect("Expected result.").clone();
+        assert_eq!(&m, method);
+        assert_eq!(p, params);
+    }
+
+    /// Returns params of all requests of given method made so far
+    pub fn requests_of(&self, method: &str) -> Vec<Vec<rpc::Value>> {
+        self.requests
+            .lock()
+            .iter()
+            .filter(|(m, _)| m == method)
+            .map(|(_, p)| p.clone())
+            .collect()
+    }
+
     /// Assert no more requests
     pub fn assert_no_more_requests(&self) {
-        let requests = self.requests.borrow();
+        let requests = self.requests.lock();
         assert_eq!(
             self.asserted,
             requests.len(),
@@ -71,3 +149,111 @@ impl TestTransport {
         );
     }
 }
+
+/// Duplex Test Transport
+///
+/// Requests are answered by the inner `TestTransport`, every subscription yields the notifications
+/// set with `set_notifications`.
+#[derive(Debug, Default, Clone)]
+pub struct DuplexTestTransport {
+    /// Transport answering the requests
+    pub inner: TestTransport,
+    notifications: Arc<Mutex<Vec<rpc::Value>>>,
+}
+
+impl DuplexTestTransport {
+    /// Set notifications yielded by subsequent subscriptions
+    pub fn set_notifications(&mut self, notifications: Vec<rpc::Value>) {
+        *self.notifications.lock() = notifications;
+    }
+}
+
+impl Transport for DuplexTestTransport {
+    type Out = Result<rpc::Value>;
+
+    fn prepare(&self, method: &str, params: Vec<rpc::Value>) -> (RequestId, rpc::Call) {
+        self.inner.prepare(method, params)
+    }
+
+    fn send(&self, id: RequestId, request: rpc::Call) -> Self::Out {
+        self.inner.send(id, request)
+    }
+}
+
+impl DuplexTransport for DuplexTestTransport {
+    type NotificationStream = futures::stream::Iter<std::vec::IntoIter<rpc::Value>>;
+
+    fn subscribe(&self, _id: SubscriptionId) -> error::Result<Self::NotificationStream> {
+        Ok(futures::stream::iter(self.notifications.lock().clone()))
+    }
+
+    fn unsubscribe(&self, _id: SubscriptionId) -> error::Result {
+        Ok(())
+    }
+}
+
+/// Block without transactions as returned by `eth_getBlockByNumber`, also usable as a `newHeads`
+/// notification.
+pub fn block(number: u64, hash: H256, parent_hash: H256, timestamp: u64) -> rpc::Value {
+    serde_json::json!({
+        "number": U64::from(number),
+        "hash": hash,
+        "parentHash": parent_hash,
+        "sha3Uncles": H256::zero(),
+        "miner": Address::zero(),
+        "stateRoot": H256::zero(),
+        "transactionsRoot": H256::zero(),
+        "receiptsRoot": H256::zero(),
+        "logsBloom": H2048::zero(),
+        "gasUsed": "0x0",
+        "gasLimit": "0x0",
+        "extraData": "0x",
+        "timestamp": U64::from(timestamp),
+        "difficulty": "0x0",
+        "uncles": [],
+        "transactions": [],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn should_respond_by_method() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.add_response(json!("0x1"));
+        transport.add_method_response("eth_chainId", json!("0x5"));
+        transport.add_method_error("eth_call", Error::Revert("nope".into()));
+
+        // when
+        let call = futures::executor::block_on(transport.execute("eth_call", vec![json!({"to": "0x1", "data": "0x"})]));
+        let block_number = futures::executor::block_on(transport.execute("eth_blockNumber", vec![]));
+        let chain_id = futures::executor::block_on(transport.execute("eth_chainId", vec![]));
+
+        // then
+        assert_eq!(call, Err(Error::Revert("nope".into())));
+        assert_eq!(block_number, Ok(json!("0x1")));
+        assert_eq!(chain_id, Ok(json!("0x5")));
+        transport.assert_request_json("eth_call", &[json!({"data": "0x", "to": "0x1"})]);
+        transport.assert_request("eth_blockNumber", &[]);
+        transport.assert_request_json("eth_chainId", &[]);
+        transport.assert_no_more_requests();
+        assert_eq!(transport.requests_of("eth_chainId"), vec![Vec::<rpc::Value>::new()]);
+    }
+
+    #[test]
+    fn should_delay_responses() {
+        let mut transport = TestTransport::default();
+        transport.set_latency(Duration::from_millis(20));
+        transport.add_response(json!(true));
+
+        let started = std::time::Instant::now();
+        let result = futures::executor::block_on(transport.execute("eth_syncing", vec![]));
+
+        assert_eq!(result, Ok(json!(true)));
+        assert!(started.elapsed() >= Duration::from_millis(20));
+    }
+}
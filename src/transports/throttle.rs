@@ -0,0 +1,308 @@
+//! Rate limiting transport wrapper.
+
+use crate::{api, error, rpc, transports::ConnectionEvent, BatchTransport, DuplexTransport, RequestId, Transport};
+use futures::{
+    stream::{BoxStream, StreamExt},
+    task::{Context, Poll},
+    Future, FutureExt,
+};
+use futures_timer::Delay;
+use parking_lot::Mutex;
+use std::{
+    pin::Pin,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// A transport wrapper enforcing a token-bucket rate limit on outgoing requests.
+///
+/// Requests above the limit are queued (delayed) instead of being sent and rejected
+/// by the provider. Every call of a batch consumes one token, requests dropped before being sent
+/// give their tokens back. Subscriptions are not throttled.
+///
+/// ```no_run
+/// # fn example() -> web3::Result<()> {
+/// let http = web3::transports::Http::new("http://localhost:8545")?;
+/// // At most 10 requests per second, with bursts of up to 20 requests.
+/// let web3 = web3::Web3::new(web3::transports::Throttled::new(http, 10.0, 20));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Throttled<T> {
+    transport: T,
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+impl<T> Throttled<T> {
+    /// Wraps given transport allowing `requests_per_second` on average with bursts of `burst` requests.
+    ///
+    /// Panics if `requests_per_second` is not positive or `burst` is zero.
+    pub fn new(transport: T, requests_per_second: f64, burst: u32) -> Self {
+        assert!(requests_per_second > 0.0, "requests_per_second must be positive");
+        assert!(burst > 0, "burst must be at least 1");
+        Throttled {
+            transport,
+            bucket: Arc::new(Mutex::new(Bucket {
+                per_second: requests_per_second,
+                burst: burst as f64,
+                tokens: burst as f64,
+                updated: Instant::now(),
+            })),
+        }
+    }
+
+    /// Borrows the inner transport.
+    pub fn inner(&self) -> &T {
+        &self.transport
+    }
+
+    fn reserve(&self, requests: usize) -> (Option<Delay>, Reservation) {
+        let delay = self.bucket.lock().reserve(requests).map(Delay::new);
+        let reservation = Reservation {
+            bucket: self.bucket.clone(),
+            requests,
+        };
+        (delay, reservation)
+    }
+}
+
+/// Tokens taken for a request which wasn't sent yet, given back if it's dropped.
+struct Reservation {
+    bucket: Arc<Mutex<Bucket>>,
+    requests: usize,
+}
+
+impl Reservation {
+    /// Marks the tokens as used by a sent request.
+    fn commit(mut self) {
+        self.requests = 0;
+    }
+}
+
+impl Drop for Reservation {
+    fn drop(&mut self) {
+        if self.requests > 0 {
+            self.bucket.lock().release(self.requests);
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    per_second: f64,
+    burst: f64,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    /// Takes `requests` tokens and returns how long the caller has to wait for them.
+    ///
+    /// Tokens may go negative, so that concurrent callers are served in order.
+    fn reserve(&mut self, requests: usize) -> Option<Duration> {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.per_second).min(self.burst) - requests as f64;
+        self.updated = now;
+
+        if self.tokens >= 0.0 {
+            None
+        } else {
+            Some(Duration::from_secs_f64(-self.tokens / self.per_second))
+        }
+    }
+
+    /// Gives back tokens of requests which were not sent.
+    fn release(&mut self, requests: usize) {
+        self.tokens = (self.tokens + requests as f64).min(self.burst);
+    }
+}
+
+impl<T: Transport> Transport for Throttled<T> {
+    type Out = Response<T>;
+
+    fn prepare(&self, method: &str, params: Vec<rpc::Value>) -> (RequestId, rpc::Call) {
+        self.transport.prepare(method, params)
+    }
+
+    fn send(&self, id: RequestId, request: rpc::Call) -> Self::Out {
+        let (delay, reservation) = self.reserve(1);
+        Response {
+            delay,
+            request: Some((self.transport.clone(), id, request, reservation)),
+            response: None,
+        }
+    }
+
+    fn execute_stream<X>(&self, method: &str, params: Vec<rpc::Value>) -> Option<BoxStream<'static, error::Result<X>>>
+    where
+        X: serde::de::DeserializeOwned + Send + 'static,
+    {
+        let stream = self.transport.execute_stream(method, params)?;
+        let (delay, reservation) = self.reserve(1);
+        let stream = async move {
+            if let Some(delay) = delay {
+                delay.await;
+            }
+            reservation.commit();
+            stream
+        };
+        Some(stream.flatten_stream().boxed())
+    }
+}
+
+impl<T: BatchTransport> BatchTransport for Throttled<T> {
+    type Batch = BatchResponse<T>;
+
+    fn send_batch<I>(&self, requests: I) -> Self::Batch
+    where
+        I: IntoIterator<Item = (RequestId, rpc::Call)>,
+    {
+        let requests: Vec<_> = requests.into_iter().collect();
+        let (delay, reservation) = self.reserve(requests.len());
+        BatchResponse {
+            delay,
+            requests: Some((self.transport.clone(), requests, reservation)),
+            response: None,
+        }
+    }
+}
+
+impl<T: DuplexTransport> DuplexTransport for Throttled<T> {
+    type NotificationStream = T::NotificationStream;
+
+    fn subscribe(&self, id: api::SubscriptionId) -> error::Result<Self::NotificationStream> {
+        self.transport.subscribe(id)
+    }
+
+    fn unsubscribe(&self, id: api::SubscriptionId) -> error::Result {
+        self.transport.unsubscribe(id)
+    }
+
+    fn cancel_subscription(&self, id: api::SubscriptionId) -> error::Result {
+        self.transport.cancel_subscription(id)
+    }
+
+    fn connection_events(&self) -> futures::stream::BoxStream<'static, ConnectionEvent> {
+        self.transport.connection_events()
+    }
+}
+
+/// A throttled request, sent once the rate limit allows it.
+pub struct Response<T: Transport> {
+    delay: Option<Delay>,
+    request: Option<(T, RequestId, rpc::Call, Reservation)>,
+    response: Option<Pin<Box<T::Out>>>,
+}
+
+impl<T: Transport> Unpin for Response<T> {}
+
+impl<T: Transport> Future for Response<T> {
+    type Output = error::Result<rpc::Value>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        if let Some(delay) = self.delay.as_mut() {
+            futures::ready!(delay.poll_unpin(cx));
+            self.delay = None;
+        }
+        if let Some((transport, id, request, reservation)) = self.request.take() {
+            reservation.commit();
+            self.response = Some(Box::pin(transport.send(id, request)));
+        }
+        self.response
+            .as_mut()
+            .expect("response is set once the request is sent; qed")
+            .as_mut()
+            .poll(cx)
+    }
+}
+
+/// A throttled batch request, sent once the rate limit allows it.
+pub struct BatchResponse<T: BatchTransport> {
+    delay: Option<Delay>,
+    requests: Option<(T, Vec<(RequestId, rpc::Call)>, Reservation)>,
+    response: Option<Pin<Box<T::Batch>>>,
+}
+
+impl<T: BatchTransport> Unpin for BatchResponse<T> {}
+
+impl<T: BatchTransport> Future for BatchResponse<T> {
+    type Output = error::Result<Vec<error::Result<rpc::Value>>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        if let Some(delay) = self.delay.as_mut() {
+            futures::ready!(delay.poll_unpin(cx));
+            self.delay = None;
+        }
+        if let Some((transport, requests, reservation)) = self.requests.take() {
+            reservation.commit();
+            self.response = Some(Box::pin(transport.send_batch(requests)));
+        }
+        self.response
+            .as_mut()
+            .expect("response is set once the requests are sent; qed")
+            .as_mut()
+            .poll(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transports::test::TestTransport;
+
+    #[test]
+    fn should_allow_burst() {
+        let mut bucket = Bucket {
+            per_second: 10.0,
+            burst: 2.0,
+            tokens: 2.0,
+            updated: Instant::now(),
+        };
+
+        assert_eq!(bucket.reserve(1), None);
+        assert_eq!(bucket.reserve(1), None);
+
+        let first = bucket.reserve(1).unwrap();
+        let second = bucket.reserve(1).unwrap();
+        assert!(first <= Duration::from_millis(100));
+        assert!(second > first && second <= Duration::from_millis(200));
+    }
+
+    #[test]
+    fn should_delay_requests_above_limit() {
+        let mut transport = TestTransport::default();
+        transport.add_response(rpc::Value::Bool(true));
+        transport.add_response(rpc::Value::Bool(false));
+        let throttled = Throttled::new(transport.clone(), 20.0, 1);
+
+        let started = Instant::now();
+        let first = futures::executor::block_on(throttled.execute("eth_syncing", vec![]));
+        let second = futures::executor::block_on(throttled.execute("eth_syncing", vec![]));
+
+        assert_eq!(first, Ok(rpc::Value::Bool(true)));
+        assert_eq!(second, Ok(rpc::Value::Bool(false)));
+        assert!(started.elapsed() >= Duration::from_millis(40));
+        transport.assert_request("eth_syncing", &[]);
+        transport.assert_request("eth_syncing", &[]);
+        transport.assert_no_more_requests();
+    }
+
+    #[test]
+    fn should_release_tokens_of_dropped_requests() {
+        let mut transport = TestTransport::default();
+        transport.add_response(rpc::Value::Bool(true));
+        let throttled = Throttled::new(transport.clone(), 20.0, 1);
+
+        for _ in 0..100 {
+            drop(throttled.execute("eth_syncing", vec![]));
+        }
+        let started = Instant::now();
+        let result = futures::executor::block_on(throttled.execute("eth_syncing", vec![]));
+
+        assert_eq!(result, Ok(rpc::Value::Bool(true)));
+        assert!(started.elapsed() < Duration::from_secs(1));
+        assert_eq!(throttled.bucket.lock().tokens.round(), 0.0);
+    }
+}
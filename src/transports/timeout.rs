@@ -0,0 +1,161 @@
+//! Transport wrapper bounding the duration of requests.
+
+use crate::{
+    api,
+    error::{self, Error, TransportError},
+    helpers::Deadline,
+    rpc,
+    transports::ConnectionEvent,
+    BatchTransport, DuplexTransport, RequestId, Transport,
+};
+use futures::{
+    future,
+    stream::{self, BoxStream, StreamExt},
+};
+use futures_timer::Delay;
+use std::time::Duration;
+
+/// A transport wrapper failing requests which don't complete in time with `TransportError::Timeout`.
+///
+/// Works with any transport, `Http`, `WebSocket` and `Ipc` can also bound their requests themselves
+/// with `with_timeout`. A batch and a streamed response are limited as a whole. Single calls can be given a shorter timeout
+/// with `CallFuture::timeout`. Subscriptions are not limited.
+///
+/// ```no_run
+/// # fn example() -> web3::Result<()> {
+/// use std::time::Duration;
+///
+/// let http = web3::transports::Http::new("http://localhost:8545")?;
+/// let web3 = web3::Web3::new(web3::transports::Timeout::new(http, Duration::from_secs(10)));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Timeout<T> {
+    transport: T,
+    timeout: Duration,
+}
+
+impl<T> Timeout<T> {
+    /// Wraps given transport, failing requests not completed within `timeout`.
+    pub fn new(transport: T, timeout: Duration) -> Self {
+        Timeout { transport, timeout }
+    }
+
+    /// Returns the timeout of requests.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Borrows the inner transport.
+    pub fn inner(&self) -> &T {
+        &self.transport
+    }
+}
+
+impl<T: Transport> Transport for Timeout<T> {
+    type Out = Deadline<T::Out>;
+
+    fn prepare(&self, method: &str, params: Vec<rpc::Value>) -> (RequestId, rpc::Call) {
+        self.transport.prepare(method, params)
+    }
+
+    fn send(&self, id: RequestId, request: rpc::Call) -> Self::Out {
+        Deadline::new(self.transport.send(id, request), self.timeout)
+    }
+
+    fn execute_stream<X>(&self, method: &str, params: Vec<rpc::Value>) -> Option<BoxStream<'static, error::Result<X>>>
+    where
+        X: serde::de::DeserializeOwned + Send + 'static,
+    {
+        let stream = self.transport.execute_stream(method, params)?;
+        // the whole response has to be received in time
+        let state = (stream, Delay::new(self.timeout));
+        let stream = stream::unfold(Some(state), |state| async move {
+            let (mut stream, mut delay) = state?;
+            let next = match future::select(stream.next(), &mut delay).await {
+                future::Either::Left((next, _)) => Some(next),
+                future::Either::Right(_) => None,
+            };
+            match next {
+                Some(Some(item)) => Some((item, Some((stream, delay)))),
+                Some(None) => None,
+                None => Some((Err(Error::Transport(TransportError::Timeout)), None)),
+            }
+        });
+        Some(stream.boxed())
+    }
+}
+
+impl<T: BatchTransport> BatchTransport for Timeout<T> {
+    type Batch = Deadline<T::Batch>;
+
+    fn send_batch<I>(&self, requests: I) -> Self::Batch
+    where
+        I: IntoIterator<Item = (RequestId, rpc::Call)>,
+    {
+        Deadline::new(self.transport.send_batch(requests), self.timeout)
+    }
+}
+
+impl<T: DuplexTransport> DuplexTransport for Timeout<T> {
+    type NotificationStream = T::NotificationStream;
+
+    fn subscribe(&self, id: api::SubscriptionId) -> error::Result<Self::NotificationStream> {
+        self.transport.subscribe(id)
+    }
+
+    fn unsubscribe(&self, id: api::SubscriptionId) -> error::Result {
+        self.transport.unsubscribe(id)
+    }
+
+    fn cancel_subscription(&self, id: api::SubscriptionId) -> error::Result {
+        self.transport.cancel_subscription(id)
+    }
+
+    fn connection_events(&self) -> futures::stream::BoxStream<'static, ConnectionEvent> {
+        self.transport.connection_events()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        api::Namespace,
+        error::{Error, TransportError},
+        transports::test::TestTransport,
+    };
+
+    #[test]
+    fn should_fail_slow_requests() {
+        let mut transport = TestTransport::default();
+        transport.set_latency(Duration::from_millis(10));
+        transport.add_response(rpc::Value::Bool(true));
+        transport.add_response(rpc::Value::Bool(true));
+
+        let fast = Timeout::new(transport.clone(), Duration::from_secs(5));
+        let slow = Timeout::new(transport.clone(), Duration::from_millis(1));
+
+        assert_eq!(
+            futures::executor::block_on(fast.execute("eth_syncing", vec![])),
+            Ok(rpc::Value::Bool(true))
+        );
+        assert_eq!(
+            futures::executor::block_on(slow.execute("eth_syncing", vec![])),
+            Err(Error::Transport(TransportError::Timeout))
+        );
+    }
+
+    #[test]
+    fn should_override_timeout_per_call() {
+        let mut transport = TestTransport::default();
+        transport.set_latency(Duration::from_millis(10));
+        transport.add_response(rpc::Value::String("0x1".into()));
+        let eth = api::Eth::new(Timeout::new(transport, Duration::from_secs(5)));
+
+        let result = futures::executor::block_on(eth.block_number().timeout(Duration::from_millis(1)));
+
+        assert_eq!(result, Err(Error::Transport(TransportError::Timeout)));
+    }
+}
@@ -0,0 +1,596 @@
+//! Transport wrapper validating requests and responses against the Execution API schemas.
+
+use crate::{
+    api,
+    error::{self, Error, TransportError},
+    helpers, rpc,
+    transports::ConnectionEvent,
+    BatchTransport, DuplexTransport, RequestId, Transport,
+};
+use futures::{
+    future,
+    stream::{self, BoxStream, StreamExt},
+    task::{Context, Poll},
+    Future,
+};
+use serde_json::{Map, Value};
+use std::{collections::BTreeMap, pin::Pin, sync::Arc};
+
+/// A subset of the `ethereum/execution-apis` OpenRPC document covering the core `eth_` methods.
+const SCHEMAS: &str = include_str!("schemas.json");
+
+/// A development transport wrapper checking that the params of outgoing calls and the results
+/// of incoming responses match the official Execution API schemas.
+///
+/// It is meant to run in tests against a real node to catch drift between the types of this
+/// crate and the spec (e.g. a renamed field or a missing required one). Methods without a bundled
+/// schema are passed through, of streamed responses (`Transport::execute_stream`) only the params
+/// are checked. Validation only happens in debug builds, in release builds
+/// the wrapper is a no-op.
+///
+/// By default mismatches are logged as warnings, in [strict](Validating::strict) mode the
+/// request fails instead.
+///
+/// ```no_run
+/// # fn example() -> web3::Result<()> {
+/// let http = web3::transports::Http::new("http://localhost:8545")?;
+/// let web3 = web3::Web3::new(web3::transports::Validating::new(http).strict());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Validating<T> {
+    transport: T,
+    schemas: Option<Arc<Schemas>>,
+    strict: bool,
+}
+
+impl<T> Validating<T> {
+    /// Wraps given transport, logging schema violations.
+    pub fn new(transport: T) -> Self {
+        Validating {
+            transport,
+            schemas: if cfg!(debug_assertions) {
+                Some(Arc::new(Schemas::bundled()))
+            } else {
+                None
+            },
+            strict: false,
+        }
+    }
+
+    /// Fails requests with invalid params or results instead of logging a warning.
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    /// Borrows the inner transport.
+    pub fn inner(&self) -> &T {
+        &self.transport
+    }
+
+    fn validator(&self, request: &rpc::Call) -> Result<Option<Validator>, Error> {
+        let (schemas, call) = match (&self.schemas, request) {
+            (Some(schemas), rpc::Call::MethodCall(call)) => (schemas, call),
+            _ => return Ok(None),
+        };
+        if !schemas.methods.contains_key(&call.method) {
+            return Ok(None);
+        }
+
+        let params = match call.params {
+            rpc::Params::Array(ref params) => params.as_slice(),
+            _ => &[],
+        };
+        if let Err(violation) = schemas.check_params(&call.method, params) {
+            let message = format!("{} params don't match the schema: {}", call.method, violation);
+            if self.strict {
+                return Err(Error::Transport(TransportError::Message(message)));
+            }
+            event!(warn, "{}", message);
+        }
+        Ok(Some(Validator {
+            schemas: schemas.clone(),
+            method: call.method.clone(),
+            strict: self.strict,
+        }))
+    }
+}
+
+impl<T: Transport> Transport for Validating<T> {
+    type Out = Response<T::Out>;
+
+    fn prepare(&self, method: &str, params: Vec<rpc::Value>) -> (RequestId, rpc::Call) {
+        self.transport.prepare(method, params)
+    }
+
+    fn send(&self, id: RequestId, request: rpc::Call) -> Self::Out {
+        match self.validator(&request) {
+            Ok(validator) => Response {
+                response: Some(Box::pin(self.transport.send(id, request))),
+                validator,
+                error: None,
+            },
+            Err(error) => Response {
+                response: None,
+                validator: None,
+                error: Some(error),
+            },
+        }
+    }
+
+    fn execute_stream<X>(&self, method: &str, params: Vec<rpc::Value>) -> Option<BoxStream<'static, error::Result<X>>>
+    where
+        X: serde::de::DeserializeOwned + Send + 'static,
+    {
+        let call = helpers::build_request(0, method, params.clone());
+        let stream = self.transport.execute_stream(method, params)?;
+        // items are decoded while they are received, only the params can be checked
+        match self.validator(&call) {
+            Ok(_) => Some(stream),
+            Err(error) => Some(stream::once(future::ready(Err(error))).boxed()),
+        }
+    }
+}
+
+impl<T: BatchTransport> BatchTransport for Validating<T> {
+    type Batch = BatchResponse<T::Batch>;
+
+    fn send_batch<I>(&self, requests: I) -> Self::Batch
+    where
+        I: IntoIterator<Item = (RequestId, rpc::Call)>,
+    {
+        let requests: Vec<_> = requests.into_iter().collect();
+        let validators = match requests
+            .iter()
+            .map(|(_, request)| self.validator(request))
+            .collect::<Result<Vec<_>, _>>()
+        {
+            Ok(validators) => validators,
+            Err(error) => {
+                return BatchResponse {
+                    response: None,
+                    validators: vec![],
+                    error: Some(error),
+                }
+            }
+        };
+        BatchResponse {
+            response: Some(Box::pin(self.transport.send_batch(requests))),
+            validators,
+            error: None,
+        }
+    }
+}
+
+impl<T: DuplexTransport> DuplexTransport for Validating<T> {
+    type NotificationStream = T::NotificationStream;
+
+    fn subscribe(&self, id: api::SubscriptionId) -> error::Result<Self::NotificationStream> {
+        self.transport.subscribe(id)
+    }
+
+    fn unsubscribe(&self, id: api::SubscriptionId) -> error::Result {
+        self.transport.unsubscribe(id)
+    }
+
+    fn cancel_subscription(&self, id: api::SubscriptionId) -> error::Result {
+        self.transport.cancel_subscription(id)
+    }
+
+    fn connection_events(&self) -> futures::stream::BoxStream<'static, ConnectionEvent> {
+        self.transport.connection_events()
+    }
+}
+
+/// A request which result is validated once it arrives.
+pub struct Response<F> {
+    response: Option<Pin<Box<F>>>,
+    validator: Option<Validator>,
+    error: Option<Error>,
+}
+
+impl<F> Unpin for Response<F> {}
+
+impl<F: Future<Output = error::Result<rpc::Value>>> Future for Response<F> {
+    type Output = error::Result<rpc::Value>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        if let Some(error) = self.error.take() {
+            return Poll::Ready(Err(error));
+        }
+        let result = futures::ready!(self
+            .response
+            .as_mut()
+            .expect("response is set unless the request was rejected; qed")
+            .as_mut()
+            .poll(cx));
+        Poll::Ready(match (result, self.validator.as_ref()) {
+            (Ok(value), Some(validator)) => validator.check(value),
+            (result, _) => result,
+        })
+    }
+}
+
+/// A batch request which results are validated once they arrive.
+pub struct BatchResponse<F> {
+    response: Option<Pin<Box<F>>>,
+    validators: Vec<Option<Validator>>,
+    error: Option<Error>,
+}
+
+impl<F> Unpin for BatchResponse<F> {}
+
+impl<F: Future<Output = error::Result<Vec<error::Result<rpc::Value>>>>> Future for BatchResponse<F> {
+    type Output = error::Result<Vec<error::Result<rpc::Value>>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        if let Some(error) = self.error.take() {
+            return Poll::Ready(Err(error));
+        }
+        let results = futures::ready!(self
+            .response
+            .as_mut()
+            .expect("response is set unless the requests were rejected; qed")
+            .as_mut()
+            .poll(cx))?;
+        Poll::Ready(Ok(results
+            .into_iter()
+            .zip(self.validators.iter().map(Some).chain(std::iter::repeat(None)))
+            .map(|(result, validator)| match (result, validator) {
+                (Ok(value), Some(Some(validator))) => validator.check(value),
+                (result, _) => result,
+            })
+            .collect()))
+    }
+}
+
+#[derive(Debug)]
+struct Validator {
+    schemas: Arc<Schemas>,
+    method: String,
+    strict: bool,
+}
+
+impl Validator {
+    fn check(&self, value: rpc::Value) -> error::Result<rpc::Value> {
+        if let Err(violation) = self.schemas.check_result(&self.method, &value) {
+            let message = format!("{} result doesn't match the schema: {}", self.method, violation);
+            if self.strict {
+                return Err(Error::InvalidResponse(message));
+            }
+            event!(warn, "{}", message);
+        }
+        Ok(value)
+    }
+}
+
+/// Method and type schemas, in a subset of JSON Schema used by the Execution API spec.
+#[derive(Debug, serde::Deserialize)]
+struct Schemas {
+    components: BTreeMap<String, Value>,
+    methods: BTreeMap<String, Method>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct Method {
+    params: Vec<Param>,
+    result: Value,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct Param {
+    name: String,
+    required: bool,
+    schema: Value,
+}
+
+impl Schemas {
+    fn bundled() -> Self {
+        serde_json::from_str(SCHEMAS).expect("bundled schemas are valid; qed")
+    }
+
+    fn check_params(&self, method: &str, params: &[Value]) -> Result<(), String> {
+        let method = match self.methods.get(method) {
+            Some(method) => method,
+            None => return Ok(()),
+        };
+        if params.len() > method.params.len() {
+            return Err(format!(
+                "expected at most {} params, got {}",
+                method.params.len(),
+                params.len()
+            ));
+        }
+        for (index, param) in method.params.iter().enumerate() {
+            match params.get(index) {
+                Some(value) => self
+                    .check(value, &param.schema)
+                    .map_err(|e| format!("{}: {}", param.name, e))?,
+                None if param.required => return Err(format!("{}: missing required param", param.name)),
+                None => (),
+            }
+        }
+        Ok(())
+    }
+
+    fn check_result(&self, method: &str, value: &Value) -> Result<(), String> {
+        match self.methods.get(method) {
+            Some(method) => self.check(value, &method.result),
+            None => Ok(()),
+        }
+    }
+
+    fn check(&self, value: &Value, schema: &Value) -> Result<(), String> {
+        let schema = match schema.as_object() {
+            Some(schema) => schema,
+            None => return Ok(()),
+        };
+
+        if let Some(name) = schema.get("$ref").and_then(Value::as_str) {
+            let referenced = self
+                .components
+                .get(name)
+                .ok_or_else(|| format!("unknown schema `{}`", name))?;
+            return self.check(value, referenced).map_err(|e| format!("{} ({})", e, name));
+        }
+
+        if let Some(variants) = schema.get("oneOf").and_then(Value::as_array) {
+            let results: Vec<_> = variants.iter().map(|variant| self.check(value, variant)).collect();
+            if !results.iter().any(Result::is_ok) {
+                // report the variant of matching type, if there is only one
+                let mut candidates = results
+                    .into_iter()
+                    .filter_map(Result::err)
+                    .filter(|error| !error.starts_with("expected "));
+                return Err(match (candidates.next(), candidates.next()) {
+                    (Some(error), None) => error,
+                    _ => format!("{} doesn't match any of the allowed variants", value),
+                });
+            }
+        }
+
+        if let Some(ty) = schema.get("type").and_then(Value::as_str) {
+            check_type(value, ty)?;
+        }
+
+        if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+            if !allowed.contains(value) {
+                return Err(format!("{} is not one of {:?}", value, allowed));
+            }
+        }
+
+        if let (Some(format), Some(value)) = (schema.get("format").and_then(Value::as_str), value.as_str()) {
+            check_format(value, format)?;
+        }
+
+        if let (Some(items), Some(values)) = (schema.get("items"), value.as_array()) {
+            for (index, value) in values.iter().enumerate() {
+                self.check(value, items).map_err(|e| format!("[{}]: {}", index, e))?;
+            }
+        }
+
+        if let Some(object) = value.as_object() {
+            self.check_object(object, schema)?;
+        }
+
+        Ok(())
+    }
+
+    fn check_object(&self, object: &Map<String, Value>, schema: &Map<String, Value>) -> Result<(), String> {
+        if let Some(required) = schema.get("required").and_then(Value::as_array) {
+            for field in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(field) {
+                    return Err(format!("missing required field `{}`", field));
+                }
+            }
+        }
+
+        let properties = schema.get("properties").and_then(Value::as_object);
+        for (field, value) in object {
+            match properties.and_then(|properties| properties.get(field)) {
+                Some(property) => self.check(value, property).map_err(|e| format!("{}: {}", field, e))?,
+                None if schema.get("additionalProperties") == Some(&Value::Bool(false)) => {
+                    return Err(format!("unexpected field `{}`", field))
+                }
+                None => (),
+            }
+        }
+        Ok(())
+    }
+}
+
+fn check_type(value: &Value, ty: &str) -> Result<(), String> {
+    let matches = match ty {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "number" => value.is_number(),
+        "integer" => value.is_u64() || value.is_i64(),
+        "string" => value.is_string(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        _ => return Err(format!("unknown type `{}`", ty)),
+    };
+    if matches {
+        Ok(())
+    } else {
+        Err(format!("expected {}, got {}", ty, value))
+    }
+}
+
+fn check_format(value: &str, format: &str) -> Result<(), String> {
+    let digits = value
+        .strip_prefix("0x")
+        .filter(|digits| digits.bytes().all(|b| b.is_ascii_hexdigit()))
+        .ok_or_else(|| format!("{:?} is not a 0x-prefixed hex string", value))?;
+
+    let valid = match format {
+        // no leading zeros
+        "uint" => digits == "0" || (!digits.is_empty() && !digits.starts_with('0')),
+        "bytes" => digits.len() % 2 == 0,
+        "bytes8" => digits.len() == 16,
+        "bytes32" => digits.len() == 64,
+        "bytes256" => digits.len() == 512,
+        "address" => digits.len() == 40,
+        _ => return Err(format!("unknown format `{}`", format)),
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("{:?} is not a valid {}", value, format))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        api::Namespace,
+        transports::test::TestTransport,
+        types::{Address, BlockId, BlockNumber, CallRequest, FilterBuilder, H256, U256},
+    };
+    use futures::executor::block_on;
+
+    fn schemas() -> Schemas {
+        Schemas::bundled()
+    }
+
+    #[test]
+    fn bundled_schemas_are_consistent() {
+        let schemas = schemas();
+        let refs = SCHEMAS
+            .split("\"$ref\": \"")
+            .skip(1)
+            .map(|rest| &rest[..rest.find('"').unwrap()]);
+        for name in refs {
+            assert!(schemas.components.contains_key(name), "unknown schema `{}`", name);
+        }
+    }
+
+    #[test]
+    fn should_accept_serialized_requests() {
+        let schemas = schemas();
+        let address = serde_json::to_value(Address::from_low_u64_be(1)).unwrap();
+        let latest = serde_json::to_value(BlockNumber::Latest).unwrap();
+        let block = serde_json::to_value(BlockNumber::Number(0x10.into())).unwrap();
+        let hash = serde_json::to_value(BlockId::Hash(H256::from_low_u64_be(2))).unwrap();
+        let call = serde_json::to_value(CallRequest {
+            to: Some(Address::from_low_u64_be(3)),
+            value: Some(U256::from(0x100)),
+            data: Some(vec![0x12, 0x34].into()),
+            ..Default::default()
+        })
+        .unwrap();
+        let filter = serde_json::to_value(
+            FilterBuilder::default()
+                .from_block(BlockNumber::Earliest)
+                .address(vec![Address::from_low_u64_be(4)])
+                .topics(Some(vec![H256::from_low_u64_be(5)]), None, None, None)
+                .build(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            schemas.check_params("eth_getBalance", &[address.clone(), latest]),
+            Ok(())
+        );
+        assert_eq!(schemas.check_params("eth_getCode", &[address, hash]), Ok(()));
+        assert_eq!(schemas.check_params("eth_call", &[call, block.clone()]), Ok(()));
+        assert_eq!(
+            schemas.check_params("eth_getBlockByNumber", &[block, Value::Bool(false)]),
+            Ok(())
+        );
+        assert_eq!(schemas.check_params("eth_getLogs", &[filter]), Ok(()));
+    }
+
+    #[test]
+    fn should_reject_invalid_params() {
+        let schemas = schemas();
+        assert_eq!(
+            schemas.check_params("eth_getBalance", &[]),
+            Err("Address: missing required param".into())
+        );
+        assert_eq!(
+            schemas.check_params("eth_getBalance", &[Value::String("0x1234".into())]),
+            Err("Address: \"0x1234\" is not a valid address (address)".into())
+        );
+        assert_eq!(
+            schemas.check_params(
+                "eth_getBlockByNumber",
+                &[Value::String("0x01".into()), Value::Bool(false)]
+            ),
+            Err("Block: \"0x01\" doesn't match any of the allowed variants (BlockNumberOrTag)".into())
+        );
+        assert!(schemas.check_params("eth_chainId", &[Value::Null]).is_err());
+        assert_eq!(schemas.check_params("unknown_method", &[Value::Null]), Ok(()));
+    }
+
+    #[test]
+    fn should_validate_results() {
+        let schemas = schemas();
+        let log = serde_json::json!({
+            "address": "0x0000000000000000000000000000000000000001",
+            "topics": ["0x0000000000000000000000000000000000000000000000000000000000000002"],
+            "data": "0x",
+            "blockNumber": "0x10",
+            "logIndex": "0x0",
+            "removed": false
+        });
+        assert_eq!(
+            schemas.check_result("eth_getLogs", &Value::Array(vec![log.clone()])),
+            Ok(())
+        );
+        assert_eq!(schemas.check_result("eth_getBlockByNumber", &Value::Null), Ok(()));
+
+        let mut invalid = log;
+        invalid["data"] = "0x123".into();
+        assert_eq!(
+            schemas.check_result("eth_getLogs", &Value::Array(vec![invalid])),
+            Err("[0]: data: \"0x123\" is not a valid bytes (bytes) (Log)".into())
+        );
+
+        let receipt = serde_json::json!({
+            "transactionHash": "0x0000000000000000000000000000000000000000000000000000000000000002",
+            "transactionIndex": "0x0",
+            "blockHash": "0x0000000000000000000000000000000000000000000000000000000000000003",
+            "blockNumber": "0x10",
+            "from": "0x0000000000000000000000000000000000000001",
+            "to": null,
+            "gasUsed": "0x5208",
+            "contractAddress": null,
+            "logs": []
+        });
+        assert_eq!(
+            schemas.check_result("eth_getTransactionReceipt", &receipt),
+            Err("missing required field `cumulativeGasUsed` (ReceiptInfo)".into())
+        );
+    }
+
+    #[test]
+    fn should_fail_in_strict_mode() {
+        let mut transport = TestTransport::default();
+        transport.add_response(Value::String("0x01".into()));
+        transport.add_response(Value::String("0x01".into()));
+        let lenient = crate::api::Eth::new(Validating::new(transport.clone()));
+        let strict = crate::api::Eth::new(Validating::new(transport.clone()).strict());
+
+        assert_eq!(block_on(lenient.chain_id()), Ok(1.into()));
+        assert!(matches!(
+            block_on(strict.chain_id()),
+            Err(Error::InvalidResponse(ref msg))
+                if msg == "eth_chainId result doesn't match the schema: \"0x01\" is not a valid uint (uint)"
+        ));
+
+        let invalid = Validating::new(transport.clone()).strict();
+        assert!(matches!(
+            block_on(invalid.execute("eth_getBalance", vec![Value::String("0x1234".into())])),
+            Err(Error::Transport(TransportError::Message(_)))
+        ));
+        transport.assert_request("eth_chainId", &[]);
+        transport.assert_request("eth_chainId", &[]);
+        // prepared, but never sent
+        transport.assert_request("eth_getBalance", &[r#""0x1234""#.into()]);
+        transport.assert_no_more_requests();
+    }
+}
@@ -4,23 +4,33 @@ use self::compat::{TcpStream, TlsStream};
 use crate::{
     api::SubscriptionId,
     error::{self, TransportError},
-    helpers, rpc, BatchTransport, DuplexTransport, Error, RequestId, Transport,
+    helpers::{self, backoff::Backoff},
+    rpc,
+    transports::{
+        connection::ConnectionState,
+        notifications::{NotificationBuffer, NotificationSender, NotificationStream},
+        AuthProvider, ConnectionEvent, IdStrategy,
+    },
+    BatchTransport, DuplexTransport, Error, RequestId, Transport,
 };
 use futures::{
     channel::{mpsc, oneshot},
+    stream::{BoxStream, Fuse, FusedStream},
     task::{Context, Poll},
     AsyncRead, AsyncWrite, Future, FutureExt, Stream, StreamExt,
 };
+use futures_timer::Delay;
 use soketto::{
     connection,
     handshake::{Client, ServerResponse},
 };
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, HashMap},
     fmt,
     marker::Unpin,
     pin::Pin,
     sync::{atomic, Arc},
+    time::Duration,
 };
 use url::Url;
 
@@ -36,10 +46,19 @@ impl From<connection::Error> for Error {
     }
 }
 
+/// How often requests which were dropped (e.g. timed out) are removed from the pending requests.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(10);
+
 type SingleResult = error::Result<rpc::Value>;
 type BatchResult = error::Result<Vec<SingleResult>>;
-type Pending = oneshot::Sender<BatchResult>;
-type Subscription = mpsc::UnboundedSender<rpc::Value>;
+type Subscription = NotificationSender;
+
+/// A request awaiting its response.
+struct Pending {
+    /// Ids of the calls, in the order of the results
+    ids: Vec<rpc::Id>,
+    sender: oneshot::Sender<BatchResult>,
+}
 
 /// Stream, either plain TCP or TLS.
 enum MaybeTlsStream<P, T> {
@@ -91,7 +110,7 @@ where
 }
 
 struct WsServerTask {
-    pending: BTreeMap<RequestId, Pending>,
+    pending: HashMap<rpc::Id, Pending>,
     subscriptions: BTreeMap<SubscriptionId, Subscription>,
     sender: connection::Sender<MaybeTlsStream<TcpStream, TlsStream>>,
     receiver: connection::Receiver<MaybeTlsStream<TcpStream, TlsStream>>,
@@ -99,7 +118,7 @@ struct WsServerTask {
 
 impl WsServerTask {
     /// Create new WebSocket transport.
-    pub async fn new(url: &str) -> error::Result<Self> {
+    pub async fn new(url: &str, auth: Option<&dyn AuthProvider>) -> error::Result<Self> {
         let url = Url::parse(url)?;
 
         let scheme = match url.scheme() {
@@ -125,7 +144,7 @@ impl WsServerTask {
 
         let addrs = format!("{}:{}", host, port);
 
-        log::trace!("Connecting TcpStream with address: {}", addrs);
+        event!(trace, "Connecting TcpStream with address: {}", addrs);
         let stream = compat::raw_tcp_stream(addrs).await?;
         stream.set_nodelay(true)?;
         let socket = if scheme == "wss" {
@@ -155,20 +174,24 @@ impl WsServerTask {
             None => url.path().to_owned(),
         };
 
-        log::trace!(
+        event!(
+            trace,
             "Connecting websocket client with host: {} and resource: {}",
             host,
             resource
         );
         let mut client = Client::new(socket, host, &resource);
-        let maybe_encoded = url.password().map(|password| {
-            use headers::authorization::{Authorization, Credentials};
-            Authorization::basic(url.username(), password)
-                .0
-                .encode()
-                .as_bytes()
-                .to_vec()
-        });
+        let maybe_encoded = match auth {
+            Some(auth) => Some(format!("Bearer {}", auth.token()?).into_bytes()),
+            None => url.password().map(|password| {
+                use headers::authorization::{Authorization, Credentials};
+                Authorization::basic(url.username(), password)
+                    .0
+                    .encode()
+                    .as_bytes()
+                    .to_vec()
+            }),
+        };
 
         let headers = maybe_encoded.as_ref().map(|head| {
             [soketto::handshake::client::Header {
@@ -199,7 +222,28 @@ impl WsServerTask {
         })
     }
 
-    async fn into_task(self, requests: mpsc::UnboundedReceiver<TransportMessage>) {
+    async fn into_task(
+        self,
+        requests: mpsc::UnboundedReceiver<TransportMessage>,
+        events: Arc<ConnectionState>,
+        reconnect: Option<Reconnect>,
+    ) {
+        let mut requests = requests.fuse();
+        let mut task = self;
+        loop {
+            task.run(&mut requests, &events).await;
+            task = match reconnect {
+                Some(ref reconnect) => match reconnect.run(&mut requests, &events).await {
+                    Some(task) => task,
+                    None => break,
+                },
+                None => break,
+            };
+        }
+        events.close();
+    }
+
+    async fn run(self, requests: &mut Fuse<mpsc::UnboundedReceiver<TransportMessage>>, events: &ConnectionState) {
         let Self {
             receiver,
             mut sender,
@@ -208,45 +252,55 @@ impl WsServerTask {
         } = self;
 
         let receiver = as_data_stream(receiver).fuse();
-        let requests = requests.fuse();
         pin_mut!(receiver);
-        pin_mut!(requests);
+        let mut prune = Delay::new(PRUNE_INTERVAL).fuse();
         loop {
             select! {
                 msg = requests.next() => match msg {
-                    Some(TransportMessage::Request { id, request, sender: tx }) => {
-                        if pending.insert(id, tx).is_some() {
-                            log::warn!("Replacing a pending request with id {:?}", id);
+                    Some(TransportMessage::Request { id, ids, request, sender: tx }) => {
+                        if pending.insert(id.clone(), Pending { ids, sender: tx }).is_some() {
+                            event!(warn, "Replacing a pending request with id {:?}", id);
                         }
                         let res = sender.send_text(request).await;
                         let res2 = sender.flush().await;
                         if let Err(e) = res.and(res2) {
                             // TODO [ToDr] Re-connect.
-                            log::error!("WS connection error: {:?}", e);
+                            event!(error, "WS connection error: {:?}", e);
                             pending.remove(&id);
                         }
                     }
                     Some(TransportMessage::Subscribe { id, sink }) => {
                         if subscriptions.insert(id.clone(), sink).is_some() {
-                            log::warn!("Replacing already-registered subscription with id {:?}", id);
+                            event!(warn, "Replacing already-registered subscription with id {:?}", id);
                         }
                     }
                     Some(TransportMessage::Unsubscribe { id }) => {
                         if subscriptions.remove(&id).is_none() {
-                            log::warn!("Unsubscribing from non-existent subscription with id {:?}", id);
+                            event!(warn, "Unsubscribing from non-existent subscription with id {:?}", id);
                         }
                     }
                     None => {}
                 },
                 res = receiver.next() => match res {
                     Some(Ok(data)) => {
-                        handle_message(&data, &subscriptions, &mut pending);
+                        if let Some((id, result)) = handle_message(&data, &mut pending) {
+                            notify(&mut subscriptions, id, result).await;
+                        }
                     },
                     Some(Err(e)) => {
-                        log::error!("WS connection error: {:?}", e);
+                        event!(error, "WS connection error: {:?}", e);
+                        events.emit(ConnectionEvent::Disconnected { cause: format!("{:?}", e) });
+                        break;
+                    },
+                    None => {
+                        events.emit(ConnectionEvent::Disconnected { cause: "Connection closed".into() });
                         break;
                     },
-                    None => break,
+                },
+                _ = prune => {
+                    // responses of dropped (e.g. timed out) requests would be ignored anyway
+                    pending.retain(|_, pending| !pending.sender.is_canceled());
+                    prune = Delay::new(PRUNE_INTERVAL).fuse();
                 },
                 complete => break,
             }
@@ -254,6 +308,61 @@ impl WsServerTask {
     }
 }
 
+/// How to re-establish a lost connection.
+struct Reconnect {
+    url: String,
+    auth: Option<Arc<dyn AuthProvider>>,
+    backoff: Backoff,
+}
+
+impl Reconnect {
+    /// Connects again (with a fresh token of the auth provider), returns `None` once all transport
+    /// handles are dropped or the backoff gives up.
+    async fn run(
+        &self,
+        requests: &mut Fuse<mpsc::UnboundedReceiver<TransportMessage>>,
+        events: &ConnectionState,
+    ) -> Option<WsServerTask> {
+        if requests.is_terminated() {
+            return None;
+        }
+        event!(warn, "WS connection lost, reconnecting to {}", self.url);
+        let mut delays = self.backoff.delays();
+        let mut attempt = 0;
+        loop {
+            let delay = match delays.next() {
+                Some(delay) => delay,
+                None => {
+                    event!(warn, "WS reconnection to {} failed, giving up", self.url);
+                    return None;
+                }
+            };
+            // requests can't be sent until reconnected, so fail them right away
+            let retry = Delay::new(delay).fuse();
+            pin_mut!(retry);
+            loop {
+                select! {
+                    message = requests.next() => match message {
+                        Some(message) => drop(message),
+                        None => return None,
+                    },
+                    _ = retry => break,
+                }
+            }
+            attempt += 1;
+            events.emit(ConnectionEvent::Reconnecting { attempt });
+            match WsServerTask::new(&self.url, self.auth.as_deref()).await {
+                Ok(task) => {
+                    event!(info, "WS reconnected to {}", self.url);
+                    events.emit(ConnectionEvent::Connected);
+                    return Some(task);
+                }
+                Err(err) => event!(debug, "WS reconnection failed: {:?}", err),
+            }
+        }
+    }
+}
+
 #[cfg(feature = "ws-rustls-tokio")]
 async fn tokio_rustls_connect(
     host: &str,
@@ -292,68 +401,102 @@ fn as_data_stream<T: Unpin + futures::AsyncRead + futures::AsyncWrite>(
     })
 }
 
-fn handle_message(
-    data: &[u8],
-    subscriptions: &BTreeMap<SubscriptionId, Subscription>,
-    pending: &mut BTreeMap<RequestId, Pending>,
-) {
-    log::trace!("Message received: {:?}", data);
+/// Responds to pending requests, returns notifications to be delivered to the subscriptions.
+fn handle_message(data: &[u8], pending: &mut HashMap<rpc::Id, Pending>) -> Option<(SubscriptionId, rpc::Value)> {
+    event!(trace, "Message received: {:?}", data);
     if let Ok(notification) = helpers::to_notification_from_slice(data) {
-        if let rpc::Params::Map(params) = notification.params {
-            let id = params.get("subscription");
-            let result = params.get("result");
-
-            if let (Some(&rpc::Value::String(ref id)), Some(result)) = (id, result) {
-                let id: SubscriptionId = id.clone().into();
-                if let Some(stream) = subscriptions.get(&id) {
-                    if let Err(e) = stream.unbounded_send(result.clone()) {
-                        log::error!("Error sending notification: {:?} (id: {:?}", e, id);
-                    }
-                } else {
-                    log::warn!("Got notification for unknown subscription (id: {:?})", id);
-                }
+        if let rpc::Params::Map(mut params) = notification.params {
+            let id = params.remove("subscription");
+            let result = params.remove("result");
+
+            if let (Some(rpc::Value::String(id)), Some(result)) = (id.clone(), result) {
+                return Some((id.into(), result));
             } else {
-                log::error!("Got unsupported notification (id: {:?})", id);
+                event!(error, "Got unsupported notification (id: {:?})", id);
             }
         }
     } else {
-        let response = helpers::to_response_from_slice(data);
-        let outputs = match response {
-            Ok(rpc::Response::Single(output)) => vec![output],
-            Ok(rpc::Response::Batch(outputs)) => outputs,
-            _ => vec![],
-        };
-
-        let id = match outputs.get(0) {
-            Some(&rpc::Output::Success(ref success)) => success.id.clone(),
-            Some(&rpc::Output::Failure(ref failure)) => failure.id.clone(),
-            None => rpc::Id::Num(0),
+        let outputs: Vec<_> = match helpers::arbitrary_precision_deserialize_workaround(data) {
+            Ok(rpc::Value::Array(items)) => items.into_iter().filter_map(parse_output).collect(),
+            Ok(item) => parse_output(item).into_iter().collect(),
+            Err(err) => {
+                event!(warn, "Got unsupported response: {}", err);
+                return None;
+            }
         };
 
-        if let rpc::Id::Num(num) = id {
-            if let Some(request) = pending.remove(&(num as usize)) {
-                log::trace!("Responding to (id: {:?}) with {:?}", num, outputs);
-                if let Err(err) = request.send(helpers::to_results_from_outputs(outputs)) {
-                    log::warn!("Sending a response to deallocated channel: {:?}", err);
+        // Requests are registered with the id of their first call, which may be anywhere in a batch response.
+        let id = outputs
+            .iter()
+            .map(|(id, _)| id)
+            .find(|id| pending.contains_key(id))
+            .cloned();
+        match id.and_then(|id| pending.remove(&id)) {
+            Some(request) => {
+                event!(trace, "Responding to (ids: {:?}) with {:?}", request.ids, outputs);
+                let results = match_outputs(&request.ids, outputs);
+                if let Err(err) = request.sender.send(Ok(results)) {
+                    event!(warn, "Sending a response to deallocated channel: {:?}", err);
                 }
-            } else {
-                log::warn!("Got response for unknown request (id: {:?})", num);
             }
-        } else {
-            log::warn!("Got unsupported response (id: {:?})", id);
+            None => event!(warn, "Got response for unknown request: {:?}", outputs),
+        }
+    }
+    None
+}
+
+/// Parses an output of a response, turning a malformed output into an error for its id.
+fn parse_output(value: rpc::Value) -> Option<(rpc::Id, SingleResult)> {
+    match serde_json::from_value::<rpc::Output>(value.clone()) {
+        Ok(rpc::Output::Success(success)) => Some((success.id, Ok(success.result))),
+        Ok(rpc::Output::Failure(failure)) => Some((failure.id, Err(Error::Rpc(failure.error)))),
+        Err(err) => match value.get("id").cloned().map(serde_json::from_value) {
+            Some(Ok(id)) if id != rpc::Id::Null => {
+                Some((id, Err(Error::InvalidResponse(format!("Invalid response: {}", err)))))
+            }
+            _ => {
+                event!(warn, "Got unsupported response: {:?}", value);
+                None
+            }
+        },
+    }
+}
+
+/// Matches outputs to the calls of a request, in the order of the calls.
+///
+/// Batch responses may come in any order, calls without an output get an error.
+fn match_outputs(ids: &[rpc::Id], outputs: Vec<(rpc::Id, SingleResult)>) -> Vec<SingleResult> {
+    let mut outputs: HashMap<_, _> = outputs.into_iter().collect();
+    ids.iter()
+        .map(|id| {
+            outputs
+                .remove(id)
+                .unwrap_or_else(|| Err(Error::InvalidResponse(format!("batch response is missing id {:?}", id))))
+        })
+        .collect()
+}
+
+/// Delivers a notification, waiting (and so pausing reads) while a bounded buffer is full.
+async fn notify(subscriptions: &mut BTreeMap<SubscriptionId, Subscription>, id: SubscriptionId, result: rpc::Value) {
+    if let Some(stream) = subscriptions.get_mut(&id) {
+        if let Err(e) = stream.send(result).await {
+            event!(error, "Error sending notification: {:?} (id: {:?}", e, id);
         }
+    } else {
+        event!(warn, "Got notification for unknown subscription (id: {:?})", id);
     }
 }
 
 enum TransportMessage {
     Request {
-        id: RequestId,
+        id: rpc::Id,
+        ids: Vec<rpc::Id>,
         request: String,
         sender: oneshot::Sender<BatchResult>,
     },
     Subscribe {
         id: SubscriptionId,
-        sink: mpsc::UnboundedSender<rpc::Value>,
+        sink: NotificationSender,
     },
     Unsubscribe {
         id: SubscriptionId,
@@ -365,6 +508,10 @@ enum TransportMessage {
 pub struct WebSocket {
     id: Arc<atomic::AtomicUsize>,
     requests: mpsc::UnboundedSender<TransportMessage>,
+    notification_buffer: NotificationBuffer,
+    id_strategy: IdStrategy,
+    events: Arc<ConnectionState>,
+    timeout: Option<Duration>,
 }
 
 impl fmt::Debug for WebSocket {
@@ -376,28 +523,123 @@ impl fmt::Debug for WebSocket {
 impl WebSocket {
     /// Create new WebSocket transport.
     pub async fn new(url: &str) -> error::Result<Self> {
+        Self::connect(url, None, None).await
+    }
+
+    /// Create new WebSocket transport authenticated with the current token of the provider.
+    ///
+    /// The token is sent as a bearer token in the handshake, replacing basic auth credentials of
+    /// the url. The transport doesn't reconnect, see `connect` to combine the provider with reconnects.
+    pub async fn with_auth_provider<A: AuthProvider + 'static>(url: &str, provider: A) -> error::Result<Self> {
+        Self::connect(url, Some(Arc::new(provider)), None).await
+    }
+
+    /// Creates a new WebSocket transport which reconnects when the connection is lost (e.g. the node restarts).
+    ///
+    /// Reconnection is attempted according to `backoff` (a `Duration` retries at a fixed interval), the
+    /// transport is closed once the backoff gives up. Requests in flight when the connection is lost
+    /// fail with a transport error, as do requests sent before the connection is re-established.
+    /// Subscription streams end, since the node doesn't keep subscriptions across connections.
+    pub async fn with_reconnect<B: Into<Backoff>>(url: &str, backoff: B) -> error::Result<Self> {
+        Self::connect(url, None, Some(backoff.into())).await
+    }
+
+    /// Creates a new WebSocket transport, optionally authenticated and reconnecting.
+    ///
+    /// See `with_auth_provider` and `with_reconnect`, the provider is asked for a fresh token at
+    /// every reconnection.
+    pub async fn connect(
+        url: &str,
+        auth: Option<Arc<dyn AuthProvider>>,
+        reconnect: Option<Backoff>,
+    ) -> error::Result<Self> {
         let id = Arc::new(atomic::AtomicUsize::new(1));
-        let task = WsServerTask::new(url).await?;
+        let task = WsServerTask::new(url, auth.as_deref()).await?;
+        let reconnect = reconnect.map(|backoff| Reconnect {
+            url: url.into(),
+            auth,
+            backoff,
+        });
+        let events = Arc::new(ConnectionState::default());
+        events.emit(ConnectionEvent::Connected);
         // TODO [ToDr] Not unbounded?
         let (sink, stream) = mpsc::unbounded();
         // Spawn background task for the transport.
         #[cfg(feature = "ws-tokio")]
-        tokio::spawn(task.into_task(stream));
+        tokio::spawn(task.into_task(stream, events.clone(), reconnect));
         #[cfg(feature = "ws-async-std")]
-        async_std::task::spawn(task.into_task(stream));
+        async_std::task::spawn(task.into_task(stream, events.clone(), reconnect));
+
+        Ok(Self {
+            id,
+            requests: sink,
+            notification_buffer: Default::default(),
+            id_strategy: Default::default(),
+            events,
+            timeout: None,
+        })
+    }
 
-        Ok(Self { id, requests: sink })
+    /// Sets how notifications of subsequent subscriptions are buffered.
+    ///
+    /// With [`NotificationBuffer::Bounded`] the connection is not read while a subscription
+    /// stream is full, which propagates the backpressure to the node.
+    pub fn with_notification_buffer(mut self, buffer: NotificationBuffer) -> Self {
+        self.notification_buffer = buffer;
+        self
+    }
+
+    /// Sets how JSON-RPC ids of requests are generated.
+    ///
+    /// Responses are matched to requests by these ids, so they must be unique among the
+    /// pending requests of the connection.
+    pub fn with_id_strategy(mut self, strategy: IdStrategy) -> Self {
+        self.id_strategy = strategy;
+        self
+    }
+
+    /// Fails requests (and batches) without a response within `timeout` with `TransportError::Timeout`.
+    ///
+    /// Without a timeout requests wait for the response as long as the connection is open.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the counter value of the next request, see [`IdStrategy`](crate::transports::IdStrategy#request-counter).
+    #[cfg(feature = "test-support")]
+    pub fn set_next_request_id(&self, id: RequestId) {
+        self.id.store(id, atomic::Ordering::Release);
     }
 
     fn send(&self, msg: TransportMessage) -> error::Result {
         self.requests.unbounded_send(msg).map_err(dropped_err)
     }
 
-    fn send_request(&self, id: RequestId, request: rpc::Request) -> error::Result<oneshot::Receiver<BatchResult>> {
+    fn send_request(
+        &self,
+        id: RequestId,
+        request: rpc::Request,
+        span: &helpers::RequestSpan,
+    ) -> error::Result<oneshot::Receiver<BatchResult>> {
+        // Responses (to batches) are matched by the id of the (first) call.
+        let ids: Vec<_> = match request {
+            rpc::Request::Single(ref call) => vec![helpers::id_of_call(call)],
+            rpc::Request::Batch(ref calls) => calls.iter().map(helpers::id_of_call).collect(),
+        };
+        let rpc_id = match ids.first() {
+            Some(rpc::Id::Null) | None => rpc::Id::Num(id as u64),
+            Some(rpc_id) => rpc_id.clone(),
+        };
         let request = helpers::to_string(&request);
-        log::debug!("[{}] Calling: {}", id, request);
+        span.in_scope(|| event!(debug, "[{}] Calling: {}", id, request));
         let (sender, receiver) = oneshot::channel();
-        self.send(TransportMessage::Request { id, request, sender })?;
+        self.send(TransportMessage::Request {
+            id: rpc_id,
+            ids,
+            request,
+            sender,
+        })?;
         Ok(receiver)
     }
 }
@@ -428,14 +670,23 @@ enum ResponseState {
 pub struct Response<R, T> {
     extract: T,
     state: ResponseState,
+    span: helpers::RequestSpan,
+    deadline: Option<Delay>,
     _data: std::marker::PhantomData<R>,
 }
 
 impl<R, T> Response<R, T> {
-    fn new(response: error::Result<oneshot::Receiver<BatchResult>>, extract: T) -> Self {
+    fn new(
+        response: error::Result<oneshot::Receiver<BatchResult>>,
+        extract: T,
+        span: helpers::RequestSpan,
+        timeout: Option<Duration>,
+    ) -> Self {
         Self {
             extract,
             state: ResponseState::Receiver(Some(response)),
+            span,
+            deadline: timeout.map(Delay::new),
             _data: Default::default(),
         }
     }
@@ -451,12 +702,29 @@ where
         loop {
             match self.state {
                 ResponseState::Receiver(ref mut res) => {
-                    let receiver = res.take().expect("Receiver state is active only once; qed")?;
-                    self.state = ResponseState::Waiting(receiver)
+                    match res.take().expect("Receiver state is active only once; qed") {
+                        Ok(receiver) => self.state = ResponseState::Waiting(receiver),
+                        Err(err) => {
+                            let result = Err(err);
+                            self.span.finish(&result);
+                            return Poll::Ready(result);
+                        }
+                    }
                 }
                 ResponseState::Waiting(ref mut future) => {
-                    let response = ready!(future.poll_unpin(cx)).map_err(dropped_err)?;
-                    return Poll::Ready((self.extract)(response));
+                    let response = match future.poll_unpin(cx) {
+                        Poll::Ready(response) => response.map_err(dropped_err),
+                        Poll::Pending => match self.deadline {
+                            Some(ref mut delay) => {
+                                ready!(delay.poll_unpin(cx));
+                                Err(Error::Transport(TransportError::Timeout))
+                            }
+                            None => return Poll::Pending,
+                        },
+                    };
+                    let result = response.and_then(&self.extract);
+                    self.span.finish(&result);
+                    return Poll::Ready(result);
                 }
             }
         }
@@ -468,14 +736,15 @@ impl Transport for WebSocket {
 
     fn prepare(&self, method: &str, params: Vec<rpc::Value>) -> (RequestId, rpc::Call) {
         let id = self.id.fetch_add(1, atomic::Ordering::AcqRel);
-        let request = helpers::build_request(id, method, params);
+        let request = self.id_strategy.build_request(id, method, params);
 
         (id, request)
     }
 
     fn send(&self, id: RequestId, request: rpc::Call) -> Self::Out {
-        let response = self.send_request(id, rpc::Request::Single(request));
-        Response::new(response, batch_to_single)
+        let span = helpers::RequestSpan::new("ws", id, &request);
+        let response = self.send_request(id, rpc::Request::Single(request), &span);
+        Response::new(response, batch_to_single, span, self.timeout)
     }
 }
 
@@ -489,17 +758,17 @@ impl BatchTransport for WebSocket {
         let mut it = requests.into_iter();
         let (id, first) = it.next().map(|x| (x.0, Some(x.1))).unwrap_or_else(|| (0, None));
         let requests = first.into_iter().chain(it.map(|x| x.1)).collect();
-        let response = self.send_request(id, rpc::Request::Batch(requests));
-        Response::new(response, batch_to_batch)
+        let span = helpers::RequestSpan::batch("ws", id);
+        let response = self.send_request(id, rpc::Request::Batch(requests), &span);
+        Response::new(response, batch_to_batch, span, self.timeout)
     }
 }
 
 impl DuplexTransport for WebSocket {
-    type NotificationStream = mpsc::UnboundedReceiver<rpc::Value>;
+    type NotificationStream = NotificationStream;
 
     fn subscribe(&self, id: SubscriptionId) -> error::Result<Self::NotificationStream> {
-        // TODO [ToDr] Not unbounded?
-        let (sink, stream) = mpsc::unbounded();
+        let (sink, stream) = self.notification_buffer.channel();
         self.send(TransportMessage::Subscribe { id, sink })?;
         Ok(stream)
     }
@@ -507,6 +776,10 @@ impl DuplexTransport for WebSocket {
     fn unsubscribe(&self, id: SubscriptionId) -> error::Result {
         self.send(TransportMessage::Unsubscribe { id })
     }
+
+    fn connection_events(&self) -> BoxStream<'static, ConnectionEvent> {
+        self.events.listen()
+    }
 }
 
 /// Compatibility layer between async-std and tokio
@@ -603,6 +876,162 @@ mod tests {
         assert_eq!(res.await, Ok(rpc::Value::String("x".into())));
     }
 
+    #[tokio::test]
+    async fn should_send_token_in_handshake() {
+        use tokio::io::AsyncReadExt;
+
+        // given
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("ws://user:pass@{}", listener.local_addr().unwrap());
+        let handshake = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            while !request.ends_with(b"\r\n\r\n") {
+                let mut buf = [0u8; 1024];
+                let read = socket.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..read]);
+            }
+            String::from_utf8(request).unwrap()
+        });
+
+        // when
+        let ws = WebSocket::with_auth_provider(&endpoint, || Ok("token".to_owned())).await;
+
+        // then
+        assert!(ws.is_err());
+        let request = handshake.await.unwrap();
+        assert!(request.contains("Authorization: Bearer token\r\n"), "{}", request);
+    }
+
+    #[tokio::test]
+    async fn should_time_out_requests_without_response() {
+        // given
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("ws://{}", listener.local_addr().unwrap());
+        tokio::spawn(silent_server(listener));
+        let ws = WebSocket::new(&endpoint)
+            .await
+            .unwrap()
+            .with_timeout(Duration::from_millis(10));
+
+        // when
+        let res = ws.execute("eth_accounts", vec![]);
+
+        // then
+        assert_eq!(res.await, Err(Error::Transport(TransportError::Timeout)));
+    }
+
+    #[tokio::test]
+    async fn should_reconnect_with_fresh_token() {
+        // given
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("ws://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            // the first connection is dropped right after the handshake
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut server = handshake::Server::new(BufReader::new(BufWriter::new(compat::compat(socket))));
+            let key = server.receive_request().await.unwrap().key();
+            let accept = handshake::server::Response::Accept { key, protocol: None };
+            server.send_response(&accept).await.unwrap();
+            drop(server);
+            silent_server(listener).await;
+        });
+        let tokens = Arc::new(atomic::AtomicUsize::new(0));
+        let provider = {
+            let tokens = tokens.clone();
+            move || Ok(format!("token-{}", tokens.fetch_add(1, atomic::Ordering::SeqCst)))
+        };
+
+        // when
+        let ws = WebSocket::connect(
+            &endpoint,
+            Some(Arc::new(provider)),
+            Some(Duration::from_millis(10).into()),
+        )
+        .await
+        .unwrap();
+        let mut events = ws.connection_events();
+
+        // then
+        assert_eq!(events.next().await, Some(ConnectionEvent::Connected));
+        assert!(matches!(
+            events.next().await,
+            Some(ConnectionEvent::Disconnected { .. })
+        ));
+        assert_eq!(events.next().await, Some(ConnectionEvent::Reconnecting { attempt: 1 }));
+        let mut event = events.next().await;
+        while let Some(ConnectionEvent::Reconnecting { .. }) = event {
+            event = events.next().await;
+        }
+        assert_eq!(event, Some(ConnectionEvent::Connected));
+        assert_eq!(tokens.load(atomic::Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn should_dispatch_responses_with_string_ids() {
+        // given
+        let mut pending = HashMap::new();
+        let (sender, mut rx) = oneshot::channel();
+        let ids = vec![rpc::Id::Str("client-1".into())];
+        pending.insert(rpc::Id::Str("client-1".into()), Pending { ids, sender });
+        let (sender, mut other) = oneshot::channel();
+        let ids = vec![rpc::Id::Num(1)];
+        pending.insert(rpc::Id::Num(1), Pending { ids, sender });
+
+        // when
+        let notification = handle_message(br#"{"jsonrpc":"2.0","id":"client-1","result":"x"}"#, &mut pending);
+
+        // then
+        assert!(notification.is_none());
+        assert_eq!(rx.try_recv(), Ok(Some(Ok(vec![Ok(rpc::Value::String("x".into()))]))));
+        assert_eq!(other.try_recv(), Ok(None));
+        assert_eq!(pending.len(), 1);
+    }
+
+    #[test]
+    fn should_match_batch_outputs_to_calls() {
+        // given
+        let mut pending = HashMap::new();
+        let (sender, mut rx) = oneshot::channel();
+        let ids = vec![rpc::Id::Num(1), rpc::Id::Num(2), rpc::Id::Num(3), rpc::Id::Num(4)];
+        pending.insert(rpc::Id::Num(1), Pending { ids, sender });
+
+        // when
+        let notification = handle_message(
+            br#"[
+                {"jsonrpc":"2.0","id":3,"error":{"code":-32000,"message":"failed"}},
+                {"jsonrpc":"2.0","id":2,"result":"0x2"},
+                {"jsonrpc":"2.0","id":1,"unexpected":"0x1"}
+            ]"#,
+            &mut pending,
+        );
+
+        // then
+        assert!(notification.is_none());
+        assert!(pending.is_empty());
+        let results = rx.try_recv().unwrap().unwrap().unwrap();
+        assert_eq!(results.len(), 4);
+        assert!(matches!(results[0], Err(Error::InvalidResponse(_))));
+        assert_eq!(results[1], Ok(rpc::Value::String("0x2".into())));
+        assert!(matches!(results[2], Err(Error::Rpc(ref err)) if err.message == "failed"));
+        assert_eq!(
+            results[3],
+            Err(Error::InvalidResponse("batch response is missing id Num(4)".into()))
+        );
+    }
+
+    /// Accepts a connection and never responds to requests.
+    async fn silent_server(listener: tokio::net::TcpListener) {
+        let (socket, _) = listener.accept().await.unwrap();
+        let mut server = handshake::Server::new(BufReader::new(BufWriter::new(compat::compat(socket))));
+        let key = server.receive_request().await.unwrap().key();
+        let accept = handshake::server::Response::Accept { key, protocol: None };
+        server.send_response(&accept).await.unwrap();
+        let (_sender, mut receiver) = server.into_builder().finish();
+        let mut data = Vec::new();
+        while receiver.receive_data(&mut data).await.is_ok() {}
+    }
+
     async fn server(listener: compat::TcpListener, addr: &str) {
         let mut incoming = TcpListenerStream::new(listener);
         println!("Listening on: {}", addr);
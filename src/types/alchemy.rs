@@ -0,0 +1,57 @@
+use crate::types::{Address, Transaction};
+use serde::{Deserialize, Serialize};
+
+/// Address filter of the `alchemy_minedTransactions` subscription.
+///
+/// A transaction matches if it is sent from `from` and to `to`, unset fields match any address.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct AlchemyAddressFilter {
+    /// Sender
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<Address>,
+    /// Recipient
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<Address>,
+}
+
+impl AlchemyAddressFilter {
+    /// Matches transactions sent from given address.
+    pub fn from(address: Address) -> Self {
+        AlchemyAddressFilter {
+            from: Some(address),
+            to: None,
+        }
+    }
+
+    /// Matches transactions sent to given address.
+    pub fn to(address: Address) -> Self {
+        AlchemyAddressFilter {
+            from: None,
+            to: Some(address),
+        }
+    }
+}
+
+/// Notification of the `alchemy_minedTransactions` subscription.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct AlchemyMinedTransaction {
+    /// Whether the transaction was removed from the canonical chain by a reorganization
+    #[serde(default)]
+    pub removed: bool,
+    /// Mined transaction
+    pub transaction: Transaction,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_serialize_address_filter() {
+        let filter = AlchemyAddressFilter::to(Address::from_low_u64_be(1));
+        assert_eq!(
+            serde_json::to_string(&filter).unwrap(),
+            r#"{"to":"0x0000000000000000000000000000000000000001"}"#
+        );
+    }
+}
@@ -1,5 +1,6 @@
-use crate::types::{Bytes, H160, H2048, H256, H64, U256, U64};
+use crate::types::{Address, Bytes, H160, H2048, H256, H64, U256, U64};
 use serde::{de::Error, ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
 
 /// The block header type returned from RPC calls.
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
@@ -14,8 +15,13 @@ pub struct BlockHeader {
     #[cfg_attr(feature = "allow-missing-fields", serde(default))]
     pub uncles_hash: H256,
     /// Miner/author's address.
+    ///
+    /// Some clients only return the `author` field, see `beneficiary` for an accessor handling both.
     #[serde(rename = "miner", default, deserialize_with = "null_to_default")]
     pub author: H160,
+    /// Author's address from the `author` field, returned instead of or next to `miner` by some clients.
+    #[serde(rename = "author", default, skip_serializing_if = "Option::is_none")]
+    pub author_alias: Option<H160>,
     /// State root hash
     #[serde(rename = "stateRoot")]
     pub state_root: H256,
@@ -53,6 +59,18 @@ pub struct BlockHeader {
     pub mix_hash: Option<H256>,
     /// Nonce
     pub nonce: Option<H64>,
+    /// Withdrawals root hash (if past Shanghai)
+    #[serde(rename = "withdrawalsRoot", default, skip_serializing_if = "Option::is_none")]
+    pub withdrawals_root: Option<H256>,
+    /// Blob gas used by the transactions of the block (if past Cancun)
+    #[serde(rename = "blobGasUsed", default, skip_serializing_if = "Option::is_none")]
+    pub blob_gas_used: Option<U64>,
+    /// Excess blob gas, determining the blob base fee (if past Cancun)
+    #[serde(rename = "excessBlobGas", default, skip_serializing_if = "Option::is_none")]
+    pub excess_blob_gas: Option<U64>,
+    /// Root of the parent beacon block (if past Cancun)
+    #[serde(rename = "parentBeaconBlockRoot", default, skip_serializing_if = "Option::is_none")]
+    pub parent_beacon_block_root: Option<H256>,
 }
 
 /// The block type returned from RPC calls.
@@ -69,8 +87,13 @@ pub struct Block<TX> {
     #[cfg_attr(feature = "allow-missing-fields", serde(default))]
     pub uncles_hash: H256,
     /// Miner/author's address.
+    ///
+    /// Some clients only return the `author` field, see `beneficiary` for an accessor handling both.
     #[serde(rename = "miner", default, deserialize_with = "null_to_default")]
     pub author: H160,
+    /// Author's address from the `author` field, returned instead of or next to `miner` by some clients.
+    #[serde(rename = "author", default, skip_serializing_if = "Option::is_none")]
+    pub author_alias: Option<H160>,
     /// State root hash
     #[serde(rename = "stateRoot")]
     pub state_root: H256,
@@ -121,6 +144,71 @@ pub struct Block<TX> {
     pub mix_hash: Option<H256>,
     /// Nonce
     pub nonce: Option<H64>,
+    /// Withdrawals root hash (if past Shanghai)
+    #[serde(rename = "withdrawalsRoot", default, skip_serializing_if = "Option::is_none")]
+    pub withdrawals_root: Option<H256>,
+    /// Validator withdrawals processed in this block (if past Shanghai)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub withdrawals: Option<Vec<Withdrawal>>,
+    /// Blob gas used by the transactions of the block (if past Cancun)
+    #[serde(rename = "blobGasUsed", default, skip_serializing_if = "Option::is_none")]
+    pub blob_gas_used: Option<U64>,
+    /// Excess blob gas, determining the blob base fee (if past Cancun)
+    #[serde(rename = "excessBlobGas", default, skip_serializing_if = "Option::is_none")]
+    pub excess_blob_gas: Option<U64>,
+    /// Root of the parent beacon block (if past Cancun)
+    #[serde(rename = "parentBeaconBlockRoot", default, skip_serializing_if = "Option::is_none")]
+    pub parent_beacon_block_root: Option<H256>,
+    /// Fields not covered above, e.g. chain specific ones like Arbitrum's `l1BlockNumber`.
+    #[serde(flatten)]
+    pub other: BTreeMap<String, serde_json::Value>,
+}
+
+impl BlockHeader {
+    /// Returns the block author, from either the `miner` or the `author` field.
+    pub fn beneficiary(&self) -> H160 {
+        beneficiary(self.author, self.author_alias)
+    }
+}
+
+/// Number and hash of a block, decoded from a block object without the rest of its fields.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct BlockNumberAndHash {
+    /// Block number. None if pending.
+    pub number: Option<U64>,
+    /// Hash of the block. None if pending.
+    pub hash: Option<H256>,
+}
+
+impl<TX> Block<TX> {
+    /// Returns the block author, from either the `miner` or the `author` field.
+    ///
+    /// Note that on chains sealed by validators (e.g. Polygon PoS) both fields may be zero,
+    /// in which case the producer has to be recovered from the seal (see `Bor::author`).
+    pub fn beneficiary(&self) -> H160 {
+        beneficiary(self.author, self.author_alias)
+    }
+}
+
+fn beneficiary(miner: H160, author: Option<H160>) -> H160 {
+    match author {
+        Some(author) if miner.is_zero() => author,
+        _ => miner,
+    }
+}
+
+/// A validator withdrawal from the consensus layer (EIP-4895).
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Withdrawal {
+    /// Monotonically increasing withdrawal index
+    pub index: U64,
+    /// Index of the validator the withdrawal belongs to
+    pub validator_index: U64,
+    /// Recipient of the withdrawn ether
+    pub address: Address,
+    /// Withdrawn amount in Gwei
+    pub amount: U256,
 }
 
 fn null_to_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
@@ -288,6 +376,86 @@ mod tests {
         assert_eq!(block.author, Default::default());
     }
 
+    fn chain_block(fields: Value) -> Value {
+        let mut json = serde_json::json!({
+            "number": "0x1b4",
+            "hash": "0x0e670ec64341771606e55d6b4ca35a1a6b75ee3d5145a99d05921026d1527331",
+            "parentHash": "0x9646252be9520f6e71339a8df9c55e4d7619deeb018d2a3f2d21fc165dde5eb5",
+            "sha3Uncles": "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347",
+            "logsBloom": null,
+            "transactionsRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+            "receiptsRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+            "stateRoot": "0xd5855eb08b3387c0af375e9cdb6acfc05eb8f519e419b874b6ff2ffda7ed1dff",
+            "difficulty": "0x2",
+            "extraData": "0x",
+            "gasLimit": "0x1c9c380",
+            "gasUsed": "0x9f759",
+            "timestamp": "0x64373057",
+            "transactions": [],
+            "uncles": []
+        });
+        json.as_object_mut()
+            .unwrap()
+            .extend(fields.as_object().unwrap().clone());
+        json
+    }
+
+    // The values below are synthetic, only the presence of `miner`/`author` follows what the
+    // respective clients return.
+    #[test]
+    fn block_beneficiary() {
+        // Polygon PoS: the producer is only in the seal
+        let json = chain_block(serde_json::json!({
+            "miner": "0x0000000000000000000000000000000000000000",
+        }));
+        let block: Block<()> = serde_json::from_value(json).unwrap();
+        assert_eq!(block.beneficiary(), H160::zero());
+
+        // BSC: `miner` only
+        let json = chain_block(serde_json::json!({
+            "miner": "0x0000000000000000000000000000000000000001",
+        }));
+        let block: Block<()> = serde_json::from_value(json).unwrap();
+        assert_eq!(block.beneficiary(), H160::from_low_u64_be(1));
+        assert_eq!(block.author_alias, None);
+
+        // Gnosis (Nethermind): both fields
+        let json = chain_block(serde_json::json!({
+            "author": "0x0000000000000000000000000000000000000002",
+            "miner": "0x0000000000000000000000000000000000000002",
+        }));
+        let block: Block<()> = serde_json::from_value(json).unwrap();
+        assert_eq!(block.beneficiary(), H160::from_low_u64_be(2));
+        assert_eq!(block.author_alias, Some(H160::from_low_u64_be(2)));
+
+        // `author` only
+        let json = chain_block(serde_json::json!({
+            "author": "0x0000000000000000000000000000000000000003",
+        }));
+        let block: Block<()> = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(block.beneficiary(), H160::from_low_u64_be(3));
+        assert_eq!(block.author, H160::zero());
+        assert_eq!(serde_json::to_value(&block).unwrap()["author"], json["author"]);
+    }
+
+    #[test]
+    fn block_keeps_unknown_fields() {
+        let json = chain_block(serde_json::json!({
+            "miner": "0x0000000000000000000000000000000000000001",
+            "blobGasUsed": "0x20000",
+            "l1BlockNumber": "0x12a05f2",
+        }));
+
+        let block: Block<()> = serde_json::from_value(json).unwrap();
+
+        assert_eq!(block.blob_gas_used, Some(0x20000.into()));
+        assert_eq!(block.other.len(), 1);
+        assert_eq!(block.other["l1BlockNumber"], "0x12a05f2");
+        let json = serde_json::to_value(&block).unwrap();
+        assert_eq!(json["blobGasUsed"], "0x20000");
+        assert_eq!(json["l1BlockNumber"], "0x12a05f2");
+    }
+
     #[test]
     fn post_london_block() {
         let json = serde_json::json!(
@@ -325,6 +493,66 @@ mod tests {
         assert_eq!(block.base_fee_per_gas, Some(U256::from(7)));
     }
 
+    #[test]
+    fn post_shanghai_block() {
+        let json = serde_json::json!(
+        {
+            "baseFeePerGas": "0x7",
+            "miner": "0x0000000000000000000000000000000000000001",
+            "number": "0x1b4",
+            "hash": "0x0e670ec64341771606e55d6b4ca35a1a6b75ee3d5145a99d05921026d1527331",
+            "parentHash": "0x9646252be9520f6e71339a8df9c55e4d7619deeb018d2a3f2d21fc165dde5eb5",
+            "mixHash": "0x1010101010101010101010101010101010101010101010101010101010101010",
+            "nonce": "0x0000000000000000",
+            "sha3Uncles": "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347",
+            "logsBloom":  "0x0e670ec64341771606e55d6b4ca35a1a6b75ee3d5145a99d05921026d15273310e670ec64341771606e55d6b4ca35a1a6b75ee3d5145a99d05921026d15273310e670ec64341771606e55d6b4ca35a1a6b75ee3d5145a99d05921026d15273310e670ec64341771606e55d6b4ca35a1a6b75ee3d5145a99d05921026d15273310e670ec64341771606e55d6b4ca35a1a6b75ee3d5145a99d05921026d15273310e670ec64341771606e55d6b4ca35a1a6b75ee3d5145a99d05921026d15273310e670ec64341771606e55d6b4ca35a1a6b75ee3d5145a99d05921026d15273310e670ec64341771606e55d6b4ca35a1a6b75ee3d5145a99d05921026d1527331",
+            "transactionsRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+            "receiptsRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+            "stateRoot": "0xd5855eb08b3387c0af375e9cdb6acfc05eb8f519e419b874b6ff2ffda7ed1dff",
+            "difficulty": "0x0",
+            "totalDifficulty": "0xc70d815d562d3cfa955",
+            "extraData": "0x",
+            "size": "0x27f07",
+            "gasLimit": "0x1c9c380",
+            "gasUsed": "0x9f759",
+            "timestamp": "0x64373057",
+            "transactions": [],
+            "uncles": [],
+            "withdrawalsRoot": "0x7a4ecf19774d15cf9c15adf0dd8e8a250c128b26c9e2ab2a08d6c9c8ffbd104f",
+            "withdrawals": [
+              {
+                "index": "0x0",
+                "validatorIndex": "0x4f4d",
+                "address": "0x388ea662ef2c223ec0b047d41bf3c0f362142ad5",
+                "amount": "0x3b9aca00"
+              }
+            ]
+          }
+        );
+
+        let block: Block<()> = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(
+            block.withdrawals_root,
+            Some(
+                "0x7a4ecf19774d15cf9c15adf0dd8e8a250c128b26c9e2ab2a08d6c9c8ffbd104f"
+                    .parse()
+                    .unwrap()
+            )
+        );
+        assert_eq!(
+            block.withdrawals,
+            Some(vec![Withdrawal {
+                index: 0.into(),
+                validator_index: 0x4f4d.into(),
+                address: "0x388ea662ef2c223ec0b047d41bf3c0f362142ad5".parse().unwrap(),
+                amount: 1_000_000_000.into(),
+            }])
+        );
+
+        let header: BlockHeader = serde_json::from_value(json).unwrap();
+        assert_eq!(header.withdrawals_root, block.withdrawals_root);
+    }
+
     #[test]
     fn serialize_deserialize_block_number() {
         // BlockNumber::Latest
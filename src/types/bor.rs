@@ -0,0 +1,109 @@
+use crate::types::{Address, Bytes, H256, H520, U256};
+use serde::{Deserialize, Serialize};
+
+const EXTRA_VANITY: usize = 32;
+const EXTRA_SEAL: usize = 65;
+const VALIDATOR_BYTES: usize = 40;
+
+/// Validator returned by `bor_getCurrentValidators`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct BorValidator {
+    /// Validator id
+    #[serde(rename = "ID")]
+    pub id: u64,
+    /// Signer address
+    pub signer: Address,
+    /// Voting power
+    #[serde(rename = "power")]
+    pub voting_power: i64,
+    /// Proposer priority
+    #[serde(rename = "accum")]
+    pub proposer_priority: i64,
+}
+
+/// Bor specific content of a block `extraData` field.
+///
+/// The extra data consists of a 32 bytes vanity prefix, followed by the next validator set
+/// (only present in the last block of a sprint) and a 65 bytes seal signature of the block producer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BorExtraData {
+    /// Vanity prefix
+    pub vanity: H256,
+    /// Validator set as `(signer, voting power)` pairs
+    pub validators: Vec<(Address, U256)>,
+    /// Block producer signature
+    pub seal: H520,
+}
+
+impl BorExtraData {
+    /// Parses the `extraData` field of a Bor block.
+    ///
+    /// Returns `None` if the data doesn't follow the Bor layout.
+    pub fn from_extra_data(extra_data: &Bytes) -> Option<Self> {
+        let data = &extra_data.0;
+        if data.len() < EXTRA_VANITY + EXTRA_SEAL {
+            return None;
+        }
+        let validators = data[EXTRA_VANITY..data.len() - EXTRA_SEAL].chunks_exact(VALIDATOR_BYTES);
+        if !validators.remainder().is_empty() {
+            return None;
+        }
+
+        Some(BorExtraData {
+            vanity: H256::from_slice(&data[..EXTRA_VANITY]),
+            validators: validators
+                .map(|validator| {
+                    (
+                        Address::from_slice(&validator[..20]),
+                        U256::from_big_endian(&validator[20..]),
+                    )
+                })
+                .collect(),
+            seal: H520::from_slice(&data[data.len() - EXTRA_SEAL..]),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_deserialize_validator() {
+        let validator: BorValidator = serde_json::from_str(
+            r#"{"ID":1,"signer":"0x0000000000000000000000000000000000000123","power":10,"accum":-20}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            validator,
+            BorValidator {
+                id: 1,
+                signer: Address::from_low_u64_be(0x123),
+                voting_power: 10,
+                proposer_priority: -20,
+            }
+        );
+    }
+
+    #[test]
+    fn should_parse_extra_data() {
+        let mut data = vec![1u8; EXTRA_VANITY];
+        data.extend_from_slice(Address::from_low_u64_be(0x123).as_bytes());
+        data.extend_from_slice(&[0u8; 19]);
+        data.push(5);
+        data.extend_from_slice(&[2u8; EXTRA_SEAL]);
+
+        let extra = BorExtraData::from_extra_data(&data.into()).unwrap();
+        assert_eq!(extra.vanity, H256::repeat_byte(1));
+        assert_eq!(extra.validators, vec![(Address::from_low_u64_be(0x123), 5.into())]);
+        assert_eq!(extra.seal, H520::repeat_byte(2));
+
+        let mut data = vec![0u8; EXTRA_VANITY + EXTRA_SEAL];
+        let extra = BorExtraData::from_extra_data(&data.clone().into()).unwrap();
+        assert!(extra.validators.is_empty());
+
+        data.push(0);
+        assert_eq!(BorExtraData::from_extra_data(&data.into()), None);
+    }
+}
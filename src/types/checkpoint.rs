@@ -0,0 +1,153 @@
+use crate::types::{Log, H256};
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// Current version of the checkpoint formats.
+const CHECKPOINT_VERSION: u32 = 1;
+
+fn default_version() -> u32 {
+    CHECKPOINT_VERSION
+}
+
+fn deserialize_version<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+    let version = u32::deserialize(deserializer)?;
+    if version > CHECKPOINT_VERSION {
+        return Err(serde::de::Error::custom(format!(
+            "unsupported checkpoint version {}, expected at most {}",
+            version, CHECKPOINT_VERSION
+        )));
+    }
+    Ok(version)
+}
+
+/// Position of a log in the chain, ordered by block and log index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct LogKey {
+    /// Block number
+    #[serde(rename = "blockNumber")]
+    pub block_number: u64,
+    /// Index of the log in the block
+    #[serde(rename = "logIndex")]
+    pub log_index: u64,
+}
+
+impl LogKey {
+    /// Returns the position of a mined log, `None` for pending logs.
+    pub fn from_log(log: &Log) -> Option<Self> {
+        Some(LogKey {
+            block_number: log.block_number?.as_u64(),
+            log_index: log.log_index?.as_u64(),
+        })
+    }
+}
+
+/// Progress of a log stream (`helpers::LogPager`), to be persisted by restartable indexers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogCheckpoint {
+    /// Format version
+    #[serde(default = "default_version", deserialize_with = "deserialize_version")]
+    pub version: u32,
+    /// The last processed log, all logs up to and including it are skipped when resuming.
+    pub last: LogKey,
+}
+
+impl LogCheckpoint {
+    /// Checkpoint after given log was processed, `None` for pending logs.
+    pub fn after_log(log: &Log) -> Option<Self> {
+        LogKey::from_log(log).map(|last| LogCheckpoint {
+            version: CHECKPOINT_VERSION,
+            last,
+        })
+    }
+
+    /// Checkpoint after all logs of given block were processed.
+    pub fn after_block(block_number: u64) -> Self {
+        LogCheckpoint {
+            version: CHECKPOINT_VERSION,
+            last: LogKey {
+                block_number,
+                log_index: u64::MAX,
+            },
+        }
+    }
+
+    /// Returns the first block which might contain unprocessed logs.
+    pub fn next_block(&self) -> u64 {
+        if self.last.log_index == u64::MAX {
+            self.last.block_number.saturating_add(1)
+        } else {
+            self.last.block_number
+        }
+    }
+
+    /// Whether given log was already processed.
+    pub fn is_processed(&self, log: &Log) -> bool {
+        matches!(LogKey::from_log(log), Some(key) if key <= self.last)
+    }
+}
+
+/// A canonical block tracked by a checkpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockRef {
+    /// Block number
+    pub number: u64,
+    /// Block hash
+    pub hash: H256,
+}
+
+/// Tracked canonical chain of a `confirm::ReorgWatcher` (the reorg window).
+///
+/// Restoring the window allows reorgs which happened while the indexer was offline to be detected.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainCheckpoint {
+    /// Format version
+    #[serde(default = "default_version", deserialize_with = "deserialize_version")]
+    pub version: u32,
+    /// Tracked blocks, oldest first
+    pub blocks: Vec<BlockRef>,
+}
+
+impl ChainCheckpoint {
+    /// Creates a checkpoint from tracked blocks, oldest first.
+    pub fn new(blocks: Vec<BlockRef>) -> Self {
+        ChainCheckpoint {
+            version: CHECKPOINT_VERSION,
+            blocks,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_roundtrip_log_checkpoint() {
+        let log: Log = serde_json::from_str(
+            r#"{
+            "address": "0x0000000000000000000000000000000000000123",
+            "topics": [],
+            "data": "0x",
+            "blockNumber": "0x10",
+            "logIndex": "0x2"
+        }"#,
+        )
+        .unwrap();
+        let checkpoint = LogCheckpoint::after_log(&log).unwrap();
+
+        let json = serde_json::to_string(&checkpoint).unwrap();
+        assert_eq!(json, r#"{"version":1,"last":{"blockNumber":16,"logIndex":2}}"#);
+        assert_eq!(serde_json::from_str::<LogCheckpoint>(&json).unwrap(), checkpoint);
+        assert!(checkpoint.is_processed(&log));
+        assert_eq!(checkpoint.next_block(), 16);
+        assert_eq!(LogCheckpoint::after_block(16).next_block(), 17);
+    }
+
+    #[test]
+    fn should_reject_unknown_version() {
+        let result = serde_json::from_str::<ChainCheckpoint>(r#"{"version":2,"blocks":[]}"#);
+        assert!(result.is_err());
+
+        let checkpoint = serde_json::from_str::<ChainCheckpoint>(r#"{"version":1,"blocks":[]}"#).unwrap();
+        assert_eq!(checkpoint, ChainCheckpoint::new(vec![]));
+    }
+}
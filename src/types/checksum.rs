@@ -0,0 +1,192 @@
+//! EIP-55 and EIP-1191 checksummed addresses.
+
+use crate::{signing::keccak256, types::Address};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, str::FromStr};
+
+/// An error parsing a checksummed address.
+#[derive(Debug, derive_more::Display, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksumError {
+    /// Not a `0x`-prefixed, 20 bytes long hex string.
+    #[display(fmt = "Invalid address")]
+    InvalidAddress,
+    /// Mixed-case address with a wrong checksum.
+    #[display(fmt = "Invalid address checksum")]
+    InvalidChecksum,
+}
+
+impl std::error::Error for ChecksumError {}
+
+/// Checksum helpers for [`Address`].
+pub trait AddressChecksum: Sized {
+    /// Formats the address with mixed-case checksum.
+    ///
+    /// Uses EIP-55 if `chain_id` is `None`, or the chain-specific EIP-1191 checksum
+    /// (used e.g. by RSK) otherwise.
+    fn to_checksum(&self, chain_id: Option<u64>) -> String;
+
+    /// Parses a `0x`-prefixed address, validating its EIP-55 checksum.
+    ///
+    /// All-lowercase and all-uppercase addresses carry no checksum and are accepted as is.
+    fn from_checksum(s: &str) -> Result<Self, ChecksumError> {
+        Self::from_checksum_with_chain(s, None)
+    }
+
+    /// Parses a `0x`-prefixed address, validating its checksum for given chain.
+    ///
+    /// Same as `from_checksum` if `chain_id` is `None`, validates the EIP-1191 checksum otherwise.
+    fn from_checksum_with_chain(s: &str, chain_id: Option<u64>) -> Result<Self, ChecksumError>;
+}
+
+impl AddressChecksum for Address {
+    fn to_checksum(&self, chain_id: Option<u64>) -> String {
+        let hex = hex::encode(self.as_bytes());
+        let hash = match chain_id {
+            Some(chain_id) => keccak256(format!("{}0x{}", chain_id, hex).as_bytes()),
+            None => keccak256(hex.as_bytes()),
+        };
+        let checksummed: String = hex
+            .chars()
+            .enumerate()
+            .map(|(i, c)| {
+                let nibble = (hash[i / 2] >> if i % 2 == 0 { 4 } else { 0 }) & 0xf;
+                if nibble >= 8 {
+                    c.to_ascii_uppercase()
+                } else {
+                    c
+                }
+            })
+            .collect();
+        format!("0x{}", checksummed)
+    }
+
+    fn from_checksum_with_chain(s: &str, chain_id: Option<u64>) -> Result<Self, ChecksumError> {
+        let hex = s.strip_prefix("0x").ok_or(ChecksumError::InvalidAddress)?;
+        if hex.len() != 40 {
+            return Err(ChecksumError::InvalidAddress);
+        }
+        let address = Address::from_str(hex).map_err(|_| ChecksumError::InvalidAddress)?;
+        let mixed_case = hex.chars().any(|c| c.is_ascii_lowercase()) && hex.chars().any(|c| c.is_ascii_uppercase());
+        if mixed_case && address.to_checksum(chain_id) != s {
+            return Err(ChecksumError::InvalidChecksum);
+        }
+        Ok(address)
+    }
+}
+
+/// Address (de)serialized in EIP-55 checksummed form.
+///
+/// Deserialization accepts unchecksummed addresses, but rejects mixed-case ones with a wrong checksum.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ChecksumAddress(pub Address);
+
+impl From<Address> for ChecksumAddress {
+    fn from(address: Address) -> Self {
+        ChecksumAddress(address)
+    }
+}
+
+impl From<ChecksumAddress> for Address {
+    fn from(address: ChecksumAddress) -> Self {
+        address.0
+    }
+}
+
+impl fmt::Display for ChecksumAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0.to_checksum(None))
+    }
+}
+
+impl FromStr for ChecksumAddress {
+    type Err = ChecksumError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Address::from_checksum(s).map(ChecksumAddress)
+    }
+}
+
+impl Serialize for ChecksumAddress {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0.to_checksum(None))
+    }
+}
+
+impl<'de> Deserialize<'de> for ChecksumAddress {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EIP55: &[&str] = &[
+        "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+        "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+        "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+        "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+    ];
+
+    #[test]
+    fn should_checksum_eip55() {
+        for expected in EIP55 {
+            let address: Address = expected[2..].to_lowercase().parse().unwrap();
+            assert_eq!(&address.to_checksum(None), expected);
+            assert_eq!(Address::from_checksum(expected), Ok(address));
+        }
+    }
+
+    #[test]
+    fn should_checksum_eip1191() {
+        let address: Address = "5aaeb6053f3e94c9b9a09f33669435e7ef1beaed".parse().unwrap();
+        assert_eq!(
+            address.to_checksum(Some(30)),
+            "0x5aaEB6053f3e94c9b9a09f33669435E7ef1bEAeD"
+        );
+        assert_eq!(
+            address.to_checksum(Some(31)),
+            "0x5aAeb6053F3e94c9b9A09F33669435E7EF1BEaEd"
+        );
+    }
+
+    #[test]
+    fn should_validate_eip1191_checksum() {
+        let rsk = "0x5aaEB6053f3e94c9b9a09f33669435E7ef1bEAeD";
+        let address: Address = rsk[2..].to_lowercase().parse().unwrap();
+        assert_eq!(Address::from_checksum_with_chain(rsk, Some(30)), Ok(address));
+        assert_eq!(
+            Address::from_checksum_with_chain(rsk, Some(31)),
+            Err(ChecksumError::InvalidChecksum)
+        );
+        assert_eq!(Address::from_checksum(rsk), Err(ChecksumError::InvalidChecksum));
+        assert_eq!(
+            Address::from_checksum_with_chain(EIP55[0], None),
+            Address::from_checksum(EIP55[0])
+        );
+    }
+
+    #[test]
+    fn should_validate_checksum() {
+        let lower = EIP55[0].to_lowercase();
+        assert!(Address::from_checksum(&lower).is_ok());
+        assert!(Address::from_checksum(&format!("0x{}", lower[2..].to_uppercase())).is_ok());
+        assert_eq!(
+            Address::from_checksum("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD"),
+            Err(ChecksumError::InvalidChecksum)
+        );
+        assert_eq!(Address::from_checksum(&lower[2..]), Err(ChecksumError::InvalidAddress));
+        assert_eq!(Address::from_checksum("0x5aAeb6"), Err(ChecksumError::InvalidAddress));
+    }
+
+    #[test]
+    fn should_serialize_checksummed() {
+        let address: ChecksumAddress = EIP55[1].to_lowercase().parse().unwrap();
+        let json = serde_json::to_string(&address).unwrap();
+        assert_eq!(json, format!("\"{}\"", EIP55[1]));
+        assert_eq!(serde_json::from_str::<ChecksumAddress>(&json).unwrap(), address);
+        assert!(serde_json::from_str::<ChecksumAddress>("\"0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD\"").is_err());
+    }
+}
@@ -0,0 +1,104 @@
+use crate::types::{Address, H256};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Clique (PoA) voting state at a block, returned by `clique_getSnapshot`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CliqueSnapshot {
+    /// Block number of the snapshot
+    pub number: u64,
+    /// Block hash of the snapshot
+    pub hash: H256,
+    /// Authorized signers
+    #[serde(serialize_with = "serialize_signers", deserialize_with = "deserialize_signers")]
+    pub signers: BTreeSet<Address>,
+    /// Signers of the recent blocks, by block number
+    pub recents: BTreeMap<u64, Address>,
+    /// Votes cast in the current epoch, in chronological order
+    pub votes: Vec<CliqueVote>,
+    /// Current vote tally of the proposed signers
+    pub tally: BTreeMap<Address, CliqueTally>,
+}
+
+/// A vote of a signer to authorize or deauthorize an address.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CliqueVote {
+    /// Signer casting the vote
+    pub signer: Address,
+    /// Block the vote was cast in
+    pub block: u64,
+    /// Address voted on
+    pub address: Address,
+    /// Whether to authorize or deauthorize the address
+    pub authorize: bool,
+}
+
+/// Vote tally of an address.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CliqueTally {
+    /// Whether the votes are to authorize or deauthorize the address
+    pub authorize: bool,
+    /// Number of votes
+    pub votes: u64,
+}
+
+/// Signers are sent as a map of addresses to empty objects.
+fn serialize_signers<S: Serializer>(signers: &BTreeSet<Address>, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(signers.iter().map(|signer| (signer, BTreeMap::<(), ()>::new())))
+}
+
+fn deserialize_signers<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BTreeSet<Address>, D::Error> {
+    let signers = BTreeMap::<Address, serde::de::IgnoredAny>::deserialize(deserializer)?;
+    Ok(signers.into_keys().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_deserialize_snapshot() {
+        let json = r#"{
+            "number": 12,
+            "hash": "0x0000000000000000000000000000000000000000000000000000000000000123",
+            "signers": {"0x0000000000000000000000000000000000000001": {}, "0x0000000000000000000000000000000000000002": {}},
+            "recents": {"11": "0x0000000000000000000000000000000000000001", "12": "0x0000000000000000000000000000000000000002"},
+            "votes": [{
+                "signer": "0x0000000000000000000000000000000000000001",
+                "block": 10,
+                "address": "0x0000000000000000000000000000000000000003",
+                "authorize": true
+            }],
+            "tally": {"0x0000000000000000000000000000000000000003": {"authorize": true, "votes": 1}}
+        }"#;
+
+        let snapshot: CliqueSnapshot = serde_json::from_str(json).unwrap();
+
+        assert_eq!(snapshot.number, 12);
+        assert_eq!(
+            snapshot.signers,
+            vec![Address::from_low_u64_be(1), Address::from_low_u64_be(2)]
+                .into_iter()
+                .collect()
+        );
+        assert_eq!(snapshot.recents.get(&12), Some(&Address::from_low_u64_be(2)));
+        assert_eq!(
+            snapshot.votes,
+            vec![CliqueVote {
+                signer: Address::from_low_u64_be(1),
+                block: 10,
+                address: Address::from_low_u64_be(3),
+                authorize: true,
+            }]
+        );
+        assert_eq!(
+            snapshot.tally.get(&Address::from_low_u64_be(3)),
+            Some(&CliqueTally {
+                authorize: true,
+                votes: 1
+            })
+        );
+        let reserialized: CliqueSnapshot = serde_json::from_value(serde_json::to_value(&snapshot).unwrap()).unwrap();
+        assert_eq!(reserialized, snapshot);
+    }
+}
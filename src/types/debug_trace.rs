@@ -0,0 +1,586 @@
+//! Types for the Geth `debug` tracing API and utilities to profile the traces.
+use crate::types::{Address, Bytes, U256};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+
+/// Options of `debug_traceTransaction` and `debug_traceCall`.
+///
+/// The default options use the struct logger (opcode level traces).
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TracingOptions {
+    /// Name of the built-in tracer (e.g. `callTracer`) or JavaScript tracer code.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tracer: Option<String>,
+    /// Configuration of the tracer.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tracer_config: Option<serde_json::Value>,
+    /// Do not capture storage (struct logger only).
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub disable_storage: bool,
+    /// Do not capture the stack (struct logger only).
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub disable_stack: bool,
+    /// Capture memory (struct logger only).
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub enable_memory: bool,
+    /// Capture return data (struct logger only).
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub enable_return_data: bool,
+    /// Timeout of the tracing, e.g. `10s`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<String>,
+}
+
+impl TracingOptions {
+    /// Options using the built-in `callTracer`.
+    pub fn call_tracer() -> Self {
+        TracingOptions {
+            tracer: Some("callTracer".into()),
+            ..Default::default()
+        }
+    }
+}
+
+/// Result of a transaction trace.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum GethTrace {
+    /// Output of the struct logger.
+    Default(DefaultFrame),
+    /// Output of the `callTracer`.
+    CallTracer(Box<CallFrame>),
+    /// Output of any other tracer.
+    Unknown(serde_json::Value),
+}
+
+/// Output of the struct logger.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DefaultFrame {
+    /// Whether the transaction failed.
+    pub failed: bool,
+    /// Gas used by the transaction.
+    pub gas: u64,
+    /// Hex encoded return value.
+    pub return_value: String,
+    /// Executed opcodes.
+    pub struct_logs: Vec<StructLog>,
+}
+
+/// Single executed opcode.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLog {
+    /// Program counter.
+    pub pc: u64,
+    /// Opcode name.
+    pub op: String,
+    /// Gas remaining before executing the opcode.
+    pub gas: u64,
+    /// Gas cost of the opcode (including gas forwarded to sub calls).
+    pub gas_cost: u64,
+    /// Call depth, starting at `1`.
+    pub depth: u64,
+    /// Error of the opcode, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Stack before executing the opcode (top of the stack is last).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stack: Option<Vec<U256>>,
+    /// Memory before executing the opcode as hex encoded 32 byte words.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory: Option<Vec<String>>,
+    /// Storage slots accessed so far.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub storage: Option<BTreeMap<String, String>>,
+    /// Gas refund counter.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refund: Option<u64>,
+}
+
+/// Output of the `callTracer`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallFrame {
+    /// Type of the call (`CALL`, `STATICCALL`, `CREATE`, ...).
+    #[serde(rename = "type")]
+    pub call_type: String,
+    /// Caller.
+    pub from: Address,
+    /// Callee, missing for failed contract creation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<Address>,
+    /// Transferred value.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<U256>,
+    /// Gas provided to the call.
+    pub gas: U256,
+    /// Gas used by the call (including sub calls).
+    pub gas_used: U256,
+    /// Input data.
+    pub input: Bytes,
+    /// Output data.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<Bytes>,
+    /// Error of the call, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Decoded revert reason, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revert_reason: Option<String>,
+    /// Sub calls.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub calls: Vec<CallFrame>,
+}
+
+impl CallFrame {
+    /// Returns a printer of the call tree.
+    ///
+    /// ```text
+    /// CALL 0x7a250d5630b4cf539739df2c5dacb4c659f2488d 0x38ed1739 gas used: 120335
+    /// ├─ STATICCALL 0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2 0x0902f1ac gas used: 2504
+    /// └─ CALL 0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48 0xa9059cbb gas used: 40377 (execution reverted)
+    /// ```
+    pub fn tree(&self) -> CallTree<'_> {
+        CallTree(self)
+    }
+
+    fn call_site(&self) -> CallSite {
+        CallSite {
+            call_type: self.call_type.clone(),
+            to: self.to,
+            selector: selector(&self.input.0),
+        }
+    }
+}
+
+/// Human-readable call tree, see [`CallFrame::tree`].
+#[derive(Debug, Clone, Copy)]
+pub struct CallTree<'a>(&'a CallFrame);
+
+impl CallTree<'_> {
+    fn fmt_frame(f: &mut fmt::Formatter, frame: &CallFrame, prefix: &str) -> fmt::Result {
+        write!(f, "{} gas used: {}", frame.call_site(), frame.gas_used)?;
+        match (&frame.revert_reason, &frame.error) {
+            (Some(reason), _) => writeln!(f, " (reverted: {})", reason)?,
+            (None, Some(error)) => writeln!(f, " ({})", error)?,
+            (None, None) => writeln!(f)?,
+        }
+        for (i, call) in frame.calls.iter().enumerate() {
+            let last = i + 1 == frame.calls.len();
+            write!(f, "{}{}", prefix, if last { "└─ " } else { "├─ " })?;
+            let prefix = format!("{}{}", prefix, if last { "   " } else { "│  " });
+            Self::fmt_frame(f, call, &prefix)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for CallTree<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Self::fmt_frame(f, self.0, "")
+    }
+}
+
+/// A place in the code which is called: the callee together with the function selector.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CallSite {
+    /// Type of the call (`CALL`, `STATICCALL`, `CREATE`, ...).
+    pub call_type: String,
+    /// Callee, `None` if unknown (e.g. contract creation).
+    pub to: Option<Address>,
+    /// Function selector, `None` if unknown or the input is shorter than 4 bytes.
+    pub selector: Option<[u8; 4]>,
+}
+
+impl fmt::Display for CallSite {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.call_type)?;
+        if let Some(to) = self.to {
+            write!(f, " {:?}", to)?;
+        }
+        if let Some(selector) = self.selector {
+            write!(f, " 0x{}", hex::encode(selector))?;
+        }
+        Ok(())
+    }
+}
+
+/// Gas usage aggregated per call site.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GasProfileEntry {
+    /// The call site.
+    pub call_site: CallSite,
+    /// Number of times the call site was called.
+    pub calls: u64,
+    /// Gas used by all the calls, including sub calls.
+    pub gas_used: u64,
+    /// Gas used by all the calls, excluding sub calls.
+    pub self_gas: u64,
+}
+
+/// Gas usage of a transaction per call site, sorted by the gas used (descending).
+///
+/// Calls of precompiles and accounts without code are only visible in `callTracer` output;
+/// for the struct logger their cost is attributed to the caller. Intrinsic gas of the
+/// transaction is not attributed to any call site in struct logger output.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GasProfile {
+    /// Aggregated call sites.
+    pub entries: Vec<GasProfileEntry>,
+}
+
+impl GasProfile {
+    /// Profiles the output of the `callTracer`.
+    pub fn from_call_frame(frame: &CallFrame) -> Self {
+        fn visit(frame: &CallFrame, profile: &mut GasProfileBuilder) {
+            let gas_used = frame.gas_used.low_u64();
+            let children = frame.calls.iter().map(|call| call.gas_used.low_u64()).sum::<u64>();
+            profile.record(frame.call_site(), gas_used, gas_used.saturating_sub(children));
+            frame.calls.iter().for_each(|call| visit(call, profile));
+        }
+
+        let mut profile = GasProfileBuilder::default();
+        visit(frame, &mut profile);
+        profile.build()
+    }
+
+    /// Profiles the output of the struct logger.
+    ///
+    /// Callees are known only if the stack was captured, selectors only if memory was
+    /// captured as well (see [`TracingOptions::enable_memory`]).
+    pub fn from_struct_logs(logs: &[StructLog]) -> Self {
+        struct Frame {
+            call_site: CallSite,
+            first_gas: u64,
+            last_gas: u64,
+            last_cost: u64,
+            children: u64,
+        }
+
+        fn close(frames: &mut Vec<Frame>, profile: &mut GasProfileBuilder) {
+            if let Some(frame) = frames.pop() {
+                let gas_used = frame
+                    .first_gas
+                    .saturating_sub(frame.last_gas.saturating_sub(frame.last_cost));
+                profile.record(frame.call_site, gas_used, gas_used.saturating_sub(frame.children));
+                if let Some(parent) = frames.last_mut() {
+                    parent.children += gas_used;
+                }
+            }
+        }
+
+        let mut profile = GasProfileBuilder::default();
+        let mut frames: Vec<Frame> = vec![];
+        let mut pending = None;
+        for log in logs {
+            while frames.len() as u64 > log.depth {
+                close(&mut frames, &mut profile);
+            }
+            if (frames.len() as u64) < log.depth {
+                let call_site = pending.take().unwrap_or_else(|| CallSite {
+                    call_type: "CALL".into(),
+                    to: None,
+                    selector: None,
+                });
+                frames.push(Frame {
+                    call_site,
+                    first_gas: log.gas,
+                    last_gas: log.gas,
+                    last_cost: 0,
+                    children: 0,
+                });
+            }
+            if let Some(frame) = frames.last_mut() {
+                frame.last_gas = log.gas;
+                frame.last_cost = log.gas_cost;
+            }
+            pending = struct_log_call_site(log);
+        }
+        while !frames.is_empty() {
+            close(&mut frames, &mut profile);
+        }
+        profile.build()
+    }
+
+    /// Total gas used by the profiled call sites, excluding sub calls.
+    pub fn total_self_gas(&self) -> u64 {
+        self.entries.iter().map(|entry| entry.self_gas).sum()
+    }
+}
+
+impl fmt::Display for GasProfile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{:>12} {:>12} {:>6}  call site", "gas used", "self gas", "calls")?;
+        for entry in &self.entries {
+            writeln!(
+                f,
+                "{:>12} {:>12} {:>6}  {}",
+                entry.gas_used, entry.self_gas, entry.calls, entry.call_site
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct GasProfileBuilder {
+    entries: HashMap<CallSite, GasProfileEntry>,
+}
+
+impl GasProfileBuilder {
+    fn record(&mut self, call_site: CallSite, gas_used: u64, self_gas: u64) {
+        let entry = self
+            .entries
+            .entry(call_site.clone())
+            .or_insert_with(|| GasProfileEntry {
+                call_site,
+                calls: 0,
+                gas_used: 0,
+                self_gas: 0,
+            });
+        entry.calls += 1;
+        entry.gas_used += gas_used;
+        entry.self_gas += self_gas;
+    }
+
+    fn build(self) -> GasProfile {
+        let mut entries: Vec<_> = self.entries.into_values().collect();
+        entries.sort_by(|a, b| {
+            b.gas_used
+                .cmp(&a.gas_used)
+                .then_with(|| b.self_gas.cmp(&a.self_gas))
+                .then_with(|| a.call_site.to_string().cmp(&b.call_site.to_string()))
+        });
+        GasProfile { entries }
+    }
+}
+
+fn selector(input: &[u8]) -> Option<[u8; 4]> {
+    input
+        .get(..4)
+        .map(|selector| [selector[0], selector[1], selector[2], selector[3]])
+}
+
+/// Call site of a call opcode, read from the captured stack and memory.
+fn struct_log_call_site(log: &StructLog) -> Option<CallSite> {
+    // position of the arguments offset from the top of the stack (top is the gas)
+    let args = match log.op.as_str() {
+        "CALL" | "CALLCODE" => Some(3),
+        "DELEGATECALL" | "STATICCALL" => Some(2),
+        "CREATE" | "CREATE2" => None,
+        _ => return None,
+    };
+    let stack = log.stack.as_deref().unwrap_or_default();
+    let peek = |n: usize| stack.len().checked_sub(n + 1).map(|i| stack[i]);
+
+    let to = args.and(peek(1)).map(|to| {
+        let mut bytes = [0u8; 32];
+        to.to_big_endian(&mut bytes);
+        Address::from_slice(&bytes[12..])
+    });
+    let selector = args
+        .and_then(|args| Some((peek(args)?, peek(args + 1)?)))
+        .filter(|(_, len)| *len >= 4.into())
+        .and_then(|(offset, _)| memory_word(log.memory.as_deref()?, offset));
+
+    Some(CallSite {
+        call_type: log.op.clone(),
+        to,
+        selector,
+    })
+}
+
+fn memory_word(memory: &[String], offset: U256) -> Option<[u8; 4]> {
+    if offset > usize::MAX.into() {
+        return None;
+    }
+    let offset = offset.as_usize();
+    let mut bytes = Vec::with_capacity(64);
+    for word in memory.get(offset / 32..=offset.checked_add(3)? / 32)? {
+        bytes.extend(hex::decode(word.trim_start_matches("0x")).ok()?);
+    }
+    selector(bytes.get(offset % 32..)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE_CALL_TRACE: &str = r#"{
+        "type": "CALL",
+        "from": "0x2c7536e3605d9c16a7a3d7b1898e529396a65c23",
+        "to": "0x7a250d5630b4cf539739df2c5dacb4c659f2488d",
+        "value": "0x0",
+        "gas": "0x30d40",
+        "gasUsed": "0x1d60f",
+        "input": "0x38ed17390000000000000000000000000000000000000000000000000000000000000001",
+        "output": "0x",
+        "calls": [
+            {
+                "type": "STATICCALL",
+                "from": "0x7a250d5630b4cf539739df2c5dacb4c659f2488d",
+                "to": "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+                "gas": "0x2d3c8",
+                "gasUsed": "0x9c8",
+                "input": "0x0902f1ac",
+                "output": "0x0000000000000000000000000000000000000000000000000000000000000001"
+            },
+            {
+                "type": "CALL",
+                "from": "0x7a250d5630b4cf539739df2c5dacb4c659f2488d",
+                "to": "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+                "value": "0x0",
+                "gas": "0x2a300",
+                "gasUsed": "0x9db9",
+                "input": "0xa9059cbb0000000000000000000000002c7536e3605d9c16a7a3d7b1898e529396a65c23",
+                "error": "execution reverted",
+                "revertReason": "transfer amount exceeds balance",
+                "calls": [
+                    {
+                        "type": "STATICCALL",
+                        "from": "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+                        "to": "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+                        "gas": "0x28000",
+                        "gasUsed": "0x9c8",
+                        "input": "0x0902f1ac"
+                    }
+                ]
+            }
+        ]
+    }"#;
+
+    const EXAMPLE_STRUCT_LOGS: &str = r#"{
+        "failed": false,
+        "gas": 43000,
+        "returnValue": "",
+        "structLogs": [
+            {"pc": 0, "op": "PUSH1", "gas": 100000, "gasCost": 3, "depth": 1, "stack": []},
+            {
+                "pc": 2,
+                "op": "STATICCALL",
+                "gas": 99997,
+                "gasCost": 97000,
+                "depth": 1,
+                "stack": ["0x0", "0x0", "0x4", "0x1e", "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2", "0x17b2e"],
+                "memory": [
+                    "0000000000000000000000000000000000000000000000000000000000000902",
+                    "f1ac000000000000000000000000000000000000000000000000000000000000"
+                ]
+            },
+            {"pc": 0, "op": "PUSH1", "gas": 97000, "gasCost": 3, "depth": 2, "stack": []},
+            {"pc": 2, "op": "SLOAD", "gas": 96997, "gasCost": 2100, "depth": 2, "stack": ["0x0"]},
+            {"pc": 3, "op": "STOP", "gas": 94897, "gasCost": 0, "depth": 2, "stack": ["0x1"]},
+            {"pc": 3, "op": "POP", "gas": 94894, "gasCost": 2, "depth": 1, "stack": ["0x1"]},
+            {"pc": 4, "op": "CALL", "gas": 94892, "gasCost": 100, "depth": 1, "stack": ["0x0", "0x0", "0x0", "0x0", "0x0", "0x1", "0x0"]},
+            {"pc": 5, "op": "STOP", "gas": 94792, "gasCost": 0, "depth": 1, "stack": ["0x1"]}
+        ]
+    }"#;
+
+    #[test]
+    fn should_serialize_tracing_options() {
+        let options = TracingOptions {
+            enable_memory: true,
+            timeout: Some("10s".into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_json::to_string(&options).unwrap(),
+            r#"{"enableMemory":true,"timeout":"10s"}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&TracingOptions::call_tracer()).unwrap(),
+            r#"{"tracer":"callTracer"}"#
+        );
+    }
+
+    #[test]
+    fn should_deserialize_traces() {
+        let trace: GethTrace = serde_json::from_str(EXAMPLE_CALL_TRACE).unwrap();
+        assert!(matches!(trace, GethTrace::CallTracer(ref frame) if frame.calls.len() == 2));
+
+        let trace: GethTrace = serde_json::from_str(EXAMPLE_STRUCT_LOGS).unwrap();
+        assert!(matches!(trace, GethTrace::Default(ref frame) if frame.struct_logs.len() == 8));
+
+        let trace: GethTrace = serde_json::from_str(r#"{"0x01": {"balance": "0x0"}}"#).unwrap();
+        assert!(matches!(trace, GethTrace::Unknown(_)));
+    }
+
+    #[test]
+    fn should_print_call_tree() {
+        let frame: CallFrame = serde_json::from_str(EXAMPLE_CALL_TRACE).unwrap();
+
+        assert_eq!(
+            frame.tree().to_string(),
+            "CALL 0x7a250d5630b4cf539739df2c5dacb4c659f2488d 0x38ed1739 gas used: 120335\n\
+             ├─ STATICCALL 0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2 0x0902f1ac gas used: 2504\n\
+             └─ CALL 0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48 0xa9059cbb gas used: 40377 (reverted: transfer amount exceeds balance)\n\
+             \u{20}  └─ STATICCALL 0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2 0x0902f1ac gas used: 2504\n"
+        );
+    }
+
+    #[test]
+    fn should_profile_call_frames() {
+        let frame: CallFrame = serde_json::from_str(EXAMPLE_CALL_TRACE).unwrap();
+        let profile = GasProfile::from_call_frame(&frame);
+
+        let balance_of = CallSite {
+            call_type: "STATICCALL".into(),
+            to: Some("c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2".parse().unwrap()),
+            selector: Some([0x09, 0x02, 0xf1, 0xac]),
+        };
+        assert_eq!(profile.entries.len(), 3);
+        assert_eq!(profile.entries[0].gas_used, 120_335);
+        assert_eq!(profile.entries[0].self_gas, 120_335 - 2504 - 40_377);
+        assert_eq!(profile.entries[1].self_gas, 40_377 - 2504);
+        assert_eq!(
+            profile.entries[2],
+            GasProfileEntry {
+                call_site: balance_of,
+                calls: 2,
+                gas_used: 5008,
+                self_gas: 5008,
+            }
+        );
+        assert_eq!(profile.total_self_gas(), 120_335);
+    }
+
+    #[test]
+    fn should_profile_struct_logs() {
+        let frame: DefaultFrame = serde_json::from_str(EXAMPLE_STRUCT_LOGS).unwrap();
+        let profile = GasProfile::from_struct_logs(&frame.struct_logs);
+
+        assert_eq!(
+            profile.entries,
+            vec![
+                GasProfileEntry {
+                    call_site: CallSite {
+                        call_type: "CALL".into(),
+                        to: None,
+                        selector: None,
+                    },
+                    calls: 1,
+                    gas_used: 5208,
+                    self_gas: 3105,
+                },
+                GasProfileEntry {
+                    call_site: CallSite {
+                        call_type: "STATICCALL".into(),
+                        to: Some("c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2".parse().unwrap()),
+                        selector: Some([0x09, 0x02, 0xf1, 0xac]),
+                    },
+                    calls: 1,
+                    gas_used: 2103,
+                    self_gas: 2103,
+                },
+            ]
+        );
+        assert!(profile
+            .to_string()
+            .contains("STATICCALL 0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2 0x0902f1ac"));
+    }
+}
@@ -0,0 +1,136 @@
+use crate::types::{Address, Bytes, Withdrawal, H2048, H256, H64, U256, U64};
+use serde::{Deserialize, Serialize};
+
+/// Identifier of a payload being built, returned by `engine_forkchoiceUpdated`.
+pub type PayloadId = H64;
+
+/// An execution block exchanged with the consensus layer (Cancun).
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionPayloadV3 {
+    /// Hash of the parent block
+    pub parent_hash: H256,
+    /// Beneficiary of the priority fees
+    pub fee_recipient: Address,
+    /// State root after executing the block
+    pub state_root: H256,
+    /// Root of the receipts trie
+    pub receipts_root: H256,
+    /// Bloom filter of the logs
+    pub logs_bloom: H2048,
+    /// Randomness of the beacon chain
+    pub prev_randao: H256,
+    /// Block number
+    pub block_number: U64,
+    /// Gas limit
+    pub gas_limit: U64,
+    /// Gas used
+    pub gas_used: U64,
+    /// Timestamp (unix seconds)
+    pub timestamp: U64,
+    /// Extra data
+    pub extra_data: Bytes,
+    /// Base fee per gas
+    pub base_fee_per_gas: U256,
+    /// Hash of the block
+    pub block_hash: H256,
+    /// Signed transactions, encoded as in `eth_sendRawTransaction`
+    pub transactions: Vec<Bytes>,
+    /// Validator withdrawals
+    pub withdrawals: Vec<Withdrawal>,
+    /// Blob gas used by the transactions
+    pub blob_gas_used: U64,
+    /// Excess blob gas
+    pub excess_blob_gas: U64,
+}
+
+/// Status of a payload validation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PayloadValidity {
+    /// The payload is valid
+    Valid,
+    /// The payload (or one of its ancestors) is invalid
+    Invalid,
+    /// The node is syncing and can't validate the payload yet
+    Syncing,
+    /// The payload extends a side chain, it's not validated yet
+    Accepted,
+    /// The block hash of the payload doesn't match its contents
+    InvalidBlockHash,
+}
+
+/// Result of a payload validation.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayloadStatus {
+    /// Validation status
+    pub status: PayloadValidity,
+    /// Hash of the most recent valid ancestor (or the payload itself)
+    pub latest_valid_hash: Option<H256>,
+    /// Reason of an invalid status
+    pub validation_error: Option<String>,
+}
+
+/// Head, safe and finalized blocks of the canonical chain, as seen by the consensus layer.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkchoiceState {
+    /// Head of the canonical chain
+    pub head_block_hash: H256,
+    /// Safe block
+    pub safe_block_hash: H256,
+    /// Finalized block
+    pub finalized_block_hash: H256,
+}
+
+/// Attributes of a payload to be built on top of the new head (Cancun).
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayloadAttributesV3 {
+    /// Timestamp of the payload
+    pub timestamp: U64,
+    /// Randomness of the beacon chain
+    pub prev_randao: H256,
+    /// Beneficiary of the priority fees
+    pub suggested_fee_recipient: Address,
+    /// Validator withdrawals to include
+    pub withdrawals: Vec<Withdrawal>,
+    /// Root of the parent beacon block
+    pub parent_beacon_block_root: H256,
+}
+
+/// Response of `engine_forkchoiceUpdated`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkchoiceUpdated {
+    /// Validation status of the new head
+    pub payload_status: PayloadStatus,
+    /// Identifier of the payload build process, if attributes were given
+    pub payload_id: Option<PayloadId>,
+}
+
+/// Blobs of the transactions of a payload (EIP-4844).
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct BlobsBundleV1 {
+    /// KZG commitments of the blobs
+    pub commitments: Vec<Bytes>,
+    /// KZG proofs of the blobs
+    pub proofs: Vec<Bytes>,
+    /// Blobs
+    pub blobs: Vec<Bytes>,
+}
+
+/// Response of `engine_getPayloadV3`.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayloadEnvelopeV3 {
+    /// The built payload
+    pub execution_payload: ExecutionPayloadV3,
+    /// Fees received by the fee recipient, in wei
+    pub block_value: U256,
+    /// Blobs of the payload transactions
+    pub blobs_bundle: BlobsBundleV1,
+    /// Whether the consensus layer should prefer a locally built payload over a builder's one
+    pub should_override_builder: bool,
+}
@@ -0,0 +1,22 @@
+use crate::types::U256;
+use std::time::Duration;
+
+/// Node status returned by `Web3::health`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeHealth {
+    /// Whether the node is not syncing
+    pub synced: bool,
+    /// Number of connected peers
+    pub peers: U256,
+    /// Time since the latest block was produced (zero if its timestamp is in the future)
+    pub head_age: Duration,
+    /// Chain id reported by `eth_chainId`
+    pub chain_id: U256,
+}
+
+impl NodeHealth {
+    /// Whether the node is synced, has at least `min_peers` peers and its head isn't older than `max_head_age`.
+    pub fn is_ready(&self, min_peers: usize, max_head_age: Duration) -> bool {
+        self.synced && self.peers >= U256::from(min_peers) && self.head_age <= max_head_age
+    }
+}
@@ -96,6 +96,33 @@ pub struct Filter {
     limit: Option<usize>,
 }
 
+impl Filter {
+    /// Returns `from_block` of the filter.
+    pub fn from_block(&self) -> Option<BlockNumber> {
+        self.from_block
+    }
+
+    /// Returns `to_block` of the filter.
+    pub fn to_block(&self) -> Option<BlockNumber> {
+        self.to_block
+    }
+
+    /// Returns `block_hash` of the filter.
+    pub fn block_hash(&self) -> Option<H256> {
+        self.block_hash
+    }
+
+    /// Returns a copy of the filter restricted to given block range.
+    pub fn with_block_range(&self, from: BlockNumber, to: BlockNumber) -> Filter {
+        Filter {
+            from_block: Some(from),
+            to_block: Some(to),
+            block_hash: None,
+            ..self.clone()
+        }
+    }
+}
+
 /// Filter Builder
 #[derive(Default, Clone)]
 pub struct FilterBuilder {
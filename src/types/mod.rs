@@ -1,15 +1,27 @@
 //! Web3 Types
 
+#[cfg(feature = "alchemy")]
+mod alchemy;
 mod block;
+mod bor;
 mod bytes;
 mod bytes_array;
+mod checkpoint;
+mod checksum;
+mod clique;
+mod debug_trace;
+mod engine;
 mod fee_history;
+mod health;
 mod log;
+mod parity_local_transaction;
 mod parity_peers;
 mod parity_pending_transaction;
 mod proof;
 mod recovery;
 mod signed;
+#[cfg(feature = "simulation")]
+mod simulation;
 mod sync_state;
 mod trace_filtering;
 mod traces;
@@ -18,23 +30,39 @@ mod transaction_id;
 mod transaction_request;
 mod txpool;
 mod uint;
+pub mod units;
 mod work;
+#[cfg(feature = "zksync")]
+mod zksync;
 
 pub use self::{
-    block::{Block, BlockHeader, BlockId, BlockNumber},
+    block::{Block, BlockHeader, BlockId, BlockNumber, BlockNumberAndHash, Withdrawal},
+    bor::{BorExtraData, BorValidator},
     bytes::Bytes,
     bytes_array::BytesArray,
+    checkpoint::{BlockRef, ChainCheckpoint, LogCheckpoint, LogKey},
+    checksum::{AddressChecksum, ChecksumAddress, ChecksumError},
+    clique::{CliqueSnapshot, CliqueTally, CliqueVote},
+    debug_trace::{
+        CallFrame, CallSite, CallTree, DefaultFrame, GasProfile, GasProfileEntry, GethTrace, StructLog, TracingOptions,
+    },
+    engine::{
+        BlobsBundleV1, ExecutionPayloadV3, ForkchoiceState, ForkchoiceUpdated, PayloadAttributesV3, PayloadEnvelopeV3,
+        PayloadId, PayloadStatus, PayloadValidity,
+    },
     fee_history::FeeHistory,
+    health::NodeHealth,
     log::{Filter, FilterBuilder, Log},
+    parity_local_transaction::LocalTransactionStatus,
     parity_peers::{
         EthProtocolInfo, ParityPeerInfo, ParityPeerType, PeerNetworkInfo, PeerProtocolsInfo, PipProtocolInfo,
     },
     parity_pending_transaction::{
         FilterCondition, ParityPendingTransactionFilter, ParityPendingTransactionFilterBuilder, ToFilter,
     },
-    proof::Proof,
+    proof::{Proof, StorageProof},
     recovery::{ParseSignatureError, Recovery, RecoveryMessage},
-    signed::{SignedData, SignedTransaction, TransactionParameters},
+    signed::{ExtraField, ExtraFields, SignedData, SignedTransaction, TransactionParameters},
     sync_state::{SyncInfo, SyncState},
     trace_filtering::{
         Action, ActionType, Call, CallResult, CallType, Create, CreateResult, Res, Reward, RewardType, Suicide, Trace,
@@ -44,14 +72,28 @@ pub use self::{
         AccountDiff, BlockTrace, ChangedType, Diff, MemoryDiff, StateDiff, StorageDiff, TraceType, TransactionTrace,
         VMExecutedOperation, VMOperation, VMTrace,
     },
-    transaction::{AccessList, AccessListItem, RawTransaction, Receipt as TransactionReceipt, Transaction},
+    transaction::{
+        merge_access_lists, AccessList, AccessListBuilder, AccessListItem, AccessListWithGasUsed, RawTransaction,
+        Receipt as TransactionReceipt, Transaction,
+    },
     transaction_id::TransactionId,
-    transaction_request::{CallRequest, TransactionCondition, TransactionRequest},
-    txpool::{TxpoolContentInfo, TxpoolInspectInfo, TxpoolStatus},
+    transaction_request::{CallRequest, MissingSender, TransactionCondition, TransactionRequest},
+    txpool::{TxpoolContentFromInfo, TxpoolContentInfo, TxpoolInspectInfo, TxpoolStatus},
     uint::{H128, H160, H2048, H256, H512, H520, H64, U128, U256, U64},
     work::Work,
 };
 
+#[cfg(feature = "alchemy")]
+pub use self::alchemy::{AlchemyAddressFilter, AlchemyMinedTransaction};
+#[cfg(feature = "simulation")]
+pub use self::simulation::{decode_revert_reason, BlockOverride, Bundle, SimulatedCall, StateContext};
+#[cfg(feature = "zksync")]
+pub use self::zksync::{
+    bytecode_hash as zksync_bytecode_hash, BridgeContracts, Eip712Transaction, L2ToL1MessageProof, PaymasterParams,
+    ZkSyncFee, DEFAULT_GAS_PER_PUBDATA_LIMIT as ZKSYNC_DEFAULT_GAS_PER_PUBDATA_LIMIT,
+    EIP712_TX_TYPE as ZKSYNC_EIP712_TX_TYPE,
+};
+
 /// Address
 pub type Address = H160;
 /// Index in block
@@ -0,0 +1,71 @@
+//! Types for the status of local transactions of a parity node
+use crate::types::{Transaction, H256, U256};
+use serde::{Deserialize, Serialize};
+
+/// Status of a transaction sent through the node (only openethereum/Parity and Nethermind)
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum LocalTransactionStatus {
+    /// Ready to be included in a block
+    Pending,
+    /// Waiting for a transaction with lower nonce
+    Future,
+    /// Included in a block
+    Mined {
+        /// transaction
+        transaction: Transaction,
+    },
+    /// Removed from the queue because it became stale
+    Culled {
+        /// transaction
+        transaction: Transaction,
+    },
+    /// Dropped because of the limit of the queue
+    Dropped {
+        /// transaction
+        transaction: Transaction,
+    },
+    /// Replaced by a transaction with the same nonce and a higher gas price
+    Replaced {
+        /// transaction
+        transaction: Transaction,
+        /// hash of the replacing transaction
+        hash: H256,
+        /// gas price of the replacing transaction
+        #[serde(rename = "gasPrice")]
+        gas_price: U256,
+    },
+    /// Rejected when imported to the queue
+    Rejected {
+        /// transaction
+        transaction: Transaction,
+        /// reason of the rejection
+        error: String,
+    },
+    /// Found invalid when included in a block
+    Invalid {
+        /// transaction
+        transaction: Transaction,
+    },
+    /// Canceled by the sender
+    Canceled {
+        /// transaction
+        transaction: Transaction,
+    },
+}
+
+impl LocalTransactionStatus {
+    /// Returns the transaction, not included in the status of pending and future transactions.
+    pub fn transaction(&self) -> Option<&Transaction> {
+        match self {
+            Self::Pending | Self::Future => None,
+            Self::Mined { transaction }
+            | Self::Culled { transaction }
+            | Self::Dropped { transaction }
+            | Self::Replaced { transaction, .. }
+            | Self::Rejected { transaction, .. }
+            | Self::Invalid { transaction }
+            | Self::Canceled { transaction } => Some(transaction),
+        }
+    }
+}
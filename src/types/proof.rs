@@ -1,5 +1,5 @@
 use crate::types::Bytes;
-use ethereum_types::{H256, U256};
+use ethereum_types::{Address, H256, U256};
 use serde::{Deserialize, Serialize};
 
 ///Proof struct returned by eth_getProof method
@@ -7,6 +7,9 @@ use serde::{Deserialize, Serialize};
 /// https://eips.ethereum.org/EIPS/eip-1186
 #[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Proof {
+    /// the address of the account
+    #[serde(default)]
+    pub address: Address,
     /// the balance of the account. See eth_getBalance
     pub balance: U256,
     ///  hash of the code of the account
@@ -60,13 +60,25 @@ impl Recovery {
 
     /// Retrieve the Recovery Id ("Standard V")
     ///
+    /// Accepts the y-parity of typed transactions (`0` or `1`), 'Electrum' notation and
+    /// EIP-155 values of any chain id.
+    ///
     /// Returns `None` if `v` value is invalid
     /// (equivalent of returning `4` in some implementations).
     pub fn recovery_id(&self) -> Option<i32> {
         match self.v {
+            0 | 1 => Some(self.v as _),
             27 => Some(0),
             28 => Some(1),
-            v if v >= 35 => Some(((v - 1) % 2) as _),
+            v if v >= 35 => Some(((v - 35) % 2) as _),
+            _ => None,
+        }
+    }
+
+    /// Retrieve the EIP-155 chain id `v` is protected with, if any.
+    pub fn chain_id(&self) -> Option<u64> {
+        match self.v {
+            v if v >= 35 => Some((v - 35) / 2),
             _ => None,
         }
     }
@@ -191,4 +203,17 @@ mod tests {
             .unwrap();
         assert_eq!((sig.to_vec(), id), expected_signature);
     }
+
+    #[test]
+    fn recovery_id_of_large_chain_ids() {
+        let chain_id = u64::from(u32::MAX) * 2;
+        let recovery = |v| Recovery::new(H256::zero(), v, H256::zero(), H256::zero());
+
+        assert_eq!(recovery(chain_id * 2 + 35).recovery_id(), Some(0));
+        assert_eq!(recovery(chain_id * 2 + 36).recovery_id(), Some(1));
+        assert_eq!(recovery(chain_id * 2 + 36).chain_id(), Some(chain_id));
+        assert_eq!(recovery(1).recovery_id(), Some(1));
+        assert_eq!(recovery(28).chain_id(), None);
+        assert_eq!(recovery(29).recovery_id(), None);
+    }
 }
@@ -1,4 +1,4 @@
-use crate::types::{AccessList, Address, Bytes, CallRequest, H256, U256, U64};
+use crate::types::{AccessList, AccessListWithGasUsed, Address, Bytes, CallRequest, H256, U256, U64};
 use serde::{Deserialize, Serialize};
 
 /// Struct representing signed data returned from `Accounts::sign` method.
@@ -53,7 +53,8 @@ pub struct TransactionParameters {
     pub data: Bytes,
     /// The chain ID (None for network ID)
     pub chain_id: Option<u64>,
-    /// Transaction type, Some(1) for AccessList transaction, None for Legacy
+    /// Transaction type, Some(0) for Legacy, Some(1) for AccessList and Some(2) for EIP-1559
+    /// transaction, None to derive it from `access_list` (see `effective_transaction_type`)
     pub transaction_type: Option<U64>,
     /// Access list
     pub access_list: Option<AccessList>,
@@ -61,6 +62,53 @@ pub struct TransactionParameters {
     pub max_fee_per_gas: Option<U256>,
     /// miner bribe
     pub max_priority_fee_per_gas: Option<U256>,
+    /// Chain specific fields of legacy transactions (e.g. Celo's `feeCurrency`), typed transactions
+    /// with extra fields are rejected
+    pub extra_fields: ExtraFields,
+}
+
+/// A single chain specific transaction field.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExtraField {
+    /// Optional address, encoded as an empty string when missing (like `to`).
+    Address(Option<Address>),
+    /// Unsigned integer.
+    Uint(U256),
+    /// Arbitrary bytes.
+    Bytes(Bytes),
+}
+
+/// Chain specific fields extending the legacy (type 0) transaction format.
+///
+/// Some chains require additional fields to be signed along with a transaction. These are
+/// RLP encoded right after `gas` (and before `to`), which is where Celo-compatible chains
+/// expect `feeCurrency`, `gatewayFeeRecipient` and `gatewayFee`.
+///
+/// Typed (EIP-2718) transactions have no extra fields, `Accounts::sign_transaction` rejects them.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExtraFields(pub Vec<ExtraField>);
+
+impl ExtraFields {
+    /// Fields of a Celo legacy transaction.
+    ///
+    /// `fee_currency` is the token used to pay for gas (`None` for the native currency).
+    pub fn celo(fee_currency: Option<Address>, gateway_fee_recipient: Option<Address>, gateway_fee: U256) -> Self {
+        ExtraFields(vec![
+            ExtraField::Address(fee_currency),
+            ExtraField::Address(gateway_fee_recipient),
+            ExtraField::Uint(gateway_fee),
+        ])
+    }
+
+    /// Returns true if there are no extra fields.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Number of extra fields.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
 }
 
 /// The default fas for transactions.
@@ -85,6 +133,35 @@ impl Default for TransactionParameters {
             access_list: None,
             max_fee_per_gas: None,
             max_priority_fee_per_gas: None,
+            extra_fields: Default::default(),
+        }
+    }
+}
+
+impl TransactionParameters {
+    /// Attaches an access list (e.g. returned by `Eth::create_access_list`).
+    ///
+    /// Legacy transactions are turned into access list (type 1) transactions. `gas` is left as is,
+    /// the gas used reported along with the list is not a safe limit (use `Eth::estimate_gas`).
+    pub fn with_access_list(mut self, access_list: impl Into<AccessListWithGasUsed>) -> Self {
+        let access_list = access_list.into();
+        if self.transaction_type.is_none() {
+            self.transaction_type = Some(if self.max_fee_per_gas.is_some() { 2 } else { 1 }.into());
+        }
+        self.access_list = Some(access_list.access_list);
+        self
+    }
+
+    /// Returns the type of the transaction to sign.
+    ///
+    /// Unless set explicitly, transactions with an access list are access list (type 1)
+    /// transactions, or EIP-1559 (type 2) ones when `max_fee_per_gas` is set. Other transactions
+    /// are legacy ones.
+    pub fn effective_transaction_type(&self) -> Option<U64> {
+        match self.transaction_type {
+            Some(transaction_type) => Some(transaction_type),
+            None if self.access_list.is_some() => Some(if self.max_fee_per_gas.is_some() { 2 } else { 1 }.into()),
+            None => None,
         }
     }
 }
@@ -103,6 +180,7 @@ impl From<CallRequest> for TransactionParameters {
             access_list: call.access_list,
             max_fee_per_gas: call.max_fee_per_gas,
             max_priority_fee_per_gas: call.max_priority_fee_per_gas,
+            extra_fields: Default::default(),
         }
     }
 }
@@ -141,6 +219,14 @@ pub struct SignedTransaction {
     pub transaction_hash: H256,
 }
 
+#[cfg(feature = "signing")]
+impl SignedTransaction {
+    /// Recovers the sender from the raw transaction.
+    pub fn recover_from(&self) -> Result<Address, crate::signing::RecoveryError> {
+        crate::signing::recover_transaction(&self.raw_transaction.0)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -149,4 +235,43 @@ mod tests {
     fn verify_transaction_default_gas() {
         assert_eq!(TRANSACTION_DEFAULT_GAS, U256::from(100_000));
     }
+
+    #[test]
+    fn should_attach_access_list() {
+        let access_list = AccessListWithGasUsed {
+            access_list: vec![crate::types::AccessListItem::new(Address::zero(), vec![H256::zero()])],
+            gas_used: 200_000.into(),
+            error: None,
+        };
+
+        let tx = TransactionParameters::default().with_access_list(access_list.clone());
+
+        assert_eq!(tx.transaction_type, Some(1.into()));
+        assert_eq!(tx.gas, TRANSACTION_DEFAULT_GAS);
+        assert_eq!(tx.access_list, Some(access_list.access_list));
+    }
+
+    #[test]
+    fn should_derive_transaction_type() {
+        let legacy = TransactionParameters::default();
+        assert_eq!(legacy.effective_transaction_type(), None);
+
+        let access_list = TransactionParameters {
+            access_list: Some(vec![]),
+            ..Default::default()
+        };
+        assert_eq!(access_list.effective_transaction_type(), Some(1.into()));
+
+        let eip1559 = TransactionParameters {
+            max_fee_per_gas: Some(1.into()),
+            ..access_list.clone()
+        };
+        assert_eq!(eip1559.effective_transaction_type(), Some(2.into()));
+
+        let explicit = TransactionParameters {
+            transaction_type: Some(0.into()),
+            ..access_list
+        };
+        assert_eq!(explicit.effective_transaction_type(), Some(0.into()));
+    }
 }
@@ -0,0 +1,228 @@
+//! Types of the bundle simulation API (`eth_callMany` / `debug_traceCallMany`).
+
+use crate::{
+    error::Error,
+    types::{Address, BlockId, BlockNumber, Bytes, CallFrame, CallRequest, U256, U64},
+};
+use ethabi::{ParamType, Token};
+use serde::{Deserialize, Serialize};
+
+/// Selector of `Error(string)`.
+const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+/// Selector of `Panic(uint256)`.
+const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+/// Block fields overridden while simulating a bundle.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockOverride {
+    /// Block number
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_number: Option<U64>,
+    /// Block author
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coinbase: Option<Address>,
+    /// Block timestamp
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<U64>,
+    /// Block gas limit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_limit: Option<U64>,
+    /// Block difficulty
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub difficulty: Option<U256>,
+    /// Block base fee
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_fee: Option<U256>,
+}
+
+/// Calls executed one after another, each seeing the state changes of the previous ones.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Bundle {
+    /// Calls of the bundle
+    pub transactions: Vec<CallRequest>,
+    /// Block fields overridden for the bundle
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_override: Option<BlockOverride>,
+}
+
+impl Bundle {
+    /// Creates a bundle of given calls.
+    pub fn new(transactions: Vec<CallRequest>) -> Self {
+        Bundle {
+            transactions,
+            block_override: None,
+        }
+    }
+
+    /// Overrides block fields for the bundle.
+    pub fn block_override(mut self, block_override: BlockOverride) -> Self {
+        self.block_override = Some(block_override);
+        self
+    }
+}
+
+/// State the bundles are simulated on top of.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateContext {
+    /// Block to simulate in
+    pub block_number: BlockId,
+    /// Number of transactions of the block applied before the bundles, all of them if not set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_index: Option<u64>,
+}
+
+impl Default for StateContext {
+    fn default() -> Self {
+        BlockNumber::Latest.into()
+    }
+}
+
+impl<T: Into<BlockId>> From<T> for StateContext {
+    fn from(block: T) -> Self {
+        StateContext {
+            block_number: block.into(),
+            transaction_index: None,
+        }
+    }
+}
+
+/// Outcome of a single simulated call.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SimulatedCall {
+    /// Returned (or revert) data
+    pub output: Bytes,
+    /// Error of the call, if it failed
+    pub error: Option<String>,
+    /// Gas used by the call, if reported by the backend
+    pub gas_used: Option<U256>,
+}
+
+impl SimulatedCall {
+    /// Whether the call succeeded.
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// Reason of the failure, decoded from the revert data if possible.
+    pub fn revert_reason(&self) -> Option<String> {
+        let error = self.error.as_ref()?;
+        Some(decode_revert_reason(&self.output.0).unwrap_or_else(|| error.clone()))
+    }
+
+    /// Decodes the output of a successful call of `function`.
+    ///
+    /// Fails with `Error::Revert` if the call failed.
+    pub fn decode_output(&self, function: &ethabi::Function) -> Result<Vec<Token>, Error> {
+        if let Some(reason) = self.revert_reason() {
+            return Err(Error::Revert(reason));
+        }
+        function
+            .decode_output(&self.output.0)
+            .map_err(|err| Error::Decoder(format!("{:?}", err)))
+    }
+}
+
+impl From<CallFrame> for SimulatedCall {
+    fn from(frame: CallFrame) -> Self {
+        SimulatedCall {
+            output: frame.output.unwrap_or_default(),
+            error: frame.error,
+            gas_used: Some(frame.gas_used),
+        }
+    }
+}
+
+/// Decodes the reason of a revert from `Error(string)` or `Panic(uint256)` revert data.
+pub fn decode_revert_reason(data: &[u8]) -> Option<String> {
+    if data.len() < 4 {
+        return None;
+    }
+    let (selector, args) = data.split_at(4);
+    if selector == ERROR_SELECTOR {
+        match ethabi::decode(&[ParamType::String], args).ok()?.pop()? {
+            Token::String(reason) => Some(reason),
+            _ => None,
+        }
+    } else if selector == PANIC_SELECTOR {
+        match ethabi::decode(&[ParamType::Uint(256)], args).ok()?.pop()? {
+            Token::Uint(code) => Some(format!("panic code {:#x}", code)),
+            _ => None,
+        }
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    const REVERT_DATA: [u8; 100] = hex!("08c379a0000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000000056572726f72000000000000000000000000000000000000000000000000000000");
+
+    #[test]
+    fn should_decode_revert_reason() {
+        assert_eq!(decode_revert_reason(&REVERT_DATA), Some("error".into()));
+        assert_eq!(
+            decode_revert_reason(&hex!(
+                "4e487b710000000000000000000000000000000000000000000000000000000000000011"
+            )),
+            Some("panic code 0x11".into())
+        );
+        assert_eq!(decode_revert_reason(&hex!("12345678")), None);
+        assert_eq!(decode_revert_reason(&[]), None);
+    }
+
+    #[test]
+    fn should_decode_call_output() {
+        #[allow(deprecated)]
+        let function = ethabi::Function {
+            name: "balanceOf".into(),
+            inputs: vec![],
+            outputs: vec![ethabi::Param {
+                name: "".into(),
+                kind: ParamType::Uint(256),
+                internal_type: None,
+            }],
+            constant: None,
+            state_mutability: ethabi::StateMutability::View,
+        };
+        let success = SimulatedCall {
+            output: hex!("0000000000000000000000000000000000000000000000000000000000000005").into(),
+            ..Default::default()
+        };
+        let failure = SimulatedCall {
+            output: REVERT_DATA.into(),
+            error: Some("execution reverted".into()),
+            gas_used: None,
+        };
+
+        assert_eq!(success.decode_output(&function), Ok(vec![Token::Uint(5.into())]));
+        assert_eq!(failure.revert_reason(), Some("error".into()));
+        assert_eq!(failure.decode_output(&function), Err(Error::Revert("error".into())));
+    }
+
+    #[test]
+    fn should_serialize_bundle() {
+        let bundle = Bundle::new(vec![CallRequest::default()]).block_override(BlockOverride {
+            timestamp: Some(0x10.into()),
+            ..Default::default()
+        });
+        let context = StateContext {
+            transaction_index: Some(2),
+            ..BlockNumber::Number(0x5.into()).into()
+        };
+
+        assert_eq!(
+            serde_json::to_string(&bundle).unwrap(),
+            r#"{"transactions":[{}],"blockOverride":{"timestamp":"0x10"}}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&context).unwrap(),
+            r#"{"blockNumber":"0x5","transactionIndex":2}"#
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use crate::types::{Address, Bytes, Index, Log, H2048, H256, U256, U64};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Description of a Transaction, pending or in the chain.
 #[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
@@ -55,6 +56,9 @@ pub struct Transaction {
     /// miner bribe
     #[serde(rename = "maxPriorityFeePerGas", skip_serializing_if = "Option::is_none")]
     pub max_priority_fee_per_gas: Option<U256>,
+    /// Fields not covered above, e.g. `yParity` or `mint` and `sourceHash` of OP Stack deposits.
+    #[serde(flatten)]
+    pub other: BTreeMap<String, serde_json::Value>,
 }
 
 /// "Receipt" of an executed transaction: details of its execution.
@@ -111,6 +115,9 @@ pub struct Receipt {
     /// Transaction revert reason
     #[serde(rename = "revertReason")]
     pub revert_reason: Option<String>,
+    /// Fields not covered above, e.g. `l1Fee`, `l1GasUsed` or `depositNonce` returned by L2 nodes.
+    #[serde(flatten)]
+    pub other: BTreeMap<String, serde_json::Value>,
 }
 
 impl Receipt {
@@ -129,6 +136,12 @@ pub struct RawTransaction {
     pub tx: Transaction,
 }
 
+impl From<RawTransaction> for Bytes {
+    fn from(raw: RawTransaction) -> Self {
+        raw.raw
+    }
+}
+
 /// Access list
 pub type AccessList = Vec<AccessListItem>;
 
@@ -142,9 +155,123 @@ pub struct AccessListItem {
     pub storage_keys: Vec<H256>,
 }
 
+impl AccessListItem {
+    /// Creates an item accessing given storage keys of an address.
+    pub fn new(address: Address, storage_keys: Vec<H256>) -> Self {
+        AccessListItem { address, storage_keys }
+    }
+}
+
+/// Access list builder, merging entries of the same address and skipping duplicate keys.
+///
+/// ```
+/// # use web3::types::{AccessListBuilder, Address, H256};
+/// let access_list = AccessListBuilder::default()
+///     .address(Address::repeat_byte(1))
+///     .storage_keys(vec![H256::zero(), H256::repeat_byte(1)])
+///     .address(Address::repeat_byte(2))
+///     .address(Address::repeat_byte(1))
+///     .storage_keys(vec![H256::zero()])
+///     .build();
+/// assert_eq!(access_list.len(), 2);
+/// assert_eq!(access_list[0].storage_keys.len(), 2);
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AccessListBuilder {
+    items: AccessList,
+    current: Option<usize>,
+}
+
+impl AccessListBuilder {
+    /// Adds an accessed address, following `storage_keys` calls add keys of this address.
+    pub fn address(mut self, address: Address) -> Self {
+        let position = match self.items.iter().position(|item| item.address == address) {
+            Some(position) => position,
+            None => {
+                self.items.push(AccessListItem::new(address, vec![]));
+                self.items.len() - 1
+            }
+        };
+        self.current = Some(position);
+        self
+    }
+
+    /// Adds storage keys of the last added address.
+    ///
+    /// # Panics
+    ///
+    /// If no address was added yet.
+    pub fn storage_keys(mut self, keys: impl IntoIterator<Item = H256>) -> Self {
+        let current = self.current.expect("storage_keys is called after address; qed");
+        let item = &mut self.items[current];
+        for key in keys {
+            if !item.storage_keys.contains(&key) {
+                item.storage_keys.push(key);
+            }
+        }
+        self
+    }
+
+    /// Adds all entries of an access list.
+    pub fn extend(mut self, access_list: impl IntoIterator<Item = AccessListItem>) -> Self {
+        for item in access_list {
+            self = self.address(item.address).storage_keys(item.storage_keys);
+        }
+        self
+    }
+
+    /// Returns the access list, entries ordered by first occurrence.
+    pub fn build(self) -> AccessList {
+        self.items
+    }
+}
+
+impl From<AccessList> for AccessListBuilder {
+    fn from(access_list: AccessList) -> Self {
+        AccessListBuilder::default().extend(access_list)
+    }
+}
+
+/// Merges access lists, removing duplicate addresses and storage keys.
+pub fn merge_access_lists(access_lists: impl IntoIterator<Item = AccessList>) -> AccessList {
+    access_lists
+        .into_iter()
+        .fold(AccessListBuilder::default(), AccessListBuilder::extend)
+        .build()
+}
+
+/// Access list returned by `eth_createAccessList`.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessListWithGasUsed {
+    /// Accessed addresses and storage keys
+    pub access_list: AccessList,
+    /// Gas used by the transaction with the access list applied
+    pub gas_used: U256,
+    /// Execution error, if the transaction reverted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl From<AccessList> for AccessListWithGasUsed {
+    fn from(access_list: AccessList) -> Self {
+        AccessListWithGasUsed {
+            access_list,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<AccessListWithGasUsed> for AccessList {
+    fn from(result: AccessListWithGasUsed) -> Self {
+        result.access_list
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{RawTransaction, Receipt};
+    use super::{merge_access_lists, AccessListBuilder, AccessListItem, RawTransaction, Receipt, Transaction};
+    use crate::types::{Address, H256};
 
     #[test]
     fn test_deserialize_receipt() {
@@ -275,6 +402,66 @@ mod tests {
         assert_eq!(receipt.logs.len(), 1);
     }
 
+    #[test]
+    fn should_keep_l2_receipt_fields() {
+        let receipt_str = r#"{
+        "blockHash": "0x83eaba432089a0bfe99e9fc9022d1cfcb78f95f407821be81737c84ae0b439c5",
+        "blockNumber": "0x38",
+        "contractAddress": null,
+        "from": "0xdeaddeaddeaddeaddeaddeaddeaddeaddead0001",
+        "to": "0x4200000000000000000000000000000000000015",
+        "cumulativeGasUsed": "0xb1a4",
+        "gasUsed": "0xb1a4",
+        "logs": [],
+        "logsBloom": "0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+        "transactionHash": "0x422fb0d5953c0c48cbb42fb58e1c30f5e150441c68374d70ca7d4f191fd56f26",
+        "transactionIndex": "0x0",
+        "status": "0x1",
+        "type": "0x7e",
+        "effectiveGasPrice": "0x0",
+        "depositNonce": "0x7a1f",
+        "l1Fee": "0x12"
+    }"#;
+
+        let receipt: Receipt = serde_json::from_str(receipt_str).unwrap();
+        assert_eq!(receipt.transaction_type, Some(0x7e.into()));
+        assert_eq!(receipt.other.len(), 2);
+        assert_eq!(receipt.other["depositNonce"], "0x7a1f");
+
+        let serialized = serde_json::to_value(&receipt).unwrap();
+        assert_eq!(serialized["l1Fee"], "0x12");
+        assert_eq!(serde_json::from_value::<Receipt>(serialized).unwrap(), receipt);
+    }
+
+    #[test]
+    fn should_keep_deposit_transaction_fields() {
+        let tx_str = r#"{
+        "hash": "0x422fb0d5953c0c48cbb42fb58e1c30f5e150441c68374d70ca7d4f191fd56f26",
+        "nonce": "0x38",
+        "blockHash": "0x83eaba432089a0bfe99e9fc9022d1cfcb78f95f407821be81737c84ae0b439c5",
+        "blockNumber": "0x38",
+        "transactionIndex": "0x0",
+        "from": "0xdeaddeaddeaddeaddeaddeaddeaddeaddead0001",
+        "to": "0x4200000000000000000000000000000000000015",
+        "value": "0x0",
+        "gasPrice": "0x0",
+        "gas": "0xf4240",
+        "input": "0x",
+        "type": "0x7e",
+        "mint": "0x0",
+        "sourceHash": "0x5f3b1e3c2a4c7e4f6a59e9f1b9d0c6e3f1d4f0a1b2c3d4e5f60718293a4b5c6d"
+    }"#;
+
+        let tx: Transaction = serde_json::from_str(tx_str).unwrap();
+        assert_eq!(tx.transaction_type, Some(0x7e.into()));
+        assert_eq!(tx.other.len(), 2);
+        assert_eq!(tx.other["mint"], "0x0");
+
+        let serialized = serde_json::to_value(&tx).unwrap();
+        assert_eq!(serialized["sourceHash"], tx.other["sourceHash"]);
+        assert_eq!(serde_json::from_value::<Transaction>(serialized).unwrap(), tx);
+    }
+
     #[test]
     fn test_deserialize_signed_tx_parity() {
         // taken from RPC docs.
@@ -319,4 +506,36 @@ mod tests {
 
         let _tx: RawTransaction = serde_json::from_str(tx_str).unwrap();
     }
+
+    #[test]
+    fn should_merge_access_lists() {
+        let (first, second) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let (key1, key2) = (H256::repeat_byte(1), H256::repeat_byte(2));
+
+        let merged = merge_access_lists(vec![
+            vec![
+                AccessListItem::new(first, vec![key1]),
+                AccessListItem::new(second, vec![]),
+            ],
+            vec![AccessListItem::new(first, vec![key2, key1])],
+        ]);
+
+        assert_eq!(
+            merged,
+            vec![
+                AccessListItem::new(first, vec![key1, key2]),
+                AccessListItem::new(second, vec![])
+            ]
+        );
+        assert_eq!(
+            AccessListBuilder::from(merged.clone())
+                .address(second)
+                .storage_keys(vec![key2, key2])
+                .build(),
+            vec![
+                AccessListItem::new(first, vec![key1, key2]),
+                AccessListItem::new(second, vec![key2])
+            ]
+        );
+    }
 }
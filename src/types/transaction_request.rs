@@ -1,5 +1,6 @@
-use crate::types::{AccessList, Address, Bytes, U256, U64};
+use crate::types::{AccessList, Address, Bytes, Transaction, H256, U256, U64};
 use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
 
 /// Call contract request (eth_call / eth_estimateGas)
 ///
@@ -250,12 +251,106 @@ pub enum TransactionCondition {
     Timestamp(u64),
 }
 
+/// Gas price of a transaction, unless it carries EIP-1559 fees.
+///
+/// For mined EIP-1559 transactions nodes report the effective price in `gasPrice`, which isn't a
+/// valid parameter next to `maxFeePerGas`.
+fn legacy_gas_price(tx: &Transaction) -> Option<U256> {
+    if tx.max_fee_per_gas.is_some() || tx.transaction_type == Some(U64::from(2)) {
+        None
+    } else {
+        tx.gas_price
+    }
+}
+
+/// Re-simulates a transaction, e.g. with `eth_call` at the parent of its block.
+impl From<&Transaction> for CallRequest {
+    fn from(tx: &Transaction) -> Self {
+        CallRequest {
+            from: tx.from,
+            to: tx.to,
+            gas: Some(tx.gas),
+            gas_price: legacy_gas_price(tx),
+            value: Some(tx.value),
+            data: Some(tx.input.clone()),
+            transaction_type: tx.transaction_type,
+            access_list: tx.access_list.clone(),
+            max_fee_per_gas: tx.max_fee_per_gas,
+            max_priority_fee_per_gas: tx.max_priority_fee_per_gas,
+        }
+    }
+}
+
+impl From<Transaction> for CallRequest {
+    fn from(tx: Transaction) -> Self {
+        CallRequest::from(&tx)
+    }
+}
+
+impl From<TransactionRequest> for CallRequest {
+    fn from(tx: TransactionRequest) -> Self {
+        CallRequest {
+            from: Some(tx.from),
+            to: tx.to,
+            gas: tx.gas,
+            gas_price: tx.gas_price,
+            value: tx.value,
+            data: tx.data,
+            transaction_type: tx.transaction_type,
+            access_list: tx.access_list,
+            max_fee_per_gas: tx.max_fee_per_gas,
+            max_priority_fee_per_gas: tx.max_priority_fee_per_gas,
+        }
+    }
+}
+
+/// Replays a transaction, keeping its nonce.
+///
+/// Fails if the sender is unknown (some nodes omit `from` of pending transactions).
+impl TryFrom<&Transaction> for TransactionRequest {
+    type Error = MissingSender;
+
+    fn try_from(tx: &Transaction) -> Result<Self, Self::Error> {
+        Ok(TransactionRequest {
+            from: tx.from.ok_or(MissingSender(tx.hash))?,
+            to: tx.to,
+            gas: Some(tx.gas),
+            gas_price: legacy_gas_price(tx),
+            value: Some(tx.value),
+            data: Some(tx.input.clone()),
+            nonce: Some(tx.nonce),
+            condition: None,
+            transaction_type: tx.transaction_type,
+            access_list: tx.access_list.clone(),
+            max_fee_per_gas: tx.max_fee_per_gas,
+            max_priority_fee_per_gas: tx.max_priority_fee_per_gas,
+        })
+    }
+}
+
+impl TryFrom<Transaction> for TransactionRequest {
+    type Error = MissingSender;
+
+    fn try_from(tx: Transaction) -> Result<Self, Self::Error> {
+        TransactionRequest::try_from(&tx)
+    }
+}
+
+/// Transaction with given hash has no sender.
+#[derive(Debug, Clone, PartialEq, derive_more::Display)]
+#[display(fmt = "Transaction {:?} has no sender", _0)]
+pub struct MissingSender(pub H256);
+
+impl std::error::Error for MissingSender {}
+
 #[cfg(test)]
 mod tests {
     use super::{
-        Address, CallRequest, CallRequestBuilder, TransactionCondition, TransactionRequest, TransactionRequestBuilder,
+        Address, CallRequest, CallRequestBuilder, MissingSender, Transaction, TransactionCondition, TransactionRequest,
+        TransactionRequestBuilder,
     };
     use hex_literal::hex;
+    use std::convert::TryFrom;
 
     #[test]
     fn should_serialize_call_request() {
@@ -438,4 +533,46 @@ mod tests {
         //then
         assert_eq!(tx_request_builder.build(), tx_request);
     }
+
+    #[test]
+    fn should_convert_eip1559_transaction() {
+        let tx = Transaction {
+            from: Some(Address::from_low_u64_be(5)),
+            to: Some(Address::from_low_u64_be(6)),
+            nonce: 7.into(),
+            gas: 21_000.into(),
+            // effective price reported for mined transactions
+            gas_price: Some(12.into()),
+            max_fee_per_gas: Some(20.into()),
+            max_priority_fee_per_gas: Some(2.into()),
+            transaction_type: Some(2.into()),
+            input: hex!("010203").into(),
+            ..Default::default()
+        };
+
+        let call = CallRequest::from(&tx);
+        let request = TransactionRequest::try_from(tx).unwrap();
+
+        assert_eq!(call.gas_price, None);
+        assert_eq!(call.max_fee_per_gas, Some(20.into()));
+        assert_eq!(call.data, Some(hex!("010203").into()));
+        assert_eq!(request.nonce, Some(7.into()));
+        assert_eq!(request.gas_price, None);
+        assert_eq!(request.max_priority_fee_per_gas, Some(2.into()));
+        assert_eq!(CallRequest::from(request), call);
+    }
+
+    #[test]
+    fn should_convert_legacy_transaction() {
+        let tx = Transaction {
+            gas_price: Some(12.into()),
+            ..Default::default()
+        };
+
+        assert_eq!(CallRequest::from(&tx).gas_price, Some(12.into()));
+        assert_eq!(
+            TransactionRequest::try_from(&tx),
+            Err(MissingSender(Default::default()))
+        );
+    }
 }
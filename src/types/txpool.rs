@@ -1,6 +1,6 @@
-use crate::types::{Address, Transaction, U64};
+use crate::types::{Address, Transaction, U256, U64};
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, ops::Range};
 
 /// Transaction Pool Content Info
 ///
@@ -18,6 +18,79 @@ pub struct TxpoolContentInfo {
     pub queued: BTreeMap<Address, BTreeMap<String, Transaction>>,
 }
 
+impl TxpoolContentInfo {
+    /// Returns the transactions sent by `sender`.
+    pub fn from_sender(&self, sender: Address) -> TxpoolContentFromInfo {
+        TxpoolContentFromInfo {
+            pending: self.pending.get(&sender).cloned().unwrap_or_default(),
+            queued: self.queued.get(&sender).cloned().unwrap_or_default(),
+        }
+    }
+
+    /// Returns the transactions paying at least `min_gas_price`.
+    ///
+    /// For EIP-1559 transactions without `gasPrice` the `maxFeePerGas` is compared.
+    pub fn with_min_gas_price(&self, min_gas_price: U256) -> TxpoolContentInfo {
+        let filter = |txs: &BTreeMap<Address, BTreeMap<String, Transaction>>| {
+            txs.iter()
+                .map(|(sender, txs)| {
+                    let txs: BTreeMap<_, _> = txs
+                        .iter()
+                        .filter(|(_, tx)| tx.gas_price.or(tx.max_fee_per_gas).unwrap_or_default() >= min_gas_price)
+                        .map(|(nonce, tx)| (nonce.clone(), tx.clone()))
+                        .collect();
+                    (*sender, txs)
+                })
+                .filter(|(_, txs)| !txs.is_empty())
+                .collect()
+        };
+        TxpoolContentInfo {
+            pending: filter(&self.pending),
+            queued: filter(&self.queued),
+        }
+    }
+
+    /// Detects missing nonces of every sender.
+    ///
+    /// Returns ranges of nonces which are missing between the lowest and the highest nonce
+    /// of the pending and queued transactions, i.e. the gaps keeping transactions queued.
+    /// Senders without gaps are omitted.
+    pub fn nonce_gaps(&self) -> BTreeMap<Address, Vec<Range<U256>>> {
+        let mut nonces = BTreeMap::<Address, Vec<U256>>::new();
+        for (sender, txs) in self.pending.iter().chain(&self.queued) {
+            nonces
+                .entry(*sender)
+                .or_default()
+                .extend(txs.values().map(|tx| tx.nonce));
+        }
+        nonces
+            .into_iter()
+            .filter_map(|(sender, mut nonces)| {
+                nonces.sort();
+                nonces.dedup();
+                let gaps: Vec<_> = nonces
+                    .windows(2)
+                    .filter(|pair| pair[1] > pair[0] + 1)
+                    .map(|pair| pair[0] + 1..pair[1])
+                    .collect();
+                Some((sender, gaps)).filter(|(_, gaps)| !gaps.is_empty())
+            })
+            .collect()
+    }
+}
+
+/// Transaction Pool Content Info of a single sender
+///
+/// See [here](https://geth.ethereum.org/docs/rpc/ns-txpool#txpool_contentfrom) for more details
+///
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct TxpoolContentFromInfo {
+    /// pending tx by nonce
+    pub pending: BTreeMap<String, Transaction>,
+    /// queued tx by nonce
+    pub queued: BTreeMap<String, Transaction>,
+}
+
 /// Transaction Pool Inspect Info
 ///
 /// The inspect inspection property can be queried to list a textual summary
@@ -55,6 +128,30 @@ pub struct TxpoolStatus {
 #[cfg(test)]
 mod tests {
     use super::{TxpoolContentInfo, TxpoolInspectInfo, TxpoolStatus};
+    use crate::types::{Address, Transaction, U256};
+    use std::collections::BTreeMap;
+
+    fn content(pending: &[(u64, u64, u64)], queued: &[(u64, u64, u64)]) -> TxpoolContentInfo {
+        let txs = |txs: &[(u64, u64, u64)]| {
+            let mut map = BTreeMap::<Address, BTreeMap<String, Transaction>>::new();
+            for &(sender, nonce, gas_price) in txs {
+                let tx = Transaction {
+                    from: Some(Address::from_low_u64_be(sender)),
+                    nonce: nonce.into(),
+                    gas_price: Some(gas_price.into()),
+                    ..Default::default()
+                };
+                map.entry(Address::from_low_u64_be(sender))
+                    .or_default()
+                    .insert(nonce.to_string(), tx);
+            }
+            map
+        };
+        TxpoolContentInfo {
+            pending: txs(pending),
+            queued: txs(queued),
+        }
+    }
 
     #[test]
     fn should_deserialize_txpool_content() {
@@ -211,4 +308,40 @@ mod tests {
         let serialized: String = serde_json::to_string_pretty(&deserialized).unwrap();
         assert_eq!(txpool_status_str.trim(), serialized);
     }
+
+    #[test]
+    fn should_filter_txpool_content() {
+        let content = content(&[(1, 5, 10), (1, 6, 20), (2, 0, 30)], &[(1, 9, 5), (3, 2, 20)]);
+
+        let from = content.from_sender(Address::from_low_u64_be(1));
+        assert_eq!(from.pending.keys().collect::<Vec<_>>(), vec!["5", "6"]);
+        assert_eq!(from.queued.keys().collect::<Vec<_>>(), vec!["9"]);
+        assert_eq!(content.from_sender(Address::from_low_u64_be(4)), Default::default());
+
+        let expensive = content.with_min_gas_price(20.into());
+        assert_eq!(
+            expensive.pending.keys().copied().collect::<Vec<_>>(),
+            vec![Address::from_low_u64_be(1), Address::from_low_u64_be(2)]
+        );
+        assert_eq!(expensive.pending[&Address::from_low_u64_be(1)].len(), 1);
+        assert_eq!(
+            expensive.queued.keys().copied().collect::<Vec<_>>(),
+            vec![Address::from_low_u64_be(3)]
+        );
+    }
+
+    #[test]
+    fn should_detect_nonce_gaps() {
+        let content = content(
+            &[(1, 5, 10), (1, 6, 20), (2, 0, 30)],
+            &[(1, 9, 5), (1, 11, 5), (2, 1, 5)],
+        );
+
+        let gaps = content.nonce_gaps();
+        assert_eq!(gaps.len(), 1);
+        assert_eq!(
+            gaps[&Address::from_low_u64_be(1)],
+            vec![U256::from(7)..U256::from(9), U256::from(10)..U256::from(11)]
+        );
+    }
 }
@@ -0,0 +1,261 @@
+//! Conversions between wei and other denominations of ether (or tokens).
+//!
+//! ```
+//! use web3::types::units::{format_units, parse_units, Unit};
+//!
+//! let value = parse_units("1.5", "ether").unwrap();
+//! assert_eq!(value, Unit::Finney.value() * 1_500);
+//! assert_eq!(format_units(value, Unit::Gwei).unwrap(), "1500000000.0");
+//! ```
+
+use crate::types::U256;
+use std::{
+    convert::{Infallible, TryFrom, TryInto},
+    fmt,
+    str::FromStr,
+};
+
+/// An error parsing a unit or an amount.
+#[derive(Debug, derive_more::Display, Clone, PartialEq, Eq)]
+pub enum UnitsError {
+    /// Unknown name of a unit.
+    #[display(fmt = "Unknown unit: {}", _0)]
+    InvalidUnit(String),
+    /// Not a non-negative decimal number.
+    #[display(fmt = "Invalid number: {}", _0)]
+    InvalidNumber(String),
+    /// More fractional digits than the decimals of the unit.
+    #[display(fmt = "{} has more than {} decimals", _0, _1)]
+    TooManyDecimals(String, u32),
+    /// The amount doesn't fit 256 bits.
+    #[display(fmt = "{} is too large", _0)]
+    Overflow(String),
+    /// One unit of given decimals doesn't fit 256 bits (more than 77 decimals).
+    #[display(fmt = "Unit of {} decimals is too large", _0)]
+    InvalidDecimals(u32),
+}
+
+impl std::error::Error for UnitsError {}
+
+impl From<Infallible> for UnitsError {
+    fn from(err: Infallible) -> Self {
+        match err {}
+    }
+}
+
+/// A denomination of ether, or the decimals of a token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    /// 1 wei
+    Wei,
+    /// 10^3 wei
+    Kwei,
+    /// 10^6 wei
+    Mwei,
+    /// 10^9 wei
+    Gwei,
+    /// 10^12 wei
+    Szabo,
+    /// 10^15 wei
+    Finney,
+    /// 10^18 wei
+    Ether,
+    /// 10^N base units, e.g. the `decimals()` of an ERC-20 token
+    Decimals(u32),
+}
+
+/// Largest number of decimals of a unit, `10^77` being the largest power of ten fitting 256 bits.
+const MAX_DECIMALS: u32 = 77;
+
+impl Unit {
+    /// Number of decimals of the unit.
+    pub fn decimals(&self) -> u32 {
+        match *self {
+            Unit::Wei => 0,
+            Unit::Kwei => 3,
+            Unit::Mwei => 6,
+            Unit::Gwei => 9,
+            Unit::Szabo => 12,
+            Unit::Finney => 15,
+            Unit::Ether => 18,
+            Unit::Decimals(decimals) => decimals,
+        }
+    }
+
+    /// Number of decimals of the unit, failing if one unit doesn't fit 256 bits.
+    fn checked_decimals(&self) -> Result<u32, UnitsError> {
+        match self.decimals() {
+            decimals if decimals > MAX_DECIMALS => Err(UnitsError::InvalidDecimals(decimals)),
+            decimals => Ok(decimals),
+        }
+    }
+
+    /// Value of one unit in wei (base units).
+    ///
+    /// Panics if the unit has more than 77 decimals.
+    pub fn value(&self) -> U256 {
+        U256::exp10(self.decimals() as usize)
+    }
+}
+
+impl From<u32> for Unit {
+    fn from(decimals: u32) -> Self {
+        Unit::Decimals(decimals)
+    }
+}
+
+impl FromStr for Unit {
+    type Err = UnitsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_ascii_lowercase().as_str() {
+            "wei" => Unit::Wei,
+            "kwei" | "babbage" => Unit::Kwei,
+            "mwei" | "lovelace" => Unit::Mwei,
+            "gwei" | "shannon" => Unit::Gwei,
+            "szabo" | "microether" => Unit::Szabo,
+            "finney" | "milliether" => Unit::Finney,
+            "ether" | "eth" => Unit::Ether,
+            _ => return Err(UnitsError::InvalidUnit(s.into())),
+        })
+    }
+}
+
+impl TryFrom<&str> for Unit {
+    type Error = UnitsError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Unit::Wei => write!(f, "wei"),
+            Unit::Kwei => write!(f, "kwei"),
+            Unit::Mwei => write!(f, "mwei"),
+            Unit::Gwei => write!(f, "gwei"),
+            Unit::Szabo => write!(f, "szabo"),
+            Unit::Finney => write!(f, "finney"),
+            Unit::Ether => write!(f, "ether"),
+            Unit::Decimals(decimals) => write!(f, "10^{}", decimals),
+        }
+    }
+}
+
+/// Parses a decimal amount of given unit (e.g. `"ether"`, `Unit::Gwei` or `6u32` decimals) into
+/// wei (base units).
+pub fn parse_units<U>(amount: &str, unit: U) -> Result<U256, UnitsError>
+where
+    U: TryInto<Unit>,
+    UnitsError: From<U::Error>,
+{
+    let decimals = unit.try_into()?.checked_decimals()?;
+    let invalid = || UnitsError::InvalidNumber(amount.into());
+    let (integer, fraction) = match amount.split_once('.') {
+        Some((integer, fraction)) => (integer, fraction),
+        None => (amount, ""),
+    };
+    let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if (integer.is_empty() && fraction.is_empty()) || !is_digits(integer) || !is_digits(fraction) {
+        return Err(invalid());
+    }
+
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.len() > decimals as usize {
+        return Err(UnitsError::TooManyDecimals(amount.into(), decimals));
+    }
+    let digits = format!("{}{:0<width$}", integer, fraction, width = decimals as usize);
+    let digits = digits.trim_start_matches('0');
+    if digits.is_empty() {
+        return Ok(U256::zero());
+    }
+    U256::from_dec_str(digits).map_err(|_| UnitsError::Overflow(amount.into()))
+}
+
+/// Formats an amount of wei (base units) as a decimal amount of given unit.
+///
+/// Trailing zeros of the fraction are omitted, except for a single one (e.g. `"1.0"`).
+pub fn format_units<U>(amount: U256, unit: U) -> Result<String, UnitsError>
+where
+    U: TryInto<Unit>,
+    UnitsError: From<U::Error>,
+{
+    let decimals = unit.try_into()?.checked_decimals()? as usize;
+    if decimals == 0 {
+        return Ok(amount.to_string());
+    }
+    let digits = format!("{:0>width$}", amount.to_string(), width = decimals + 1);
+    let (integer, fraction) = digits.split_at(digits.len() - decimals);
+    let fraction = fraction.trim_end_matches('0');
+    Ok(format!(
+        "{}.{}",
+        integer,
+        if fraction.is_empty() { "0" } else { fraction }
+    ))
+}
+
+/// Formats an amount of wei as ether, e.g. `"0.1"`.
+pub fn wei_to_eth(wei: U256) -> String {
+    format_units(wei, Unit::Ether).expect("Unit conversion is infallible; qed")
+}
+
+/// Parses an amount of ether into wei.
+pub fn eth_to_wei(eth: &str) -> Result<U256, UnitsError> {
+    parse_units(eth, Unit::Ether)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_units() {
+        assert_eq!(parse_units("1.5", "ether"), Ok(U256::exp10(17) * 15));
+        assert_eq!(parse_units("0.1", Unit::Ether), Ok(U256::exp10(17)));
+        assert_eq!(parse_units(".5", "gwei"), Ok(500_000_000.into()));
+        assert_eq!(parse_units("2.", "Gwei"), Ok(2_000_000_000u64.into()));
+        assert_eq!(parse_units("1.230000", 2u32), Ok(123.into()));
+        assert_eq!(parse_units("0000", "wei"), Ok(0.into()));
+        assert_eq!(eth_to_wei("1"), Ok(U256::exp10(18)));
+    }
+
+    #[test]
+    fn should_reject_invalid_amounts() {
+        assert_eq!(
+            parse_units("1", "tether"),
+            Err(UnitsError::InvalidUnit("tether".into()))
+        );
+        for amount in ["", ".", "-1", "1e18", "1.2.3", " 1", "0x10"] {
+            assert_eq!(
+                parse_units(amount, "ether"),
+                Err(UnitsError::InvalidNumber(amount.into()))
+            );
+        }
+        assert_eq!(
+            parse_units("1.0000000001", "gwei"),
+            Err(UnitsError::TooManyDecimals("1.0000000001".into(), 9))
+        );
+        let max = U256::MAX.to_string();
+        assert_eq!(parse_units(&max, "wei"), Ok(U256::MAX));
+        assert_eq!(parse_units(&max, "kwei"), Err(UnitsError::Overflow(max)));
+        assert_eq!(parse_units("1", 77u32), Ok(U256::exp10(77)));
+        assert_eq!(parse_units("1", u32::MAX), Err(UnitsError::InvalidDecimals(u32::MAX)));
+        assert_eq!(format_units(1.into(), 78u32), Err(UnitsError::InvalidDecimals(78)));
+    }
+
+    #[test]
+    fn should_format_units() {
+        assert_eq!(format_units(U256::exp10(17) * 15, "ether"), Ok("1.5".into()));
+        assert_eq!(format_units(U256::exp10(18), Unit::Ether), Ok("1.0".into()));
+        assert_eq!(format_units(U256::from(1), Unit::Gwei), Ok("0.000000001".into()));
+        assert_eq!(format_units(U256::from(123), Unit::Wei), Ok("123".into()));
+        assert_eq!(format_units(U256::from(123), 6u32), Ok("0.000123".into()));
+        assert_eq!(wei_to_eth(U256::zero()), "0.0");
+        assert_eq!(
+            parse_units(&format_units(U256::MAX, "ether").unwrap(), "ether"),
+            Ok(U256::MAX)
+        );
+    }
+}
@@ -0,0 +1,420 @@
+//! zkSync Era specific types.
+
+use crate::{
+    signing,
+    types::{Address, Bytes, H256, U256},
+};
+use rlp::RlpStream;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// EIP-712 transaction type id used by zkSync Era.
+pub const EIP712_TX_TYPE: u8 = 0x71;
+
+/// Default `gasPerPubdataByteLimit` used by the zkSync Era SDKs.
+pub const DEFAULT_GAS_PER_PUBDATA_LIMIT: u64 = 50_000;
+
+/// Fee estimate returned by `zks_estimateFee`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ZkSyncFee {
+    /// Gas limit
+    pub gas_limit: U256,
+    /// Maximum fee per gas
+    pub max_fee_per_gas: U256,
+    /// Maximum priority fee per gas
+    pub max_priority_fee_per_gas: U256,
+    /// Gas per pubdata byte limit
+    pub gas_per_pubdata_limit: U256,
+}
+
+/// Addresses of the default bridges returned by `zks_getBridgeContracts`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BridgeContracts {
+    /// L1 ERC20 default bridge
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_erc20_default_bridge: Option<Address>,
+    /// L2 ERC20 default bridge
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l2_erc20_default_bridge: Option<Address>,
+    /// L1 WETH bridge
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_weth_bridge: Option<Address>,
+    /// L2 WETH bridge
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l2_weth_bridge: Option<Address>,
+    /// L1 shared default bridge
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_shared_default_bridge: Option<Address>,
+    /// L2 shared default bridge
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l2_shared_default_bridge: Option<Address>,
+}
+
+/// Merkle proof of an L2 to L1 message returned by `zks_getL2ToL1MsgProof`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct L2ToL1MessageProof {
+    /// Merkle proof
+    pub proof: Vec<H256>,
+    /// Position of the leaf in the tree
+    pub id: u64,
+    /// Root hash of the tree
+    pub root: H256,
+}
+
+/// Paymaster parameters of an EIP-712 transaction.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymasterParams {
+    /// Paymaster address
+    pub paymaster: Address,
+    /// Input passed to the paymaster
+    pub paymaster_input: Bytes,
+}
+
+/// zkSync Era EIP-712 (type `0x71`) transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Eip712Transaction {
+    /// Sender address
+    pub from: Address,
+    /// Recipient address
+    pub to: Address,
+    /// Nonce
+    pub nonce: U256,
+    /// Gas limit
+    pub gas_limit: U256,
+    /// Gas per pubdata byte limit
+    pub gas_per_pubdata_byte_limit: U256,
+    /// Maximum fee per gas
+    pub max_fee_per_gas: U256,
+    /// Maximum priority fee per gas
+    pub max_priority_fee_per_gas: U256,
+    /// Transfered value
+    pub value: U256,
+    /// Data
+    pub data: Bytes,
+    /// Bytecodes of contracts deployed by this transaction
+    pub factory_deps: Vec<Bytes>,
+    /// Paymaster parameters
+    pub paymaster_params: Option<PaymasterParams>,
+    /// Chain id
+    pub chain_id: u64,
+}
+
+impl Eip712Transaction {
+    /// Creates a new transaction with default gas per pubdata limit and zero fees.
+    pub fn new(from: Address, to: Address, chain_id: u64) -> Self {
+        Eip712Transaction {
+            from,
+            to,
+            nonce: U256::zero(),
+            gas_limit: U256::zero(),
+            gas_per_pubdata_byte_limit: DEFAULT_GAS_PER_PUBDATA_LIMIT.into(),
+            max_fee_per_gas: U256::zero(),
+            max_priority_fee_per_gas: U256::zero(),
+            value: U256::zero(),
+            data: Bytes::default(),
+            factory_deps: vec![],
+            paymaster_params: None,
+            chain_id,
+        }
+    }
+
+    /// Applies a fee estimate returned by `zks_estimateFee`.
+    pub fn with_fee(mut self, fee: &ZkSyncFee) -> Self {
+        self.gas_limit = fee.gas_limit;
+        self.max_fee_per_gas = fee.max_fee_per_gas;
+        self.max_priority_fee_per_gas = fee.max_priority_fee_per_gas;
+        self.gas_per_pubdata_byte_limit = fee.gas_per_pubdata_limit;
+        self
+    }
+
+    /// Returns the EIP-712 digest which has to be signed by the sender.
+    pub fn eip712_hash(&self) -> H256 {
+        let domain_separator = {
+            let mut encoded = Vec::with_capacity(4 * 32);
+            encoded.extend_from_slice(&signing::keccak256(
+                b"EIP712Domain(string name,string version,uint256 chainId)",
+            ));
+            encoded.extend_from_slice(&signing::keccak256(b"zkSync"));
+            encoded.extend_from_slice(&signing::keccak256(b"2"));
+            encoded.extend_from_slice(&word(U256::from(self.chain_id)));
+            signing::keccak256(&encoded)
+        };
+
+        let mut digest = Vec::with_capacity(2 + 2 * 32);
+        digest.extend_from_slice(&[0x19, 0x01]);
+        digest.extend_from_slice(&domain_separator);
+        digest.extend_from_slice(&self.struct_hash());
+        signing::keccak256(&digest).into()
+    }
+
+    fn struct_hash(&self) -> [u8; 32] {
+        const TYPE: &[u8] = b"Transaction(uint256 txType,uint256 from,uint256 to,uint256 gasLimit,\
+uint256 gasPerPubdataByteLimit,uint256 maxFeePerGas,uint256 maxPriorityFeePerGas,uint256 paymaster,\
+uint256 nonce,uint256 value,bytes data,bytes32[] factoryDeps,bytes paymasterInput)";
+
+        let (paymaster, paymaster_input) = match &self.paymaster_params {
+            Some(params) => (params.paymaster, &params.paymaster_input.0[..]),
+            None => (Address::zero(), &[][..]),
+        };
+        let factory_deps = self
+            .factory_deps
+            .iter()
+            .flat_map(|dep| bytecode_hash(&dep.0).0)
+            .collect::<Vec<_>>();
+
+        let mut encoded = Vec::with_capacity(14 * 32);
+        encoded.extend_from_slice(&signing::keccak256(TYPE));
+        encoded.extend_from_slice(&word(EIP712_TX_TYPE.into()));
+        encoded.extend_from_slice(&address_word(self.from));
+        encoded.extend_from_slice(&address_word(self.to));
+        encoded.extend_from_slice(&word(self.gas_limit));
+        encoded.extend_from_slice(&word(self.gas_per_pubdata_byte_limit));
+        encoded.extend_from_slice(&word(self.max_fee_per_gas));
+        encoded.extend_from_slice(&word(self.max_priority_fee_per_gas));
+        encoded.extend_from_slice(&address_word(paymaster));
+        encoded.extend_from_slice(&word(self.nonce));
+        encoded.extend_from_slice(&word(self.value));
+        encoded.extend_from_slice(&signing::keccak256(&self.data.0));
+        encoded.extend_from_slice(&signing::keccak256(&factory_deps));
+        encoded.extend_from_slice(&signing::keccak256(paymaster_input));
+        signing::keccak256(&encoded)
+    }
+
+    /// Encodes the transaction with the given 65 bytes `r || s || v` signature.
+    pub fn encode(&self, signature: &[u8]) -> Vec<u8> {
+        let mut stream = RlpStream::new();
+        stream.begin_list(16);
+        stream.append(&self.nonce);
+        stream.append(&self.max_priority_fee_per_gas);
+        stream.append(&self.max_fee_per_gas);
+        stream.append(&self.gas_limit);
+        stream.append(&self.to);
+        stream.append(&self.value);
+        stream.append(&self.data.0);
+        stream.append(&self.chain_id);
+        stream.append_empty_data();
+        stream.append_empty_data();
+        stream.append(&self.chain_id);
+        stream.append(&self.from);
+        stream.append(&self.gas_per_pubdata_byte_limit);
+        stream.begin_list(self.factory_deps.len());
+        for dep in &self.factory_deps {
+            stream.append(&dep.0);
+        }
+        stream.append(&signature);
+        match &self.paymaster_params {
+            Some(params) => {
+                stream.begin_list(2);
+                stream.append(&params.paymaster);
+                stream.append(&params.paymaster_input.0);
+            }
+            None => {
+                stream.begin_list(0);
+            }
+        }
+
+        let mut encoded = vec![EIP712_TX_TYPE];
+        encoded.extend_from_slice(stream.as_raw());
+        encoded
+    }
+
+    /// Transaction hash as computed by zkSync Era for a given signature.
+    pub fn transaction_hash(&self, signature: &[u8]) -> H256 {
+        let mut encoded = Vec::with_capacity(64);
+        encoded.extend_from_slice(self.eip712_hash().as_bytes());
+        encoded.extend_from_slice(&signing::keccak256(signature));
+        signing::keccak256(&encoded).into()
+    }
+
+    /// Sign and return a raw signed transaction.
+    #[cfg(feature = "signing")]
+    pub fn sign(&self, key: impl signing::Key) -> crate::types::SignedTransaction {
+        let hash = self.eip712_hash();
+        let signature = key.sign(hash.as_bytes(), None).expect("hash is non-zero 32-bytes; qed");
+
+        let mut custom_signature = Vec::with_capacity(65);
+        custom_signature.extend_from_slice(signature.r.as_bytes());
+        custom_signature.extend_from_slice(signature.s.as_bytes());
+        custom_signature.push(signature.v as u8);
+
+        crate::types::SignedTransaction {
+            message_hash: hash,
+            v: signature.v,
+            r: signature.r,
+            s: signature.s,
+            raw_transaction: self.encode(&custom_signature).into(),
+            transaction_hash: self.transaction_hash(&custom_signature),
+        }
+    }
+}
+
+/// Computes the zkSync Era hash of a contract bytecode.
+///
+/// The hash is the SHA-256 of the bytecode where the first two bytes are replaced with
+/// the version (`0x0100`) and the next two with the bytecode length in 32-byte words.
+pub fn bytecode_hash(bytecode: &[u8]) -> H256 {
+    let mut hash: [u8; 32] = Sha256::digest(bytecode).into();
+    let words = (bytecode.len() / 32) as u16;
+    hash[0] = 1;
+    hash[1] = 0;
+    hash[2..4].copy_from_slice(&words.to_be_bytes());
+    hash.into()
+}
+
+fn word(value: U256) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    value.to_big_endian(&mut word);
+    word
+}
+
+fn address_word(address: Address) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[12..].copy_from_slice(address.as_bytes());
+    word
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    #[test]
+    fn should_hash_bytecode() {
+        assert_eq!(
+            bytecode_hash(&[0u8; 32]),
+            H256(hex!("01000001f862bd776c8fc18b8e9f8e20089714856ee233b3902a591d0d5f2925"))
+        );
+    }
+
+    #[test]
+    fn should_deserialize_fee() {
+        let fee: ZkSyncFee = serde_json::from_str(
+            r#"{"gas_limit":"0x156c00","gas_per_pubdata_limit":"0x143b","max_fee_per_gas":"0xee6b280","max_priority_fee_per_gas":"0x0"}"#,
+        )
+        .unwrap();
+        assert_eq!(fee.gas_limit, 0x156c00.into());
+        assert_eq!(fee.gas_per_pubdata_limit, 0x143b.into());
+        assert_eq!(fee.max_fee_per_gas, 0xee6b280u64.into());
+        assert_eq!(fee.max_priority_fee_per_gas, 0.into());
+    }
+
+    #[test]
+    fn should_encode_unsigned_fields() {
+        let tx = Eip712Transaction {
+            nonce: 1.into(),
+            gas_limit: 21000.into(),
+            ..Eip712Transaction::new(Address::repeat_byte(1), Address::repeat_byte(2), 324)
+        };
+        let encoded = tx.encode(&[0u8; 65]);
+        assert_eq!(encoded[0], EIP712_TX_TYPE);
+
+        let rlp = rlp::Rlp::new(&encoded[1..]);
+        assert_eq!(rlp.item_count().unwrap(), 16);
+        assert_eq!(rlp.val_at::<U256>(0).unwrap(), 1.into());
+        assert_eq!(rlp.val_at::<Address>(4).unwrap(), Address::repeat_byte(2));
+        assert_eq!(rlp.val_at::<u64>(10).unwrap(), 324);
+        assert_eq!(rlp.val_at::<Address>(11).unwrap(), Address::repeat_byte(1));
+        assert_eq!(rlp.val_at::<U256>(12).unwrap(), DEFAULT_GAS_PER_PUBDATA_LIMIT.into());
+        assert_eq!(rlp.at(13).unwrap().item_count().unwrap(), 0);
+        assert_eq!(rlp.val_at::<Vec<u8>>(14).unwrap(), vec![0u8; 65]);
+        assert_eq!(rlp.at(15).unwrap().item_count().unwrap(), 0);
+    }
+
+    #[test]
+    fn should_hash_depending_on_chain() {
+        let tx = Eip712Transaction::new(Address::repeat_byte(1), Address::repeat_byte(2), 324);
+        let other = Eip712Transaction {
+            chain_id: 300,
+            ..tx.clone()
+        };
+        assert_ne!(tx.eip712_hash(), other.eip712_hash());
+    }
+
+    #[cfg(feature = "signing")]
+    #[test]
+    fn should_sign_transaction() {
+        use crate::signing::{recover, SecretKey, SecretKeyRef};
+        use std::str::FromStr;
+
+        let key = SecretKey::from_str("4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318").unwrap();
+        let from = signing::Key::address(&SecretKeyRef::new(&key));
+        let tx = Eip712Transaction {
+            value: 1_000.into(),
+            factory_deps: vec![vec![0u8; 32].into()],
+            paymaster_params: Some(PaymasterParams {
+                paymaster: Address::repeat_byte(3),
+                paymaster_input: vec![1, 2, 3].into(),
+            }),
+            ..Eip712Transaction::new(from, Address::repeat_byte(2), 324)
+        };
+
+        let signed = tx.sign(SecretKeyRef::new(&key));
+        assert!(signed.v == 27 || signed.v == 28);
+        assert_eq!(signed.message_hash, tx.eip712_hash());
+
+        let mut signature = [0u8; 64];
+        signature[..32].copy_from_slice(signed.r.as_bytes());
+        signature[32..].copy_from_slice(signed.s.as_bytes());
+        let recovered = recover(signed.message_hash.as_bytes(), &signature, (signed.v - 27) as i32).unwrap();
+        assert_eq!(recovered, from);
+
+        let rlp = rlp::Rlp::new(&signed.raw_transaction.0[1..]);
+        assert_eq!(rlp.at(13).unwrap().item_count().unwrap(), 1);
+        assert_eq!(rlp.val_at::<Vec<u8>>(14).unwrap().len(), 65);
+        assert_eq!(
+            rlp.at(15).unwrap().val_at::<Address>(0).unwrap(),
+            Address::repeat_byte(3)
+        );
+    }
+
+    #[cfg(feature = "signing")]
+    #[test]
+    fn should_match_signed_transaction_fixture() {
+        use crate::signing::{SecretKey, SecretKeyRef};
+        use std::str::FromStr;
+
+        // fixture produced by a separate implementation of the zkSync Era EIP-712 transaction
+        // format (as serialized by `zksync-ethers`' `serializeEip712`), with an RFC 6979 signature
+        let key = SecretKey::from_str("4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318").unwrap();
+        let tx = Eip712Transaction {
+            nonce: 7.into(),
+            gas_limit: 0x156c00.into(),
+            max_fee_per_gas: 250_000_000.into(),
+            value: 1_000.into(),
+            data: hex!("a9059cbb").to_vec().into(),
+            factory_deps: vec![vec![0u8; 32].into()],
+            paymaster_params: Some(PaymasterParams {
+                paymaster: Address::repeat_byte(3),
+                paymaster_input: vec![1, 2, 3].into(),
+            }),
+            ..Eip712Transaction::new(
+                hex!("2c7536e3605d9c16a7a3d7b1898e529396a65c23").into(),
+                Address::repeat_byte(2),
+                324,
+            )
+        };
+
+        let signed = tx.sign(SecretKeyRef::new(&key));
+
+        assert_eq!(
+            signed.message_hash,
+            H256(hex!("21c4b716e02a9b93cfc5727b07430d8343d799582fcc75d94d119126ce4f9349"))
+        );
+        assert_eq!(
+            signed.raw_transaction.0,
+            hex!(
+                "71f8c70780840ee6b28083156c009402020202020202020202020202020202020202028203e884a9059cbb8201448080
+                 820144942c7536e3605d9c16a7a3d7b1898e529396a65c2382c350e1a000000000000000000000000000000000000000
+                 00000000000000000000000000b84193a5ed9e59a821736a24f377b91f618618ea54e306cf47f132e3516fe9c884681c
+                 c5b8cb62a92d48dc1dbba28419b76a834fd6d8d8d1798b2dfd0426ebbb51721bd9940303030303030303030303030303
+                 03030303030383010203"
+            )
+        );
+        assert_eq!(
+            signed.transaction_hash,
+            H256(hex!("8d6f7b4370d07670ab8225b2913723305e07e4c0dafe5bd75fba5c790fdb3bad"))
+        );
+    }
+}
@@ -0,0 +1,176 @@
+//! Derive macros for the `web3` crate.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, parse_quote, Data, DeriveInput, Error, Fields, Index, Type};
+
+/// Derives `web3::contract::tokens::Tokenizable` for a struct matching a Solidity struct (tuple).
+///
+/// Fields are converted in declaration order and have to be `Tokenizable` themselves, so derived
+/// structs can be nested and used in `Vec`s. `Tokenize` and `Detokenize` come with the blanket
+/// implementations, so the struct can be passed to `Contract::call` and returned by `Contract::query`.
+#[proc_macro_derive(Tokenizable)]
+pub fn derive_tokenizable(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match tokenizable(input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Derives `web3::contract::tokens::Detokenize` for a struct holding all outputs of a function.
+///
+/// Each field takes one output, in declaration order, e.g. `struct Reserves(U256, U256, u32)` for
+/// `getReserves`. Use `Tokenizable` instead for a function returning a single struct (tuple).
+#[proc_macro_derive(Detokenize)]
+pub fn derive_detokenize(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match detokenize(input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Fields of a struct, with expressions constructing it from `tokens` and converting it into tokens.
+struct Conversion {
+    count: usize,
+    types: Vec<Type>,
+    construct: TokenStream2,
+    into_tokens: TokenStream2,
+}
+
+fn conversion(input: &DeriveInput, derive: &str) -> syn::Result<Conversion> {
+    let fields = match input.data {
+        Data::Struct(ref data) => &data.fields,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                format!("{} can only be derived for structs", derive),
+            ))
+        }
+    };
+
+    let name = &input.ident;
+    let count = fields.len();
+    let types = fields.iter().map(|field| field.ty.clone()).collect::<Vec<_>>();
+    let (construct, into_tokens) = match fields {
+        Fields::Named(fields) => {
+            let names = fields.named.iter().map(|field| &field.ident).collect::<Vec<_>>();
+            (
+                quote! { #name { #( #names: next(&mut tokens)?, )* } },
+                quote! { #( ::web3::contract::tokens::Tokenizable::into_token(self.#names), )* },
+            )
+        }
+        Fields::Unnamed(_) => {
+            let indexes = (0..count).map(Index::from);
+            (
+                quote! { #name ( #( next::<#types>(&mut tokens)?, )* ) },
+                quote! { #( ::web3::contract::tokens::Tokenizable::into_token(self.#indexes), )* },
+            )
+        }
+        Fields::Unit => (quote! { #name }, quote! {}),
+    };
+
+    Ok(Conversion {
+        count,
+        types,
+        construct,
+        into_tokens,
+    })
+}
+
+/// Adds a `Tokenizable` bound for every field type.
+fn add_bounds(input: &mut DeriveInput, types: &[Type]) {
+    let where_clause = input.generics.make_where_clause();
+    for ty in types {
+        where_clause
+            .predicates
+            .push(parse_quote! { #ty: ::web3::contract::tokens::Tokenizable });
+    }
+}
+
+/// Helper converting the next token, the number of tokens has to be checked before.
+fn next_fn() -> TokenStream2 {
+    quote! {
+        fn next<T: ::web3::contract::tokens::Tokenizable>(
+            tokens: &mut impl ::core::iter::Iterator<Item = ::web3::ethabi::Token>,
+        ) -> ::core::result::Result<T, ::web3::contract::Error> {
+            T::from_token(tokens.next().expect("Length validated in guard; qed"))
+        }
+    }
+}
+
+fn tokenizable(mut input: DeriveInput) -> syn::Result<TokenStream2> {
+    let Conversion {
+        count,
+        types,
+        construct,
+        into_tokens,
+    } = conversion(&input, "Tokenizable")?;
+    add_bounds(&mut input, &types);
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let expected = format!("Expected `Tuple` of {} elements for `{}`, got {{:?}}", count, name);
+    let next = next_fn();
+
+    Ok(quote! {
+        impl #impl_generics ::web3::contract::tokens::Tokenizable for #name #ty_generics #where_clause {
+            fn from_token(
+                token: ::web3::ethabi::Token,
+            ) -> ::core::result::Result<Self, ::web3::contract::Error> {
+                #next
+
+                match token {
+                    ::web3::ethabi::Token::Tuple(tokens) if tokens.len() == #count => {
+                        #[allow(unused_mut, unused_variables)]
+                        let mut tokens = tokens.into_iter();
+                        ::core::result::Result::Ok(#construct)
+                    }
+                    other => ::core::result::Result::Err(::web3::contract::Error::InvalidOutputType(
+                        ::std::format!(#expected, other),
+                    )),
+                }
+            }
+
+            fn into_token(self) -> ::web3::ethabi::Token {
+                ::web3::ethabi::Token::Tuple(::std::vec![ #into_tokens ])
+            }
+        }
+
+        impl #impl_generics ::web3::contract::tokens::TokenizableItem for #name #ty_generics #where_clause {}
+    })
+}
+
+fn detokenize(mut input: DeriveInput) -> syn::Result<TokenStream2> {
+    let Conversion {
+        count,
+        types,
+        construct,
+        ..
+    } = conversion(&input, "Detokenize")?;
+    add_bounds(&mut input, &types);
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let expected = format!("Expected {} elements for `{}`, got a list of {{}}: {{:?}}", count, name);
+    let next = next_fn();
+
+    Ok(quote! {
+        impl #impl_generics ::web3::contract::tokens::Detokenize for #name #ty_generics #where_clause {
+            fn from_tokens(
+                tokens: ::std::vec::Vec<::web3::ethabi::Token>,
+            ) -> ::core::result::Result<Self, ::web3::contract::Error> {
+                #next
+
+                if tokens.len() != #count {
+                    return ::core::result::Result::Err(::web3::contract::Error::InvalidOutputType(
+                        ::std::format!(#expected, tokens.len(), tokens),
+                    ));
+                }
+                #[allow(unused_mut, unused_variables)]
+                let mut tokens = tokens.into_iter();
+                ::core::result::Result::Ok(#construct)
+            }
+        }
+    })
+}